@@ -87,6 +87,7 @@ function wrapSdkClient(sdk) {
                 userPublicKeys: () => boundAccount.userPublicKeys(),
                 aspSecret: () => boundAccount.aspSecret(),
                 userNotes: (limit) => boundAccount.userNotes(limit),
+                sentNotes: (limit) => boundAccount.sentNotes(limit),
                 isRegistered: () => boundAccount.isRegistered(),
                 registerPublicKeys: (options) => boundAccount.registerPublicKeys(options ?? {}),
                 deriveAspUserLeaf: () => boundAccount.deriveAspUserLeaf(),
@@ -23,8 +23,8 @@
 //! To Build the test circuits use `BUILD_TESTS=1 cargo build`
 //!
 //! The script also generates Groth16 proving and verification keys for selected
-//! entry-point circuits (see `PolicyFlags::all_stems` and
-//! `selectiveDisclosure_*` below) and outputs them to `testdata/`.
+//! entry-point circuits (see `PolicyFlags::all_stems`,
+//! `selectiveDisclosure_*` and `notePossession` below) and outputs them to `testdata/`.
 //! `std::env::var("CIRCUIT_OUT_DIR")`
 
 use anyhow::{Context, Result, anyhow, bail};
@@ -58,6 +58,10 @@ const SELECTIVE_DISCLOSURE_CIRCUITS: &[&str] = &[
     "selectiveDisclosure_4",
 ];
 
+/// Proof of possession of a note private key, verified by the public key
+/// registry on `register`.
+const NOTE_POSSESSION_CIRCUIT: &str = "notePossession";
+
 /// Policy transaction circuits combined with Global View Key encryption. Each
 /// of the 4 ASP policy configs is offered in view-only and traceable modes.
 const POLICY_GLOBAL_VIEW_KEY_CIRCUITS: &[&str] = &[
@@ -92,6 +96,7 @@ fn groth16_key_circuits() -> Vec<String> {
             .iter()
             .map(|stem| (*stem).to_owned()),
     );
+    circuits.push(NOTE_POSSESSION_CIRCUIT.to_owned());
     circuits.extend(
         POLICY_GLOBAL_VIEW_KEY_CIRCUITS
             .iter()
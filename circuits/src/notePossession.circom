@@ -0,0 +1,28 @@
+pragma circom 2.2.2;
+// Proof of possession of a note private key, checked by the public key
+// registry before it accepts a note public key.
+
+include "./keypair.circom";
+
+// Proves knowledge of `privateKey` such that `publicKey = Keypair(privateKey)`.
+//
+// `binding` ties the proof to one registration (the registry derives it from
+// the owner address and the encryption key), so a proof observed on-chain
+// cannot be replayed to register the same note key under another owner.
+template NotePossession() {
+    /** PRIVATE INPUTS **/
+    signal input privateKey;
+    /** PUBLIC INPUTS **/
+    signal input publicKey;
+    signal input binding;
+
+    component keypair = Keypair();
+    keypair.privateKey <== privateKey;
+    keypair.publicKey === publicKey;
+
+    // Constrain `binding` so it cannot be changed without invalidating the proof.
+    signal bindingSquare;
+    bindingSquare <== binding * binding;
+}
+
+component main {public [publicKey, binding]} = NotePossession();
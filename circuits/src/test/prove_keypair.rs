@@ -123,4 +123,58 @@ mod tests {
 
         Ok(())
     }
+
+    /// Run a note-possession case and report whether the proof verified.
+    ///
+    /// Witness failures (e.g. a violated `===`) count as a rejection.
+    fn note_possession_verifies(
+        wasm: &PathBuf,
+        r1cs: &PathBuf,
+        private_key: Scalar,
+        public_key: Scalar,
+        binding: Scalar,
+    ) -> bool {
+        let mut inputs = Inputs::new();
+        inputs.set("privateKey", private_key);
+        inputs.set("publicKey", public_key);
+        inputs.set("binding", binding);
+
+        let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            prove_and_verify(wasm, r1cs, &inputs)
+        }));
+        matches!(outcome, Ok(Ok(ref res)) if res.verified)
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_note_possession_accepts_matching_key() -> anyhow::Result<()> {
+        let (wasm, r1cs) = load_artifacts("notePossession")?;
+
+        let sk = Scalar::from(0xC0FFEE_u64);
+        let pk = derive_public_key(sk);
+        assert!(
+            note_possession_verifies(&wasm, &r1cs, sk, pk, Scalar::from(42u64)),
+            "Note possession proof did not verify"
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_note_possession_rejects_foreign_key() -> anyhow::Result<()> {
+        let (wasm, r1cs) = load_artifacts("notePossession")?;
+
+        let victim_pk = derive_public_key(Scalar::from(7u64));
+        assert!(
+            !note_possession_verifies(
+                &wasm,
+                &r1cs,
+                Scalar::from(8u64),
+                victim_pk,
+                Scalar::from(42u64)
+            ),
+            "Proof for a key the prover does not hold unexpectedly verified"
+        );
+        Ok(())
+    }
 }
@@ -1,10 +1,15 @@
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
-use stellar_private_payments::{ProverArtifacts, types::PolicyFlags};
+use stellar_private_payments::{
+    ProverArtifacts,
+    types::{ContractConfig, GvkMode, PolicyFlags, gvk_circuit_stem},
+};
 
 use crate::config::default_data_dir;
 
+const NOTE_POSSESSION_STEM: &str = "notePossession";
+
 pub fn load_transact_artifacts(
     circuits_dir: Option<&Path>,
 ) -> Result<Vec<(PolicyFlags, ProverArtifacts)>> {
@@ -21,14 +26,45 @@ pub fn load_transact_artifacts_for_policy(
     circuits_dir: Option<&Path>,
     policy_flags: PolicyFlags,
 ) -> Result<ProverArtifacts> {
+    load_circuit_artifacts(circuits_dir, &policy_flags.circuit_stem())
+}
+
+/// `(PolicyFlags, GvkMode)` of every enabled GVK pool in `deployment`,
+/// deduplicated. Plain pools are covered by [`load_transact_artifacts`].
+pub fn gvk_pool_circuits(deployment: &ContractConfig) -> Vec<(PolicyFlags, GvkMode)> {
+    let mut circuits: Vec<(PolicyFlags, GvkMode)> = Vec::new();
+    for pool in deployment.pools.iter().filter(|pool| pool.enabled) {
+        let key = (pool.policy_flags, pool.gvk_mode);
+        if pool.gvk_mode != GvkMode::Off && !circuits.contains(&key) {
+            circuits.push(key);
+        }
+    }
+    circuits
+}
+
+/// Load the `policy_tx_gvk_*` circuit for a GVK pool.
+pub fn load_gvk_transact_artifacts(
+    circuits_dir: Option<&Path>,
+    policy_flags: PolicyFlags,
+    gvk_mode: GvkMode,
+) -> Result<ProverArtifacts> {
+    load_circuit_artifacts(circuits_dir, &gvk_circuit_stem(policy_flags, gvk_mode))
+}
+
+/// Load the `notePossession` circuit used to prove key ownership on
+/// registration.
+pub fn load_note_possession_artifacts(circuits_dir: Option<&Path>) -> Result<ProverArtifacts> {
+    load_circuit_artifacts(circuits_dir, NOTE_POSSESSION_STEM)
+}
+
+fn load_circuit_artifacts(circuits_dir: Option<&Path>, stem: &str) -> Result<ProverArtifacts> {
     let circuits = circuits_dir
         .map(PathBuf::from)
         .unwrap_or_else(default_circuits_dir);
-    let stem = policy_flags.circuit_stem();
 
     Ok(ProverArtifacts {
-        proving_key: read_proving_key(&circuits, &stem)?,
-        circuit_graph: read_circuit_graph(&circuits, &stem)?,
+        proving_key: read_proving_key(&circuits, stem)?,
+        circuit_graph: read_circuit_graph(&circuits, stem)?,
         circuit_r1cs: std::fs::read(circuits.join(format!("{stem}.r1cs")))
             .with_context(|| format!("read {}", circuits.join(format!("{stem}.r1cs")).display()))?,
     })
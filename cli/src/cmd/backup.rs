@@ -0,0 +1,80 @@
+//! `backup export` / `backup import` — move a wallet between data dirs, or
+//! between the CLI and the browser app, as an encrypted backup file.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use stellar_private_payments::types::{Passphrase, correlation_id_or_new};
+
+use crate::{config::CliConfig, onboard, output, session::ClientSession};
+
+#[derive(Serialize)]
+struct BackupOut<'a> {
+    account: &'a str,
+    path: &'a str,
+}
+
+// No value fields: the passphrase must never reach the logs.
+#[tracing::instrument(
+    name = "cmd_backup_export",
+    skip_all,
+    fields(correlation_id = %correlation_id_or_new())
+)]
+pub fn export(config: &CliConfig, path: &Path, passphrase: &str, json: bool) -> Result<()> {
+    let account = config.require_account()?;
+    onboard::ensure_ready(config, &account)?;
+    let network = config.resolve_network()?;
+    let session = ClientSession::new(config, &account, &network, true)?;
+    let backup = session
+        .account()
+        .export_backup(&Passphrase(passphrase.to_string()))
+        .map_err(|e| anyhow::anyhow!("export backup: {e}"))?;
+    std::fs::write(path, backup)
+        .with_context(|| format!("failed to write backup to {}", path.display()))?;
+
+    let path = path.display().to_string();
+    let payload = BackupOut {
+        account: &account.address,
+        path: &path,
+    };
+    if json {
+        return output::emit(&payload, true);
+    }
+    output::print_section("Backup exported");
+    output::print_kv("account", payload.account);
+    output::print_kv("path", payload.path);
+    Ok(())
+}
+
+// No value fields: the passphrase must never reach the logs.
+#[tracing::instrument(
+    name = "cmd_backup_import",
+    skip_all,
+    fields(correlation_id = %correlation_id_or_new())
+)]
+pub fn import(config: &CliConfig, path: &Path, passphrase: &str, json: bool) -> Result<()> {
+    let account = config.require_account()?;
+    let network = config.resolve_network()?;
+    let backup = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read backup from {}", path.display()))?;
+    // No `ensure_ready`: restoring is how keys get into an empty data dir.
+    let session = ClientSession::new(config, &account, &network, true)?;
+    session
+        .account()
+        .import_backup(&backup, &Passphrase(passphrase.to_string()))
+        .map_err(|e| anyhow::anyhow!("import backup: {e}"))?;
+
+    let path = path.display().to_string();
+    let payload = BackupOut {
+        account: &account.address,
+        path: &path,
+    };
+    if json {
+        return output::emit(&payload, true);
+    }
+    output::print_section("Backup imported");
+    output::print_kv("account", payload.account);
+    output::print_kv("path", payload.path);
+    Ok(())
+}
@@ -1,5 +1,5 @@
-//! `keys` — show the account's privacy public keys; `asp-secret` reveals
-//! the ASP secret (membership blinding).
+//! `keys` — show the account's privacy public keys and ASP membership leaf;
+//! `asp-secret` reveals the ASP secret (membership blinding).
 
 use anyhow::Result;
 use serde::Serialize;
@@ -22,6 +22,13 @@ pub fn show(config: &CliConfig, json: bool) -> Result<()> {
         .account()
         .user_public_keys()
         .map_err(|e| anyhow::anyhow!("privacy keys: {e}"))?;
+    // With --sub-account this is the sub-account's leaf, which the ASP admin
+    // inserts before the sub-account can spend.
+    let asp_leaf = session
+        .account()
+        .derive_asp_user_leaf()
+        .map_err(|e| anyhow::anyhow!("ASP membership leaf: {e}"))?
+        .to_string();
 
     let note = hex0x(&note)?;
     let encryption = hex0x(&enc)?;
@@ -31,11 +38,13 @@ pub fn show(config: &CliConfig, json: bool) -> Result<()> {
         account: &'a str,
         note_public_key: &'a str,
         encryption_public_key: &'a str,
+        asp_leaf: &'a str,
     }
     let payload = KeysOut {
         account: &account.address,
         note_public_key: &note,
         encryption_public_key: &encryption,
+        asp_leaf: &asp_leaf,
     };
     if json {
         return output::emit(&payload, true);
@@ -44,6 +53,7 @@ pub fn show(config: &CliConfig, json: bool) -> Result<()> {
     output::print_kv("account", payload.account);
     output::print_kv("note_public_key", payload.note_public_key);
     output::print_kv("encryption_public_key", payload.encryption_public_key);
+    output::print_kv("asp_leaf", payload.asp_leaf);
     Ok(())
 }
 
@@ -1,3 +1,4 @@
+pub mod backup;
 pub mod config;
 pub mod disclaimer;
 pub mod feed;
@@ -6,4 +7,6 @@ pub mod license;
 pub mod overview;
 pub mod pool;
 pub mod register;
+pub mod request;
 pub mod version;
+pub mod viewing_key;
@@ -1,17 +1,23 @@
 //! Core value operations: deposit, transfer, withdraw. Each takes the pool
 //! contract id and requires a ready account.
 
-use anyhow::Result;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
 use stellar_private_payments::{
-    Error,
-    types::{Sensitive, TransactionResult, correlation_id_or_new},
+    Error, TransferOptions,
+    types::{
+        NoteAmount, PaymentRequest, Sensitive, TransactionResult, TransferRecipient,
+        correlation_id_or_new,
+    },
 };
 
 use crate::{
     config::{CliConfig, validate_pool},
     explorer::Explorer,
     onboard, output,
-    session::{ClientSession, parse_amount, parse_transfer_recipient},
+    session::{ClientSession, RecipientArgs, parse_amount, parse_transfer_recipient},
 };
 
 fn open_pool(
@@ -30,12 +36,28 @@ fn open_pool(
     skip_all,
     fields(correlation_id = %correlation_id_or_new(), amount = ?Sensitive(&amount))
 )]
-pub fn deposit(config: &CliConfig, pool: &str, amount: &str, json: bool) -> Result<()> {
+pub fn deposit(
+    config: &CliConfig,
+    pool: &str,
+    amount: &str,
+    recipient: &RecipientArgs,
+    memo: Option<&str>,
+    json: bool,
+) -> Result<()> {
     let pool = open_pool(config, pool)?;
     let amount = parse_amount(amount)?;
-    let result = pool
-        .deposit(amount)
-        .map_err(|e| map_pool_err(config, e, json))?;
+    let result = if recipient.is_empty() {
+        if memo.is_some() {
+            anyhow::bail!("--memo needs a recipient: a deposit to yourself carries no memo");
+        }
+        pool.deposit(amount)
+    } else {
+        let options = TransferOptions {
+            memo: memo.map(str::to_string),
+        };
+        pool.deposit_to(recipient.recipient()?, amount, options)
+    }
+    .map_err(|e| map_pool_err(config, e, json))?;
     print_tx_results(
         config,
         "Deposit submitted",
@@ -47,26 +69,152 @@ pub fn deposit(config: &CliConfig, pool: &str, amount: &str, json: bool) -> Resu
 #[tracing::instrument(
     name = "cmd_transfer",
     skip_all,
-    fields(correlation_id = %correlation_id_or_new(), amount = ?Sensitive(&amount), recipient = ?Sensitive(&to))
+    fields(correlation_id = %correlation_id_or_new(), amount = ?Sensitive(&amount), recipient = ?Sensitive(&recipient.to))
 )]
 pub fn transfer(
     config: &CliConfig,
     pool: &str,
     amount: &str,
-    to: Option<&str>,
-    note_key: Option<&str>,
-    encryption_key: Option<&str>,
+    recipient: &RecipientArgs,
+    memo: Option<&str>,
     json: bool,
 ) -> Result<()> {
     let pool = open_pool(config, pool)?;
-    let recipient = parse_transfer_recipient(to, note_key, encryption_key)?;
+    let recipient = recipient.recipient()?;
     let amount = parse_amount(amount)?;
+    let options = TransferOptions {
+        memo: memo.map(str::to_string),
+    };
     let results = pool
-        .transfer(recipient, amount)
+        .transfer(recipient, amount, options)
         .map_err(|e| map_pool_err(config, e, json))?;
     print_tx_results(config, "Transfer submitted", &results, json)
 }
 
+/// One recipient of `transfer-many`, as printed.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PayoutRow {
+    line: usize,
+    recipient: String,
+    amount: String,
+    tx_hash: Option<String>,
+}
+
+#[tracing::instrument(
+    name = "cmd_transfer_many",
+    skip_all,
+    fields(correlation_id = %correlation_id_or_new())
+)]
+pub fn transfer_many(
+    config: &CliConfig,
+    pool: &str,
+    file: &Path,
+    memo: Option<&str>,
+    json: bool,
+) -> Result<()> {
+    let contents =
+        std::fs::read_to_string(file).with_context(|| format!("read {}", file.display()))?;
+    let entries = parse_payout_file(&contents)?;
+    let pool = open_pool(config, pool)?;
+    let options = TransferOptions {
+        memo: memo.map(str::to_string),
+    };
+    let (mut rows, recipients): (Vec<PayoutRow>, Vec<_>) = entries.into_iter().unzip();
+    let report = pool
+        .transfer_many(recipients, options)
+        .map_err(|e| map_pool_err(config, e, json))?;
+
+    for (row, payout) in rows.iter_mut().zip(report.payouts) {
+        row.tx_hash = payout.tx_hash;
+    }
+    if json {
+        output::emit(&rows, true)?;
+    } else {
+        output::print_section("Payouts");
+        for row in &rows {
+            output::print_kv(
+                &format!("line {}", row.line),
+                format!(
+                    "{} {} → {}",
+                    row.recipient,
+                    row.amount,
+                    row.tx_hash.as_deref().unwrap_or("not paid")
+                ),
+            );
+        }
+    }
+    match report.error {
+        Some(error) => Err(anyhow::anyhow!(
+            "payout stopped early: {error}; run `spp sync`, then transfer-many again with the unpaid lines"
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Recipient, amount and memo of one `transfer-many` line.
+type PayoutEntry = (TransferRecipient, NoteAmount, Option<String>);
+
+/// Parse `transfer-many` lines of `<recipient> <amount> [memo]`, where the
+/// recipient is a `G…` address, `sub:<n>` or `<note key hex>:<encryption key
+/// hex>` and the memo is the rest of the line. Blank lines and `#` comments
+/// are skipped.
+fn parse_payout_file(contents: &str) -> Result<Vec<(PayoutRow, PayoutEntry)>> {
+    let mut entries = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index.saturating_add(1);
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some((recipient, rest)) = line.split_once(char::is_whitespace) else {
+            anyhow::bail!("line {line_number}: expected `<recipient> <amount> [memo]`");
+        };
+        let rest = rest.trim_start();
+        let (amount, memo) = match rest.split_once(char::is_whitespace) {
+            Some((amount, memo)) => (amount, Some(memo.trim().to_string())),
+            None => (rest, None),
+        };
+        let parsed = if let Some(index) = recipient.strip_prefix("sub:") {
+            let index = index
+                .parse::<u32>()
+                .with_context(|| format!("line {line_number}: invalid sub-account index"))?;
+            parse_transfer_recipient(None, None, None, Some(index))
+        } else if let Some((note_key, encryption_key)) = recipient.split_once(':') {
+            parse_transfer_recipient(None, Some(note_key), Some(encryption_key), None)
+        } else {
+            parse_transfer_recipient(Some(recipient), None, None, None)
+        }
+        .with_context(|| format!("line {line_number}"))?;
+        let parsed_amount = parse_amount(amount).with_context(|| format!("line {line_number}"))?;
+        let row = PayoutRow {
+            line: line_number,
+            recipient: recipient.to_string(),
+            amount: amount.to_string(),
+            tx_hash: None,
+        };
+        entries.push((row, (parsed, parsed_amount, memo)));
+    }
+    if entries.is_empty() {
+        anyhow::bail!("no recipients in payout file");
+    }
+    Ok(entries)
+}
+
+#[tracing::instrument(
+    name = "cmd_pay",
+    skip_all,
+    fields(correlation_id = %correlation_id_or_new())
+)]
+pub fn pay(config: &CliConfig, uri: &str, json: bool) -> Result<()> {
+    let request = PaymentRequest::parse(uri)?;
+    let pool = open_pool(config, &request.pool_contract_id)?;
+    let results = pool
+        .pay_request(uri)
+        .map_err(|e| map_pool_err(config, e, json))?;
+    print_tx_results(config, "Payment submitted", &results, json)
+}
+
 #[tracing::instrument(
     name = "cmd_withdraw",
     skip_all,
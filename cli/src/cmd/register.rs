@@ -38,8 +38,9 @@ pub fn run(config: &CliConfig, json: bool) -> Result<()> {
 }
 
 /// Register the account's privacy keys on-chain. Returns the transaction hash.
-/// Registration is idempotent on-chain (re-registering just updates the stored
-/// keys), so no local pre-check is required.
+/// Registration is idempotent on-chain (re-registering the same keys is a
+/// no-op), so no local pre-check is required. Needs a full session: the
+/// registry requires a `notePossession` proof.
 pub fn register_account(
     config: &CliConfig,
     account: &crate::account::Account,
@@ -49,7 +50,7 @@ pub fn register_account(
         "Preparing address registration for {}",
         stellar_private_payments::types::Sensitive(&account.address)
     );
-    let result = ClientSession::new(config, account, network, true)?.register_public_keys()?;
+    let result = ClientSession::new(config, account, network, false)?.register_public_keys()?;
     log::info!("Registration confirmed: {}", result.tx_hash);
     Ok(result.tx_hash)
 }
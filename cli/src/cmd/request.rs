@@ -0,0 +1,73 @@
+//! `request` — print an `spp:` payment request URI for the account's keys, to
+//! be paid with `spp pay`.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use serde::Serialize;
+use stellar_private_payments::types::{Sensitive, correlation_id_or_new};
+
+use crate::{
+    config::{CliConfig, validate_pool},
+    onboard, output,
+    session::{ClientSession, parse_amount},
+};
+
+#[tracing::instrument(
+    name = "cmd_request",
+    skip_all,
+    fields(correlation_id = %correlation_id_or_new(), amount = ?Sensitive(&amount))
+)]
+pub fn run(
+    config: &CliConfig,
+    pool: &str,
+    amount: &str,
+    memo: Option<&str>,
+    expires_in: Option<u64>,
+    json: bool,
+) -> Result<()> {
+    let account = config.require_account()?;
+    onboard::ensure_ready(config, &account)?;
+    validate_pool(pool, &config.deployment)?;
+    let amount = parse_amount(amount)?;
+    let network = config.resolve_network()?;
+    let session = ClientSession::new(config, &account, &network, true)?;
+    let mut request = session
+        .account()
+        .payment_request(pool, amount)
+        .map_err(|e| anyhow::anyhow!("payment request: {e}"))?;
+    request.memo = memo.map(str::to_string);
+    request.expires_at = expires_in
+        .map(|secs| {
+            SystemTime::now()
+                .checked_add(Duration::from_secs(secs))
+                .ok_or_else(|| anyhow::anyhow!("--expires-in is too large"))?
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .map_err(|e| anyhow::anyhow!("system clock: {e}"))
+        })
+        .transpose()?;
+    let uri = request.to_uri()?;
+
+    #[derive(Serialize)]
+    struct RequestOut<'a> {
+        account: &'a str,
+        uri: &'a str,
+        expires_at: Option<u64>,
+    }
+    let payload = RequestOut {
+        account: &account.address,
+        uri: &uri,
+        expires_at: request.expires_at,
+    };
+    if json {
+        return output::emit(&payload, true);
+    }
+    output::print_section("Payment request");
+    output::print_kv("account", payload.account);
+    if let Some(expires_at) = payload.expires_at {
+        output::print_kv("expires_at", expires_at);
+    }
+    output::print_kv("uri", payload.uri);
+    Ok(())
+}
@@ -0,0 +1,94 @@
+//! `viewing-key export` / `viewing-key import` — share an account's viewing
+//! key, and follow it as a watch-only account that syncs and reads balances
+//! but cannot prove or sign.
+
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use stellar_private_payments::types::{ViewingKey, correlation_id_or_new};
+
+use crate::{config::CliConfig, onboard, output, session::ClientSession};
+
+#[derive(Serialize)]
+struct ViewingKeyOut<'a> {
+    account: &'a str,
+    path: &'a str,
+}
+
+// No value fields: the viewing key reveals every note of the account.
+#[tracing::instrument(
+    name = "cmd_viewing_key_export",
+    skip_all,
+    fields(correlation_id = %correlation_id_or_new())
+)]
+pub fn export(config: &CliConfig, path: &Path, json: bool) -> Result<()> {
+    let account = config.require_account()?;
+    onboard::ensure_ready(config, &account)?;
+    let network = config.resolve_network()?;
+    let session = ClientSession::new(config, &account, &network, true)?;
+    let viewing_key = session
+        .account()
+        .export_viewing_key()
+        .map_err(|e| anyhow::anyhow!("export viewing key: {e}"))?;
+    std::fs::write(path, viewing_key.to_json()?)
+        .with_context(|| format!("failed to write viewing key to {}", path.display()))?;
+
+    let path = path.display().to_string();
+    let payload = ViewingKeyOut {
+        account: &account.address,
+        path: &path,
+    };
+    if json {
+        return output::emit(&payload, true);
+    }
+    output::print_section("Viewing key exported");
+    output::print_kv("account", payload.account);
+    output::print_kv("path", payload.path);
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "cmd_viewing_key_import",
+    skip_all,
+    fields(correlation_id = %correlation_id_or_new())
+)]
+pub fn import(config: &CliConfig, path: &Path, json: bool) -> Result<()> {
+    let account = config.require_account()?;
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read viewing key from {}", path.display()))?;
+    let viewing_key = ViewingKey::from_json(&contents)?;
+    if viewing_key.address != account.address {
+        bail!(
+            "viewing key is for {}, not --account {} ({}); add a public-key-only identity \
+             with `stellar keys add <alias> --public-key {}`",
+            viewing_key.address,
+            account.alias,
+            account.address,
+            viewing_key.address
+        );
+    }
+    // No `ensure_ready`: a watch-only account has no keys until this import.
+    let mut storage = config.open_storage()?;
+    config.unlock_storage(&mut storage)?;
+    storage
+        .save_viewing_key(&viewing_key)
+        .context("save viewing key to local wallet database")?;
+
+    let path = path.display().to_string();
+    let payload = ViewingKeyOut {
+        account: &account.address,
+        path: &path,
+    };
+    if json {
+        return output::emit(&payload, true);
+    }
+    output::print_section("Viewing key imported");
+    output::print_kv("account", payload.account);
+    output::print_kv("path", payload.path);
+    println!(
+        "\nWatch-only: run `spp onboard --account {}` once, then `spp overview`.",
+        account.alias
+    );
+    Ok(())
+}
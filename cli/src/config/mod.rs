@@ -3,7 +3,10 @@ mod toml;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, bail};
-use stellar_private_payments::{state::SqliteStorage, types::ContractConfig};
+use stellar_private_payments::{
+    state::SqliteStorage,
+    types::{ContractConfig, Passphrase},
+};
 
 use crate::{
     account::{Account, resolve},
@@ -24,6 +27,11 @@ pub const EMBEDDED_DEPLOYMENT_LABEL: &str = "embedded:testnet";
 /// Deployment config provisioned into the data dir by `scripts/install.sh`.
 pub const DEPLOYMENT_FILE_NAME: &str = "deployments.json";
 
+/// Error for commands that need the privacy keys of a passphrase-protected
+/// wallet.
+pub const WALLET_LOCKED: &str =
+    "wallet is locked: pass --wallet-passphrase or set SPP_WALLET_PASSPHRASE";
+
 /// CLI flag overrides used to build a [`CliConfig`].
 #[derive(Debug, Default)]
 pub struct CliConfigOverrides {
@@ -31,8 +39,10 @@ pub struct CliConfigOverrides {
     pub network: Option<String>,
     pub data_dir: Option<PathBuf>,
     pub account: Option<String>,
+    pub sub_account: u32,
     pub stellar_config_dir: Option<PathBuf>,
     pub circuits_dir: Option<PathBuf>,
+    pub wallet_passphrase: Option<String>,
 }
 
 /// Resolved (offline) CLI configuration.
@@ -55,7 +65,13 @@ pub struct CliConfig {
     pub stellar_config_dir: Option<PathBuf>,
     /// `stellar keys` alias supplied via `--account`.
     pub account: Option<String>,
+    /// Sub-account of `account` supplied via `--sub-account`; 0 for the
+    /// account itself.
+    pub sub_account: u32,
     pub circuits_dir: Option<PathBuf>,
+    /// Wallet passphrase supplied via `--wallet-passphrase` /
+    /// `SPP_WALLET_PASSPHRASE`.
+    pub wallet_passphrase: Option<Passphrase>,
 }
 
 impl CliConfig {
@@ -70,8 +86,10 @@ impl CliConfig {
             network,
             data_dir,
             account,
+            sub_account,
             stellar_config_dir,
             circuits_dir,
+            wallet_passphrase,
         } = overrides;
 
         let deployment_path = deployment_path.or(file.defaults.deployment.map(toml::expand_path));
@@ -98,7 +116,9 @@ impl CliConfig {
             data_dir,
             stellar_config_dir,
             account,
+            sub_account,
             circuits_dir,
+            wallet_passphrase: wallet_passphrase.map(Passphrase),
         })
     }
 
@@ -135,6 +155,18 @@ impl CliConfig {
         let path = self.db_path();
         SqliteStorage::connect_file(&path).with_context(|| format!("open {}", path.display()))
     }
+
+    /// Unlock `storage` with `--wallet-passphrase`, sealing keys still stored
+    /// in plaintext. Without a passphrase, fails only if the wallet is
+    /// locked.
+    pub fn unlock_storage(&self, storage: &mut SqliteStorage) -> Result<()> {
+        if let Some(passphrase) = &self.wallet_passphrase {
+            storage.unlock(passphrase).context("unlock wallet")?;
+        } else if storage.is_locked()? {
+            bail!(WALLET_LOCKED);
+        }
+        Ok(())
+    }
 }
 
 pub fn default_data_dir() -> PathBuf {
@@ -18,6 +18,7 @@ use config::{
     CliConfig, CliConfigOverrides, default_config_path, load_file_config, resolve_config_path,
 };
 use onboard::OnboardArgs;
+use session::RecipientArgs;
 
 #[derive(Debug, Parser)]
 #[command(
@@ -62,6 +63,21 @@ struct Cli {
     #[arg(long, global = true, env = "STELLAR_ACCOUNT")]
     account: Option<String>,
 
+    /// Sub-account of --account to act as (0 is the account itself)
+    #[arg(long, global = true, default_value_t = 0)]
+    sub_account: u32,
+
+    /// Wallet passphrase sealing the privacy keys in the data dir; the first
+    /// use sets it and seals keys stored in plaintext (prefer the environment
+    /// variable over the flag)
+    #[arg(
+        long,
+        global = true,
+        env = "SPP_WALLET_PASSPHRASE",
+        hide_env_values = true
+    )]
+    wallet_passphrase: Option<String>,
+
     /// Emit JSON instead of human-readable output
     #[arg(long, global = true)]
     json: bool,
@@ -115,7 +131,19 @@ enum Commands {
         #[command(subcommand)]
         command: ConfigCommands,
     },
-    /// Show your note and encryption public keys
+    /// Export or restore an encrypted wallet backup (also readable by the
+    /// web app)
+    Backup {
+        #[command(subcommand)]
+        command: BackupCommands,
+    },
+    /// Export a viewing key, or import one to watch an account without
+    /// spend authority
+    ViewingKey {
+        #[command(subcommand)]
+        command: ViewingKeyCommands,
+    },
+    /// Show your note and encryption public keys and ASP membership leaf
     Keys,
     /// Reveal the ASP secret (keep it private)
     AspSecret,
@@ -123,12 +151,19 @@ enum Commands {
     Version,
     /// Register your public keys in the on-chain address book
     Register,
-    /// Deposit public tokens into a pool
+    /// Deposit public tokens into a pool, for yourself or straight into
+    /// another user's shielded balance
     Deposit {
         /// Pool contract id (C…)
         pool: String,
         /// Amount in token units (e.g. 1 or 0.0001)
         amount: String,
+        /// Recipient; omit to deposit to yourself
+        #[command(flatten)]
+        recipient: RecipientArgs,
+        /// Memo encrypted to the recipient (at most 127 bytes)
+        #[arg(long)]
+        memo: Option<String>,
     },
     /// Private transfer to a recipient
     Transfer {
@@ -136,15 +171,42 @@ enum Commands {
         pool: String,
         /// Amount in token units (e.g. 1 or 0.0001)
         amount: String,
-        /// Recipient Stellar address (G…); looked up in the registry
+        #[command(flatten)]
+        recipient: RecipientArgs,
+        /// Memo encrypted to the recipient (at most 127 bytes)
         #[arg(long)]
-        to: Option<String>,
-        /// Recipient BN254 note public key (hex)
+        memo: Option<String>,
+    },
+    /// Pay many recipients from a file in one chain of transfers
+    TransferMany {
+        /// Pool contract id (C…)
+        pool: String,
+        /// File of `<recipient> <amount> [memo]` lines; the recipient is a G…
+        /// address, `sub:<n>` or `<note key hex>:<encryption key hex>`, and
+        /// the memo runs to the end of the line
+        file: PathBuf,
+        /// Memo encrypted to every recipient without its own (at most 127
+        /// bytes)
+        #[arg(long)]
+        memo: Option<String>,
+    },
+    /// Print an `spp:` payment request URI for your keys
+    Request {
+        /// Pool contract id (C…)
+        pool: String,
+        /// Amount in token units (e.g. 1 or 0.0001)
+        amount: String,
+        /// Memo for the payer to attach (at most 127 bytes)
         #[arg(long)]
-        note_key: Option<String>,
-        /// Recipient X25519 encryption public key (hex)
+        memo: Option<String>,
+        /// Seconds until the request expires
         #[arg(long)]
-        encryption_key: Option<String>,
+        expires_in: Option<u64>,
+    },
+    /// Pay an `spp:` payment request URI
+    Pay {
+        /// Payment request URI (spp:…)
+        uri: String,
     },
     /// Withdraw to a public Stellar address
     Withdraw {
@@ -183,6 +245,41 @@ enum ConfigCommands {
     },
 }
 
+#[derive(Debug, Subcommand)]
+enum BackupCommands {
+    /// Write keys, notes, operation history and settings to an encrypted file
+    Export {
+        /// Backup file to write
+        path: PathBuf,
+        /// Backup passphrase (prefer the environment variable over the flag)
+        #[arg(long, env = "SPP_BACKUP_PASSPHRASE", hide_env_values = true)]
+        passphrase: String,
+    },
+    /// Restore a backup file for --account
+    Import {
+        /// Backup file to read
+        path: PathBuf,
+        /// Backup passphrase (prefer the environment variable over the flag)
+        #[arg(long, env = "SPP_BACKUP_PASSPHRASE", hide_env_values = true)]
+        passphrase: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ViewingKeyCommands {
+    /// Write the viewing key of --account to a file (reveals all its notes)
+    Export {
+        /// Viewing key file to write
+        path: PathBuf,
+    },
+    /// Import a viewing key as a watch-only --account (a public-key-only
+    /// `stellar keys` identity)
+    Import {
+        /// Viewing key file to read
+        path: PathBuf,
+    },
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
     logging::init(cli.verbose, cli.json);
@@ -202,8 +299,10 @@ fn main() -> Result<()> {
             network: cli.network,
             data_dir: cli.data_dir,
             account: cli.account,
+            sub_account: cli.sub_account,
             stellar_config_dir: cli.stellar_config_dir,
             circuits_dir: cli.circuits_dir,
+            wallet_passphrase: cli.wallet_passphrase,
         },
     )?;
 
@@ -240,26 +339,44 @@ fn main() -> Result<()> {
                 cmd::config::set_bootnode(&config, url.as_deref(), disable, json)
             }
         },
+        Commands::Backup { command } => match command {
+            BackupCommands::Export { path, passphrase } => {
+                cmd::backup::export(&config, &path, &passphrase, json)
+            }
+            BackupCommands::Import { path, passphrase } => {
+                cmd::backup::import(&config, &path, &passphrase, json)
+            }
+        },
+        Commands::ViewingKey { command } => match command {
+            ViewingKeyCommands::Export { path } => cmd::viewing_key::export(&config, &path, json),
+            ViewingKeyCommands::Import { path } => cmd::viewing_key::import(&config, &path, json),
+        },
         Commands::Keys => cmd::keys::show(&config, json),
         Commands::AspSecret => cmd::keys::asp_secret(&config, json),
         Commands::Version => cmd::version::run(json),
         Commands::Register => cmd::register::run(&config, json),
-        Commands::Deposit { pool, amount } => cmd::pool::deposit(&config, &pool, &amount, json),
+        Commands::Deposit {
+            pool,
+            amount,
+            recipient,
+            memo,
+        } => cmd::pool::deposit(&config, &pool, &amount, &recipient, memo.as_deref(), json),
         Commands::Transfer {
             pool,
             amount,
-            to,
-            note_key,
-            encryption_key,
-        } => cmd::pool::transfer(
-            &config,
-            &pool,
-            &amount,
-            to.as_deref(),
-            note_key.as_deref(),
-            encryption_key.as_deref(),
-            json,
-        ),
+            recipient,
+            memo,
+        } => cmd::pool::transfer(&config, &pool, &amount, &recipient, memo.as_deref(), json),
+        Commands::TransferMany { pool, file, memo } => {
+            cmd::pool::transfer_many(&config, &pool, &file, memo.as_deref(), json)
+        }
+        Commands::Request {
+            pool,
+            amount,
+            memo,
+            expires_in,
+        } => cmd::request::run(&config, &pool, &amount, memo.as_deref(), expires_in, json),
+        Commands::Pay { uri } => cmd::pool::pay(&config, &uri, json),
         Commands::Withdraw { pool, amount, to } => {
             cmd::pool::withdraw(&config, &pool, &amount, to.as_deref(), json)
         }
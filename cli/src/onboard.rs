@@ -47,7 +47,8 @@ pub struct OnboardArgs {
 }
 
 /// Gate for account commands: stellar-cli present, disclaimer accepted, keys
-/// derived. Bails with a pointer to `spp onboard` when not ready.
+/// derived or a viewing key imported. Bails with a pointer to `spp onboard`
+/// when not ready.
 pub fn ensure_ready(config: &CliConfig, account: &Account) -> Result<()> {
     stellar_cli::ensure_installed()?;
     let mut storage = config.open_storage()?;
@@ -57,7 +58,7 @@ pub fn ensure_ready(config: &CliConfig, account: &Account) -> Result<()> {
             account.alias
         );
     }
-    if storage.get_user_keys(&account.address)?.is_none() {
+    if !storage.has_user_keys(&account.address)? && !storage.is_watch_only(&account.address)? {
         bail!(
             "Privacy keys are not set up. Run: spp onboard --account {}",
             account.alias
@@ -95,12 +96,17 @@ pub fn run(config: &CliConfig, args: &OnboardArgs, json: bool) -> Result<()> {
     }
 
     // 4. Derive privacy keys.
-    if storage.get_user_keys(&account.address)?.is_some() {
+    let watch_only = storage.is_watch_only(&account.address)?;
+    if watch_only {
+        say(interactive, "Watch-only account: viewing key present.");
+    } else if storage.has_user_keys(&account.address)? {
         say(interactive, "Privacy keys already present.");
     } else {
         if interactive {
             println!("\n{KEYS_TEXT}");
         }
+        // Keys are sealed on save once a passphrase is set.
+        config.unlock_storage(&mut storage)?;
         derive_and_save_keys(config, &account, &mut storage)?;
         say(interactive, "Privacy keys derived and stored.");
     }
@@ -111,8 +117,10 @@ pub fn run(config: &CliConfig, args: &OnboardArgs, json: bool) -> Result<()> {
     // 6. Explorer.
     configure_explorer(&mut storage, args, interactive)?;
 
-    // 7. Optional registration.
-    maybe_register(config, &account, args, interactive)?;
+    // 7. Optional registration; a viewing key cannot prove note possession.
+    if !watch_only {
+        maybe_register(config, &account, args, interactive)?;
+    }
 
     say(interactive, "\nOnboarding complete.");
     Ok(())
@@ -1,5 +1,11 @@
 use crate::{
-    account::Account, artifacts::load_transact_artifacts, config::CliConfig, signer::AliasSigner,
+    account::Account,
+    artifacts::{
+        gvk_pool_circuits, load_gvk_transact_artifacts, load_note_possession_artifacts,
+        load_transact_artifacts,
+    },
+    config::{CliConfig, WALLET_LOCKED},
+    signer::AliasSigner,
     stellar_cli::StellarNetwork,
 };
 use anyhow::Result;
@@ -27,6 +33,18 @@ impl ClientSession {
         let storage_path = config.db_path().to_string_lossy().into_owned();
         let storage =
             LocalStorage::open(&storage_path).map_err(|e| anyhow::anyhow!("open storage: {e}"))?;
+        // Unlocking also seals keys a passphrase-less data dir stored in
+        // plaintext.
+        if let Some(passphrase) = &config.wallet_passphrase {
+            storage
+                .unlock(passphrase)
+                .map_err(|e| anyhow::anyhow!("unlock wallet: {e}"))?;
+        } else if storage
+            .is_locked()
+            .map_err(|e| anyhow::anyhow!("wallet lock state: {e}"))?
+        {
+            anyhow::bail!(WALLET_LOCKED);
+        }
         let bootnode_setting = storage
             .storage()
             .get_bootnode_setting()
@@ -43,11 +61,39 @@ impl ClientSession {
             )
             .map_err(|e| anyhow::anyhow!("init client: {e}"))?
         } else {
-            let artifacts = load_transact_artifacts(Some(config.circuits_dir_path().as_path()))?;
-            let prover = Handle::from_box(Box::new(
-                LocalProver::from_artifacts(&artifacts)
-                    .map_err(|e| anyhow::anyhow!("init transact prover: {e}"))?,
-            ) as Box<dyn Prover>);
+            let circuits_dir = config.circuits_dir_path();
+            let artifacts = load_transact_artifacts(Some(circuits_dir.as_path()))?;
+            let mut local = LocalProver::from_artifacts(&artifacts)
+                .map_err(|e| anyhow::anyhow!("init transact prover: {e}"))?;
+            // GVK circuits are only needed for the deployment's GVK pools;
+            // other pools keep working when they are not installed.
+            for (policy_flags, gvk_mode) in gvk_pool_circuits(&config.deployment) {
+                match load_gvk_transact_artifacts(
+                    Some(circuits_dir.as_path()),
+                    policy_flags,
+                    gvk_mode,
+                ) {
+                    Ok(gvk) => {
+                        local = local
+                            .with_gvk_artifacts(policy_flags, gvk_mode, &gvk)
+                            .map_err(|e| anyhow::anyhow!("init GVK transact prover: {e}"))?;
+                    }
+                    Err(e) => log::warn!(
+                        "GVK transact circuit for {policy_flags:?} {gvk_mode:?} unavailable: {e:#}"
+                    ),
+                }
+            }
+            // Only `register` needs this circuit; sessions that never register
+            // keep working when it is not installed.
+            match load_note_possession_artifacts(Some(circuits_dir.as_path())) {
+                Ok(possession) => {
+                    local = local
+                        .with_note_possession(&possession)
+                        .map_err(|e| anyhow::anyhow!("init notePossession prover: {e}"))?;
+                }
+                Err(e) => log::debug!("notePossession circuit unavailable: {e:#}"),
+            }
+            let prover = Handle::from_box(Box::new(local) as Box<dyn Prover>);
             Client::init(
                 network.rpc_url.clone(),
                 storage,
@@ -57,9 +103,14 @@ impl ClientSession {
             )
             .map_err(|e| anyhow::anyhow!("init client: {e}"))?
         };
-        let sdk_account = client
+        let mut sdk_account = client
             .account(&account.address, alias_signer(config, account, network))
             .map_err(|e| anyhow::anyhow!("open account session: {e}"))?;
+        if config.sub_account != 0 {
+            sdk_account = sdk_account
+                .sub_account(config.sub_account)
+                .map_err(|e| anyhow::anyhow!("open sub-account {}: {e}", config.sub_account))?;
+        }
 
         Ok(Self {
             client,
@@ -163,23 +214,61 @@ pub fn parse_amount(raw: &str) -> Result<NoteAmount> {
     Ok(NoteAmount::from(amount))
 }
 
-/// Parse `--to` address or explicit note + encryption keys into a
-/// [`TransferRecipient`].
+/// Recipient flags shared by `transfer` and `deposit`.
+#[derive(Debug, clap::Args)]
+pub struct RecipientArgs {
+    /// Recipient Stellar address (G…); looked up in the registry
+    #[arg(long)]
+    pub to: Option<String>,
+    /// Recipient BN254 note public key (hex)
+    #[arg(long)]
+    pub note_key: Option<String>,
+    /// Recipient X25519 encryption public key (hex)
+    #[arg(long)]
+    pub encryption_key: Option<String>,
+    /// Another sub-account of --account
+    #[arg(long)]
+    pub to_sub_account: Option<u32>,
+}
+
+impl RecipientArgs {
+    /// Whether no recipient flag was given.
+    pub fn is_empty(&self) -> bool {
+        self.to.is_none()
+            && self.note_key.is_none()
+            && self.encryption_key.is_none()
+            && self.to_sub_account.is_none()
+    }
+
+    pub fn recipient(&self) -> Result<TransferRecipient> {
+        parse_transfer_recipient(
+            self.to.as_deref(),
+            self.note_key.as_deref(),
+            self.encryption_key.as_deref(),
+            self.to_sub_account,
+        )
+    }
+}
+
+/// Parse `--to` address, explicit note + encryption keys or
+/// `--to-sub-account` into a [`TransferRecipient`].
 pub fn parse_transfer_recipient(
     to: Option<&str>,
     note_key: Option<&str>,
     encryption_key: Option<&str>,
+    to_sub_account: Option<u32>,
 ) -> Result<TransferRecipient> {
-    match (to, note_key, encryption_key) {
-        (Some(address), None, None) => Ok(TransferRecipient::from(address)),
-        (None, Some(note_key), Some(encryption_key)) => Ok(TransferRecipient::keys(
+    match (to, note_key, encryption_key, to_sub_account) {
+        (Some(address), None, None, None) => Ok(TransferRecipient::from(address)),
+        (None, None, None, Some(index)) => Ok(TransferRecipient::SubAccount(index)),
+        (None, Some(note_key), Some(encryption_key), None) => Ok(TransferRecipient::keys(
             NotePublicKey::parse(note_key)
                 .map_err(|e| anyhow::anyhow!("invalid recipient note key: {e}"))?,
             EncryptionPublicKey::parse(encryption_key)
                 .map_err(|e| anyhow::anyhow!("invalid recipient encryption key: {e}"))?,
         )),
         _ => anyhow::bail!(
-            "specify the recipient with --to <G…>, or both --note-key <hex> and --encryption-key <hex>"
+            "specify the recipient with --to <G…>, both --note-key <hex> and --encryption-key <hex>, or --to-sub-account <n>"
         ),
     }
 }
@@ -15,7 +15,7 @@
 // Use Soroban's allocator for heap allocations
 extern crate alloc;
 
-pub use contract_types::{Groth16Error, Groth16Proof, VerificationKeyBytes};
+pub use contract_types::{BatchVerification, Groth16Error, Groth16Proof, VerificationKeyBytes};
 use soroban_sdk::{
     BytesN, Env, Vec, contract, contractimpl,
     crypto::bn254::{Bn254Fr, Bn254G1Affine as G1Affine, Bn254G2Affine as G2Affine},
@@ -90,6 +90,21 @@ impl CircomGroth16Verifier {
         Self::verify_with_vk(&env, &vk, proof, public_inputs)
     }
 
+    /// Verify several proofs against the embedded verification key at once.
+    ///
+    /// The proofs are folded into a single pairing check with a random linear
+    /// combination (see [`Self::verify_batch_with_vk`]), so `N` proofs cost
+    /// `N + 3` pairings instead of `4N`. `public_inputs[i]` belongs to
+    /// `proofs[i]`.
+    pub fn verify_batch(
+        env: Env,
+        proofs: Vec<Groth16Proof>,
+        public_inputs: Vec<Vec<Bn254Fr>>,
+    ) -> Result<BatchVerification, Groth16Error> {
+        let vk = embedded_vk(&env);
+        Self::verify_batch_with_vk(&env, &vk, proofs, public_inputs)
+    }
+
     pub(crate) fn verify_with_vk(
         env: &Env,
         vk: &VerificationKey,
@@ -97,27 +112,7 @@ impl CircomGroth16Verifier {
         pub_inputs: Vec<Bn254Fr>,
     ) -> Result<bool, Groth16Error> {
         let bn = env.crypto().bn254();
-
-        if pub_inputs.len().checked_add(1) != Some(vk.ic.len()) {
-            return Err(Groth16Error::MalformedPublicInputs);
-        }
-
-        let mut vk_x = vk.ic.get(0).ok_or(Groth16Error::MalformedPublicInputs)?;
-
-        for i in 0..pub_inputs.len() {
-            let s = pub_inputs
-                .get(i)
-                .ok_or(Groth16Error::MalformedPublicInputs)?;
-            let ic_idx = i
-                .checked_add(1)
-                .ok_or(Groth16Error::MalformedPublicInputs)?;
-            let v = vk
-                .ic
-                .get(ic_idx)
-                .ok_or(Groth16Error::MalformedPublicInputs)?;
-            let prod = bn.g1_mul(&v, &s);
-            vk_x = bn.g1_add(&vk_x, &prod);
-        }
+        let vk_x = prepare_inputs(env, vk, &pub_inputs)?;
 
         // Compute the pairing check:
         // e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1
@@ -138,6 +133,127 @@ impl CircomGroth16Verifier {
             Err(Groth16Error::InvalidProof)
         }
     }
+
+    /// Batch verification against an explicit verification key.
+    ///
+    /// Each proof `i` is weighted by a fresh 128-bit scalar `r_i` drawn from
+    /// the env PRNG, and the combined check is
+    ///
+    /// `prod_i e(-r_i*A_i, B_i) * e(sum r_i*alpha, beta)
+    ///   * e(sum r_i*vk_x_i, gamma) * e(sum r_i*C_i, delta) == 1`
+    ///
+    /// A forged proof passes only if it cancels against the others under
+    /// scalars it cannot predict. When the combined check fails, the proofs
+    /// are re-verified one at a time to report the first failing index.
+    pub(crate) fn verify_batch_with_vk(
+        env: &Env,
+        vk: &VerificationKey,
+        proofs: Vec<Groth16Proof>,
+        public_inputs: Vec<Vec<Bn254Fr>>,
+    ) -> Result<BatchVerification, Groth16Error> {
+        if proofs.is_empty() {
+            return Err(Groth16Error::EmptyBatch);
+        }
+        if proofs.len() != public_inputs.len() {
+            return Err(Groth16Error::MalformedPublicInputs);
+        }
+
+        let bn = env.crypto().bn254();
+        let mut g1_points: Vec<G1Affine> = Vec::new(env);
+        let mut g2_points: Vec<G2Affine> = Vec::new(env);
+        let mut alpha_acc: Option<G1Affine> = None;
+        let mut vk_x_acc: Option<G1Affine> = None;
+        let mut c_acc: Option<G1Affine> = None;
+
+        for (proof, inputs) in proofs.iter().zip(public_inputs.iter()) {
+            let vk_x = prepare_inputs(env, vk, &inputs)?;
+            let r = batch_scalar(env);
+
+            #[allow(clippy::arithmetic_side_effects)]
+            let neg_a = -proof.a;
+            g1_points.push_back(bn.g1_mul(&neg_a, &r));
+            g2_points.push_back(proof.b);
+
+            alpha_acc = Some(accumulate(env, alpha_acc, bn.g1_mul(&vk.alpha, &r)));
+            vk_x_acc = Some(accumulate(env, vk_x_acc, bn.g1_mul(&vk_x, &r)));
+            c_acc = Some(accumulate(env, c_acc, bn.g1_mul(&proof.c, &r)));
+        }
+
+        // The batch is non-empty, so every accumulator has been set.
+        let (Some(alpha_acc), Some(vk_x_acc), Some(c_acc)) = (alpha_acc, vk_x_acc, c_acc) else {
+            return Err(Groth16Error::EmptyBatch);
+        };
+        g1_points.push_back(alpha_acc);
+        g2_points.push_back(vk.beta.clone());
+        g1_points.push_back(vk_x_acc);
+        g2_points.push_back(vk.gamma.clone());
+        g1_points.push_back(c_acc);
+        g2_points.push_back(vk.delta.clone());
+
+        if bn.pairing_check(g1_points, g2_points) {
+            return Ok(BatchVerification::Valid);
+        }
+
+        for (i, (proof, inputs)) in proofs.iter().zip(public_inputs.iter()).enumerate() {
+            if Self::verify_with_vk(env, vk, proof, inputs).is_err() {
+                let index = u32::try_from(i).map_err(|_| Groth16Error::MalformedPublicInputs)?;
+                return Ok(BatchVerification::Invalid(index));
+            }
+        }
+        // Unreachable unless the random combination itself cancelled out.
+        Ok(BatchVerification::Valid)
+    }
+}
+
+/// Compute `vk_x = IC[0] + sum_i pub_inputs[i] * IC[i + 1]`.
+fn prepare_inputs(
+    env: &Env,
+    vk: &VerificationKey,
+    pub_inputs: &Vec<Bn254Fr>,
+) -> Result<G1Affine, Groth16Error> {
+    let bn = env.crypto().bn254();
+
+    if pub_inputs.len().checked_add(1) != Some(vk.ic.len()) {
+        return Err(Groth16Error::MalformedPublicInputs);
+    }
+
+    let mut vk_x = vk.ic.get(0).ok_or(Groth16Error::MalformedPublicInputs)?;
+
+    for i in 0..pub_inputs.len() {
+        let s = pub_inputs
+            .get(i)
+            .ok_or(Groth16Error::MalformedPublicInputs)?;
+        let ic_idx = i
+            .checked_add(1)
+            .ok_or(Groth16Error::MalformedPublicInputs)?;
+        let v = vk
+            .ic
+            .get(ic_idx)
+            .ok_or(Groth16Error::MalformedPublicInputs)?;
+        let prod = bn.g1_mul(&v, &s);
+        vk_x = bn.g1_add(&vk_x, &prod);
+    }
+    Ok(vk_x)
+}
+
+/// Draw a non-zero 128-bit batching scalar from the env PRNG.
+///
+/// 128 bits always fits below the BN254 scalar modulus, so no reduction is
+/// needed; the low bit is forced to one to rule out a zero weight.
+fn batch_scalar(env: &Env) -> Bn254Fr {
+    let hi: u64 = env.prng().gen_range(..);
+    let lo: u64 = env.prng().gen_range(..);
+    let mut buf = [0u8; 32];
+    buf[16..24].copy_from_slice(&hi.to_be_bytes());
+    buf[24..32].copy_from_slice(&(lo | 1).to_be_bytes());
+    Bn254Fr::from_bytes(BytesN::from_array(env, &buf))
+}
+
+fn accumulate(env: &Env, acc: Option<G1Affine>, point: G1Affine) -> G1Affine {
+    match acc {
+        Some(acc) => env.crypto().bn254().g1_add(&acc, &point),
+        None => point,
+    }
 }
 
 #[cfg(test)]
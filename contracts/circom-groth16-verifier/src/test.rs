@@ -107,6 +107,58 @@ fn build_test(
     )
 }
 
+/// Build `count` proofs for the same circuit and verification key, each with
+/// its own public-input vector.
+fn build_batch(
+    env: &Env,
+    count: u64,
+) -> (VerificationKeyBytes, Vec<Groth16Proof>, Vec<Vec<Bn254Fr>>) {
+    let mut rng = seeded_rng();
+    let setup = ElevenInputCircuit {
+        inputs: [ArkFr::from(0u64); 11],
+    };
+    let params = Groth16::<Bn254, CircomReduction>::generate_random_parameters_with_reduction(
+        setup, &mut rng,
+    )
+    .expect("params failed to generate");
+
+    let mut proofs: Vec<Groth16Proof> = Vec::new(env);
+    let mut all_inputs: Vec<Vec<Bn254Fr>> = Vec::new(env);
+    for seed in 0..count {
+        let inputs = [ArkFr::from(seed.saturating_add(100)); 11];
+        let proof = Groth16::<Bn254, CircomReduction>::create_random_proof_with_reduction(
+            ElevenInputCircuit { inputs },
+            &params,
+            &mut rng,
+        )
+        .expect("proof failed");
+        proofs.push_back(groth16_proof_from_ark(env, &proof));
+
+        let mut public_inputs: Vec<Bn254Fr> = Vec::new(env);
+        for value in inputs {
+            public_inputs.push_back(fr_from_ark(env, value));
+        }
+        all_inputs.push_back(public_inputs);
+    }
+
+    let vk_bytes_ext = vk_bytes_from_ark(env, &params.vk);
+    let vk_bytes = VerificationKeyBytes {
+        alpha: vk_bytes_ext.alpha,
+        beta: vk_bytes_ext.beta,
+        gamma: vk_bytes_ext.gamma,
+        delta: vk_bytes_ext.delta,
+        ic: vk_bytes_ext.ic,
+    };
+    (vk_bytes, proofs, all_inputs)
+}
+
+/// Run `f` inside a registered verifier contract frame so the env PRNG is
+/// available to batch verification.
+fn in_contract<T>(env: &Env, f: impl FnOnce() -> T) -> T {
+    let contract_id = env.register(CircomGroth16Verifier, ());
+    env.as_contract(&contract_id, f)
+}
+
 /// Create a test environment that disables snapshot writing under Miri.
 /// Miri's isolation mode blocks filesystem operations, which the Soroban SDK
 /// uses for test snapshots.
@@ -166,3 +218,53 @@ fn groth16_proof_parsing_checks_size() {
         Err(Groth16Error::MalformedProof)
     ));
 }
+
+#[test]
+fn batch_verifies_valid_proofs() {
+    let env = test_env();
+    let (vk_bytes, proofs, public_inputs) = build_batch(&env, 3);
+    let vk = verification_key_from_bytes(&env, &vk_bytes);
+
+    let result = in_contract(&env, || {
+        CircomGroth16Verifier::verify_batch_with_vk(&env, &vk, proofs, public_inputs)
+    });
+
+    assert_eq!(result, Ok(BatchVerification::Valid));
+}
+
+#[test]
+fn batch_reports_failing_proof_index() {
+    let env = test_env();
+    let (vk_bytes, proofs, mut public_inputs) = build_batch(&env, 3);
+    let vk = verification_key_from_bytes(&env, &vk_bytes);
+
+    // Swap in the public inputs of proof 2 for proof 1: both vectors are well
+    // formed, but proof 1 no longer matches its statement.
+    let other = public_inputs.get(2).expect("third input vector");
+    public_inputs.set(1, other);
+
+    let result = in_contract(&env, || {
+        CircomGroth16Verifier::verify_batch_with_vk(&env, &vk, proofs, public_inputs)
+    });
+
+    assert_eq!(result, Ok(BatchVerification::Invalid(1)));
+}
+
+#[test]
+fn batch_rejects_empty_and_mismatched_input() {
+    let env = test_env();
+    let (vk_bytes, proofs, public_inputs) = build_batch(&env, 2);
+    let vk = verification_key_from_bytes(&env, &vk_bytes);
+
+    let empty = in_contract(&env, || {
+        CircomGroth16Verifier::verify_batch_with_vk(&env, &vk, Vec::new(&env), Vec::new(&env))
+    });
+    assert_eq!(empty, Err(Groth16Error::EmptyBatch));
+
+    let mut one_input = public_inputs.clone();
+    one_input.pop_back();
+    let mismatched = in_contract(&env, || {
+        CircomGroth16Verifier::verify_batch_with_vk(&env, &vk, proofs, one_input)
+    });
+    assert_eq!(mismatched, Err(Groth16Error::MalformedPublicInputs));
+}
@@ -3,7 +3,7 @@
 //! `#[contractclient]` generates a caller-side struct and exports nothing, so
 //! these are safe to share (see the crate docs for what is not).
 
-use contract_types::{Groth16Error, Groth16Proof};
+use contract_types::{BatchVerification, Groth16Error, Groth16Proof};
 use soroban_sdk::{Env, U256, Vec, contractclient, crypto::bn254::Bn254Fr};
 
 #[contractclient(crate_path = "soroban_sdk", name = "ASPMembershipClient")]
@@ -23,4 +23,10 @@ pub trait CircomGroth16VerifierInterface {
         proof: Groth16Proof,
         public_inputs: Vec<Bn254Fr>,
     ) -> Result<bool, Groth16Error>;
+
+    fn verify_batch(
+        env: Env,
+        proofs: Vec<Groth16Proof>,
+        public_inputs: Vec<Vec<Bn254Fr>>,
+    ) -> Result<BatchVerification, Groth16Error>;
 }
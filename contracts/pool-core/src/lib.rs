@@ -5,8 +5,9 @@
 //! dead-code-eliminates. Two contract crates therefore cannot depend on each
 //! other for *any* item without dragging in each other's exports and colliding
 //! on identically-named methods. This crate holds the parts that carry no
-//! exports — the Merkle tree, policy flags, external-transaction data, and the
-//! cross-contract client traits — so both contracts can share one copy.
+//! exports — the Merkle tree, policy flags, external-transaction data, the
+//! aggregate pool counters, and the cross-contract client traits — so both
+//! contracts can share one copy.
 //!
 //! Nothing here may declare `#[contract]` or `#[contractimpl]`.
 
@@ -17,9 +18,11 @@ pub mod clients;
 pub mod ext_data;
 pub mod merkle_with_history;
 pub mod policy;
+pub mod stats;
 
 pub use clients::{
     ASPMembershipClient, ASPMembershipInterface, ASPNonMembershipClient, ASPNonMembershipInterface,
     CircomGroth16VerifierClient, CircomGroth16VerifierInterface,
 };
 pub use ext_data::{ExtData, hash_ext_data};
+pub use stats::{PoolConfig, PoolStats};
@@ -257,6 +257,23 @@ impl MerkleTreeWithHistory {
             .ok_or(Error::NotInitialized)
     }
 
+    /// Get the next free leaf index, which is also the number of leaves
+    /// inserted so far.
+    pub fn get_next_index(env: &Env) -> Result<u64, Error> {
+        env.storage()
+            .persistent()
+            .get(&MerkleDataKey::NextIndex)
+            .ok_or(Error::NotInitialized)
+    }
+
+    /// Get the configured number of tree levels.
+    pub fn get_levels(env: &Env) -> Result<u32, Error> {
+        env.storage()
+            .persistent()
+            .get(&MerkleDataKey::Levels)
+            .ok_or(Error::NotInitialized)
+    }
+
     /// Hash two U256 values using Poseidon2 compression
     ///
     /// Computes the Poseidon2 hash of two field elements in compression mode.
@@ -0,0 +1,117 @@
+//! Aggregate pool counters and the read-only views built on them.
+//!
+//! Counters are updated by `transact` so explorers, dashboards and the SDK can
+//! read the shielded supply without replaying every event. Like `amounts`,
+//! these helpers take no contract `Error` type: mutations return `None` on
+//! overflow and each contract maps that to its own `Error::Overflow`.
+//!
+//! Counters start at zero for a pool deployed before they existed, so on such
+//! a pool they only cover activity since the upgrade.
+
+use soroban_sdk::{Address, Env, I256, U256, contracttype};
+
+use crate::merkle_with_history::{Error as MerkleError, MerkleTreeWithHistory};
+
+/// Storage keys for the aggregate counters.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StatsDataKey {
+    /// Sum of all deposited token amounts
+    TotalDeposited,
+    /// Sum of all withdrawn token amounts
+    TotalWithdrawn,
+    /// Number of nullifiers marked spent
+    NullifierCount,
+}
+
+/// Live pool counters returned by `get_stats`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolStats {
+    /// Sum of all deposits, in token base units
+    pub total_deposited: i128,
+    /// Sum of all withdrawals, in token base units
+    pub total_withdrawn: i128,
+    /// Tokens currently held in shielded notes (`deposited - withdrawn`)
+    pub shielded_supply: i128,
+    /// Number of commitments in the tree; also the next leaf index
+    pub next_index: u64,
+    /// Number of spent nullifiers
+    pub nullifier_count: u64,
+}
+
+/// Static pool configuration returned by `get_config`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PoolConfig {
+    /// Token contract used for deposits and withdrawals
+    pub token: Address,
+    /// Groth16 verifier contract
+    pub verifier: Address,
+    /// ASP membership contract
+    pub asp_membership: Address,
+    /// ASP non-membership contract
+    pub asp_non_membership: Address,
+    /// Maximum allowed deposit per transaction
+    pub maximum_deposit_amount: U256,
+    /// Depth of the commitment Merkle tree
+    pub levels: u32,
+    /// ASP policy flag bitset (see `crate::policy`)
+    pub policy_flags: u32,
+}
+
+fn load_i128(env: &Env, key: &StatsDataKey) -> i128 {
+    env.storage().persistent().get(key).unwrap_or(0)
+}
+
+fn load_u64(env: &Env, key: &StatsDataKey) -> u64 {
+    env.storage().persistent().get(key).unwrap_or(0)
+}
+
+/// Record the token flow of one transaction from its signed `ext_amount`:
+/// positive values count as deposits, negative ones as withdrawals.
+///
+/// Returns `None` if the amount does not fit in `i128` or a counter would
+/// overflow.
+pub fn record_ext_amount(env: &Env, ext_amount: &I256) -> Option<()> {
+    let zero = I256::from_i32(env, 0);
+    if *ext_amount > zero {
+        let amount = ext_amount.to_i128()?;
+        let total = load_i128(env, &StatsDataKey::TotalDeposited).checked_add(amount)?;
+        env.storage()
+            .persistent()
+            .set(&StatsDataKey::TotalDeposited, &total);
+    } else if *ext_amount < zero {
+        let amount = zero.sub(ext_amount).to_i128()?;
+        let total = load_i128(env, &StatsDataKey::TotalWithdrawn).checked_add(amount)?;
+        env.storage()
+            .persistent()
+            .set(&StatsDataKey::TotalWithdrawn, &total);
+    }
+    Some(())
+}
+
+/// Add `count` freshly spent nullifiers to the running total.
+pub fn record_nullifiers(env: &Env, count: u32) -> Option<()> {
+    let total = load_u64(env, &StatsDataKey::NullifierCount).checked_add(u64::from(count))?;
+    env.storage()
+        .persistent()
+        .set(&StatsDataKey::NullifierCount, &total);
+    Some(())
+}
+
+/// Assemble the current [`PoolStats`].
+pub fn load_stats(env: &Env) -> Result<PoolStats, MerkleError> {
+    let total_deposited = load_i128(env, &StatsDataKey::TotalDeposited);
+    let total_withdrawn = load_i128(env, &StatsDataKey::TotalWithdrawn);
+    let shielded_supply = total_deposited
+        .checked_sub(total_withdrawn)
+        .ok_or(MerkleError::Overflow)?;
+    Ok(PoolStats {
+        total_deposited,
+        total_withdrawn,
+        shielded_supply,
+        next_index: MerkleTreeWithHistory::get_next_index(env)?,
+        nullifier_count: load_u64(env, &StatsDataKey::NullifierCount),
+    })
+}
@@ -15,9 +15,10 @@
 #![allow(clippy::too_many_arguments)]
 use contract_types::Groth16Proof;
 use pool_core::{
-    ASPMembershipClient, ASPNonMembershipClient, CircomGroth16VerifierClient, amounts,
+    ASPMembershipClient, ASPNonMembershipClient, CircomGroth16VerifierClient, PoolConfig,
+    PoolStats, amounts,
     merkle_with_history::{Error as MerkleError, MerkleTreeWithHistory},
-    policy,
+    policy, stats,
 };
 use soroban_sdk::{
     Address, Bytes, BytesN, Env, I256, U256, Vec, contract, contracterror, contractevent,
@@ -511,6 +512,10 @@ impl PoolContract {
             NewNullifierEvent { nullifier: n }.publish(env);
         }
 
+        // Aggregate counters for `get_stats`
+        stats::record_nullifiers(env, proof.input_nullifiers.len()).ok_or(Error::Overflow)?;
+        stats::record_ext_amount(env, &ext_data.ext_amount).ok_or(Error::Overflow)?;
+
         // 7. Process withdrawal if ext_amount < 0
         let token = Self::get_token(env)?;
         let token_client = TokenClient::new(env, &token);
@@ -594,6 +599,29 @@ impl PoolContract {
             .ok_or(Error::NotInitialized)
     }
 
+    /// Get the pool's static configuration in a single call.
+    ///
+    /// Returns the token, verifier and ASP contract addresses, the maximum
+    /// deposit, the tree depth and the policy flags.
+    pub fn get_config(env: &Env) -> Result<PoolConfig, Error> {
+        Ok(PoolConfig {
+            token: Self::get_token(env)?,
+            verifier: Self::get_verifier(env)?,
+            asp_membership: Self::get_asp_membership(env)?,
+            asp_non_membership: Self::get_asp_non_membership(env)?,
+            maximum_deposit_amount: Self::get_maximum_deposit(env)?,
+            levels: MerkleTreeWithHistory::get_levels(env)?,
+            policy_flags: Self::load_policy_flags(env)?,
+        })
+    }
+
+    /// Get the pool's live counters: deposit and withdrawal totals, the
+    /// resulting shielded supply, the next leaf index and the number of
+    /// spent nullifiers.
+    pub fn get_stats(env: &Env) -> Result<PoolStats, Error> {
+        Ok(stats::load_stats(env)?)
+    }
+
     /// Get the latest root of the Merkle tree that defines the pool
     pub fn get_root(env: &Env) -> Result<U256, Error> {
         Ok(MerkleTreeWithHistory::get_last_root(env)?)
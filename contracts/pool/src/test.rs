@@ -6,6 +6,7 @@ use crate::{
 use asp_membership::{ASPMembership, ASPMembershipClient};
 use asp_non_membership::{ASPNonMembership, ASPNonMembershipClient};
 use circom_groth16_verifier::{CircomGroth16Verifier, Groth16Proof};
+use pool_core::stats;
 use soroban_sdk::{
     Address, Bytes, BytesN, Env, I256, U256, Vec,
     crypto::bn254::{Bn254G1Affine as G1Affine, Bn254G2Affine as G2Affine},
@@ -987,6 +988,77 @@ fn is_spent_true_after_nullifier_marked() {
     assert!(pool.is_spent(&spent));
 }
 
+#[test]
+fn get_config_returns_constructor_values() {
+    let env = test_env();
+    let setup = setup_test_contracts(&env);
+    let max = U256::from_u32(&env, 1000);
+    let flags = policy::ALLOWLIST_BIT | policy::BLOCKLIST_BIT;
+    let pool_id = register_pool(&env, &setup, max.clone(), 5, flags);
+    let pool = PoolContractClient::new(&env, &pool_id);
+
+    let config = pool.get_config();
+
+    assert_eq!(config.token, setup.token);
+    assert_eq!(config.verifier, setup.verifier);
+    assert_eq!(config.asp_membership, setup.asp_membership_address);
+    assert_eq!(config.asp_non_membership, setup.asp_non_membership_address);
+    assert_eq!(config.maximum_deposit_amount, max);
+    assert_eq!(config.levels, 5);
+    assert_eq!(config.policy_flags, flags);
+}
+
+#[test]
+fn get_stats_tracks_flows_and_tree_growth() {
+    let env = test_env();
+    let setup = setup_test_contracts(&env);
+    let pool_id = register_pool(
+        &env,
+        &setup,
+        U256::from_u32(&env, 1000),
+        5,
+        policy::ALLOWLIST_BIT | policy::BLOCKLIST_BIT,
+    );
+    let pool = PoolContractClient::new(&env, &pool_id);
+
+    let fresh = pool.get_stats();
+    assert_eq!(fresh.total_deposited, 0);
+    assert_eq!(fresh.shielded_supply, 0);
+    assert_eq!(fresh.next_index, 0);
+    assert_eq!(fresh.nullifier_count, 0);
+
+    // Replay the bookkeeping `internal_transact` does for a 500 deposit
+    // followed by a 2-input 200 withdrawal.
+    env.as_contract(&pool_id, || {
+        stats::record_ext_amount(&env, &I256::from_i32(&env, 500))
+            .unwrap_or_else(|| panic!("deposit should be recorded"));
+        MerkleTreeWithHistory::insert_two_leaves(
+            &env,
+            U256::from_u32(&env, 1),
+            U256::from_u32(&env, 2),
+        )
+        .unwrap_or_else(|err| panic!("expected leaf insertion to succeed: {err:?}"));
+
+        stats::record_nullifiers(&env, 2)
+            .unwrap_or_else(|| panic!("nullifiers should be recorded"));
+        stats::record_ext_amount(&env, &I256::from_i32(&env, -200))
+            .unwrap_or_else(|| panic!("withdrawal should be recorded"));
+        MerkleTreeWithHistory::insert_two_leaves(
+            &env,
+            U256::from_u32(&env, 3),
+            U256::from_u32(&env, 4),
+        )
+        .unwrap_or_else(|err| panic!("expected leaf insertion to succeed: {err:?}"));
+    });
+
+    let after = pool.get_stats();
+    assert_eq!(after.total_deposited, 500);
+    assert_eq!(after.total_withdrawn, 200);
+    assert_eq!(after.shielded_supply, 300);
+    assert_eq!(after.next_index, 4);
+    assert_eq!(after.nullifier_count, 2);
+}
+
 #[test]
 fn test_pool_events_exact_shapes() {
     use crate::pool::{NewCommitmentEvent, NewNullifierEvent};
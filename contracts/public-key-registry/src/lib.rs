@@ -1,6 +1,47 @@
 #![no_std]
 
-use soroban_sdk::{Address, Bytes, Env, contract, contractevent, contractimpl, contracttype};
+use contract_types::{Groth16Error, Groth16Proof};
+use soroban_sdk::{
+    Address, Bytes, BytesN, Env, U256, Vec, contract, contractclient, contracterror, contractevent,
+    contractimpl, contracttype, crypto::bn254::Bn254Fr, xdr::ToXdr,
+};
+use soroban_utils::constants::bn256_modulus;
+
+/// Length in bytes of both registered public keys
+const KEY_LEN: u32 = 32;
+
+/// Contract error types
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    /// Encryption key is not 32 bytes long
+    InvalidEncryptionKeyLength = 1,
+    /// Note key is not 32 bytes long
+    InvalidNoteKeyLength = 2,
+    /// Owner has no active registration
+    NotRegistered = 3,
+    /// Registration version counter overflowed
+    Overflow = 4,
+    /// Note key proof of possession did not verify
+    InvalidProof = 5,
+    /// Note key is not a canonical BN254 field element
+    NonCanonicalNoteKey = 6,
+    /// Registry has a `notePossession` verifier but no proof was given
+    MissingProof = 7,
+    /// The contract has not been yet initialized
+    NotInitialized = 8,
+}
+
+/// Verifier for the `notePossession` circuit.
+#[contractclient(name = "NotePossessionVerifierClient")]
+pub trait NotePossessionVerifier {
+    fn verify(
+        env: Env,
+        proof: Groth16Proof,
+        public_inputs: Vec<Bn254Fr>,
+    ) -> Result<bool, Groth16Error>;
+}
 
 /// User account registration data
 ///
@@ -9,6 +50,7 @@ use soroban_sdk::{Address, Bytes, Env, contract, contractevent, contractimpl, co
 /// Not required to interact with the pool. But facilitates in-pool transfers
 /// via events. As parties can learn about each other public key.
 #[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Account {
     /// Owner address of the account
     pub owner: Address,
@@ -34,12 +76,48 @@ pub struct PublicKeyEvent {
     pub encryption_key: Bytes,
     /// BN254 note public key
     pub note_key: Bytes,
+    /// Per-owner registration version, starting at 1
+    pub version: u32,
+}
+
+/// Event emitted when a user revokes their registered public keys
+///
+/// Senders must stop encrypting notes to the revoked keys. `version` is the
+/// version of the registration that was removed.
+#[contractevent]
+#[derive(Clone)]
+pub struct PublicKeyRevokedEvent {
+    /// Address of the account owner
+    #[topic]
+    pub owner: Address,
+    /// Version of the revoked registration
+    pub version: u32,
+}
+
+/// Data a note key proof of possession is bound to.
+///
+/// Hashed into the circuit's public `binding` input so a proof cannot be
+/// replayed for another owner or encryption key.
+#[contracttype]
+#[derive(Clone)]
+pub struct PossessionBinding {
+    /// Owner address of the account
+    pub owner: Address,
+    /// X25519 encryption public key registered alongside the note key
+    pub encryption_key: Bytes,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 enum DataKey {
+    /// Administrator address allowed to set the verifier
+    Admin,
+    /// `notePossession` Groth16 verifier contract
+    Verifier,
     Registration(Address),
+    /// Last version issued to an owner. Kept across `unregister` so versions
+    /// never repeat.
+    Version(Address),
 }
 
 #[contracttype]
@@ -47,6 +125,27 @@ enum DataKey {
 struct Registration {
     encryption_key: Bytes,
     note_key: Bytes,
+    version: u32,
+}
+
+/// Hash a [`PossessionBinding`] into the `binding` public input.
+///
+/// Serializes the binding to XDR, hashes it with Keccak256 and reduces the
+/// result modulo the BN256 field size.
+pub fn possession_binding(env: &Env, owner: &Address, encryption_key: &Bytes) -> U256 {
+    let payload = PossessionBinding {
+        owner: owner.clone(),
+        encryption_key: encryption_key.clone(),
+    }
+    .to_xdr(env);
+    let digest: BytesN<32> = env.crypto().keccak256(&payload).into();
+    U256::from_be_bytes(env, &Bytes::from(digest)).rem_euclid(&bn256_modulus(env))
+}
+
+fn u256_to_fr(env: &Env, value: &U256) -> Bn254Fr {
+    let mut buf = [0u8; 32];
+    value.to_be_bytes().copy_into_slice(&mut buf);
+    Bn254Fr::from_bytes(BytesN::from_array(env, &buf))
 }
 
 /// Public key registry contract.
@@ -58,34 +157,208 @@ pub struct PublicKeyRegistry;
 
 #[contractimpl]
 impl PublicKeyRegistry {
+    /// Constructor: set the admin and the `notePossession` verifier contract.
+    ///
+    /// Without a verifier, registrations are accepted without a proof of
+    /// possession until the admin calls [`Self::set_verifier`]. Only deploy
+    /// that way until the `notePossession` keys are available to every
+    /// client that registers.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `admin` - Address allowed to set the verifier later
+    /// * `verifier` - `notePossession` Groth16 verifier contract, if any
+    pub fn __constructor(env: Env, admin: Address, verifier: Option<Address>) {
+        env.storage().persistent().set(&DataKey::Admin, &admin);
+        if let Some(verifier) = verifier {
+            env.storage().instance().set(&DataKey::Verifier, &verifier);
+        }
+    }
+
+    /// Update the contract administrator
+    ///
+    /// Changes the admin address to a new address. Only the current admin
+    /// can call this function.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `new_admin` - Address of the new administrator
+    pub fn update_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        if !env.storage().persistent().has(&DataKey::Admin) {
+            return Err(Error::NotInitialized);
+        }
+        soroban_utils::update_admin(&env, &DataKey::Admin, &new_admin);
+        Ok(())
+    }
+
+    /// Set the `notePossession` verifier contract. Only the admin can call
+    /// this.
+    ///
+    /// From then on every new or rotated registration must carry a proof of
+    /// possession. The verifier can be replaced, e.g. after a new trusted
+    /// setup, but never removed.
+    ///
+    /// # Arguments
+    /// * `env` - The Soroban environment
+    /// * `verifier` - `notePossession` Groth16 verifier contract
+    pub fn set_verifier(env: Env, verifier: Address) -> Result<(), Error> {
+        let admin: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)?;
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::Verifier, &verifier);
+        Ok(())
+    }
+
     /// Register a user's public encryption and note keys.
-    pub fn register(env: Env, account: Account) {
+    ///
+    /// `proof` is a `notePossession` Groth16 proof that the owner knows the
+    /// private key behind `note_key`, bound to the owner and encryption key
+    /// (see [`possession_binding`]). Without it anyone could publish a
+    /// victim's note key under their own address. It is required when the
+    /// registry has a verifier and ignored otherwise.
+    ///
+    /// Registering different keys rotates the registration and bumps the
+    /// owner's version. Re-registering the current keys is a no-op.
+    pub fn register(env: Env, account: Account, proof: Option<Groth16Proof>) -> Result<(), Error> {
         account.owner.require_auth();
-        assert_eq!(account.encryption_key.len(), 32);
-        assert_eq!(account.note_key.len(), 32);
+        if account.encryption_key.len() != KEY_LEN {
+            return Err(Error::InvalidEncryptionKeyLength);
+        }
+        if account.note_key.len() != KEY_LEN {
+            return Err(Error::InvalidNoteKeyLength);
+        }
 
         let key = DataKey::Registration(account.owner.clone());
-        let next = Registration {
-            encryption_key: account.encryption_key.clone(),
-            note_key: account.note_key.clone(),
-        };
-
-        if env
+        if let Some(current) = env
             .storage()
             .persistent()
             .get::<DataKey, Registration>(&key)
-            == Some(next.clone())
+            && current.encryption_key == account.encryption_key
+            && current.note_key == account.note_key
         {
-            return;
+            return Ok(());
         }
 
-        env.storage().persistent().set(&key, &next);
+        Self::verify_possession(&env, &account, proof)?;
+
+        let version_key = DataKey::Version(account.owner.clone());
+        let version = env
+            .storage()
+            .persistent()
+            .get::<DataKey, u32>(&version_key)
+            .unwrap_or(0)
+            .checked_add(1)
+            .ok_or(Error::Overflow)?;
+
+        env.storage().persistent().set(&version_key, &version);
+        env.storage().persistent().set(
+            &key,
+            &Registration {
+                encryption_key: account.encryption_key.clone(),
+                note_key: account.note_key.clone(),
+                version,
+            },
+        );
         PublicKeyEvent {
             owner: account.owner,
             encryption_key: account.encryption_key,
             note_key: account.note_key,
+            version,
         }
         .publish(&env);
+        Ok(())
+    }
+
+    /// Remove the owner's registration and emit a revocation event.
+    ///
+    /// Used after a key compromise or when leaving the pool. The owner can
+    /// register again later; the new registration gets a higher version.
+    pub fn unregister(env: Env, owner: Address) -> Result<(), Error> {
+        owner.require_auth();
+        let key = DataKey::Registration(owner.clone());
+        let registration: Registration = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(Error::NotRegistered)?;
+
+        env.storage().persistent().remove(&key);
+        PublicKeyRevokedEvent {
+            owner,
+            version: registration.version,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Return the `notePossession` verifier contract address, if
+    /// registrations must prove possession of the note key.
+    pub fn get_verifier(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Verifier)
+    }
+
+    /// Return the owner's active registration.
+    pub fn get_account(env: Env, owner: Address) -> Result<Account, Error> {
+        let registration: Registration = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Registration(owner.clone()))
+            .ok_or(Error::NotRegistered)?;
+        Ok(Account {
+            owner,
+            encryption_key: registration.encryption_key,
+            note_key: registration.note_key,
+        })
+    }
+
+    /// Return the last registration version issued to the owner, or 0 if
+    /// they never registered.
+    pub fn get_version(env: Env, owner: Address) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Version(owner))
+            .unwrap_or(0)
+    }
+}
+
+impl PublicKeyRegistry {
+    /// Verify the `notePossession` proof for `account`, if the registry has a
+    /// verifier.
+    ///
+    /// Public inputs, in circuit order: `publicKey` (the note key, stored
+    /// little-endian like every SDK field element) and `binding`.
+    fn verify_possession(
+        env: &Env,
+        account: &Account,
+        proof: Option<Groth16Proof>,
+    ) -> Result<(), Error> {
+        let mut note_key_be = [0u8; 32];
+        account.note_key.copy_into_slice(&mut note_key_be);
+        note_key_be.reverse();
+        let note_key = U256::from_be_bytes(env, &Bytes::from_array(env, &note_key_be));
+        if note_key >= bn256_modulus(env) {
+            return Err(Error::NonCanonicalNoteKey);
+        }
+        let Some(verifier) = Self::get_verifier(env.clone()) else {
+            return Ok(());
+        };
+        let proof = proof.ok_or(Error::MissingProof)?;
+        let binding = possession_binding(env, &account.owner, &account.encryption_key);
+
+        let mut public_inputs: Vec<Bn254Fr> = Vec::new(env);
+        public_inputs.push_back(u256_to_fr(env, &note_key));
+        public_inputs.push_back(u256_to_fr(env, &binding));
+
+        let client = NotePossessionVerifierClient::new(env, &verifier);
+        // The circom verifier returns `Groth16Error::InvalidProof` for a bad
+        // proof rather than `false`; map every rejection to our typed error.
+        match client.try_verify(&proof, &public_inputs) {
+            Ok(Ok(true)) => Ok(()),
+            _ => Err(Error::InvalidProof),
+        }
     }
 }
 
@@ -1,6 +1,7 @@
 use super::*;
 use soroban_sdk::{
-    Address, Bytes, Env,
+    Address, Bytes, Env, contract, contractimpl, contracttype,
+    crypto::bn254::{Bn254G1Affine as G1Affine, Bn254G2Affine as G2Affine},
     testutils::{Address as _, Events as _},
 };
 
@@ -18,6 +19,87 @@ fn test_env() -> Env {
     }
 }
 
+#[contracttype]
+enum MockVerifierKey {
+    Accept,
+    LastInputs,
+}
+
+/// Stand-in for the `notePossession` verifier: accepts or rejects every proof
+/// as configured and records the public inputs it was called with. Rejects
+/// with `Groth16Error::InvalidProof`, like the circom verifier.
+#[contract]
+struct MockVerifier;
+
+#[contractimpl]
+impl MockVerifier {
+    pub fn __constructor(env: Env, accept: bool) {
+        env.storage()
+            .instance()
+            .set(&MockVerifierKey::Accept, &accept);
+    }
+
+    pub fn verify(
+        env: Env,
+        _proof: Groth16Proof,
+        public_inputs: Vec<Bn254Fr>,
+    ) -> Result<bool, Groth16Error> {
+        env.storage()
+            .instance()
+            .set(&MockVerifierKey::LastInputs, &public_inputs);
+        if env
+            .storage()
+            .instance()
+            .get(&MockVerifierKey::Accept)
+            .unwrap_or(false)
+        {
+            Ok(true)
+        } else {
+            Err(Groth16Error::InvalidProof)
+        }
+    }
+}
+
+struct Setup {
+    env: Env,
+    contract_id: Address,
+    verifier: Address,
+}
+
+impl Setup {
+    fn new(accept: bool) -> Self {
+        let env = test_env();
+        let verifier = env.register(MockVerifier, (accept,));
+        let admin = Address::generate(&env);
+        let contract_id = env.register(PublicKeyRegistry, (admin, Some(verifier.clone())));
+        Self {
+            env,
+            contract_id,
+            verifier,
+        }
+    }
+
+    fn client(&self) -> PublicKeyRegistryClient<'_> {
+        PublicKeyRegistryClient::new(&self.env, &self.contract_id)
+    }
+}
+
+fn mock_proof(env: &Env) -> Groth16Proof {
+    let mut g1 = [0u8; 64];
+    g1[31] = 1;
+    g1[63] = 2;
+    let mut g2 = [0u8; 128];
+    g2[31] = 1;
+    g2[63] = 1;
+    g2[95] = 1;
+    g2[127] = 1;
+    Groth16Proof {
+        a: G1Affine::from_array(env, &g1),
+        b: G2Affine::from_array(env, &g2),
+        c: G1Affine::from_array(env, &g1),
+    }
+}
+
 fn account(env: &Env, owner: Address, enc_fill: u8, note_fill: u8) -> Account {
     Account {
         owner,
@@ -28,16 +110,17 @@ fn account(env: &Env, owner: Address, enc_fill: u8, note_fill: u8) -> Account {
 
 #[test]
 fn register_saves_registration() {
-    let env = test_env();
-    let contract_id = env.register(PublicKeyRegistry, ());
-    let client = PublicKeyRegistryClient::new(&env, &contract_id);
-    let owner = Address::generate(&env);
-    let account = account(&env, owner.clone(), 0x11, 0x22);
+    let setup = Setup::new(true);
+    let (env, contract_id) = (&setup.env, &setup.contract_id);
+    let client = setup.client();
+    let proof = Some(mock_proof(env));
+    let owner = Address::generate(env);
+    let account = account(env, owner.clone(), 0x11, 0x22);
 
     env.mock_all_auths();
-    client.register(&account);
+    client.register(&account, &proof);
 
-    let stored: Registration = env.as_contract(&contract_id, || {
+    let stored: Registration = env.as_contract(contract_id, || {
         env.storage()
             .persistent()
             .get(&DataKey::Registration(owner.clone()))
@@ -49,19 +132,20 @@ fn register_saves_registration() {
 
 #[test]
 fn duplicate_registration_is_noop() {
-    let env = test_env();
-    let contract_id = env.register(PublicKeyRegistry, ());
-    let client = PublicKeyRegistryClient::new(&env, &contract_id);
-    let owner = Address::generate(&env);
-    let account = account(&env, owner.clone(), 0x11, 0x22);
+    let setup = Setup::new(true);
+    let (env, contract_id) = (&setup.env, &setup.contract_id);
+    let client = setup.client();
+    let proof = Some(mock_proof(env));
+    let owner = Address::generate(env);
+    let account = account(env, owner.clone(), 0x11, 0x22);
 
     env.mock_all_auths();
-    client.register(&account);
+    client.register(&account, &proof);
     let events_after_first = env.events().all().events().len();
-    client.register(&account);
+    client.register(&account, &proof);
     let events_after_second = env.events().all().events().len();
 
-    let stored: Registration = env.as_contract(&contract_id, || {
+    let stored: Registration = env.as_contract(contract_id, || {
         env.storage()
             .persistent()
             .get(&DataKey::Registration(owner.clone()))
@@ -75,20 +159,21 @@ fn duplicate_registration_is_noop() {
 
 #[test]
 fn key_rotation_overwrites_registration() {
-    let env = test_env();
-    let contract_id = env.register(PublicKeyRegistry, ());
-    let client = PublicKeyRegistryClient::new(&env, &contract_id);
-    let owner = Address::generate(&env);
-    let initial = account(&env, owner.clone(), 0x11, 0x22);
-    let rotated = account(&env, owner.clone(), 0x33, 0x44);
+    let setup = Setup::new(true);
+    let (env, contract_id) = (&setup.env, &setup.contract_id);
+    let client = setup.client();
+    let proof = Some(mock_proof(env));
+    let owner = Address::generate(env);
+    let initial = account(env, owner.clone(), 0x11, 0x22);
+    let rotated = account(env, owner.clone(), 0x33, 0x24);
 
     env.mock_all_auths();
-    client.register(&initial);
+    client.register(&initial, &proof);
     let first_events = env.events().all().events().len();
-    client.register(&rotated);
+    client.register(&rotated, &proof);
     let second_events = env.events().all().events().len();
 
-    let stored: Registration = env.as_contract(&contract_id, || {
+    let stored: Registration = env.as_contract(contract_id, || {
         env.storage()
             .persistent()
             .get(&DataKey::Registration(owner.clone()))
@@ -98,65 +183,75 @@ fn key_rotation_overwrites_registration() {
     assert_eq!(second_events, 1);
     assert_eq!(stored.encryption_key, rotated.encryption_key);
     assert_eq!(stored.note_key, rotated.note_key);
+    assert_eq!(stored.version, 2);
+    assert_eq!(client.get_version(&owner), 2);
 }
 
 #[test]
 #[should_panic(expected = "Error(Auth, InvalidAction)")]
 fn register_requires_owner_auth() {
-    let env = test_env();
-    let contract_id = env.register(PublicKeyRegistry, ());
-    let client = PublicKeyRegistryClient::new(&env, &contract_id);
-    let owner = Address::generate(&env);
-    let account = account(&env, owner, 0x11, 0x22);
+    let setup = Setup::new(true);
+    let env = &setup.env;
+    let client = setup.client();
+    let proof = Some(mock_proof(env));
+    let owner = Address::generate(env);
+    let account = account(env, owner, 0x11, 0x22);
 
-    client.register(&account);
+    client.register(&account, &proof);
 }
 
 #[test]
-#[should_panic]
 fn register_rejects_short_encryption_key() {
-    let env = test_env();
-    let contract_id = env.register(PublicKeyRegistry, ());
-    let client = PublicKeyRegistryClient::new(&env, &contract_id);
-    let owner = Address::generate(&env);
+    let setup = Setup::new(true);
+    let env = &setup.env;
+    let client = setup.client();
+    let proof = Some(mock_proof(env));
+    let owner = Address::generate(env);
     let account = Account {
         owner,
-        encryption_key: Bytes::from_slice(&env, &[0x11; 31]),
-        note_key: Bytes::from_array(&env, &[0x22; 32]),
+        encryption_key: Bytes::from_slice(env, &[0x11; 31]),
+        note_key: Bytes::from_array(env, &[0x22; 32]),
     };
 
     env.mock_all_auths();
-    client.register(&account);
+    assert_eq!(
+        client.try_register(&account, &proof),
+        Err(Ok(Error::InvalidEncryptionKeyLength))
+    );
 }
 
 #[test]
-#[should_panic]
 fn register_rejects_short_note_key() {
-    let env = test_env();
-    let contract_id = env.register(PublicKeyRegistry, ());
-    let client = PublicKeyRegistryClient::new(&env, &contract_id);
-    let owner = Address::generate(&env);
+    let setup = Setup::new(true);
+    let env = &setup.env;
+    let client = setup.client();
+    let proof = Some(mock_proof(env));
+    let owner = Address::generate(env);
     let account = Account {
         owner,
-        encryption_key: Bytes::from_array(&env, &[0x11; 32]),
-        note_key: Bytes::from_slice(&env, &[0x22; 31]),
+        encryption_key: Bytes::from_array(env, &[0x11; 32]),
+        note_key: Bytes::from_slice(env, &[0x22; 31]),
     };
 
     env.mock_all_auths();
-    client.register(&account);
+    assert_eq!(
+        client.try_register(&account, &proof),
+        Err(Ok(Error::InvalidNoteKeyLength))
+    );
 }
 
 #[test]
 fn test_public_key_event_exact_shape() {
     use soroban_sdk::events::Event;
-    let env = test_env();
-    let contract_id = env.register(PublicKeyRegistry, ());
-    let client = PublicKeyRegistryClient::new(&env, &contract_id);
-    let owner = Address::generate(&env);
-    let account = account(&env, owner.clone(), 0x11, 0x22);
+    let setup = Setup::new(true);
+    let (env, contract_id) = (&setup.env, &setup.contract_id);
+    let client = setup.client();
+    let proof = Some(mock_proof(env));
+    let owner = Address::generate(env);
+    let account = account(env, owner.clone(), 0x11, 0x22);
 
     env.mock_all_auths();
-    client.register(&account);
+    client.register(&account, &proof);
 
     let events = env.events().all();
     assert_eq!(events.events().len(), 1);
@@ -165,7 +260,219 @@ fn test_public_key_event_exact_shape() {
         owner,
         encryption_key: account.encryption_key,
         note_key: account.note_key,
+        version: 1,
+    }
+    .to_xdr(env, contract_id);
+    assert_eq!(events.events()[0], expected);
+}
+
+#[test]
+fn unregister_removes_registration_and_emits_revocation() {
+    use soroban_sdk::events::Event;
+    let setup = Setup::new(true);
+    let (env, contract_id) = (&setup.env, &setup.contract_id);
+    let client = setup.client();
+    let proof = Some(mock_proof(env));
+    let owner = Address::generate(env);
+    let account = account(env, owner.clone(), 0x11, 0x22);
+
+    env.mock_all_auths();
+    client.register(&account, &proof);
+    assert_eq!(client.get_account(&owner), account);
+    client.unregister(&owner);
+
+    let events = env.events().all();
+    assert_eq!(events.events().len(), 1);
+    let expected = PublicKeyRevokedEvent {
+        owner: owner.clone(),
+        version: 1,
     }
-    .to_xdr(&env, &contract_id);
+    .to_xdr(env, contract_id);
     assert_eq!(events.events()[0], expected);
+    assert_eq!(
+        client.try_get_account(&owner),
+        Err(Ok(Error::NotRegistered))
+    );
+    // The version survives so a later registration cannot reuse it.
+    assert_eq!(client.get_version(&owner), 1);
+}
+
+#[test]
+fn unregister_without_registration_fails() {
+    let setup = Setup::new(true);
+    let env = &setup.env;
+    let client = setup.client();
+    let owner = Address::generate(env);
+
+    env.mock_all_auths();
+    assert_eq!(client.try_unregister(&owner), Err(Ok(Error::NotRegistered)));
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn unregister_requires_owner_auth() {
+    let setup = Setup::new(true);
+    let env = &setup.env;
+    let client = setup.client();
+    let proof = Some(mock_proof(env));
+    let owner = Address::generate(env);
+
+    env.mock_all_auths();
+    client.register(&account(env, owner.clone(), 0x11, 0x22), &proof);
+    env.set_auths(&[]);
+    client.unregister(&owner);
+}
+
+#[test]
+fn reregister_after_revocation_bumps_version() {
+    let setup = Setup::new(true);
+    let (env, contract_id) = (&setup.env, &setup.contract_id);
+    let client = setup.client();
+    let proof = Some(mock_proof(env));
+    let owner = Address::generate(env);
+    let account = account(env, owner.clone(), 0x11, 0x22);
+
+    env.mock_all_auths();
+    client.register(&account, &proof);
+    client.unregister(&owner);
+    client.register(&account, &proof);
+
+    let stored: Registration = env.as_contract(contract_id, || {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Registration(owner.clone()))
+            .expect("registration should be stored")
+    });
+    assert_eq!(stored.version, 2);
+    assert_eq!(env.events().all().events().len(), 1);
+}
+
+#[test]
+fn register_rejects_invalid_possession_proof() {
+    let setup = Setup::new(false);
+    let env = &setup.env;
+    let client = setup.client();
+    let owner = Address::generate(env);
+    let account = account(env, owner.clone(), 0x11, 0x22);
+
+    env.mock_all_auths();
+    assert_eq!(
+        client.try_register(&account, &Some(mock_proof(env))),
+        Err(Ok(Error::InvalidProof))
+    );
+    assert_eq!(client.get_version(&owner), 0);
+    assert_eq!(env.events().all().events().len(), 0);
+}
+
+#[test]
+fn register_binds_proof_to_note_key_and_owner() {
+    let setup = Setup::new(true);
+    let env = &setup.env;
+    let client = setup.client();
+    let owner = Address::generate(env);
+    let account = account(env, owner.clone(), 0x11, 0x22);
+
+    env.mock_all_auths();
+    client.register(&account, &Some(mock_proof(env)));
+
+    let inputs: Vec<Bn254Fr> = env.as_contract(&setup.verifier, || {
+        env.storage()
+            .instance()
+            .get(&MockVerifierKey::LastInputs)
+            .expect("verifier should have been called")
+    });
+    // Note keys are little-endian field elements; the verifier takes big-endian.
+    let mut note_key_be = [0x22u8; 32];
+    note_key_be.reverse();
+    let binding = possession_binding(env, &owner, &account.encryption_key);
+    let mut expected: Vec<Bn254Fr> = Vec::new(env);
+    expected.push_back(Bn254Fr::from_bytes(BytesN::from_array(env, &note_key_be)));
+    expected.push_back(u256_to_fr(env, &binding));
+    assert_eq!(inputs, expected);
+
+    // The binding changes with either the owner or the encryption key, so a
+    // proof cannot be replayed for another registration.
+    let other_owner = Address::generate(env);
+    assert_ne!(
+        possession_binding(env, &other_owner, &account.encryption_key),
+        binding
+    );
+    assert_ne!(
+        possession_binding(env, &owner, &Bytes::from_array(env, &[0x33; 32])),
+        binding
+    );
+}
+
+#[test]
+fn register_rejects_non_canonical_note_key() {
+    let setup = Setup::new(true);
+    let env = &setup.env;
+    let client = setup.client();
+    let owner = Address::generate(env);
+    let account = account(env, owner, 0x11, 0xff);
+
+    env.mock_all_auths();
+    assert_eq!(
+        client.try_register(&account, &Some(mock_proof(env))),
+        Err(Ok(Error::NonCanonicalNoteKey))
+    );
+}
+
+#[test]
+fn register_requires_proof_when_verifier_is_set() {
+    let setup = Setup::new(true);
+    let env = &setup.env;
+    let client = setup.client();
+    let owner = Address::generate(env);
+    let account = account(env, owner.clone(), 0x11, 0x22);
+
+    env.mock_all_auths();
+    assert_eq!(client.get_verifier(), Some(setup.verifier.clone()));
+    assert_eq!(
+        client.try_register(&account, &None),
+        Err(Ok(Error::MissingProof))
+    );
+    assert_eq!(client.get_version(&owner), 0);
+}
+
+#[test]
+fn register_without_verifier_skips_possession_proof() {
+    let env = test_env();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(PublicKeyRegistry, (admin, None::<Address>));
+    let client = PublicKeyRegistryClient::new(&env, &contract_id);
+    let owner = Address::generate(&env);
+    let account = account(&env, owner.clone(), 0x11, 0x22);
+
+    env.mock_all_auths();
+    assert_eq!(client.get_verifier(), None);
+    client.register(&account, &None);
+    assert_eq!(client.get_account(&owner), account);
+    assert_eq!(client.get_version(&owner), 1);
+}
+
+#[test]
+fn set_verifier_enforces_possession_proofs() {
+    let env = test_env();
+    let admin = Address::generate(&env);
+    let contract_id = env.register(PublicKeyRegistry, (admin, None::<Address>));
+    let client = PublicKeyRegistryClient::new(&env, &contract_id);
+    let verifier = env.register(MockVerifier, (true,));
+
+    // Only the admin may set the verifier.
+    assert!(client.try_set_verifier(&verifier).is_err());
+    assert_eq!(client.get_verifier(), None);
+
+    env.mock_all_auths();
+    client.set_verifier(&verifier);
+    assert_eq!(client.get_verifier(), Some(verifier));
+
+    let owner = Address::generate(&env);
+    let account = account(&env, owner.clone(), 0x11, 0x22);
+    assert_eq!(
+        client.try_register(&account, &None),
+        Err(Ok(Error::MissingProof))
+    );
+    client.register(&account, &Some(mock_proof(&env)));
+    assert_eq!(client.get_version(&owner), 1);
 }
@@ -16,6 +16,21 @@ pub enum Groth16Error {
     MalformedPublicInputs = 1,
     /// The proof bytes are malformed.
     MalformedProof = 2,
+    /// A batch verification was requested with no proofs.
+    EmptyBatch = 3,
+}
+
+/// Outcome of a batch Groth16 verification.
+///
+/// A batch that fails the combined pairing check is re-checked proof by proof
+/// so the caller learns which entry was rejected.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BatchVerification {
+    /// Every proof in the batch verified.
+    Valid,
+    /// The proof at this index (the first one found) did not verify.
+    Invalid(u32),
 }
 
 /// Groth16 verification key for BN254 curve (byte-oriented).
@@ -5,6 +5,7 @@
 set -euo pipefail
 
 die() { echo "deploy.sh: $*" >&2; exit 1; }
+warn() { echo "deploy.sh: warning: $*" >&2; }
 need() { command -v "$1" >/dev/null 2>&1 || die "missing '$1'"; }
 step() { echo "==> $*" >&2; }
 
@@ -396,6 +397,23 @@ build_verifier_wasm_for_suffix() {
   [[ -z "$tmp_vk" ]] || rm -f "$tmp_vk"
 }
 
+# Verifier for the public-key registry's note-key proof of possession.
+# Without notePossession keys the registry is deployed with no verifier and
+# accepts registrations without a proof until the admin calls set_verifier.
+NOTE_POSSESSION_VERIFIER_WASM_NAME="circom_groth16_verifier_note_possession.wasm"
+NOTE_POSSESSION_VK="$ROOT_DIR/deployments/$NETWORK/circuit_keys/notePossession_vk.json"
+
+build_note_possession_verifier_wasm() {
+  local vk_path="$NOTE_POSSESSION_VK"
+  if [[ ! -f "$vk_path" ]]; then
+    warn "VK not found for notePossession: $vk_path; the registry will not require proofs"
+    return 0
+  fi
+  step "building note-possession verifier WASM from $vk_path"
+  "$SCRIPT_DIR/../../scripts/build-verifier-with-vk.sh" \
+    "$vk_path" --out-dir "$WASM_DIR" --wasm-name "$NOTE_POSSESSION_VERIFIER_WASM_NAME"
+}
+
 step "build contracts"
 mkdir -p "$WASM_DIR"
 for pkg in asp-membership asp-non-membership public-key-registry pool; do
@@ -410,6 +428,7 @@ if [[ "$SKIP_INIT" != "true" ]]; then
     build_verifier_wasm_for_suffix "${UNIQUE_POLICY_SUFFIXES[$_u_i]}"
     _u_i=$((_u_i + 1))
   done
+  build_note_possession_verifier_wasm
 fi
 
 ASP_MEMBERSHIP_WASM="$WASM_DIR/asp_membership.wasm"
@@ -532,8 +551,27 @@ else
   done
 fi
 
+# The registry constructor runs on deploy, so it gets its verifier in both
+# modes; --skip-init needs the note-possession verifier WASM prebuilt, like the
+# pool verifier.
+NOTE_POSSESSION_VERIFIER_ID=""
+note_possession_verifier_wasm="$WASM_DIR/$NOTE_POSSESSION_VERIFIER_WASM_NAME"
+if [[ -f "$note_possession_verifier_wasm" ]]; then
+  step "deploy circom-groth16-verifier (note possession)"
+  NOTE_POSSESSION_VERIFIER_ID="$(deploy_contract circom-groth16-verifier "$note_possession_verifier_wasm")"
+elif [[ -f "$NOTE_POSSESSION_VK" ]]; then
+  die "missing wasm: $note_possession_verifier_wasm (run build-verifier-with-vk.sh or deploy without --skip-init)"
+fi
+
 step "deploy public-key-registry"
-PUBLIC_KEY_REGISTRY_ID="$(deploy_contract public-key-registry "$PUBLIC_KEY_REGISTRY_WASM")"
+if [[ -n "$NOTE_POSSESSION_VERIFIER_ID" ]]; then
+  PUBLIC_KEY_REGISTRY_ID="$(deploy_contract public-key-registry "$PUBLIC_KEY_REGISTRY_WASM" \
+    --admin "$ADMIN_ADDR" --verifier "$NOTE_POSSESSION_VERIFIER_ID")"
+else
+  PUBLIC_KEY_REGISTRY_ID="$(deploy_contract public-key-registry "$PUBLIC_KEY_REGISTRY_WASM" \
+    --admin "$ADMIN_ADDR")"
+  warn "public-key-registry deployed without a notePossession verifier; registrations are not proof-checked until the admin runs: stellar contract invoke --id $PUBLIC_KEY_REGISTRY_ID -- set_verifier --verifier <note-possession verifier id>"
+fi
 
 POOL_IDS=()
 POOL_TOKEN_IDS=()
@@ -585,6 +623,7 @@ Deployment complete
   ASP membership:      $ASP_MEMBERSHIP_ID
   ASP non-membership:  $ASP_NON_MEMBERSHIP_ID
   Public key registry: $PUBLIC_KEY_REGISTRY_ID
+  Registry verifier:   ${NOTE_POSSESSION_VERIFIER_ID:-none (proofs not required)}
   Pools deployed:      ${#POOL_IDS[@]}
   Constructed:         $([[ "$SKIP_INIT" == "true" ]] && echo "no" || echo "yes")
 __DEPLOY_SUMMARY__
@@ -59,6 +59,7 @@ impl TestNote {
         SpendableNote {
             commitment: self.commitment(),
             amount: note_amount(self.input.amount),
+            created_at_ledger: None,
         }
     }
 
@@ -93,6 +94,7 @@ fn transfer_target() -> SpendTarget {
             "0x0000000000000000000000000000000000000000000000000000000000000601",
         )
         .expect("test recipient enc key"),
+        None,
     )
 }
 
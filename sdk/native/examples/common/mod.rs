@@ -37,7 +37,10 @@ use stellar_private_payments::{
     Handle, LocalProver, LocalSigner, LocalStorage, Prover, Signer,
     blocking::{Account, Client, PrivatePool},
     chain::LocalSigner as StellarSigner,
-    types::{AssetDescriptor, ContractConfig, NoteAmount, PoolConfigEntry, ProverArtifacts},
+    types::{
+        AssetDescriptor, ContractConfig, GvkMode, NoteAmount, PoolConfigEntry, ProverArtifacts,
+        gvk_circuit_stem,
+    },
 };
 
 /// Initialize a `tracing_subscriber` formatter driven by `RUST_LOG`.
@@ -172,7 +175,8 @@ pub fn select_pool(config: &ContractConfig) -> Result<&PoolConfigEntry, String>
     }
 }
 
-/// Read the proving key, witness graph, and r1cs for `pool`'s policy flags.
+/// Read the proving key, witness graph, and r1cs for `pool`'s policy flags
+/// and GVK mode.
 ///
 /// The proving key and witness graph are read from `SPP_CIRCUIT_KEYS_DIR`;
 /// r1cs from `SPP_CIRCUIT_ARTIFACTS_DIR`. Missing files produce a message that
@@ -189,7 +193,7 @@ pub fn read_artifacts_for_pool(pool: &PoolConfigEntry) -> Result<ProverArtifacts
             .to_string_lossy()
             .into_owned(),
     );
-    let stem = pool.policy_flags.circuit_stem();
+    let stem = gvk_circuit_stem(pool.policy_flags, pool.gvk_mode);
 
     let proving_key_path = Path::new(&keys_dir).join(format!("{stem}_proving_key.bin"));
     let graph_path = Path::new(&keys_dir).join(format!("{stem}.graph.bin"));
@@ -239,10 +243,20 @@ pub fn build_client_for_pool(
 ) -> Result<Client, String> {
     let rpc_url = env_or("SPP_RPC_URL", "https://soroban-testnet.stellar.org");
     let artifacts = read_artifacts_for_pool(pool)?;
-    let prover = Handle::from_box(Box::new(
+    let prover = if pool.gvk_mode == GvkMode::Off {
         LocalProver::from_artifacts(&[(pool.policy_flags, artifacts)])
-            .map_err(|e| format!("init local prover: {e}"))?,
-    ) as Box<dyn Prover>);
+    } else {
+        // `from_artifacts` needs a plain circuit; the GVK pool never uses it.
+        let plain = read_artifacts_for_pool(&PoolConfigEntry {
+            gvk_mode: GvkMode::Off,
+            ..pool.clone()
+        })?;
+        LocalProver::from_artifacts(&[(pool.policy_flags, plain)]).and_then(|prover| {
+            prover.with_gvk_artifacts(pool.policy_flags, pool.gvk_mode, &artifacts)
+        })
+    }
+    .map_err(|e| format!("init local prover: {e}"))?;
+    let prover = Handle::from_box(Box::new(prover) as Box<dyn Prover>);
     Client::init(&rpc_url, storage, prover, config, bootnode_url())
         .map_err(|e| format!("init client: {e}"))
 }
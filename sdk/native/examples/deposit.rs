@@ -111,11 +111,11 @@ fn run_verbose_deposit(
         let signed = pool.sign(&prepared)?;
 
         println!("  submitting...");
-        let hash = pool.submit(signed)?;
+        let hash = pool.submit_step(&mut plan, signed)?;
         println!("  submitted tx hash: {hash}");
 
         println!("  confirming...");
-        let result = pool.confirm(&hash)?;
+        let result = pool.confirm_step(&mut plan, &hash)?;
         results.push(result);
     }
 
@@ -20,7 +20,7 @@
 
 mod common;
 
-use stellar_private_payments::{Error, TransferRecipient};
+use stellar_private_payments::{Error, TransferOptions, TransferRecipient};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     common::init_tracing()?;
@@ -46,6 +46,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 "Estimated number of on-chain transactions: {}",
                 estimate.tx_count
             );
+            for strategy in &estimate.strategies {
+                println!(
+                    "  {:<10} {} tx(s), {} note(s) spent, {} stroops change",
+                    strategy.strategy.as_str(),
+                    strategy.tx_count,
+                    strategy.note_count,
+                    u128::from(strategy.change)
+                );
+            }
         }
         Err(Error::Plan(e)) => {
             println!("Estimate unavailable: {e}");
@@ -89,7 +98,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("Lower SPP_AMOUNT_STROOPS or deposit more to see a spend plan.");
         } else {
             let recipient = TransferRecipient::from(account.user_address());
-            let transfer_plan = pool.prepare_transfer(&notes, recipient, amount)?;
+            let transfer_plan =
+                pool.prepare_transfer(&notes, recipient, amount, TransferOptions::default())?;
             print_plan_cursor(&transfer_plan);
         }
     }
@@ -34,7 +34,7 @@
 mod common;
 
 use stellar_private_payments::{
-    Error, TransferRecipient,
+    Error, TransferOptions, TransferRecipient,
     types::{EncryptionPublicKey, NotePublicKey},
 };
 
@@ -97,12 +97,17 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Intentional duplicate work: `pool.transfer` below re-fetches spendable
     // notes and re-runs `prepare_transfer` internally. We build the plan here
     // only to show the expected tx count before committing to the real submit.
-    let plan = pool.prepare_transfer(&notes, recipient.clone(), amount)?;
+    let plan = pool.prepare_transfer(
+        &notes,
+        recipient.clone(),
+        amount,
+        TransferOptions::default(),
+    )?;
     println!("Expected on-chain transactions: {}", plan.tx_count());
 
     println!();
     println!("Submitting transfer (proving may take a while)...");
-    match pool.transfer(recipient, amount) {
+    match pool.transfer(recipient, amount, TransferOptions::default()) {
         Ok(results) => {
             println!("Transfer submitted and confirmed.");
             for (i, result) in results.iter().enumerate() {
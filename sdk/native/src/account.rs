@@ -1,14 +1,20 @@
 use crate::types::{
-    ContractConfig, EncryptionPublicKey, Field, NotePublicKey, PortfolioBalance, UserNoteSummary,
+    ContractConfig, EncryptionPublicKey, Field, NoteAmount, NotePublicKey, Passphrase,
+    PaymentRequest, PortfolioBalance, SentNoteSummary, UserNoteSummary, ViewingKey,
+    sub_account_address,
 };
 
-use crate::chain::{Limits, ReadXdr, StateFetcher, TransactionEnvelope, submit_tx};
+use crate::chain::{
+    Limits, ReadXdr, StateFetcher, TransactionEnvelope, possession_binding_offchain, submit_tx,
+};
 
 use crate::{
     Error, Handle, PrivatePool, PrivatePoolConfig, Prover, Signer, Storage,
     chain::RpcClient,
+    planner::CoinSelection,
     sync::{SyncHandle, catch_up, confirm_tx},
     types::TransactionResult,
+    zk::flows::NotePossessionParams,
 };
 
 /// Stellar account session
@@ -22,6 +28,7 @@ pub struct Account<S: Storage> {
     signer: Handle<dyn Signer>,
     sync: SyncHandle,
     contract_config: ContractConfig,
+    sub_account: u32,
 }
 
 impl<S: Storage> Account<S> {
@@ -42,6 +49,7 @@ impl<S: Storage> Account<S> {
             signer,
             sync,
             contract_config,
+            sub_account: 0,
         }
     }
 
@@ -49,6 +57,41 @@ impl<S: Storage> Account<S> {
         &self.user_address
     }
 
+    /// Sub-account index of this session; 0 for the account itself.
+    pub fn sub_account_index(&self) -> u32 {
+        self.sub_account
+    }
+
+    /// Open sub-account `index` of this wallet, deriving and storing its keys
+    /// on first use.
+    ///
+    /// A sub-account has its own note key, encryption key and ASP membership
+    /// leaf, so its notes are not linkable to the other sub-accounts through
+    /// a shared key. It keeps its own notes and scan state; transactions are
+    /// still signed and submitted by [`Self::user_address`]. Index 0 is the
+    /// account itself. Move funds between sub-accounts with
+    /// [`crate::TransferRecipient::SubAccount`].
+    ///
+    /// Onboarding inserts only the account's own ASP leaf. Before a
+    /// sub-account can spend, the ASP admin must insert the leaf from
+    /// [`Self::derive_asp_user_leaf`] on its session; until then spending
+    /// fails with [`Error::SubAccountNotAdmitted`].
+    pub async fn sub_account(&self, index: u32) -> Result<Self, Error> {
+        self.storage
+            .ensure_sub_account(&self.user_address, index)
+            .await?;
+        Ok(Self {
+            rpc: self.rpc.clone(),
+            storage: self.storage.fork()?,
+            prover: self.prover.clone(),
+            user_address: self.user_address.clone(),
+            signer: self.signer.clone(),
+            sync: self.sync.clone(),
+            contract_config: self.contract_config.clone(),
+            sub_account: index,
+        })
+    }
+
     pub fn signer(&self) -> &Handle<dyn Signer> {
         &self.signer
     }
@@ -74,24 +117,37 @@ impl<S: Storage> Account<S> {
     pub async fn portfolio(&self) -> Result<Vec<PortfolioBalance>, Error> {
         self.ensure_synced().await?;
         self.storage
-            .list_portfolio_balances(&self.user_address, &self.contract_config)
+            .list_portfolio_balances(&self.storage_address(), &self.contract_config)
             .await
     }
 
     /// Locally derived note and encryption public keys for this account.
     pub async fn user_public_keys(&self) -> Result<(NotePublicKey, EncryptionPublicKey), Error> {
-        self.storage.user_public_keys(&self.user_address).await
+        self.storage.user_public_keys(&self.storage_address()).await
+    }
+
+    /// A [`PaymentRequest`] for `amount` in `pool_contract_id`, paid to this
+    /// account's keys. Set `memo` / `expires_at` on the result before
+    /// encoding it with [`PaymentRequest::to_uri`].
+    pub async fn payment_request(
+        &self,
+        pool_contract_id: impl Into<String>,
+        amount: NoteAmount,
+    ) -> Result<PaymentRequest, Error> {
+        let (note, enc) = self.user_public_keys().await?;
+        Ok(PaymentRequest::new(pool_contract_id, amount, note, enc))
     }
 
     /// Locally derived ASP membership blinding for this account.
     pub async fn asp_secret(&self) -> Result<Field, Error> {
-        self.storage.asp_secret(&self.user_address).await
+        self.storage.asp_secret(&self.storage_address()).await
     }
 
     /// Derive the ASP membership tree leaf for this account's note public key.
     pub async fn derive_asp_user_leaf(&self) -> Result<Field, Error> {
-        let note = self.storage.user_public_keys(&self.user_address).await?.0;
-        let blinding = self.storage.asp_secret(&self.user_address).await?;
+        let address = self.storage_address();
+        let note = self.storage.user_public_keys(&address).await?.0;
+        let blinding = self.storage.asp_secret(&address).await?;
         crate::crypto::derive_asp_user_leaf(&note, &blinding)
     }
 
@@ -101,14 +157,31 @@ impl<S: Storage> Account<S> {
     pub async fn user_notes(&self, limit: u32) -> Result<Vec<UserNoteSummary>, Error> {
         self.ensure_synced().await?;
         self.storage
-            .list_user_notes(&self.user_address, limit)
+            .list_user_notes(&self.storage_address(), limit)
+            .await
+    }
+
+    /// Notes this account sent to others across all pools (newest first),
+    /// recovered from chain events with its outgoing viewing key.
+    ///
+    /// With [`SyncMode::Inline`], local storage is synced before reading.
+    pub async fn sent_notes(&self, limit: u32) -> Result<Vec<SentNoteSummary>, Error> {
+        self.ensure_synced().await?;
+        self.storage
+            .list_sent_notes(&self.storage_address(), limit)
             .await
     }
 
     /// Whether this account's public keys are registered on-chain.
     ///
+    /// The registry maps a Stellar address to one key pair, so a sub-account
+    /// is never registered.
+    ///
     /// With [`SyncMode::Inline`], local storage is synced before reading.
     pub async fn is_registered(&self) -> Result<bool, Error> {
+        if self.sub_account != 0 {
+            return Ok(false);
+        }
         self.ensure_synced().await?;
         Ok(self
             .storage
@@ -119,14 +192,24 @@ impl<S: Storage> Account<S> {
     }
 
     /// Register this account's public keys on the deployment-wide registry.
+    ///
+    /// When the registry has a `notePossession` verifier it requires a proof,
+    /// so the note key must be the one derived for this account and the
+    /// prover must support [`Prover::prove_note_possession`].
     pub async fn register_public_keys(
         &self,
         note_public_key: Option<NotePublicKey>,
         encryption_public_key: Option<EncryptionPublicKey>,
     ) -> Result<TransactionResult, Error> {
+        if self.sub_account != 0 {
+            return Err(Error::Other(
+                "sub-accounts cannot be registered; share their keys directly".into(),
+            ));
+        }
+        let keys = self.storage().user_keys(&self.user_address).await?;
         let (note_pk, enc_pk) = match (note_public_key, encryption_public_key) {
             (Some(note), Some(enc)) => (note, enc),
-            (None, None) => self.storage().user_public_keys(&self.user_address).await?,
+            (None, None) => (keys.note_keypair.public, keys.encryption_keypair.public),
             _ => {
                 return Err(Error::Other(
                     "note and encryption public keys must both be provided or both omitted".into(),
@@ -136,8 +219,36 @@ impl<S: Storage> Account<S> {
 
         let fetcher = StateFetcher::new(self.rpc.clone(), self.contract_config.clone())
             .map_err(|e| Error::Other(format!("state fetcher: {e:#}")))?;
+        let requires_proof = fetcher
+            .registry_requires_possession_proof()
+            .await
+            .map_err(|e| Error::Other(format!("registry verifier: {e:#}")))?;
+        let proof = if requires_proof {
+            if note_pk.0 != keys.note_keypair.public.0 {
+                return Err(Error::Other(
+                    "note public key does not match this account's note key; cannot prove \
+                     possession"
+                        .into(),
+                ));
+            }
+            let binding_be = possession_binding_offchain(&self.user_address, enc_pk.0)
+                .map_err(|e| Error::Other(format!("possession binding: {e:#}")))?;
+            let binding = Field::try_from_be_bytes(binding_be)
+                .map_err(|e| Error::Other(format!("possession binding: {e:#}")))?;
+            let proof = self
+                .prover
+                .prove_note_possession(NotePossessionParams {
+                    note_private_key: keys.note_keypair.private,
+                    binding,
+                })
+                .await?;
+            Some(proof)
+        } else {
+            None
+        };
+
         let prepared = fetcher
-            .prepare_register(&self.user_address, note_pk.0, enc_pk.0)
+            .prepare_register(&self.user_address, note_pk.0, enc_pk.0, proof.as_deref())
             .await
             .map_err(|e| Error::Other(format!("prepare register: {e:#}")))?;
         let signed = self.signer.sign_soroban_transaction(&prepared).await?;
@@ -149,12 +260,48 @@ impl<S: Storage> Account<S> {
         confirm_tx(fetcher.rpc(), hash).await
     }
 
+    /// Encrypted, portable backup of this account's keys, notes, operation
+    /// history and app settings (JSON), sealed under `passphrase`. Notes and
+    /// history of every sub-account are included; their keys are re-derived
+    /// on import.
+    ///
+    /// The file format is shared with `sdk/web`, so a backup can be restored
+    /// in either.
+    pub async fn export_backup(&self, passphrase: &Passphrase) -> Result<String, Error> {
+        self.storage
+            .export_backup(&self.user_address, passphrase)
+            .await
+    }
+
+    /// Restore a backup made by [`Account::export_backup`] for this account,
+    /// then rebuild its notes from the restored events.
+    pub async fn import_backup(&self, backup: &str, passphrase: &Passphrase) -> Result<(), Error> {
+        self.storage
+            .import_backup(&self.user_address, backup, passphrase)
+            .await?;
+        self.storage.process_pending_state().await
+    }
+
+    /// Viewing key of this account. A watcher opens it with
+    /// [`crate::Client::watch_account`] to follow balances, received notes
+    /// and sent history without being able to spend.
+    ///
+    /// Anyone holding it sees every note of the account; share it like a
+    /// backup.
+    pub async fn export_viewing_key(&self) -> Result<ViewingKey, Error> {
+        self.storage
+            .export_viewing_key(&self.storage_address())
+            .await
+    }
+
     /// Create an owned pool session for `pool_contract_id`.
     pub fn pool(&self, pool_contract_id: impl Into<String>) -> Result<PrivatePool<S>, Error> {
         let cfg = PrivatePoolConfig {
             contract_config: self.contract_config.clone(),
             pool_contract_id: pool_contract_id.into(),
             user_address: self.user_address.clone(),
+            sub_account: self.sub_account,
+            coin_selection: CoinSelection::default(),
         };
 
         PrivatePool::init(
@@ -167,6 +314,10 @@ impl<S: Storage> Account<S> {
         )
     }
 
+    fn storage_address(&self) -> String {
+        sub_account_address(&self.user_address, self.sub_account)
+    }
+
     async fn ensure_synced(&self) -> Result<(), Error> {
         self.sync
             .ensure_synced(&self.rpc, &self.storage, &self.contract_config)
@@ -1,6 +1,9 @@
 //! Sync wrapper around [`crate::Account`] via a shared Tokio runtime.
 
-use crate::types::{EncryptionPublicKey, Field, NotePublicKey, PortfolioBalance, UserNoteSummary};
+use crate::types::{
+    EncryptionPublicKey, Field, NoteAmount, NotePublicKey, Passphrase, PaymentRequest,
+    PortfolioBalance, SentNoteSummary, UserNoteSummary, ViewingKey,
+};
 
 use crate::{
     Error, Handle, Signer, account::Account as AsyncAccount, storage::LocalStorage,
@@ -29,6 +32,16 @@ impl Account {
         self.inner.signer()
     }
 
+    /// Sub-account index of this session; 0 for the account itself.
+    pub fn sub_account_index(&self) -> u32 {
+        self.inner.sub_account_index()
+    }
+
+    /// Open sub-account `index` of this wallet; see [`AsyncAccount::sub_account`].
+    pub fn sub_account(&self, index: u32) -> Result<Self, Error> {
+        Ok(Self::from_inner(block_on(self.inner.sub_account(index))?))
+    }
+
     pub fn storage(&self) -> &LocalStorage {
         self.inner.storage()
     }
@@ -46,6 +59,16 @@ impl Account {
         block_on(self.inner.user_public_keys())
     }
 
+    /// A payment request paid to this account; see
+    /// [`AsyncAccount::payment_request`].
+    pub fn payment_request(
+        &self,
+        pool_contract_id: impl Into<String>,
+        amount: NoteAmount,
+    ) -> Result<PaymentRequest, Error> {
+        block_on(self.inner.payment_request(pool_contract_id, amount))
+    }
+
     pub fn asp_secret(&self) -> Result<Field, Error> {
         block_on(self.inner.asp_secret())
     }
@@ -58,6 +81,10 @@ impl Account {
         block_on(self.inner.user_notes(limit))
     }
 
+    pub fn sent_notes(&self, limit: u32) -> Result<Vec<SentNoteSummary>, Error> {
+        block_on(self.inner.sent_notes(limit))
+    }
+
     pub fn is_registered(&self) -> Result<bool, Error> {
         block_on(self.inner.is_registered())
     }
@@ -76,6 +103,21 @@ impl Account {
         )
     }
 
+    /// Encrypted, portable backup of this account's wallet (JSON).
+    pub fn export_backup(&self, passphrase: &Passphrase) -> Result<String, Error> {
+        block_on(self.inner.export_backup(passphrase))
+    }
+
+    /// Restore a backup made by [`Account::export_backup`] for this account.
+    pub fn import_backup(&self, backup: &str, passphrase: &Passphrase) -> Result<(), Error> {
+        block_on(self.inner.import_backup(backup, passphrase))
+    }
+
+    /// Viewing key of this account, for [`super::Client::watch_account`].
+    pub fn export_viewing_key(&self) -> Result<ViewingKey, Error> {
+        block_on(self.inner.export_viewing_key())
+    }
+
     pub fn pool(&self, pool_contract_id: impl Into<String>) -> Result<PrivatePool, Error> {
         Ok(PrivatePool::from_inner(self.inner.pool(pool_contract_id)?))
     }
@@ -1,6 +1,6 @@
 //! Sync wrapper around [`crate::Client`] via a shared Tokio runtime.
 
-use crate::types::{ContractConfig, OperationalFeedItem, RecipientLookup};
+use crate::types::{ContractConfig, OperationalFeedItem, RecipientLookup, ViewingKey};
 
 use crate::{
     BackgroundSync, Error, Handle, Prover, Signer, chain::StateFetcher,
@@ -80,6 +80,14 @@ impl Client {
         ))
     }
 
+    /// Import `viewing_key` and open a watch-only account session; see
+    /// [`crate::Client::watch_account`].
+    pub fn watch_account(&self, viewing_key: &ViewingKey) -> Result<Account, Error> {
+        Ok(Account::from_inner(block_on(
+            self.inner.watch_account(viewing_key),
+        )?))
+    }
+
     pub fn state_fetcher(&self) -> Result<StateFetcher, Error> {
         self.inner.state_fetcher()
     }
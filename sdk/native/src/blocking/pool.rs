@@ -1,7 +1,7 @@
 //! Sync wrapper around [`crate::PrivatePool`] via a shared Tokio runtime.
 
 use crate::{
-    planner::SpendableNote,
+    planner::{CoinSelection, SpendableNote},
     types::{NoteAmount, Sensitive, UserNoteSummary, correlation_id_or_new},
 };
 
@@ -10,7 +10,10 @@ use crate::{
     error::Error,
     pool::PrivatePool as AsyncPrivatePool,
     storage::LocalStorage,
-    types::{Estimate, PrivatePoolConfig, SignedTransaction, TransactionResult, TransferRecipient},
+    types::{
+        Estimate, PayoutReport, PrivatePoolConfig, ResumedPlan, SignedTransaction, StoredPlan,
+        TransactionResult, TransferOptions, TransferRecipient,
+    },
 };
 
 use super::runtime::block_on;
@@ -31,6 +34,17 @@ impl PrivatePool {
         self.inner.config()
     }
 
+    /// The async pool this wraps, e.g. for
+    /// [`crate::BackgroundSync::with_consolidation`].
+    pub fn into_async(self) -> AsyncPrivatePool<LocalStorage> {
+        self.inner
+    }
+
+    /// See [`AsyncPrivatePool::set_coin_selection`].
+    pub fn set_coin_selection(&mut self, coin_selection: CoinSelection) {
+        self.inner.set_coin_selection(coin_selection);
+    }
+
     #[tracing::instrument(name = "blocking_estimate", level = "info", skip_all, fields(correlation_id = %correlation_id_or_new(), amount = ?Sensitive(&amount)))]
     pub fn estimate(&self, amount: NoteAmount) -> Result<Estimate, Error> {
         block_on(self.inner.estimate(amount))
@@ -46,8 +60,69 @@ impl PrivatePool {
         &self,
         recipient: impl Into<TransferRecipient>,
         amount: NoteAmount,
+        options: TransferOptions,
+    ) -> Result<Vec<TransactionResult>, Error> {
+        block_on(self.inner.transfer(recipient, amount, options))
+    }
+
+    /// Deposit straight into `recipient`'s shielded balance; see
+    /// [`AsyncPrivatePool::deposit_to`].
+    #[tracing::instrument(name = "blocking_deposit_to", level = "info", skip_all, fields(correlation_id = %correlation_id_or_new(), amount = ?Sensitive(&amount)))]
+    pub fn deposit_to(
+        &self,
+        recipient: impl Into<TransferRecipient>,
+        amount: NoteAmount,
+        options: TransferOptions,
+    ) -> Result<TransactionResult, Error> {
+        block_on(self.inner.deposit_to(recipient, amount, options))
+    }
+
+    /// Deposit to many recipients, two per transaction.
+    #[tracing::instrument(name = "blocking_deposit_to_many", level = "info", skip_all, fields(correlation_id = %correlation_id_or_new()))]
+    pub fn deposit_to_many(
+        &self,
+        recipients: Vec<(TransferRecipient, NoteAmount)>,
+        options: TransferOptions,
     ) -> Result<Vec<TransactionResult>, Error> {
-        block_on(self.inner.transfer(recipient, amount))
+        block_on(self.inner.deposit_to_many(recipients, options))
+    }
+
+    /// Pay several recipients in one chain; see
+    /// [`AsyncPrivatePool::transfer_many`].
+    #[tracing::instrument(name = "blocking_transfer_many", level = "info", skip_all, fields(correlation_id = %correlation_id_or_new()))]
+    pub fn transfer_many(
+        &self,
+        recipients: Vec<(TransferRecipient, NoteAmount, Option<String>)>,
+        options: TransferOptions,
+    ) -> Result<PayoutReport, Error> {
+        block_on(self.inner.transfer_many(recipients, options))
+    }
+
+    /// Merge the two smallest notes; see [`AsyncPrivatePool::consolidate`].
+    #[tracing::instrument(name = "blocking_consolidate", level = "info", skip_all, fields(correlation_id = %correlation_id_or_new()))]
+    pub fn consolidate(&self) -> Result<Option<TransactionResult>, Error> {
+        block_on(self.inner.consolidate())
+    }
+
+    /// Finish or abandon persisted plans; see
+    /// [`AsyncPrivatePool::resume_plans`].
+    #[tracing::instrument(name = "blocking_resume_plans", level = "info", skip_all, fields(correlation_id = %correlation_id_or_new()))]
+    pub fn resume_plans(&self) -> Result<Vec<ResumedPlan>, Error> {
+        block_on(self.inner.resume_plans())
+    }
+
+    pub fn active_plans(&self) -> Result<Vec<StoredPlan>, Error> {
+        block_on(self.inner.active_plans())
+    }
+
+    pub fn abandon_plan(&self, plan_id: i64) -> Result<(), Error> {
+        block_on(self.inner.abandon_plan(plan_id))
+    }
+
+    /// Pay an `spp:` payment request URI; see [`AsyncPrivatePool::pay_request`].
+    #[tracing::instrument(name = "blocking_pay_request", level = "info", skip_all, fields(correlation_id = %correlation_id_or_new()))]
+    pub fn pay_request(&self, uri: &str) -> Result<Vec<TransactionResult>, Error> {
+        block_on(self.inner.pay_request(uri))
     }
 
     #[tracing::instrument(name = "blocking_withdraw", level = "info", skip_all, fields(correlation_id = %correlation_id_or_new(), amount = ?Sensitive(&amount)))]
@@ -68,13 +143,37 @@ impl PrivatePool {
         self.inner.prepare_deposit(amount)
     }
 
+    pub fn prepare_deposit_to(
+        &self,
+        recipients: Vec<(TransferRecipient, NoteAmount)>,
+        options: TransferOptions,
+    ) -> Result<PreparedTransactionPlan, Error> {
+        block_on(self.inner.prepare_deposit_to(recipients, options))
+    }
+
     pub fn prepare_transfer(
         &self,
         wallet: &[SpendableNote],
         recipient: impl Into<TransferRecipient>,
         amount: NoteAmount,
+        options: TransferOptions,
     ) -> Result<PreparedTransactionPlan, Error> {
-        block_on(self.inner.prepare_transfer(wallet, recipient, amount))
+        block_on(
+            self.inner
+                .prepare_transfer(wallet, recipient, amount, options),
+        )
+    }
+
+    pub fn prepare_transfer_many(
+        &self,
+        wallet: &[SpendableNote],
+        recipients: Vec<(TransferRecipient, NoteAmount, Option<String>)>,
+        options: TransferOptions,
+    ) -> Result<PreparedTransactionPlan, Error> {
+        block_on(
+            self.inner
+                .prepare_transfer_many(wallet, recipients, options),
+        )
     }
 
     pub fn prepare_withdraw(
@@ -127,6 +226,26 @@ impl PrivatePool {
         block_on(self.inner.confirm(hash))
     }
 
+    /// See [`AsyncPrivatePool::submit_step`].
+    #[tracing::instrument(name = "blocking_submit_step", level = "info", skip_all, fields(correlation_id = %correlation_id_or_new()))]
+    pub fn submit_step(
+        &self,
+        plan: &mut PreparedTransactionPlan,
+        signed_tx: SignedTransaction,
+    ) -> Result<String, Error> {
+        block_on(self.inner.submit_step(plan, signed_tx))
+    }
+
+    /// See [`AsyncPrivatePool::confirm_step`].
+    #[tracing::instrument(name = "blocking_confirm_step", level = "info", skip_all, fields(correlation_id = %correlation_id_or_new(), hash = %hash))]
+    pub fn confirm_step(
+        &self,
+        plan: &mut PreparedTransactionPlan,
+        hash: &str,
+    ) -> Result<TransactionResult, Error> {
+        block_on(self.inner.confirm_step(plan, hash))
+    }
+
     pub fn disclose(
         &self,
         req: crate::DisclosureRequest,
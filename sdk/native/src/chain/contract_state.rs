@@ -1,7 +1,7 @@
 use super::{
     conversions::{
         field_to_scval_u256, scval_to_address_string, scval_to_baby_jub_jub_point, scval_to_bool,
-        scval_to_policy_flags, scval_to_u32, scval_to_u64, scval_to_u256,
+        scval_to_i128, scval_to_policy_flags, scval_to_u32, scval_to_u64, scval_to_u256,
     },
     rpc::{Client, ContractDataBulkRequest},
     soroban_encode::BASE_FEE,
@@ -13,9 +13,9 @@ use stellar_strkey::ed25519;
 use stellar_xdr::{self as xdr, ReadXdr};
 
 use crate::types::{
-    AspMembership, AspNonMembership, AspNonMembershipProof, BabyJubJubPoint, ContractConfig,
-    ContractsStateData, ExtAmount, Field, GvkMode, NotePublicKey, PoolInfo, SMT_DEPTH,
-    TransactChainContext, U256, transact_chain_context_from_state,
+    AspMembership, AspNonMembership, BabyJubJubPoint, ContractConfig, ContractsStateData,
+    ExtAmount, Field, GlobalViewKeyCiphertext, GvkMode, PoolInfo, TransactChainContext, U256,
+    transact_chain_context_from_state,
 };
 
 macro_rules! get_state {
@@ -31,15 +31,6 @@ pub struct StateFetcher {
     config: ContractConfig,
 }
 
-#[derive(Clone, Debug)]
-struct ParsedFindResult {
-    found: bool,
-    siblings: Vec<Field>,
-    not_found_key: Field,
-    not_found_value: Field,
-    is_old0: bool,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OnchainProofPublicInputs {
@@ -51,6 +42,12 @@ pub struct OnchainProofPublicInputs {
     pub ext_data_hash_be: [u8; 32],
     pub asp_membership_root: Field,
     pub asp_non_membership_root: Field,
+    /// Input-note GVK ciphertexts (`pool-gvk` traceable mode only).
+    #[serde(default)]
+    pub input_gvk_ciphertexts: Vec<GlobalViewKeyCiphertext>,
+    /// Output-note GVK ciphertexts (`pool-gvk` only).
+    #[serde(default)]
+    pub output_gvk_ciphertexts: Vec<GlobalViewKeyCiphertext>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -99,6 +96,33 @@ impl StateFetcher {
         Ok((admin_view_key, gvk_mode))
     }
 
+    /// Reads the pool's aggregate counters (`pool_core::stats`), defaulting
+    /// each to zero when the entry has not been written yet.
+    fn stats_fields_from_pool_state(
+        pool_state: &HashMap<String, xdr::ScVal>,
+    ) -> Result<(ExtAmount, ExtAmount, u64)> {
+        let total_deposited = pool_state
+            .get("TotalDeposited")
+            .map(scval_to_i128)
+            .transpose()?
+            .unwrap_or(0);
+        let total_withdrawn = pool_state
+            .get("TotalWithdrawn")
+            .map(scval_to_i128)
+            .transpose()?
+            .unwrap_or(0);
+        let spent_nullifiers = pool_state
+            .get("NullifierCount")
+            .map(scval_to_u64)
+            .transpose()?
+            .unwrap_or(0);
+        Ok((
+            ExtAmount::from(total_deposited),
+            ExtAmount::from(total_withdrawn),
+            spent_nullifiers,
+        ))
+    }
+
     /// Cross-checks a pool's configured GVK settings against what the chain
     /// actually stores.
     ///
@@ -221,10 +245,18 @@ impl StateFetcher {
                     "MaximumDepositAmount",
                     "PolicyFlags",
                 ],
-                // Only written by `contracts/pool-gvk`, never by
-                // `contracts/pool`, so a missing entry is expected rather
-                // than an error. Read below with `.get(...)`, not `get_state!`.
-                optional_enum_keys: vec!["AdminViewKey", "GvkMode"],
+                // `AdminViewKey`/`GvkMode` are only written by
+                // `contracts/pool-gvk`, and the aggregate counters only once
+                // a transaction has touched them, so a missing entry is
+                // expected rather than an error. Read below with `.get(...)`,
+                // not `get_state!`.
+                optional_enum_keys: vec![
+                    "AdminViewKey",
+                    "GvkMode",
+                    "TotalDeposited",
+                    "TotalWithdrawn",
+                    "NullifierCount",
+                ],
                 valued_keys: vec![],
             });
         }
@@ -356,6 +388,8 @@ impl StateFetcher {
                 )?);
                 let (gvk_admin_view_key, gvk_mode) = Self::gvk_fields_from_pool_state(pool_state)?;
                 Self::verify_gvk_config(pool, gvk_admin_view_key, gvk_mode)?;
+                let (total_deposited, total_withdrawn, spent_nullifiers) =
+                    Self::stats_fields_from_pool_state(pool_state)?;
 
                 let pool_info = PoolInfo {
                     ledger: base_latest_ledger,
@@ -400,6 +434,9 @@ impl StateFetcher {
                     )?)?,
                     admin_view_key: gvk_admin_view_key,
                     gvk_mode,
+                    total_deposited,
+                    total_withdrawn,
+                    spent_nullifiers,
                 };
 
                 out.push(pool_info);
@@ -479,113 +516,16 @@ impl StateFetcher {
         ))
     }
 
-    /// Builds ASP SMT non-membership proof data by querying the on-chain SMT
-    /// via `simulateTransaction`.
-    ///
-    /// - if `non_membership_root == 0`, returns a dummy "empty tree" proof
-    ///   padded to `smt_depth`
-    /// - otherwise calls `asp_non_membership.find_key(key)`, pads shorter
-    ///   sibling paths to `smt_depth`, and rejects paths deeper than the
-    ///   configured policy SMT depth
-    pub async fn get_nonmembership_proof(
-        &self,
-        note_pubkey: &NotePublicKey,
-        non_membership_root: Field,
-        smt_depth: usize,
-        source_account: &str,
-    ) -> Result<AspNonMembershipProof> {
-        if smt_depth == 0 {
-            return Err(anyhow!("smt_depth must be > 0"));
-        }
-
-        // NotePublicKey bytes are little-endian field bytes (see
-        // prover::serialization).
-        let key = Field::try_from_le_bytes(*note_pubkey.as_ref())?;
-
-        // Empty tree case (root = 0): non-membership is trivially provable.
-        if non_membership_root.is_zero() {
-            return Ok(AspNonMembershipProof {
-                key,
-                old_key: Field::ZERO,
-                old_value: Field::ZERO,
-                is_old0: true,
-                siblings: vec![Field::ZERO; smt_depth],
-                root: Field::ZERO,
-            });
-        }
-
-        let tx = Self::build_find_key_simulation_tx(
-            &self.config.asp_non_membership,
-            source_account,
-            key,
-        )?;
-        let retval = self.simulate_single_retval(&tx).await?;
-        Self::nonmembership_proof_from_find_result(&retval, key, non_membership_root, smt_depth)
-    }
-
-    fn nonmembership_proof_from_find_result(
-        retval: &xdr::ScVal,
-        key: Field,
-        non_membership_root: Field,
-        smt_depth: usize,
-    ) -> Result<AspNonMembershipProof> {
-        let parsed = Self::parse_find_result(retval)?;
-
-        if parsed.found {
-            return Err(anyhow!(
-                "User note key exists in non-membership tree (user is blocklisted)"
-            ));
-        }
-
-        let mut siblings = parsed.siblings;
-        if siblings.len() > smt_depth {
-            return Err(anyhow!(
-                "ASP non-membership proof has {} sibling(s), but the configured policy SMT depth is {}",
-                siblings.len(),
-                smt_depth
-            ));
-        }
-
-        let padding = smt_depth.saturating_sub(siblings.len());
-        siblings.extend(core::iter::repeat_n(Field::ZERO, padding));
-
-        Ok(AspNonMembershipProof {
-            key,
-            old_key: parsed.not_found_key,
-            old_value: parsed.not_found_value,
-            is_old0: parsed.is_old0,
-            siblings,
-            root: non_membership_root,
-        })
-    }
-
     /// Pool + ASP chain anchors for a single `transact` prove step.
+    ///
+    /// The ASP non-membership proof is not fetched here: it is built from the
+    /// locally indexed tree so the note key never reaches the RPC node.
     pub async fn transact_chain_context(
         &self,
         pool_contract_id: &str,
-        note_pubkey: &NotePublicKey,
-        user_address: &str,
     ) -> Result<TransactChainContext> {
         let data = self.contracts_data_for_pool(pool_contract_id).await?;
-        let policy_flags = data
-            .pools
-            .first()
-            .map(|pool| pool.policy_flags)
-            .ok_or_else(|| anyhow!("pool data not fetched for {pool_contract_id}"))?;
-        let non_membership_proof = if policy_flags.requires_non_membership_proofs() {
-            Some(
-                self.get_nonmembership_proof(
-                    note_pubkey,
-                    data.asp_non_membership.root,
-                    SMT_DEPTH as usize,
-                    user_address,
-                )
-                .await?,
-            )
-        } else {
-            None
-        };
-        transact_chain_context_from_state(data, pool_contract_id, non_membership_proof)
+        transact_chain_context_from_state(data, pool_contract_id)
     }
 
     /// Checks whether a pool Merkle root is still known by the deployed pool.
@@ -644,6 +584,31 @@ impl StateFetcher {
         Ok(scval_to_bool(&retval)?)
     }
 
+    /// Whether the public key registry has a `notePossession` verifier, so
+    /// `register` must carry a proof of possession of the note key.
+    ///
+    /// Simulates the registry's `get_verifier` entrypoint.
+    ///
+    /// # Errors
+    /// Returns an error if the simulation fails or the contract returns
+    /// something other than an optional address.
+    pub async fn registry_requires_possession_proof(&self) -> Result<bool> {
+        let tx = Self::build_invoke_contract_tx_envelope(
+            &self.config.deployer,
+            xdr::SequenceNumber(0),
+            BASE_FEE,
+            &self.config.public_key_registry,
+            "get_verifier",
+            Vec::new(),
+            Vec::new(),
+        )?;
+        match self.simulate_single_retval(&tx).await? {
+            xdr::ScVal::Void => Ok(false),
+            xdr::ScVal::Address(_) => Ok(true),
+            other => Err(anyhow!("unexpected get_verifier result: {other:?}")),
+        }
+    }
+
     async fn simulate_single_retval(&self, tx: &xdr::TransactionEnvelope) -> Result<xdr::ScVal> {
         let sim = self.client.simulate_transaction(tx).await?;
 
@@ -665,22 +630,6 @@ impl StateFetcher {
         )?)
     }
 
-    fn build_find_key_simulation_tx(
-        contract_id: &str,
-        source_account: &str,
-        key: Field,
-    ) -> Result<xdr::TransactionEnvelope> {
-        Self::build_invoke_contract_tx_envelope(
-            source_account,
-            xdr::SequenceNumber(0),
-            BASE_FEE,
-            contract_id,
-            "find_key",
-            vec![field_to_scval_u256(key)],
-            Vec::new(),
-        )
-    }
-
     fn build_is_known_root_simulation_tx(
         contract_id: &str,
         source_account: &str,
@@ -760,78 +709,6 @@ impl StateFetcher {
         }))
     }
 
-    fn parse_find_result(val: &xdr::ScVal) -> Result<ParsedFindResult> {
-        let xdr::ScVal::Map(Some(map)) = val else {
-            return Err(anyhow!("FindResult: expected ScVal::Map, got {val:?}"));
-        };
-
-        let mut fields = std::collections::HashMap::<String, xdr::ScVal>::new();
-        for xdr::ScMapEntry { key, val } in map.iter() {
-            let name = match key {
-                xdr::ScVal::Symbol(sym) => sym.to_utf8_string()?,
-                _ => {
-                    return Err(anyhow!(
-                        "FindResult: field name should be a symbol: {key:?}"
-                    ));
-                }
-            };
-            fields.insert(name, val.clone());
-        }
-
-        let found = scval_to_bool(
-            fields
-                .get("found")
-                .ok_or_else(|| anyhow!("FindResult missing field: found"))?,
-        )?;
-
-        let mut siblings = Vec::new();
-        if let Some(v) = fields.get("siblings") {
-            match v {
-                xdr::ScVal::Vec(Some(sc_vec)) => {
-                    for inner in sc_vec.0.iter() {
-                        let u = scval_to_u256(inner)?;
-                        siblings.push(Field::try_from_u256(u)?);
-                    }
-                }
-                xdr::ScVal::Vec(None) => {}
-                other => return Err(anyhow!("FindResult.siblings: unexpected ScVal: {other:?}")),
-            }
-        }
-
-        let not_found_key = fields
-            .get("not_found_key")
-            .or_else(|| fields.get("notFoundKey"))
-            .map(scval_to_u256)
-            .transpose()?
-            .map(Field::try_from_u256)
-            .transpose()?
-            .unwrap_or(Field::ZERO);
-
-        let not_found_value = fields
-            .get("not_found_value")
-            .or_else(|| fields.get("notFoundValue"))
-            .map(scval_to_u256)
-            .transpose()?
-            .map(Field::try_from_u256)
-            .transpose()?
-            .unwrap_or(Field::ZERO);
-
-        let is_old0 = fields
-            .get("is_old0")
-            .or_else(|| fields.get("isOld0"))
-            .map(scval_to_bool)
-            .transpose()?
-            .unwrap_or(false);
-
-        Ok(ParsedFindResult {
-            found,
-            siblings,
-            not_found_key,
-            not_found_value,
-            is_old0,
-        })
-    }
-
     fn muxed_account_from_g(account: &str) -> Result<xdr::MuxedAccount> {
         let pk = ed25519::PublicKey::from_string(account)?;
         Ok(xdr::MuxedAccount::Ed25519(xdr::Uint256(pk.0)))
@@ -864,39 +741,6 @@ mod tests {
         }
     }
 
-    fn find_result_scval(found: bool, siblings: Vec<Field>) -> xdr::ScVal {
-        let siblings = xdr::ScVec::try_from(
-            siblings
-                .into_iter()
-                .map(field_to_scval_u256)
-                .collect::<Vec<_>>(),
-        )
-        .expect("siblings");
-        let entries = xdr::ScMap(
-            vec![
-                sc_map_entry("found", xdr::ScVal::Bool(found)),
-                sc_map_entry("siblings", xdr::ScVal::Vec(Some(siblings))),
-            ]
-            .try_into()
-            .expect("find result map"),
-        );
-
-        xdr::ScVal::Map(Some(entries))
-    }
-
-    #[test]
-    fn non_membership_proof_rejects_over_depth_find_result() {
-        let retval = find_result_scval(false, vec![field(1), field(2), field(3)]);
-        let err =
-            StateFetcher::nonmembership_proof_from_find_result(&retval, field(9), field(10), 2)
-                .expect_err("over-depth parsed FindResult must be rejected");
-
-        assert!(
-            err.to_string().contains("ASP non-membership proof has 3 sibling(s), but the configured policy SMT depth is 2"),
-            "{err:#}"
-        );
-    }
-
     fn baby_jub_jub_point_scval(x: Field, y: Field) -> xdr::ScVal {
         let entries = xdr::ScMap(
             vec![
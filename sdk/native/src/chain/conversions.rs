@@ -1,6 +1,6 @@
 use crate::{
     chain::rpc::Error,
-    types::{BabyJubJubPoint, ContractEvent, Field, U256},
+    types::{BabyJubJubPoint, ContractEvent, Field, GlobalViewKeyCiphertext, U256},
 };
 use base64::{Engine as _, engine::general_purpose::STANDARD};
 use core::ops::Shl;
@@ -167,6 +167,17 @@ pub fn scval_to_u64(val: &xdr::ScVal) -> Result<u64, Error> {
     }
 }
 
+pub fn scval_to_i128(val: &xdr::ScVal) -> Result<i128, Error> {
+    if let xdr::ScVal::I128(parts) = val {
+        let hi = i128::from(parts.hi)
+            .checked_shl(64)
+            .ok_or_else(|| Error::UnexpectedScVal("I128 overflow (hi)".into()))?;
+        Ok(hi | i128::from(parts.lo))
+    } else {
+        Err(Error::UnexpectedScVal(format!("{val:?}")))
+    }
+}
+
 pub fn scval_to_bool(val: &xdr::ScVal) -> Result<bool, Error> {
     if let xdr::ScVal::Bool(n) = val {
         Ok(*n)
@@ -218,6 +229,48 @@ pub fn scval_to_baby_jub_jub_point(val: &xdr::ScVal) -> Result<BabyJubJubPoint,
     })
 }
 
+/// Decode a `pool-gvk::gvk::GvkCiphertext` (`{ r: BabyJubJubPoint, c1: U256,
+/// c2: U256, c3: U256 }`) carried by `pool-gvk`'s pool events.
+pub fn scval_to_gvk_ciphertext(val: &xdr::ScVal) -> Result<GlobalViewKeyCiphertext, Error> {
+    let xdr::ScVal::Map(Some(map)) = val else {
+        return Err(Error::UnexpectedScVal(format!(
+            "GvkCiphertext: expected ScVal::Map, found: {val:?}"
+        )));
+    };
+
+    let mut r = None;
+    let mut c1 = None;
+    let mut c2 = None;
+    let mut c3 = None;
+    for xdr::ScMapEntry { key, val } in map.iter() {
+        let xdr::ScVal::Symbol(name) = key else {
+            continue;
+        };
+        match name.to_utf8_string_lossy().as_str() {
+            "r" => r = Some(scval_to_baby_jub_jub_point(val)?),
+            "c1" => c1 = Some(scval_to_u256(val)?),
+            "c2" => c2 = Some(scval_to_u256(val)?),
+            "c3" => c3 = Some(scval_to_u256(val)?),
+            _ => {}
+        }
+    }
+
+    let field = |value: Option<U256>, name: &str| -> Result<Field, Error> {
+        let value = value.ok_or_else(|| {
+            Error::UnexpectedScVal(format!("GvkCiphertext missing field: {name}"))
+        })?;
+        Field::try_from_u256(value)
+            .map_err(|e| Error::UnexpectedScVal(format!("GvkCiphertext.{name}: {e}")))
+    };
+
+    Ok(GlobalViewKeyCiphertext {
+        r: r.ok_or_else(|| Error::UnexpectedScVal("GvkCiphertext missing field: r".into()))?,
+        c1: field(c1, "c1")?,
+        c2: field(c2, "c2")?,
+        c3: field(c3, "c3")?,
+    })
+}
+
 #[derive(Debug)]
 pub struct ParsedContractEvent {
     // Unique identifier for this event, based on the TOID format.
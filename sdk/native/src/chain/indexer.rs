@@ -28,12 +28,16 @@ impl<S: ContractDataStorage> Indexer<S> {
             .filter(|meta| active_contract_ids.contains(meta.contract_id.as_str()))
             .collect();
 
-        let probe_ledger = active_sync
-            .iter()
-            .map(|meta| meta.last_indexed_ledger)
-            .filter(|ledger| *ledger > 0)
-            .min()
-            .unwrap_or(min_pool_ledger);
+        let probe_ledger = if has_untracked_contract(&contract_ids, &active_sync) {
+            min_pool_ledger
+        } else {
+            active_sync
+                .iter()
+                .map(|meta| meta.last_indexed_ledger)
+                .filter(|ledger| *ledger > 0)
+                .min()
+                .unwrap_or(min_pool_ledger)
+        };
 
         match client
             .get_contract_events(&contract_ids, probe_ledger, 1, None)
@@ -77,12 +81,20 @@ impl<S: ContractDataStorage> Indexer<S> {
             .filter(|meta| active_contract_ids.contains(meta.contract_id.as_str()))
             .collect();
 
-        let start_ledger = active_sync
-            .iter()
-            .map(|meta| meta.last_indexed_ledger)
-            .min()
-            .unwrap_or(self.min_pool_ledger)
-            .min(network_tip);
+        // A contract without sync metadata (e.g. newly indexed after an SDK
+        // upgrade) is backfilled from the pool deployment ledger. Raw event
+        // inserts are idempotent, so replaying for the others is harmless.
+        let untracked = has_untracked_contract(&self.contract_ids, &active_sync);
+        let start_ledger = if untracked {
+            self.min_pool_ledger
+        } else {
+            active_sync
+                .iter()
+                .map(|meta| meta.last_indexed_ledger)
+                .min()
+                .unwrap_or(self.min_pool_ledger)
+        }
+        .min(network_tip);
 
         if active_sync
             .iter()
@@ -101,7 +113,9 @@ impl<S: ContractDataStorage> Indexer<S> {
             .iter()
             .filter_map(|meta| (!meta.cursor.is_empty()).then_some(meta.cursor.as_str()))
             .collect();
-        let mut cursor = if unique_cursors.len() <= 1 {
+        let mut cursor = if untracked {
+            None
+        } else if unique_cursors.len() <= 1 {
             active_sync
                 .first()
                 .and_then(|meta| (!meta.cursor.is_empty()).then(|| meta.cursor.clone()))
@@ -192,6 +206,13 @@ impl<S: ContractDataStorage> Indexer<S> {
     }
 }
 
+fn has_untracked_contract(contract_ids: &[String], active_sync: &[SyncMetadata]) -> bool {
+    !active_sync.is_empty()
+        && contract_ids
+            .iter()
+            .any(|id| !active_sync.iter().any(|meta| &meta.contract_id == id))
+}
+
 #[async_trait::async_trait(?Send)]
 pub trait ContractDataStorage {
     /// Gets the last synced ledger and cursor for all contracts.
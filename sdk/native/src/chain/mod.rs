@@ -2,6 +2,7 @@ mod contract_state;
 mod conversions;
 mod ext_data_hash;
 mod indexer;
+mod possession_binding;
 mod rpc;
 mod signer;
 mod soroban_encode;
@@ -14,11 +15,12 @@ pub use contract_state::{OnchainProofPublicInputs, PreparedSorobanTx, StateFetch
 pub use conversions::*;
 pub use ext_data_hash::hash_ext_data_offchain;
 pub use indexer::{ContractDataStorage, Indexer};
+pub use possession_binding::possession_binding_offchain;
 pub use rpc::{
-    Client, Client as RpcClient, Error as RpcError, Event, GetTransactionResponse,
-    SendTransactionResponse,
+    Client, Client as RpcClient, Error as RpcError, Event, GetNetworkResponse,
+    GetTransactionResponse, SendTransactionResponse,
 };
 pub use signer::{LocalSigner, Signature, auth_sign_steps, unsigned_tx_for_signing, verify_tx};
 pub use stellar_xdr::{Limits, ReadXdr, TransactionEnvelope, WriteXdr};
-pub use submit::{TxConfirmStatus, confirm_tx, submit_tx};
+pub use submit::{TxConfirmStatus, confirm_tx, submit_tx, tx_hash};
 pub use tx_prepare::PoolTransactInput;
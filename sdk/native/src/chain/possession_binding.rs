@@ -0,0 +1,39 @@
+use crate::types::{BN254_MODULUS_BE, U256};
+use anyhow::Result;
+use core::ops::Rem;
+use sha3::{Digest, Keccak256};
+use stellar_xdr::{Limits, ScAddress, ScMap, ScMapEntry, ScSymbol, ScVal, WriteXdr};
+
+use crate::chain::conversions::bytes_to_scval;
+
+// please refer to possession_binding in contracts/public-key-registry/src/lib.rs
+/// Computes the `binding` public input of the `notePossession` circuit as a
+/// big-endian field element.
+pub fn possession_binding_offchain(owner: &str, encryption_key: [u8; 32]) -> Result<[u8; 32]> {
+    // `PossessionBinding` serializes to an XDR map sorted alphabetically by key
+    let entries: [(&str, ScVal); 2] = [
+        ("encryption_key", bytes_to_scval(encryption_key)?),
+        ("owner", ScVal::Address(owner.parse::<ScAddress>()?)),
+    ];
+
+    let mut map_entries: Vec<ScMapEntry> = Vec::with_capacity(entries.len());
+    for (k, v) in entries {
+        let sym: stellar_xdr::StringM<32> = k.try_into()?;
+        map_entries.push(ScMapEntry {
+            key: ScVal::Symbol(ScSymbol(sym)),
+            val: v,
+        });
+    }
+    let payload = ScVal::Map(Some(ScMap(map_entries.try_into()?))).to_xdr(Limits::none())?;
+
+    let mut hasher = Keccak256::new();
+    hasher.update(&payload);
+    let digest = hasher.finalize();
+
+    // Reduce into the BN254 scalar field as the contract does with `rem_euclid`.
+    let mut digest_be = [0u8; 32];
+    digest_be.copy_from_slice(digest.as_slice());
+    let digest_u256 = U256::from_big_endian(&digest_be);
+    let modulus = U256::from_big_endian(&BN254_MODULUS_BE);
+    Ok(Rem::rem(digest_u256, modulus).to_big_endian())
+}
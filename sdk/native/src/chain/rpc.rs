@@ -103,6 +103,13 @@ pub struct GetLatestLedgerResponse {
     pub sequence: u32,
 }
 
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct GetNetworkResponse {
+    pub passphrase: String,
+    #[serde(rename = "protocolVersion")]
+    pub protocol_version: u32,
+}
+
 pub type SegmentFilter = String;
 pub type TopicFilter = Vec<SegmentFilter>;
 
@@ -273,6 +280,8 @@ impl Client {
     const DEFAULT_TIMEOUT_SECS: u32 = 30;
     // https://developers.stellar.org/docs/data/apis/rpc/api-reference/methods/getLedgerEntries
     const MAX_LEDGER_KEYS_PER_REQUEST: usize = 200;
+    // https://developers.stellar.org/docs/data/apis/rpc/api-reference/methods/getEvents
+    const MAX_CONTRACT_IDS_PER_FILTER: usize = 5;
 
     /// Creates a client with the default 30-second timeout.
     pub fn new(base_url: &str) -> Result<Self, Error> {
@@ -425,7 +434,7 @@ impl Client {
         topics: &[TopicFilter],
         limit: Option<usize>,
     ) -> Result<GetEventsResponse, Error> {
-        let mut filters = serde_json::Map::new();
+        let mut base_filter = serde_json::Map::new();
 
         event_type
             .and_then(|t| match t {
@@ -433,10 +442,26 @@ impl Client {
                 EventType::Contract => Some("contract"),
                 EventType::System => Some("system"),
             })
-            .map(|t| filters.insert("type".to_string(), t.into()));
+            .map(|t| base_filter.insert("type".to_string(), t.into()));
 
-        filters.insert("topics".to_string(), topics.into());
-        filters.insert("contractIds".to_string(), contract_ids.into());
+        base_filter.insert("topics".to_string(), topics.into());
+
+        // RPC caps contract IDs per filter; filters are OR-ed together.
+        let chunks: Vec<&[String]> = if contract_ids.is_empty() {
+            vec![contract_ids]
+        } else {
+            contract_ids
+                .chunks(Self::MAX_CONTRACT_IDS_PER_FILTER)
+                .collect()
+        };
+        let filters: Vec<serde_json::Value> = chunks
+            .into_iter()
+            .map(|chunk| {
+                let mut filter = base_filter.clone();
+                filter.insert("contractIds".to_string(), chunk.into());
+                filter.into()
+            })
+            .collect();
 
         let mut pagination = serde_json::Map::new();
         if let Some(limit) = limit {
@@ -444,7 +469,7 @@ impl Client {
         }
 
         let mut params = json!({
-            "filters": [filters],
+            "filters": filters,
             "pagination": pagination,
         });
 
@@ -468,6 +493,10 @@ impl Client {
         self.rpc_call("getLatestLedger", json!({})).await
     }
 
+    pub async fn get_network(&self) -> Result<GetNetworkResponse, Error> {
+        self.rpc_call("getNetwork", json!({})).await
+    }
+
     pub async fn get_ledger_entries(
         &self,
         keys: &[LedgerKey],
@@ -1,6 +1,6 @@
 //! Off-chain Soroban `ScVal` encoding for pool contract calls.
 
-use crate::types::{ExtData, Field};
+use crate::types::{BabyJubJubPoint, ExtData, Field, GlobalViewKeyCiphertext};
 use anyhow::{Result, anyhow};
 use stellar_xdr::{self as xdr, ScAddress, ScMap, ScMapEntry, ScSymbol, ScVal};
 
@@ -48,6 +48,12 @@ pub fn groth16_proof_to_scval(proof_uncompressed: &[u8]) -> Result<ScVal> {
     ])
 }
 
+/// Encodes an optional uncompressed Groth16 proof as `Option<Groth16Proof>`:
+/// `Void` when absent.
+pub fn optional_groth16_proof_to_scval(proof_uncompressed: Option<&[u8]>) -> Result<ScVal> {
+    proof_uncompressed.map_or(Ok(ScVal::Void), groth16_proof_to_scval)
+}
+
 /// Encodes pool `Proof` public inputs + embedded proof for `transact`.
 #[allow(clippy::too_many_arguments)]
 pub fn pool_proof_to_scval(
@@ -61,6 +67,94 @@ pub fn pool_proof_to_scval(
     asp_membership_root: Field,
     asp_non_membership_root: Field,
 ) -> Result<ScVal> {
+    sorted_map(pool_proof_entries(
+        proof_uncompressed,
+        root,
+        input_nullifiers,
+        output_commitment0,
+        output_commitment1,
+        public_amount,
+        ext_data_hash_be,
+        asp_membership_root,
+        asp_non_membership_root,
+    )?)
+}
+
+/// Encodes `pool-gvk`'s `Proof`: the pool `Proof` fields plus the per-note
+/// GVK ciphertexts the circuit output.
+#[allow(clippy::too_many_arguments)]
+pub fn pool_gvk_proof_to_scval(
+    proof_uncompressed: &[u8],
+    root: Field,
+    input_nullifiers: &[Field],
+    output_commitment0: Field,
+    output_commitment1: Field,
+    public_amount: Field,
+    ext_data_hash_be: [u8; 32],
+    asp_membership_root: Field,
+    asp_non_membership_root: Field,
+    input_gvk_ciphertexts: &[GlobalViewKeyCiphertext],
+    output_gvk_ciphertexts: &[GlobalViewKeyCiphertext],
+) -> Result<ScVal> {
+    let mut entries = pool_proof_entries(
+        proof_uncompressed,
+        root,
+        input_nullifiers,
+        output_commitment0,
+        output_commitment1,
+        public_amount,
+        ext_data_hash_be,
+        asp_membership_root,
+        asp_non_membership_root,
+    )?;
+    entries.push(map_entry(
+        "input_gvk_ciphertexts",
+        gvk_ciphertexts_to_scval(input_gvk_ciphertexts)?,
+    )?);
+    entries.push(map_entry(
+        "output_gvk_ciphertexts",
+        gvk_ciphertexts_to_scval(output_gvk_ciphertexts)?,
+    )?);
+    sorted_map(entries)
+}
+
+/// Encodes a `pool-gvk` `GvkCiphertext`.
+pub fn gvk_ciphertext_to_scval(ciphertext: &GlobalViewKeyCiphertext) -> Result<ScVal> {
+    sorted_map(vec![
+        map_entry("c1", field_to_scval_u256(ciphertext.c1))?,
+        map_entry("c2", field_to_scval_u256(ciphertext.c2))?,
+        map_entry("c3", field_to_scval_u256(ciphertext.c3))?,
+        map_entry("r", baby_jub_jub_point_to_scval(&ciphertext.r)?)?,
+    ])
+}
+
+fn gvk_ciphertexts_to_scval(ciphertexts: &[GlobalViewKeyCiphertext]) -> Result<ScVal> {
+    let items = ciphertexts
+        .iter()
+        .map(gvk_ciphertext_to_scval)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ScVal::Vec(Some(xdr::ScVec::try_from(items)?)))
+}
+
+fn baby_jub_jub_point_to_scval(point: &BabyJubJubPoint) -> Result<ScVal> {
+    sorted_map(vec![
+        map_entry("x", field_to_scval_u256(point.x))?,
+        map_entry("y", field_to_scval_u256(point.y))?,
+    ])
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pool_proof_entries(
+    proof_uncompressed: &[u8],
+    root: Field,
+    input_nullifiers: &[Field],
+    output_commitment0: Field,
+    output_commitment1: Field,
+    public_amount: Field,
+    ext_data_hash_be: [u8; 32],
+    asp_membership_root: Field,
+    asp_non_membership_root: Field,
+) -> Result<Vec<ScMapEntry>> {
     let nullifiers = xdr::ScVec::try_from(
         input_nullifiers
             .iter()
@@ -69,7 +163,7 @@ pub fn pool_proof_to_scval(
             .collect::<Vec<_>>(),
     )?;
 
-    sorted_map(vec![
+    Ok(vec![
         map_entry(
             "asp_membership_root",
             field_to_scval_u256(asp_membership_root),
@@ -124,8 +218,10 @@ pub fn register_account_to_scval(
 mod tests {
     use super::*;
     use crate::chain::ext_data_hash::hash_ext_data_offchain;
+    use crate::chain::possession_binding::possession_binding_offchain;
     use contract_types::Groth16Proof;
     use pool::{ExtData as PoolExtData, Proof};
+    use pool_gvk::{Proof as GvkProof, gvk::GvkCiphertext};
     use public_key_registry::Account;
     use soroban_sdk::{
         Address, Bytes, BytesN, Env, I256, U256 as SorobanU256, Vec,
@@ -299,6 +395,21 @@ mod tests {
         assert_eq!(ours, soroban_xdr_to_vec(expected));
     }
 
+    #[test]
+    fn possession_binding_matches_contract() {
+        let env = Env::default();
+        let owner = Address::from_str(&env, TEST_ACCOUNT);
+        let encryption_key = [0xEE; 32];
+        let on_chain = public_key_registry::possession_binding(
+            &env,
+            &owner,
+            &Bytes::from_array(&env, &encryption_key),
+        );
+
+        let ours = possession_binding_offchain(TEST_ACCOUNT, encryption_key).expect("binding");
+        assert_eq!(ours.to_vec(), on_chain.to_be_bytes().to_alloc_vec());
+    }
+
     #[test]
     fn pool_proof_encoding_matches_contracttype_xdr() {
         let env = Env::default();
@@ -337,6 +448,74 @@ mod tests {
         assert_eq!(ours, soroban_xdr_to_vec(expected));
     }
 
+    #[test]
+    // Small fixed offsets over test seeds cannot overflow.
+    #[allow(clippy::arithmetic_side_effects)]
+    fn pool_gvk_proof_encoding_matches_contracttype_xdr() {
+        let env = Env::default();
+        let mut nullifiers = Vec::new(&env);
+        nullifiers.push_back(SorobanU256::from_u32(&env, 0xAA));
+        nullifiers.push_back(SorobanU256::from_u32(&env, 0xBB));
+        let on_chain_ciphertext = |seed: u32| GvkCiphertext {
+            r: pool_gvk::gvk::BabyJubJubPoint {
+                x: SorobanU256::from_u32(&env, seed),
+                y: SorobanU256::from_u32(&env, seed + 1),
+            },
+            c1: SorobanU256::from_u32(&env, seed + 2),
+            c2: SorobanU256::from_u32(&env, seed + 3),
+            c3: SorobanU256::from_u32(&env, seed + 4),
+        };
+        let ciphertext = |seed: u32| GlobalViewKeyCiphertext {
+            r: BabyJubJubPoint {
+                x: field_from_u32(seed),
+                y: field_from_u32(seed + 1),
+            },
+            c1: field_from_u32(seed + 2),
+            c2: field_from_u32(seed + 3),
+            c3: field_from_u32(seed + 4),
+        };
+        let mut input_gvk_ciphertexts = Vec::new(&env);
+        input_gvk_ciphertexts.push_back(on_chain_ciphertext(0x10));
+        input_gvk_ciphertexts.push_back(on_chain_ciphertext(0x20));
+        let mut output_gvk_ciphertexts = Vec::new(&env);
+        output_gvk_ciphertexts.push_back(on_chain_ciphertext(0x30));
+        output_gvk_ciphertexts.push_back(on_chain_ciphertext(0x40));
+
+        let proof = mk_mock_groth16_proof(&env);
+        let proof_uncompressed = proof_uncompressed_from_contract(&proof);
+        let on_chain = GvkProof {
+            proof,
+            root: SorobanU256::from_u32(&env, 0x01),
+            input_nullifiers: nullifiers,
+            output_commitment0: SorobanU256::from_u32(&env, 0x02),
+            output_commitment1: SorobanU256::from_u32(&env, 0x03),
+            public_amount: SorobanU256::from_u32(&env, 0x04),
+            ext_data_hash: BytesN::from_array(&env, &[0xCD; 32]),
+            asp_membership_root: SorobanU256::from_u32(&env, 0x05),
+            asp_non_membership_root: SorobanU256::from_u32(&env, 0x06),
+            output_gvk_ciphertexts,
+            input_gvk_ciphertexts,
+        };
+        let expected = on_chain.to_xdr(&env);
+        let ours = scval_xdr(
+            &pool_gvk_proof_to_scval(
+                &proof_uncompressed,
+                field_from_u32(1),
+                &[field_from_u32(0xAA), field_from_u32(0xBB)],
+                field_from_u32(2),
+                field_from_u32(3),
+                field_from_u32(4),
+                [0xCD; 32],
+                field_from_u32(5),
+                field_from_u32(6),
+                &[ciphertext(0x10), ciphertext(0x20)],
+                &[ciphertext(0x30), ciphertext(0x40)],
+            )
+            .expect("encode"),
+        );
+        assert_eq!(ours, soroban_xdr_to_vec(expected));
+    }
+
     #[test]
     fn pool_proof_map_keys_are_sorted() {
         let proof_uncompressed = vec![0u8; 256];
@@ -3,7 +3,15 @@
 use anyhow::{Context, Result, bail};
 use stellar_xdr::TransactionEnvelope;
 
-use crate::chain::rpc::Client;
+use crate::chain::{rpc::Client, signer::network_id};
+
+/// Hash the network assigns to `signed_tx`, known before it is submitted.
+pub fn tx_hash(signed_tx: &TransactionEnvelope, network_passphrase: &str) -> Result<String> {
+    let hash = signed_tx
+        .hash(network_id(network_passphrase))
+        .context("hash transaction envelope")?;
+    Ok(hex::encode(hash))
+}
 
 /// Submits a signed transaction; returns the transaction hash.
 #[tracing::instrument(name = "submit_tx", level = "info", skip_all, fields(correlation_id = %crate::types::correlation_id_or_new()))]
@@ -1,16 +1,63 @@
 //! Build and simulate pool contract transactions for signing/submission.
 
-use crate::types::ExtData;
+use crate::types::{ExtData, GvkMode};
 use anyhow::{Result, anyhow};
 use stellar_xdr::{self as xdr};
 
 use super::{
     contract_state::{OnchainProofPublicInputs, PreparedSorobanTx, StateFetcher},
     soroban_encode::{
-        BASE_FEE, pool_ext_data_to_scval, pool_proof_to_scval, register_account_to_scval,
+        BASE_FEE, optional_groth16_proof_to_scval, pool_ext_data_to_scval, pool_gvk_proof_to_scval,
+        pool_proof_to_scval, register_account_to_scval,
     },
 };
 
+/// Encodes the `transact` proof argument in the shape the pool's contract
+/// expects: `pool`'s `Proof`, or `pool-gvk`'s with the GVK ciphertexts.
+fn pool_transact_proof_to_scval(
+    gvk_mode: GvkMode,
+    proof_uncompressed: &[u8],
+    public: &OnchainProofPublicInputs,
+) -> Result<xdr::ScVal> {
+    if gvk_mode == GvkMode::Off {
+        if !public.input_gvk_ciphertexts.is_empty() || !public.output_gvk_ciphertexts.is_empty() {
+            return Err(anyhow!(
+                "GVK ciphertexts supplied for a pool without a Global View Key"
+            ));
+        }
+        return pool_proof_to_scval(
+            proof_uncompressed,
+            public.root,
+            &public.input_nullifiers,
+            public.output_commitment0,
+            public.output_commitment1,
+            public.public_amount,
+            public.ext_data_hash_be,
+            public.asp_membership_root,
+            public.asp_non_membership_root,
+        );
+    }
+
+    if public.output_gvk_ciphertexts.is_empty() {
+        return Err(anyhow!(
+            "missing GVK ciphertexts for a {gvk_mode:?} pool; was the proof built with a GVK circuit?"
+        ));
+    }
+    pool_gvk_proof_to_scval(
+        proof_uncompressed,
+        public.root,
+        &public.input_nullifiers,
+        public.output_commitment0,
+        public.output_commitment1,
+        public.public_amount,
+        public.ext_data_hash_be,
+        public.asp_membership_root,
+        public.asp_non_membership_root,
+        &public.input_gvk_ciphertexts,
+        &public.output_gvk_ciphertexts,
+    )
+}
+
 /// Prover output needed to prepare a pool `transact` invocation.
 #[derive(Debug, Clone)]
 pub struct PoolTransactInput {
@@ -28,18 +75,9 @@ impl StateFetcher {
         input: &PoolTransactInput,
         source_account: &str,
     ) -> Result<PreparedSorobanTx> {
-        self.enabled_pool_for(pool_contract_id)?;
-        let proof_scval = pool_proof_to_scval(
-            &input.proof_uncompressed,
-            input.public.root,
-            &input.public.input_nullifiers,
-            input.public.output_commitment0,
-            input.public.output_commitment1,
-            input.public.public_amount,
-            input.public.ext_data_hash_be,
-            input.public.asp_membership_root,
-            input.public.asp_non_membership_root,
-        )?;
+        let pool = self.enabled_pool_for(pool_contract_id)?;
+        let proof_scval =
+            pool_transact_proof_to_scval(pool.gvk_mode, &input.proof_uncompressed, &input.public)?;
         let ext_scval = pool_ext_data_to_scval(&input.ext_data)?;
         let sender_scval = xdr::ScVal::Address(
             source_account
@@ -64,13 +102,18 @@ impl StateFetcher {
 
     /// Simulates `register` on the configured public key registry contract and
     /// returns unsigned XDR + auth entries for the wallet.
+    ///
+    /// `proof_uncompressed` is the `notePossession` proof for `note_key`;
+    /// `None` for a registry without a verifier.
     pub async fn prepare_register(
         &self,
         source_account: &str,
         note_key: [u8; 32],
         encryption_key: [u8; 32],
+        proof_uncompressed: Option<&[u8]>,
     ) -> Result<PreparedSorobanTx> {
         let account_scval = register_account_to_scval(source_account, encryption_key, note_key)?;
+        let proof_scval = optional_groth16_proof_to_scval(proof_uncompressed)?;
 
         let seq = self.account_sequence(source_account).await?;
         let raw = Self::build_invoke_contract_tx_envelope(
@@ -79,7 +122,7 @@ impl StateFetcher {
             BASE_FEE,
             &self.contract_config().public_key_registry,
             "register",
-            vec![account_scval],
+            vec![account_scval, proof_scval],
             Vec::new(),
         )?;
 
@@ -203,16 +246,18 @@ mod tests {
         source_account: &str,
         note_key: [u8; 32],
         encryption_key: [u8; 32],
+        proof_uncompressed: Option<&[u8]>,
     ) -> Result<PreparedSorobanTx> {
         let config: ContractConfig = serde_json::from_str(TEST_CONFIG_JSON).expect("test config");
         let account_scval = register_account_to_scval(source_account, encryption_key, note_key)?;
+        let proof_scval = optional_groth16_proof_to_scval(proof_uncompressed)?;
         let raw = StateFetcher::build_invoke_contract_tx_envelope(
             source_account,
             next_sequence(mock.seq.clone())?,
             BASE_FEE,
             &config.public_key_registry,
             "register",
-            vec![account_scval],
+            vec![account_scval, proof_scval],
             Vec::new(),
         )?;
         let sim = mock.simulate_transaction(&raw).await?;
@@ -242,7 +287,11 @@ mod tests {
         let source = pk.to_string();
         let mock = MockRpc::new(9, fixture_sim("250"));
         let prepared = block_on(prepare_register_with_mock(
-            &mock, &source, [0xAB; 32], [0xEE; 32],
+            &mock,
+            &source,
+            [0xAB; 32],
+            [0xEE; 32],
+            Some(&[0u8; 256]),
         ))
         .expect("prepare register");
 
@@ -266,7 +315,7 @@ mod tests {
             panic!("expected contract invoke");
         };
         assert_eq!(args.function_name.to_string(), "register");
-        assert_eq!(args.args.len(), 1);
+        assert_eq!(args.args.len(), 2);
     }
 
     #[test]
@@ -295,6 +344,8 @@ mod tests {
             ext_data_hash_be: [0u8; 32],
             asp_membership_root: crate::types::Field(crate::types::U256::from(7)),
             asp_non_membership_root: crate::types::Field(crate::types::U256::from(8)),
+            input_gvk_ciphertexts: Vec::new(),
+            output_gvk_ciphertexts: Vec::new(),
         };
 
         let proof_scval = pool_proof_to_scval(
@@ -345,6 +396,56 @@ mod tests {
         assert_eq!(args.args.len(), 3);
     }
 
+    #[test]
+    fn transact_proof_encoding_follows_the_pool_gvk_mode() {
+        use crate::types::{BabyJubJubPoint, Field, GlobalViewKeyCiphertext};
+
+        let proof_uncompressed = vec![0u8; 256];
+        let ciphertext = GlobalViewKeyCiphertext {
+            r: BabyJubJubPoint {
+                x: Field::ZERO,
+                y: Field::ZERO,
+            },
+            c1: Field::ZERO,
+            c2: Field::ZERO,
+            c3: Field::ZERO,
+        };
+        let plain = OnchainProofPublicInputs {
+            root: Field::ZERO,
+            input_nullifiers: [Field::ZERO, Field::ZERO],
+            output_commitment0: Field::ZERO,
+            output_commitment1: Field::ZERO,
+            public_amount: Field::ZERO,
+            ext_data_hash_be: [0u8; 32],
+            asp_membership_root: Field::ZERO,
+            asp_non_membership_root: Field::ZERO,
+            input_gvk_ciphertexts: Vec::new(),
+            output_gvk_ciphertexts: Vec::new(),
+        };
+        let gvk = OnchainProofPublicInputs {
+            output_gvk_ciphertexts: vec![ciphertext.clone(), ciphertext],
+            ..plain.clone()
+        };
+
+        let field_count = |sc: xdr::ScVal| {
+            let xdr::ScVal::Map(Some(map)) = sc else {
+                panic!("expected map");
+            };
+            map.len()
+        };
+        let plain_sc = pool_transact_proof_to_scval(GvkMode::Off, &proof_uncompressed, &plain)
+            .expect("plain proof");
+        let gvk_sc = pool_transact_proof_to_scval(GvkMode::ViewOnly, &proof_uncompressed, &gvk)
+            .expect("gvk proof");
+        assert_eq!(field_count(plain_sc), 9);
+        assert_eq!(field_count(gvk_sc), 11);
+
+        assert!(pool_transact_proof_to_scval(GvkMode::Off, &proof_uncompressed, &gvk).is_err());
+        assert!(
+            pool_transact_proof_to_scval(GvkMode::Traceable, &proof_uncompressed, &plain).is_err()
+        );
+    }
+
     #[test]
     fn next_sequence_increments_by_one() {
         assert_eq!(
@@ -1,7 +1,7 @@
-use crate::types::{ContractConfig, OperationalFeedItem, RecipientLookup};
+use crate::types::{ContractConfig, OperationalFeedItem, RecipientLookup, ViewingKey};
 
 use crate::{
-    Account, Error, Handle, NoopProver, Prover, Signer, Storage, SyncMode,
+    Account, Error, Handle, NoopProver, NoopSigner, Prover, Signer, Storage, SyncMode,
     chain::{RpcClient, StateFetcher},
     correlation::correlation_id_or_new,
     sync::{BackgroundSync, SyncHandle, catch_up},
@@ -154,6 +154,33 @@ impl<S: Storage> Client<S> {
         ))
     }
 
+    /// Import `viewing_key` and open a watch-only [`Account`] session for its
+    /// address.
+    ///
+    /// The session syncs and reads balances, received notes and sent history.
+    /// Notes are marked spent from the spend records in the account's own
+    /// outputs, so spends made before those records existed stay unspent here.
+    /// Proving and signing fail with [`Error::WatchOnly`].
+    #[tracing::instrument(
+        name = "client_watch_account",
+        skip_all,
+        fields(correlation_id = %correlation_id_or_new())
+    )]
+    pub async fn watch_account(&self, viewing_key: &ViewingKey) -> Result<Account<S>, Error> {
+        self.storage.import_viewing_key(viewing_key).await?;
+        Ok(Account::new(
+            self.rpc.clone(),
+            self.storage.fork()?,
+            Handle::from_box(Box::new(NoopProver) as Box<dyn Prover>),
+            viewing_key.address.clone(),
+            Handle::from_box(
+                Box::new(NoopSigner::new(viewing_key.address.clone())) as Box<dyn Signer>
+            ),
+            self.sync.clone(),
+            self.contract_config.clone(),
+        ))
+    }
+
     /// Chain-state accessor for this deployment.
     pub fn state_fetcher(&self) -> Result<StateFetcher, Error> {
         StateFetcher::new(self.rpc.clone(), self.contract_config.clone())
@@ -0,0 +1,134 @@
+//! Opt-in background note consolidation.
+//!
+//! Every transfer or withdrawal leaves change, so a wallet that receives often
+//! accumulates small notes and later spends need more merge steps. A
+//! [`Consolidator`] merges the two smallest notes, one transaction at a time,
+//! while [`crate::BackgroundSync`] is idle. Runs are spaced by a minimum
+//! interval plus a random delay so consolidations don't line up with sync
+//! rounds or each other on-chain.
+
+use web_time::{Duration, Instant};
+
+use crate::{Error, Storage, pool::PrivatePool, types::AspMembershipSync};
+
+/// When background consolidation merges notes; see
+/// [`crate::BackgroundSync::with_consolidation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConsolidationPolicy {
+    /// Consolidate only while the wallet holds more spendable notes than this.
+    pub max_notes: usize,
+    /// Minimum time between two consolidation transactions.
+    pub min_interval_ms: u64,
+    /// Upper bound of the random delay added to every wait, including the
+    /// first one after start.
+    pub jitter_ms: u64,
+}
+
+impl Default for ConsolidationPolicy {
+    fn default() -> Self {
+        Self {
+            max_notes: 8,
+            min_interval_ms: 60 * 60 * 1_000,
+            jitter_ms: 60 * 60 * 1_000,
+        }
+    }
+}
+
+impl ConsolidationPolicy {
+    /// `min_interval_ms` plus [`Self::jitter`].
+    fn next_delay(&self) -> Duration {
+        Duration::from_millis(self.min_interval_ms).saturating_add(self.jitter())
+    }
+
+    /// Uniform random delay of at most `jitter_ms`.
+    fn jitter(&self) -> Duration {
+        let mut bytes = [0u8; 8];
+        let jitter = match getrandom::getrandom(&mut bytes) {
+            Ok(()) => u64::from_le_bytes(bytes)
+                .checked_rem(self.jitter_ms.saturating_add(1))
+                .unwrap_or(0),
+            Err(e) => {
+                tracing::warn!("consolidation jitter unavailable: {e}");
+                self.jitter_ms
+            }
+        };
+        Duration::from_millis(jitter)
+    }
+}
+
+/// One pool consolidated from [`crate::BackgroundSync::run`].
+pub(crate) struct Consolidator<S> {
+    pool: PrivatePool<S>,
+    policy: ConsolidationPolicy,
+    next_run: Instant,
+}
+
+impl<S: Storage> Consolidator<S> {
+    pub(crate) fn new(pool: PrivatePool<S>, policy: ConsolidationPolicy) -> Self {
+        let next_run = after(policy.jitter());
+        Self {
+            pool,
+            policy,
+            next_run,
+        }
+    }
+
+    /// Called after a caught-up sync round: merges once if the policy's wait
+    /// has elapsed and the wallet holds too many notes. Every check restarts
+    /// the wait: reading notes kicks the sync task, so checking on each round
+    /// would keep it from idling. Failures are logged and retried after the
+    /// next wait, except `SyncRequired`: this runs on the sync task, so it
+    /// returns at once and retries on the next idle round, once the indexer
+    /// has caught up.
+    pub(crate) async fn on_idle(&mut self) {
+        if Instant::now() < self.next_run {
+            return;
+        }
+        match self.pool.spendable_notes().await {
+            Ok(notes) if notes.len() <= self.policy.max_notes => {}
+            Ok(_) => match self.pool.consolidate_without_sync_wait().await {
+                Ok(Some(result)) => {
+                    tracing::info!(tx_hash = %result.tx_hash, "background consolidation confirmed");
+                }
+                Ok(None) => {}
+                Err(Error::MembershipSync(AspMembershipSync::SyncRequired(_))) => {
+                    tracing::debug!("background consolidation waits for the next sync round");
+                    return;
+                }
+                Err(e) => tracing::warn!("background consolidation failed: {e}"),
+            },
+            Err(e) => tracing::warn!("background consolidation skipped: {e}"),
+        }
+        self.next_run = after(self.policy.next_delay());
+    }
+}
+
+fn after(delay: Duration) -> Instant {
+    let now = Instant::now();
+    now.checked_add(delay).unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_stays_within_interval_and_jitter() {
+        let policy = ConsolidationPolicy {
+            max_notes: 2,
+            min_interval_ms: 1_000,
+            jitter_ms: 500,
+        };
+        for _ in 0..64 {
+            let delay = policy.next_delay();
+            assert!(delay >= Duration::from_millis(1_000));
+            assert!(delay <= Duration::from_millis(1_500));
+        }
+
+        let fixed = ConsolidationPolicy {
+            jitter_ms: 0,
+            ..policy
+        };
+        assert_eq!(fixed.next_delay(), Duration::from_millis(1_000));
+    }
+}
@@ -1,10 +1,15 @@
 //! Shared pool config, planning, and helpers (all targets).
 
 use crate::{
-    planner::{SpendSession, SpendTarget, SpendableNote, Transact},
+    planner::{
+        CoinSelection, Payout, PayoutSession, SpendSession, SpendTarget, SpendableNote, StepAction,
+        Transact, TransactionPlan, consolidation_step, plan_with,
+    },
     types::{
         EncryptionPublicKey, Estimate, ExtAmount, NoteAmount, NotePublicKey, PrivatePoolConfig,
+        StrategyEstimate, TransferOptions,
     },
+    zk::encryption::validate_memo,
 };
 
 use crate::{error::Error, plan::PreparedTransactionPlan};
@@ -29,6 +34,10 @@ impl PoolCore {
         &self.config
     }
 
+    pub fn set_coin_selection(&mut self, coin_selection: CoinSelection) {
+        self.config.coin_selection = coin_selection;
+    }
+
     pub fn prepare_deposit(&self, amount: NoteAmount) -> Result<PreparedTransactionPlan, Error> {
         if amount.is_zero() {
             return Err(Error::InvalidConfig("amount must be > 0".into()));
@@ -36,25 +45,100 @@ impl PoolCore {
         Ok(PreparedTransactionPlan::deposit(amount))
     }
 
+    /// Deposit that creates notes for `recipients` instead of the depositor:
+    /// one transaction per two recipients, one output each. A lone last
+    /// recipient also gets the zero-value second output. `options.memo` goes
+    /// to every recipient.
+    pub fn prepare_deposit_to(
+        &self,
+        recipients: Vec<(NotePublicKey, EncryptionPublicKey, NoteAmount)>,
+        options: TransferOptions,
+    ) -> Result<PreparedTransactionPlan, Error> {
+        if recipients.is_empty() {
+            return Err(Error::InvalidConfig("no deposit recipients".into()));
+        }
+        if recipients.iter().any(|(_, _, amount)| amount.is_zero()) {
+            return Err(Error::InvalidConfig("amount must be > 0".into()));
+        }
+        if let Some(memo) = &options.memo {
+            validate_memo(memo).map_err(|e| Error::InvalidConfig(e.to_string()))?;
+        }
+        let steps = recipients
+            .chunks(2)
+            .map(|pair| self.deposit_to_step(pair, &options.memo))
+            .collect::<Result<Vec<_>, _>>()?;
+        PreparedTransactionPlan::batch(steps)
+    }
+
     pub fn prepare_transfer(
         &self,
         wallet: &[SpendableNote],
         note_public_key: NotePublicKey,
         encryption_public_key: EncryptionPublicKey,
         amount: NoteAmount,
+        options: TransferOptions,
     ) -> Result<PreparedTransactionPlan, Error> {
         if amount.is_zero() {
             return Err(Error::InvalidConfig("amount must be > 0".into()));
         }
-        let session = SpendSession::setup(
+        if let Some(memo) = &options.memo {
+            validate_memo(memo).map_err(|e| Error::InvalidConfig(e.to_string()))?;
+        }
+        let session = SpendSession::setup_with(
             wallet.to_vec(),
             amount,
             self.config.pool_contract_id.clone(),
-            SpendTarget::transfer(note_public_key, encryption_public_key),
+            SpendTarget::transfer(note_public_key, encryption_public_key, options.memo),
+            self.config.coin_selection.selector(),
         )?;
         PreparedTransactionPlan::from_session(session).map_err(Error::from)
     }
 
+    /// Transfer to several recipients in one chain of transactions: each step
+    /// spends the previous step's change plus at most one more note and pays
+    /// one recipient, or two when the inputs match their amounts exactly.
+    /// Each recipient gets its own memo, or `options.memo` when it has none.
+    pub fn prepare_transfer_many(
+        &self,
+        wallet: &[SpendableNote],
+        recipients: Vec<(
+            NotePublicKey,
+            EncryptionPublicKey,
+            NoteAmount,
+            Option<String>,
+        )>,
+        options: TransferOptions,
+    ) -> Result<PreparedTransactionPlan, Error> {
+        if recipients.is_empty() {
+            return Err(Error::InvalidConfig("no transfer recipients".into()));
+        }
+        if recipients.iter().any(|(_, _, amount, _)| amount.is_zero()) {
+            return Err(Error::InvalidConfig("amount must be > 0".into()));
+        }
+        let memos = recipients
+            .iter()
+            .filter_map(|(_, _, _, memo)| memo.as_ref());
+        for memo in options.memo.iter().chain(memos) {
+            validate_memo(memo).map_err(|e| Error::InvalidConfig(e.to_string()))?;
+        }
+        let payouts = recipients
+            .into_iter()
+            .map(|(recipient_note, recipient_enc, amount, memo)| Payout {
+                recipient_note,
+                recipient_enc,
+                amount,
+                memo: memo.or_else(|| options.memo.clone()),
+            })
+            .collect();
+        let session = PayoutSession::setup(
+            wallet,
+            payouts,
+            self.config.pool_contract_id.clone(),
+            self.config.coin_selection.selector(),
+        )?;
+        PreparedTransactionPlan::from_payout(session).map_err(Error::from)
+    }
+
     pub fn prepare_withdraw(
         &self,
         wallet: &[SpendableNote],
@@ -64,26 +148,93 @@ impl PoolCore {
         if amount.is_zero() {
             return Err(Error::InvalidConfig("amount must be > 0".into()));
         }
-        let session = SpendSession::setup(
+        let session = SpendSession::setup_with(
             wallet.to_vec(),
             amount,
             self.config.pool_contract_id.clone(),
             SpendTarget::withdraw(recipient.into()),
+            self.config.coin_selection.selector(),
         )?;
         PreparedTransactionPlan::from_session(session).map_err(Error::from)
     }
 
+    /// One transaction merging the two smallest `wallet` notes into a note to
+    /// self, or `None` with fewer than two notes.
+    pub fn prepare_consolidation(
+        &self,
+        wallet: &[SpendableNote],
+    ) -> Result<Option<PreparedTransactionPlan>, Error> {
+        let Some(step) = consolidation_step(wallet)? else {
+            return Ok(None);
+        };
+        let StepAction::Consolidate { output } = step.action else {
+            return Err(Error::Other(
+                "consolidation step does not consolidate".into(),
+            ));
+        };
+        let inputs = step.resolve(wallet)?;
+        Ok(Some(PreparedTransactionPlan::from_transact(Transact::new(
+            inputs.iter().map(|note| note.commitment).collect(),
+            [output, NoteAmount::ZERO],
+            ExtAmount::ZERO,
+            self.config.pool_contract_id.clone(),
+            [None, None],
+            [None, None],
+        ))))
+    }
+
+    /// Transactions a spend of `amount` takes with the configured
+    /// [`CoinSelection`], plus its cost under every built-in strategy.
     pub fn estimate(
         &self,
         wallet: &[SpendableNote],
         amount: NoteAmount,
     ) -> Result<Estimate, Error> {
-        let plan = crate::planner::plan(amount, wallet)?;
+        let plan = plan_with(amount, wallet, self.config.coin_selection.selector())?;
+        let strategies = CoinSelection::ALL
+            .into_iter()
+            .filter_map(|strategy| {
+                plan_with(amount, wallet, strategy.selector())
+                    .ok()
+                    .map(|plan| strategy_estimate(strategy, &plan))
+            })
+            .collect();
         Ok(Estimate {
             tx_count: u32::try_from(plan.len()).unwrap_or(u32::MAX),
+            strategies,
         })
     }
 
+    fn deposit_to_step(
+        &self,
+        pair: &[(NotePublicKey, EncryptionPublicKey, NoteAmount)],
+        memo: &Option<String>,
+    ) -> Result<Transact, Error> {
+        let [(first_note, first_enc, first_amount), rest @ ..] = pair else {
+            return Err(Error::Other("empty deposit step".into()));
+        };
+        let (second_note, second_enc, second_amount, second_memo) = match rest.first() {
+            Some((note, enc, amount)) => (note, enc, *amount, memo.clone()),
+            None => (first_note, first_enc, NoteAmount::ZERO, None),
+        };
+        let total = first_amount
+            .checked_add(second_amount)
+            .ok_or_else(|| Error::Other("deposit amount overflow".into()))?;
+        let ext_amount = ExtAmount::try_from(total)
+            .map_err(|_| Error::Other("deposit amount exceeds ext_amount range".into()))?;
+
+        let mut step = Transact::new(
+            Vec::new(),
+            [*first_amount, second_amount],
+            ext_amount,
+            self.config.pool_contract_id.clone(),
+            [Some(first_note.clone()), Some(second_note.clone())],
+            [Some(first_enc.clone()), Some(second_enc.clone())],
+        );
+        step.out_memos = [memo.clone(), second_memo];
+        Ok(step)
+    }
+
     pub fn deposit_transact_step(
         &self,
         note_pub: NotePublicKey,
@@ -103,3 +254,24 @@ impl PoolCore {
         ))
     }
 }
+
+fn strategy_estimate(strategy: CoinSelection, plan: &TransactionPlan) -> StrategyEstimate {
+    let note_count = plan
+        .into_iter()
+        .flat_map(|step| [Some(&step.inputs.0), step.inputs.1.as_ref()])
+        .flatten()
+        .filter(|note| note.commitment.is_some())
+        .count();
+    let change = match &plan.final_step().action {
+        StepAction::Final {
+            outputs: (_, change),
+        } => change.unwrap_or(NoteAmount::ZERO),
+        StepAction::Consolidate { .. } => NoteAmount::ZERO,
+    };
+    StrategyEstimate {
+        strategy,
+        tx_count: u32::try_from(plan.len()).unwrap_or(u32::MAX),
+        note_count: u32::try_from(note_count).unwrap_or(u32::MAX),
+        change,
+    }
+}
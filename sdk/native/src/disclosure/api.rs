@@ -18,7 +18,7 @@ use crate::{
     chain::StateFetcher,
     error::Error,
     prover::Prover,
-    transact::{build_validated_pool_tree, load_user_key_material},
+    transact::{build_pool_merkle_proofs, load_user_key_material},
     zk::merkle::MerkleProof,
 };
 
@@ -68,7 +68,7 @@ pub enum BuildDisclosureInputs {
 }
 
 pub fn build_disclosure_inputs(
-    storage: &SqliteStorage,
+    storage: &mut SqliteStorage,
     req: &DisclosureInputsRequest,
 ) -> anyhow::Result<BuildDisclosureInputs> {
     if req.selected_commitments.is_empty() || req.selected_commitments.len() > 4 {
@@ -84,20 +84,9 @@ pub fn build_disclosure_inputs(
     let (note_privkey, _note_pubkey, _encryption_pubkey, _membership_blinding) =
         load_user_key_material(storage, &req.user_address)?;
 
-    let tree = match build_validated_pool_tree(
-        storage,
-        &req.pool_address,
-        req.pool_next_index,
-        req.tree_depth,
-        pool_root,
-    )? {
-        Ok(tree) => tree,
-        Err(status) => return Ok(BuildDisclosureInputs::MembershipSync(status)),
-    };
-
-    let mut notes = Vec::with_capacity(req.selected_commitments.len());
+    let mut selected = Vec::with_capacity(req.selected_commitments.len());
     for commitment in &req.selected_commitments {
-        let (amount, blinding, leaf_index) = storage
+        let note = storage
             .get_user_note_by_commitment(&req.pool_address, &req.user_address, commitment)?
             .ok_or_else(|| {
                 anyhow::anyhow!(
@@ -105,17 +94,37 @@ pub fn build_disclosure_inputs(
                     req.pool_address
                 )
             })?;
+        selected.push((*commitment, note));
+    }
+
+    let leaf_indices: Vec<u32> = selected
+        .iter()
+        .map(|(_, (_, _, leaf_index))| *leaf_index)
+        .collect();
+    let proofs = match build_pool_merkle_proofs(
+        storage,
+        &req.pool_address,
+        req.pool_next_index,
+        req.tree_depth,
+        pool_root,
+        &leaf_indices,
+    )? {
+        Ok(proofs) => proofs,
+        Err(status) => return Ok(BuildDisclosureInputs::MembershipSync(status)),
+    };
 
+    let mut notes = Vec::with_capacity(selected.len());
+    for ((commitment, (amount, blinding, _)), proof) in selected.into_iter().zip(proofs) {
         let MerkleProof {
             path_elements,
             path_indices,
             root,
             ..
-        } = tree.proof(leaf_index)?;
+        } = proof;
 
         notes.push(DisclosureInputs {
             root,
-            note_commitment: *commitment,
+            note_commitment: commitment,
             note_amount: amount,
             note_private_key: note_privkey.clone(),
             note_blinding: blinding,
@@ -1,6 +1,6 @@
 use crate::{
     planner::{PlanError, SpendSessionError},
-    types::AspMembershipSync,
+    types::{AspMembershipSync, Field},
 };
 
 use crate::types::TransactionResult;
@@ -22,9 +22,20 @@ pub enum Error {
     #[error("ASP membership sync required: {0:?}")]
     MembershipSync(AspMembershipSync),
 
+    /// Sub-account `index` cannot spend until the ASP admin inserts its
+    /// membership `leaf` (see [`crate::Account::derive_asp_user_leaf`]).
+    #[error(
+        "sub-account {index} is not in the ASP membership tree: ask the ASP admin to insert leaf {leaf}"
+    )]
+    SubAccountNotAdmitted { index: u32, leaf: Field },
+
     #[error(transparent)]
     PlanExecution(#[from] PlanExecutionError),
 
+    /// The account was opened from a viewing key, which cannot prove or sign.
+    #[error("account {0} is watch-only: a viewing key cannot prove or sign")]
+    WatchOnly(String),
+
     /// The user rejected the wallet signing request (SEP-0043 error code -4).
     #[error("wallet request rejected by user: {0}")]
     UserRejected(String),
@@ -43,8 +54,9 @@ impl Error {
 /// on-chain.
 ///
 /// `completed` are those successes; `cause` is why the remaining steps did not
-/// finish. Recovery is sync + a fresh plan from current notes — the same plan
-/// is not resumed.
+/// finish. The plan stays persisted: [`crate::PrivatePool::resume_plans`]
+/// finishes it, or [`crate::PrivatePool::abandon_plan`] drops it before a
+/// fresh plan from current notes.
 #[derive(Debug, thiserror::Error)]
 #[error("plan failed after {} confirmed transaction(s): {cause}", .completed.len())]
 pub struct PlanExecutionError {
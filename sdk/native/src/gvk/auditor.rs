@@ -0,0 +1,571 @@
+//! Audit of a `pool-gvk` pool from the local database.
+
+use super::{
+    babyjub::Point,
+    cipher::{DecryptedNote, admin_view_key, decrypt, note_commitment},
+    dleq,
+};
+use crate::{
+    state::{GvkNoteRef, PoolGvkCiphertextRow, SqliteStorage},
+    types::{
+        BabyJubJubPoint, Field, GVK_DECRYPTION_RECEIPT_VERSION, GvkDecryptionReceipt, NoteAmount,
+        Secret, U256,
+    },
+    zk::serialization::field_to_scalar,
+};
+use anyhow::{Context, Result, anyhow};
+use ark_bn254::Fr as Scalar;
+use ark_ff::PrimeField;
+use serde::Serialize;
+use std::{collections::HashMap, fmt::Write as _};
+
+/// Decrypts and checks the GVK ciphertexts of a pool with the administrator's
+/// private scalar `d`.
+///
+/// The auditor only knows its own key: callers must check that
+/// [`Auditor::admin_view_key`] equals the pool's on-chain `AdminViewKey`
+/// before trusting a report, since every ciphertext of a pool under a
+/// different key shows up as [`NoteCheck::Mismatch`].
+#[derive(Debug)]
+pub struct Auditor {
+    d: Secret<[u8; 32]>,
+    admin_view_key: BabyJubJubPoint,
+}
+
+impl Auditor {
+    /// Builds an auditor from `d` as 32 little-endian bytes.
+    ///
+    /// Fails if `d` is zero or not a canonical BN254 field element.
+    pub fn new(admin_private_key: Secret<[u8; 32]>) -> Result<Self> {
+        let d = Field::try_from_le_bytes(*admin_private_key.expose())
+            .context("GVK admin private key is not a field element")?;
+        if d.is_zero() {
+            return Err(anyhow!("GVK admin private key must be non-zero"));
+        }
+        let admin_view_key = admin_view_key(field_to_scalar(&d));
+        Ok(Self {
+            d: admin_private_key,
+            admin_view_key,
+        })
+    }
+
+    /// The administrator view key `D = d * BASE8` this auditor decrypts for.
+    pub fn admin_view_key(&self) -> BabyJubJubPoint {
+        self.admin_view_key
+    }
+
+    fn scalar(&self) -> Scalar {
+        Scalar::from_le_bytes_mod_order(self.d.expose())
+    }
+
+    /// Decrypts every indexed ciphertext of `pool_contract_id` and builds the
+    /// pool's transaction graph.
+    ///
+    /// Output notes are checked against their on-chain commitment. Input
+    /// notes (traceable pools only) are linked to the earlier output whose
+    /// commitment they reopen.
+    pub fn audit(&self, storage: &SqliteStorage, pool_contract_id: &str) -> Result<AuditReport> {
+        let rows = storage.get_pool_gvk_ciphertexts(pool_contract_id)?;
+        self.audit_rows(pool_contract_id, rows)
+    }
+
+    /// Builds a [`GvkDecryptionReceipt`] for the note carried by event
+    /// `event_id`, for handing to a third party who does not hold `d`.
+    ///
+    /// Fails if the event has no indexed GVK ciphertext, or if the
+    /// decryption does not open an output's on-chain commitment or yield a
+    /// valid amount.
+    pub fn decryption_receipt(
+        &self,
+        storage: &SqliteStorage,
+        pool_contract_id: &str,
+        event_id: &str,
+    ) -> Result<GvkDecryptionReceipt> {
+        let row = storage
+            .get_pool_gvk_ciphertexts(pool_contract_id)?
+            .into_iter()
+            .find(|row| row.event_id == event_id)
+            .ok_or_else(|| anyhow!("no GVK ciphertext indexed for event {event_id}"))?;
+        self.receipt_for_row(pool_contract_id, row)
+    }
+
+    pub(crate) fn receipt_for_row(
+        &self,
+        pool_contract_id: &str,
+        row: PoolGvkCiphertextRow,
+    ) -> Result<GvkDecryptionReceipt> {
+        let d = self.scalar();
+        let decrypted = decrypt(&row.ciphertext, d).with_context(|| {
+            format!("failed to decrypt GVK ciphertext of event {}", row.event_id)
+        })?;
+        let commitment = note_commitment(&decrypted);
+        if let GvkNoteRef::Output {
+            commitment: onchain,
+            ..
+        } = row.note
+            && onchain != commitment
+        {
+            return Err(anyhow!(
+                "GVK decryption of event {} does not open its on-chain commitment",
+                row.event_id
+            ));
+        }
+        let amount = note_amount(decrypted.amount).ok_or_else(|| {
+            anyhow!(
+                "GVK decryption of event {} is not a valid note amount",
+                row.event_id
+            )
+        })?;
+        let (shared_point, proof) = dleq::prove(d, Point::from_app(&row.ciphertext.r))?;
+
+        Ok(GvkDecryptionReceipt {
+            version: GVK_DECRYPTION_RECEIPT_VERSION,
+            pool_address: pool_contract_id.to_string(),
+            event_id: row.event_id,
+            admin_view_key: self.admin_view_key,
+            ciphertext: row.ciphertext,
+            shared_point: shared_point.to_app(),
+            proof,
+            note_public_key: decrypted.pk,
+            amount,
+            blinding: decrypted.blinding,
+            commitment,
+        })
+    }
+
+    fn audit_rows(
+        &self,
+        pool_contract_id: &str,
+        rows: Vec<PoolGvkCiphertextRow>,
+    ) -> Result<AuditReport> {
+        let d = self.scalar();
+        build_report(pool_contract_id, self.admin_view_key, rows, |row| {
+            decrypt(&row.ciphertext, d)
+        })
+    }
+}
+
+/// Decrypts `rows` in order with `decrypt` and builds the pool's transaction
+/// graph. Shared by [`Auditor`] and the threshold auditor, which differ only
+/// in how they recover the shared point.
+pub(super) fn build_report(
+    pool_contract_id: &str,
+    admin_view_key: BabyJubJubPoint,
+    rows: Vec<PoolGvkCiphertextRow>,
+    mut decrypt: impl FnMut(&PoolGvkCiphertextRow) -> Result<DecryptedNote>,
+) -> Result<AuditReport> {
+    let mut transactions: Vec<AuditedTransaction> = Vec::new();
+    let mut outputs: HashMap<Field, SpentOutput> = HashMap::new();
+
+    for row in rows {
+        let decrypted = decrypt(&row).with_context(|| {
+            format!("failed to decrypt GVK ciphertext of event {}", row.event_id)
+        })?;
+        let PoolGvkCiphertextRow {
+            event_id,
+            ledger,
+            note,
+            ..
+        } = row;
+        let transaction_id = transaction_id(&event_id).to_string();
+        let commitment = note_commitment(&decrypted);
+        let amount = note_amount(decrypted.amount);
+
+        if transactions.last().is_none_or(|tx| tx.id != transaction_id) {
+            transactions.push(AuditedTransaction {
+                id: transaction_id.clone(),
+                ledger,
+                inputs: Vec::new(),
+                outputs: Vec::new(),
+            });
+        }
+        let tx = transactions
+            .last_mut()
+            .expect("a transaction was pushed above");
+
+        match note {
+            GvkNoteRef::Output {
+                commitment: onchain,
+                leaf_index,
+            } => {
+                let check = if commitment == onchain {
+                    outputs.insert(
+                        onchain,
+                        SpentOutput {
+                            transaction_id: transaction_id.clone(),
+                            leaf_index,
+                        },
+                    );
+                    NoteCheck::Verified
+                } else {
+                    NoteCheck::Mismatch
+                };
+                tx.outputs.push(AuditedNote {
+                    event_id,
+                    commitment: onchain,
+                    leaf_index: Some(leaf_index),
+                    nullifier: None,
+                    pk: decrypted.pk,
+                    amount,
+                    blinding: decrypted.blinding,
+                    check,
+                    spends: None,
+                });
+            }
+            GvkNoteRef::Input { nullifier } => {
+                let spends = outputs.get(&commitment).cloned();
+                let check = match (&spends, amount) {
+                    (Some(_), _) => NoteCheck::Linked,
+                    (None, Some(amount)) if amount == NoteAmount::ZERO => NoteCheck::Dummy,
+                    (None, _) => NoteCheck::Mismatch,
+                };
+                tx.inputs.push(AuditedNote {
+                    event_id,
+                    commitment,
+                    leaf_index: None,
+                    nullifier: Some(nullifier),
+                    pk: decrypted.pk,
+                    amount,
+                    blinding: decrypted.blinding,
+                    check,
+                    spends,
+                });
+            }
+        }
+    }
+
+    Ok(AuditReport {
+        pool_contract_id: pool_contract_id.to_string(),
+        admin_view_key,
+        transactions,
+    })
+}
+
+/// Events emitted by one contract invocation share the TOID before the
+/// hyphen of their id.
+fn transaction_id(event_id: &str) -> &str {
+    event_id.split_once('-').map_or(event_id, |(toid, _)| toid)
+}
+
+/// `None` if the decrypted amount does not fit a note amount, which only a
+/// wrong key or corrupt ciphertext produces.
+fn note_amount(amount: Field) -> Option<NoteAmount> {
+    let value = U256::from(amount);
+    if value > U256::from(u128::MAX) {
+        return None;
+    }
+    Some(NoteAmount::from(value.low_u128()))
+}
+
+/// Outcome of checking one decrypted note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NoteCheck {
+    /// Output note whose decryption reopens its on-chain commitment.
+    Verified,
+    /// Input note whose decryption reopens an earlier verified output.
+    Linked,
+    /// Zero-amount input matching no output: the circuit's dummy input.
+    Dummy,
+    /// The decryption matches nothing: wrong key or corrupt data.
+    Mismatch,
+}
+
+impl NoteCheck {
+    fn as_str(self) -> &'static str {
+        match self {
+            NoteCheck::Verified => "verified",
+            NoteCheck::Linked => "linked",
+            NoteCheck::Dummy => "dummy",
+            NoteCheck::Mismatch => "mismatch",
+        }
+    }
+}
+
+/// The output an input note spends.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpentOutput {
+    pub transaction_id: String,
+    pub leaf_index: u32,
+}
+
+/// One decrypted note.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditedNote {
+    pub event_id: String,
+    /// On-chain commitment for outputs; recomputed from the decryption for
+    /// inputs.
+    pub commitment: Field,
+    /// Outputs only.
+    pub leaf_index: Option<u32>,
+    /// Inputs only.
+    pub nullifier: Option<Field>,
+    pub pk: Field,
+    /// `None` when the decrypted value is not a valid amount.
+    pub amount: Option<NoteAmount>,
+    pub blinding: Field,
+    pub check: NoteCheck,
+    /// The output this input spends, when linked.
+    pub spends: Option<SpentOutput>,
+}
+
+/// The GVK notes of one pool transaction, in event order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditedTransaction {
+    /// TOID shared by the transaction's events.
+    pub id: String,
+    pub ledger: u32,
+    /// Empty for view-only pools.
+    pub inputs: Vec<AuditedNote>,
+    pub outputs: Vec<AuditedNote>,
+}
+
+/// Result of [`Auditor::audit`].
+///
+/// Carries every note's blinding, enough to open its commitment: handle it
+/// with the same care as the admin key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditReport {
+    pub pool_contract_id: String,
+    pub admin_view_key: BabyJubJubPoint,
+    pub transactions: Vec<AuditedTransaction>,
+}
+
+/// Header of [`AuditReport::to_csv`].
+const CSV_HEADER: &str = "transaction_id,ledger,event_id,direction,leaf_index,nullifier,commitment,pk,amount,blinding,check,spends_transaction_id,spends_leaf_index";
+
+impl AuditReport {
+    /// Notes that failed their check.
+    pub fn mismatches(&self) -> impl Iterator<Item = &AuditedNote> {
+        self.transactions
+            .iter()
+            .flat_map(|tx| tx.inputs.iter().chain(tx.outputs.iter()))
+            .filter(|note| note.check == NoteCheck::Mismatch)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("failed to serialize GVK audit report")
+    }
+
+    /// One row per note, inputs before outputs within a transaction.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(CSV_HEADER);
+        out.push('\n');
+        for tx in &self.transactions {
+            let notes = tx
+                .inputs
+                .iter()
+                .map(|note| ("input", note))
+                .chain(tx.outputs.iter().map(|note| ("output", note)));
+            for (direction, note) in notes {
+                let optional = |value: Option<String>| value.unwrap_or_default();
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                    tx.id,
+                    tx.ledger,
+                    note.event_id,
+                    direction,
+                    optional(note.leaf_index.map(|i| i.to_string())),
+                    optional(note.nullifier.map(|n| n.to_string())),
+                    note.commitment,
+                    note.pk,
+                    optional(note.amount.map(|a| a.to_string())),
+                    note.blinding,
+                    note.check.as_str(),
+                    optional(note.spends.as_ref().map(|s| s.transaction_id.clone())),
+                    optional(note.spends.as_ref().map(|s| s.leaf_index.to_string())),
+                )
+                .expect("writing to a String cannot fail");
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::{
+        gvk::cipher::encrypt,
+        types::{
+            ContractEvent, ContractsEventData, GlobalViewKeyCiphertext, NewCommitmentEvent,
+            NewNullifierEvent,
+        },
+    };
+
+    pub(in crate::gvk) fn field(v: u128) -> Field {
+        Field::from(NoteAmount::from(v))
+    }
+
+    fn secret(v: u128) -> Secret<[u8; 32]> {
+        Secret::new(field(v).to_le_bytes())
+    }
+
+    fn note(pk: u128, amount: u128, blinding: u128) -> DecryptedNote {
+        DecryptedNote {
+            pk: field(pk),
+            amount: field(amount),
+            blinding: field(blinding),
+        }
+    }
+
+    fn raw_event(id: &str, ledger: u32) -> ContractEvent {
+        ContractEvent {
+            id: id.to_string(),
+            ledger,
+            contract_id: "CPOOLGVK".to_string(),
+            topics: vec!["dummy".to_string()],
+            value: "dummy".to_string(),
+        }
+    }
+
+    /// Two traceable transactions: a deposit creating `paid` and a dummy
+    /// change note, then a transfer spending `paid` next to a dummy input.
+    pub(in crate::gvk) fn traceable_pool(admin: &BabyJubJubPoint) -> Result<SqliteStorage> {
+        let mut storage = SqliteStorage::connect_in_memory()?;
+        let ct = |note: &DecryptedNote, idx: u64| -> GlobalViewKeyCiphertext {
+            encrypt(note, field(idx.into()), admin, field(77), idx)
+        };
+        let paid = note(1, 500, 11);
+        let change = note(2, 0, 12);
+        let received = note(3, 500, 13);
+        let dummy_input = note(4, 0, 14);
+
+        storage.save_events_batch(&ContractsEventData {
+            cursor: "c1".to_string(),
+            latest_ledger: 20,
+            events: vec![
+                raw_event("0000000000000000010-0000000000", 10),
+                raw_event("0000000000000000010-0000000001", 10),
+                raw_event("0000000000000000020-0000000000", 20),
+                raw_event("0000000000000000020-0000000001", 20),
+                raw_event("0000000000000000020-0000000002", 20),
+            ],
+        })?;
+        storage.save_commitment_events_batch(&vec![
+            NewCommitmentEvent {
+                id: "0000000000000000010-0000000000".to_string(),
+                commitment: note_commitment(&paid),
+                index: 0,
+                encrypted_output: vec![],
+                gvk_ciphertext: Some(ct(&paid, 2)),
+            },
+            NewCommitmentEvent {
+                id: "0000000000000000010-0000000001".to_string(),
+                commitment: note_commitment(&change),
+                index: 1,
+                encrypted_output: vec![],
+                gvk_ciphertext: Some(ct(&change, 3)),
+            },
+            NewCommitmentEvent {
+                id: "0000000000000000020-0000000002".to_string(),
+                commitment: note_commitment(&received),
+                index: 2,
+                encrypted_output: vec![],
+                gvk_ciphertext: Some(ct(&received, 2)),
+            },
+        ])?;
+        storage.save_nullifier_events_batch(&vec![
+            NewNullifierEvent {
+                id: "0000000000000000020-0000000000".to_string(),
+                nullifier: field(901),
+                gvk_ciphertext: Some(ct(&paid, 0)),
+            },
+            NewNullifierEvent {
+                id: "0000000000000000020-0000000001".to_string(),
+                nullifier: field(902),
+                gvk_ciphertext: Some(ct(&dummy_input, 1)),
+            },
+        ])?;
+        Ok(storage)
+    }
+
+    #[test]
+    fn audit_verifies_outputs_and_links_inputs() -> Result<()> {
+        let auditor = Auditor::new(secret(0xC0FFEE))?;
+        let storage = traceable_pool(&auditor.admin_view_key())?;
+
+        let report = auditor.audit(&storage, "CPOOLGVK")?;
+
+        assert_eq!(report.transactions.len(), 2);
+        let deposit = &report.transactions[0];
+        assert_eq!(deposit.id, "0000000000000000010");
+        assert!(deposit.inputs.is_empty());
+        assert!(
+            deposit
+                .outputs
+                .iter()
+                .all(|note| note.check == NoteCheck::Verified)
+        );
+        assert_eq!(deposit.outputs[0].amount, Some(NoteAmount::from(500u128)));
+
+        let transfer = &report.transactions[1];
+        assert_eq!(transfer.inputs[0].check, NoteCheck::Linked);
+        assert_eq!(
+            transfer.inputs[0].spends,
+            Some(SpentOutput {
+                transaction_id: "0000000000000000010".to_string(),
+                leaf_index: 0,
+            })
+        );
+        assert_eq!(transfer.inputs[0].commitment, deposit.outputs[0].commitment);
+        assert_eq!(transfer.inputs[1].check, NoteCheck::Dummy);
+        assert_eq!(transfer.outputs[0].check, NoteCheck::Verified);
+        assert_eq!(report.mismatches().count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn audit_with_the_wrong_key_reports_mismatches() -> Result<()> {
+        let admin = Auditor::new(secret(0xC0FFEE))?;
+        let storage = traceable_pool(&admin.admin_view_key())?;
+
+        let report = Auditor::new(secret(0xBEEF))?.audit(&storage, "CPOOLGVK")?;
+
+        assert_eq!(report.mismatches().count(), 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn report_renders_as_json_and_csv() -> Result<()> {
+        let auditor = Auditor::new(secret(0xC0FFEE))?;
+        let storage = traceable_pool(&auditor.admin_view_key())?;
+        let report = auditor.audit(&storage, "CPOOLGVK")?;
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()?)?;
+        assert_eq!(json["transactions"][1]["inputs"][0]["check"], "linked");
+        assert_eq!(
+            json["transactions"][1]["inputs"][0]["spends"]["transactionId"],
+            "0000000000000000010"
+        );
+
+        let csv = report.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(lines.len(), 6);
+        let header_columns = CSV_HEADER.split(',').count();
+        assert!(
+            lines
+                .iter()
+                .all(|line| line.split(',').count() == header_columns)
+        );
+        assert!(
+            lines[3].starts_with("0000000000000000020,20,0000000000000000020-0000000000,input,")
+        );
+        assert!(lines[3].ends_with(",linked,0000000000000000010,0"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn auditor_rejects_zero_key() {
+        assert!(Auditor::new(Secret::new([0u8; 32])).is_err());
+    }
+}
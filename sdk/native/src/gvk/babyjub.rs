@@ -0,0 +1,155 @@
+//! Baby JubJub curve arithmetic in circomlib coordinates.
+//!
+//! Ported from `circuits/src/test/utils/babyjub.rs`. `ark-ed-on-bn254` uses a
+//! different curve form than circomlib, so points computed with it would not
+//! match the circuit's `R` and `D`.
+
+// Finite-field arithmetic cannot overflow.
+#![allow(clippy::arithmetic_side_effects)]
+
+use crate::{
+    types::BabyJubJubPoint,
+    zk::serialization::{field_to_scalar, scalar_to_field},
+};
+use ark_bn254::Fr as Scalar;
+use ark_ff::{BigInteger, Field as _, One, PrimeField, Zero};
+use core::str::FromStr;
+
+/// The circomlib Baby JubJub generator `BASE8` (prime-order subgroup).
+const BASE8_X: &str =
+    "5299619240641551281634865583518297030282874472190772894086521144482721001553";
+const BASE8_Y: &str =
+    "16950150798460657717958625567821834550301663161624707787222815936182638968203";
+
+/// Curve coefficient `a`.
+fn coeff_a() -> Scalar {
+    Scalar::from(168700u64)
+}
+
+/// Curve coefficient `d`.
+fn coeff_d() -> Scalar {
+    Scalar::from(168696u64)
+}
+
+/// An affine Baby JubJub point. The identity is `(0, 1)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Point {
+    pub x: Scalar,
+    pub y: Scalar,
+}
+
+impl Point {
+    pub fn from_app(point: &BabyJubJubPoint) -> Self {
+        Point {
+            x: field_to_scalar(&point.x),
+            y: field_to_scalar(&point.y),
+        }
+    }
+
+    pub fn to_app(self) -> BabyJubJubPoint {
+        BabyJubJubPoint {
+            x: scalar_to_field(&self.x),
+            y: scalar_to_field(&self.y),
+        }
+    }
+}
+
+/// The group identity `(0, 1)`.
+pub(crate) fn identity() -> Point {
+    Point {
+        x: Scalar::zero(),
+        y: Scalar::one(),
+    }
+}
+
+/// The circomlib `BASE8` generator.
+pub(crate) fn base8() -> Point {
+    Point {
+        x: Scalar::from_str(BASE8_X).expect("Invalid BASE8 x coordinate"),
+        y: Scalar::from_str(BASE8_Y).expect("Invalid BASE8 y coordinate"),
+    }
+}
+
+/// Complete twisted Edwards addition, matching circomlib's `BabyAdd`.
+///
+/// The denominators never vanish for points on the curve; callers must
+/// check untrusted points with [`is_on_curve`] first.
+pub(crate) fn add(p: Point, q: Point) -> Point {
+    let a = coeff_a();
+    let d = coeff_d();
+    let beta = p.x * q.y;
+    let gamma = p.y * q.x;
+    let delta = (p.y - a * p.x) * (q.x + q.y);
+    let tau = beta * gamma;
+
+    let x_den = (Scalar::one() + d * tau)
+        .inverse()
+        .expect("BabyJubJub addition denominator is never zero");
+    let y_den = (Scalar::one() - d * tau)
+        .inverse()
+        .expect("BabyJubJub addition denominator is never zero");
+
+    Point {
+        x: (beta + gamma) * x_den,
+        y: (delta + a * beta - gamma) * y_den,
+    }
+}
+
+/// Point doubling, matching circomlib's `BabyDbl`.
+pub(crate) fn double(p: Point) -> Point {
+    add(p, p)
+}
+
+/// Multiply `point` by the integer value of `scalar` via double-and-add,
+/// yielding the same group element as the circuit's `EscalarMulFix` /
+/// `EscalarMulAny`.
+pub(crate) fn scalar_mul(point: Point, scalar: Scalar) -> Point {
+    let mut acc = identity();
+    let mut base = point;
+    for bit in scalar.into_bigint().to_bits_le() {
+        if bit {
+            acc = add(acc, base);
+        }
+        base = double(base);
+    }
+    acc
+}
+
+/// Clear the cofactor: return `8 * point` (three doublings), matching the
+/// circuit's `8*D`.
+pub(crate) fn mul8(point: Point) -> Point {
+    double(double(double(point)))
+}
+
+/// Whether `point` satisfies the Baby JubJub curve equation.
+pub(crate) fn is_on_curve(point: Point) -> bool {
+    let a = coeff_a();
+    let d = coeff_d();
+    let x2 = point.x * point.x;
+    let y2 = point.y * point.y;
+    a * x2 + y2 == Scalar::one() + d * x2 * y2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base8_is_on_curve_and_has_prime_order() {
+        let g = base8();
+        assert!(is_on_curve(g));
+
+        // The prime-order subgroup order `l`.
+        let l = Scalar::from_str(
+            "2736030358979909402780800718157159386076813972158567259200215660948447373041",
+        )
+        .expect("subgroup order");
+        assert_eq!(scalar_mul(g, l), identity());
+    }
+
+    #[test]
+    fn mul8_matches_scalar_mul_by_eight() {
+        let g = base8();
+        assert_eq!(mul8(g), scalar_mul(g, Scalar::from(8u64)));
+    }
+}
@@ -0,0 +1,145 @@
+//! Admin-side Global View Key decryption.
+//!
+//! Mirrors the encryption in `circuits/src/globalViewKey.circom` and the
+//! reference implementation in `circuits/src/test/utils/global_view_key.rs`.
+//! See `docs/src/global_view_key.md` for the scheme.
+
+// Finite-field arithmetic cannot overflow.
+#![allow(clippy::arithmetic_side_effects)]
+
+use super::babyjub::{Point, base8, is_on_curve, mul8, scalar_mul};
+use crate::{
+    types::{BabyJubJubPoint, Field, GlobalViewKeyCiphertext},
+    zk::{
+        crypto::poseidon2_hash3_internal,
+        serialization::{field_to_scalar, scalar_to_field},
+    },
+};
+use anyhow::{Result, anyhow};
+use ark_bn254::Fr as Scalar;
+use taceo_poseidon2::bn254::t4;
+
+/// Domain separation for the keystream KDF.
+const DOM_KDF: u64 = 0x06;
+
+/// The plaintext an administrator recovers from a GVK ciphertext.
+///
+/// Decryption always yields *some* field elements: a wrong key or a corrupt
+/// ciphertext is only detected by recomputing the note commitment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecryptedNote {
+    pub pk: Field,
+    pub amount: Field,
+    pub blinding: Field,
+}
+
+/// The administrator view key `D = d * BASE8` for the private scalar `d`.
+pub(crate) fn admin_view_key(d: Scalar) -> BabyJubJubPoint {
+    scalar_mul(base8(), d).to_app()
+}
+
+/// The three keystream pads from a single width-4 Poseidon2 permutation over
+/// `(S.x, S.y, 0, 0x06)`.
+fn keystream(s: Point) -> [Scalar; 3] {
+    let perm = t4::permutation(&[s.x, s.y, Scalar::from(0u64), Scalar::from(DOM_KDF)]);
+    [perm[0], perm[1], perm[2]]
+}
+
+/// Decrypt `ct` with the administrator private scalar `d`.
+///
+/// The circuit computes `S = r * (8*D)`, so the shared secret is recovered
+/// as `8 * (d * R)`. Errors if `R` is not on the curve, which the circuit
+/// never produces.
+pub(crate) fn decrypt(ct: &GlobalViewKeyCiphertext, d: Scalar) -> Result<DecryptedNote> {
+    let big_r = Point::from_app(&ct.r);
+    if !is_on_curve(big_r) {
+        return Err(anyhow!("GVK ciphertext R is not on the Baby JubJub curve"));
+    }
+    Ok(decrypt_with_shared_point(ct, scalar_mul(big_r, d)))
+}
+
+/// Decrypt `ct` from the shared point `P = d * R`, without `d`.
+///
+/// Lets a verifier holding a proof that `P` matches the admin view key
+/// recompute the plaintext on its own.
+pub(crate) fn decrypt_with_shared_point(ct: &GlobalViewKeyCiphertext, p: Point) -> DecryptedNote {
+    let k = keystream(mul8(p));
+    DecryptedNote {
+        pk: scalar_to_field(&(field_to_scalar(&ct.c1) - k[0])),
+        amount: scalar_to_field(&(field_to_scalar(&ct.c2) - k[1])),
+        blinding: scalar_to_field(&(field_to_scalar(&ct.c3) - k[2])),
+    }
+}
+
+/// `commitment = Poseidon2(amount, pk, blinding)` with domain `0x01`.
+pub(crate) fn note_commitment(note: &DecryptedNote) -> Field {
+    scalar_to_field(&poseidon2_hash3_internal(
+        field_to_scalar(&note.amount),
+        field_to_scalar(&note.pk),
+        field_to_scalar(&note.blinding),
+        Some(Scalar::from(1u64)),
+    ))
+}
+
+/// Encrypt a note exactly as the circuit does, for building test fixtures.
+#[cfg(test)]
+pub(crate) fn encrypt(
+    note: &DecryptedNote,
+    salt: Field,
+    d_pub: &BabyJubJubPoint,
+    nonce: Field,
+    idx: u64,
+) -> GlobalViewKeyCiphertext {
+    let dom = Some(Scalar::from(0x05u64));
+    let d_pub_point = Point::from_app(d_pub);
+    let h1 = poseidon2_hash3_internal(
+        field_to_scalar(&note.pk),
+        field_to_scalar(&note.amount),
+        field_to_scalar(&note.blinding),
+        dom,
+    );
+    let h2 = poseidon2_hash3_internal(h1, field_to_scalar(&salt), d_pub_point.x, dom);
+    let h3 = poseidon2_hash3_internal(h2, d_pub_point.y, field_to_scalar(&nonce), dom);
+    let r = poseidon2_hash3_internal(h3, Scalar::from(idx), Scalar::from(0u64), dom);
+
+    let k = keystream(scalar_mul(mul8(d_pub_point), r));
+    GlobalViewKeyCiphertext {
+        r: scalar_mul(base8(), r).to_app(),
+        c1: scalar_to_field(&(field_to_scalar(&note.pk) + k[0])),
+        c2: scalar_to_field(&(field_to_scalar(&note.amount) + k[1])),
+        c3: scalar_to_field(&(field_to_scalar(&note.blinding) + k[2])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note() -> DecryptedNote {
+        DecryptedNote {
+            pk: Field::from(crate::types::NoteAmount::from(101u128)),
+            amount: Field::from(crate::types::NoteAmount::from(1_000u128)),
+            blinding: Field::from(crate::types::NoteAmount::from(202u128)),
+        }
+    }
+
+    #[test]
+    fn decrypt_round_trips_with_the_admin_scalar() -> Result<()> {
+        let d = Scalar::from(0xC0FFEEu64);
+        let ct = encrypt(&note(), Field::ONE, &admin_view_key(d), Field::ONE, 2);
+
+        assert_eq!(decrypt(&ct, d)?, note());
+        assert_ne!(decrypt(&ct, Scalar::from(0xBEEFu64))?, note());
+
+        Ok(())
+    }
+
+    #[test]
+    fn decrypt_rejects_off_curve_r() {
+        let d = Scalar::from(0xC0FFEEu64);
+        let mut ct = encrypt(&note(), Field::ONE, &admin_view_key(d), Field::ONE, 2);
+        ct.r.x = Field::ONE;
+
+        assert!(decrypt(&ct, d).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+//! Chaum-Pedersen proof of discrete-log equality over Baby JubJub.
+//!
+//! Proves `D = d * BASE8` and `P = d * R` for the same secret `d` without
+//! revealing it. The auditor uses it to show that the shared point behind a
+//! GVK decryption really is the one its published view key determines.
+//!
+//! Scalars live modulo the prime subgroup order `l`, not the BN254 field, so
+//! they are handled as `ruint` integers rather than `Fr` elements.
+
+use super::babyjub::{Point, add, base8, identity, is_on_curve, scalar_mul};
+use crate::{
+    types::{Field, GvkDleqProof},
+    zk::serialization::scalar_to_bytes,
+};
+use anyhow::{Result, anyhow};
+use ark_bn254::Fr as Scalar;
+use ark_ff::PrimeField;
+use ruint::aliases::{U256, U512};
+use sha2::{Digest, Sha512};
+
+/// Order `l` of the Baby JubJub subgroup generated by `BASE8`.
+const SUBGROUP_ORDER: &str =
+    "2736030358979909402780800718157159386076813972158567259200215660948447373041";
+
+/// Domain separation for the Fiat-Shamir challenge.
+const CHALLENGE_DOMAIN: &[u8] = b"stellar-private-payments/gvk-dleq/v1";
+
+pub(super) fn subgroup_order() -> U256 {
+    U256::from_str_radix(SUBGROUP_ORDER, 10).expect("Baby JubJub subgroup order is a valid integer")
+}
+
+/// Reduce 64 uniformly random bytes modulo `l`.
+fn wide_reduce(bytes: [u8; 64]) -> U256 {
+    let order = U512::from_str_radix(SUBGROUP_ORDER, 10)
+        .expect("Baby JubJub subgroup order is a valid integer");
+    let wide = U512::from_le_bytes(bytes).reduce_mod(order);
+    U256::from_le_slice(&wide.to_le_bytes::<64>()[..32])
+}
+
+/// A uniformly random integer modulo `l`.
+pub(super) fn random_scalar() -> Result<U256> {
+    let mut bytes = [0u8; 64];
+    getrandom::getrandom(&mut bytes).map_err(|e| anyhow!("Random generation failed: {}", e))?;
+    Ok(wide_reduce(bytes))
+}
+
+pub(super) fn to_scalar(value: U256) -> Scalar {
+    Scalar::from_le_bytes_mod_order(&value.to_le_bytes::<32>())
+}
+
+fn scalar_mod_order(value: Scalar) -> U256 {
+    U256::from_le_slice(&scalar_to_bytes(&value)).reduce_mod(subgroup_order())
+}
+
+/// Whether `point` is on the curve and in the prime-order subgroup.
+pub(crate) fn in_subgroup(point: Point) -> bool {
+    is_on_curve(point) && scalar_mul(point, to_scalar(subgroup_order())) == identity()
+}
+
+fn challenge(d: Point, r: Point, p: Point, a1: Point, a2: Point) -> U256 {
+    let mut hasher = Sha512::new();
+    hasher.update(CHALLENGE_DOMAIN);
+    for point in [base8(), d, r, p, a1, a2] {
+        hasher.update(scalar_to_bytes(&point.x));
+        hasher.update(scalar_to_bytes(&point.y));
+    }
+    wide_reduce(hasher.finalize().into())
+}
+
+/// Prove `D = d * BASE8` and `P = d * R`, where `D` and `P` are computed here.
+pub(crate) fn prove(d: Scalar, r: Point) -> Result<(Point, GvkDleqProof)> {
+    let order = subgroup_order();
+    let d_pub = scalar_mul(base8(), d);
+    let p = scalar_mul(r, d);
+
+    let k = random_scalar()?;
+
+    let a1 = scalar_mul(base8(), to_scalar(k));
+    let a2 = scalar_mul(r, to_scalar(k));
+    let c = challenge(d_pub, r, p, a1, a2);
+    let response = k.add_mod(c.mul_mod(scalar_mod_order(d), order), order);
+
+    let proof = GvkDleqProof {
+        a1: a1.to_app(),
+        a2: a2.to_app(),
+        response: Field::try_from_le_bytes(response.to_le_bytes::<32>())?,
+    };
+    Ok((p, proof))
+}
+
+/// Check a proof that `P = d * R` for the `d` behind `D`.
+///
+/// Errors if any point is off the curve, or if `D`, `R` or `P` is outside
+/// the prime-order subgroup, where the equality would not bind `d`.
+pub(crate) fn verify(d_pub: Point, r: Point, p: Point, proof: &GvkDleqProof) -> Result<bool> {
+    let a1 = Point::from_app(&proof.a1);
+    let a2 = Point::from_app(&proof.a2);
+    for (name, point) in [("D", d_pub), ("R", r), ("P", p)] {
+        if !in_subgroup(point) {
+            return Err(anyhow!(
+                "{name} is not in the Baby JubJub prime-order subgroup"
+            ));
+        }
+    }
+    if d_pub == identity() {
+        return Err(anyhow!("D must not be the identity"));
+    }
+    if !is_on_curve(a1) || !is_on_curve(a2) {
+        return Err(anyhow!("DLEQ commitments are not on the Baby JubJub curve"));
+    }
+    let response = U256::from_le_bytes(proof.response.to_le_bytes());
+    if response >= subgroup_order() {
+        return Err(anyhow!(
+            "DLEQ response is not reduced modulo the subgroup order"
+        ));
+    }
+
+    let c = to_scalar(challenge(d_pub, r, p, a1, a2));
+    let z = to_scalar(response);
+    Ok(scalar_mul(base8(), z) == add(a1, scalar_mul(d_pub, c))
+        && scalar_mul(r, z) == add(a2, scalar_mul(p, c)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ephemeral() -> Point {
+        scalar_mul(base8(), Scalar::from(0x5EEDu64))
+    }
+
+    #[test]
+    fn proof_round_trips() -> Result<()> {
+        let d = Scalar::from(0xC0FFEEu64);
+        let (p, proof) = prove(d, ephemeral())?;
+
+        assert_eq!(p, scalar_mul(ephemeral(), d));
+        assert!(verify(scalar_mul(base8(), d), ephemeral(), p, &proof)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn proof_does_not_verify_for_another_key_or_point() -> Result<()> {
+        let d = Scalar::from(0xC0FFEEu64);
+        let (p, proof) = prove(d, ephemeral())?;
+        let other = Scalar::from(0xBEEFu64);
+
+        assert!(!verify(scalar_mul(base8(), other), ephemeral(), p, &proof)?);
+        assert!(!verify(
+            scalar_mul(base8(), d),
+            ephemeral(),
+            scalar_mul(ephemeral(), other),
+            &proof
+        )?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn verify_rejects_points_outside_the_subgroup() -> Result<()> {
+        let d = Scalar::from(0xC0FFEEu64);
+        let (p, proof) = prove(d, ephemeral())?;
+        let off_curve = Point {
+            x: Scalar::from(1u64),
+            y: Scalar::from(1u64),
+        };
+
+        assert!(verify(off_curve, ephemeral(), p, &proof).is_err());
+        assert!(verify(identity(), ephemeral(), p, &proof).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+//! Global View Key (GVK) administrator tooling.
+//!
+//! [`Auditor`] decrypts the GVK ciphertexts the indexer stored for a
+//! `pool-gvk` pool with the administrator's private scalar `d`, checks each
+//! plaintext against the on-chain commitments and, for traceable pools,
+//! links spent notes to the outputs that created them. It can also issue a
+//! [`GvkDecryptionReceipt`](crate::types::GvkDecryptionReceipt) for a single
+//! note, which anyone can check with [`verify_gvk_decryption_receipt`]
+//! without learning `d`. For pools whose `D` must not have a single owner,
+//! [`DkgParticipant`] generates it among several auditors and
+//! [`ThresholdAuditor`] combines their partial decryptions. See
+//! `docs/src/global_view_key.md` for the scheme.
+
+mod auditor;
+mod babyjub;
+mod cipher;
+mod dleq;
+mod receipt;
+mod threshold;
+
+pub use auditor::{AuditReport, AuditedNote, AuditedTransaction, Auditor, NoteCheck, SpentOutput};
+pub use cipher::DecryptedNote;
+pub use receipt::{verify_gvk_decryption_receipt, verify_gvk_decryption_receipt_offline};
+pub use threshold::{DkgParticipant, KeyShare, ThresholdAuditor};
@@ -0,0 +1,152 @@
+//! Third-party verification of GVK decryption receipts.
+
+use super::{
+    babyjub::Point,
+    cipher::{DecryptedNote, decrypt_with_shared_point, note_commitment},
+    dleq,
+};
+use crate::{
+    chain::StateFetcher,
+    error::Error,
+    types::{BabyJubJubPoint, Field, GvkDecryptionReceipt, GvkDecryptionVerificationReport},
+};
+use anyhow::Result;
+
+/// Verify the cryptographic content of `receipt` against the pool's
+/// on-chain view key `pool_admin_view_key`, if known.
+///
+/// Errors on a malformed receipt: unsupported version, or points off the
+/// curve or outside the prime-order subgroup.
+pub fn verify_gvk_decryption_receipt_offline(
+    receipt: &GvkDecryptionReceipt,
+    pool_admin_view_key: Option<&BabyJubJubPoint>,
+) -> Result<GvkDecryptionVerificationReport> {
+    receipt.validate()?;
+    let shared_point = Point::from_app(&receipt.shared_point);
+    let proof_verified = dleq::verify(
+        Point::from_app(&receipt.admin_view_key),
+        Point::from_app(&receipt.ciphertext.r),
+        shared_point,
+        &receipt.proof,
+    )?;
+
+    let opening = DecryptedNote {
+        pk: receipt.note_public_key,
+        amount: Field::from(receipt.amount),
+        blinding: receipt.blinding,
+    };
+    let decryption_verified =
+        decrypt_with_shared_point(&receipt.ciphertext, shared_point) == opening;
+    let opening_verified = note_commitment(&opening) == receipt.commitment;
+
+    Ok(GvkDecryptionVerificationReport {
+        proof_verified,
+        decryption_verified,
+        opening_verified,
+        admin_view_key_matches_pool: pool_admin_view_key == Some(&receipt.admin_view_key),
+    })
+}
+
+/// Verify a GVK decryption receipt: DLEQ proof, decryption, commitment
+/// opening, and that the receipt's view key is the pool's on-chain key.
+///
+/// Does not check that the pool emitted `receipt.ciphertext` for
+/// `receipt.commitment`; compare against the indexed pool events for that.
+pub async fn verify_gvk_decryption_receipt(
+    fetcher: &StateFetcher,
+    receipt: &GvkDecryptionReceipt,
+) -> Result<GvkDecryptionVerificationReport, Error> {
+    let pool_admin_view_key = fetcher
+        .contracts_data_for_pool(&receipt.pool_address)
+        .await
+        .map_err(|e| Error::Other(format!("pool state fetch failed: {e:#}")))?
+        .pools
+        .into_iter()
+        .next()
+        .and_then(|pool| pool.admin_view_key);
+    verify_gvk_decryption_receipt_offline(receipt, pool_admin_view_key.as_ref())
+        .map_err(|e| Error::Other(format!("GVK receipt verification failed: {e:#}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gvk::{Auditor, cipher::encrypt},
+        state::{GvkNoteRef, PoolGvkCiphertextRow},
+        types::{NoteAmount, Secret},
+    };
+
+    fn field(v: u128) -> Field {
+        Field::from(NoteAmount::from(v))
+    }
+
+    fn receipt(auditor: &Auditor) -> Result<GvkDecryptionReceipt> {
+        let note = DecryptedNote {
+            pk: field(1),
+            amount: field(500),
+            blinding: field(11),
+        };
+        let ciphertext = encrypt(&note, field(2), &auditor.admin_view_key(), field(77), 2);
+        auditor.receipt_for_row(
+            "CPOOLGVK",
+            PoolGvkCiphertextRow {
+                event_id: "0000000000000000010-0000000000".to_string(),
+                ledger: 10,
+                note: GvkNoteRef::Output {
+                    commitment: note_commitment(&note),
+                    leaf_index: 0,
+                },
+                ciphertext,
+            },
+        )
+    }
+
+    #[test]
+    fn receipt_verifies_against_the_pool_key() -> Result<()> {
+        let auditor = Auditor::new(Secret::new(field(0xC0FFEE).to_le_bytes()))?;
+        let receipt = receipt(&auditor)?;
+
+        let report =
+            verify_gvk_decryption_receipt_offline(&receipt, Some(&auditor.admin_view_key()))?;
+        assert!(report.is_valid());
+
+        let other = Auditor::new(Secret::new(field(0xBEEF).to_le_bytes()))?;
+        let report =
+            verify_gvk_decryption_receipt_offline(&receipt, Some(&other.admin_view_key()))?;
+        assert!(!report.admin_view_key_matches_pool);
+        assert!(report.proof_verified);
+
+        Ok(())
+    }
+
+    #[test]
+    fn tampered_receipt_fails_verification() -> Result<()> {
+        let auditor = Auditor::new(Secret::new(field(0xC0FFEE).to_le_bytes()))?;
+        let key = auditor.admin_view_key();
+
+        let mut wrong_amount = receipt(&auditor)?;
+        wrong_amount.amount = NoteAmount::from(501u128);
+        let report = verify_gvk_decryption_receipt_offline(&wrong_amount, Some(&key))?;
+        assert!(!report.decryption_verified);
+        assert!(!report.opening_verified);
+
+        let mut wrong_commitment = receipt(&auditor)?;
+        wrong_commitment.commitment = field(42);
+        let report = verify_gvk_decryption_receipt_offline(&wrong_commitment, Some(&key))?;
+        assert!(report.decryption_verified);
+        assert!(!report.opening_verified);
+
+        let mut forged_key = receipt(&auditor)?;
+        forged_key.admin_view_key =
+            Auditor::new(Secret::new(field(0xBEEF).to_le_bytes()))?.admin_view_key();
+        let report = verify_gvk_decryption_receipt_offline(&forged_key, Some(&key))?;
+        assert!(!report.proof_verified);
+
+        let mut wrong_version = receipt(&auditor)?;
+        wrong_version.version = 2;
+        assert!(verify_gvk_decryption_receipt_offline(&wrong_version, Some(&key)).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,552 @@
+//! Threshold Global View Key shared among several auditors.
+//!
+//! `n` auditors run a Feldman-verified distributed key generation so that `D`
+//! is published without anyone ever holding `d`: each participant deals a
+//! random polynomial of degree `t - 1`, and `d` is the sum of their constant
+//! terms. Decryption needs `t` partial decryptions `x_i * R`, each with a
+//! DLEQ proof against the participant's verification key, combined by
+//! Lagrange interpolation in the exponent.
+//!
+//! Participant indices start at 1. Scalars live modulo the subgroup order
+//! `l`, as in [`dleq`].
+
+use super::{
+    auditor::{AuditReport, build_report},
+    babyjub::{Point, add, base8, identity, is_on_curve, scalar_mul},
+    cipher::decrypt_with_shared_point,
+    dleq::{self, in_subgroup, random_scalar, subgroup_order, to_scalar},
+};
+use crate::{
+    state::{PoolGvkCiphertextRow, SqliteStorage},
+    types::{
+        BabyJubJubPoint, Field, GvkDkgDealing, GvkDkgShare, GvkPartialDecryption,
+        GvkThresholdPublicKey, Secret,
+    },
+};
+use anyhow::{Context, Result, anyhow};
+use ark_bn254::Fr as Scalar;
+use ruint::aliases::U256;
+use std::collections::{BTreeMap, HashMap};
+
+fn field_from_u256(value: U256) -> Result<Field> {
+    Field::try_from_le_bytes(value.to_le_bytes::<32>())
+}
+
+fn u256_from_field(value: Field) -> U256 {
+    U256::from_le_bytes(value.to_le_bytes())
+}
+
+/// Evaluates the polynomial with `coefficients` (constant term first) at `x`
+/// modulo `l`.
+fn evaluate(coefficients: &[U256], x: u32) -> U256 {
+    let order = subgroup_order();
+    let x = U256::from(x);
+    coefficients.iter().rev().fold(U256::ZERO, |acc, c| {
+        acc.mul_mod(x, order).add_mod(*c, order)
+    })
+}
+
+/// Evaluates the committed polynomial `sum_k x^k * commitments[k]`.
+fn evaluate_commitments(commitments: &[Point], x: u32) -> Point {
+    commitments.iter().rev().fold(identity(), |acc, c| {
+        add(scalar_mul(acc, Scalar::from(x)), *c)
+    })
+}
+
+/// Lagrange coefficient at zero of `participant` within `participants`,
+/// modulo `l`.
+fn lagrange_at_zero(participant: u32, participants: &[u32]) -> Result<U256> {
+    let order = subgroup_order();
+    let i = U256::from(participant);
+    let (numerator, denominator) = participants
+        .iter()
+        .filter(|&&m| m != participant)
+        .map(|&m| U256::from(m))
+        .fold((U256::from(1u64), U256::from(1u64)), |(num, den), m| {
+            let diff = m.add_mod(order.wrapping_sub(i), order);
+            (num.mul_mod(m, order), den.mul_mod(diff, order))
+        });
+    let inverse = denominator
+        .inv_mod(order)
+        .ok_or_else(|| anyhow!("duplicate participant index {participant}"))?;
+    Ok(numerator.mul_mod(inverse, order))
+}
+
+fn check_parameters(participant: u32, threshold: u32, participants: u32) -> Result<()> {
+    if threshold == 0 || threshold > participants {
+        return Err(anyhow!(
+            "GVK threshold {threshold} is invalid for {participants} participants"
+        ));
+    }
+    if participant == 0 || participant > participants {
+        return Err(anyhow!(
+            "participant index {participant} is outside 1..={participants}"
+        ));
+    }
+    Ok(())
+}
+
+/// One participant's state during the distributed key generation.
+///
+/// 1. Broadcast [`DkgParticipant::dealing`] to every participant.
+/// 2. Send [`DkgParticipant::share_for`] privately to each participant,
+///    including this one.
+/// 3. Once every dealing and every share addressed to this participant has
+///    arrived, call [`DkgParticipant::finish`]. It fails, naming the dealer,
+///    if a share does not match its dealing; the participants must then
+///    exclude that dealer and start over.
+///
+/// A dealer who sees the others' dealings before publishing its own can bias
+/// `D`. That does not help decrypt anything, but a coordinator should collect
+/// every dealing before forwarding any.
+pub struct DkgParticipant {
+    index: u32,
+    threshold: u32,
+    participants: u32,
+    coefficients: Secret<Vec<[u8; 32]>>,
+}
+
+impl std::fmt::Debug for DkgParticipant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DkgParticipant")
+            .field("index", &self.index)
+            .field("threshold", &self.threshold)
+            .field("participants", &self.participants)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DkgParticipant {
+    /// Starts a `threshold`-of-`participants` key generation as participant
+    /// `index`, drawing a fresh random polynomial.
+    pub fn new(index: u32, threshold: u32, participants: u32) -> Result<Self> {
+        check_parameters(index, threshold, participants)?;
+        let mut coefficients = Vec::new();
+        for _ in 0..threshold {
+            coefficients.push(random_scalar()?.to_le_bytes::<32>());
+        }
+        Ok(Self {
+            index,
+            threshold,
+            participants,
+            coefficients: Secret::new(coefficients),
+        })
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    fn coefficients(&self) -> Vec<U256> {
+        self.coefficients
+            .expose()
+            .iter()
+            .map(|bytes| U256::from_le_bytes(*bytes))
+            .collect()
+    }
+
+    /// The public commitments to this participant's polynomial.
+    pub fn dealing(&self) -> GvkDkgDealing {
+        GvkDkgDealing {
+            dealer: self.index,
+            commitments: self
+                .coefficients()
+                .into_iter()
+                .map(|a| scalar_mul(base8(), to_scalar(a)).to_app())
+                .collect(),
+        }
+    }
+
+    /// The secret share this participant deals to `recipient`.
+    pub fn share_for(&self, recipient: u32) -> Result<GvkDkgShare> {
+        check_parameters(recipient, self.threshold, self.participants)?;
+        Ok(GvkDkgShare {
+            dealer: self.index,
+            recipient,
+            value: field_from_u256(evaluate(&self.coefficients(), recipient))?,
+        })
+    }
+
+    /// Verifies every received share against its dealing and derives this
+    /// participant's key share and the group's public key.
+    pub fn finish(
+        self,
+        dealings: &[GvkDkgDealing],
+        shares: &[GvkDkgShare],
+    ) -> Result<(KeyShare, GvkThresholdPublicKey)> {
+        let threshold =
+            usize::try_from(self.threshold).map_err(|_| anyhow!("GVK threshold out of range"))?;
+        let dealings: BTreeMap<u32, &GvkDkgDealing> = dealings
+            .iter()
+            .map(|dealing| (dealing.dealer, dealing))
+            .collect();
+        let shares: BTreeMap<u32, &GvkDkgShare> = shares
+            .iter()
+            .filter(|share| share.recipient == self.index)
+            .map(|share| (share.dealer, share))
+            .collect();
+
+        let order = subgroup_order();
+        let mut secret = U256::ZERO;
+        let mut group_commitments = vec![identity(); threshold];
+        for dealer in 1..=self.participants {
+            let dealing = dealings
+                .get(&dealer)
+                .ok_or_else(|| anyhow!("missing dealing from participant {dealer}"))?;
+            let share = shares
+                .get(&dealer)
+                .ok_or_else(|| anyhow!("missing share from participant {dealer}"))?;
+            if dealing.commitments.len() != threshold {
+                return Err(anyhow!(
+                    "dealing from participant {dealer} has {} commitments, expected {threshold}",
+                    dealing.commitments.len()
+                ));
+            }
+            let commitments: Vec<Point> = dealing.commitments.iter().map(Point::from_app).collect();
+            if !commitments.iter().all(|c| in_subgroup(*c)) {
+                return Err(anyhow!(
+                    "dealing from participant {dealer} has a commitment outside the subgroup"
+                ));
+            }
+            let value = u256_from_field(share.value);
+            if value >= order
+                || scalar_mul(base8(), to_scalar(value))
+                    != evaluate_commitments(&commitments, self.index)
+            {
+                return Err(anyhow!(
+                    "share from participant {dealer} does not match its dealing"
+                ));
+            }
+
+            secret = secret.add_mod(value, order);
+            for (sum, c) in group_commitments.iter_mut().zip(&commitments) {
+                *sum = add(*sum, *c);
+            }
+        }
+
+        let admin_view_key = group_commitments
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow!("GVK threshold must be positive"))?;
+        if admin_view_key == identity() {
+            return Err(anyhow!("distributed key generation produced the identity"));
+        }
+        let public = GvkThresholdPublicKey {
+            threshold: self.threshold,
+            admin_view_key: admin_view_key.to_app(),
+            verification_keys: (1..=self.participants)
+                .map(|i| evaluate_commitments(&group_commitments, i).to_app())
+                .collect(),
+        };
+        let key_share = KeyShare::new(
+            self.index,
+            Secret::new(secret.to_le_bytes::<32>()),
+            public.clone(),
+        )?;
+        Ok((key_share, public))
+    }
+}
+
+/// A participant's share `x_i` of a threshold GVK.
+#[derive(Debug)]
+pub struct KeyShare {
+    index: u32,
+    x: Secret<[u8; 32]>,
+    public: GvkThresholdPublicKey,
+}
+
+impl KeyShare {
+    /// Restores a key share from `x_i` as 32 little-endian bytes.
+    ///
+    /// Fails unless `x_i * BASE8` is participant `index`'s verification key.
+    pub fn new(index: u32, x: Secret<[u8; 32]>, public: GvkThresholdPublicKey) -> Result<Self> {
+        public.validate()?;
+        let verification_key = public
+            .verification_key(index)
+            .ok_or_else(|| anyhow!("participant index {index} is not part of the key"))?;
+        let value = U256::from_le_bytes(*x.expose());
+        if value >= subgroup_order()
+            || scalar_mul(base8(), to_scalar(value)) != Point::from_app(verification_key)
+        {
+            return Err(anyhow!(
+                "key share does not match participant {index}'s verification key"
+            ));
+        }
+        Ok(Self { index, x, public })
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The secret `x_i`, for storing the share. Handle like an admin key.
+    pub fn secret(&self) -> &Secret<[u8; 32]> {
+        &self.x
+    }
+
+    pub fn public_key(&self) -> &GvkThresholdPublicKey {
+        &self.public
+    }
+
+    /// Partially decrypts every indexed GVK ciphertext of `pool_contract_id`.
+    pub fn partial_decrypt(
+        &self,
+        storage: &SqliteStorage,
+        pool_contract_id: &str,
+    ) -> Result<Vec<GvkPartialDecryption>> {
+        storage
+            .get_pool_gvk_ciphertexts(pool_contract_id)?
+            .iter()
+            .map(|row| self.partial_decrypt_row(row))
+            .collect()
+    }
+
+    pub(crate) fn partial_decrypt_row(
+        &self,
+        row: &PoolGvkCiphertextRow,
+    ) -> Result<GvkPartialDecryption> {
+        let r = Point::from_app(&row.ciphertext.r);
+        if !in_subgroup(r) {
+            return Err(anyhow!(
+                "GVK ciphertext R of event {} is not in the Baby JubJub subgroup",
+                row.event_id
+            ));
+        }
+        let x = to_scalar(U256::from_le_bytes(*self.x.expose()));
+        let (share, proof) = dleq::prove(x, r)?;
+        Ok(GvkPartialDecryption {
+            event_id: row.event_id.clone(),
+            participant: self.index,
+            share: share.to_app(),
+            proof,
+        })
+    }
+}
+
+/// Combines partial decryptions from `t` participants to audit a pool whose
+/// `D` came from a distributed key generation.
+///
+/// Produces the same [`AuditReport`] as [`super::Auditor`]. Partial
+/// decryptions whose proof does not verify are ignored, so a participant
+/// cannot corrupt the report, only withhold its share.
+#[derive(Debug, Clone)]
+pub struct ThresholdAuditor {
+    public: GvkThresholdPublicKey,
+}
+
+impl ThresholdAuditor {
+    pub fn new(public: GvkThresholdPublicKey) -> Result<Self> {
+        public.validate()?;
+        Ok(Self { public })
+    }
+
+    pub fn admin_view_key(&self) -> BabyJubJubPoint {
+        self.public.admin_view_key
+    }
+
+    /// Decrypts every indexed ciphertext of `pool_contract_id` from
+    /// `partials` and builds the pool's transaction graph.
+    ///
+    /// Fails if any ciphertext lacks `t` valid partial decryptions.
+    pub fn audit(
+        &self,
+        storage: &SqliteStorage,
+        pool_contract_id: &str,
+        partials: &[GvkPartialDecryption],
+    ) -> Result<AuditReport> {
+        let rows = storage.get_pool_gvk_ciphertexts(pool_contract_id)?;
+        self.audit_rows(pool_contract_id, rows, partials)
+    }
+
+    fn audit_rows(
+        &self,
+        pool_contract_id: &str,
+        rows: Vec<PoolGvkCiphertextRow>,
+        partials: &[GvkPartialDecryption],
+    ) -> Result<AuditReport> {
+        let mut by_event: HashMap<&str, Vec<&GvkPartialDecryption>> = HashMap::new();
+        for partial in partials {
+            by_event
+                .entry(partial.event_id.as_str())
+                .or_default()
+                .push(partial);
+        }
+        build_report(pool_contract_id, self.admin_view_key(), rows, |row| {
+            let partials = by_event
+                .get(row.event_id.as_str())
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let shared_point = self.combine(row, partials)?;
+            Ok(decrypt_with_shared_point(&row.ciphertext, shared_point))
+        })
+    }
+
+    /// Recovers `d * R` from the first `t` partial decryptions of distinct
+    /// participants whose proofs verify.
+    fn combine(
+        &self,
+        row: &PoolGvkCiphertextRow,
+        partials: &[&GvkPartialDecryption],
+    ) -> Result<Point> {
+        let threshold = usize::try_from(self.public.threshold)
+            .map_err(|_| anyhow!("GVK threshold out of range"))?;
+        let r = Point::from_app(&row.ciphertext.r);
+        if !is_on_curve(r) {
+            return Err(anyhow!("GVK ciphertext R is not on the Baby JubJub curve"));
+        }
+
+        let mut shares: BTreeMap<u32, Point> = BTreeMap::new();
+        for partial in partials {
+            if shares.len() == threshold {
+                break;
+            }
+            let Some(verification_key) = self.public.verification_key(partial.participant) else {
+                continue;
+            };
+            let share = Point::from_app(&partial.share);
+            let verified =
+                dleq::verify(Point::from_app(verification_key), r, share, &partial.proof)
+                    .unwrap_or(false);
+            if verified {
+                shares.entry(partial.participant).or_insert(share);
+            }
+        }
+        if shares.len() < threshold {
+            return Err(anyhow!(
+                "{} valid partial decryptions, need {threshold}",
+                shares.len()
+            ));
+        }
+
+        let participants: Vec<u32> = shares.keys().copied().collect();
+        shares
+            .iter()
+            .try_fold(identity(), |acc, (&participant, &share)| {
+                let lambda = lagrange_at_zero(participant, &participants)
+                    .context("failed to interpolate partial decryptions")?;
+                Ok(add(acc, scalar_mul(share, to_scalar(lambda))))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gvk::auditor::tests::traceable_pool;
+
+    /// Runs a complete key generation and returns each participant's share.
+    fn dkg(threshold: u32, participants: u32) -> Result<(Vec<KeyShare>, GvkThresholdPublicKey)> {
+        let dealers = (1..=participants)
+            .map(|i| DkgParticipant::new(i, threshold, participants))
+            .collect::<Result<Vec<_>>>()?;
+        let dealings: Vec<GvkDkgDealing> = dealers.iter().map(DkgParticipant::dealing).collect();
+        let shares = dealers
+            .iter()
+            .flat_map(|dealer| (1..=participants).map(move |j| dealer.share_for(j)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut key_shares = Vec::new();
+        let mut public = None;
+        for dealer in dealers {
+            let (key_share, key) = dealer.finish(&dealings, &shares)?;
+            assert!(public.as_ref().is_none_or(|p| *p == key));
+            public = Some(key);
+            key_shares.push(key_share);
+        }
+        Ok((key_shares, public.expect("at least one participant")))
+    }
+
+    #[test]
+    fn any_threshold_of_partials_decrypts_the_pool() -> Result<()> {
+        let (shares, public) = dkg(2, 3)?;
+        let storage = traceable_pool(&public.admin_view_key)?;
+        let auditor = ThresholdAuditor::new(public)?;
+
+        for pair in [[0, 1], [0, 2], [1, 2]] {
+            let partials = pair
+                .iter()
+                .map(|&i| shares[i].partial_decrypt(&storage, "CPOOLGVK"))
+                .collect::<Result<Vec<_>>>()?
+                .concat();
+            let report = auditor.audit(&storage, "CPOOLGVK", &partials)?;
+            assert_eq!(report.transactions.len(), 2);
+            assert_eq!(report.mismatches().count(), 0);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn audit_fails_below_threshold_and_ignores_bad_partials() -> Result<()> {
+        let (shares, public) = dkg(2, 3)?;
+        let storage = traceable_pool(&public.admin_view_key)?;
+        let auditor = ThresholdAuditor::new(public)?;
+
+        let alone = shares[0].partial_decrypt(&storage, "CPOOLGVK")?;
+        assert!(auditor.audit(&storage, "CPOOLGVK", &alone).is_err());
+
+        // Participant 2 claims participant 3's slot: its proofs do not verify
+        // against participant 3's verification key.
+        let mut forged = shares[1].partial_decrypt(&storage, "CPOOLGVK")?;
+        for partial in &mut forged {
+            partial.participant = 3;
+        }
+        let partials = [alone, forged].concat();
+        assert!(auditor.audit(&storage, "CPOOLGVK", &partials).is_err());
+
+        let honest = shares[2].partial_decrypt(&storage, "CPOOLGVK")?;
+        let partials = [partials, honest].concat();
+        assert_eq!(
+            auditor
+                .audit(&storage, "CPOOLGVK", &partials)?
+                .mismatches()
+                .count(),
+            0
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn finish_rejects_a_share_that_does_not_match_its_dealing() -> Result<()> {
+        let dealers = (1..=3)
+            .map(|i| DkgParticipant::new(i, 2, 3))
+            .collect::<Result<Vec<_>>>()?;
+        let dealings: Vec<GvkDkgDealing> = dealers.iter().map(DkgParticipant::dealing).collect();
+        let mut shares = dealers
+            .iter()
+            .map(|dealer| dealer.share_for(1))
+            .collect::<Result<Vec<_>>>()?;
+        shares[1].value = Field::ONE;
+
+        let err = dealers
+            .into_iter()
+            .next()
+            .expect("three dealers")
+            .finish(&dealings, &shares)
+            .expect_err("tampered share");
+        assert!(err.to_string().contains("participant 2"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn key_share_must_match_its_verification_key() -> Result<()> {
+        let (shares, public) = dkg(2, 3)?;
+        let x = *shares[0].secret().expose();
+
+        assert!(KeyShare::new(1, Secret::new(x), public.clone()).is_ok());
+        assert!(KeyShare::new(2, Secret::new(x), public.clone()).is_err());
+        assert!(KeyShare::new(4, Secret::new(x), public).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn parameters_are_validated() {
+        assert!(DkgParticipant::new(1, 0, 3).is_err());
+        assert!(DkgParticipant::new(1, 4, 3).is_err());
+        assert!(DkgParticipant::new(0, 2, 3).is_err());
+        assert!(DkgParticipant::new(4, 2, 3).is_err());
+    }
+}
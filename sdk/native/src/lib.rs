@@ -6,7 +6,7 @@
 //!
 //! ```no_run
 //! use stellar_private_payments::{
-//!     Client, Handle, LocalProver, LocalSigner, LocalStorage, ProverArtifacts,
+//!     Client, Handle, LocalProver, LocalSigner, LocalStorage, ProverArtifacts, TransferOptions,
 //!     types::{ContractConfig, NoteAmount, PolicyFlags, TransferRecipient},
 //! };
 //!
@@ -33,7 +33,7 @@
 //! let pool = account.pool("CA2TZ...")?;
 //!
 //! pool.deposit(10_000_000u128.into()).await?;
-//! pool.transfer("G...", 5_000_000u128.into()).await?;
+//! pool.transfer("G...", 5_000_000u128.into(), TransferOptions::memo("invoice 42")).await?;
 //! pool.withdraw(3_000_000u128.into(), "G...").await?;
 //! let balance = pool.balance().await?;
 //! # Ok(())
@@ -44,6 +44,7 @@
 
 pub mod chain;
 pub mod disclosure;
+pub mod gvk;
 pub mod planner;
 pub mod state;
 pub mod types;
@@ -53,6 +54,7 @@ mod account;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod blocking;
 mod client;
+mod consolidation;
 mod core;
 mod correlation;
 pub mod crypto;
@@ -69,6 +71,7 @@ mod transact;
 
 pub use account::Account;
 pub use client::Client;
+pub use consolidation::ConsolidationPolicy;
 pub use core::PoolCore;
 pub use disclosure::{
     BuildDisclosureInputs, DisclosureInputs, DisclosureInputsRequest, DisclosureProveParams,
@@ -77,23 +80,27 @@ pub use disclosure::{
 pub use error::{Error, PlanExecutionError};
 pub use handle::Handle;
 pub use plan::PreparedTransactionPlan;
-pub use planner::{SpendTarget, SpendableNote, Transact};
+pub use planner::{CoinSelection, CoinSelector, SpendTarget, SpendableNote, Transact};
 pub use pool::PrivatePool;
 pub use prover::{LocalProver, NoopProver, Prover, ProverEngine};
-pub use signer::{LocalSigner, Signer};
+pub use signer::{LocalSigner, NoopSigner, Signer};
 pub use storage::{LocalStorage, Storage};
 pub use sync::{BackgroundSync, BackgroundSyncStop, SyncHandle, SyncMode, bootnode_required};
 pub use transact::{
     BuildTransactParams, PreparedProverTx, PreparedTxPublic, TransactRequest,
-    build_transact_params, build_validated_pool_tree, load_user_key_material,
-    transact_request_from_step,
+    build_pool_merkle_proofs, build_transact_params, build_validated_pool_tree,
+    load_user_key_material, transact_request_from_step,
 };
 pub use types::{
-    Estimate, OperationalFeedItem, PolicyFlags, PortfolioBalance, PrivatePoolConfig,
-    ProverArtifacts, RecipientLookup, SignedTransaction, TransactChainContext, TransactionResult,
-    TransferRecipient, UserNoteSummary,
+    Estimate, OperationalFeedItem, PayoutReport, PayoutStatus, PlanStatus, PolicyFlags,
+    PortfolioBalance, PrivatePoolConfig, ProverArtifacts, RecipientLookup, ResumedPlan,
+    SentNoteSummary, SignedTransaction, StoredPlan, StrategyEstimate, TransactChainContext,
+    TransactionResult, TransferOptions, TransferRecipient, UserNoteSummary,
+};
+pub use zk::{
+    encryption::{KEY_DERIVATION_MESSAGE, MAX_MEMO_LEN},
+    prover::convert_proof_to_soroban,
 };
-pub use zk::{encryption::KEY_DERIVATION_MESSAGE, prover::convert_proof_to_soroban};
 
 /// Groth16 prove output for a transact step (simulate / sign / submit).
 pub type PreparedTransaction = PreparedProverTx;
@@ -1,56 +1,105 @@
 //! Logical transaction plans (tx-planner session), resolved one on-chain tx at
 //! a time.
 
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    planner::{SpendSession, Transact},
-    types::{Field, NoteAmount},
+    planner::{PayoutSession, SpendSession, SpendableNote, Transact},
+    types::{Field, InFlightStep, NoteAmount, PlanStatus, StoredPlan},
 };
 
 use crate::error::Error;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) enum PlanKind {
-    Deposit { amount: NoteAmount },
+    Deposit {
+        amount: NoteAmount,
+    },
     Spend(SpendSession),
     Raw(Transact),
+    /// Independent steps with no inputs, run in order (deposits to
+    /// recipients).
+    Batch(Vec<Transact>),
+    /// Chained payments to several recipients.
+    Payout(PayoutSession),
 }
 
 /// Frozen multi-tx spend; each on-chain step is executed via
 /// [`crate::pool::PrivatePool::transfer`] / [`withdraw`] / [`deposit`].
-#[derive(Debug)]
+///
+/// Serializes to its [`StoredPlan::state`]; the journal fields are kept in
+/// their own columns.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct PreparedTransactionPlan {
     tx_count: u32,
     current_tx: u32,
     kind: PlanKind,
+    /// Storage row once a step was submitted.
+    #[serde(skip)]
+    id: Option<i64>,
+    /// State before the oldest step proved but not yet confirmed.
+    #[serde(skip)]
+    checkpoint: Option<String>,
+    /// Hashes of confirmed steps.
+    #[serde(skip)]
+    confirmed: Vec<String>,
+    /// [`Self::pending_notes`] as of the last confirmed step.
+    #[serde(skip)]
+    pending: Vec<SpendableNote>,
 }
 
 impl PreparedTransactionPlan {
-    pub(crate) fn deposit(amount: NoteAmount) -> Self {
+    fn new(tx_count: u32, kind: PlanKind) -> Self {
         Self {
-            tx_count: 1,
+            tx_count,
             current_tx: 0,
-            kind: PlanKind::Deposit { amount },
+            kind,
+            id: None,
+            checkpoint: None,
+            confirmed: Vec::new(),
+            pending: Vec::new(),
         }
     }
 
+    /// Rebuild a plan from `state`, its [`StoredPlan::state`] or in-flight
+    /// state, with the journal of `stored`.
+    pub(crate) fn from_stored(stored: &StoredPlan, state: &str) -> Result<Self, Error> {
+        let mut plan: Self = serde_json::from_str(state)
+            .map_err(|e| Error::Other(format!("stored plan state: {e}")))?;
+        plan.id = stored.id;
+        plan.confirmed = stored.confirmed.clone();
+        plan.pending = stored.pending_notes.clone();
+        Ok(plan)
+    }
+
+    pub(crate) fn deposit(amount: NoteAmount) -> Self {
+        Self::new(1, PlanKind::Deposit { amount })
+    }
+
+    pub(crate) fn batch(steps: Vec<Transact>) -> Result<Self, Error> {
+        let tx_count = u32::try_from(steps.len())
+            .map_err(|_| Error::Other("too many transactions in plan".into()))?;
+        Ok(Self::new(tx_count, PlanKind::Batch(steps)))
+    }
+
     pub(crate) fn from_session(
         session: SpendSession,
     ) -> Result<Self, crate::planner::SpendSessionError> {
         let tx_count = u32::try_from(session.len())
             .map_err(|_| crate::planner::SpendSessionError::Complete)?;
-        Ok(Self {
-            tx_count,
-            current_tx: 0,
-            kind: PlanKind::Spend(session),
-        })
+        Ok(Self::new(tx_count, PlanKind::Spend(session)))
+    }
+
+    pub(crate) fn from_payout(
+        session: PayoutSession,
+    ) -> Result<Self, crate::planner::SpendSessionError> {
+        let tx_count = u32::try_from(session.len())
+            .map_err(|_| crate::planner::SpendSessionError::Complete)?;
+        Ok(Self::new(tx_count, PlanKind::Payout(session)))
     }
 
     pub fn from_transact(step: Transact) -> Self {
-        Self {
-            tx_count: 1,
-            current_tx: 0,
-            kind: PlanKind::Raw(step),
-        }
+        Self::new(1, PlanKind::Raw(step))
     }
 
     pub fn tx_count(&self) -> u32 {
@@ -65,6 +114,129 @@ impl PreparedTransactionPlan {
         self.current_tx >= self.tx_count
     }
 
+    /// Storage row of the plan once a step was submitted through
+    /// [`crate::PrivatePool::submit_step`].
+    pub fn plan_id(&self) -> Option<i64> {
+        self.id
+    }
+
+    /// Hashes of the steps confirmed through
+    /// [`crate::PrivatePool::confirm_step`], in order.
+    pub fn confirmed(&self) -> &[String] {
+        &self.confirmed
+    }
+
+    pub(crate) fn set_id(&mut self, id: i64) {
+        self.id = Some(id);
+    }
+
+    fn snapshot(&self) -> Result<String, Error> {
+        serde_json::to_string(self).map_err(|e| Error::Other(format!("serialize plan: {e}")))
+    }
+
+    /// Journal entry for the current state, with no step in flight.
+    pub(crate) fn stored(
+        &self,
+        pool_contract_id: &str,
+        user_address: &str,
+        status: PlanStatus,
+    ) -> Result<StoredPlan, Error> {
+        Ok(self.record(
+            pool_contract_id,
+            user_address,
+            status,
+            self.snapshot()?,
+            None,
+        ))
+    }
+
+    /// Journal entry once the last proved step was submitted as `tx_hash`:
+    /// the state before that step, with the step in flight.
+    pub(crate) fn submitted(
+        &self,
+        pool_contract_id: &str,
+        user_address: &str,
+        tx_hash: &str,
+    ) -> Result<StoredPlan, Error> {
+        let after = self.snapshot()?;
+        let before = self.checkpoint.clone().unwrap_or_else(|| after.clone());
+        let in_flight = InFlightStep {
+            tx_hash: tx_hash.to_string(),
+            state: after,
+        };
+        Ok(self.record(
+            pool_contract_id,
+            user_address,
+            PlanStatus::Active,
+            before,
+            Some(in_flight),
+        ))
+    }
+
+    fn record(
+        &self,
+        pool_contract_id: &str,
+        user_address: &str,
+        status: PlanStatus,
+        state: String,
+        in_flight: Option<InFlightStep>,
+    ) -> StoredPlan {
+        StoredPlan {
+            id: self.id,
+            pool_contract_id: pool_contract_id.to_string(),
+            user_address: user_address.to_string(),
+            status,
+            tx_count: self.tx_count,
+            state,
+            confirmed: self.confirmed.clone(),
+            pending_notes: self.pending.clone(),
+            in_flight,
+        }
+    }
+
+    /// Record `tx_hash` as the confirmation of the last proved step and
+    /// return the journal entry for the new state.
+    pub(crate) fn confirm(
+        &mut self,
+        pool_contract_id: &str,
+        user_address: &str,
+        tx_hash: &str,
+    ) -> Result<StoredPlan, Error> {
+        self.confirmed.push(tx_hash.to_string());
+        self.checkpoint = None;
+        self.pending = self.pending_notes()?;
+        let status = if self.is_complete() {
+            PlanStatus::Completed
+        } else {
+            PlanStatus::Active
+        };
+        self.stored(pool_contract_id, user_address, status)
+    }
+
+    /// Notes created by confirmed steps that the next step spends.
+    pub(crate) fn pending_notes(&self) -> Result<Vec<SpendableNote>, Error> {
+        Ok(match &self.kind {
+            PlanKind::Spend(session) => session.pending_notes()?,
+            PlanKind::Payout(session) => session.pending_notes(),
+            PlanKind::Deposit { .. } | PlanKind::Raw(_) | PlanKind::Batch(_) => Vec::new(),
+        })
+    }
+
+    /// Input commitments of the next step; empty for deposits or once
+    /// complete.
+    pub(crate) fn next_inputs(&self) -> Result<Vec<Field>, Error> {
+        if self.is_complete() {
+            return Ok(Vec::new());
+        }
+        if let Some(step) = self.raw_transact_step() {
+            return Ok(step.input_commitments.clone());
+        }
+        Ok(self
+            .current_spend_step()?
+            .map(|step| step.input_commitments)
+            .unwrap_or_default())
+    }
+
     pub(crate) fn kind_mut(&mut self) -> &mut PlanKind {
         &mut self.kind
     }
@@ -79,7 +251,20 @@ impl PreparedTransactionPlan {
     pub(crate) fn deposit_amount(&self) -> Option<NoteAmount> {
         match &self.kind {
             PlanKind::Deposit { amount } => Some(*amount),
-            PlanKind::Spend(_) | PlanKind::Raw(_) => None,
+            PlanKind::Spend(_) | PlanKind::Raw(_) | PlanKind::Batch(_) | PlanKind::Payout(_) => {
+                None
+            }
+        }
+    }
+
+    /// For plans built by `prepare_transfer_many`, the indices of the
+    /// recipients each step pays; empty for other plans.
+    pub fn paid_by_step(&self) -> Vec<Vec<usize>> {
+        match &self.kind {
+            PlanKind::Payout(session) => (0..session.len())
+                .map(|step| session.paid_by_step(step))
+                .collect(),
+            _ => Vec::new(),
         }
     }
 
@@ -87,14 +272,18 @@ impl PreparedTransactionPlan {
         &self,
     ) -> Result<Option<Transact>, crate::planner::SpendSessionError> {
         match &self.kind {
-            PlanKind::Deposit { .. } | PlanKind::Raw(_) => Ok(None),
+            PlanKind::Deposit { .. } | PlanKind::Raw(_) | PlanKind::Batch(_) => Ok(None),
             PlanKind::Spend(session) => session.step(),
+            PlanKind::Payout(session) => session.step(),
         }
     }
 
     pub(crate) fn raw_transact_step(&self) -> Option<&Transact> {
         match &self.kind {
             PlanKind::Raw(step) => Some(step),
+            PlanKind::Batch(steps) => usize::try_from(self.current_tx)
+                .ok()
+                .and_then(|index| steps.get(index)),
             _ => None,
         }
     }
@@ -103,9 +292,18 @@ impl PreparedTransactionPlan {
         &mut self,
         output_commitments: &[Field; 2],
     ) -> Result<(), Error> {
+        let checkpoint = match self.checkpoint {
+            Some(_) => None,
+            None => Some(self.snapshot()?),
+        };
         match self.kind_mut() {
-            PlanKind::Deposit { .. } | PlanKind::Raw(_) => self.advance(),
-            PlanKind::Spend(_) => self.complete_pending_spend(output_commitments)?,
+            PlanKind::Deposit { .. } | PlanKind::Raw(_) | PlanKind::Batch(_) => self.advance(),
+            PlanKind::Spend(_) | PlanKind::Payout(_) => {
+                self.complete_pending_spend(output_commitments)?
+            }
+        }
+        if checkpoint.is_some() {
+            self.checkpoint = checkpoint;
         }
         Ok(())
     }
@@ -114,10 +312,89 @@ impl PreparedTransactionPlan {
         &mut self,
         output_commitments: &[Field; 2],
     ) -> Result<(), crate::planner::SpendSessionError> {
-        if let PlanKind::Spend(session) = self.kind_mut() {
-            session.complete_step(output_commitments)?;
+        match self.kind_mut() {
+            PlanKind::Spend(session) => session.complete_step(output_commitments)?,
+            PlanKind::Payout(session) => session.complete_step(output_commitments)?,
+            PlanKind::Deposit { .. } | PlanKind::Raw(_) | PlanKind::Batch(_) => {}
         }
         self.advance();
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POOL_CONTRACT_ID: &str = "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABSC4";
+    const USER_ADDRESS: &str = "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF";
+
+    fn proved_deposit() -> PreparedTransactionPlan {
+        let mut plan = PreparedTransactionPlan::deposit(NoteAmount::from(5u128));
+        plan.finish_proved_tx(&[Field::ZERO, Field::ZERO])
+            .expect("finish proved deposit");
+        plan
+    }
+
+    #[test]
+    fn from_stored_restores_journal() {
+        let plan = proved_deposit();
+        let mut stored = plan
+            .stored(POOL_CONTRACT_ID, USER_ADDRESS, PlanStatus::Active)
+            .expect("stored plan");
+        stored.id = Some(7);
+        stored.confirmed = vec!["aa".into()];
+
+        let restored =
+            PreparedTransactionPlan::from_stored(&stored, &stored.state).expect("restore plan");
+        assert_eq!(restored.plan_id(), Some(7));
+        assert_eq!(restored.confirmed(), ["aa".to_string()]);
+        assert_eq!(restored.tx_count(), 1);
+        assert!(restored.is_complete());
+
+        let err = PreparedTransactionPlan::from_stored(&stored, "not a plan")
+            .expect_err("invalid state should not restore");
+        assert!(matches!(err, Error::Other(_)));
+    }
+
+    #[test]
+    fn submitted_keeps_the_step_in_flight() {
+        let plan = proved_deposit();
+        let stored = plan
+            .submitted(POOL_CONTRACT_ID, USER_ADDRESS, "aa")
+            .expect("submitted plan");
+        assert_eq!(stored.status, PlanStatus::Active);
+        assert!(stored.confirmed.is_empty());
+
+        let before =
+            PreparedTransactionPlan::from_stored(&stored, &stored.state).expect("state before");
+        assert_eq!(before.current_tx(), 0, "stored state predates the step");
+
+        let in_flight = stored.in_flight.as_ref().expect("step in flight");
+        assert_eq!(in_flight.tx_hash, "aa");
+        let after =
+            PreparedTransactionPlan::from_stored(&stored, &in_flight.state).expect("state after");
+        assert!(after.is_complete(), "in-flight state includes the step");
+    }
+
+    #[test]
+    fn confirm_moves_past_the_step() {
+        let mut plan = proved_deposit();
+        let stored = plan
+            .confirm(POOL_CONTRACT_ID, USER_ADDRESS, "aa")
+            .expect("confirmed plan");
+        assert_eq!(stored.status, PlanStatus::Completed);
+        assert_eq!(stored.confirmed, vec!["aa".to_string()]);
+        assert!(stored.in_flight.is_none());
+        assert_eq!(plan.confirmed(), ["aa".to_string()]);
+
+        let resubmitted = plan
+            .submitted(POOL_CONTRACT_ID, USER_ADDRESS, "bb")
+            .expect("journal after confirm");
+        assert_eq!(
+            resubmitted.state,
+            resubmitted.in_flight.expect("step in flight").state,
+            "a confirmed step is no longer a checkpoint"
+        );
+    }
+}
@@ -13,4 +13,10 @@ pub enum SpendSessionError {
 
     #[error("withdraw requires a recipient address")]
     MissingWithdrawRecipient,
+
+    #[error("payout batch has no recipients")]
+    NoPayouts,
+
+    #[error("payout amounts must be positive")]
+    ZeroPayout,
 }
@@ -1,22 +1,30 @@
 //! [`SpendSession`] — step through a [`TransactionPlan`] with wallet updates.
 
 mod error;
+mod payout;
 
 pub use error::SpendSessionError;
+pub use payout::{Payout, PayoutSession};
+
+use serde::{Deserialize, Serialize};
 
 use crate::types::{
     EncryptionPublicKey, ExtAmount, Field, NoteAmount, NotePublicKey, Sensitive,
     correlation_id_or_new,
 };
 
-use crate::planner::plan::{PlannedStep, SpendableNote, StepAction, plan};
+use crate::planner::plan::{
+    CoinSelector, MinSteps, PlannedStep, SpendableNote, StepAction, plan_with,
+};
 
 /// Recipient data for the final step.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum SpendTarget {
     Transfer {
         recipient_note: NotePublicKey,
         recipient_enc: EncryptionPublicKey,
+        /// Memo encrypted to the recipient on the final step's payment output.
+        memo: Option<String>,
     },
     Withdraw {
         recipient: String,
@@ -24,10 +32,15 @@ pub enum SpendTarget {
 }
 
 impl SpendTarget {
-    pub fn transfer(recipient_note: NotePublicKey, recipient_enc: EncryptionPublicKey) -> Self {
+    pub fn transfer(
+        recipient_note: NotePublicKey,
+        recipient_enc: EncryptionPublicKey,
+        memo: Option<String>,
+    ) -> Self {
         Self::Transfer {
             recipient_note,
             recipient_enc,
+            memo,
         }
     }
 
@@ -37,7 +50,7 @@ impl SpendTarget {
 }
 
 /// One on-chain `transact` call derived from the current plan step.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Transact {
     pub input_commitments: Vec<Field>,
     pub output_amounts: [NoteAmount; 2],
@@ -45,6 +58,8 @@ pub struct Transact {
     pub ext_recipient: String,
     pub out_recipient_note_pubkeys: [Option<NotePublicKey>; 2],
     pub out_recipient_encryption_pubkeys: [Option<EncryptionPublicKey>; 2],
+    /// Memos encrypted into each output; [`Transact::new`] leaves both empty.
+    pub out_memos: [Option<String>; 2],
 }
 
 impl Transact {
@@ -63,12 +78,13 @@ impl Transact {
             ext_recipient,
             out_recipient_note_pubkeys,
             out_recipient_encryption_pubkeys,
+            out_memos: [None, None],
         }
     }
 }
 
 /// Runs a frozen transaction plan step-by-step.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SpendSession {
     steps: Vec<PlannedStep>,
     wallet: Vec<SpendableNote>,
@@ -79,15 +95,26 @@ pub struct SpendSession {
 
 impl SpendSession {
     /// Create a new SpendSession from a wallet, amount, and target.
-    #[tracing::instrument(skip_all, fields(stage = "spend_session_setup", correlation_id = %correlation_id_or_new(), wallet_size = wallet.len(), amount = ?Sensitive(&amount), target = ?Sensitive(&target)))]
     pub fn setup(
         wallet: Vec<SpendableNote>,
         amount: NoteAmount,
         pool_address: String,
         target: SpendTarget,
+    ) -> Result<Self, SpendSessionError> {
+        Self::setup_with(wallet, amount, pool_address, target, &MinSteps)
+    }
+
+    /// [`Self::setup`] with notes chosen by `selector`.
+    #[tracing::instrument(skip_all, fields(stage = "spend_session_setup", correlation_id = %correlation_id_or_new(), wallet_size = wallet.len(), amount = ?Sensitive(&amount), target = ?Sensitive(&target)))]
+    pub fn setup_with(
+        wallet: Vec<SpendableNote>,
+        amount: NoteAmount,
+        pool_address: String,
+        target: SpendTarget,
+        selector: &dyn CoinSelector,
     ) -> Result<Self, SpendSessionError> {
         validate_target(&target)?;
-        let tx_plan = plan(amount, &wallet)?;
+        let tx_plan = plan_with(amount, &wallet, selector)?;
         Ok(Self {
             steps: tx_plan.into_iter().collect(),
             wallet,
@@ -143,6 +170,21 @@ impl SpendSession {
         Ok(Some(tx))
     }
 
+    /// Merge output of a completed step that the next step spends.
+    pub fn pending_notes(&self) -> Result<Vec<SpendableNote>, SpendSessionError> {
+        let Some(step) = self.steps.get(self.step_index) else {
+            return Ok(Vec::new());
+        };
+        let planned = std::iter::once(&step.inputs.0).chain(step.inputs.1.as_ref());
+        Ok(step
+            .resolve(&self.wallet)?
+            .into_iter()
+            .zip(planned)
+            .filter(|(_, input)| input.commitment.is_none())
+            .map(|(note, _)| note)
+            .collect())
+    }
+
     /// Advance after a successful submit (`output_commitments` from prove
     /// result).
     #[tracing::instrument(skip_all, name = "spend_session_complete_step", fields(correlation_id = %correlation_id_or_new(), step_index = self.step_index))]
@@ -162,6 +204,7 @@ impl SpendSession {
             StepAction::Consolidate { output } => Some(SpendableNote {
                 commitment: output_commitments[0],
                 amount: output,
+                created_at_ledger: None,
             }),
             StepAction::Final { .. } => None,
         };
@@ -216,11 +259,13 @@ fn materialize_step(
             ext_recipient: pool_address.to_string(),
             out_recipient_note_pubkeys: [None, None],
             out_recipient_encryption_pubkeys: [None, None],
+            out_memos: [None, None],
         }),
         StepAction::Final { outputs } => match target {
             SpendTarget::Transfer {
                 recipient_note,
                 recipient_enc,
+                memo,
             } => {
                 let out1 = outputs.1.unwrap_or(NoteAmount::ZERO);
                 let (out_note_pks, out_enc_pks) = if outputs.1.is_some() {
@@ -241,6 +286,7 @@ fn materialize_step(
                     ext_recipient: pool_address.to_string(),
                     out_recipient_note_pubkeys: out_note_pks,
                     out_recipient_encryption_pubkeys: out_enc_pks,
+                    out_memos: [memo.clone(), None],
                 })
             }
             SpendTarget::Withdraw { recipient } => {
@@ -255,6 +301,7 @@ fn materialize_step(
                     ext_recipient: recipient.clone(),
                     out_recipient_note_pubkeys: [None, None],
                     out_recipient_encryption_pubkeys: [None, None],
+                    out_memos: [None, None],
                 })
             }
         },
@@ -283,6 +330,7 @@ mod tests {
         SpendableNote {
             commitment: Field::from(NoteAmount::from(commitment_value)),
             amount: NoteAmount::from(amount),
+            created_at_ledger: None,
         }
     }
 
@@ -296,6 +344,7 @@ mod tests {
                 "0x0000000000000000000000000000000000000000000000000000000000000002",
             )
             .expect("test enc key"),
+            Some("INV-42".to_string()),
         )
     }
 
@@ -337,6 +386,8 @@ mod tests {
         assert!(step.out_recipient_encryption_pubkeys[0].is_some());
         assert!(step.out_recipient_note_pubkeys[1].is_none());
         assert!(step.out_recipient_encryption_pubkeys[1].is_none());
+        assert_eq!(step.out_memos[0].as_deref(), Some("INV-42"));
+        assert!(step.out_memos[1].is_none());
     }
 
     #[test]
@@ -369,6 +420,8 @@ mod tests {
         while let Some(step) = exec.step().expect("step") {
             steps = steps.checked_add(1).expect("step count fits in u32");
             assert_eq!(exec.is_consolidate_step(), steps == 1);
+            // Only the payment output of the final step carries the memo.
+            assert_eq!(step.out_memos[0].is_some(), steps == 2);
             let merge = if step.output_amounts[0] == NoteAmount::from(8) {
                 Field::from(NoteAmount::from(900))
             } else {
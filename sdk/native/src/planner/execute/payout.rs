@@ -0,0 +1,406 @@
+//! [`PayoutSession`] — pay many recipients in one chain of transacts.
+//!
+//! Notes covering the total are selected once. Every step spends the change
+//! of the previous step plus the next selected note, so merging wallet notes
+//! and paying recipients share the same transactions. A step pays one
+//! recipient and carries its change forward, or pays two recipients when its
+//! inputs match their amounts exactly.
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{
+    EncryptionPublicKey, ExtAmount, Field, NoteAmount, NotePublicKey, correlation_id_or_new,
+};
+
+use crate::planner::plan::{CoinSelector, PlanError, SpendableNote, TRANSACTION_LIMIT};
+
+use super::{SpendSessionError, Transact};
+
+/// One recipient of a [`PayoutSession`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Payout {
+    pub recipient_note: NotePublicKey,
+    pub recipient_enc: EncryptionPublicKey,
+    pub amount: NoteAmount,
+    /// Memo encrypted to this recipient.
+    pub memo: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+enum ChainOutput {
+    /// Pays the payout at this index.
+    Pay(usize),
+    /// Change back to the sender, spent by the next step.
+    Change(NoteAmount),
+    Zero,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ChainStep {
+    /// Spends the change output of the previous step.
+    carry_in: bool,
+    notes: Vec<SpendableNote>,
+    outputs: [ChainOutput; 2],
+}
+
+/// Runs a frozen payout chain step-by-step.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PayoutSession {
+    payouts: Vec<Payout>,
+    steps: Vec<ChainStep>,
+    pool_address: String,
+    carry: Option<SpendableNote>,
+    step_index: usize,
+}
+
+impl PayoutSession {
+    /// Select notes covering every payout with `selector` and freeze the
+    /// chain.
+    #[tracing::instrument(skip_all, fields(stage = "payout_session_setup", correlation_id = %correlation_id_or_new(), wallet_size = wallet.len(), payouts = payouts.len()))]
+    pub fn setup(
+        wallet: &[SpendableNote],
+        payouts: Vec<Payout>,
+        pool_address: String,
+        selector: &dyn CoinSelector,
+    ) -> Result<Self, SpendSessionError> {
+        if payouts.is_empty() {
+            return Err(SpendSessionError::NoPayouts);
+        }
+        let mut total = NoteAmount::ZERO;
+        for payout in &payouts {
+            if payout.amount.is_zero() {
+                return Err(SpendSessionError::ZeroPayout);
+            }
+            total = total
+                .checked_add(payout.amount)
+                .ok_or(PlanError::InputAmountOverflow)?;
+        }
+        let selected = select_notes(wallet, total, selector)?;
+        let steps = chain_steps(&payouts, selected)?;
+        Ok(Self {
+            payouts,
+            steps,
+            pool_address,
+            carry: None,
+            step_index: 0,
+        })
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.step_index >= self.steps.len()
+    }
+
+    pub fn step_index(&self) -> usize {
+        self.step_index
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn payouts(&self) -> &[Payout] {
+        &self.payouts
+    }
+
+    /// Indices into [`Self::payouts`] paid by step `step_index`.
+    pub fn paid_by_step(&self, step_index: usize) -> Vec<usize> {
+        self.steps
+            .get(step_index)
+            .map(|step| {
+                step.outputs
+                    .iter()
+                    .filter_map(|output| match output {
+                        ChainOutput::Pay(index) => Some(*index),
+                        ChainOutput::Change(_) | ChainOutput::Zero => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Materialize the next step into a transaction payload.
+    pub fn step(&self) -> Result<Option<Transact>, SpendSessionError> {
+        let Some(step) = self.steps.get(self.step_index) else {
+            return Ok(None);
+        };
+        let mut input_commitments = Vec::with_capacity(2);
+        if step.carry_in {
+            let carry = self
+                .carry
+                .as_ref()
+                .ok_or(PlanError::internal("payout step spends missing change"))?;
+            input_commitments.push(carry.commitment);
+        }
+        input_commitments.extend(step.notes.iter().map(|note| note.commitment));
+
+        // Zero outputs go to the step's first recipient, as in a transfer.
+        let step_recipient = step.outputs.iter().find_map(|output| match output {
+            ChainOutput::Pay(index) => self.payouts.get(*index),
+            ChainOutput::Change(_) | ChainOutput::Zero => None,
+        });
+        let mut tx = Transact {
+            input_commitments,
+            output_amounts: [NoteAmount::ZERO; 2],
+            ext_amount: ExtAmount::ZERO,
+            ext_recipient: self.pool_address.clone(),
+            out_recipient_note_pubkeys: [None, None],
+            out_recipient_encryption_pubkeys: [None, None],
+            out_memos: [None, None],
+        };
+        for (slot, output) in step.outputs.iter().enumerate() {
+            let (amount, payout) = match output {
+                ChainOutput::Pay(index) => {
+                    let payout = self
+                        .payouts
+                        .get(*index)
+                        .ok_or(PlanError::internal("payout index out of range"))?;
+                    tx.out_memos[slot] = payout.memo.clone();
+                    (payout.amount, Some(payout))
+                }
+                ChainOutput::Change(amount) => (*amount, None),
+                ChainOutput::Zero => (NoteAmount::ZERO, step_recipient),
+            };
+            tx.output_amounts[slot] = amount;
+            tx.out_recipient_note_pubkeys[slot] = payout.map(|p| p.recipient_note.clone());
+            tx.out_recipient_encryption_pubkeys[slot] = payout.map(|p| p.recipient_enc.clone());
+        }
+        Ok(Some(tx))
+    }
+
+    /// Change of a completed step that the next step spends.
+    pub fn pending_notes(&self) -> Vec<SpendableNote> {
+        match (self.steps.get(self.step_index), &self.carry) {
+            (Some(step), Some(carry)) if step.carry_in => vec![carry.clone()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Advance after a successful prove (`output_commitments` of the step).
+    pub fn complete_step(
+        &mut self,
+        output_commitments: &[Field; 2],
+    ) -> Result<(), SpendSessionError> {
+        let step = self
+            .steps
+            .get(self.step_index)
+            .ok_or(SpendSessionError::Complete)?;
+        self.carry = step.outputs.iter().zip(output_commitments).find_map(
+            |(output, commitment)| match output {
+                ChainOutput::Change(amount) => Some(SpendableNote {
+                    commitment: *commitment,
+                    amount: *amount,
+                    created_at_ledger: None,
+                }),
+                ChainOutput::Pay(_) | ChainOutput::Zero => None,
+            },
+        );
+        self.step_index = self
+            .step_index
+            .checked_add(1)
+            .expect("step_index stays within chain length");
+        Ok(())
+    }
+}
+
+fn select_notes(
+    wallet: &[SpendableNote],
+    total: NoteAmount,
+    selector: &dyn CoinSelector,
+) -> Result<Vec<SpendableNote>, PlanError> {
+    if wallet.is_empty() {
+        return Err(PlanError::NoSpendableNotes);
+    }
+    let (indices, _) = selector
+        .select(wallet, total)?
+        .into_selection()
+        .ok_or(PlanError::NoCombination)?;
+    if indices.len() > TRANSACTION_LIMIT {
+        return Err(PlanError::TooManyNotes {
+            selected: indices.len(),
+            max_notes: TRANSACTION_LIMIT,
+        });
+    }
+    indices
+        .into_iter()
+        .map(|index| {
+            wallet
+                .get(index)
+                .cloned()
+                .ok_or(PlanError::NoteIndexOutOfRange { index })
+        })
+        .collect()
+}
+
+fn chain_steps(
+    payouts: &[Payout],
+    selected: Vec<SpendableNote>,
+) -> Result<Vec<ChainStep>, PlanError> {
+    let mut notes = selected.into_iter();
+    let mut carry: Option<NoteAmount> = None;
+    let mut next = 0usize;
+    let mut steps = Vec::new();
+    while let Some(payout) = payouts.get(next) {
+        let carry_in = carry.is_some();
+        let mut available = carry.take().unwrap_or(NoteAmount::ZERO);
+        let mut step_notes = Vec::with_capacity(2);
+        let capacity = if carry_in { 1 } else { 2 };
+        while step_notes.len() < capacity {
+            let Some(note) = notes.next() else { break };
+            available = available
+                .checked_add(note.amount)
+                .ok_or(PlanError::InputAmountOverflow)?;
+            step_notes.push(note);
+        }
+        if !carry_in && step_notes.is_empty() {
+            return Err(PlanError::NoCombination);
+        }
+
+        let outputs = if available >= payout.amount {
+            let rest = available
+                .checked_sub(payout.amount)
+                .ok_or(PlanError::internal("payout change underflow"))?;
+            let following = payouts.get(next.saturating_add(1));
+            if following.is_some_and(|following| following.amount == rest) {
+                let outputs = [
+                    ChainOutput::Pay(next),
+                    ChainOutput::Pay(next.saturating_add(1)),
+                ];
+                next = next.saturating_add(2);
+                outputs
+            } else if rest.is_zero() {
+                next = next.saturating_add(1);
+                [ChainOutput::Pay(next.saturating_sub(1)), ChainOutput::Zero]
+            } else {
+                carry = Some(rest);
+                next = next.saturating_add(1);
+                [
+                    ChainOutput::Pay(next.saturating_sub(1)),
+                    ChainOutput::Change(rest),
+                ]
+            }
+        } else if notes.as_slice().is_empty() {
+            // The selection covers the total, so this only happens on a
+            // planner bug.
+            return Err(PlanError::NoCombination);
+        } else {
+            carry = Some(available);
+            [ChainOutput::Change(available), ChainOutput::Zero]
+        };
+        steps.push(ChainStep {
+            carry_in,
+            notes: step_notes,
+            outputs,
+        });
+    }
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::planner::plan::MinSteps;
+
+    fn note(amount: u128, commitment: u128) -> SpendableNote {
+        SpendableNote {
+            commitment: Field::from(NoteAmount::from(commitment)),
+            amount: NoteAmount::from(amount),
+            created_at_ledger: None,
+        }
+    }
+
+    fn payout(amount: u128) -> Payout {
+        Payout {
+            recipient_note: NotePublicKey([1u8; 32]),
+            recipient_enc: EncryptionPublicKey([2u8; 32]),
+            amount: NoteAmount::from(amount),
+            memo: Some("salary".to_string()),
+        }
+    }
+
+    fn run(session: &mut PayoutSession) -> Vec<Transact> {
+        let mut txs = Vec::new();
+        let mut next_commitment = 10_000u128;
+        while let Some(tx) = session.step().expect("step") {
+            let commitments = [
+                Field::from(NoteAmount::from(next_commitment)),
+                Field::from(NoteAmount::from(next_commitment.saturating_add(1))),
+            ];
+            next_commitment = next_commitment.saturating_add(2);
+            session.complete_step(&commitments).expect("complete step");
+            txs.push(tx);
+        }
+        txs
+    }
+
+    #[test]
+    fn single_note_pays_each_recipient_and_carries_change() {
+        let mut session = PayoutSession::setup(
+            &[note(100, 1)],
+            vec![payout(10), payout(20), payout(30)],
+            "POOL".into(),
+            &MinSteps,
+        )
+        .expect("setup payouts");
+        assert_eq!(session.len(), 3);
+        let txs = run(&mut session);
+
+        assert_eq!(
+            txs[0].output_amounts,
+            [NoteAmount::from(10), NoteAmount::from(90)]
+        );
+        assert_eq!(txs[1].input_commitments.len(), 1);
+        assert_eq!(
+            txs[1].input_commitments[0],
+            Field::from(NoteAmount::from(10_001u128))
+        );
+        assert_eq!(
+            txs[2].output_amounts,
+            [NoteAmount::from(30), NoteAmount::from(40)]
+        );
+        assert!(txs[2].out_recipient_note_pubkeys[0].is_some());
+        assert!(txs[2].out_recipient_note_pubkeys[1].is_none());
+        assert_eq!(txs[2].out_memos[0].as_deref(), Some("salary"));
+        assert!(txs[2].out_memos[1].is_none());
+    }
+
+    #[test]
+    fn merges_notes_while_paying_and_fills_both_slots() {
+        let mut session = PayoutSession::setup(
+            &[note(5, 1), note(7, 2), note(9, 3)],
+            vec![payout(6), payout(4), payout(11)],
+            "POOL".into(),
+            &MinSteps,
+        )
+        .expect("setup payouts");
+        let txs = run(&mut session);
+
+        let paid: Vec<Vec<usize>> = (0..session.len())
+            .map(|index| session.paid_by_step(index))
+            .collect();
+        assert_eq!(paid.concat(), vec![0, 1, 2]);
+        for tx in &txs {
+            assert!(tx.input_commitments.len() <= 2);
+            assert_eq!(tx.ext_amount, ExtAmount::ZERO);
+        }
+        assert!(txs.len() <= 3, "payments share steps with note merges");
+    }
+
+    #[test]
+    fn rejects_insufficient_funds_and_zero_payouts() {
+        assert!(
+            PayoutSession::setup(&[note(5, 1)], vec![payout(6)], "POOL".into(), &MinSteps).is_err()
+        );
+        assert!(matches!(
+            PayoutSession::setup(&[note(5, 1)], vec![payout(0)], "POOL".into(), &MinSteps),
+            Err(SpendSessionError::ZeroPayout)
+        ));
+        assert!(matches!(
+            PayoutSession::setup(&[note(5, 1)], Vec::new(), "POOL".into(), &MinSteps),
+            Err(SpendSessionError::NoPayouts)
+        ));
+    }
+}
@@ -3,8 +3,9 @@
 mod execute;
 mod plan;
 
-pub use execute::{SpendSession, SpendSessionError, SpendTarget, Transact};
+pub use execute::{Payout, PayoutSession, SpendSession, SpendSessionError, SpendTarget, Transact};
 pub use plan::{
-    CombinationResult, PlanError, PlannedStep, SpendableNote, StepAction, StepNote,
-    TRANSACTION_LIMIT, TransactionPlan, find_combination, plan,
+    CoinSelection, CoinSelector, CombinationResult, MinChange, MinSteps, PlanError, PlannedStep,
+    PrivacyPreferring, SpendableNote, StepAction, StepNote, TRANSACTION_LIMIT, TransactionPlan,
+    consolidation_step, find_combination, find_min_change_combination, plan, plan_with,
 };
@@ -24,6 +24,47 @@ pub enum CombinationResult {
     Impossible,
 }
 
+impl CombinationResult {
+    /// Selected indices and change, or `None` for [`Self::Impossible`].
+    pub fn into_selection(self) -> Option<(Vec<usize>, Option<NoteAmount>)> {
+        match self {
+            Self::Impossible => None,
+            Self::OneExact(i) => Some((vec![i], None)),
+            Self::TwoExact(i, j) => Some((vec![i, j], None)),
+            Self::OneOvershoot(i, excess) => Some((vec![i], Some(excess))),
+            Self::TwoOvershoot(i, j, excess) => Some((vec![i, j], Some(excess))),
+            Self::ExactK(indices) => Some((indices, None)),
+            Self::Overshoot(indices, excess) => Some((indices, Some(excess))),
+        }
+    }
+
+    /// Same tier with every index passed through `map`.
+    pub(crate) fn map_indices(self, map: impl Fn(usize) -> usize) -> Self {
+        match self {
+            Self::Impossible => Self::Impossible,
+            Self::OneExact(i) => Self::OneExact(map(i)),
+            Self::TwoExact(i, j) => Self::TwoExact(map(i), map(j)),
+            Self::OneOvershoot(i, excess) => Self::OneOvershoot(map(i), excess),
+            Self::TwoOvershoot(i, j, excess) => Self::TwoOvershoot(map(i), map(j), excess),
+            Self::ExactK(indices) => Self::ExactK(indices.into_iter().map(map).collect()),
+            Self::Overshoot(indices, excess) => {
+                Self::Overshoot(indices.into_iter().map(map).collect(), excess)
+            }
+        }
+    }
+
+    fn from_overshoot(mut indices: Vec<usize>, excess: NoteAmount) -> Self {
+        match (indices.len(), excess.is_zero()) {
+            (1, true) => Self::OneExact(indices.remove(0)),
+            (1, false) => Self::OneOvershoot(indices.remove(0), excess),
+            (2, true) => Self::TwoExact(indices[0], indices[1]),
+            (2, false) => Self::TwoOvershoot(indices[0], indices[1], excess),
+            (_, true) => Self::ExactK(indices),
+            (_, false) => Self::Overshoot(indices, excess),
+        }
+    }
+}
+
 /// Find a combination of elements with sum equal to or larger than the goal,
 /// prioritizing the lowest combination count (with two-note pairs preferred
 /// over single-note exact matches).
@@ -98,6 +139,77 @@ pub fn find_combination(
     }
 }
 
+/// Like [`find_combination`], but minimizes change instead of note count: an
+/// exact combination of up to [`TRANSACTION_LIMIT`] notes beats any
+/// overshoot, and among overshoots the smallest excess wins.
+#[tracing::instrument(level = "trace", skip_all, fields(correlation_id = %correlation_id_or_new(), note_count = values.len(), goal = ?Sensitive(&goal)))]
+pub fn find_min_change_combination(
+    values: &[NoteAmount],
+    goal: NoteAmount,
+) -> Result<CombinationResult, PlanError> {
+    let fewest = find_combination(values, goal)?;
+    let mut best = match &fewest {
+        CombinationResult::OneOvershoot(i, excess) => (*excess, vec![*i]),
+        CombinationResult::TwoOvershoot(i, j, excess) => (*excess, vec![*i, *j]),
+        CombinationResult::Overshoot(indices, excess) => (*excess, indices.clone()),
+        // Exact or impossible: nothing smaller to find.
+        CombinationResult::OneExact(_)
+        | CombinationResult::TwoExact(..)
+        | CombinationResult::ExactK(_)
+        | CombinationResult::Impossible => return Ok(fewest),
+    };
+
+    let mut sorted_vals: Vec<(usize, NoteAmount)> = values.iter().copied().enumerate().collect();
+    sorted_vals.sort_unstable_by_key(|&(_, x)| x);
+    if let OneScan::Overshoot(i, excess) = OneScan::scan(&sorted_vals, goal)
+        && excess < best.0
+    {
+        best = (excess, vec![i]);
+    }
+    if let TwoScan::Overshoot(i, j, excess) = TwoScan::scan(&sorted_vals, goal)?
+        && excess < best.0
+    {
+        best = (excess, vec![i, j]);
+    }
+
+    let mut candidates_desc: Vec<(usize, NoteAmount)> = values
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|&(_, x)| x < goal)
+        .collect();
+    candidates_desc.sort_unstable_by_key(|&(_, x)| std::cmp::Reverse(x));
+    let max_k = std::cmp::min(candidates_desc.len(), TRANSACTION_LIMIT);
+    for k in 3..=max_k {
+        let mut iteration_limit = 5000;
+        let mut path = Vec::new();
+        let mut best_overshoot = None;
+        let mut ctx = DfsContext {
+            candidates: &candidates_desc,
+            goal,
+            target_k: k,
+            iteration_limit: &mut iteration_limit,
+            path: &mut path,
+            best_overshoot: &mut best_overshoot,
+        };
+        if KScan::dfs(NoteAmount::ZERO, 0, &mut ctx)? {
+            tracing::trace!(
+                tier = "min-change-exact",
+                count = k,
+                "min-change scan succeeded"
+            );
+            return Ok(CombinationResult::ExactK(path));
+        }
+        if let Some((excess, overshoot_path)) = best_overshoot
+            && excess < best.0
+        {
+            best = (excess, overshoot_path);
+        }
+    }
+    let (excess, indices) = best;
+    Ok(CombinationResult::from_overshoot(indices, excess))
+}
+
 enum TwoScan {
     Exact(usize, usize),
     Overshoot(usize, usize, NoteAmount),
@@ -361,6 +473,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn min_change_prefers_exact_k_over_two_overshoot() {
+        let dataset = amounts(&[2, 3, 5, 11]);
+        assert_eq!(
+            find(&dataset, goal(10)),
+            CombinationResult::TwoOvershoot(0, 3, excess(3))
+        );
+        assert_eq!(
+            find_min_change_combination(&dataset, goal(10)).expect("min-change combination"),
+            CombinationResult::ExactK(vec![2, 1, 0])
+        );
+    }
+
     #[test]
     fn comb_two_exact_before_one_exact() {
         let dataset = amounts(&[4, 6, 10]);
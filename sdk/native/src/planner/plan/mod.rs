@@ -2,9 +2,15 @@
 
 mod combination;
 mod error;
+mod selector;
 
-pub use combination::{CombinationResult, TRANSACTION_LIMIT, find_combination};
+pub use combination::{
+    CombinationResult, TRANSACTION_LIMIT, find_combination, find_min_change_combination,
+};
 pub use error::PlanError;
+pub use selector::{CoinSelection, CoinSelector, MinChange, MinSteps, PrivacyPreferring};
+
+use serde::{Deserialize, Serialize};
 
 use crate::types::{Field, NoteAmount, Sensitive, correlation_id_or_new};
 
@@ -15,13 +21,13 @@ pub struct TransactionPlan {
 }
 
 /// One on-chain `transact` (at most 2 real inputs after padding).
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PlannedStep {
     pub inputs: (StepNote, Option<StepNote>),
     pub action: StepAction,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum StepAction {
     /// Merge two notes into one.
     Consolidate { output: NoteAmount },
@@ -32,15 +38,18 @@ pub enum StepAction {
 }
 
 /// One row in the wallet index (planner input only).
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SpendableNote {
     /// Stable id (matches DB / UI note id).
     pub commitment: Field,
     pub amount: NoteAmount,
+    /// Ledger the note was received in, when known; used by
+    /// [`PrivacyPreferring`].
+    pub created_at_ledger: Option<u32>,
 }
 
 /// One row in the wallet index (planner step input only).
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StepNote {
     /// Stable id (matches DB / UI note id).
     ///
@@ -107,34 +116,35 @@ fn combo_tier(combo: &CombinationResult) -> &'static str {
     }
 }
 
-/// Build a plan from unspent notes and a target spend amount.
-#[tracing::instrument(skip_all, fields(stage = "transaction_planning", correlation_id = %correlation_id_or_new(), amount = ?Sensitive(&amount), note_count = notes.len()))]
+/// Build a plan from unspent notes and a target spend amount, selecting
+/// notes with [`MinSteps`].
 pub fn plan(
     amount: NoteAmount,
     notes: &[SpendableNote],
+) -> std::result::Result<TransactionPlan, PlanError> {
+    plan_with(amount, notes, &MinSteps)
+}
+
+/// [`plan`] with notes chosen by `selector`.
+#[tracing::instrument(skip_all, fields(stage = "transaction_planning", correlation_id = %correlation_id_or_new(), amount = ?Sensitive(&amount), note_count = notes.len(), selector = ?selector))]
+pub fn plan_with(
+    amount: NoteAmount,
+    notes: &[SpendableNote],
+    selector: &dyn CoinSelector,
 ) -> std::result::Result<TransactionPlan, PlanError> {
     if notes.is_empty() {
         return Err(PlanError::NoSpendableNotes);
     }
 
-    let values: Vec<NoteAmount> = notes.iter().map(|n| n.amount).collect();
-    let combo = find_combination(&values, amount)?;
+    let combo = selector.select(notes, amount)?;
     tracing::debug!(
         combo_tier = combo_tier(&combo),
         "transaction plan combination result"
     );
 
-    let (indices, change) = match combo {
-        CombinationResult::Impossible => {
-            tracing::warn!("transaction planning rejected: no combination possible");
-            return Err(PlanError::NoCombination);
-        }
-        CombinationResult::OneExact(i) => (vec![i], None),
-        CombinationResult::TwoExact(i, j) => (vec![i, j], None),
-        CombinationResult::OneOvershoot(i, excess) => (vec![i], Some(excess)),
-        CombinationResult::TwoOvershoot(i, j, excess) => (vec![i, j], Some(excess)),
-        CombinationResult::ExactK(indices) => (indices, None),
-        CombinationResult::Overshoot(indices, excess) => (indices, Some(excess)),
+    let Some((indices, change)) = combo.into_selection() else {
+        tracing::warn!("transaction planning rejected: no combination possible");
+        return Err(PlanError::NoCombination);
     };
 
     if indices.len() > TRANSACTION_LIMIT {
@@ -198,6 +208,27 @@ pub fn plan(
     TransactionPlan::assemble(steps).ok_or(PlanError::InvalidPlan)
 }
 
+/// One [`StepAction::Consolidate`] step merging the two smallest `notes`, or
+/// `None` when there are fewer than two.
+pub fn consolidation_step(notes: &[SpendableNote]) -> Result<Option<PlannedStep>, PlanError> {
+    let mut sorted: Vec<&SpendableNote> = notes.iter().collect();
+    sorted.sort_unstable_by_key(|note| note.amount);
+    let [first, second, ..] = sorted.as_slice() else {
+        return Ok(None);
+    };
+    let output = first
+        .amount
+        .checked_add(second.amount)
+        .ok_or(PlanError::InputAmountOverflow)?;
+    Ok(Some(PlannedStep {
+        inputs: (
+            StepNote::from_spendable((*first).clone()),
+            Some(StepNote::from_spendable((*second).clone())),
+        ),
+        action: StepAction::Consolidate { output },
+    }))
+}
+
 impl StepNote {
     fn from_spendable(note: SpendableNote) -> Self {
         StepNote {
@@ -294,6 +325,7 @@ mod tests {
         SpendableNote {
             commitment: Field::from(NoteAmount::from(commitment_value)),
             amount: NoteAmount::from(amount),
+            created_at_ledger: None,
         }
     }
 
@@ -621,6 +653,7 @@ mod tests {
         SpendableNote {
             commitment: Field::from(NoteAmount::from(commitment)),
             amount: NoteAmount::from(amount),
+            created_at_ledger: None,
         }
     }
 
@@ -660,6 +693,7 @@ mod tests {
         let merged = SpendableNote {
             commitment: Field::from(NoteAmount::from(900)),
             amount: merge_amount,
+            created_at_ledger: None,
         };
         let merged_commitment = merged.commitment;
 
@@ -693,4 +727,32 @@ mod tests {
         assert_eq!(outputs.0, spend.into(), "final output is spend amount");
         assert!(outputs.1.is_none(), "no change");
     }
+
+    #[test]
+    fn consolidation_merges_two_smallest() {
+        let wallet = vec![
+            committed_note(9, 101),
+            committed_note(2, 102),
+            committed_note(5, 103),
+            committed_note(3, 104),
+        ];
+        let step = consolidation_step(&wallet)
+            .expect("consolidation step")
+            .expect("two notes to merge");
+        let StepAction::Consolidate { output } = step.action else {
+            panic!("expected a consolidate step");
+        };
+        assert_eq!(output, NoteAmount::from(5));
+        let inputs = step.resolve(&wallet).expect("resolve consolidation");
+        assert_eq!(
+            inputs.iter().map(|note| note.amount).collect::<Vec<_>>(),
+            vec![NoteAmount::from(2), NoteAmount::from(3)]
+        );
+
+        assert!(
+            consolidation_step(&wallet[..1])
+                .expect("consolidation step")
+                .is_none()
+        );
+    }
 }
@@ -0,0 +1,202 @@
+//! [`CoinSelector`] strategies: which wallet notes a spend uses.
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    CombinationResult, PlanError, SpendableNote, find_combination, find_min_change_combination,
+};
+use crate::types::NoteAmount;
+
+/// Picks the wallet notes that fund a spend of `amount`.
+///
+/// Returned indices point into `notes`. The planner merges the selection in
+/// order, so the number of notes sets the number of transactions.
+pub trait CoinSelector: fmt::Debug + Send + Sync {
+    fn select(
+        &self,
+        notes: &[SpendableNote],
+        amount: NoteAmount,
+    ) -> Result<CombinationResult, PlanError>;
+}
+
+/// Fewest notes, hence fewest transactions ([`find_combination`] tiers).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinSteps;
+
+impl CoinSelector for MinSteps {
+    fn select(
+        &self,
+        notes: &[SpendableNote],
+        amount: NoteAmount,
+    ) -> Result<CombinationResult, PlanError> {
+        let values: Vec<NoteAmount> = notes.iter().map(|note| note.amount).collect();
+        find_combination(&values, amount)
+    }
+}
+
+/// Smallest change note, even at the cost of extra merge steps.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinChange;
+
+impl CoinSelector for MinChange {
+    fn select(
+        &self,
+        notes: &[SpendableNote],
+        amount: NoteAmount,
+    ) -> Result<CombinationResult, PlanError> {
+        let values: Vec<NoteAmount> = notes.iter().map(|note| note.amount).collect();
+        find_min_change_combination(&values, amount)
+    }
+}
+
+/// Avoids spending two notes received in the same ledger together: notes
+/// that arrived together likely share a sender, and merging them links them
+/// again on-chain. Uses the largest note of each ledger and falls back to
+/// [`MinSteps`] over all notes when those cannot cover the amount.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrivacyPreferring;
+
+impl CoinSelector for PrivacyPreferring {
+    fn select(
+        &self,
+        notes: &[SpendableNote],
+        amount: NoteAmount,
+    ) -> Result<CombinationResult, PlanError> {
+        let mut representatives: Vec<usize> = Vec::with_capacity(notes.len());
+        for (index, note) in notes.iter().enumerate() {
+            let same_ledger = note.created_at_ledger.and_then(|ledger| {
+                representatives
+                    .iter()
+                    .position(|&other| notes[other].created_at_ledger == Some(ledger))
+            });
+            match same_ledger {
+                Some(slot) if note.amount > notes[representatives[slot]].amount => {
+                    representatives[slot] = index;
+                }
+                Some(_) => {}
+                None => representatives.push(index),
+            }
+        }
+        if representatives.len() < notes.len() {
+            let values: Vec<NoteAmount> = representatives
+                .iter()
+                .map(|&index| notes[index].amount)
+                .collect();
+            let combo = find_combination(&values, amount)?;
+            if combo != CombinationResult::Impossible {
+                return Ok(combo.map_indices(|index| representatives[index]));
+            }
+            tracing::debug!("privacy coin selection falls back to notes sharing a ledger");
+        }
+        MinSteps.select(notes, amount)
+    }
+}
+
+/// Built-in [`CoinSelector`] a [`crate::PrivatePool`] plans spends with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CoinSelection {
+    /// [`MinSteps`].
+    #[default]
+    MinSteps,
+    /// [`MinChange`].
+    MinChange,
+    /// [`PrivacyPreferring`].
+    Privacy,
+}
+
+impl CoinSelection {
+    pub const ALL: [Self; 3] = [Self::MinSteps, Self::MinChange, Self::Privacy];
+
+    pub fn selector(self) -> &'static dyn CoinSelector {
+        match self {
+            Self::MinSteps => &MinSteps,
+            Self::MinChange => &MinChange,
+            Self::Privacy => &PrivacyPreferring,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::MinSteps => "min-steps",
+            Self::MinChange => "min-change",
+            Self::Privacy => "privacy",
+        }
+    }
+}
+
+impl fmt::Display for CoinSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CoinSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|selection| selection.as_str() == s)
+            .ok_or_else(|| {
+                format!("unknown coin selection {s:?} (expected min-steps, min-change or privacy)")
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Field;
+
+    fn note(amount: u128, commitment: u128, ledger: u32) -> SpendableNote {
+        SpendableNote {
+            commitment: Field::from(NoteAmount::from(commitment)),
+            amount: NoteAmount::from(amount),
+            created_at_ledger: Some(ledger),
+        }
+    }
+
+    #[test]
+    fn privacy_avoids_notes_from_one_ledger() {
+        let notes = [
+            note(3, 1, 100),
+            note(7, 2, 100),
+            note(4, 3, 101),
+            note(6, 4, 102),
+        ];
+        let goal = NoteAmount::from(10u128);
+
+        assert_eq!(
+            MinSteps.select(&notes, goal).expect("min-steps selection"),
+            CombinationResult::TwoExact(0, 1)
+        );
+        assert_eq!(
+            PrivacyPreferring
+                .select(&notes, goal)
+                .expect("privacy selection"),
+            CombinationResult::TwoExact(2, 3)
+        );
+    }
+
+    #[test]
+    fn privacy_falls_back_when_ledgers_cannot_cover() {
+        let notes = [note(4, 1, 100), note(6, 2, 100)];
+        assert_eq!(
+            PrivacyPreferring
+                .select(&notes, NoteAmount::from(10u128))
+                .expect("privacy selection"),
+            CombinationResult::TwoExact(0, 1)
+        );
+    }
+
+    #[test]
+    fn coin_selection_names_roundtrip() {
+        for selection in CoinSelection::ALL {
+            assert_eq!(selection.as_str().parse::<CoinSelection>(), Ok(selection));
+        }
+        assert!("fastest".parse::<CoinSelection>().is_err());
+    }
+}
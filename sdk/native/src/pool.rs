@@ -1,11 +1,13 @@
 //! Async per-pool private payments API
 
 use crate::{
-    planner::{SpendableNote, Transact},
+    planner::{CoinSelection, SpendableNote, Transact},
     types::{EncryptionPublicKey, NoteAmount, NotePublicKey, Sensitive, UserNoteSummary},
 };
 
-use crate::chain::{Limits, ReadXdr, StateFetcher, TransactionEnvelope, submit_tx};
+use crate::chain::{
+    Limits, ReadXdr, StateFetcher, TransactionEnvelope, TxConfirmStatus, submit_tx, tx_hash,
+};
 
 use crate::{
     PoolCore, PreparedTransaction,
@@ -23,15 +25,18 @@ use crate::{
     signer::Signer,
     sleep::sleep,
     storage::Storage,
-    sync::{SyncHandle, confirm_tx},
+    sync::{SyncHandle, confirm_tx, poll_tx_status},
     transact::transact_request_from_step,
     types::{
         AspMembershipSync, DisclosureContext, DisclosureReceipt, DisclosureVerificationReport,
-        Estimate, PrivatePoolConfig, SignedTransaction, TransactChainContext, TransactionResult,
-        TransferRecipient,
+        Estimate, PaymentRequest, PayoutReport, PayoutStatus, PlanStatus, PrivatePoolConfig,
+        ResumedPlan, SignedTransaction, StoredPlan, TransactChainContext, TransactionResult,
+        TransferOptions, TransferRecipient,
     },
 };
 
+use web_time::{SystemTime, UNIX_EPOCH};
+
 const POLL_INTERVAL_MS: u32 = 200;
 const SYNC_MAX_RETRIES: u32 = 50;
 const DISCLOSE_MAX_RETRIES: u32 = 50;
@@ -77,6 +82,12 @@ impl<S> PrivatePool<S> {
     pub fn config(&self) -> &PrivatePoolConfig {
         &self.config
     }
+
+    /// Choose how later spends pick their input notes.
+    pub fn set_coin_selection(&mut self, coin_selection: CoinSelection) {
+        self.config.coin_selection = coin_selection;
+        self.core.set_coin_selection(coin_selection);
+    }
 }
 
 impl<S: Storage> PrivatePool<S> {
@@ -96,7 +107,10 @@ impl<S: Storage> PrivatePool<S> {
     pub async fn notes(&self) -> Result<Vec<UserNoteSummary>, Error> {
         self.ensure_synced().await?;
         self.storage
-            .notes(&self.config.pool_contract_id, &self.config.user_address)
+            .notes(
+                &self.config.pool_contract_id,
+                &self.config.storage_address(),
+            )
             .await
     }
 
@@ -115,19 +129,193 @@ impl<S: Storage> PrivatePool<S> {
             .ok_or_else(|| Error::Other("deposit produced no transaction".into()))
     }
 
-    #[tracing::instrument(skip(self, recipient), fields(correlation_id = %correlation_id_or_new(), amount = ?Sensitive(amount)))]
+    /// Deposit public tokens straight into `recipient`'s shielded balance; no
+    /// note is created for this account.
+    #[tracing::instrument(skip(self, recipient, options), fields(correlation_id = %correlation_id_or_new(), amount = ?Sensitive(amount)))]
+    pub async fn deposit_to(
+        &self,
+        recipient: impl Into<TransferRecipient>,
+        amount: NoteAmount,
+        options: TransferOptions,
+    ) -> Result<TransactionResult, Error> {
+        self.deposit_to_many(vec![(recipient.into(), amount)], options)
+            .await?
+            .pop()
+            .ok_or_else(|| Error::Other("deposit produced no transaction".into()))
+    }
+
+    /// Deposit to many recipients, two per transaction; see
+    /// [`Self::deposit_to`]. Results are in transaction order.
+    #[tracing::instrument(skip_all, fields(correlation_id = %correlation_id_or_new(), recipients = recipients.len()))]
+    pub async fn deposit_to_many(
+        &self,
+        recipients: Vec<(TransferRecipient, NoteAmount)>,
+        options: TransferOptions,
+    ) -> Result<Vec<TransactionResult>, Error> {
+        tracing::info!(
+            recipients = recipients.len(),
+            "deposit to recipients started"
+        );
+        let mut plan = self.prepare_deposit_to(recipients, options).await?;
+        self.execute(&mut plan).await
+    }
+
+    #[tracing::instrument(skip(self, recipient, options), fields(correlation_id = %correlation_id_or_new(), amount = ?Sensitive(amount)))]
     pub async fn transfer(
         &self,
         recipient: impl Into<TransferRecipient>,
         amount: NoteAmount,
+        options: TransferOptions,
     ) -> Result<Vec<TransactionResult>, Error> {
         let recipient = recipient.into();
         tracing::info!(recipient = ?Sensitive(&recipient), amount = ?Sensitive(amount), "transfer started");
         let wallet = self.spendable_notes().await?;
-        let mut plan = self.prepare_transfer(&wallet, recipient, amount).await?;
+        let mut plan = self
+            .prepare_transfer(&wallet, recipient, amount, options)
+            .await?;
         self.execute(&mut plan).await
     }
 
+    /// Pay several recipients in one chain of transactions, merging wallet
+    /// notes and paying in the same steps; see
+    /// [`PoolCore::prepare_transfer_many`]. If the chain stops after some
+    /// steps confirmed, the report holds who was paid and the failure; it is
+    /// an error only when nothing confirmed. A recipient's memo overrides
+    /// `options.memo` for that recipient.
+    #[tracing::instrument(skip_all, fields(correlation_id = %correlation_id_or_new(), recipients = recipients.len()))]
+    pub async fn transfer_many(
+        &self,
+        recipients: Vec<(TransferRecipient, NoteAmount, Option<String>)>,
+        options: TransferOptions,
+    ) -> Result<PayoutReport, Error> {
+        tracing::info!(
+            recipients = recipients.len(),
+            "transfer to recipients started"
+        );
+        let wallet = self.spendable_notes().await?;
+        let mut plan = self
+            .prepare_transfer_many(&wallet, recipients.clone(), options)
+            .await?;
+        let paid_by_step = plan.paid_by_step();
+        let (transactions, error) = match self.execute(&mut plan).await {
+            Ok(results) => (results, None),
+            Err(Error::PlanExecution(PlanExecutionError { completed, cause })) => {
+                (completed, Some(*cause))
+            }
+            Err(error) => return Err(error),
+        };
+
+        let mut payouts: Vec<PayoutStatus> = recipients
+            .into_iter()
+            .map(|(recipient, amount, memo)| PayoutStatus {
+                recipient,
+                amount,
+                memo,
+                tx_hash: None,
+            })
+            .collect();
+        for (result, paid) in transactions.iter().zip(&paid_by_step) {
+            for index in paid {
+                if let Some(payout) = payouts.get_mut(*index) {
+                    payout.tx_hash = Some(result.tx_hash.clone());
+                }
+            }
+        }
+        Ok(PayoutReport {
+            payouts,
+            transactions,
+            error,
+        })
+    }
+
+    /// Merge the two smallest spendable notes into one note to self in a
+    /// single transaction; `None` when there are fewer than two. See
+    /// [`crate::BackgroundSync::with_consolidation`] to do this on idle sync
+    /// rounds.
+    #[tracing::instrument(skip_all, fields(correlation_id = %correlation_id_or_new()))]
+    pub async fn consolidate(&self) -> Result<Option<TransactionResult>, Error> {
+        self.consolidate_with(SYNC_MAX_RETRIES).await
+    }
+
+    /// [`Self::consolidate`] from inside [`crate::BackgroundSync::run`]: the
+    /// indexer cannot advance until it returns, so it fails with
+    /// `SyncRequired` at once instead of waiting for a sync.
+    pub(crate) async fn consolidate_without_sync_wait(
+        &self,
+    ) -> Result<Option<TransactionResult>, Error> {
+        self.consolidate_with(0).await
+    }
+
+    async fn consolidate_with(
+        &self,
+        sync_retries: u32,
+    ) -> Result<Option<TransactionResult>, Error> {
+        let wallet = self.spendable_notes().await?;
+        let Some(mut plan) = self.core.prepare_consolidation(&wallet)? else {
+            return Ok(None);
+        };
+        tracing::info!(notes = wallet.len(), "consolidation started");
+        Ok(self.execute_with(&mut plan, sync_retries).await?.pop())
+    }
+
+    /// Finish or abandon the multi-step plans persisted for this account and
+    /// pool, e.g. after a crash or a closed tab part way through a plan.
+    ///
+    /// A step left in flight is looked up on-chain first: the plan moves past
+    /// it if it confirmed and retries it if it failed. A step still unknown
+    /// after 30s is retried only when it spends notes, since the pool rejects
+    /// a second spend of them; a step without inputs abandons the plan, as a
+    /// retry could deposit twice. After syncing, a plan whose next step spends
+    /// a note that is no longer spendable is abandoned; the others run their
+    /// remaining steps and stay active if they stop again. Call this while no
+    /// other plan of the account is running.
+    #[tracing::instrument(skip_all, fields(correlation_id = %correlation_id_or_new()))]
+    pub async fn resume_plans(&self) -> Result<Vec<ResumedPlan>, Error> {
+        let plans = self.active_plans().await?;
+        tracing::info!(plans = plans.len(), "resume plans started");
+        let mut resumed = Vec::with_capacity(plans.len());
+        for stored in plans {
+            resumed.push(self.resume_plan(stored).await?);
+        }
+        Ok(resumed)
+    }
+
+    /// Pay an `spp:` [`PaymentRequest`] URI: a private transfer of its amount
+    /// and memo to its recipient keys. Fails when the request is for another
+    /// pool or has expired.
+    #[tracing::instrument(skip_all, fields(correlation_id = %correlation_id_or_new()))]
+    pub async fn pay_request(&self, uri: &str) -> Result<Vec<TransactionResult>, Error> {
+        let request = self.payment_request(uri)?;
+        tracing::info!(amount = ?Sensitive(request.amount), "pay request started");
+        self.transfer(
+            TransferRecipient::keys(request.note_public_key, request.encryption_public_key),
+            request.amount,
+            TransferOptions { memo: request.memo },
+        )
+        .await
+    }
+
+    /// Parse an `spp:` payment request URI and check that it can be paid
+    /// from this pool: same pool contract and not expired.
+    pub fn payment_request(&self, uri: &str) -> Result<PaymentRequest, Error> {
+        let request = PaymentRequest::parse(uri)
+            .map_err(|e| Error::Other(format!("payment request: {e:#}")))?;
+        if request.pool_contract_id != self.config.pool_contract_id {
+            return Err(Error::Other(format!(
+                "payment request is for pool {}, not {}",
+                request.pool_contract_id, self.config.pool_contract_id
+            )));
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| Error::Other(format!("system clock: {e}")))?
+            .as_secs();
+        if request.is_expired(now) {
+            return Err(Error::Other("payment request has expired".into()));
+        }
+        Ok(request)
+    }
+
     #[tracing::instrument(skip(self, recipient), fields(correlation_id = %correlation_id_or_new(), amount = ?Sensitive(amount)))]
     pub async fn withdraw(
         &self,
@@ -187,7 +375,7 @@ impl<S: Storage> PrivatePool<S> {
                 .map_err(|e| Error::Other(format!("invalid pool merkle_next_index: {e}")))?;
 
             let inputs_req = DisclosureInputsRequest {
-                user_address: self.config.user_address.clone(),
+                user_address: self.config.storage_address(),
                 pool_address: self.config.pool_contract_id.clone(),
                 selected_commitments: selected_commitments.clone(),
                 pool_root: Some(pool_root),
@@ -263,7 +451,10 @@ impl<S: Storage> PrivatePool<S> {
     pub async fn spendable_notes(&self) -> Result<Vec<SpendableNote>, Error> {
         self.ensure_synced().await?;
         self.storage
-            .spendable_notes(&self.config.pool_contract_id, &self.config.user_address)
+            .spendable_notes(
+                &self.config.pool_contract_id,
+                &self.config.storage_address(),
+            )
             .await
     }
 
@@ -271,16 +462,55 @@ impl<S: Storage> PrivatePool<S> {
         self.core.prepare_deposit(amount)
     }
 
+    /// Plan for [`Self::deposit_to_many`], resolving each recipient like
+    /// [`Self::prepare_transfer`].
+    pub async fn prepare_deposit_to(
+        &self,
+        recipients: Vec<(TransferRecipient, NoteAmount, Option<String>)>,
+        options: TransferOptions,
+    ) -> Result<PreparedTransactionPlan, Error> {
+        let mut resolved = Vec::with_capacity(recipients.len());
+        for (recipient, amount, memo) in recipients {
+            let (note_public_key, encryption_public_key) =
+                self.resolve_transfer_recipient(recipient).await?;
+            resolved.push((note_public_key, encryption_public_key, amount, memo));
+        }
+        self.core.prepare_deposit_to(resolved, options)
+    }
+
     pub async fn prepare_transfer(
         &self,
         wallet: &[SpendableNote],
         recipient: impl Into<TransferRecipient>,
         amount: NoteAmount,
+        options: TransferOptions,
     ) -> Result<PreparedTransactionPlan, Error> {
         let (note_public_key, encryption_public_key) =
             self.resolve_transfer_recipient(recipient.into()).await?;
-        self.core
-            .prepare_transfer(wallet, note_public_key, encryption_public_key, amount)
+        self.core.prepare_transfer(
+            wallet,
+            note_public_key,
+            encryption_public_key,
+            amount,
+            options,
+        )
+    }
+
+    /// Plan for [`Self::transfer_many`], resolving each recipient like
+    /// [`Self::prepare_transfer`].
+    pub async fn prepare_transfer_many(
+        &self,
+        wallet: &[SpendableNote],
+        recipients: Vec<(TransferRecipient, NoteAmount)>,
+        options: TransferOptions,
+    ) -> Result<PreparedTransactionPlan, Error> {
+        let mut resolved = Vec::with_capacity(recipients.len());
+        for (recipient, amount) in recipients {
+            let (note_public_key, encryption_public_key) =
+                self.resolve_transfer_recipient(recipient).await?;
+            resolved.push((note_public_key, encryption_public_key, amount));
+        }
+        self.core.prepare_transfer_many(wallet, resolved, options)
     }
 
     pub fn prepare_withdraw(
@@ -304,12 +534,7 @@ impl<S: Storage> PrivatePool<S> {
     }
 
     pub async fn submit(&self, signed_tx: SignedTransaction) -> Result<String, Error> {
-        let envelope = TransactionEnvelope::from_xdr_base64(&signed_tx.signed_xdr, Limits::none())
-            .map_err(|e| Error::Other(format!("invalid signed transaction xdr: {e}")))?;
-
-        submit_tx(&self.rpc, &envelope)
-            .await
-            .map_err(|e| Error::Other(format!("submit transaction: {e:#}")))
+        self.submit_envelope(&signed_envelope(&signed_tx)?).await
     }
 
     pub async fn confirm(&self, hash: &str) -> Result<TransactionResult, Error> {
@@ -320,6 +545,79 @@ impl<S: Storage> PrivatePool<S> {
         self.signer.sign_transaction(prepared).await
     }
 
+    /// [`Self::submit`] the step of `plan` proved last. The plan is persisted
+    /// with that step in flight under the transaction's hash before it is
+    /// sent, so [`Self::resume_plans`] can reconcile the step even if
+    /// submitting fails after the network accepted it.
+    pub async fn submit_step(
+        &self,
+        plan: &mut PreparedTransactionPlan,
+        signed_tx: SignedTransaction,
+    ) -> Result<String, Error> {
+        let envelope = signed_envelope(&signed_tx)?;
+        let network = self
+            .rpc
+            .get_network()
+            .await
+            .map_err(|e| Error::Other(format!("get network: {e:#}")))?;
+        let hash = tx_hash(&envelope, &network.passphrase)
+            .map_err(|e| Error::Other(format!("transaction hash: {e:#}")))?;
+        let stored = plan.submitted(
+            &self.config.pool_contract_id,
+            &self.config.storage_address(),
+            &hash,
+        );
+        self.journal(plan, stored).await;
+
+        let submitted = self.submit_envelope(&envelope).await?;
+        if submitted != hash {
+            tracing::warn!(hash, submitted, "rpc reported a different transaction hash");
+        }
+        Ok(submitted)
+    }
+
+    /// [`Self::confirm`] a step sent with [`Self::submit_step`] and persist
+    /// the plan past it.
+    pub async fn confirm_step(
+        &self,
+        plan: &mut PreparedTransactionPlan,
+        hash: &str,
+    ) -> Result<TransactionResult, Error> {
+        let result = self.confirm(hash).await?;
+        let stored = plan.confirm(
+            &self.config.pool_contract_id,
+            &self.config.storage_address(),
+            hash,
+        );
+        self.journal(plan, stored).await;
+        Ok(result)
+    }
+
+    /// Plans persisted for this account and pool that are neither completed
+    /// nor abandoned, oldest first.
+    pub async fn active_plans(&self) -> Result<Vec<StoredPlan>, Error> {
+        self.storage
+            .active_plans(
+                &self.config.pool_contract_id,
+                &self.config.storage_address(),
+            )
+            .await
+    }
+
+    /// Mark an active plan abandoned, so [`Self::resume_plans`] leaves it. A
+    /// step already in flight may still confirm.
+    pub async fn abandon_plan(&self, plan_id: i64) -> Result<(), Error> {
+        let mut stored = self
+            .active_plans()
+            .await?
+            .into_iter()
+            .find(|plan| plan.id == Some(plan_id))
+            .ok_or_else(|| Error::Other(format!("no active transaction plan {plan_id}")))?;
+        stored.status = PlanStatus::Abandoned;
+        self.storage.save_plan(&stored).await?;
+        Ok(())
+    }
+
     // helpers
 
     async fn ensure_synced(&self) -> Result<(), Error> {
@@ -346,6 +644,13 @@ impl<S: Storage> PrivatePool<S> {
                     )
                     .await
             }
+            TransferRecipient::SubAccount(index) => {
+                let address = self
+                    .storage
+                    .ensure_sub_account(&self.config.user_address, index)
+                    .await?;
+                self.storage.user_public_keys(&address).await
+            }
         }
     }
 
@@ -368,29 +673,40 @@ impl<S: Storage> PrivatePool<S> {
         };
         let req = transact_request_from_step(
             &step,
-            &self.config.user_address,
+            &self.config.storage_address(),
             &self.config.pool_contract_id,
             &chain,
         );
 
-        let params = self.storage.build_transact_params(&req).await?;
+        let params = match self.storage.build_transact_params(&req).await {
+            Err(Error::MembershipSync(AspMembershipSync::RegisterAtASP))
+                if self.config.sub_account != 0 =>
+            {
+                return Err(self.sub_account_not_admitted().await?);
+            }
+            params => params?,
+        };
         let prepared = self.prover.prove_transact(params).await?;
 
         plan.finish_proved_tx(&prepared.prepared.output_commitments)?;
         Ok(prepared)
     }
 
+    /// A sub-account's leaf is derived locally and never inserted by
+    /// onboarding, so waiting for the ASP tree to catch up cannot help.
+    async fn sub_account_not_admitted(&self) -> Result<Error, Error> {
+        let address = self.config.storage_address();
+        let (note, _) = self.storage.user_public_keys(&address).await?;
+        let blinding = self.storage.asp_secret(&address).await?;
+        Ok(Error::SubAccountNotAdmitted {
+            index: self.config.sub_account,
+            leaf: crate::crypto::derive_asp_user_leaf(&note, &blinding)?,
+        })
+    }
+
     async fn fetch_transact_chain_context(&self) -> Result<TransactChainContext, Error> {
-        let (note_pub, _) = self
-            .storage
-            .user_public_keys(&self.config.user_address)
-            .await?;
         self.fetcher
-            .transact_chain_context(
-                &self.config.pool_contract_id,
-                &note_pub,
-                &self.config.user_address,
-            )
+            .transact_chain_context(&self.config.pool_contract_id)
             .await
             .map_err(|e| Error::Other(format!("fetch chain context: {e:#}")))
     }
@@ -398,6 +714,16 @@ impl<S: Storage> PrivatePool<S> {
     async fn execute(
         &self,
         plan: &mut PreparedTransactionPlan,
+    ) -> Result<Vec<TransactionResult>, Error> {
+        self.execute_with(plan, SYNC_MAX_RETRIES).await
+    }
+
+    /// Run `plan` to completion, waiting for a sync at most `sync_retries`
+    /// times per step while proving reports `SyncRequired`.
+    async fn execute_with(
+        &self,
+        plan: &mut PreparedTransactionPlan,
+        sync_retries: u32,
     ) -> Result<Vec<TransactionResult>, Error> {
         let mut results = Vec::new();
         while !plan.is_complete() {
@@ -410,7 +736,7 @@ impl<S: Storage> PrivatePool<S> {
                         Ok(prepared) => break prepared,
                         Err(Error::MembershipSync(AspMembershipSync::SyncRequired(gap))) => {
                             sync_waits = sync_waits.saturating_add(1);
-                            if sync_waits > SYNC_MAX_RETRIES {
+                            if sync_waits > sync_retries {
                                 return Err(PlanExecutionError::into_error(
                                     results,
                                     Error::MembershipSync(AspMembershipSync::SyncRequired(gap)),
@@ -432,14 +758,14 @@ impl<S: Storage> PrivatePool<S> {
                 Ok(signed) => signed,
                 Err(error) => return Err(PlanExecutionError::into_error(results, error)),
             };
-            let hash = match self.submit(signed).await {
+            let hash = match self.submit_step(plan, signed).await {
                 Ok(hash) => {
                     tracing::info!(hash, "transaction submitted");
                     hash
                 }
                 Err(error) => return Err(PlanExecutionError::into_error(results, error)),
             };
-            let result = match self.confirm(&hash).await {
+            let result = match self.confirm_step(plan, &hash).await {
                 Ok(result) => {
                     tracing::info!(hash, "transaction confirmed");
                     result
@@ -451,11 +777,314 @@ impl<S: Storage> PrivatePool<S> {
         Ok(results)
     }
 
+    /// Save `stored` as `plan`'s journal entry. Failures are only logged: the
+    /// plan keeps running, it just cannot be resumed from this point.
+    async fn journal(&self, plan: &mut PreparedTransactionPlan, stored: Result<StoredPlan, Error>) {
+        let saved = match stored {
+            Ok(stored) => self.storage.save_plan(&stored).await,
+            Err(error) => Err(error),
+        };
+        match saved {
+            Ok(id) => plan.set_id(id),
+            Err(error) => tracing::warn!("transaction plan not persisted: {error}"),
+        }
+    }
+
+    async fn submit_envelope(&self, envelope: &TransactionEnvelope) -> Result<String, Error> {
+        submit_tx(&self.rpc, envelope)
+            .await
+            .map_err(|e| Error::Other(format!("submit transaction: {e:#}")))
+    }
+
+    async fn resume_plan(&self, mut stored: StoredPlan) -> Result<ResumedPlan, Error> {
+        let plan_id = stored
+            .id
+            .ok_or_else(|| Error::Other("stored plan has no id".into()))?;
+        tracing::info!(plan_id, "resume plan");
+
+        let mut landed = None;
+        let mut unknown = None;
+        let state = match &stored.in_flight {
+            None => stored.state.clone(),
+            Some(step) => match poll_tx_status(&self.rpc, &step.tx_hash).await {
+                Ok(TxConfirmStatus::Success) => {
+                    landed = Some(step.tx_hash.clone());
+                    step.state.clone()
+                }
+                Ok(TxConfirmStatus::Failed { .. }) => stored.state.clone(),
+                Ok(TxConfirmStatus::Pending) => {
+                    unknown = Some(step.tx_hash.clone());
+                    stored.state.clone()
+                }
+                Err(error) => return Ok(resumed(&stored, plan_id, Some(error))),
+            },
+        };
+        let mut plan = match PreparedTransactionPlan::from_stored(&stored, &state) {
+            Ok(plan) => plan,
+            Err(error) => return self.abandon_stored(stored, error).await,
+        };
+        if let Some(hash) = unknown
+            && plan.next_inputs().is_ok_and(|inputs| inputs.is_empty())
+        {
+            let error = Error::Other(format!(
+                "transaction {hash} is still unknown on-chain; its step has no inputs, so a \
+                 retry could deposit twice"
+            ));
+            return self.abandon_stored(stored, error).await;
+        }
+        let pool_contract_id = &self.config.pool_contract_id;
+        let user_address = self.config.storage_address();
+        let reconciled = match landed {
+            Some(hash) => plan.confirm(pool_contract_id, &user_address, &hash),
+            None if plan.is_complete() => {
+                plan.stored(pool_contract_id, &user_address, PlanStatus::Completed)
+            }
+            None => plan.stored(pool_contract_id, &user_address, PlanStatus::Active),
+        };
+        stored = match reconciled {
+            Ok(reconciled) => reconciled,
+            Err(error) => return self.abandon_stored(stored, error).await,
+        };
+        self.storage.save_plan(&stored).await?;
+        if plan.is_complete() {
+            return Ok(resumed(&stored, plan_id, None));
+        }
+
+        // Outputs of the confirmed steps must be indexed before they are spent.
+        if let Err(error) = self.ensure_synced().await {
+            return Ok(resumed(&stored, plan_id, Some(error)));
+        }
+        let inputs = match plan.next_inputs() {
+            Ok(inputs) => inputs,
+            Err(error) => return self.abandon_stored(stored, error).await,
+        };
+        let spendable = self
+            .storage
+            .spendable_notes(pool_contract_id, &user_address)
+            .await?;
+        if let Some(missing) = inputs
+            .iter()
+            .find(|commitment| !spendable.iter().any(|note| note.commitment == **commitment))
+        {
+            let error = Error::Other(format!("plan input note {missing} is no longer spendable"));
+            return self.abandon_stored(stored, error).await;
+        }
+
+        let error = match self.execute(&mut plan).await {
+            Ok(_) => None,
+            Err(Error::PlanExecution(PlanExecutionError { cause, .. })) => Some(*cause),
+            Err(error) => Some(error),
+        };
+        let status = if plan.is_complete() {
+            PlanStatus::Completed
+        } else {
+            PlanStatus::Active
+        };
+        Ok(ResumedPlan {
+            plan_id,
+            status,
+            transactions: transaction_results(plan.confirmed()),
+            error,
+        })
+    }
+
+    async fn abandon_stored(
+        &self,
+        mut stored: StoredPlan,
+        error: Error,
+    ) -> Result<ResumedPlan, Error> {
+        tracing::warn!(plan_id = ?stored.id, "transaction plan abandoned: {error}");
+        stored.status = PlanStatus::Abandoned;
+        let plan_id = self.storage.save_plan(&stored).await?;
+        Ok(ResumedPlan {
+            plan_id,
+            status: PlanStatus::Abandoned,
+            transactions: transaction_results(&stored.confirmed),
+            error: Some(error),
+        })
+    }
+
     async fn deposit_transact_step(&self, amount: NoteAmount) -> Result<Transact, Error> {
         let (note_pub, enc_pub) = self
             .storage
-            .user_public_keys(&self.config.user_address)
+            .user_public_keys(&self.config.storage_address())
             .await?;
         self.core.deposit_transact_step(note_pub, enc_pub, amount)
     }
 }
+
+fn resumed(stored: &StoredPlan, plan_id: i64, error: Option<Error>) -> ResumedPlan {
+    ResumedPlan {
+        plan_id,
+        status: stored.status,
+        transactions: transaction_results(&stored.confirmed),
+        error,
+    }
+}
+
+fn transaction_results(hashes: &[String]) -> Vec<TransactionResult> {
+    hashes
+        .iter()
+        .map(|tx_hash| TransactionResult {
+            tx_hash: tx_hash.clone(),
+        })
+        .collect()
+}
+
+fn signed_envelope(signed_tx: &SignedTransaction) -> Result<TransactionEnvelope, Error> {
+    TransactionEnvelope::from_xdr_base64(&signed_tx.signed_xdr, Limits::none())
+        .map_err(|e| Error::Other(format!("invalid signed transaction xdr: {e}")))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::{
+        LocalStorage, NoopProver, NoopSigner, SyncMode,
+        planner::CoinSelection,
+        types::{ContractConfig, Field},
+    };
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{body_string_contains, method},
+    };
+
+    const POOL_CONTRACT_ID: &str = "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABSC4";
+    const USER_ADDRESS: &str = "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF";
+    const TX_HASH: &str = "aa";
+
+    const TEST_CONFIG_JSON: &str = r#"{
+        "network": "test",
+        "deployer": "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF",
+        "admin": "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF",
+        "asp_membership": "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABSC4",
+        "asp_non_membership": "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABSC4",
+        "verifiers": {
+            "AB": "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABSC4"
+        },
+        "public_key_registry": "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABSC4",
+        "pools": [{
+            "poolContractId": "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABSC4",
+            "tokenContractId": "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABSC4",
+            "deploymentLedger": 1,
+            "enabled": true,
+            "policyFlags": ["allowlist", "blocklist"],
+            "asset": {"kind": "native"}
+        }]
+    }"#;
+
+    /// Pool over a fresh wallet database whose RPC answers `getTransaction`
+    /// with `status`; every other RPC call fails.
+    async fn test_pool(status: &str) -> (MockServer, PrivatePool<LocalStorage>) {
+        static RUN: AtomicUsize = AtomicUsize::new(0);
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("getTransaction"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": { "status": status },
+            })))
+            .mount(&server)
+            .await;
+
+        let db_path = std::env::temp_dir().join(format!(
+            "stellar-pool-test-{}-{}.sqlite",
+            std::process::id(),
+            RUN.fetch_add(1, Ordering::Relaxed),
+        ));
+        let _ = std::fs::remove_file(&db_path);
+        let storage = LocalStorage::open(&db_path.to_string_lossy()).expect("open storage");
+
+        let contract_config: ContractConfig =
+            serde_json::from_str(TEST_CONFIG_JSON).expect("test config");
+        let config = PrivatePoolConfig {
+            contract_config,
+            pool_contract_id: POOL_CONTRACT_ID.into(),
+            user_address: USER_ADDRESS.into(),
+            sub_account: 0,
+            coin_selection: CoinSelection::default(),
+        };
+        let sync = SyncHandle::inline(None);
+        sync.set_mode(SyncMode::Background);
+        let pool = PrivatePool::init(
+            RpcClient::new(&server.uri()).expect("rpc client"),
+            config,
+            storage,
+            Handle::from_box(Box::new(NoopSigner::new(USER_ADDRESS)) as Box<dyn Signer>),
+            Handle::from_box(Box::new(NoopProver) as Box<dyn Prover>),
+            sync,
+        )
+        .expect("pool");
+        (server, pool)
+    }
+
+    /// Journal a deposit plan with its only step in flight as [`TX_HASH`].
+    async fn journal_in_flight_deposit(pool: &PrivatePool<LocalStorage>) -> i64 {
+        let mut plan = PreparedTransactionPlan::deposit(NoteAmount::from(5u128));
+        plan.finish_proved_tx(&[Field::ZERO, Field::ZERO])
+            .expect("finish proved deposit");
+        let stored = plan
+            .submitted(POOL_CONTRACT_ID, USER_ADDRESS, TX_HASH)
+            .expect("submitted plan");
+        pool.storage.save_plan(&stored).await.expect("save plan")
+    }
+
+    #[tokio::test]
+    async fn resume_confirms_a_step_that_landed() {
+        let (_server, pool) = test_pool("SUCCESS").await;
+        let plan_id = journal_in_flight_deposit(&pool).await;
+
+        let resumed = pool.resume_plans().await.expect("resume plans");
+        assert_eq!(resumed.len(), 1);
+        assert_eq!(resumed[0].plan_id, plan_id);
+        assert_eq!(resumed[0].status, PlanStatus::Completed);
+        assert!(resumed[0].error.is_none());
+        let hashes: Vec<_> = resumed[0]
+            .transactions
+            .iter()
+            .map(|tx| tx.tx_hash.as_str())
+            .collect();
+        assert_eq!(hashes, [TX_HASH]);
+        assert!(pool.active_plans().await.expect("active plans").is_empty());
+    }
+
+    #[tokio::test]
+    async fn resume_retries_a_step_that_failed() {
+        let (_server, pool) = test_pool("FAILED").await;
+        let plan_id = journal_in_flight_deposit(&pool).await;
+
+        let resumed = pool.resume_plans().await.expect("resume plans");
+        assert_eq!(resumed.len(), 1);
+        assert_eq!(resumed[0].plan_id, plan_id);
+        assert_eq!(resumed[0].status, PlanStatus::Active);
+        assert!(resumed[0].transactions.is_empty());
+        assert!(
+            resumed[0].error.is_some(),
+            "the retry stops at the mock RPC"
+        );
+
+        let active = pool.active_plans().await.expect("active plans");
+        assert_eq!(active.len(), 1);
+        assert!(active[0].in_flight.is_none());
+        let plan = PreparedTransactionPlan::from_stored(&active[0], &active[0].state)
+            .expect("restore plan");
+        assert_eq!(plan.current_tx(), 0, "the failed step runs again");
+    }
+
+    #[tokio::test]
+    async fn resume_abandons_an_unknown_step_without_inputs() {
+        let (_server, pool) = test_pool("NOT_FOUND").await;
+        let plan_id = journal_in_flight_deposit(&pool).await;
+
+        let resumed = pool.resume_plans().await.expect("resume plans");
+        assert_eq!(resumed.len(), 1);
+        assert_eq!(resumed[0].plan_id, plan_id);
+        assert_eq!(resumed[0].status, PlanStatus::Abandoned);
+        let error = resumed[0].error.as_ref().expect("abandon reason");
+        assert!(error.to_string().contains("still unknown on-chain"));
+        assert!(pool.active_plans().await.expect("active plans").is_empty());
+    }
+}
@@ -1,8 +1,8 @@
 use std::{cell::RefCell, collections::HashMap};
 
 use crate::{
-    types::{DisclosureReceipt, PolicyFlags},
-    zk::flows::TransactParams,
+    types::{DisclosureReceipt, GvkMode, PolicyFlags},
+    zk::flows::{NotePossessionParams, TransactParams},
 };
 
 use super::{Prover, ProverEngine};
@@ -13,9 +13,16 @@ use crate::{
 
 /// In-process Groth16 prover for pool transact circuits.
 ///
-/// Holds one [`ProverEngine`] per [`PolicyFlags`]. Witness/proof generation
-/// follows `params.policy_flags` (on-chain pool policy).
-pub struct LocalProver(RefCell<HashMap<PolicyFlags, ProverEngine>>);
+/// Holds one [`ProverEngine`] per `(PolicyFlags, GvkMode)`. Witness/proof
+/// generation follows `params.policy_flags` and `params.gvk_mode` (on-chain
+/// pool configuration). [`LocalProver::from_artifacts`] registers the plain
+/// circuits; GVK pools need [`LocalProver::with_gvk_artifacts`]. The
+/// `notePossession` engine used for key registration is optional; see
+/// [`LocalProver::with_note_possession`].
+pub struct LocalProver {
+    engines: RefCell<HashMap<(PolicyFlags, GvkMode), ProverEngine>>,
+    note_possession: Option<RefCell<ProverEngine>>,
+}
 
 impl LocalProver {
     pub fn from_artifacts(artifacts: &[(PolicyFlags, ProverArtifacts)]) -> Result<Self, Error> {
@@ -27,24 +34,69 @@ impl LocalProver {
                 &bundle.circuit_r1cs,
             )
             .map_err(|e| Error::Other(format!("init prover for {flags:?}: {e:#}")))?;
-            engines.insert(*flags, engine);
+            engines.insert((*flags, GvkMode::Off), engine);
         }
         if engines.is_empty() {
             return Err(Error::Other(
                 "at least one transact circuit is required".into(),
             ));
         }
-        Ok(Self(RefCell::new(engines)))
+        Ok(Self {
+            engines: RefCell::new(engines),
+            note_possession: None,
+        })
+    }
+
+    /// Adds the `policy_tx_gvk_*` circuit for `(policy_flags, gvk_mode)`,
+    /// required to transact on a `pool-gvk` deployment.
+    pub fn with_gvk_artifacts(
+        self,
+        policy_flags: PolicyFlags,
+        gvk_mode: GvkMode,
+        artifacts: &ProverArtifacts,
+    ) -> Result<Self, Error> {
+        if gvk_mode == GvkMode::Off {
+            return Err(Error::Other(
+                "GVK artifacts require a GVK mode; use from_artifacts for plain circuits".into(),
+            ));
+        }
+        let engine = ProverEngine::new(
+            &artifacts.proving_key,
+            &artifacts.circuit_graph,
+            &artifacts.circuit_r1cs,
+        )
+        .map_err(|e| {
+            Error::Other(format!(
+                "init prover for {policy_flags:?} {gvk_mode:?}: {e:#}"
+            ))
+        })?;
+        self.engines
+            .borrow_mut()
+            .insert((policy_flags, gvk_mode), engine);
+        Ok(self)
+    }
+
+    /// Adds the `notePossession` circuit, required to register public keys.
+    pub fn with_note_possession(mut self, artifacts: &ProverArtifacts) -> Result<Self, Error> {
+        let engine = ProverEngine::new(
+            &artifacts.proving_key,
+            &artifacts.circuit_graph,
+            &artifacts.circuit_r1cs,
+        )
+        .map_err(|e| Error::Other(format!("init notePossession prover: {e:#}")))?;
+        self.note_possession = Some(RefCell::new(engine));
+        Ok(self)
     }
 
     pub fn prove(&self, params: TransactParams) -> Result<PreparedProverTx, Error> {
         let flags = params.policy_flags;
-        self.0
+        let gvk_mode = params.gvk_mode;
+        self.engines
             .borrow_mut()
-            .get_mut(&flags)
+            .get_mut(&(flags, gvk_mode))
             .ok_or_else(|| {
                 Error::Other(format!(
-                    "no transact prover configured for policy flags {flags:?}"
+                    "no transact prover configured for policy flags {flags:?} with GVK mode {gvk_mode:?}"
                 ))
             })?
             .prove_transact(params)
@@ -67,6 +119,17 @@ impl Prover for LocalProver {
         ))
     }
 
+    async fn prove_note_possession(&self, params: NotePossessionParams) -> Result<Vec<u8>, Error> {
+        self.note_possession
+            .as_ref()
+            .ok_or_else(|| {
+                Error::Other("notePossession proving is not configured for this prover".into())
+            })?
+            .borrow_mut()
+            .prove_note_possession(params)
+            .map_err(|e| Error::Other(format!("prove note possession: {e:#}")))
+    }
+
     async fn verify_disclosure_proof(
         &self,
         _receipt: &DisclosureReceipt,
@@ -9,7 +9,10 @@ pub use noop::NoopProver;
 use crate::{
     chain::hash_ext_data_offchain,
     zk::{
-        flows::{TransactArtifacts, TransactParams, transact},
+        flows::{
+            NotePossessionParams, TransactArtifacts, TransactParams,
+            gvk_ciphertexts_from_public_inputs, note_possession, transact,
+        },
         prover::Prover as Groth16Prover,
         witness::WitnessCalculator,
     },
@@ -68,13 +71,21 @@ impl ProverEngine {
         self.prove(artifacts)
     }
 
-    fn prove(&mut self, artifacts: TransactArtifacts) -> Result<PreparedProverTx> {
-        let circuit_inputs_json = serde_json::to_string(&artifacts.circuit_inputs)?;
-        let ext_data = artifacts.ext_data.clone();
+    /// Prove a `notePossession` statement; returns the 256-byte uncompressed
+    /// proof expected by the public key registry.
+    pub fn prove_note_possession(&mut self, params: NotePossessionParams) -> Result<Vec<u8>> {
+        let circuit_inputs = note_possession(params)?;
+        let circuit_inputs_json = serde_json::to_string(&circuit_inputs)?;
+        let (proof_uncompressed, _) = self.prove_inputs(&circuit_inputs_json)?;
+        Ok(proof_uncompressed)
+    }
 
+    /// Proves `circuit_inputs_json`; returns the 256-byte uncompressed proof
+    /// and the little-endian public signals it was verified against.
+    fn prove_inputs(&mut self, circuit_inputs_json: &str) -> Result<(Vec<u8>, Vec<u8>)> {
         let witness_bytes = self
             .witness
-            .compute_witness(&circuit_inputs_json)
+            .compute_witness(circuit_inputs_json)
             .context("witness calculation failed")?;
 
         let proof_compressed = self.prover.prove_bytes(&witness_bytes)?;
@@ -90,8 +101,17 @@ impl ProverEngine {
                 proof_uncompressed.len()
             );
         }
+        Ok((proof_uncompressed, public_inputs))
+    }
+
+    fn prove(&mut self, artifacts: TransactArtifacts) -> Result<PreparedProverTx> {
+        let circuit_inputs_json = serde_json::to_string(&artifacts.circuit_inputs)?;
+        let ext_data = artifacts.ext_data.clone();
+        let (proof_uncompressed, public_inputs) = self.prove_inputs(&circuit_inputs_json)?;
 
         let p = artifacts.prepared;
+        let (input_gvk_ciphertexts, output_gvk_ciphertexts) =
+            gvk_ciphertexts_from_public_inputs(&public_inputs, p.gvk_mode)?;
         let prepared = PreparedTxPublic {
             pool_root: p.pool_root,
             input_nullifiers: p.input_nullifiers,
@@ -100,6 +120,8 @@ impl ProverEngine {
             ext_data_hash_be: p.ext_data_hash_be,
             asp_membership_root: p.asp_membership_root,
             asp_non_membership_root: p.asp_non_membership_root,
+            input_gvk_ciphertexts,
+            output_gvk_ciphertexts,
         };
 
         Ok(PreparedProverTx {
@@ -124,6 +146,10 @@ pub trait Prover {
         params: DisclosureProveParams,
     ) -> Result<DisclosureReceipt, Error>;
 
+    /// Proves knowledge of a note private key for public key registration;
+    /// returns the 256-byte uncompressed Groth16 proof.
+    async fn prove_note_possession(&self, params: NotePossessionParams) -> Result<Vec<u8>, Error>;
+
     async fn verify_disclosure_proof(
         &self,
         receipt: &DisclosureReceipt,
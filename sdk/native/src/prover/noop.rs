@@ -5,7 +5,10 @@
 //! cost (deserializing the proving key, compiling the circuit WASM). Any
 //! attempt to actually prove returns an error rather than silently misbehaving.
 
-use crate::{types::DisclosureReceipt, zk::flows::TransactParams};
+use crate::{
+    types::DisclosureReceipt,
+    zk::flows::{NotePossessionParams, TransactParams},
+};
 
 use crate::{disclosure::DisclosureProveParams, error::Error, transact::PreparedProverTx};
 
@@ -29,6 +32,10 @@ impl Prover for NoopProver {
         Err(Error::Other(READ_ONLY.into()))
     }
 
+    async fn prove_note_possession(&self, _params: NotePossessionParams) -> Result<Vec<u8>, Error> {
+        Err(Error::Other(READ_ONLY.into()))
+    }
+
     async fn verify_disclosure_proof(
         &self,
         _receipt: &DisclosureReceipt,
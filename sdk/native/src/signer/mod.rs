@@ -5,8 +5,10 @@ use crate::chain::PreparedSorobanTx;
 use crate::{PreparedTransaction, error::Error, types::SignedTransaction};
 
 mod local;
+mod noop;
 
 pub use local::LocalSigner;
+pub use noop::NoopSigner;
 
 /// Signs a simulated [`PreparedTransaction`] before chain submission.
 #[async_trait::async_trait(?Send)]
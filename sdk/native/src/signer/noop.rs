@@ -0,0 +1,40 @@
+//! A [`Signer`] that signs nothing — for watch-only account sessions.
+//!
+//! A watch-only account holds a viewing key, not the Stellar secret, so any
+//! signing request errors instead of reaching a wallet that does not exist.
+
+use crate::chain::PreparedSorobanTx;
+
+use super::Signer;
+use crate::{PreparedTransaction, error::Error, types::SignedTransaction};
+
+/// A no-op [`Signer`] for `user_address`; every signing method errors with
+/// [`Error::WatchOnly`].
+pub struct NoopSigner {
+    user_address: String,
+}
+
+impl NoopSigner {
+    pub fn new(user_address: impl Into<String>) -> Self {
+        Self {
+            user_address: user_address.into(),
+        }
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl Signer for NoopSigner {
+    async fn sign_transaction(
+        &self,
+        _prepared: &PreparedTransaction,
+    ) -> Result<SignedTransaction, Error> {
+        Err(Error::WatchOnly(self.user_address.clone()))
+    }
+
+    async fn sign_soroban_transaction(
+        &self,
+        _prepared: &PreparedSorobanTx,
+    ) -> Result<SignedTransaction, Error> {
+        Err(Error::WatchOnly(self.user_address.clone()))
+    }
+}
@@ -1,34 +1,33 @@
 use crate::{
     chain::{
         ParsedContractEvent, parse_event_metadata, scval_to_address_string, scval_to_bytes,
-        scval_to_u32, scval_to_u64, scval_to_u256,
+        scval_to_gvk_ciphertext, scval_to_u32, scval_to_u64, scval_to_u256,
     },
     types::{
-        ContractEvent, Field, LeafAddedEvent, LeafDeletedEvent, LeafInsertedEvent,
-        LeafUpdatedEvent, NewCommitmentEvent, NewNullifierEvent, ProcessedEvent, PublicKeyEvent,
+        ContractEvent, Field, GlobalViewKeyCiphertext, LeafAddedEvent, LeafDeletedEvent,
+        LeafInsertedEvent, LeafUpdatedEvent, NewCommitmentEvent, NewNullifierEvent, ProcessedEvent,
+        PublicKeyEvent, PublicKeyRevokedEvent,
     },
 };
 use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use stellar_xdr as xdr;
 
 /// Field name emitted by `contracts/pool-gvk`'s pool events, carrying the
 /// admin-decryptable ciphertext of the note.
 const GVK_CIPHERTEXT_FIELD: &str = "gvk_ciphertext";
 
-/// Warn when a pool event carries GVK data this SDK does not yet decode.
+/// Decode the `gvk_ciphertext` field of a pool event.
 ///
-/// `pool-gvk` reuses `pool`'s event names and adds `gvk_ciphertext`. Event
-/// data is a name-keyed map, so the extra field decodes harmlessly and the
-/// parsers below simply ignore it — but ignoring it silently means a GVK pool
-/// can be indexed with its audit data quietly discarded. This makes that
-/// visible until the field is actually consumed.
-fn warn_unconsumed_gvk_ciphertext(parsed: &ParsedContractEvent) {
-    if parsed.values.contains_key(GVK_CIPHERTEXT_FIELD) {
-        tracing::warn!(
-            "event `{}` id {} from contract {} carries a `{GVK_CIPHERTEXT_FIELD}` field that is not yet parsed; GVK data is being dropped",
-            parsed.name,
-            parsed.id,
-            parsed.contract_id,
-        );
+/// `pool-gvk` reuses `pool`'s event names and adds this field, so plain pools
+/// simply lack it. `pool-gvk`'s nullifier event carries an
+/// `Option<GvkCiphertext>`, which encodes `None` as `ScVal::Void`.
+fn parse_gvk_ciphertext(
+    values: &HashMap<String, xdr::ScVal>,
+) -> Result<Option<GlobalViewKeyCiphertext>> {
+    match values.get(GVK_CIPHERTEXT_FIELD) {
+        None | Some(xdr::ScVal::Void) => Ok(None),
+        Some(val) => Ok(Some(scval_to_gvk_ciphertext(val)?)),
     }
 }
 
@@ -46,13 +45,13 @@ pub fn parse_event(event: ContractEvent) -> Result<ProcessedEvent> {
         "public_key_event" | "PublicKeyEvent" => {
             ProcessedEvent::PublicKey(parse_public_key_event(parsed)?)
         }
+        "public_key_revoked_event" | "PublicKeyRevokedEvent" => {
+            ProcessedEvent::PublicKeyRevoked(parse_public_key_revoked_event(parsed)?)
+        }
         // ASP membership events contracts/asp-membership
         "leaf_added" | "LeafAdded" => ProcessedEvent::LeafAdded(parse_leaf_added(parsed)?),
         // ASP non-membership events contracts/asp-non-membership
-        // for now they're not collected - check also sdk/native/src/chain/indexer.rs
-        // if they should be collected then
-        // sdk/native/src/state/processor.rs should be extended
-        // (to avoid looping over the unprocessed events)
+        // replayed locally to build non-membership proofs (see transact.rs)
         "leaf_inserted" | "LeafInserted" => {
             ProcessedEvent::LeafInserted(parse_leaf_inserted(parsed)?)
         }
@@ -69,17 +68,27 @@ pub fn parse_event(event: ContractEvent) -> Result<ProcessedEvent> {
 //     /// The nullifier that was spent
 //     #[topic]
 //     pub nullifier: U256,
+//     /// `pool-gvk` only, present in TRACEABLE mode
+//     pub gvk_ciphertext: Option<GvkCiphertext>,
 // }
 fn parse_new_nullifier_event(parsed: ParsedContractEvent) -> Result<NewNullifierEvent> {
-    warn_unconsumed_gvk_ciphertext(&parsed);
     let ParsedContractEvent {
-        id, name, topics, ..
+        id,
+        name,
+        topics,
+        values,
+        ..
     } = parsed;
     let nullifier_scval = topics
         .first()
         .ok_or_else(|| anyhow!("event `{name}` id {id} should have a nullifier topic value"))?;
     let nullifier = Field::try_from_u256(scval_to_u256(nullifier_scval)?)?;
-    Ok(NewNullifierEvent { id, nullifier })
+    let gvk_ciphertext = parse_gvk_ciphertext(&values)?;
+    Ok(NewNullifierEvent {
+        id,
+        nullifier,
+        gvk_ciphertext,
+    })
 }
 
 // #[contractevent]
@@ -92,9 +101,10 @@ fn parse_new_nullifier_event(parsed: ParsedContractEvent) -> Result<NewNullifier
 //     pub index: u32,
 //     /// Encrypted output data (decryptable by the recipient)
 //     pub encrypted_output: Bytes,
+//     /// `pool-gvk` only
+//     pub gvk_ciphertext: GvkCiphertext,
 // }
 fn parse_new_commitment_event(parsed: ParsedContractEvent) -> Result<NewCommitmentEvent> {
-    warn_unconsumed_gvk_ciphertext(&parsed);
     let ParsedContractEvent {
         id,
         name,
@@ -114,11 +124,13 @@ fn parse_new_commitment_event(parsed: ParsedContractEvent) -> Result<NewCommitme
         .get("encrypted_output")
         .ok_or_else(|| anyhow!("event `{name}` id {id} should have an encrypted_output value"))?;
     let encrypted_output = scval_to_bytes(encrypted_output_scval)?;
+    let gvk_ciphertext = parse_gvk_ciphertext(&values)?;
     Ok(NewCommitmentEvent {
         id,
         commitment,
         index,
         encrypted_output,
+        gvk_ciphertext,
     })
 }
 
@@ -132,6 +144,8 @@ fn parse_new_commitment_event(parsed: ParsedContractEvent) -> Result<NewCommitme
 //     pub encryption_key: Bytes,
 //     /// BN254 note public key
 //     pub note_key: Bytes,
+//     /// Per-owner registration version, starting at 1
+//     pub version: u32,
 // }
 fn parse_public_key_event(parsed: ParsedContractEvent) -> Result<PublicKeyEvent> {
     let ParsedContractEvent {
@@ -153,14 +167,45 @@ fn parse_public_key_event(parsed: ParsedContractEvent) -> Result<PublicKeyEvent>
         .get("note_key")
         .ok_or_else(|| anyhow!("event `{name}` id {id} should have an note_key value"))?;
     let note_key = scval_to_bytes(note_key_scval)?.try_into()?;
+    // Registrations emitted before versioning carry no `version` field.
+    let version = values.get("version").map(scval_to_u32).transpose()?;
     Ok(PublicKeyEvent {
         id,
         owner,
         encryption_key,
         note_key,
+        version,
     })
 }
 
+// #[contractevent]
+// #[derive(Clone)]
+// pub struct PublicKeyRevokedEvent {
+//     /// Address of the account owner
+//     #[topic]
+//     pub owner: Address,
+//     /// Version of the revoked registration
+//     pub version: u32,
+// }
+fn parse_public_key_revoked_event(parsed: ParsedContractEvent) -> Result<PublicKeyRevokedEvent> {
+    let ParsedContractEvent {
+        id,
+        name,
+        topics,
+        values,
+        ..
+    } = parsed;
+    let owner_scval = topics
+        .first()
+        .ok_or_else(|| anyhow!("event `{name}` id {id} should have a owner topic value"))?;
+    let owner = scval_to_address_string(owner_scval)?;
+    let version_scval = values
+        .get("version")
+        .ok_or_else(|| anyhow!("event `{name}` id {id} should have a version value"))?;
+    let version = scval_to_u32(version_scval)?;
+    Ok(PublicKeyRevokedEvent { id, owner, version })
+}
+
 // Event emitted when a new leaf is added to the Merkle tree
 // #[contractevent(topics = ["LeafAdded"])]
 // struct LeafAddedEvent {
@@ -284,7 +329,7 @@ fn parse_leaf_deleted(parsed: ParsedContractEvent) -> Result<LeafDeletedEvent> {
 }
 
 #[cfg(test)]
-mod gvk_passthrough_tests {
+mod gvk_ciphertext_tests {
     use super::*;
     use stellar_xdr::{self as xdr, WriteXdr};
 
@@ -378,15 +423,16 @@ mod gvk_passthrough_tests {
         }
     }
 
-    fn nullifier_event(with_gvk: bool) -> ContractEvent {
-        let entries = if with_gvk {
-            vec![xdr::ScMapEntry {
+    /// `gvk_ciphertext` is the field's raw value, `None` to omit the field
+    /// as `pool` does.
+    fn nullifier_event(gvk_ciphertext: Option<xdr::ScVal>) -> ContractEvent {
+        let entries: Vec<_> = gvk_ciphertext
+            .into_iter()
+            .map(|val| xdr::ScMapEntry {
                 key: symbol(GVK_CIPHERTEXT_FIELD),
-                val: gvk_ciphertext(),
-            }]
-        } else {
-            vec![]
-        };
+                val,
+            })
+            .collect();
 
         ContractEvent {
             id: "0000000000000000002-0000000000".to_string(),
@@ -399,32 +445,23 @@ mod gvk_passthrough_tests {
         }
     }
 
-    /// Guards the two tests below from passing vacuously: if the fixture did
-    /// not actually carry `gvk_ciphertext`, "parses identically" would be
-    /// trivially true and would prove nothing.
-    #[test]
-    fn gvk_fixtures_really_carry_the_field() {
-        for (event, expected) in [
-            (commitment_event(true), true),
-            (commitment_event(false), false),
-            (nullifier_event(true), true),
-            (nullifier_event(false), false),
-        ] {
-            let parsed = parse_event_metadata(event).expect("parse metadata");
-            assert_eq!(
-                parsed.values.contains_key(GVK_CIPHERTEXT_FIELD),
-                expected,
-                "fixture `{}` gvk presence",
-                parsed.name
-            );
+    fn expected_ciphertext() -> GlobalViewKeyCiphertext {
+        let field = |v: u64| Field(crate::types::U256::from(v));
+        GlobalViewKeyCiphertext {
+            r: crate::types::BabyJubJubPoint {
+                x: field(11),
+                y: field(12),
+            },
+            c1: field(1),
+            c2: field(2),
+            c3: field(3),
         }
     }
 
-    /// The deferral of GVK support rests on this: a `pool-gvk` commitment
-    /// event must decode to exactly what the equivalent `pool` event decodes
-    /// to, with the unknown field ignored rather than breaking the parse.
+    /// A `pool-gvk` commitment event must decode to what the equivalent
+    /// `pool` event decodes to, plus the ciphertext.
     #[test]
-    fn commitment_event_parses_identically_with_and_without_gvk_ciphertext() {
+    fn commitment_event_decodes_gvk_ciphertext() {
         let ProcessedEvent::Commitment(without) =
             parse_event(commitment_event(false)).expect("parse pool event")
         else {
@@ -439,21 +476,41 @@ mod gvk_passthrough_tests {
         assert_eq!(with.commitment, without.commitment);
         assert_eq!(with.index, without.index);
         assert_eq!(with.encrypted_output, without.encrypted_output);
+        assert_eq!(without.gvk_ciphertext, None);
+        assert_eq!(with.gvk_ciphertext, Some(expected_ciphertext()));
     }
 
     #[test]
-    fn nullifier_event_parses_identically_with_and_without_gvk_ciphertext() {
-        let ProcessedEvent::Nullifier(without) =
-            parse_event(nullifier_event(false)).expect("parse pool event")
-        else {
-            panic!("expected a nullifier event");
-        };
-        let ProcessedEvent::Nullifier(with) =
-            parse_event(nullifier_event(true)).expect("parse pool-gvk event")
+    fn nullifier_event_decodes_gvk_ciphertext() {
+        let ProcessedEvent::Nullifier(traceable) =
+            parse_event(nullifier_event(Some(gvk_ciphertext()))).expect("parse traceable event")
         else {
             panic!("expected a nullifier event");
         };
+        assert_eq!(traceable.gvk_ciphertext, Some(expected_ciphertext()));
+
+        for value in [None, Some(xdr::ScVal::Void)] {
+            let ProcessedEvent::Nullifier(parsed) =
+                parse_event(nullifier_event(value)).expect("parse event traceable ciphertext")
+            else {
+                panic!("expected a nullifier event");
+            };
+            assert_eq!(parsed.nullifier, traceable.nullifier);
+            assert_eq!(parsed.gvk_ciphertext, None);
+        }
+    }
+
+    #[test]
+    fn malformed_gvk_ciphertext_is_an_error() {
+        let malformed = xdr::ScVal::Map(Some(xdr::ScMap(
+            vec![xdr::ScMapEntry {
+                key: symbol("c1"),
+                val: u256(1),
+            }]
+            .try_into()
+            .expect("ciphertext map"),
+        )));
 
-        assert_eq!(with.nullifier, without.nullifier);
+        assert!(parse_event(nullifier_event(Some(malformed))).is_err());
     }
 }
@@ -0,0 +1,260 @@
+//! Passphrase sealing of the secret `keypairs` columns.
+//!
+//! The vault key is derived from the user's passphrase with Argon2id and
+//! seals each secret column with XChaCha20-Poly1305. A sealed blob is
+//! `version (1) || nonce (24) || ciphertext || tag (16)`; the associated
+//! data binds it to its column and to the row's note public key, so sealed
+//! values cannot be swapped between columns or accounts.
+
+use crate::types::{Passphrase, Secret};
+use anyhow::{Result, anyhow};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    Key, KeyInit, XChaCha20Poly1305, XNonce,
+    aead::{Aead, Payload},
+};
+use std::sync::{Arc, Mutex};
+use zeroize::Zeroizing;
+
+/// Argon2id memory cost in KiB (64 MiB).
+#[cfg(not(test))]
+const KDF_M_COST_KIB: u32 = 64 * 1024;
+/// Cheap parameters keep unit tests fast; stored parameters are always
+/// read back from the database, so this never affects real wallets.
+#[cfg(test)]
+const KDF_M_COST_KIB: u32 = 64;
+const KDF_T_COST: u32 = 3;
+const KDF_P_COST: u32 = 1;
+
+/// Upper bounds on Argon2id parameters read from a backup file or the
+/// database; anything larger could make a crafted file exhaust memory or
+/// stall the derivation. Memory cost is in KiB (1 GiB).
+const KDF_MAX_M_COST_KIB: u32 = 1024 * 1024;
+const KDF_MAX_T_COST: u32 = 10;
+const KDF_MAX_P_COST: u32 = 4;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const SEALED_VERSION: u8 = 1;
+
+/// Plaintext of `key_vault.check_value`.
+const CHECK_PLAINTEXT: &[u8] = b"stellar-private-payments key vault";
+/// Associated-data prefix of every sealed value.
+const AAD_DOMAIN: &[u8] = b"stellar-private-payments/key-vault/v1/";
+
+/// Argon2id parameters as stored in `key_vault`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct KdfParams {
+    pub salt: [u8; SALT_LEN],
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl KdfParams {
+    /// Current parameters with a fresh random salt.
+    pub fn generate() -> Result<Self> {
+        let mut salt = [0u8; SALT_LEN];
+        getrandom::getrandom(&mut salt).map_err(|e| anyhow!("Random generation failed: {}", e))?;
+        Ok(Self {
+            salt,
+            m_cost: KDF_M_COST_KIB,
+            t_cost: KDF_T_COST,
+            p_cost: KDF_P_COST,
+        })
+    }
+
+    /// Fails when a cost exceeds the fixed bounds.
+    pub fn check_bounds(&self) -> Result<()> {
+        if self.m_cost > KDF_MAX_M_COST_KIB
+            || self.t_cost > KDF_MAX_T_COST
+            || self.p_cost > KDF_MAX_P_COST
+        {
+            return Err(anyhow!(
+                "KDF parameters out of range (m_cost {} KiB, t_cost {}, p_cost {}; at most \
+                 {KDF_MAX_M_COST_KIB} KiB, {KDF_MAX_T_COST}, {KDF_MAX_P_COST})",
+                self.m_cost,
+                self.t_cost,
+                self.p_cost
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Key sealing the secret columns. Zeroized on drop.
+pub(crate) struct VaultKey(Secret<[u8; 32]>);
+
+impl VaultKey {
+    /// Derives the key with Argon2id. Parameters outside
+    /// [`KdfParams::check_bounds`] are rejected before any memory is
+    /// allocated.
+    pub fn derive(passphrase: &Passphrase, params: &KdfParams) -> Result<Self> {
+        params.check_bounds()?;
+        let argon2 = Argon2::new(
+            Algorithm::Argon2id,
+            Version::V0x13,
+            Params::new(params.m_cost, params.t_cost, params.p_cost, Some(32))
+                .map_err(|e| anyhow!("invalid key vault KDF parameters: {e}"))?,
+        );
+        let mut key = [0u8; 32];
+        argon2
+            .hash_password_into(passphrase.0.as_bytes(), &params.salt, &mut key)
+            .map_err(|e| anyhow!("key vault KDF failed: {e}"))?;
+        Ok(Self(Secret::new(key)))
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(Key::from_slice(self.0.expose()))
+    }
+
+    fn aad(column: &str, context: &[u8]) -> Vec<u8> {
+        [AAD_DOMAIN, column.as_bytes(), b"/", context].concat()
+    }
+
+    /// Seals `plaintext` for `column` of the row identified by `context`.
+    pub fn seal(&self, column: &str, context: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::getrandom(&mut nonce).map_err(|e| anyhow!("Random generation failed: {}", e))?;
+        let aad = Self::aad(column, context);
+        let ciphertext = self
+            .cipher()
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| anyhow!("failed to seal {column}"))?;
+        Ok([&[SEALED_VERSION][..], &nonce, &ciphertext].concat())
+    }
+
+    /// Opens a value sealed by [`VaultKey::seal`] with the same `column` and
+    /// `context`.
+    pub fn open(&self, column: &str, context: &[u8], sealed: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        let (version, rest) = sealed
+            .split_first()
+            .ok_or_else(|| anyhow!("sealed {column} is empty"))?;
+        if *version != SEALED_VERSION {
+            return Err(anyhow!("unsupported sealed {column} version {version}"));
+        }
+        if rest.len() < NONCE_LEN {
+            return Err(anyhow!("sealed {column} is truncated"));
+        }
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        let aad = Self::aad(column, context);
+        self.cipher()
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &aad,
+                },
+            )
+            .map(Zeroizing::new)
+            .map_err(|_| anyhow!("failed to open sealed {column}"))
+    }
+
+    /// `key_vault.check_value` for this key.
+    pub fn check_value(&self) -> Result<Vec<u8>> {
+        self.seal("check_value", &[], CHECK_PLAINTEXT)
+    }
+
+    /// Whether `check_value` was produced by this key.
+    pub fn matches(&self, check_value: &[u8]) -> bool {
+        self.open("check_value", &[], check_value)
+            .is_ok_and(|plaintext| plaintext.as_slice() == CHECK_PLAINTEXT)
+    }
+}
+
+/// The unlocked vault key, shared by every connection forked from the same
+/// wallet so that one unlock or lock applies to all of them.
+#[derive(Clone, Default)]
+pub(crate) struct KeyVault(Arc<Mutex<Option<Arc<VaultKey>>>>);
+
+impl KeyVault {
+    /// The vault key, or `None` while locked.
+    pub fn key(&self) -> Option<Arc<VaultKey>> {
+        self.0.lock().ok().and_then(|key| key.clone())
+    }
+
+    pub fn set(&self, key: VaultKey) {
+        if let Ok(mut slot) = self.0.lock() {
+            *slot = Some(Arc::new(key));
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut slot) = self.0.lock() {
+            *slot = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(passphrase: &str, params: &KdfParams) -> Result<VaultKey> {
+        VaultKey::derive(&Passphrase(passphrase.to_string()), params)
+    }
+
+    #[test]
+    fn seal_round_trips_and_binds_column_and_context() -> Result<()> {
+        let params = KdfParams::generate()?;
+        let key = key("correct horse", &params)?;
+
+        let sealed = key.seal("note_private_key", b"pk-1", &[7u8; 32])?;
+        assert_eq!(
+            key.open("note_private_key", b"pk-1", &sealed)?.as_slice(),
+            &[7u8; 32]
+        );
+        assert!(key.open("membership_blinding", b"pk-1", &sealed).is_err());
+        assert!(key.open("note_private_key", b"pk-2", &sealed).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn derive_rejects_out_of_range_parameters() -> Result<()> {
+        let params = KdfParams::generate()?;
+        for oversized in [
+            KdfParams {
+                m_cost: u32::MAX,
+                ..params.clone()
+            },
+            KdfParams {
+                t_cost: KDF_MAX_T_COST.saturating_add(1),
+                ..params.clone()
+            },
+            KdfParams {
+                p_cost: KDF_MAX_P_COST.saturating_add(1),
+                ..params.clone()
+            },
+        ] {
+            assert!(key("correct horse", &oversized).is_err());
+        }
+        assert!(
+            KdfParams {
+                m_cost: KDF_MAX_M_COST_KIB,
+                ..params
+            }
+            .check_bounds()
+            .is_ok()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_value_detects_a_wrong_passphrase() -> Result<()> {
+        let params = KdfParams::generate()?;
+        let check_value = key("correct horse", &params)?.check_value()?;
+
+        assert!(key("correct horse", &params)?.matches(&check_value));
+        assert!(!key("battery staple", &params)?.matches(&check_value));
+
+        Ok(())
+    }
+}
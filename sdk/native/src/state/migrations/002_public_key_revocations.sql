@@ -0,0 +1,20 @@
+-- Public key registry versions and revocations.
+--
+-- `version` is the per-owner registration version carried by `PublicKeyEvent`; it is NULL for
+-- registrations emitted before the registry tracked versions.
+ALTER TABLE public_keys ADD COLUMN version INTEGER;
+
+-- Registrations removed on-chain via `unregister`.
+--
+-- A registration is active only while no revocation for the same owner follows it in event
+-- order, so senders never encrypt to a revoked key.
+CREATE TABLE public_key_revocations (
+    owner TEXT NOT NULL,
+    -- Version of the revoked registration.
+    version INTEGER NOT NULL,
+    -- Foreign key to `raw_contract_events.id` for the event that revoked the keys.
+    event_id TEXT NOT NULL PRIMARY KEY,
+    FOREIGN KEY (event_id) REFERENCES raw_contract_events(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_public_key_revocations_owner ON public_key_revocations (owner);
@@ -0,0 +1,16 @@
+-- Operations on the ASP non-membership sparse Merkle tree observed on-chain.
+--
+-- Replayed in event order to rebuild the tree locally, so blocklist non-membership proofs are
+-- built without asking an RPC node about the note key being spent.
+CREATE TABLE asp_non_membership_leaves (
+    -- 'insert', 'update' or 'delete'.
+    op TEXT NOT NULL CHECK (op IN ('insert', 'update', 'delete')),
+    key BLOB NOT NULL CHECK (length(key) = 32),
+    -- Leaf value after the operation; NULL for deletions.
+    value BLOB CHECK (value IS NULL OR length(value) = 32),
+    -- Tree root after the operation.
+    root BLOB NOT NULL CHECK (length(root) = 32),
+    -- Foreign key to `raw_contract_events.id` for the event that changed the leaf.
+    event_id TEXT NOT NULL PRIMARY KEY,
+    FOREIGN KEY (event_id) REFERENCES raw_contract_events(id) ON DELETE CASCADE
+);
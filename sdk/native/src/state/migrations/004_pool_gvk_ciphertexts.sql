@@ -0,0 +1,19 @@
+-- Global View Key ciphertexts carried by `pool-gvk` pool events.
+--
+-- `NewCommitmentEvent` carries the ciphertext of every output note; `NewNullifierEvent` carries
+-- the ciphertext of the spent note in traceable mode only. The columns are NULL for plain pools
+-- and, on nullifiers, for view-only pools. A row has either all five set or none.
+--
+-- `gvk_r_x`/`gvk_r_y` is the ephemeral public key `R`; `gvk_c1`..`gvk_c3` encrypt the note public
+-- key, amount and blinding. Field elements are 32-byte LE blobs, like the rest of the schema.
+ALTER TABLE pool_commitments ADD COLUMN gvk_r_x BLOB CHECK (gvk_r_x IS NULL OR length(gvk_r_x) = 32);
+ALTER TABLE pool_commitments ADD COLUMN gvk_r_y BLOB CHECK (gvk_r_y IS NULL OR length(gvk_r_y) = 32);
+ALTER TABLE pool_commitments ADD COLUMN gvk_c1 BLOB CHECK (gvk_c1 IS NULL OR length(gvk_c1) = 32);
+ALTER TABLE pool_commitments ADD COLUMN gvk_c2 BLOB CHECK (gvk_c2 IS NULL OR length(gvk_c2) = 32);
+ALTER TABLE pool_commitments ADD COLUMN gvk_c3 BLOB CHECK (gvk_c3 IS NULL OR length(gvk_c3) = 32);
+
+ALTER TABLE pool_nullifiers ADD COLUMN gvk_r_x BLOB CHECK (gvk_r_x IS NULL OR length(gvk_r_x) = 32);
+ALTER TABLE pool_nullifiers ADD COLUMN gvk_r_y BLOB CHECK (gvk_r_y IS NULL OR length(gvk_r_y) = 32);
+ALTER TABLE pool_nullifiers ADD COLUMN gvk_c1 BLOB CHECK (gvk_c1 IS NULL OR length(gvk_c1) = 32);
+ALTER TABLE pool_nullifiers ADD COLUMN gvk_c2 BLOB CHECK (gvk_c2 IS NULL OR length(gvk_c2) = 32);
+ALTER TABLE pool_nullifiers ADD COLUMN gvk_c3 BLOB CHECK (gvk_c3 IS NULL OR length(gvk_c3) = 32);
@@ -0,0 +1,18 @@
+-- Passphrase protection for the secret `keypairs` columns.
+--
+-- `key_vault` holds at most one row: the Argon2id parameters that turn the user's passphrase
+-- into the vault key, and `check_value`, a known plaintext sealed under that key so a wrong
+-- passphrase is detected before anything is decrypted. No row means no passphrase was ever set.
+CREATE TABLE key_vault (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    kdf_salt BLOB NOT NULL CHECK (length(kdf_salt) = 16),
+    kdf_m_cost INTEGER NOT NULL,
+    kdf_t_cost INTEGER NOT NULL,
+    kdf_p_cost INTEGER NOT NULL,
+    check_value BLOB NOT NULL
+);
+
+-- When `sealed` is 1, `encryption_private_key`, `note_private_key` and `membership_blinding`
+-- hold XChaCha20-Poly1305 ciphertexts under the vault key instead of 32-byte plaintexts.
+-- Plaintext rows written before a passphrase was set are sealed on first unlock.
+ALTER TABLE keypairs ADD COLUMN sealed INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,32 @@
+-- Incremental pool commitment tree, so proofs no longer rebuild the tree from every leaf.
+--
+-- `pool_merkle_frontiers` holds one `MerkleFrontier` per pool: the tree depth, the number of
+-- folded `pool_commitments` leaves, and the concatenated 32-byte LE `filled` (`depth` nodes) and
+-- `edge` (`depth + 1` nodes, the last being the root) vectors. A pool gets a row the first time a
+-- proof is built against it; the depth is only known then.
+CREATE TABLE pool_merkle_frontiers (
+    contract_id INTEGER PRIMARY KEY,
+    depth INTEGER NOT NULL CHECK (depth BETWEEN 1 AND 32),
+    leaf_count INTEGER NOT NULL,
+    filled BLOB NOT NULL CHECK (length(filled) = 32 * depth),
+    edge BLOB NOT NULL CHECK (length(edge) = 32 * (depth + 1)),
+    FOREIGN KEY (contract_id) REFERENCES contracts(contract_id) ON DELETE CASCADE
+);
+
+-- Root after each of the latest folded leaves, pruned to the pool contract's `ROOT_HISTORY_SIZE`.
+CREATE TABLE pool_merkle_roots (
+    contract_id INTEGER NOT NULL,
+    leaf_count INTEGER NOT NULL,
+    root BLOB NOT NULL CHECK (length(root) = 32),
+    PRIMARY KEY (contract_id, leaf_count),
+    FOREIGN KEY (contract_id) REFERENCES contracts(contract_id) ON DELETE CASCADE
+);
+
+-- Authentication paths of commitments owned by a local account, advanced together with the
+-- frontier. `leaf_count` is the frontier leaf count the path is valid for.
+CREATE TABLE pool_note_witnesses (
+    commitment_id INTEGER PRIMARY KEY,
+    leaf_count INTEGER NOT NULL,
+    path BLOB NOT NULL,
+    FOREIGN KEY (commitment_id) REFERENCES pool_commitments(id) ON DELETE CASCADE
+);
@@ -0,0 +1,17 @@
+-- Notes a local account sent to someone else, recovered from the sender copy of each encrypted
+-- output with the account's outgoing viewing key. Filled by the same commitment scan as
+-- `user_notes`, so a resync rebuilds sent history from chain events alone. Change outputs back to
+-- the sender are not recorded here; they are already in `user_notes`.
+CREATE TABLE user_sent_notes (
+    -- FK to `pool_commitments.id`; an output has a single sender.
+    commitment_id INTEGER PRIMARY KEY,
+    account_id INTEGER NOT NULL,
+    recipient_note_key BLOB NOT NULL CHECK (length(recipient_note_key) = 32),
+    recipient_encryption_key BLOB NOT NULL CHECK (length(recipient_encryption_key) = 32),
+    amount TEXT NOT NULL,
+
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (commitment_id) REFERENCES pool_commitments(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_user_sent_notes_account ON user_sent_notes(account_id);
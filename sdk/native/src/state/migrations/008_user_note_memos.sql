@@ -0,0 +1,3 @@
+-- Sender memo decrypted from a received note's encrypted output; NULL when the output carried
+-- none. Outputs from before memos never carry one, so existing rows need no backfill.
+ALTER TABLE user_notes ADD COLUMN memo TEXT;
@@ -0,0 +1,46 @@
+-- Watch-only accounts, imported from an exported viewing key.
+--
+-- A viewing key holds no note private key, so the account can see its notes but cannot prove.
+-- One row per account; an account that also has `keypairs` is scanned with those instead. When
+-- `sealed` is 1, `encryption_private_key` and `outgoing_viewing_key` hold ciphertexts under the
+-- vault key, like the secret `keypairs` columns.
+CREATE TABLE viewing_keys (
+    account_id INTEGER PRIMARY KEY,
+    note_public_key BLOB NOT NULL CHECK (length(note_public_key) = 32),
+    encryption_private_key BLOB NOT NULL,
+    outgoing_viewing_key BLOB NOT NULL,
+    sealed INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+-- Watch-only accounts cannot compute nullifiers, so `user_notes.expected_nullifier` becomes
+-- nullable: NULL until a spend record in one of the account's own outputs names the nullifier.
+-- SQLite cannot drop a NOT NULL constraint in place, so the table is rebuilt. No other table
+-- references `user_notes`.
+CREATE TABLE user_notes_new (
+    id BLOB NOT NULL PRIMARY KEY CHECK (length(id) = 32),
+    account_id INTEGER NOT NULL,
+    commitment_id INTEGER NOT NULL UNIQUE,
+    nullifier_id INTEGER UNIQUE,
+    expected_nullifier BLOB CHECK (length(expected_nullifier) = 32),
+    blinding BLOB NOT NULL CHECK (length(blinding) = 32),
+    amount TEXT NOT NULL,
+    memo TEXT,
+
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    FOREIGN KEY (commitment_id) REFERENCES pool_commitments(id) ON DELETE CASCADE,
+    FOREIGN KEY (nullifier_id) REFERENCES pool_nullifiers(id) ON DELETE CASCADE
+);
+
+INSERT INTO user_notes_new (
+    id, account_id, commitment_id, nullifier_id, expected_nullifier, blinding, amount, memo
+)
+SELECT id, account_id, commitment_id, nullifier_id, expected_nullifier, blinding, amount, memo
+FROM user_notes;
+
+DROP TABLE user_notes;
+ALTER TABLE user_notes_new RENAME TO user_notes;
+
+CREATE INDEX idx_user_notes_unspent_expected_nullifier
+    ON user_notes(expected_nullifier)
+    WHERE nullifier_id IS NULL;
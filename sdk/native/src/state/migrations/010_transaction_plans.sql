@@ -0,0 +1,44 @@
+-- Multi-step transaction plans, persisted while they run so `PrivatePool::resume_plans` can finish
+-- or abandon them after a crash or a closed tab. Scoped per (address, pool) like
+-- `app_user_operations`.
+--
+-- `state` is the JSON plan (step list and wallet snapshot) after the last confirmed step. A step
+-- that was submitted but not yet seen confirmed is `in_flight_tx_hash`, with the plan state once
+-- it confirms in `in_flight_state`. Plans are kept once completed or abandoned, as history.
+CREATE TABLE app_transaction_plans (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    address TEXT NOT NULL,
+    pool_contract_id TEXT NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('active', 'completed', 'abandoned')),
+    tx_count INTEGER NOT NULL,
+    state TEXT NOT NULL,
+    in_flight_tx_hash TEXT,
+    in_flight_state TEXT,
+    -- UTC epoch seconds, generated by the DB (not the client clock).
+    created_at INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+    updated_at INTEGER NOT NULL DEFAULT (strftime('%s','now')),
+    CHECK ((in_flight_tx_hash IS NULL) = (in_flight_state IS NULL))
+);
+
+CREATE INDEX idx_transaction_plans_active
+    ON app_transaction_plans(address, pool_contract_id)
+    WHERE status = 'active';
+
+-- Confirmed transaction of each finished step, in step order.
+CREATE TABLE app_transaction_plan_txs (
+    plan_id INTEGER NOT NULL,
+    step_index INTEGER NOT NULL,
+    tx_hash TEXT NOT NULL,
+    PRIMARY KEY (plan_id, step_index),
+    FOREIGN KEY (plan_id) REFERENCES app_transaction_plans(id) ON DELETE CASCADE
+);
+
+-- Notes created by confirmed steps that the next step spends. Not linked to `user_notes`: the
+-- note may not be indexed yet when the step confirms.
+CREATE TABLE app_transaction_plan_pending_notes (
+    plan_id INTEGER NOT NULL,
+    commitment BLOB NOT NULL CHECK (length(commitment) = 32),
+    amount TEXT NOT NULL,
+    PRIMARY KEY (plan_id, commitment),
+    FOREIGN KEY (plan_id) REFERENCES app_transaction_plans(id) ON DELETE CASCADE
+);
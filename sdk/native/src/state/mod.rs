@@ -1,13 +1,15 @@
 mod disclaimer;
 pub mod events_parsers;
+mod key_vault;
 mod processor;
 mod storage;
 pub use disclaimer::{CURRENT_DISCLAIMER_HASH_HEX, CURRENT_DISCLAIMER_TEXT_MD};
 pub use processor::{process_events, process_notes};
 pub use storage::{
-    APP_SETTING_BOOTNODE_CONFIG, APP_SETTING_EXPLORER, AccountKeys, DEFAULT_BOOTNODE_URL,
-    DeriveNoteFn, DerivedUserNoteRow, PoolCommitmentRow, Storage, Storage as SqliteStorage,
-    StoredUserKeys,
+    APP_SETTING_BOOTNODE_CONFIG, APP_SETTING_EXPLORER, AccountKeys, AspNonMembershipLeafOp,
+    DEFAULT_BOOTNODE_URL, DeriveNoteFn, DeriveSentNoteFn, DerivedSenderCopyRow, DerivedSentNoteRow,
+    DerivedUserNoteRow, GvkNoteRef, PoolCommitmentRow, PoolGvkCiphertextRow, Storage,
+    Storage as SqliteStorage, StoredUserKeys,
 };
 
 mod process_local;
@@ -1,8 +1,11 @@
-use crate::{error::Error, zk::notes::try_decrypt_and_derive_user_note};
+use crate::{
+    error::Error,
+    zk::notes::{try_decrypt_and_derive_user_note, try_open_sender_copy},
+};
 
 use super::{
-    AccountKeys, DerivedUserNoteRow, PoolCommitmentRow, SqliteStorage, process_events,
-    process_notes,
+    AccountKeys, DerivedSenderCopyRow, DerivedSentNoteRow, DerivedUserNoteRow, PoolCommitmentRow,
+    SqliteStorage, process_events, process_notes,
 };
 
 const PROCESS_FETCH_LIMIT: u32 = 50;
@@ -17,7 +20,8 @@ pub(crate) fn process_local_state(storage: &mut SqliteStorage) -> Result<(), Err
 pub fn process_local_state_batch(storage: &mut SqliteStorage) -> anyhow::Result<bool> {
     let did_raw = process_events(storage, PROCESS_FETCH_LIMIT)?;
     let mut derive = derive_user_note;
-    let did_notes = process_notes(storage, PROCESS_FETCH_LIMIT, &mut derive)?;
+    let mut derive_sent = derive_sent_note;
+    let did_notes = process_notes(storage, PROCESS_FETCH_LIMIT, &mut derive, &mut derive_sent)?;
     Ok(did_raw || did_notes)
 }
 
@@ -26,8 +30,9 @@ fn derive_user_note(
     row: &PoolCommitmentRow,
 ) -> anyhow::Result<Option<DerivedUserNoteRow>> {
     let opt = try_decrypt_and_derive_user_note(
-        &account.note_keypair,
-        &account.encryption_keypair.private,
+        &account.note_public_key,
+        account.note_private_key.as_ref(),
+        &account.encryption_private_key,
         &row.commitment,
         row.leaf_index,
         &row.encrypted_output,
@@ -36,5 +41,26 @@ fn derive_user_note(
         amount: d.amount,
         blinding: d.blinding,
         expected_nullifier: d.expected_nullifier,
+        memo: d.memo,
+    }))
+}
+
+fn derive_sent_note(
+    account: &AccountKeys,
+    row: &PoolCommitmentRow,
+) -> anyhow::Result<Option<DerivedSenderCopyRow>> {
+    let opt = try_open_sender_copy(
+        &account.note_public_key,
+        &account.outgoing_viewing_key,
+        &row.commitment,
+        &row.encrypted_output,
+    )?;
+    Ok(opt.map(|copy| DerivedSenderCopyRow {
+        sent: copy.sent.map(|s| DerivedSentNoteRow {
+            recipient_note_key: s.recipient_note_public_key,
+            recipient_encryption_key: s.recipient_encryption_public_key,
+            amount: s.amount,
+        }),
+        spent_inputs: copy.spent_inputs,
     }))
 }
@@ -10,7 +10,11 @@ pub fn process_events(storage: &mut SqliteStorage, limit: u32) -> Result<bool> {
     let mut nullifiers = vec![];
     let mut commitments = vec![];
     let mut pubkeys = vec![];
+    let mut revocations = vec![];
     let mut leaves = vec![];
+    let mut smt_inserted = vec![];
+    let mut smt_updated = vec![];
+    let mut smt_deleted = vec![];
     while let Some(event) = unprocessed.pop() {
         let parsed = match parse_event(event) {
             Ok(parsed) => parsed,
@@ -26,29 +30,40 @@ pub fn process_events(storage: &mut SqliteStorage, limit: u32) -> Result<bool> {
             ProcessedEvent::Nullifier(ev) => nullifiers.push(ev),
             ProcessedEvent::Commitment(ev) => commitments.push(ev),
             ProcessedEvent::PublicKey(ev) => pubkeys.push(ev),
+            ProcessedEvent::PublicKeyRevoked(ev) => revocations.push(ev),
             ProcessedEvent::LeafAdded(ev) => leaves.push(ev),
-            _ => tracing::warn!("event won't be saved to the storage: {parsed:?}"),
+            ProcessedEvent::LeafInserted(ev) => smt_inserted.push(ev),
+            ProcessedEvent::LeafUpdated(ev) => smt_updated.push(ev),
+            ProcessedEvent::LeafDeleted(ev) => smt_deleted.push(ev),
         }
     }
     storage.save_nullifier_events_batch(&nullifiers)?;
     storage.save_commitment_events_batch(&commitments)?;
     storage.save_public_key_events_batch(&pubkeys)?;
+    storage.save_public_key_revocations_batch(&revocations)?;
     storage.save_leaf_added_events_batch(&leaves)?;
+    storage.save_leaf_inserted_events_batch(&smt_inserted)?;
+    storage.save_leaf_updated_events_batch(&smt_updated)?;
+    storage.save_leaf_deleted_events_batch(&smt_deleted)?;
     Ok(true)
 }
 
 /// Process already-parsed events (commitments/nullifiers) into local user
 /// state.
 ///
-/// This scans pool commitments for decryptable outputs (per account) and
-/// reconciles pool nullifiers against locally-computed expected nullifiers.
+/// This scans pool commitments for decryptable outputs and for outputs the
+/// account sent (per account), folds new commitments into the pool Merkle
+/// frontiers and the witnesses of owned notes, and reconciles pool nullifiers
+/// against locally-computed expected nullifiers.
 pub fn process_notes(
     storage: &mut SqliteStorage,
     limit: u32,
     derive: &mut super::storage::DeriveNoteFn<'_>,
+    derive_sent: &mut super::storage::DeriveSentNoteFn<'_>,
 ) -> Result<bool> {
     let mut did_work = false;
-    did_work |= storage.scan_commitments_for_user_notes(limit, derive)?;
+    did_work |= storage.scan_commitments_for_user_notes(limit, derive, derive_sent)?;
+    did_work |= storage.advance_pool_frontiers()?;
     did_work |= storage.reconcile_nullifiers(limit)?;
     Ok(did_work)
 }
@@ -1,15 +1,28 @@
-use super::disclaimer::{CURRENT_DISCLAIMER_HASH_HEX, CURRENT_DISCLAIMER_TEXT_MD};
+use super::{
+    disclaimer::{CURRENT_DISCLAIMER_HASH_HEX, CURRENT_DISCLAIMER_TEXT_MD},
+    key_vault::{KdfParams, KeyVault, VaultKey},
+};
+use crate::planner::SpendableNote;
 use crate::types::{
-    AspMembershipSync, BootnodeSetting, ContractConfig, ContractEvent, EncryptionKeyPair,
-    EncryptionPrivateKey, EncryptionPublicKey, Field, LeafAddedEvent, NewCommitmentEvent,
-    NewNullifierEvent, NoteAmount, NoteKeyPair, NotePrivateKey, NotePublicKey, OperationalFeedItem,
-    PortfolioBalance, PublicKeyEvent, RecipientLookup, UserNoteSummary, UserOperation,
+    AspMembershipSync, BabyJubJubPoint, BootnodeSetting, ContractConfig, ContractEvent,
+    EncryptionKeyPair, EncryptionPrivateKey, EncryptionPublicKey, Field, GlobalViewKeyCiphertext,
+    InFlightStep, LeafAddedEvent, LeafDeletedEvent, LeafInsertedEvent, LeafUpdatedEvent,
+    NewCommitmentEvent, NewNullifierEvent, NoteAmount, NoteKeyPair, NotePrivateKey, NotePublicKey,
+    OperationalFeedItem, OutgoingViewingKey, Passphrase, PlanStatus, PortfolioBalance,
+    PublicKeyEvent, PublicKeyRevokedEvent, RecipientLookup, SentNoteSummary, StoredPlan,
+    UserNoteSummary, UserOperation, ViewingKey, WALLET_BACKUP_FORMAT, WALLET_BACKUP_KDF_ARGON2ID,
+    WALLET_BACKUP_VERSION, WalletBackup, WalletBackupFile, WalletBackupKdf, WalletBackupKeys,
+    WalletBackupNote, WalletBackupOperation, WalletBackupSubAccount, sub_account_address,
 };
+use crate::zk::encryption::{SpentInput, derive_outgoing_viewing_key, derive_sub_account_keys};
+use crate::zk::merkle::{MerkleFrontier, MerklePrefixTree, MerkleProof, MerkleWitness};
 use anyhow::{Context, Result, anyhow};
-use rusqlite::{Connection, Error as SqlError, OptionalExtension, params};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use rusqlite::{Connection, Error as SqlError, OptionalExtension, Transaction, params};
 use rusqlite_migration::{M, Migrations};
 use serde::{Serialize, de::DeserializeOwned};
-use std::path::Path;
+use std::{collections::BTreeMap, path::Path};
+use zeroize::Zeroizing;
 
 // shouldn't be changed for WASM OPFS otherwise the db will be lost
 const DB_NAME: &str = "spp.db";
@@ -17,11 +30,91 @@ pub const APP_SETTING_BOOTNODE_CONFIG: &str = "bootnode_config";
 pub const APP_SETTING_EXPLORER: &str = "explorer";
 pub const DEFAULT_BOOTNODE_URL: &str = "https://bootnode.dev-nethermind.xyz";
 
-const MIGRATION_ARRAY: &[M] = &[M::up(include_str!("schema.sql"))];
+const MIGRATION_ARRAY: &[M] = &[
+    M::up(include_str!("schema.sql")),
+    M::up(include_str!("migrations/002_public_key_revocations.sql")),
+    M::up(include_str!("migrations/003_asp_non_membership_leaves.sql")),
+    M::up(include_str!("migrations/004_pool_gvk_ciphertexts.sql")),
+    M::up(include_str!("migrations/005_key_vault.sql")),
+    M::up(include_str!("migrations/006_pool_merkle_frontier.sql")),
+    M::up(include_str!("migrations/007_user_sent_notes.sql")),
+    M::up(include_str!("migrations/008_user_note_memos.sql")),
+    M::up(include_str!("migrations/009_watch_only_accounts.sql")),
+    M::up(include_str!("migrations/010_transaction_plans.sql")),
+];
 const MIGRATIONS: Migrations = Migrations::from_slice(MIGRATION_ARRAY);
 
 pub struct Storage {
     conn: Connection,
+    key_vault: KeyVault,
+}
+
+/// Secret `keypairs` columns, sealed under the vault key once a passphrase is
+/// set.
+const SEALED_KEY_COLUMNS: [&str; 3] = [
+    "encryption_private_key",
+    "note_private_key",
+    "membership_blinding",
+];
+
+/// Secret `viewing_keys` columns, sealed like [`SEALED_KEY_COLUMNS`].
+const SEALED_VIEWING_KEY_COLUMNS: [&str; 2] = ["encryption_private_key", "outgoing_viewing_key"];
+
+/// A `viewing_keys` row as stored; see [`StoredKeypairRow`].
+struct StoredViewingKeyRow {
+    account_id: i64,
+    note_public_key: NotePublicKey,
+    /// In [`SEALED_VIEWING_KEY_COLUMNS`] order.
+    secrets: [Zeroizing<Vec<u8>>; 2],
+    sealed: bool,
+}
+
+impl StoredViewingKeyRow {
+    /// Reads `account_id, note_public_key, encryption_private_key,
+    /// outgoing_viewing_key, sealed`.
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            account_id: row.get(0)?,
+            note_public_key: row.get(1)?,
+            secrets: [Zeroizing::new(row.get(2)?), Zeroizing::new(row.get(3)?)],
+            sealed: row.get(4)?,
+        })
+    }
+}
+
+/// A `keypairs` row as stored: the secret columns hold either 32-byte
+/// plaintexts or sealed blobs, depending on `sealed`.
+struct StoredKeypairRow {
+    account_id: i64,
+    /// In [`SEALED_KEY_COLUMNS`] order.
+    secrets: [Zeroizing<Vec<u8>>; 3],
+    encryption_public_key: EncryptionPublicKey,
+    note_public_key: NotePublicKey,
+    sealed: bool,
+}
+
+impl StoredKeypairRow {
+    /// Reads `account_id, encryption_private_key, encryption_public_key,
+    /// note_private_key, note_public_key, membership_blinding, sealed`.
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            account_id: row.get(0)?,
+            secrets: [
+                Zeroizing::new(row.get(1)?),
+                Zeroizing::new(row.get(3)?),
+                Zeroizing::new(row.get(5)?),
+            ],
+            encryption_public_key: row.get(2)?,
+            note_public_key: row.get(4)?,
+            sealed: row.get(6)?,
+        })
+    }
+}
+
+fn secret_key_bytes(column: &str, bytes: &[u8]) -> Result<[u8; 32]> {
+    bytes
+        .try_into()
+        .map_err(|_| anyhow!("{column} must be 32 bytes, got {}", bytes.len()))
 }
 
 #[derive(Debug, Clone)]
@@ -31,12 +124,27 @@ pub struct DisclaimerState {
     pub accepted: bool,
 }
 
+/// Keys the commitment scan opens an account's outputs with.
 #[derive(Debug, Clone)]
 pub struct AccountKeys {
     pub account_id: i64,
-    pub note_keypair: NoteKeyPair,
-    pub encryption_keypair: EncryptionKeyPair,
-    pub membership_blinding: Field,
+    pub note_public_key: NotePublicKey,
+    /// `None` for a watch-only account, which cannot derive nullifiers.
+    pub note_private_key: Option<NotePrivateKey>,
+    pub encryption_private_key: EncryptionPrivateKey,
+    pub outgoing_viewing_key: OutgoingViewingKey,
+}
+
+impl AccountKeys {
+    fn from_user_keys(account_id: i64, keys: StoredUserKeys) -> Self {
+        Self {
+            account_id,
+            outgoing_viewing_key: derive_outgoing_viewing_key(&keys.note_keypair.private),
+            note_public_key: keys.note_keypair.public,
+            note_private_key: Some(keys.note_keypair.private),
+            encryption_private_key: keys.encryption_keypair.private,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -54,16 +162,79 @@ pub struct PoolCommitmentRow {
     pub encrypted_output: Vec<u8>,
 }
 
+/// One stored change to the ASP non-membership tree, with the root it
+/// produced on-chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AspNonMembershipLeafOp {
+    Insert {
+        key: Field,
+        value: Field,
+        root: Field,
+    },
+    Update {
+        key: Field,
+        value: Field,
+        root: Field,
+    },
+    Delete {
+        key: Field,
+        root: Field,
+    },
+}
+
+/// Which pool note a stored GVK ciphertext encrypts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GvkNoteRef {
+    /// An output note, from `NewCommitmentEvent`.
+    Output { commitment: Field, leaf_index: u32 },
+    /// A spent input note, from `NewNullifierEvent` (traceable pools only).
+    Input { nullifier: Field },
+}
+
+/// A GVK ciphertext indexed from a `pool-gvk` event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolGvkCiphertextRow {
+    /// `raw_contract_events.id` of the event. Events of one transaction share
+    /// the TOID prefix before the hyphen.
+    pub event_id: String,
+    pub ledger: u32,
+    pub note: GvkNoteRef,
+    pub ciphertext: GlobalViewKeyCiphertext,
+}
+
 #[derive(Debug, Clone)]
 pub struct DerivedUserNoteRow {
     pub amount: NoteAmount,
     pub blinding: Field,
-    pub expected_nullifier: Field,
+    /// `None` for a watch-only account until a spend record names it.
+    pub expected_nullifier: Option<Field>,
+    pub memo: Option<String>,
 }
 
 pub type DeriveNoteFn<'a> =
     dyn FnMut(&AccountKeys, &PoolCommitmentRow) -> Result<Option<DerivedUserNoteRow>> + 'a;
 
+/// An output the account sent to someone else, opened with its outgoing
+/// viewing key.
+#[derive(Debug, Clone)]
+pub struct DerivedSentNoteRow {
+    pub recipient_note_key: NotePublicKey,
+    pub recipient_encryption_key: EncryptionPublicKey,
+    pub amount: NoteAmount,
+}
+
+/// What an account's outgoing viewing key opens in one output.
+#[derive(Debug, Clone, Default)]
+pub struct DerivedSenderCopyRow {
+    /// The output, when the account sent it to someone else.
+    pub sent: Option<DerivedSentNoteRow>,
+    /// Inputs of the transaction that created the output.
+    pub spent_inputs: Vec<SpentInput>,
+}
+
+pub type DeriveSentNoteFn<'a> =
+    dyn FnMut(&AccountKeys, &PoolCommitmentRow) -> Result<Option<DerivedSenderCopyRow>> + 'a;
+
 impl Storage {
     pub fn connect() -> Result<Self> {
         Self::connect_file(DB_NAME)
@@ -80,29 +251,375 @@ impl Storage {
     fn connect_with_connection(mut conn: Connection) -> Result<Self> {
         MIGRATIONS.to_latest(&mut conn)?;
         conn.pragma_update(None, "foreign_keys", "ON")?;
-        Ok(Self { conn })
+        // Overwrite deleted content so replaced secrets (e.g. keys sealed by
+        // `unlock`) do not linger in free pages or the journal.
+        conn.pragma_update(None, "secure_delete", "ON")?;
+        Ok(Self {
+            conn,
+            key_vault: KeyVault::default(),
+        })
     }
 
-    pub fn save_events_batch(&mut self, data: &crate::types::ContractsEventData) -> Result<()> {
+    /// The in-memory vault key, to share with another connection to the same
+    /// database via [`Storage::share_key_vault`].
+    pub(crate) fn key_vault(&self) -> KeyVault {
+        self.key_vault.clone()
+    }
+
+    /// Makes this connection unlock and lock together with the one
+    /// `key_vault` came from.
+    pub(crate) fn share_key_vault(&mut self, key_vault: KeyVault) {
+        self.key_vault = key_vault;
+    }
+
+    /// Whether a wallet passphrase has been set.
+    pub fn has_passphrase(&self) -> Result<bool> {
+        Ok(self
+            .conn
+            .query_row("SELECT EXISTS(SELECT 1 FROM key_vault)", [], |row| {
+                row.get(0)
+            })?)
+    }
+
+    /// Whether secret keys are sealed and the vault key is not in memory.
+    pub fn is_locked(&self) -> Result<bool> {
+        Ok(self.has_passphrase()? && self.key_vault.key().is_none())
+    }
+
+    /// Unlocks the secret keys with `passphrase`.
+    ///
+    /// The first unlock of a wallet sets `passphrase`. Every unlock also
+    /// seals keypairs still stored in plaintext, which migrates databases
+    /// created before passphrases existed.
+    pub fn unlock(&mut self, passphrase: &Passphrase) -> Result<()> {
         let tx = self.conn.transaction()?;
-        {
-            let mut stmt = tx.prepare(
-                "INSERT INTO raw_contract_events (id, ledger, contract_id, topics, value)
-                 VALUES (?1, ?2, ?3, ?4, ?5)
-                 ON CONFLICT(id) DO NOTHING",
-            )?;
+        let key = match Self::stored_vault(&tx)? {
+            Some((params, check_value)) => {
+                let key = VaultKey::derive(passphrase, &params)?;
+                if !key.matches(&check_value) {
+                    return Err(anyhow!("incorrect wallet passphrase"));
+                }
+                key
+            }
+            None => {
+                if passphrase.0.is_empty() {
+                    return Err(anyhow!("wallet passphrase must not be empty"));
+                }
+                Self::create_vault(&tx, passphrase)?
+            }
+        };
+        let migrated = Self::seal_plaintext_keypairs(&tx, &key)?;
+        Self::seal_plaintext_viewing_keys(&tx, &key)?;
+        tx.commit()?;
+        if migrated > 0 {
+            // Rebuild the file so no page written before `secure_delete` was
+            // on still holds the plaintext keys.
+            self.conn.execute_batch("VACUUM")?;
+            self.conn
+                .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+            tracing::info!(
+                "[STORAGE] sealed {migrated} plaintext keypairs with the wallet passphrase"
+            );
+        }
+        self.key_vault.set(key);
+        Ok(())
+    }
 
-            for event in &data.events {
-                let event_contract_id = Self::get_or_create_contract_id(&tx, &event.contract_id)?;
-                stmt.execute(params![
-                    event.id,
-                    event.ledger,
-                    event_contract_id,
-                    event.topics.join(","),
-                    event.value
-                ])?;
+    /// Drops the vault key from memory. Secret keys are unreadable until the
+    /// next [`Storage::unlock`].
+    pub fn lock(&self) {
+        self.key_vault.clear();
+    }
+
+    /// Re-seals every keypair under `new`. Fails unless `current` is the
+    /// wallet passphrase.
+    pub fn change_passphrase(&mut self, current: &Passphrase, new: &Passphrase) -> Result<()> {
+        if new.0.is_empty() {
+            return Err(anyhow!("wallet passphrase must not be empty"));
+        }
+        let tx = self.conn.transaction()?;
+        let (params, check_value) =
+            Self::stored_vault(&tx)?.ok_or_else(|| anyhow!("no wallet passphrase is set"))?;
+        let old_key = VaultKey::derive(current, &params)?;
+        if !old_key.matches(&check_value) {
+            return Err(anyhow!("incorrect wallet passphrase"));
+        }
+        Self::seal_plaintext_keypairs(&tx, &old_key)?;
+        Self::seal_plaintext_viewing_keys(&tx, &old_key)?;
+
+        tx.execute("DELETE FROM key_vault", [])?;
+        let new_key = Self::create_vault(&tx, new)?;
+        let rows: Vec<(i64, [Vec<u8>; 3], NotePublicKey)> = tx
+            .prepare(
+                "SELECT id, encryption_private_key, note_private_key, membership_blinding, note_public_key
+                 FROM keypairs",
+            )?
+            .query_map([], |row| {
+                Ok((row.get(0)?, [row.get(1)?, row.get(2)?, row.get(3)?], row.get(4)?))
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        for (id, secrets, note_public_key) in rows {
+            let [
+                encryption_private_key,
+                note_private_key,
+                membership_blinding,
+            ] = SEALED_KEY_COLUMNS
+                .iter()
+                .zip(&secrets)
+                .map(|(column, sealed)| old_key.open(column, &note_public_key.0, sealed))
+                .collect::<Result<Vec<_>>>()?
+                .try_into()
+                .map_err(|_| anyhow!("expected {} sealed columns", SEALED_KEY_COLUMNS.len()))?;
+            let resealed = Self::seal_secrets(
+                &new_key,
+                &note_public_key,
+                [
+                    encryption_private_key.as_slice(),
+                    note_private_key.as_slice(),
+                    membership_blinding.as_slice(),
+                ],
+            )?;
+            tx.execute(
+                "UPDATE keypairs
+                 SET encryption_private_key = ?1, note_private_key = ?2, membership_blinding = ?3
+                 WHERE id = ?4",
+                params![resealed[0], resealed[1], resealed[2], id],
+            )?;
+        }
+        let rows: Vec<StoredViewingKeyRow> = tx
+            .prepare(
+                "SELECT account_id, note_public_key, encryption_private_key, outgoing_viewing_key,
+                        sealed
+                 FROM viewing_keys",
+            )?
+            .query_map([], StoredViewingKeyRow::from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        for row in rows {
+            let mut opened = Vec::with_capacity(SEALED_VIEWING_KEY_COLUMNS.len());
+            for (column, sealed) in SEALED_VIEWING_KEY_COLUMNS.iter().zip(&row.secrets) {
+                opened.push(old_key.open(column, &row.note_public_key.0, sealed)?);
             }
+            let resealed = Self::seal_viewing_key_secrets(
+                &new_key,
+                &row.note_public_key,
+                [opened[0].as_slice(), opened[1].as_slice()],
+            )?;
+            tx.execute(
+                "UPDATE viewing_keys
+                 SET encryption_private_key = ?1, outgoing_viewing_key = ?2
+                 WHERE account_id = ?3",
+                params![resealed[0], resealed[1], row.account_id],
+            )?;
+        }
+        tx.commit()?;
+        self.key_vault.set(new_key);
+        Ok(())
+    }
+
+    fn stored_vault(tx: &Transaction<'_>) -> Result<Option<(KdfParams, Vec<u8>)>> {
+        tx.query_row(
+            "SELECT kdf_salt, kdf_m_cost, kdf_t_cost, kdf_p_cost, check_value
+             FROM key_vault WHERE id = 1",
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, Vec<u8>>(0)?,
+                    row.get::<_, u32>(1)?,
+                    row.get::<_, u32>(2)?,
+                    row.get::<_, u32>(3)?,
+                    row.get::<_, Vec<u8>>(4)?,
+                ))
+            },
+        )
+        .optional()?
+        .map(|(salt, m_cost, t_cost, p_cost, check_value)| {
+            let salt = salt
+                .try_into()
+                .map_err(|_| anyhow!("key vault salt has an invalid length"))?;
+            let params = KdfParams {
+                salt,
+                m_cost,
+                t_cost,
+                p_cost,
+            };
+            params
+                .check_bounds()
+                .context("invalid key vault KDF parameters")?;
+            Ok((params, check_value))
+        })
+        .transpose()
+    }
+
+    /// Stores a fresh vault for `passphrase` and returns its key.
+    fn create_vault(tx: &Transaction<'_>, passphrase: &Passphrase) -> Result<VaultKey> {
+        let params = KdfParams::generate()?;
+        let key = VaultKey::derive(passphrase, &params)?;
+        tx.execute(
+            "INSERT INTO key_vault (id, kdf_salt, kdf_m_cost, kdf_t_cost, kdf_p_cost, check_value)
+             VALUES (1, ?1, ?2, ?3, ?4, ?5)",
+            params![
+                params.salt.as_slice(),
+                params.m_cost,
+                params.t_cost,
+                params.p_cost,
+                key.check_value()?,
+            ],
+        )?;
+        Ok(key)
+    }
+
+    /// Seals every `sealed = 0` keypair under `key`. Returns how many rows
+    /// were sealed.
+    fn seal_plaintext_keypairs(tx: &Transaction<'_>, key: &VaultKey) -> Result<usize> {
+        let rows: Vec<(i64, [Zeroizing<Vec<u8>>; 3], NotePublicKey)> = tx
+            .prepare(
+                "SELECT id, encryption_private_key, note_private_key, membership_blinding, note_public_key
+                 FROM keypairs
+                 WHERE sealed = 0",
+            )?
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    [
+                        Zeroizing::new(row.get(1)?),
+                        Zeroizing::new(row.get(2)?),
+                        Zeroizing::new(row.get(3)?),
+                    ],
+                    row.get(4)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        for (id, secrets, note_public_key) in &rows {
+            let sealed = Self::seal_secrets(
+                key,
+                note_public_key,
+                secrets.each_ref().map(|secret| secret.as_slice()),
+            )?;
+            tx.execute(
+                "UPDATE keypairs
+                 SET encryption_private_key = ?1, note_private_key = ?2, membership_blinding = ?3,
+                     sealed = 1
+                 WHERE id = ?4",
+                params![sealed[0], sealed[1], sealed[2], id],
+            )?;
+        }
+        Ok(rows.len())
+    }
+
+    /// Seals every `sealed = 0` viewing key under `key`.
+    fn seal_plaintext_viewing_keys(tx: &Transaction<'_>, key: &VaultKey) -> Result<()> {
+        let rows: Vec<StoredViewingKeyRow> = tx
+            .prepare(
+                "SELECT account_id, note_public_key, encryption_private_key, outgoing_viewing_key,
+                        sealed
+                 FROM viewing_keys
+                 WHERE sealed = 0",
+            )?
+            .query_map([], StoredViewingKeyRow::from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        for row in &rows {
+            let sealed = Self::seal_viewing_key_secrets(
+                key,
+                &row.note_public_key,
+                row.secrets.each_ref().map(|secret| secret.as_slice()),
+            )?;
+            tx.execute(
+                "UPDATE viewing_keys
+                 SET encryption_private_key = ?1, outgoing_viewing_key = ?2, sealed = 1
+                 WHERE account_id = ?3",
+                params![sealed[0], sealed[1], row.account_id],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Seals `secrets`, in [`SEALED_VIEWING_KEY_COLUMNS`] order, for the
+    /// viewing key of `note_public_key`.
+    fn seal_viewing_key_secrets(
+        key: &VaultKey,
+        note_public_key: &NotePublicKey,
+        secrets: [&[u8]; 2],
+    ) -> Result<[Vec<u8>; 2]> {
+        let [encryption_private_key, outgoing_viewing_key] = secrets;
+        Ok([
+            key.seal(
+                SEALED_VIEWING_KEY_COLUMNS[0],
+                &note_public_key.0,
+                encryption_private_key,
+            )?,
+            key.seal(
+                SEALED_VIEWING_KEY_COLUMNS[1],
+                &note_public_key.0,
+                outgoing_viewing_key,
+            )?,
+        ])
+    }
+
+    /// Seals `secrets`, in [`SEALED_KEY_COLUMNS`] order, for the keypair of
+    /// `note_public_key`.
+    fn seal_secrets(
+        key: &VaultKey,
+        note_public_key: &NotePublicKey,
+        secrets: [&[u8]; 3],
+    ) -> Result<[Vec<u8>; 3]> {
+        let [
+            encryption_private_key,
+            note_private_key,
+            membership_blinding,
+        ] = secrets;
+        Ok([
+            key.seal(
+                SEALED_KEY_COLUMNS[0],
+                &note_public_key.0,
+                encryption_private_key,
+            )?,
+            key.seal(SEALED_KEY_COLUMNS[1], &note_public_key.0, note_private_key)?,
+            key.seal(
+                SEALED_KEY_COLUMNS[2],
+                &note_public_key.0,
+                membership_blinding,
+            )?,
+        ])
+    }
+
+    /// Decodes a stored keypair row, opening sealed columns with the vault
+    /// key. Fails if the row is sealed and the wallet is locked.
+    fn open_keypair_row(&self, row: StoredKeypairRow) -> Result<StoredUserKeys> {
+        let key = if row.sealed {
+            Some(
+                self.key_vault
+                    .key()
+                    .ok_or_else(|| anyhow!("wallet is locked: unlock it with the passphrase"))?,
+            )
+        } else {
+            None
+        };
+        let mut plaintexts = Vec::with_capacity(SEALED_KEY_COLUMNS.len());
+        for (column, stored) in SEALED_KEY_COLUMNS.iter().zip(&row.secrets) {
+            let plaintext = match &key {
+                Some(key) => key.open(column, &row.note_public_key.0, stored)?,
+                None => stored.clone(),
+            };
+            plaintexts.push(Zeroizing::new(secret_key_bytes(column, &plaintext)?));
         }
+
+        Ok(StoredUserKeys {
+            note_keypair: NoteKeyPair {
+                private: NotePrivateKey(*plaintexts[1]),
+                public: row.note_public_key,
+            },
+            encryption_keypair: EncryptionKeyPair {
+                private: EncryptionPrivateKey(*plaintexts[0]),
+                public: row.encryption_public_key,
+            },
+            membership_blinding: Field::try_from_le_bytes(*plaintexts[2])
+                .context("stored membership blinding is not a field element")?,
+        })
+    }
+
+    pub fn save_events_batch(&mut self, data: &crate::types::ContractsEventData) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        Self::insert_raw_events(&tx, &data.events)?;
         tx.commit()?;
         tracing::debug!(
             "[STORAGE] saved {} events and cursor {} (latest_ledger={})",
@@ -149,6 +666,27 @@ impl Storage {
         Ok(())
     }
 
+    /// Inserts raw events, ignoring ones already stored.
+    fn insert_raw_events(tx: &Transaction, events: &[ContractEvent]) -> Result<()> {
+        let mut stmt = tx.prepare(
+            "INSERT INTO raw_contract_events (id, ledger, contract_id, topics, value)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(id) DO NOTHING",
+        )?;
+
+        for event in events {
+            let event_contract_id = Self::get_or_create_contract_id(tx, &event.contract_id)?;
+            stmt.execute(params![
+                event.id,
+                event.ledger,
+                event_contract_id,
+                event.topics.join(","),
+                event.value
+            ])?;
+        }
+        Ok(())
+    }
+
     /// Clears stored RPC cursors so the indexer restarts pagination by ledger.
     pub fn clear_indexing_cursors(&mut self) -> Result<()> {
         self.conn
@@ -214,45 +752,48 @@ impl Storage {
             .unwrap_or_default())
     }
 
+    /// Whether `address` has keys stored. Unlike [`Storage::get_user_keys`],
+    /// works while the wallet is locked.
+    pub fn has_user_keys(&self, address: &str) -> Result<bool> {
+        Ok(self.conn.query_row(
+            "SELECT EXISTS(
+                SELECT 1 FROM keypairs
+                JOIN accounts ON keypairs.account_id = accounts.id
+                WHERE accounts.address = ?1
+            )",
+            params![address],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// The latest keys of `address`. Fails while the wallet is locked.
     pub fn get_user_keys(&self, address: &str) -> Result<Option<StoredUserKeys>> {
-        self.conn
+        let row = self
+            .conn
             .query_row(
                 "SELECT
+                keypairs.account_id,
                 encryption_private_key,
                 encryption_public_key,
                 note_private_key,
                 note_public_key,
-                membership_blinding
+                membership_blinding,
+                sealed
                 FROM keypairs
                 JOIN accounts ON keypairs.account_id = accounts.id
                 WHERE accounts.address = ?1
                 ORDER BY keypairs.id DESC
                 LIMIT 1",
                 params![address],
-                |row| {
-                    let enc_priv: EncryptionPrivateKey = row.get(0)?;
-                    let enc_pub: EncryptionPublicKey = row.get(1)?;
-                    let note_priv: NotePrivateKey = row.get(2)?;
-                    let note_pub: NotePublicKey = row.get(3)?;
-                    let membership_blinding: Field = row.get(4)?;
-
-                    Ok(StoredUserKeys {
-                        note_keypair: NoteKeyPair {
-                            private: note_priv,
-                            public: note_pub,
-                        },
-                        encryption_keypair: EncryptionKeyPair {
-                            private: enc_priv,
-                            public: enc_pub,
-                        },
-                        membership_blinding,
-                    })
-                },
+                StoredKeypairRow::from_row,
             )
             .optional()
-            .context(format!("Failed to fetch keys for account: {}", address))
+            .context(format!("Failed to fetch keys for account: {}", address))?;
+        row.map(|row| self.open_keypair_row(row)).transpose()
     }
 
+    /// Stores new keys for `account_address`, sealed if a passphrase is set.
+    /// Fails while the wallet is locked.
     pub fn save_encryption_and_note_keypairs(
         &mut self,
         account_address: &str,
@@ -260,6 +801,28 @@ impl Storage {
         encryption_keypair: &EncryptionKeyPair,
         membership_blinding: &Field,
     ) -> Result<()> {
+        let membership_blinding = Zeroizing::new(membership_blinding.to_le_bytes());
+        let plaintexts = [
+            encryption_keypair.private.0.as_slice(),
+            note_keypair.private.0.as_slice(),
+            membership_blinding.as_slice(),
+        ];
+        let (secrets, sealed) = if self.has_passphrase()? {
+            let key = self
+                .key_vault
+                .key()
+                .ok_or_else(|| anyhow!("wallet is locked: unlock it with the passphrase"))?;
+            (
+                Self::seal_secrets(&key, &note_keypair.public, plaintexts)?.map(Zeroizing::new),
+                true,
+            )
+        } else {
+            (
+                plaintexts.map(|secret| Zeroizing::new(secret.to_vec())),
+                false,
+            )
+        };
+
         let tx = self
             .conn
             .transaction()
@@ -267,6 +830,11 @@ impl Storage {
 
         let account_id = Self::get_or_create_account(&tx, account_address)?;
 
+        let [
+            encryption_private_key,
+            note_private_key,
+            membership_blinding,
+        ] = &secrets;
         tx.execute(
             "INSERT INTO keypairs (
                 encryption_private_key,
@@ -274,15 +842,17 @@ impl Storage {
                 note_private_key,
                 note_public_key,
                 membership_blinding,
-                account_id
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                account_id,
+                sealed
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![
-                &encryption_keypair.private,
+                encryption_private_key.as_slice(),
                 &encryption_keypair.public,
-                &note_keypair.private,
+                note_private_key.as_slice(),
                 &note_keypair.public,
-                membership_blinding,
+                membership_blinding.as_slice(),
                 account_id,
+                sealed,
             ],
         )
         .context("failed to insert keypairs")?;
@@ -294,6 +864,151 @@ impl Storage {
         Ok(())
     }
 
+    /// Derives and stores the keys of sub-account `index` of `address` unless
+    /// they are already stored, and returns its storage key (see
+    /// [`crate::types::sub_account_address`]). Fails while the wallet is
+    /// locked or when `address` has no keys.
+    pub fn ensure_sub_account_keys(&mut self, address: &str, index: u32) -> Result<String> {
+        let sub_address = sub_account_address(address, index);
+        if self.get_user_keys(&sub_address)?.is_some() {
+            return Ok(sub_address);
+        }
+        let keys = self
+            .get_user_keys(address)?
+            .ok_or_else(|| anyhow!("no keys stored for account {address}"))?;
+        let (note_keypair, encryption_keypair, membership_blinding) = derive_sub_account_keys(
+            &keys.note_keypair.private,
+            &keys.encryption_keypair.private,
+            &keys.membership_blinding,
+            index,
+        )?;
+        self.save_encryption_and_note_keypairs(
+            &sub_address,
+            &note_keypair,
+            &encryption_keypair,
+            &membership_blinding,
+        )?;
+        Ok(sub_address)
+    }
+
+    /// Stores `viewing_key` as a watch-only account, sealed if a passphrase
+    /// is set. Replaces an earlier viewing key of the same address. Fails
+    /// while the wallet is locked.
+    pub fn save_viewing_key(&mut self, viewing_key: &ViewingKey) -> Result<()> {
+        let plaintexts = [
+            viewing_key.encryption_private_key.0.as_slice(),
+            viewing_key.outgoing_viewing_key.0.as_slice(),
+        ];
+        let (secrets, sealed) = if self.has_passphrase()? {
+            let key = self
+                .key_vault
+                .key()
+                .ok_or_else(|| anyhow!("wallet is locked: unlock it with the passphrase"))?;
+            (
+                Self::seal_viewing_key_secrets(&key, &viewing_key.note_public_key, plaintexts)?
+                    .map(Zeroizing::new),
+                true,
+            )
+        } else {
+            (
+                plaintexts.map(|secret| Zeroizing::new(secret.to_vec())),
+                false,
+            )
+        };
+
+        let tx = self
+            .conn
+            .transaction()
+            .context("failed to start transaction")?;
+        let account_id = Self::get_or_create_account(&tx, &viewing_key.address)?;
+        let [encryption_private_key, outgoing_viewing_key] = &secrets;
+        tx.execute(
+            "INSERT OR REPLACE INTO viewing_keys (
+                account_id,
+                note_public_key,
+                encryption_private_key,
+                outgoing_viewing_key,
+                sealed
+            ) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                account_id,
+                &viewing_key.note_public_key,
+                encryption_private_key.as_slice(),
+                outgoing_viewing_key.as_slice(),
+                sealed,
+            ],
+        )
+        .context("failed to insert viewing key")?;
+        tx.commit().context("failed to commit transaction")?;
+        tracing::debug!(
+            "[STORAGE] saved a viewing key for the account {}",
+            crate::types::Sensitive(&viewing_key.address)
+        );
+        Ok(())
+    }
+
+    /// Whether `address` was imported from a viewing key and has no keypairs.
+    pub fn is_watch_only(&self, address: &str) -> Result<bool> {
+        Ok(self.conn.query_row(
+            "SELECT EXISTS(
+                SELECT 1
+                FROM viewing_keys v
+                JOIN accounts a ON a.id = v.account_id
+                WHERE a.address = ?1
+                  AND NOT EXISTS (SELECT 1 FROM keypairs k WHERE k.account_id = a.id)
+             )",
+            params![address],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// The viewing key of `address`: derived from its keypairs, or the one it
+    /// was imported from if it is watch-only. Fails while the wallet is
+    /// locked.
+    pub fn export_viewing_key(&self, address: &str) -> Result<ViewingKey> {
+        if let Some(keys) = self.get_user_keys(address)? {
+            return Ok(ViewingKey::new(
+                address,
+                keys.note_keypair.public,
+                keys.encryption_keypair.private,
+                derive_outgoing_viewing_key(&keys.note_keypair.private),
+            ));
+        }
+        self.get_viewing_key(address)?
+            .ok_or_else(|| anyhow!("no keys stored for account {address}"))
+    }
+
+    /// The imported viewing key of `address`, if it is a watch-only account.
+    /// Fails while the wallet is locked.
+    pub fn get_viewing_key(&self, address: &str) -> Result<Option<ViewingKey>> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT v.account_id, v.note_public_key, v.encryption_private_key,
+                        v.outgoing_viewing_key, v.sealed
+                 FROM viewing_keys v
+                 JOIN accounts a ON a.id = v.account_id
+                 WHERE a.address = ?1",
+                params![address],
+                StoredViewingKeyRow::from_row,
+            )
+            .optional()
+            .context(format!(
+                "Failed to fetch viewing key for account: {}",
+                address
+            ))?;
+        row.map(|row| {
+            let keys = self.open_viewing_key_row(row)?;
+            Ok(ViewingKey::new(
+                address,
+                keys.note_public_key,
+                keys.encryption_private_key,
+                keys.outgoing_viewing_key,
+            ))
+        })
+        .transpose()
+    }
+
     pub fn get_disclaimer_state(&mut self, address: &str) -> Result<DisclaimerState> {
         let tx = self
             .conn
@@ -429,20 +1144,29 @@ impl Storage {
         Ok(id)
     }
 
+    /// Latest active registration for `address`.
+    ///
+    /// Returns `None` if the owner never registered or if their latest
+    /// registration was followed by a revocation.
     pub fn lookup_public_key_by_address(
         &self,
         address: &str,
     ) -> Result<Option<crate::types::PublicKeyEntry>> {
         self.conn
             .query_row(
-                "SELECT owner, encryption_key, note_key, ledger
-                 FROM (
-                    SELECT p.owner, p.encryption_key, p.note_key, MAX(r.ledger) AS ledger
-                    FROM public_keys p
-                    JOIN raw_contract_events r ON r.id = p.event_id
-                    WHERE p.owner = ?1
-                    GROUP BY p.owner
-                 )",
+                "SELECT p.owner, p.encryption_key, p.note_key, r.ledger, p.version
+                 FROM public_keys p
+                 JOIN raw_contract_events r ON r.id = p.event_id
+                 WHERE p.owner = ?1
+                 AND NOT EXISTS (
+                    SELECT 1
+                    FROM public_key_revocations k
+                    JOIN raw_contract_events kr ON kr.id = k.event_id
+                    WHERE k.owner = p.owner
+                    AND (kr.ledger > r.ledger OR (kr.ledger = r.ledger AND kr.id > r.id))
+                 )
+                 ORDER BY r.ledger DESC, r.id DESC
+                 LIMIT 1",
                 params![address],
                 map_public_key_entry,
             )
@@ -459,7 +1183,8 @@ impl Storage {
                 n.amount,
                 c.leaf_index,
                 r.ledger,
-                CASE WHEN n.nullifier_id IS NULL THEN 0 ELSE 1 END AS spent
+                CASE WHEN n.nullifier_id IS NULL THEN 0 ELSE 1 END AS spent,
+                n.memo
              FROM user_notes n
              JOIN accounts a ON a.id = n.account_id
              JOIN pool_commitments c ON c.id = n.commitment_id
@@ -487,6 +1212,58 @@ impl Storage {
                 leaf_index,
                 created_at_ledger,
                 spent: spent_i64 != 0,
+                memo: row.get(6)?,
+            })
+        })?;
+
+        let mut out = Vec::new();
+        for r in rows {
+            out.push(r?);
+        }
+        Ok(out)
+    }
+
+    /// List notes `address` sent to others (newest first).
+    ///
+    /// The recipient address is resolved from the public key registry when
+    /// the recipient keys were ever announced there.
+    pub fn list_sent_notes(&self, address: &str, limit: u32) -> Result<Vec<SentNoteSummary>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT
+                c.commitment,
+                pool.address,
+                s.amount,
+                c.leaf_index,
+                r.ledger,
+                s.recipient_note_key,
+                s.recipient_encryption_key,
+                (SELECT k.owner
+                 FROM public_keys k
+                 WHERE k.note_key = s.recipient_note_key
+                 AND k.encryption_key = s.recipient_encryption_key
+                 LIMIT 1)
+             FROM user_sent_notes s
+             JOIN accounts a ON a.id = s.account_id
+             JOIN pool_commitments c ON c.id = s.commitment_id
+             JOIN raw_contract_events r ON r.id = c.event_id
+             JOIN contracts pool ON pool.contract_id = r.contract_id
+             WHERE a.address = ?1
+             ORDER BY r.ledger DESC
+             LIMIT ?2",
+        )?;
+
+        let rows = stmt.query_map(params![address, limit], |row| {
+            let leaf_index_i64: i64 = row.get(3)?;
+            let created_at_ledger_i64: i64 = row.get(4)?;
+            Ok(SentNoteSummary {
+                id: row.get(0)?,
+                pool_contract_id: row.get(1)?,
+                amount: row.get(2)?,
+                leaf_index: col_u32(leaf_index_i64, 3)?,
+                created_at_ledger: col_u32(created_at_ledger_i64, 4)?,
+                recipient_note_public_key: row.get(5)?,
+                recipient_encryption_public_key: row.get(6)?,
+                recipient_address: row.get(7)?,
             })
         })?;
 
@@ -510,7 +1287,8 @@ impl Storage {
                 n.amount,
                 c.leaf_index,
                 r.ledger,
-                CASE WHEN n.nullifier_id IS NULL THEN 0 ELSE 1 END AS spent
+                CASE WHEN n.nullifier_id IS NULL THEN 0 ELSE 1 END AS spent,
+                n.memo
              FROM user_notes n
              JOIN accounts a ON a.id = n.account_id
              JOIN pool_commitments c ON c.id = n.commitment_id
@@ -536,6 +1314,7 @@ impl Storage {
                 leaf_index,
                 created_at_ledger,
                 spent: spent_i64 != 0,
+                memo: row.get(5)?,
             })
         })?;
 
@@ -558,7 +1337,8 @@ impl Storage {
                 pool.address,
                 n.amount,
                 c.leaf_index,
-                r.ledger
+                r.ledger,
+                n.memo
              FROM user_notes n
              JOIN accounts a ON a.id = n.account_id
              JOIN pool_commitments c ON c.id = n.commitment_id
@@ -584,6 +1364,7 @@ impl Storage {
                 leaf_index,
                 created_at_ledger,
                 spent: false,
+                memo: row.get(5)?,
             })
         })?;
 
@@ -903,13 +1684,15 @@ impl Storage {
         let tx = self.conn.transaction()?;
         {
             let mut stmt = tx.prepare(
-                "INSERT INTO pool_nullifiers (nullifier, event_id)
-                    VALUES (?1, ?2)
+                "INSERT INTO pool_nullifiers
+                    (nullifier, event_id, gvk_r_x, gvk_r_y, gvk_c1, gvk_c2, gvk_c3)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
                     ON CONFLICT(nullifier) DO NOTHING",
             )?;
 
             for event in events {
-                stmt.execute(params![event.nullifier, event.id])?;
+                let [r_x, r_y, c1, c2, c3] = gvk_ciphertext_columns(event.gvk_ciphertext.as_ref());
+                stmt.execute(params![event.nullifier, event.id, r_x, r_y, c1, c2, c3])?;
             }
         }
         tx.commit()?;
@@ -921,17 +1704,25 @@ impl Storage {
         let tx = self.conn.transaction()?;
         {
             let mut stmt = tx.prepare(
-                "INSERT INTO pool_commitments (commitment, leaf_index, encrypted_output, event_id)
-                    VALUES (?1, ?2, ?3, ?4)
+                "INSERT INTO pool_commitments
+                    (commitment, leaf_index, encrypted_output, event_id,
+                     gvk_r_x, gvk_r_y, gvk_c1, gvk_c2, gvk_c3)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
                     ON CONFLICT(commitment) DO NOTHING",
             )?;
 
             for event in events {
+                let [r_x, r_y, c1, c2, c3] = gvk_ciphertext_columns(event.gvk_ciphertext.as_ref());
                 stmt.execute(params![
                     event.commitment,
                     event.index,
                     event.encrypted_output,
-                    event.id
+                    event.id,
+                    r_x,
+                    r_y,
+                    c1,
+                    c2,
+                    c3
                 ])?;
             }
         }
@@ -939,21 +1730,84 @@ impl Storage {
         Ok(())
     }
 
-    /// Batch upsert for Public Keys (Address owner and BLOB keys)
-    pub fn save_public_key_events_batch(&mut self, events: &Vec<PublicKeyEvent>) -> Result<()> {
-        let tx = self.conn.transaction()?;
-        {
-            let mut stmt = tx.prepare(
-                "INSERT INTO public_keys (owner, encryption_key, note_key, event_id)
-                    VALUES (?1, ?2, ?3, ?4)
-                    ON CONFLICT(event_id) DO NOTHING",
-            )?;
-
-            for event in events {
+    /// Fetch every GVK ciphertext indexed for a `pool-gvk` pool, input and
+    /// output notes alike, in event order.
+    ///
+    /// Plain pools, and nullifiers of view-only pools, carry no ciphertext
+    /// and contribute no rows.
+    pub fn get_pool_gvk_ciphertexts(
+        &self,
+        pool_contract_id: &str,
+    ) -> Result<Vec<PoolGvkCiphertextRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT r.id AS event_id, r.ledger AS ledger,
+                    pc.commitment AS note, pc.leaf_index AS leaf_index,
+                    pc.gvk_r_x, pc.gvk_r_y, pc.gvk_c1, pc.gvk_c2, pc.gvk_c3
+             FROM pool_commitments pc
+             JOIN raw_contract_events r ON r.id = pc.event_id
+             JOIN contracts c ON c.contract_id = r.contract_id
+             WHERE c.address = ?1 AND pc.gvk_r_x IS NOT NULL
+             UNION ALL
+             SELECT r.id, r.ledger,
+                    pn.nullifier, NULL,
+                    pn.gvk_r_x, pn.gvk_r_y, pn.gvk_c1, pn.gvk_c2, pn.gvk_c3
+             FROM pool_nullifiers pn
+             JOIN raw_contract_events r ON r.id = pn.event_id
+             JOIN contracts c ON c.contract_id = r.contract_id
+             WHERE c.address = ?1 AND pn.gvk_r_x IS NOT NULL
+             ORDER BY ledger ASC, event_id ASC",
+        )?;
+
+        let rows = stmt.query_map(params![pool_contract_id], |row| {
+            let event_id: String = row.get(0)?;
+            let ledger_i64: i64 = row.get(1)?;
+            let ledger = col_u32(ledger_i64, 1)?;
+            let note: Field = row.get(2)?;
+            let leaf_index: Option<i64> = row.get(3)?;
+            let note = match leaf_index {
+                Some(leaf_index) => GvkNoteRef::Output {
+                    commitment: note,
+                    leaf_index: col_u32(leaf_index, 3)?,
+                },
+                None => GvkNoteRef::Input { nullifier: note },
+            };
+            let ciphertext = GlobalViewKeyCiphertext {
+                r: BabyJubJubPoint {
+                    x: row.get(4)?,
+                    y: row.get(5)?,
+                },
+                c1: row.get(6)?,
+                c2: row.get(7)?,
+                c3: row.get(8)?,
+            };
+            Ok(PoolGvkCiphertextRow {
+                event_id,
+                ledger,
+                note,
+                ciphertext,
+            })
+        })?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .context("Failed to query pool GVK ciphertexts")
+    }
+
+    /// Batch upsert for Public Keys (Address owner and BLOB keys)
+    pub fn save_public_key_events_batch(&mut self, events: &Vec<PublicKeyEvent>) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO public_keys (owner, encryption_key, note_key, version, event_id)
+                    VALUES (?1, ?2, ?3, ?4, ?5)
+                    ON CONFLICT(event_id) DO NOTHING",
+            )?;
+
+            for event in events {
                 stmt.execute(params![
                     event.owner,
                     event.encryption_key,
                     event.note_key,
+                    event.version,
                     event.id
                 ])?;
             }
@@ -962,6 +1816,27 @@ impl Storage {
         Ok(())
     }
 
+    /// Batch upsert for Public Key revocations
+    pub fn save_public_key_revocations_batch(
+        &mut self,
+        events: &Vec<PublicKeyRevokedEvent>,
+    ) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO public_key_revocations (owner, version, event_id)
+                    VALUES (?1, ?2, ?3)
+                    ON CONFLICT(event_id) DO NOTHING",
+            )?;
+
+            for event in events {
+                stmt.execute(params![event.owner, event.version, event.id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     /// Batch upsert for ASP Membership Leaves
     pub fn save_leaf_added_events_batch(&mut self, events: &Vec<LeafAddedEvent>) -> Result<()> {
         let tx = self.conn.transaction()?;
@@ -1145,6 +2020,171 @@ impl Storage {
         Ok(leaves)
     }
 
+    /// Batch upsert for ASP non-membership leaf insertions
+    pub fn save_leaf_inserted_events_batch(
+        &mut self,
+        events: &Vec<LeafInsertedEvent>,
+    ) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO asp_non_membership_leaves (op, key, value, root, event_id)
+                    VALUES ('insert', ?1, ?2, ?3, ?4)
+                    ON CONFLICT(event_id) DO NOTHING",
+            )?;
+
+            for event in events {
+                stmt.execute(params![event.key, event.value, event.root, event.id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Batch upsert for ASP non-membership leaf updates
+    pub fn save_leaf_updated_events_batch(&mut self, events: &Vec<LeafUpdatedEvent>) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO asp_non_membership_leaves (op, key, value, root, event_id)
+                    VALUES ('update', ?1, ?2, ?3, ?4)
+                    ON CONFLICT(event_id) DO NOTHING",
+            )?;
+
+            for event in events {
+                stmt.execute(params![event.key, event.new_value, event.root, event.id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Batch upsert for ASP non-membership leaf deletions
+    pub fn save_leaf_deleted_events_batch(&mut self, events: &Vec<LeafDeletedEvent>) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO asp_non_membership_leaves (op, key, value, root, event_id)
+                    VALUES ('delete', ?1, NULL, ?2, ?3)
+                    ON CONFLICT(event_id) DO NOTHING",
+            )?;
+
+            for event in events {
+                stmt.execute(params![event.key, event.root, event.id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Checks whether the local ASP non-membership tree can be used to prove
+    /// against `current_root` read at `current_ledger`.
+    ///
+    /// Returns:
+    /// - `None` if raw event ingestion is caught up to `current_ledger` and the
+    ///   root after the last stored operation equals `current_root` (an empty
+    ///   table counts as the empty tree, root zero).
+    /// - `Some(AspMembershipSync::SyncRequired(Some(gap)))` if the indexer is
+    ///   behind the chain tip, or the raw events are ingested but not yet
+    ///   processed into `asp_non_membership_leaves`.
+    /// - `Err(_)` if the roots disagree at the same ledger.
+    pub fn check_asp_non_membership_precondition(
+        &self,
+        asp_non_membership_contract_id: &str,
+        current_root: &Field,
+        current_ledger: u32,
+    ) -> Result<Option<AspMembershipSync>> {
+        let sync_meta = self
+            .get_sync_metadata()?
+            .into_iter()
+            .find(|meta| meta.contract_id == asp_non_membership_contract_id);
+        let Some(sync_meta) = sync_meta else {
+            return Ok(Some(AspMembershipSync::SyncRequired(Some(current_ledger))));
+        };
+
+        if current_ledger > sync_meta.last_fully_indexed_ledger {
+            let gap = current_ledger.saturating_sub(sync_meta.last_fully_indexed_ledger);
+            return Ok(Some(AspMembershipSync::SyncRequired(Some(gap))));
+        }
+
+        // Same reasoning as `check_asp_membership_precondition`: the indexer
+        // may sit a few ledgers ahead of the state read; the root comparison
+        // is what detects real divergence.
+        let mut stmt = self.conn.prepare(
+            "SELECT l.root, r.ledger
+             FROM asp_non_membership_leaves l
+             JOIN raw_contract_events r ON r.id = l.event_id
+             JOIN contracts c ON c.contract_id = r.contract_id
+             WHERE c.address = ?1
+             ORDER BY r.ledger DESC, r.id DESC
+             LIMIT 1",
+        )?;
+
+        let (last_root, last_ledger) = stmt
+            .query_row(params![asp_non_membership_contract_id], |row| {
+                let root: Field = row.get(0)?;
+                let ledger_i64: i64 = row.get(1)?;
+                let ledger = col_u32(ledger_i64, 1)?;
+                Ok((root, ledger))
+            })
+            .optional()
+            .context("Failed to query asp_non_membership_leaves last root/ledger")?
+            .unwrap_or((Field::ZERO, 0));
+
+        if *current_root != last_root {
+            if last_ledger < current_ledger {
+                let gap = current_ledger.saturating_sub(last_ledger);
+                return Ok(Some(AspMembershipSync::SyncRequired(Some(gap))));
+            }
+            anyhow::bail!(
+                "asp non-membership root mismatch at ledger {}",
+                current_ledger
+            );
+        }
+
+        Ok(None)
+    }
+
+    /// Fetch all ASP non-membership tree operations in event order, for
+    /// replaying into a local sparse Merkle tree.
+    pub fn get_asp_non_membership_leaf_ops_ordered(
+        &self,
+        asp_non_membership_contract_id: &str,
+    ) -> Result<Vec<AspNonMembershipLeafOp>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT l.op, l.key, l.value, l.root
+             FROM asp_non_membership_leaves l
+             JOIN raw_contract_events r ON r.id = l.event_id
+             JOIN contracts c ON c.contract_id = r.contract_id
+             WHERE c.address = ?1
+             ORDER BY r.ledger ASC, r.id ASC",
+        )?;
+
+        let rows = stmt.query_map(params![asp_non_membership_contract_id], |row| {
+            let op: String = row.get(0)?;
+            let key: Field = row.get(1)?;
+            let value: Option<Field> = row.get(2)?;
+            let root: Field = row.get(3)?;
+            Ok((op, key, value, root))
+        })?;
+
+        let mut ops = Vec::new();
+        for row in rows {
+            let (op, key, value, root) = row?;
+            let op = match (op.as_str(), value) {
+                ("insert", Some(value)) => AspNonMembershipLeafOp::Insert { key, value, root },
+                ("update", Some(value)) => AspNonMembershipLeafOp::Update { key, value, root },
+                ("delete", None) => AspNonMembershipLeafOp::Delete { key, root },
+                (op, value) => {
+                    anyhow::bail!("asp_non_membership_leaves: invalid row op={op} value={value:?}")
+                }
+            };
+            ops.push(op);
+        }
+
+        Ok(ops)
+    }
+
     /// Unprocessed raw events fetch
     pub fn get_unprocessed_events(&self, limit: u32) -> Result<Vec<ContractEvent>> {
         let mut stmt = self.conn.prepare(
@@ -1153,12 +2193,16 @@ impl Storage {
                 JOIN contracts c ON c.contract_id = r.contract_id
                 LEFT JOIN pool_commitments pc ON r.id = pc.event_id
                 LEFT JOIN public_keys p ON r.id = p.event_id
+                LEFT JOIN public_key_revocations pr ON r.id = pr.event_id
                 LEFT JOIN asp_membership_leaves l ON r.id = l.event_id
+                LEFT JOIN asp_non_membership_leaves nl ON r.id = nl.event_id
                 LEFT JOIN pool_nullifiers n ON r.id = n.event_id
                 WHERE pc.event_id IS NULL
                 AND p.event_id IS NULL
+                AND pr.event_id IS NULL
                 AND n.event_id IS NULL
                 AND l.event_id IS NULL
+                AND nl.event_id IS NULL
                 ORDER BY r.ledger ASC, r.id ASC
                 LIMIT ?1",
         )?;
@@ -1183,6 +2227,8 @@ impl Storage {
         Ok(events)
     }
 
+    /// Latest keys of every account. Accounts whose keys are sealed are
+    /// skipped while the wallet is locked, so their scans resume on unlock.
     fn get_accounts_with_latest_keypairs(&self) -> Result<Vec<AccountKeys>> {
         let mut stmt = self.conn.prepare(
             "SELECT
@@ -1191,7 +2237,8 @@ impl Storage {
                 k.encryption_public_key,
                 k.note_private_key,
                 k.note_public_key,
-                k.membership_blinding
+                k.membership_blinding,
+                k.sealed
              FROM accounts a
              JOIN (
                 SELECT account_id, MAX(id) AS max_id
@@ -1203,42 +2250,85 @@ impl Storage {
              ORDER BY a.id ASC",
         )?;
 
-        let rows = stmt.query_map([], |row| {
-            let account_id: i64 = row.get(0)?;
-            let enc_priv: EncryptionPrivateKey = row.get(1)?;
-            let enc_pub: EncryptionPublicKey = row.get(2)?;
-            let note_priv: NotePrivateKey = row.get(3)?;
-            let note_pub: NotePublicKey = row.get(4)?;
-            let membership_blinding: Field = row.get(5)?;
-
-            Ok(AccountKeys {
-                account_id,
-                note_keypair: NoteKeyPair {
-                    private: note_priv,
-                    public: note_pub,
-                },
-                encryption_keypair: EncryptionKeyPair {
-                    private: enc_priv,
-                    public: enc_pub,
-                },
-                membership_blinding,
-            })
-        })?;
+        let rows = stmt.query_map([], StoredKeypairRow::from_row)?;
 
+        let locked = self.key_vault.key().is_none();
         let mut out = Vec::new();
         for r in rows {
-            out.push(r?);
+            let row = r?;
+            if row.sealed && locked {
+                tracing::debug!(
+                    "[STORAGE] wallet is locked, skipping account {}",
+                    row.account_id
+                );
+                continue;
+            }
+            let account_id = row.account_id;
+            out.push(AccountKeys::from_user_keys(
+                account_id,
+                self.open_keypair_row(row)?,
+            ));
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT v.account_id, v.note_public_key, v.encryption_private_key,
+                    v.outgoing_viewing_key, v.sealed
+             FROM viewing_keys v
+             WHERE NOT EXISTS (SELECT 1 FROM keypairs k WHERE k.account_id = v.account_id)",
+        )?;
+        let rows = stmt.query_map([], StoredViewingKeyRow::from_row)?;
+        for r in rows {
+            let row = r?;
+            if row.sealed && locked {
+                tracing::debug!(
+                    "[STORAGE] wallet is locked, skipping watch-only account {}",
+                    row.account_id
+                );
+                continue;
+            }
+            out.push(self.open_viewing_key_row(row)?);
         }
+        out.sort_by_key(|keys| keys.account_id);
         Ok(out)
     }
 
-    /// Scan pool commitments and insert decryptable notes into `user_notes`.
+    fn open_viewing_key_row(&self, row: StoredViewingKeyRow) -> Result<AccountKeys> {
+        let key = if row.sealed {
+            Some(
+                self.key_vault
+                    .key()
+                    .ok_or_else(|| anyhow!("wallet is locked: unlock it with the passphrase"))?,
+            )
+        } else {
+            None
+        };
+        let mut plaintexts = Vec::with_capacity(SEALED_VIEWING_KEY_COLUMNS.len());
+        for (column, stored) in SEALED_VIEWING_KEY_COLUMNS.iter().zip(&row.secrets) {
+            let plaintext = match &key {
+                Some(key) => key.open(column, &row.note_public_key.0, stored)?,
+                None => stored.clone(),
+            };
+            plaintexts.push(Zeroizing::new(secret_key_bytes(column, &plaintext)?));
+        }
+
+        Ok(AccountKeys {
+            account_id: row.account_id,
+            note_public_key: row.note_public_key,
+            note_private_key: None,
+            encryption_private_key: EncryptionPrivateKey(*plaintexts[0]),
+            outgoing_viewing_key: OutgoingViewingKey(*plaintexts[1]),
+        })
+    }
+
+    /// Scan pool commitments and insert decryptable notes into `user_notes`,
+    /// and outputs the account sent to others into `user_sent_notes`.
     ///
     /// Progress is tracked per-account in `account_commitment_scan`.
     pub fn scan_commitments_for_user_notes(
         &mut self,
         total_limit: u32,
         derive: &mut DeriveNoteFn<'_>,
+        derive_sent: &mut DeriveSentNoteFn<'_>,
     ) -> Result<bool> {
         const ACCOUNT_CHUNK: u32 = 4;
 
@@ -1378,21 +2468,68 @@ impl Storage {
                             max_scanned_id = row.commitment_id;
                         }
 
+                        let sender_copy = derive_sent(account, &row)?.unwrap_or_default();
+                        if let Some(sent) = sender_copy.sent {
+                            tx.execute(
+                                "INSERT OR IGNORE INTO user_sent_notes (
+                                    commitment_id,
+                                    account_id,
+                                    recipient_note_key,
+                                    recipient_encryption_key,
+                                    amount
+                                ) VALUES (?1, ?2, ?3, ?4, ?5)",
+                                params![
+                                    row.commitment_id,
+                                    account.account_id,
+                                    sent.recipient_note_key,
+                                    sent.recipient_encryption_key,
+                                    sent.amount.to_string()
+                                ],
+                            )?;
+                        }
+                        // A watch-only account cannot derive nullifiers, so it
+                        // learns them from the spend records of its own outputs.
+                        // The nullifier may already be indexed past the
+                        // reconcile cursor, so it is linked here too.
+                        for input in &sender_copy.spent_inputs {
+                            tx.execute(
+                                "UPDATE user_notes
+                                 SET expected_nullifier = ?1,
+                                     nullifier_id = COALESCE(nullifier_id, (
+                                        SELECT n.id
+                                        FROM pool_nullifiers n
+                                        JOIN raw_contract_events r ON r.id = n.event_id
+                                        WHERE r.contract_id = ?2 AND n.nullifier = ?1
+                                        LIMIT 1
+                                     ))
+                                 WHERE id = ?3 AND account_id = ?4 AND expected_nullifier IS NULL",
+                                params![
+                                    input.nullifier,
+                                    pool_contract_id,
+                                    input.commitment,
+                                    account.account_id
+                                ],
+                            )?;
+                        }
+
                         let Some(derived) = derive(account, &row)? else {
                             continue;
                         };
 
-                        let nullifier_id: Option<i64> = tx
-                            .query_row(
-                                "SELECT n.id
-                                 FROM pool_nullifiers n
-                                 JOIN raw_contract_events r ON r.id = n.event_id
-                                 WHERE r.contract_id = ?1 AND n.nullifier = ?2
-                                 LIMIT 1",
-                                params![pool_contract_id, derived.expected_nullifier],
-                                |r| r.get(0),
-                            )
-                            .optional()?;
+                        let nullifier_id: Option<i64> = match derived.expected_nullifier {
+                            Some(expected_nullifier) => tx
+                                .query_row(
+                                    "SELECT n.id
+                                     FROM pool_nullifiers n
+                                     JOIN raw_contract_events r ON r.id = n.event_id
+                                     WHERE r.contract_id = ?1 AND n.nullifier = ?2
+                                     LIMIT 1",
+                                    params![pool_contract_id, expected_nullifier],
+                                    |r| r.get(0),
+                                )
+                                .optional()?,
+                            None => None,
+                        };
 
                         tx.execute(
                             "INSERT OR IGNORE INTO user_notes (
@@ -1402,8 +2539,9 @@ impl Storage {
                                 nullifier_id,
                                 expected_nullifier,
                                 blinding,
-                                amount
-                            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                                amount,
+                                memo
+                            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
                             params![
                                 row.commitment,
                                 account.account_id,
@@ -1411,7 +2549,8 @@ impl Storage {
                                 nullifier_id,
                                 derived.expected_nullifier,
                                 derived.blinding,
-                                derived.amount.to_string()
+                                derived.amount.to_string(),
+                                derived.memo
                             ],
                         )?;
                     }
@@ -1523,6 +2662,8 @@ impl Storage {
                 )?;
             }
 
+            Self::delete_spent_note_witnesses(&tx, pool_contract_id)?;
+
             if max_id > last_nullifier_id {
                 tx.execute(
                     "UPDATE nullifier_scan_state
@@ -1549,6 +2690,21 @@ fn col_u32(val: i64, col: usize) -> Result<u32, SqlError> {
     u32::try_from(val).map_err(|_| SqlError::IntegralValueOutOfRange(col, val))
 }
 
+/// `gvk_r_x, gvk_r_y, gvk_c1, gvk_c2, gvk_c3` column values, all NULL when the
+/// event carries no ciphertext.
+fn gvk_ciphertext_columns(ciphertext: Option<&GlobalViewKeyCiphertext>) -> [Option<Field>; 5] {
+    match ciphertext {
+        Some(ct) => [
+            Some(ct.r.x),
+            Some(ct.r.y),
+            Some(ct.c1),
+            Some(ct.c2),
+            Some(ct.c3),
+        ],
+        None => [None; 5],
+    }
+}
+
 impl Storage {
     #[allow(clippy::too_many_arguments)]
     pub fn insert_operation(
@@ -1602,53 +2758,1043 @@ impl Storage {
     }
 }
 
-fn map_public_key_entry(row: &rusqlite::Row<'_>) -> Result<crate::types::PublicKeyEntry, SqlError> {
-    Ok(crate::types::PublicKeyEntry {
-        address: row.get(0)?,
-        encryption_key: row.get(1)?,
-        note_key: row.get(2)?,
-        ledger: col_u32(row.get::<_, i64>(3)?, 3)?,
-    })
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{
-        types::{ContractEvent, ContractsEventData, KeyDerivationSignature, NoteAmount},
-        zk::{crypto, encryption},
-    };
+impl Storage {
+    /// Insert `plan`, or update its row when it has an id; returns the row
+    /// id. Confirmed hashes and pending notes are replaced with those of
+    /// `plan`.
+    pub fn save_plan(&mut self, plan: &StoredPlan) -> Result<i64> {
+        let (in_flight_tx_hash, in_flight_state) = match &plan.in_flight {
+            Some(step) => (Some(step.tx_hash.as_str()), Some(step.state.as_str())),
+            None => (None, None),
+        };
+        let tx = self
+            .conn
+            .transaction()
+            .context("failed to start transaction")?;
+        let id = match plan.id {
+            Some(id) => {
+                let updated = tx.execute(
+                    "UPDATE app_transaction_plans
+                     SET status = ?2, tx_count = ?3, state = ?4, in_flight_tx_hash = ?5,
+                         in_flight_state = ?6, updated_at = strftime('%s','now')
+                     WHERE id = ?1",
+                    params![
+                        id,
+                        plan.status.as_str(),
+                        plan.tx_count,
+                        plan.state,
+                        in_flight_tx_hash,
+                        in_flight_state,
+                    ],
+                )?;
+                if updated == 0 {
+                    anyhow::bail!("transaction plan {id} not found");
+                }
+                id
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO app_transaction_plans
+                        (address, pool_contract_id, status, tx_count, state, in_flight_tx_hash,
+                         in_flight_state)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        plan.user_address,
+                        plan.pool_contract_id,
+                        plan.status.as_str(),
+                        plan.tx_count,
+                        plan.state,
+                        in_flight_tx_hash,
+                        in_flight_state,
+                    ],
+                )?;
+                tx.last_insert_rowid()
+            }
+        };
 
-    fn dummy_event(id: &str) -> ContractEvent {
-        ContractEvent {
-            id: id.to_string(),
-            ledger: 1,
-            contract_id: "CPOOL".to_string(),
-            topics: vec!["dummy".to_string()],
-            value: "dummy".to_string(),
+        tx.execute(
+            "DELETE FROM app_transaction_plan_txs WHERE plan_id = ?1",
+            params![id],
+        )?;
+        for (step_index, tx_hash) in plan.confirmed.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO app_transaction_plan_txs (plan_id, step_index, tx_hash)
+                 VALUES (?1, ?2, ?3)",
+                params![id, i64::try_from(step_index)?, tx_hash],
+            )?;
+        }
+        tx.execute(
+            "DELETE FROM app_transaction_plan_pending_notes WHERE plan_id = ?1",
+            params![id],
+        )?;
+        for note in &plan.pending_notes {
+            tx.execute(
+                "INSERT INTO app_transaction_plan_pending_notes (plan_id, commitment, amount)
+                 VALUES (?1, ?2, ?3)",
+                params![id, note.commitment, note.amount],
+            )?;
         }
+        tx.commit().context("failed to commit transaction")?;
+        Ok(id)
     }
 
-    #[test]
-    fn scan_commitments_and_reconcile_nullifiers() -> Result<()> {
-        let mut storage = Storage::connect_in_memory()?;
-
-        // Create an account with keypairs.
-        let signature = KeyDerivationSignature(vec![1u8; 64]);
-        let (note_keypair, enc_keypair) =
-            encryption::derive_encryption_and_note_keypairs(signature.clone())?;
-        let membership_blinding = encryption::derive_membership_blinding(&signature, "testnet")?;
-        storage.save_encryption_and_note_keypairs(
-            "GTESTACCOUNT",
-            &note_keypair,
-            &enc_keypair,
-            &membership_blinding,
+    /// [`PlanStatus::Active`] plans of `address` in `pool_contract_id`,
+    /// oldest first.
+    pub fn list_active_plans(
+        &self,
+        pool_contract_id: &str,
+        address: &str,
+    ) -> Result<Vec<StoredPlan>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, tx_count, state, in_flight_tx_hash, in_flight_state
+             FROM app_transaction_plans
+             WHERE address = ?1 AND pool_contract_id = ?2 AND status = 'active'
+             ORDER BY id",
         )?;
+        let rows = stmt.query_map(params![address, pool_contract_id], |row| {
+            let tx_count_i64: i64 = row.get(1)?;
+            let in_flight_tx_hash: Option<String> = row.get(3)?;
+            let in_flight_state: Option<String> = row.get(4)?;
+            Ok((
+                row.get::<_, i64>(0)?,
+                col_u32(tx_count_i64, 1)?,
+                row.get::<_, String>(2)?,
+                in_flight_tx_hash.zip(in_flight_state),
+            ))
+        })?;
 
-        let account_id: i64 = storage.conn.query_row(
-            "SELECT id FROM accounts WHERE address = ?1",
-            params!["GTESTACCOUNT"],
-            |row| row.get(0),
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, tx_count, state, in_flight) = row?;
+            out.push(StoredPlan {
+                id: Some(id),
+                pool_contract_id: pool_contract_id.to_string(),
+                user_address: address.to_string(),
+                status: PlanStatus::Active,
+                tx_count,
+                state,
+                confirmed: self.plan_tx_hashes(id)?,
+                pending_notes: self.plan_pending_notes(id)?,
+                in_flight: in_flight.map(|(tx_hash, state)| InFlightStep { tx_hash, state }),
+            });
+        }
+        Ok(out)
+    }
+
+    fn plan_tx_hashes(&self, plan_id: i64) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT tx_hash FROM app_transaction_plan_txs WHERE plan_id = ?1 ORDER BY step_index",
+        )?;
+        let rows = stmt.query_map(params![plan_id], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    fn plan_pending_notes(&self, plan_id: i64) -> Result<Vec<SpendableNote>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT commitment, amount FROM app_transaction_plan_pending_notes WHERE plan_id = ?1",
+        )?;
+        let rows = stmt.query_map(params![plan_id], |row| {
+            Ok(SpendableNote {
+                commitment: row.get(0)?,
+                amount: row.get(1)?,
+                created_at_ledger: None,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+}
+
+/// Roots kept per pool in `pool_merkle_roots`, as the pool contract's
+/// `ROOT_HISTORY_SIZE`.
+const POOL_ROOT_HISTORY_SIZE: u64 = 90;
+
+/// Concatenated 32-byte LE encodings of `fields`.
+fn fields_to_blob(fields: &[Field]) -> Vec<u8> {
+    fields.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+fn fields_from_blob(blob: &[u8]) -> Result<Vec<Field>> {
+    if !blob.len().is_multiple_of(32) {
+        anyhow::bail!("field blob length {} is not a multiple of 32", blob.len());
+    }
+    blob.chunks_exact(32)
+        .map(|chunk| Field::try_from_le_bytes(chunk.try_into().expect("32-byte chunk")))
+        .collect()
+}
+
+/// The pool contract records a root per two-leaf insert, so only even leaf
+/// counts are roots the chain has seen.
+fn is_pool_root_checkpoint(leaf_count: u64) -> bool {
+    leaf_count > 0 && leaf_count.is_multiple_of(2)
+}
+
+impl Storage {
+    /// Bring the commitment-tree frontier of `pool_contract_id` up to date
+    /// and return its `(leaf_count, root)`.
+    ///
+    /// The first call for a pool, or one with a different `depth`, builds the
+    /// frontier and the witnesses of owned notes from every stored leaf.
+    /// From then on [`Self::advance_pool_frontiers`] folds new leaves in as
+    /// they are processed, and this only catches up with what is left.
+    pub fn sync_pool_frontier(
+        &mut self,
+        pool_contract_id: &str,
+        depth: u32,
+    ) -> Result<(u64, Field)> {
+        let tx = self.conn.transaction()?;
+        let contract_id = Self::get_or_create_contract_id(&tx, pool_contract_id)?;
+        let frontier = match Self::load_pool_frontier(&tx, contract_id)? {
+            Some(frontier) if frontier.depth() == depth => {
+                Self::advance_pool_frontier(&tx, contract_id, frontier)?.0
+            }
+            _ => Self::rebuild_pool_frontier_tx(&tx, contract_id, depth)?,
+        };
+        tx.commit()?;
+        Ok((frontier.leaf_count(), frontier.root()))
+    }
+
+    /// Discard the frontier, root history and witnesses of `pool_contract_id`
+    /// and rebuild them from the stored leaves.
+    pub fn rebuild_pool_frontier(
+        &mut self,
+        pool_contract_id: &str,
+        depth: u32,
+    ) -> Result<(u64, Field)> {
+        let tx = self.conn.transaction()?;
+        let contract_id = Self::get_or_create_contract_id(&tx, pool_contract_id)?;
+        let frontier = Self::rebuild_pool_frontier_tx(&tx, contract_id, depth)?;
+        tx.commit()?;
+        Ok((frontier.leaf_count(), frontier.root()))
+    }
+
+    /// Fold newly processed commitments into every initialised pool frontier
+    /// and create witnesses for newly derived notes.
+    ///
+    /// Returns `true` when any frontier or witness changed.
+    pub fn advance_pool_frontiers(&mut self) -> Result<bool> {
+        let tx = self.conn.transaction()?;
+        let contract_ids: Vec<i64> = tx
+            .prepare("SELECT contract_id FROM pool_merkle_frontiers ORDER BY contract_id")?
+            .query_map([], |row| row.get(0))?
+            .collect::<core::result::Result<_, _>>()?;
+
+        let mut did_work = false;
+        for contract_id in contract_ids {
+            let Some(frontier) = Self::load_pool_frontier(&tx, contract_id)? else {
+                continue;
+            };
+            did_work |= Self::advance_pool_frontier(&tx, contract_id, frontier)?.1;
+        }
+        tx.commit()?;
+        Ok(did_work)
+    }
+
+    /// Root of `pool_contract_id` once it held `leaf_count` leaves, if that
+    /// root is still in the recorded history.
+    pub fn pool_merkle_root_at(
+        &self,
+        pool_contract_id: &str,
+        leaf_count: u64,
+    ) -> Result<Option<Field>> {
+        let root = self
+            .conn
+            .query_row(
+                "SELECT m.root
+                 FROM pool_merkle_roots m
+                 JOIN contracts c ON c.contract_id = m.contract_id
+                 WHERE c.address = ?1 AND m.leaf_count = ?2",
+                params![pool_contract_id, i64::try_from(leaf_count)?],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Failed to query pool root history")?;
+        Ok(root)
+    }
+
+    /// Merkle proof of `leaf_index` against the current frontier root of
+    /// `pool_contract_id`, in `O(depth)` for owned notes.
+    ///
+    /// Owned notes use their stored witness; any other leaf is proven from
+    /// the leaves [`MerkleFrontier::window_start`] asks for. Call
+    /// [`Self::sync_pool_frontier`] first.
+    pub fn pool_merkle_proof(
+        &self,
+        pool_contract_id: &str,
+        leaf_index: u32,
+    ) -> Result<MerkleProof> {
+        let contract_id: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT f.contract_id
+                 FROM pool_merkle_frontiers f
+                 JOIN contracts c ON c.contract_id = f.contract_id
+                 WHERE c.address = ?1",
+                params![pool_contract_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let Some(contract_id) = contract_id else {
+            anyhow::bail!("commitment tree of pool {pool_contract_id} is not initialised");
+        };
+        let frontier = Self::load_pool_frontier(&self.conn, contract_id)?
+            .context("pool frontier row disappeared")?;
+
+        let stored: Option<(i64, Vec<u8>)> = self
+            .conn
+            .query_row(
+                "SELECT w.leaf_count, w.path
+                 FROM pool_note_witnesses w
+                 JOIN pool_commitments pc ON pc.id = w.commitment_id
+                 JOIN raw_contract_events r ON r.id = pc.event_id
+                 WHERE r.contract_id = ?1 AND pc.leaf_index = ?2",
+                params![contract_id, leaf_index],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        match stored {
+            Some((leaf_count, path))
+                if u64::try_from(leaf_count).ok() == Some(frontier.leaf_count()) =>
+            {
+                MerkleWitness::from_parts(
+                    leaf_index,
+                    frontier.leaf_count(),
+                    fields_from_blob(&path)?,
+                )
+                .proof(&frontier)
+            }
+            _ => {
+                let start = frontier.window_start(leaf_index)?;
+                let window =
+                    Self::pool_leaves(&self.conn, contract_id, start, Some(frontier.leaf_count()))?;
+                frontier.witness(leaf_index, &window)?.proof(&frontier)
+            }
+        }
+    }
+
+    fn load_pool_frontier(conn: &Connection, contract_id: i64) -> Result<Option<MerkleFrontier>> {
+        let row: Option<(u32, i64, Vec<u8>, Vec<u8>)> = conn
+            .query_row(
+                "SELECT depth, leaf_count, filled, edge
+                 FROM pool_merkle_frontiers
+                 WHERE contract_id = ?1",
+                params![contract_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .optional()?;
+        let Some((depth, leaf_count, filled, edge)) = row else {
+            return Ok(None);
+        };
+        let frontier = MerkleFrontier::from_parts(
+            depth,
+            u64::try_from(leaf_count)?,
+            fields_from_blob(&filled)?,
+            fields_from_blob(&edge)?,
+        )?;
+        Ok(Some(frontier))
+    }
+
+    fn store_pool_frontier(
+        conn: &Connection,
+        contract_id: i64,
+        frontier: &MerkleFrontier,
+    ) -> Result<()> {
+        conn.execute(
+            "INSERT INTO pool_merkle_frontiers (contract_id, depth, leaf_count, filled, edge)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(contract_id) DO UPDATE SET
+                depth = excluded.depth,
+                leaf_count = excluded.leaf_count,
+                filled = excluded.filled,
+                edge = excluded.edge",
+            params![
+                contract_id,
+                frontier.depth(),
+                i64::try_from(frontier.leaf_count())?,
+                fields_to_blob(frontier.filled()),
+                fields_to_blob(frontier.edge())
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn store_note_witness(
+        conn: &Connection,
+        commitment_id: i64,
+        witness: &MerkleWitness,
+    ) -> Result<()> {
+        conn.execute(
+            "INSERT INTO pool_note_witnesses (commitment_id, leaf_count, path)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(commitment_id) DO UPDATE SET
+                leaf_count = excluded.leaf_count,
+                path = excluded.path",
+            params![
+                commitment_id,
+                i64::try_from(witness.leaf_count())?,
+                fields_to_blob(witness.path())
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Record `roots` as `(leaf_count, root)` and prune the history to the
+    /// latest [`POOL_ROOT_HISTORY_SIZE`] checkpoints.
+    fn record_pool_roots(
+        conn: &Connection,
+        contract_id: i64,
+        roots: &[(u64, Field)],
+    ) -> Result<()> {
+        let Some(latest) = roots.last().map(|(leaf_count, _)| *leaf_count) else {
+            return Ok(());
+        };
+        let window = POOL_ROOT_HISTORY_SIZE
+            .checked_mul(2)
+            .expect("root history window fits in u64");
+        let cutoff = latest.saturating_sub(window);
+
+        let mut stmt = conn.prepare(
+            "INSERT INTO pool_merkle_roots (contract_id, leaf_count, root)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(contract_id, leaf_count) DO UPDATE SET root = excluded.root",
+        )?;
+        for (leaf_count, root) in roots.iter().filter(|(leaf_count, _)| *leaf_count > cutoff) {
+            stmt.execute(params![contract_id, i64::try_from(*leaf_count)?, root])?;
+        }
+        conn.execute(
+            "DELETE FROM pool_merkle_roots WHERE contract_id = ?1 AND leaf_count <= ?2",
+            params![contract_id, i64::try_from(cutoff)?],
+        )?;
+        Ok(())
+    }
+
+    /// Contiguous leaves of the pool from index `from`, stopping at the
+    /// first gap or at `to` (exclusive).
+    ///
+    /// Leaves past a gap are folded in once the missing commitment is
+    /// processed.
+    fn pool_leaves(
+        conn: &Connection,
+        contract_id: i64,
+        from: u64,
+        to: Option<u64>,
+    ) -> Result<Vec<Field>> {
+        let to = to.map(i64::try_from).transpose()?.unwrap_or(i64::MAX);
+        let mut stmt = conn.prepare(
+            "SELECT pc.leaf_index, pc.commitment
+             FROM pool_commitments pc
+             JOIN raw_contract_events r ON r.id = pc.event_id
+             WHERE r.contract_id = ?1 AND pc.leaf_index >= ?2 AND pc.leaf_index < ?3
+             ORDER BY pc.leaf_index ASC",
+        )?;
+        let rows = stmt.query_map(params![contract_id, i64::try_from(from)?, to], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, Field>(1)?))
+        })?;
+
+        let mut leaves = Vec::new();
+        let mut expected = from;
+        for row in rows {
+            let (index, leaf) = row?;
+            if u64::try_from(index).ok() != Some(expected) {
+                break;
+            }
+            leaves.push(leaf);
+            expected = expected
+                .checked_add(1)
+                .context("pool_commitments index overflow")?;
+        }
+        Ok(leaves)
+    }
+
+    fn rebuild_pool_frontier_tx(
+        tx: &Transaction<'_>,
+        contract_id: i64,
+        depth: u32,
+    ) -> Result<MerkleFrontier> {
+        tx.execute(
+            "DELETE FROM pool_note_witnesses
+             WHERE commitment_id IN (
+                SELECT pc.id
+                FROM pool_commitments pc
+                JOIN raw_contract_events r ON r.id = pc.event_id
+                WHERE r.contract_id = ?1
+             )",
+            params![contract_id],
+        )?;
+        tx.execute(
+            "DELETE FROM pool_merkle_roots WHERE contract_id = ?1",
+            params![contract_id],
+        )?;
+
+        let leaves = Self::pool_leaves(tx, contract_id, 0, None)?;
+        let tree = MerklePrefixTree::new(depth, &leaves)?.into_built();
+        let frontier = MerkleFrontier::from_built(&tree);
+        Self::store_pool_frontier(tx, contract_id, &frontier)?;
+        if is_pool_root_checkpoint(frontier.leaf_count()) {
+            Self::record_pool_roots(tx, contract_id, &[(frontier.leaf_count(), frontier.root())])?;
+        }
+
+        let owned: Vec<(i64, u32)> = tx
+            .prepare(
+                "SELECT pc.id, pc.leaf_index
+                 FROM user_notes n
+                 JOIN pool_commitments pc ON pc.id = n.commitment_id
+                 JOIN raw_contract_events r ON r.id = pc.event_id
+                 WHERE r.contract_id = ?1 AND pc.leaf_index < ?2 AND n.nullifier_id IS NULL",
+            )?
+            .query_map(
+                params![contract_id, i64::try_from(frontier.leaf_count())?],
+                |row| Ok((row.get(0)?, col_u32(row.get(1)?, 1)?)),
+            )?
+            .collect::<core::result::Result<_, _>>()?;
+        for (commitment_id, leaf_index) in owned {
+            let proof = tree.proof(leaf_index)?;
+            let witness =
+                MerkleWitness::from_parts(leaf_index, frontier.leaf_count(), proof.path_elements);
+            Self::store_note_witness(tx, commitment_id, &witness)?;
+        }
+
+        Ok(frontier)
+    }
+
+    /// Drop the witnesses of owned notes in `contract_id` whose nullifier was
+    /// seen, unless another account still holds the note unspent.
+    ///
+    /// Returns whether any witness was deleted.
+    fn delete_spent_note_witnesses(tx: &Transaction<'_>, contract_id: i64) -> Result<bool> {
+        let deleted = tx.execute(
+            "DELETE FROM pool_note_witnesses
+             WHERE commitment_id IN (
+                SELECT n.commitment_id
+                FROM user_notes n
+                JOIN pool_commitments pc ON pc.id = n.commitment_id
+                JOIN raw_contract_events r ON r.id = pc.event_id
+                WHERE r.contract_id = ?1 AND n.nullifier_id IS NOT NULL
+             )
+             AND commitment_id NOT IN (
+                SELECT commitment_id FROM user_notes WHERE nullifier_id IS NULL
+             )",
+            params![contract_id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// Fold the leaves processed past `frontier`, keeping the witnesses of
+    /// unspent owned notes current and creating the missing ones.
+    ///
+    /// Returns the advanced frontier and whether anything changed.
+    fn advance_pool_frontier(
+        tx: &Transaction<'_>,
+        contract_id: i64,
+        mut frontier: MerkleFrontier,
+    ) -> Result<(MerkleFrontier, bool)> {
+        // Notes can also be marked spent while they are scanned.
+        let mut changed = Self::delete_spent_note_witnesses(tx, contract_id)?;
+
+        // Unspent owned notes by leaf index: current witnesses, and the
+        // commitments without one (new notes, or paths left stale by an older
+        // build).
+        let mut witnesses: BTreeMap<u32, (i64, MerkleWitness)> = BTreeMap::new();
+        let mut missing: BTreeMap<u32, i64> = BTreeMap::new();
+        {
+            let mut stmt = tx.prepare(
+                "SELECT pc.id, pc.leaf_index, w.leaf_count, w.path
+                 FROM user_notes n
+                 JOIN pool_commitments pc ON pc.id = n.commitment_id
+                 JOIN raw_contract_events r ON r.id = pc.event_id
+                 LEFT JOIN pool_note_witnesses w ON w.commitment_id = pc.id
+                 WHERE r.contract_id = ?1 AND n.nullifier_id IS NULL",
+            )?;
+            let rows = stmt.query_map(params![contract_id], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    col_u32(row.get(1)?, 1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                    row.get::<_, Option<Vec<u8>>>(3)?,
+                ))
+            })?;
+            for row in rows {
+                let (commitment_id, leaf_index, leaf_count, path) = row?;
+                match (leaf_count, path) {
+                    (Some(leaf_count), Some(path))
+                        if u64::try_from(leaf_count).ok() == Some(frontier.leaf_count()) =>
+                    {
+                        let witness = MerkleWitness::from_parts(
+                            leaf_index,
+                            frontier.leaf_count(),
+                            fields_from_blob(&path)?,
+                        );
+                        witnesses.insert(leaf_index, (commitment_id, witness));
+                    }
+                    _ => {
+                        missing.insert(leaf_index, commitment_id);
+                    }
+                }
+            }
+        }
+
+        let folded: Vec<u32> = missing
+            .keys()
+            .copied()
+            .take_while(|index| u64::from(*index) < frontier.leaf_count())
+            .collect();
+        for leaf_index in folded {
+            let commitment_id = missing.remove(&leaf_index).expect("missing witness");
+            let start = frontier.window_start(leaf_index)?;
+            let window = Self::pool_leaves(tx, contract_id, start, Some(frontier.leaf_count()))?;
+            let witness = frontier.witness(leaf_index, &window)?;
+            witnesses.insert(leaf_index, (commitment_id, witness));
+            changed = true;
+        }
+
+        let leaves = Self::pool_leaves(tx, contract_id, frontier.leaf_count(), None)?;
+        let folded_leaves = !leaves.is_empty();
+        let mut roots = Vec::new();
+        for leaf in leaves {
+            let leaf_index =
+                u32::try_from(frontier.leaf_count()).context("pool leaf index exceeds u32")?;
+            frontier.append(leaf)?;
+            for (_, witness) in witnesses.values_mut() {
+                witness.update(&frontier)?;
+            }
+            if let Some(commitment_id) = missing.remove(&leaf_index) {
+                witnesses.insert(
+                    leaf_index,
+                    (commitment_id, frontier.witness(leaf_index, &[leaf])?),
+                );
+            }
+            if is_pool_root_checkpoint(frontier.leaf_count()) {
+                roots.push((frontier.leaf_count(), frontier.root()));
+            }
+        }
+
+        if folded_leaves {
+            Self::store_pool_frontier(tx, contract_id, &frontier)?;
+            Self::record_pool_roots(tx, contract_id, &roots)?;
+            changed = true;
+        }
+        if changed {
+            for (commitment_id, witness) in witnesses.values() {
+                Self::store_note_witness(tx, *commitment_id, witness)?;
+            }
+        }
+
+        Ok((frontier, changed))
+    }
+}
+
+/// Column name binding sealed wallet backups in the AEAD associated data.
+const WALLET_BACKUP_COLUMN: &str = "wallet_backup";
+
+/// Associated-data context of a sealed [`WalletBackup`] of `version`.
+fn wallet_backup_context(version: u32) -> String {
+    format!("{WALLET_BACKUP_FORMAT}/{version}")
+}
+
+fn backup_raw_event(
+    contract_id: &str,
+    id: String,
+    ledger: u32,
+    topics: &str,
+    value: String,
+) -> ContractEvent {
+    ContractEvent {
+        id,
+        ledger,
+        contract_id: contract_id.to_string(),
+        topics: topics.split(',').map(|s| s.to_string()).collect(),
+        value,
+    }
+}
+
+impl Storage {
+    /// Exports `address`'s keys, notes and operation history, those of its
+    /// sub-accounts, and all app settings, as a [`WalletBackupFile`] JSON
+    /// sealed under `passphrase`.
+    ///
+    /// The passphrase is independent of the wallet passphrase. Fails while the
+    /// wallet is locked.
+    pub fn export_backup(&self, address: &str, passphrase: &Passphrase) -> Result<String> {
+        let keys = self
+            .get_user_keys(address)?
+            .ok_or_else(|| anyhow!("no keys stored for account {address}"))?;
+
+        let notes = self.backup_notes(address)?;
+        let operations = self.backup_operations(address)?;
+        let sub_accounts = self
+            .sub_account_indices(address)?
+            .into_iter()
+            .map(|index| {
+                let sub_address = sub_account_address(address, index);
+                Ok(WalletBackupSubAccount {
+                    index,
+                    notes: self.backup_notes(&sub_address)?,
+                    operations: self.backup_operations(&sub_address)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT key, value FROM app_settings ORDER BY key")?;
+        let mut settings = BTreeMap::new();
+        for row in stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })? {
+            let (key, value) = row?;
+            let value = serde_json::from_str(&value)
+                .with_context(|| format!("failed to decode app setting {key}"))?;
+            settings.insert(key, value);
+        }
+
+        let backup = WalletBackup {
+            address: address.to_string(),
+            keys: WalletBackupKeys {
+                note_keypair: keys.note_keypair,
+                encryption_keypair: keys.encryption_keypair,
+                membership_blinding: keys.membership_blinding,
+            },
+            notes,
+            operations,
+            settings,
+            sub_accounts,
+        };
+        let plaintext =
+            Zeroizing::new(serde_json::to_vec(&backup).context("failed to encode wallet backup")?);
+
+        let params = KdfParams::generate()?;
+        let key = VaultKey::derive(passphrase, &params)?;
+        let sealed = key.seal(
+            WALLET_BACKUP_COLUMN,
+            wallet_backup_context(WALLET_BACKUP_VERSION).as_bytes(),
+            &plaintext,
+        )?;
+        let file = WalletBackupFile {
+            format: WALLET_BACKUP_FORMAT.to_string(),
+            version: WALLET_BACKUP_VERSION,
+            kdf: WalletBackupKdf {
+                algorithm: WALLET_BACKUP_KDF_ARGON2ID.to_string(),
+                salt: STANDARD.encode(params.salt),
+                m_cost: params.m_cost,
+                t_cost: params.t_cost,
+                p_cost: params.p_cost,
+            },
+            sealed: STANDARD.encode(sealed),
+        };
+        tracing::debug!(
+            "[STORAGE] exported {} notes, {} operations and {} sub-accounts for the account {}",
+            backup.notes.len(),
+            backup.operations.len(),
+            backup.sub_accounts.len(),
+            crate::types::Sensitive(&address)
+        );
+        serde_json::to_string_pretty(&file).context("failed to encode wallet backup file")
+    }
+
+    /// Notes stored under `address` in every pool, spent or not.
+    fn backup_notes(&self, address: &str) -> Result<Vec<WalletBackupNote>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT
+                pool.address,
+                c.commitment,
+                c.leaf_index,
+                n.amount,
+                n.blinding,
+                r.id,
+                r.ledger,
+                r.topics,
+                r.value,
+                rn.id,
+                rn.ledger,
+                rn.topics,
+                rn.value
+             FROM user_notes n
+             JOIN accounts a ON a.id = n.account_id
+             JOIN pool_commitments c ON c.id = n.commitment_id
+             JOIN raw_contract_events r ON r.id = c.event_id
+             JOIN contracts pool ON pool.contract_id = r.contract_id
+             LEFT JOIN pool_nullifiers pn ON pn.id = n.nullifier_id
+             LEFT JOIN raw_contract_events rn ON rn.id = pn.event_id
+             WHERE a.address = ?1
+             ORDER BY c.id ASC",
+        )?;
+        let notes = stmt
+            .query_map(params![address], |row| {
+                let pool_contract_id: String = row.get(0)?;
+                let nullifier_event_id: Option<String> = row.get(9)?;
+                let nullifier_event = match nullifier_event_id {
+                    Some(id) => Some(backup_raw_event(
+                        &pool_contract_id,
+                        id,
+                        row.get(10)?,
+                        &row.get::<_, String>(11)?,
+                        row.get(12)?,
+                    )),
+                    None => None,
+                };
+                Ok(WalletBackupNote {
+                    commitment: row.get(1)?,
+                    leaf_index: col_u32(row.get(2)?, 2)?,
+                    amount: row.get(3)?,
+                    blinding: row.get(4)?,
+                    commitment_event: backup_raw_event(
+                        &pool_contract_id,
+                        row.get(5)?,
+                        row.get(6)?,
+                        &row.get::<_, String>(7)?,
+                        row.get(8)?,
+                    ),
+                    nullifier_event,
+                    pool_contract_id,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read notes for backup")?;
+        Ok(notes)
+    }
+
+    /// Operations recorded under `address`, oldest first.
+    fn backup_operations(&self, address: &str) -> Result<Vec<WalletBackupOperation>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT pool_contract_id, op_type, amount, direction, counterparty, tx_hash, created_at
+             FROM app_user_operations
+             WHERE address = ?1
+             ORDER BY id ASC",
+        )?;
+        let operations = stmt
+            .query_map(params![address], |row| {
+                Ok(WalletBackupOperation {
+                    pool_contract_id: row.get(0)?,
+                    operation: UserOperation {
+                        op_type: row.get(1)?,
+                        amount: row.get(2)?,
+                        direction: row.get(3)?,
+                        counterparty: row.get(4)?,
+                        tx_hash: row.get(5)?,
+                        created_at: row.get(6)?,
+                    },
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read operations for backup")?;
+        Ok(operations)
+    }
+
+    /// Indices of the sub-accounts of `address` with stored keys, ascending.
+    fn sub_account_indices(&self, address: &str) -> Result<Vec<u32>> {
+        let prefix = format!("{address}/");
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT address FROM accounts
+             WHERE substr(address, 1, length(?1)) = ?1",
+        )?;
+        let mut indices = stmt
+            .query_map(params![prefix], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read sub-accounts for backup")?
+            .into_iter()
+            .filter_map(|sub_address| sub_address.strip_prefix(&prefix)?.parse::<u32>().ok())
+            .filter(|index| *index != 0)
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        Ok(indices)
+    }
+
+    /// Restores a backup made by [`Storage::export_backup`] for `address`.
+    ///
+    /// Keys are stored as the account's latest keypair (sealed if a wallet
+    /// passphrase is set), and the keys of its sub-accounts are re-derived
+    /// from them. Raw note events are re-inserted for the processor
+    /// to rebuild `user_notes`; operations and events already present are
+    /// skipped, and settings overwrite local ones. Fails while the wallet is
+    /// locked.
+    pub fn import_backup(
+        &mut self,
+        address: &str,
+        backup: &str,
+        passphrase: &Passphrase,
+    ) -> Result<()> {
+        let file: WalletBackupFile =
+            serde_json::from_str(backup).context("failed to parse wallet backup file")?;
+        file.validate()?;
+        let salt = STANDARD
+            .decode(&file.kdf.salt)
+            .context("invalid wallet backup salt")?;
+        let params = KdfParams {
+            salt: salt
+                .try_into()
+                .map_err(|_| anyhow!("wallet backup salt must be 16 bytes"))?,
+            m_cost: file.kdf.m_cost,
+            t_cost: file.kdf.t_cost,
+            p_cost: file.kdf.p_cost,
+        };
+        // The file is untrusted: bound its KDF costs before deriving.
+        params
+            .check_bounds()
+            .context("invalid wallet backup KDF parameters")?;
+        let sealed = STANDARD
+            .decode(&file.sealed)
+            .context("invalid wallet backup payload")?;
+        let key = VaultKey::derive(passphrase, &params)?;
+        let plaintext = key
+            .open(
+                WALLET_BACKUP_COLUMN,
+                wallet_backup_context(file.version).as_bytes(),
+                &sealed,
+            )
+            .context("wrong passphrase or corrupted wallet backup")?;
+        let backup: WalletBackup =
+            serde_json::from_slice(&plaintext).context("failed to decode wallet backup")?;
+        if backup.address != address {
+            return Err(anyhow!(
+                "wallet backup belongs to {}, not {address}",
+                backup.address
+            ));
+        }
+
+        let current = self.get_user_keys(address)?;
+        if current
+            .is_none_or(|keys| keys.note_keypair.public.0 != backup.keys.note_keypair.public.0)
+        {
+            self.save_encryption_and_note_keypairs(
+                address,
+                &backup.keys.note_keypair,
+                &backup.keys.encryption_keypair,
+                &backup.keys.membership_blinding,
+            )?;
+        }
+        for sub_account in &backup.sub_accounts {
+            self.ensure_sub_account_keys(address, sub_account.index)?;
+        }
+
+        let events: Vec<ContractEvent> = backup
+            .notes
+            .iter()
+            .chain(backup.sub_accounts.iter().flat_map(|sub| &sub.notes))
+            .flat_map(|note| {
+                std::iter::once(&note.commitment_event).chain(note.nullifier_event.as_ref())
+            })
+            .cloned()
+            .collect();
+
+        let tx = self
+            .conn
+            .transaction()
+            .context("failed to start transaction")?;
+        Self::insert_raw_events(&tx, &events)?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO app_user_operations
+                    (address, pool_contract_id, op_type, amount, direction, counterparty, tx_hash, created_at)
+                 SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8
+                 WHERE NOT EXISTS (
+                    SELECT 1 FROM app_user_operations
+                    WHERE address = ?1 AND pool_contract_id = ?2 AND op_type = ?3
+                      AND amount = ?4 AND direction = ?5 AND counterparty IS ?6
+                      AND tx_hash IS ?7 AND created_at = ?8
+                 )",
+            )?;
+            let mut owners = vec![(address.to_string(), &backup.operations)];
+            owners.extend(
+                backup
+                    .sub_accounts
+                    .iter()
+                    .map(|sub| (sub_account_address(address, sub.index), &sub.operations)),
+            );
+            for (owner, entry) in owners
+                .iter()
+                .flat_map(|(owner, entries)| entries.iter().map(move |entry| (owner, entry)))
+            {
+                let op = &entry.operation;
+                stmt.execute(params![
+                    owner,
+                    entry.pool_contract_id,
+                    op.op_type,
+                    op.amount,
+                    op.direction,
+                    op.counterparty,
+                    op.tx_hash,
+                    op.created_at,
+                ])?;
+            }
+
+            let mut stmt = tx.prepare(
+                "INSERT INTO app_settings (key, value)
+                 VALUES (?1, ?2)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            )?;
+            for (setting, value) in &backup.settings {
+                stmt.execute(params![setting, value.to_string()])?;
+            }
+        }
+        tx.commit().context("failed to commit transaction")?;
+        tracing::debug!(
+            "[STORAGE] imported {} notes, {} operations and {} sub-accounts for the account {}",
+            backup.notes.len(),
+            backup.operations.len(),
+            backup.sub_accounts.len(),
+            crate::types::Sensitive(&address)
+        );
+        Ok(())
+    }
+}
+
+fn map_public_key_entry(row: &rusqlite::Row<'_>) -> Result<crate::types::PublicKeyEntry, SqlError> {
+    Ok(crate::types::PublicKeyEntry {
+        address: row.get(0)?,
+        encryption_key: row.get(1)?,
+        note_key: row.get(2)?,
+        ledger: col_u32(row.get::<_, i64>(3)?, 3)?,
+        version: row
+            .get::<_, Option<i64>>(4)?
+            .map(|version| col_u32(version, 4))
+            .transpose()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        types::{ContractEvent, ContractsEventData, KeyDerivationSignature, NoteAmount},
+        zk::{crypto, encryption, merkle::MerklePrefixTreeBuilt},
+    };
+
+    fn dummy_event(id: &str) -> ContractEvent {
+        ContractEvent {
+            id: id.to_string(),
+            ledger: 1,
+            contract_id: "CPOOL".to_string(),
+            topics: vec!["dummy".to_string()],
+            value: "dummy".to_string(),
+        }
+    }
+
+    fn no_sent_notes(
+        _: &AccountKeys,
+        _: &PoolCommitmentRow,
+    ) -> Result<Option<DerivedSenderCopyRow>> {
+        Ok(None)
+    }
+
+    #[test]
+    fn scan_commitments_and_reconcile_nullifiers() -> Result<()> {
+        let mut storage = Storage::connect_in_memory()?;
+
+        // Create an account with keypairs.
+        let signature = KeyDerivationSignature(vec![1u8; 64]);
+        let (note_keypair, enc_keypair) =
+            encryption::derive_encryption_and_note_keypairs(signature.clone())?;
+        let membership_blinding = encryption::derive_membership_blinding(&signature, "testnet")?;
+        storage.save_encryption_and_note_keypairs(
+            "GTESTACCOUNT",
+            &note_keypair,
+            &enc_keypair,
+            &membership_blinding,
+        )?;
+
+        let account_id: i64 = storage.conn.query_row(
+            "SELECT id FROM accounts WHERE address = ?1",
+            params!["GTESTACCOUNT"],
+            |row| row.get(0),
         )?;
 
         // Build a commitment + encrypted output addressed to the account.
@@ -1682,6 +3828,7 @@ mod tests {
             commitment,
             index: 3,
             encrypted_output: encrypted_output.clone(),
+            gvk_ciphertext: None,
         }])?;
 
         // Scan commitments -> user_notes.
@@ -1689,8 +3836,9 @@ mod tests {
                           row: &PoolCommitmentRow|
          -> Result<Option<DerivedUserNoteRow>> {
             let opt = crate::zk::notes::try_decrypt_and_derive_user_note(
-                &account.note_keypair,
-                &account.encryption_keypair.private,
+                &account.note_public_key,
+                account.note_private_key.as_ref(),
+                &account.encryption_private_key,
                 &row.commitment,
                 row.leaf_index,
                 &row.encrypted_output,
@@ -1699,9 +3847,10 @@ mod tests {
                 amount: d.amount,
                 blinding: d.blinding,
                 expected_nullifier: d.expected_nullifier,
+                memo: d.memo,
             }))
         };
-        assert!(storage.scan_commitments_for_user_notes(100, &mut derive)?);
+        assert!(storage.scan_commitments_for_user_notes(100, &mut derive, &mut no_sent_notes)?);
 
         let note_count: i64 =
             storage
@@ -1740,6 +3889,7 @@ mod tests {
         storage.save_nullifier_events_batch(&vec![NewNullifierEvent {
             id: "evt-null".to_string(),
             nullifier,
+            gvk_ciphertext: None,
         }])?;
 
         assert!(storage.reconcile_nullifiers(100)?);
@@ -1930,7 +4080,289 @@ mod tests {
             params!["GTESTACCOUNT"],
             |row| row.get(0),
         )?;
-        assert_eq!(count, 1);
+        assert_eq!(count, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unlock_seals_plaintext_keypairs_and_lock_hides_them() -> Result<()> {
+        let mut storage = Storage::connect_in_memory()?;
+        let signature = KeyDerivationSignature(vec![3u8; 64]);
+        let (note_keypair, enc_keypair) =
+            encryption::derive_encryption_and_note_keypairs(signature.clone())?;
+        let membership_blinding = encryption::derive_membership_blinding(&signature, "testnet")?;
+        storage.save_encryption_and_note_keypairs(
+            "GTESTACCOUNT",
+            &note_keypair,
+            &enc_keypair,
+            &membership_blinding,
+        )?;
+        assert!(!storage.has_passphrase()?);
+
+        let passphrase = Passphrase("correct horse".to_string());
+        storage.unlock(&passphrase)?;
+        let (stored_len, sealed): (usize, bool) = storage.conn.query_row(
+            "SELECT length(note_private_key), sealed FROM keypairs",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        assert!(sealed);
+        assert_ne!(stored_len, 32);
+        let keys = storage
+            .get_user_keys("GTESTACCOUNT")?
+            .expect("expected keypairs to exist");
+        assert_eq!(keys.note_keypair.private.0, note_keypair.private.0);
+        assert_eq!(keys.encryption_keypair.private.0, enc_keypair.private.0);
+        assert_eq!(keys.membership_blinding, membership_blinding);
+
+        storage.lock();
+        assert!(storage.is_locked()?);
+        assert!(storage.get_user_keys("GTESTACCOUNT").is_err());
+        assert!(storage.get_accounts_with_latest_keypairs()?.is_empty());
+        assert!(
+            storage
+                .save_encryption_and_note_keypairs(
+                    "GTESTACCOUNT",
+                    &note_keypair,
+                    &enc_keypair,
+                    &membership_blinding,
+                )
+                .is_err()
+        );
+        assert!(
+            storage
+                .unlock(&Passphrase("battery staple".to_string()))
+                .is_err()
+        );
+
+        storage.unlock(&passphrase)?;
+        assert_eq!(storage.get_accounts_with_latest_keypairs()?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unlock_leaves_no_plaintext_keys_in_the_file() -> Result<()> {
+        let path = std::env::temp_dir().join(format!(
+            "stellar-storage-seal-{}.sqlite",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let mut storage = Storage::connect_file(&path)?;
+        // Write the keys the way databases created before `secure_delete`
+        // did, so freed pages keep their old content.
+        storage.conn.pragma_update(None, "secure_delete", "OFF")?;
+        let signature = KeyDerivationSignature(vec![4u8; 64]);
+        let (note_keypair, enc_keypair) =
+            encryption::derive_encryption_and_note_keypairs(signature.clone())?;
+        let membership_blinding = encryption::derive_membership_blinding(&signature, "testnet")?;
+        storage.save_encryption_and_note_keypairs(
+            "GTESTACCOUNT",
+            &note_keypair,
+            &enc_keypair,
+            &membership_blinding,
+        )?;
+        let plaintext: Vec<u8> =
+            storage
+                .conn
+                .query_row("SELECT note_private_key FROM keypairs", [], |row| {
+                    row.get(0)
+                })?;
+
+        storage.unlock(&Passphrase("correct horse".to_string()))?;
+        drop(storage);
+
+        let file = std::fs::read(&path)?;
+        let _ = std::fs::remove_file(&path);
+        assert!(
+            !file
+                .windows(plaintext.len())
+                .any(|window| window == plaintext.as_slice()),
+            "plaintext note private key left in the database file"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn change_passphrase_reseals_keypairs() -> Result<()> {
+        let mut storage = Storage::connect_in_memory()?;
+        let signature = KeyDerivationSignature(vec![4u8; 64]);
+        let (note_keypair, enc_keypair) =
+            encryption::derive_encryption_and_note_keypairs(signature.clone())?;
+        let membership_blinding = encryption::derive_membership_blinding(&signature, "testnet")?;
+        let old = Passphrase("correct horse".to_string());
+        let new = Passphrase("battery staple".to_string());
+
+        storage.unlock(&old)?;
+        storage.save_encryption_and_note_keypairs(
+            "GTESTACCOUNT",
+            &note_keypair,
+            &enc_keypair,
+            &membership_blinding,
+        )?;
+        assert!(storage.change_passphrase(&new, &new).is_err());
+        storage.change_passphrase(&old, &new)?;
+
+        storage.lock();
+        assert!(storage.unlock(&old).is_err());
+        storage.unlock(&new)?;
+        let keys = storage
+            .get_user_keys("GTESTACCOUNT")?
+            .expect("expected keypairs to exist");
+        assert_eq!(keys.note_keypair.private.0, note_keypair.private.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn wallet_backup_round_trips_keys_notes_operations_and_settings() -> Result<()> {
+        let mut storage = Storage::connect_in_memory()?;
+        let sig = KeyDerivationSignature(vec![5u8; 64]);
+        let (note_keypair, enc_keypair) =
+            encryption::derive_encryption_and_note_keypairs(sig.clone())?;
+        let membership_blinding = encryption::derive_membership_blinding(&sig, "testnet")?;
+        storage.save_encryption_and_note_keypairs(
+            "GTESTACCOUNT",
+            &note_keypair,
+            &enc_keypair,
+            &membership_blinding,
+        )?;
+
+        let amount = NoteAmount::from(5);
+        let mut blinding_le = [0u8; 32];
+        blinding_le[0] = 7;
+        let blinding = Field::try_from_le_bytes(blinding_le)?;
+        let commitment_le = crypto::compute_commitment(
+            &Field::from(amount).to_le_bytes(),
+            note_keypair.public.as_ref(),
+            &blinding.to_le_bytes(),
+        )?;
+        let commitment_le: [u8; 32] = commitment_le.try_into().map_err(|v: Vec<u8>| {
+            anyhow::anyhow!("commitment: expected 32 bytes, got {}", v.len())
+        })?;
+        let commitment_event = NewCommitmentEvent {
+            id: "evt-commit".to_string(),
+            commitment: Field::try_from_le_bytes(commitment_le)?,
+            index: 3,
+            encrypted_output: encryption::encrypt_output_note(
+                &enc_keypair.public,
+                amount,
+                &blinding,
+            )?,
+            gvk_ciphertext: None,
+        };
+        let mut derive = |account: &AccountKeys,
+                          row: &PoolCommitmentRow|
+         -> Result<Option<DerivedUserNoteRow>> {
+            let opt = crate::zk::notes::try_decrypt_and_derive_user_note(
+                &account.note_public_key,
+                account.note_private_key.as_ref(),
+                &account.encryption_private_key,
+                &row.commitment,
+                row.leaf_index,
+                &row.encrypted_output,
+            )?;
+            Ok(opt.map(|d| DerivedUserNoteRow {
+                amount: d.amount,
+                blinding: d.blinding,
+                expected_nullifier: d.expected_nullifier,
+                memo: d.memo,
+            }))
+        };
+
+        storage.save_events_batch(&ContractsEventData {
+            events: vec![dummy_event("evt-commit")],
+            cursor: "cur".to_string(),
+            latest_ledger: 1,
+        })?;
+        storage.save_commitment_events_batch(&vec![commitment_event.clone()])?;
+        assert!(storage.scan_commitments_for_user_notes(100, &mut derive, &mut no_sent_notes)?);
+        storage.insert_operation(
+            "GTESTACCOUNT",
+            "CPOOL",
+            "deposit",
+            "5",
+            "in",
+            None,
+            Some("abcd"),
+        )?;
+        let sub_address = storage.ensure_sub_account_keys("GTESTACCOUNT", 2)?;
+        storage.insert_operation(
+            &sub_address,
+            "CPOOL",
+            "transfer",
+            "2",
+            "in",
+            None,
+            Some("ef01"),
+        )?;
+        storage.set_setting_json("contacts", &serde_json::json!([{"name": "alice"}]))?;
+
+        let passphrase = Passphrase("backup passphrase".to_string());
+        let backup = storage.export_backup("GTESTACCOUNT", &passphrase)?;
+        let operations = storage.list_operations("GTESTACCOUNT", "CPOOL", 10)?;
+
+        let mut restored = Storage::connect_in_memory()?;
+        assert!(
+            restored
+                .import_backup("GTESTACCOUNT", &backup, &Passphrase("wrong".to_string()))
+                .is_err()
+        );
+        assert!(
+            restored
+                .import_backup("GOTHERACCOUNT", &backup, &passphrase)
+                .is_err()
+        );
+        // A crafted memory cost is rejected before Argon2 allocates it.
+        let mut crafted: serde_json::Value = serde_json::from_str(&backup)?;
+        crafted["kdf"]["mCost"] = serde_json::json!(u32::MAX);
+        assert!(
+            restored
+                .import_backup("GTESTACCOUNT", &crafted.to_string(), &passphrase)
+                .is_err()
+        );
+        restored.import_backup("GTESTACCOUNT", &backup, &passphrase)?;
+        restored.import_backup("GTESTACCOUNT", &backup, &passphrase)?;
+
+        let keys = restored
+            .get_user_keys("GTESTACCOUNT")?
+            .expect("expected restored keypairs");
+        assert_eq!(keys.note_keypair.private.0, note_keypair.private.0);
+        assert_eq!(keys.encryption_keypair.private.0, enc_keypair.private.0);
+        assert_eq!(keys.membership_blinding, membership_blinding);
+        let restored_operations = restored.list_operations("GTESTACCOUNT", "CPOOL", 10)?;
+        assert_eq!(restored_operations.len(), 1);
+        assert_eq!(restored_operations[0].created_at, operations[0].created_at);
+        // Sub-account keys are re-derived and its operations restored.
+        let sub_keys = restored
+            .get_user_keys(&sub_address)?
+            .expect("expected restored sub-account keys");
+        let stored_sub_keys = storage
+            .get_user_keys(&sub_address)?
+            .expect("sub-account keys are stored");
+        assert_eq!(
+            sub_keys.note_keypair.public.0,
+            stored_sub_keys.note_keypair.public.0
+        );
+        let sub_operations = restored.list_operations(&sub_address, "CPOOL", 10)?;
+        assert_eq!(sub_operations.len(), 1);
+        assert_eq!(sub_operations[0].tx_hash.as_deref(), Some("ef01"));
+        assert_eq!(
+            restored.get_setting_json::<serde_json::Value>("contacts")?,
+            Some(serde_json::json!([{"name": "alice"}]))
+        );
+
+        // The processor rebuilds the note from the restored raw event.
+        let pending = restored.get_unprocessed_events(10)?;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, "evt-commit");
+        restored.save_commitment_events_batch(&vec![commitment_event])?;
+        assert!(restored.scan_commitments_for_user_notes(100, &mut derive, &mut no_sent_notes)?);
+        let notes = restored.list_pool_user_notes("CPOOL", "GTESTACCOUNT")?;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].amount, amount);
 
         Ok(())
     }
@@ -2164,29 +4596,283 @@ mod tests {
             root,
         }])?;
 
-        storage.save_events_batch(&ContractsEventData {
-            cursor: "cur-tip".to_string(),
-            latest_ledger: indexed_tip,
-            events: vec![],
-        })?;
-        storage.save_sync_progress(
-            &[crate::types::SyncMetadata {
-                contract_id: "CASP".to_string(),
-                cursor: "cur-tip".to_string(),
-                last_indexed_ledger: indexed_tip,
-                last_fully_indexed_ledger: 0,
-            }],
-            true,
+        storage.save_events_batch(&ContractsEventData {
+            cursor: "cur-tip".to_string(),
+            latest_ledger: indexed_tip,
+            events: vec![],
+        })?;
+        storage.save_sync_progress(
+            &[crate::types::SyncMetadata {
+                contract_id: "CASP".to_string(),
+                cursor: "cur-tip".to_string(),
+                last_indexed_ledger: indexed_tip,
+                last_fully_indexed_ledger: 0,
+            }],
+            true,
+        )?;
+
+        let status =
+            storage.check_asp_membership_precondition("CASP", &leaf, &root, current_ledger)?;
+        assert!(matches!(status, AspMembershipSync::UserIndex(0)));
+        Ok(())
+    }
+
+    #[test]
+    fn get_unspent_user_note_by_commitment_finds_unspent_note() -> Result<()> {
+        let mut storage = Storage::connect_in_memory()?;
+
+        let sig = KeyDerivationSignature(vec![1u8; 64]);
+        let (note_keypair, enc_keypair) =
+            encryption::derive_encryption_and_note_keypairs(sig.clone())?;
+        let membership_blinding = encryption::derive_membership_blinding(&sig, "testnet")?;
+        storage.save_encryption_and_note_keypairs(
+            "GTESTACCOUNT",
+            &note_keypair,
+            &enc_keypair,
+            &membership_blinding,
+        )?;
+
+        let amount = NoteAmount::from(5);
+        let mut blinding_le = [0u8; 32];
+        blinding_le[0] = 7;
+        let blinding = Field::try_from_le_bytes(blinding_le)?;
+
+        let amount_field_le = Field::from(amount).to_le_bytes();
+        let commitment_le = crypto::compute_commitment(
+            &amount_field_le,
+            note_keypair.public.as_ref(),
+            &blinding.to_le_bytes(),
+        )?;
+        let commitment_le: [u8; 32] = commitment_le.try_into().map_err(|v: Vec<u8>| {
+            anyhow::anyhow!("commitment: expected 32 bytes, got {}", v.len())
+        })?;
+        let commitment = Field::try_from_le_bytes(commitment_le)?;
+
+        let encrypted_output =
+            encryption::encrypt_output_note(&enc_keypair.public, amount, &blinding)?;
+
+        storage.save_events_batch(&ContractsEventData {
+            events: vec![dummy_event("evt-commit")],
+            cursor: "cur".to_string(),
+            latest_ledger: 1,
+        })?;
+        storage.save_commitment_events_batch(&vec![NewCommitmentEvent {
+            id: "evt-commit".to_string(),
+            commitment,
+            index: 3,
+            encrypted_output: encrypted_output.clone(),
+            gvk_ciphertext: None,
+        }])?;
+
+        let mut derive = |account: &AccountKeys,
+                          row: &PoolCommitmentRow|
+         -> Result<Option<DerivedUserNoteRow>> {
+            let opt = crate::zk::notes::try_decrypt_and_derive_user_note(
+                &account.note_public_key,
+                account.note_private_key.as_ref(),
+                &account.encryption_private_key,
+                &row.commitment,
+                row.leaf_index,
+                &row.encrypted_output,
+            )?;
+            Ok(opt.map(|d| DerivedUserNoteRow {
+                amount: d.amount,
+                blinding: d.blinding,
+                expected_nullifier: d.expected_nullifier,
+                memo: d.memo,
+            }))
+        };
+        assert!(storage.scan_commitments_for_user_notes(100, &mut derive, &mut no_sent_notes)?);
+
+        let result =
+            storage.get_unspent_user_note_by_commitment("CPOOL", "GTESTACCOUNT", &commitment)?;
+        assert!(result.is_some());
+        let (got_amount, got_blinding, got_leaf_index) = result.expect("just checked is_some");
+        assert_eq!(got_amount, amount);
+        assert_eq!(got_blinding, blinding);
+        assert_eq!(got_leaf_index, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_unspent_user_note_by_commitment_rejects_spent_note() -> Result<()> {
+        let mut storage = Storage::connect_in_memory()?;
+
+        let sig = KeyDerivationSignature(vec![1u8; 64]);
+        let (note_keypair, enc_keypair) =
+            encryption::derive_encryption_and_note_keypairs(sig.clone())?;
+        let membership_blinding = encryption::derive_membership_blinding(&sig, "testnet")?;
+        storage.save_encryption_and_note_keypairs(
+            "GTESTACCOUNT",
+            &note_keypair,
+            &enc_keypair,
+            &membership_blinding,
+        )?;
+
+        let amount = NoteAmount::from(5);
+        let mut blinding_le = [0u8; 32];
+        blinding_le[0] = 7;
+        let blinding = Field::try_from_le_bytes(blinding_le)?;
+
+        let amount_field_le = Field::from(amount).to_le_bytes();
+        let commitment_le = crypto::compute_commitment(
+            &amount_field_le,
+            note_keypair.public.as_ref(),
+            &blinding.to_le_bytes(),
+        )?;
+        let commitment_le: [u8; 32] = commitment_le.try_into().map_err(|v: Vec<u8>| {
+            anyhow::anyhow!("commitment: expected 32 bytes, got {}", v.len())
+        })?;
+        let commitment = Field::try_from_le_bytes(commitment_le)?;
+
+        let encrypted_output =
+            encryption::encrypt_output_note(&enc_keypair.public, amount, &blinding)?;
+
+        storage.save_events_batch(&ContractsEventData {
+            events: vec![dummy_event("evt-commit")],
+            cursor: "cur".to_string(),
+            latest_ledger: 1,
+        })?;
+        storage.save_commitment_events_batch(&vec![NewCommitmentEvent {
+            id: "evt-commit".to_string(),
+            commitment,
+            index: 3,
+            encrypted_output: encrypted_output.clone(),
+            gvk_ciphertext: None,
+        }])?;
+
+        let mut derive = |account: &AccountKeys,
+                          row: &PoolCommitmentRow|
+         -> Result<Option<DerivedUserNoteRow>> {
+            let opt = crate::zk::notes::try_decrypt_and_derive_user_note(
+                &account.note_public_key,
+                account.note_private_key.as_ref(),
+                &account.encryption_private_key,
+                &row.commitment,
+                row.leaf_index,
+                &row.encrypted_output,
+            )?;
+            Ok(opt.map(|d| DerivedUserNoteRow {
+                amount: d.amount,
+                blinding: d.blinding,
+                expected_nullifier: d.expected_nullifier,
+                memo: d.memo,
+            }))
+        };
+        storage.scan_commitments_for_user_notes(100, &mut derive, &mut no_sent_notes)?;
+
+        // Spend the note via nullifier.
+        let leaf_index: u32 = 3;
+        let mut path_indices_le = [0u8; 32];
+        path_indices_le[..8].copy_from_slice(&(u64::from(leaf_index)).to_le_bytes());
+        let signature = crypto::compute_signature(
+            &note_keypair.private.0,
+            &commitment.to_le_bytes(),
+            &path_indices_le,
+        )?;
+        let nullifier_le =
+            crypto::compute_nullifier(&commitment.to_le_bytes(), &path_indices_le, &signature)?;
+        let nullifier_le: [u8; 32] = nullifier_le.try_into().map_err(|v: Vec<u8>| {
+            anyhow::anyhow!("nullifier: expected 32 bytes, got {}", v.len())
+        })?;
+        let nullifier = Field::try_from_le_bytes(nullifier_le)?;
+
+        storage.save_events_batch(&ContractsEventData {
+            events: vec![dummy_event("evt-null")],
+            cursor: "cur2".to_string(),
+            latest_ledger: 1,
+        })?;
+        storage.save_nullifier_events_batch(&vec![NewNullifierEvent {
+            id: "evt-null".to_string(),
+            nullifier,
+            gvk_ciphertext: None,
+        }])?;
+        storage.reconcile_nullifiers(100)?;
+
+        let result =
+            storage.get_unspent_user_note_by_commitment("CPOOL", "GTESTACCOUNT", &commitment)?;
+        assert!(result.is_none(), "spent note should not be returned");
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_unspent_user_note_by_commitment_rejects_wrong_commitment() -> Result<()> {
+        let mut storage = Storage::connect_in_memory()?;
+
+        let sig = KeyDerivationSignature(vec![1u8; 64]);
+        let (note_keypair, enc_keypair) =
+            encryption::derive_encryption_and_note_keypairs(sig.clone())?;
+        let membership_blinding = encryption::derive_membership_blinding(&sig, "testnet")?;
+        storage.save_encryption_and_note_keypairs(
+            "GTESTACCOUNT",
+            &note_keypair,
+            &enc_keypair,
+            &membership_blinding,
+        )?;
+
+        let amount = NoteAmount::from(5);
+        let mut blinding_le = [0u8; 32];
+        blinding_le[0] = 7;
+        let blinding = Field::try_from_le_bytes(blinding_le)?;
+
+        let encrypted_output =
+            encryption::encrypt_output_note(&enc_keypair.public, amount, &blinding)?;
+
+        let mut commitment_le = [0u8; 32];
+        commitment_le[0] = 1;
+        let commitment = Field::try_from_le_bytes(commitment_le)?;
+
+        storage.save_events_batch(&ContractsEventData {
+            events: vec![dummy_event("evt-commit")],
+            cursor: "cur".to_string(),
+            latest_ledger: 1,
+        })?;
+        storage.save_commitment_events_batch(&vec![NewCommitmentEvent {
+            id: "evt-commit".to_string(),
+            commitment,
+            index: 0,
+            encrypted_output: encrypted_output.clone(),
+            gvk_ciphertext: None,
+        }])?;
+
+        let mut derive = |account: &AccountKeys,
+                          row: &PoolCommitmentRow|
+         -> Result<Option<DerivedUserNoteRow>> {
+            let opt = crate::zk::notes::try_decrypt_and_derive_user_note(
+                &account.note_public_key,
+                account.note_private_key.as_ref(),
+                &account.encryption_private_key,
+                &row.commitment,
+                row.leaf_index,
+                &row.encrypted_output,
+            )?;
+            Ok(opt.map(|d| DerivedUserNoteRow {
+                amount: d.amount,
+                blinding: d.blinding,
+                expected_nullifier: d.expected_nullifier,
+                memo: d.memo,
+            }))
+        };
+        storage.scan_commitments_for_user_notes(100, &mut derive, &mut no_sent_notes)?;
+
+        let wrong_commitment = Field::try_from_le_bytes([
+            2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0,
+        ])?;
+        let result = storage.get_unspent_user_note_by_commitment(
+            "CPOOL",
+            "GTESTACCOUNT",
+            &wrong_commitment,
         )?;
+        assert!(result.is_none(), "wrong commitment should not match");
 
-        let status =
-            storage.check_asp_membership_precondition("CASP", &leaf, &root, current_ledger)?;
-        assert!(matches!(status, AspMembershipSync::UserIndex(0)));
         Ok(())
     }
 
     #[test]
-    fn get_unspent_user_note_by_commitment_finds_unspent_note() -> Result<()> {
+    fn get_user_note_by_commitment_finds_unspent_note() -> Result<()> {
         let mut storage = Storage::connect_in_memory()?;
 
         let sig = KeyDerivationSignature(vec![1u8; 64]);
@@ -2229,14 +4915,16 @@ mod tests {
             commitment,
             index: 3,
             encrypted_output: encrypted_output.clone(),
+            gvk_ciphertext: None,
         }])?;
 
         let mut derive = |account: &AccountKeys,
                           row: &PoolCommitmentRow|
          -> Result<Option<DerivedUserNoteRow>> {
             let opt = crate::zk::notes::try_decrypt_and_derive_user_note(
-                &account.note_keypair,
-                &account.encryption_keypair.private,
+                &account.note_public_key,
+                account.note_private_key.as_ref(),
+                &account.encryption_private_key,
                 &row.commitment,
                 row.leaf_index,
                 &row.encrypted_output,
@@ -2245,12 +4933,12 @@ mod tests {
                 amount: d.amount,
                 blinding: d.blinding,
                 expected_nullifier: d.expected_nullifier,
+                memo: d.memo,
             }))
         };
-        assert!(storage.scan_commitments_for_user_notes(100, &mut derive)?);
+        assert!(storage.scan_commitments_for_user_notes(100, &mut derive, &mut no_sent_notes)?);
 
-        let result =
-            storage.get_unspent_user_note_by_commitment("CPOOL", "GTESTACCOUNT", &commitment)?;
+        let result = storage.get_user_note_by_commitment("CPOOL", "GTESTACCOUNT", &commitment)?;
         assert!(result.is_some());
         let (got_amount, got_blinding, got_leaf_index) = result.expect("just checked is_some");
         assert_eq!(got_amount, amount);
@@ -2261,7 +4949,7 @@ mod tests {
     }
 
     #[test]
-    fn get_unspent_user_note_by_commitment_rejects_spent_note() -> Result<()> {
+    fn get_user_note_by_commitment_finds_spent_note() -> Result<()> {
         let mut storage = Storage::connect_in_memory()?;
 
         let sig = KeyDerivationSignature(vec![1u8; 64]);
@@ -2304,14 +4992,16 @@ mod tests {
             commitment,
             index: 3,
             encrypted_output: encrypted_output.clone(),
+            gvk_ciphertext: None,
         }])?;
 
         let mut derive = |account: &AccountKeys,
                           row: &PoolCommitmentRow|
          -> Result<Option<DerivedUserNoteRow>> {
             let opt = crate::zk::notes::try_decrypt_and_derive_user_note(
-                &account.note_keypair,
-                &account.encryption_keypair.private,
+                &account.note_public_key,
+                account.note_private_key.as_ref(),
+                &account.encryption_private_key,
                 &row.commitment,
                 row.leaf_index,
                 &row.encrypted_output,
@@ -2320,9 +5010,10 @@ mod tests {
                 amount: d.amount,
                 blinding: d.blinding,
                 expected_nullifier: d.expected_nullifier,
+                memo: d.memo,
             }))
         };
-        storage.scan_commitments_for_user_notes(100, &mut derive)?;
+        storage.scan_commitments_for_user_notes(100, &mut derive, &mut no_sent_notes)?;
 
         // Spend the note via nullifier.
         let leaf_index: u32 = 3;
@@ -2348,20 +5039,118 @@ mod tests {
         storage.save_nullifier_events_batch(&vec![NewNullifierEvent {
             id: "evt-null".to_string(),
             nullifier,
+            gvk_ciphertext: None,
         }])?;
         storage.reconcile_nullifiers(100)?;
 
-        let result =
-            storage.get_unspent_user_note_by_commitment("CPOOL", "GTESTACCOUNT", &commitment)?;
-        assert!(result.is_none(), "spent note should not be returned");
+        // The unspent-only lookup rejects it, but the spent-tolerant lookup returns it.
+        assert!(
+            storage
+                .get_unspent_user_note_by_commitment("CPOOL", "GTESTACCOUNT", &commitment)?
+                .is_none(),
+            "sanity: note is spent"
+        );
+
+        let result = storage.get_user_note_by_commitment("CPOOL", "GTESTACCOUNT", &commitment)?;
+        assert!(result.is_some(), "spent note should still be returned");
+        let (got_amount, got_blinding, got_leaf_index) = result.expect("just checked is_some");
+        assert_eq!(got_amount, amount);
+        assert_eq!(got_blinding, blinding);
+        assert_eq!(got_leaf_index, 3);
 
         Ok(())
     }
 
     #[test]
-    fn get_unspent_user_note_by_commitment_rejects_wrong_commitment() -> Result<()> {
+    fn get_pool_commitment_leaves_ordered_returns_ordered_leaves() -> Result<()> {
+        let mut storage = Storage::connect_in_memory()?;
+
+        let leaf0 = Field::try_from_le_bytes([0u8; 32])?;
+        let leaf1 = Field::try_from_le_bytes([1u8; 32])?;
+        let leaf2 = Field::try_from_le_bytes([2u8; 32])?;
+
+        storage.save_events_batch(&ContractsEventData {
+            events: vec![
+                dummy_event("evt-0"),
+                dummy_event("evt-1"),
+                dummy_event("evt-2"),
+            ],
+            cursor: "cur".to_string(),
+            latest_ledger: 1,
+        })?;
+        storage.save_commitment_events_batch(&vec![
+            NewCommitmentEvent {
+                id: "evt-0".to_string(),
+                commitment: leaf0,
+                index: 0,
+                encrypted_output: vec![],
+                gvk_ciphertext: None,
+            },
+            NewCommitmentEvent {
+                id: "evt-1".to_string(),
+                commitment: leaf1,
+                index: 1,
+                encrypted_output: vec![],
+                gvk_ciphertext: None,
+            },
+            NewCommitmentEvent {
+                id: "evt-2".to_string(),
+                commitment: leaf2,
+                index: 2,
+                encrypted_output: vec![],
+                gvk_ciphertext: None,
+            },
+        ])?;
+
+        let leaves = storage.get_pool_commitment_leaves_ordered("CPOOL")?;
+        assert_eq!(leaves.len(), 3);
+        assert_eq!(leaves[0], leaf0);
+        assert_eq!(leaves[1], leaf1);
+        assert_eq!(leaves[2], leaf2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_pool_commitment_leaves_ordered_detects_gaps() -> Result<()> {
         let mut storage = Storage::connect_in_memory()?;
 
+        let leaf0 = Field::try_from_le_bytes([0u8; 32])?;
+        let leaf2 = Field::try_from_le_bytes([2u8; 32])?;
+
+        storage.save_events_batch(&ContractsEventData {
+            events: vec![dummy_event("evt-0"), dummy_event("evt-2")],
+            cursor: "cur".to_string(),
+            latest_ledger: 1,
+        })?;
+        storage.save_commitment_events_batch(&vec![
+            NewCommitmentEvent {
+                id: "evt-0".to_string(),
+                commitment: leaf0,
+                index: 0,
+                encrypted_output: vec![],
+                gvk_ciphertext: None,
+            },
+            NewCommitmentEvent {
+                id: "evt-2".to_string(),
+                commitment: leaf2,
+                index: 2,
+                encrypted_output: vec![],
+                gvk_ciphertext: None,
+            },
+        ])?;
+
+        let err = storage
+            .get_pool_commitment_leaves_ordered("CPOOL")
+            .expect_err("gap should error");
+        assert!(err.to_string().contains("gap/out-of-order"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn pool_frontier_keeps_owned_note_witnesses_current() -> Result<()> {
+        let mut storage = Storage::connect_in_memory()?;
         let sig = KeyDerivationSignature(vec![1u8; 64]);
         let (note_keypair, enc_keypair) =
             encryption::derive_encryption_and_note_keypairs(sig.clone())?;
@@ -2373,36 +5162,210 @@ mod tests {
             &membership_blinding,
         )?;
 
-        let amount = NoteAmount::from(5);
+        let depth = 4u32;
+        let leaves: Vec<Field> = (1u8..=7)
+            .map(|i| Field::try_from_le_bytes([i; 32]))
+            .collect::<Result<_>>()?;
+        let save_leaves = |storage: &mut Storage, range: core::ops::Range<u32>| -> Result<()> {
+            let ids: Vec<String> = range.clone().map(|i| format!("evt-{i}")).collect();
+            storage.save_events_batch(&ContractsEventData {
+                events: ids.iter().map(|id| dummy_event(id)).collect(),
+                cursor: "cur".to_string(),
+                latest_ledger: 1,
+            })?;
+            storage.save_commitment_events_batch(
+                &range
+                    .zip(ids)
+                    .map(|(index, id)| NewCommitmentEvent {
+                        id,
+                        commitment: leaves[index as usize],
+                        index,
+                        encrypted_output: vec![],
+                        gvk_ciphertext: None,
+                    })
+                    .collect(),
+            )
+        };
+
+        // Leaf 1 belongs to the account.
+        save_leaves(&mut storage, 0..4)?;
+        let owned = leaves[1];
+        let mut derive =
+            |_: &AccountKeys, row: &PoolCommitmentRow| -> Result<Option<DerivedUserNoteRow>> {
+                Ok((row.commitment == owned).then(|| DerivedUserNoteRow {
+                    amount: NoteAmount::from(5),
+                    blinding: Field::ONE,
+                    expected_nullifier: Some(Field::ONE),
+                    memo: None,
+                }))
+            };
+        assert!(storage.scan_commitments_for_user_notes(100, &mut derive, &mut no_sent_notes)?);
+        assert!(
+            !storage.advance_pool_frontiers()?,
+            "no frontier before the first proof"
+        );
+
+        let prefix = |count: usize| -> Result<MerklePrefixTreeBuilt> {
+            Ok(MerklePrefixTree::new(depth, &leaves[..count])?.into_built())
+        };
+        let (leaf_count, root) = storage.sync_pool_frontier("CPOOL", depth)?;
+        assert_eq!(leaf_count, 4);
+        assert_eq!(root, prefix(4)?.root()?);
+
+        save_leaves(&mut storage, 4..7)?;
+        assert!(storage.advance_pool_frontiers()?);
+        assert!(!storage.advance_pool_frontiers()?);
+
+        let witness_leaf_count: i64 =
+            storage
+                .conn
+                .query_row("SELECT leaf_count FROM pool_note_witnesses", [], |row| {
+                    row.get(0)
+                })?;
+        assert_eq!(witness_leaf_count, 7);
+
+        let full = prefix(7)?;
+        for leaf_index in [1u32, 5] {
+            let proof = storage.pool_merkle_proof("CPOOL", leaf_index)?;
+            let expected = full.proof(leaf_index)?;
+            assert_eq!(proof.root, expected.root);
+            assert_eq!(proof.path_elements, expected.path_elements);
+            assert_eq!(proof.path_indices, expected.path_indices);
+        }
+
+        assert_eq!(
+            storage.pool_merkle_root_at("CPOOL", 6)?,
+            Some(prefix(6)?.root()?)
+        );
+        assert_eq!(storage.pool_merkle_root_at("CPOOL", 5)?, None);
+        assert_eq!(
+            storage.rebuild_pool_frontier("CPOOL", depth)?,
+            (7, full.root()?)
+        );
+
+        // Spending the note drops its witness, and it is no longer tracked.
+        storage.save_events_batch(&ContractsEventData {
+            events: vec![dummy_event("evt-null")],
+            cursor: "cur-null".to_string(),
+            latest_ledger: 1,
+        })?;
+        storage.save_nullifier_events_batch(&vec![NewNullifierEvent {
+            id: "evt-null".to_string(),
+            nullifier: Field::ONE,
+            gvk_ciphertext: None,
+        }])?;
+        assert!(storage.reconcile_nullifiers(100)?);
+        let witness_count = |storage: &Storage| -> Result<i64> {
+            Ok(storage
+                .conn
+                .query_row("SELECT COUNT(*) FROM pool_note_witnesses", [], |row| {
+                    row.get(0)
+                })?)
+        };
+        assert_eq!(witness_count(&storage)?, 0);
+        assert!(!storage.advance_pool_frontiers()?);
+        assert_eq!(
+            storage.rebuild_pool_frontier("CPOOL", depth)?,
+            (7, full.root()?)
+        );
+        assert_eq!(witness_count(&storage)?, 0, "rebuild skips spent notes");
+
+        Ok(())
+    }
+
+    #[test]
+    fn scan_commitments_recovers_sent_notes() -> Result<()> {
+        let mut storage = Storage::connect_in_memory()?;
+
+        let signature = KeyDerivationSignature(vec![1u8; 64]);
+        let (note_keypair, enc_keypair) =
+            encryption::derive_encryption_and_note_keypairs(signature.clone())?;
+        let membership_blinding = encryption::derive_membership_blinding(&signature, "testnet")?;
+        storage.save_encryption_and_note_keypairs(
+            "GSENDER",
+            &note_keypair,
+            &enc_keypair,
+            &membership_blinding,
+        )?;
+        let ovk = encryption::derive_outgoing_viewing_key(&note_keypair.private);
+        let (recipient_note, recipient_enc) =
+            encryption::derive_encryption_and_note_keypairs(KeyDerivationSignature(vec![2u8; 64]))?;
+
         let mut blinding_le = [0u8; 32];
         blinding_le[0] = 7;
         let blinding = Field::try_from_le_bytes(blinding_le)?;
+        let commit = |amount: NoteAmount, note_key: &NotePublicKey| -> Result<Field> {
+            let commitment = crypto::compute_commitment(
+                &Field::from(amount).to_le_bytes(),
+                note_key.as_ref(),
+                &blinding.to_le_bytes(),
+            )?;
+            Field::try_from_le_bytes(commitment.try_into().map_err(|v: Vec<u8>| {
+                anyhow::anyhow!("commitment: expected 32 bytes, got {}", v.len())
+            })?)
+        };
 
-        let encrypted_output =
-            encryption::encrypt_output_note(&enc_keypair.public, amount, &blinding)?;
-
-        let mut commitment_le = [0u8; 32];
-        commitment_le[0] = 1;
-        let commitment = Field::try_from_le_bytes(commitment_le)?;
-
+        let sent_amount = NoteAmount::from(9);
+        let change_amount = NoteAmount::from(4);
         storage.save_events_batch(&ContractsEventData {
-            events: vec![dummy_event("evt-commit")],
+            events: vec![
+                dummy_event("evt-sent"),
+                dummy_event("evt-change"),
+                ContractEvent {
+                    contract_id: "CREGISTRY".to_string(),
+                    ..dummy_event("evt-reg")
+                },
+            ],
             cursor: "cur".to_string(),
             latest_ledger: 1,
         })?;
-        storage.save_commitment_events_batch(&vec![NewCommitmentEvent {
-            id: "evt-commit".to_string(),
-            commitment,
-            index: 0,
-            encrypted_output: encrypted_output.clone(),
+        storage.save_commitment_events_batch(&vec![
+            NewCommitmentEvent {
+                id: "evt-sent".to_string(),
+                commitment: commit(sent_amount, &recipient_note.public)?,
+                index: 0,
+                encrypted_output: encryption::encrypt_output_note_with_ovk(
+                    &recipient_note.public,
+                    &recipient_enc.public,
+                    sent_amount,
+                    &blinding,
+                    Some("for GRECIPIENT"),
+                    &[],
+                    &ovk,
+                )?,
+                gvk_ciphertext: None,
+            },
+            NewCommitmentEvent {
+                id: "evt-change".to_string(),
+                commitment: commit(change_amount, &note_keypair.public)?,
+                index: 1,
+                encrypted_output: encryption::encrypt_output_note_with_ovk(
+                    &note_keypair.public,
+                    &enc_keypair.public,
+                    change_amount,
+                    &blinding,
+                    Some("change"),
+                    &[],
+                    &ovk,
+                )?,
+                gvk_ciphertext: None,
+            },
+        ])?;
+        storage.save_public_key_events_batch(&vec![PublicKeyEvent {
+            id: "evt-reg".to_string(),
+            owner: "GRECIPIENT".to_string(),
+            encryption_key: recipient_enc.public.clone(),
+            note_key: recipient_note.public.clone(),
+            version: Some(1),
         }])?;
 
         let mut derive = |account: &AccountKeys,
                           row: &PoolCommitmentRow|
          -> Result<Option<DerivedUserNoteRow>> {
             let opt = crate::zk::notes::try_decrypt_and_derive_user_note(
-                &account.note_keypair,
-                &account.encryption_keypair.private,
+                &account.note_public_key,
+                account.note_private_key.as_ref(),
+                &account.encryption_private_key,
                 &row.commitment,
                 row.leaf_index,
                 &row.encrypted_output,
@@ -2411,76 +5374,144 @@ mod tests {
                 amount: d.amount,
                 blinding: d.blinding,
                 expected_nullifier: d.expected_nullifier,
+                memo: d.memo,
             }))
         };
-        storage.scan_commitments_for_user_notes(100, &mut derive)?;
-
-        let wrong_commitment = Field::try_from_le_bytes([
-            2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-            0, 0, 0,
-        ])?;
-        let result = storage.get_unspent_user_note_by_commitment(
-            "CPOOL",
-            "GTESTACCOUNT",
-            &wrong_commitment,
-        )?;
-        assert!(result.is_none(), "wrong commitment should not match");
+        let mut derive_sent = |account: &AccountKeys,
+                               row: &PoolCommitmentRow|
+         -> Result<Option<DerivedSenderCopyRow>> {
+            let opt = crate::zk::notes::try_open_sender_copy(
+                &account.note_public_key,
+                &account.outgoing_viewing_key,
+                &row.commitment,
+                &row.encrypted_output,
+            )?;
+            Ok(opt.map(|copy| DerivedSenderCopyRow {
+                sent: copy.sent.map(|s| DerivedSentNoteRow {
+                    recipient_note_key: s.recipient_note_public_key,
+                    recipient_encryption_key: s.recipient_encryption_public_key,
+                    amount: s.amount,
+                }),
+                spent_inputs: copy.spent_inputs,
+            }))
+        };
+        assert!(storage.scan_commitments_for_user_notes(100, &mut derive, &mut derive_sent)?);
+
+        // The change output is a received note, not a sent one.
+        let received = storage.list_user_notes("GSENDER", 10)?;
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].amount, change_amount);
+        assert_eq!(received[0].memo.as_deref(), Some("change"));
+
+        let sent = storage.list_sent_notes("GSENDER", 10)?;
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].amount, sent_amount);
+        assert_eq!(sent[0].leaf_index, 0);
+        assert_eq!(sent[0].recipient_note_public_key.0, recipient_note.public.0);
+        assert_eq!(
+            sent[0].recipient_encryption_public_key.0,
+            recipient_enc.public.0
+        );
+        assert_eq!(sent[0].recipient_address.as_deref(), Some("GRECIPIENT"));
 
         Ok(())
     }
 
     #[test]
-    fn get_user_note_by_commitment_finds_unspent_note() -> Result<()> {
+    fn watch_only_scan_sees_notes_and_spend_records() -> Result<()> {
         let mut storage = Storage::connect_in_memory()?;
 
-        let sig = KeyDerivationSignature(vec![1u8; 64]);
         let (note_keypair, enc_keypair) =
-            encryption::derive_encryption_and_note_keypairs(sig.clone())?;
-        let membership_blinding = encryption::derive_membership_blinding(&sig, "testnet")?;
-        storage.save_encryption_and_note_keypairs(
-            "GTESTACCOUNT",
-            &note_keypair,
-            &enc_keypair,
-            &membership_blinding,
-        )?;
-
-        let amount = NoteAmount::from(5);
-        let mut blinding_le = [0u8; 32];
-        blinding_le[0] = 7;
-        let blinding = Field::try_from_le_bytes(blinding_le)?;
-
-        let amount_field_le = Field::from(amount).to_le_bytes();
-        let commitment_le = crypto::compute_commitment(
-            &amount_field_le,
-            note_keypair.public.as_ref(),
-            &blinding.to_le_bytes(),
-        )?;
-        let commitment_le: [u8; 32] = commitment_le.try_into().map_err(|v: Vec<u8>| {
-            anyhow::anyhow!("commitment: expected 32 bytes, got {}", v.len())
-        })?;
-        let commitment = Field::try_from_le_bytes(commitment_le)?;
+            encryption::derive_encryption_and_note_keypairs(KeyDerivationSignature(vec![1u8; 64]))?;
+        let ovk = encryption::derive_outgoing_viewing_key(&note_keypair.private);
+        storage.save_viewing_key(&ViewingKey::new(
+            "GOWNER",
+            note_keypair.public.clone(),
+            enc_keypair.private.clone(),
+            ovk.clone(),
+        ))?;
+        assert!(storage.is_watch_only("GOWNER")?);
+        let (recipient_note, recipient_enc) =
+            encryption::derive_encryption_and_note_keypairs(KeyDerivationSignature(vec![2u8; 64]))?;
 
-        let encrypted_output =
-            encryption::encrypt_output_note(&enc_keypair.public, amount, &blinding)?;
+        let mut blinding_le = [0u8; 32];
+        blinding_le[0] = 7;
+        let blinding = Field::try_from_le_bytes(blinding_le)?;
+        let commit = |amount: NoteAmount, note_key: &NotePublicKey| -> Result<Field> {
+            let commitment = crypto::compute_commitment(
+                &Field::from(amount).to_le_bytes(),
+                note_key.as_ref(),
+                &blinding.to_le_bytes(),
+            )?;
+            Field::try_from_le_bytes(commitment.try_into().map_err(|v: Vec<u8>| {
+                anyhow::anyhow!("commitment: expected 32 bytes, got {}", v.len())
+            })?)
+        };
 
+        // A note received by the owner, then spent in a transfer whose output
+        // carries the spend record.
+        let amount = NoteAmount::from(5);
+        let received = commit(amount, &note_keypair.public)?;
+        let mut nullifier_le = [0u8; 32];
+        nullifier_le[0] = 42;
+        let nullifier = Field::try_from_le_bytes(nullifier_le)?;
         storage.save_events_batch(&ContractsEventData {
-            events: vec![dummy_event("evt-commit")],
+            events: vec![
+                dummy_event("evt-received"),
+                dummy_event("evt-sent"),
+                dummy_event("evt-null"),
+            ],
             cursor: "cur".to_string(),
             latest_ledger: 1,
         })?;
-        storage.save_commitment_events_batch(&vec![NewCommitmentEvent {
-            id: "evt-commit".to_string(),
-            commitment,
-            index: 3,
-            encrypted_output: encrypted_output.clone(),
+        storage.save_commitment_events_batch(&vec![
+            NewCommitmentEvent {
+                id: "evt-received".to_string(),
+                commitment: received,
+                index: 0,
+                encrypted_output: encryption::encrypt_output_note_with_ovk(
+                    &note_keypair.public,
+                    &enc_keypair.public,
+                    amount,
+                    &blinding,
+                    None,
+                    &[],
+                    &ovk,
+                )?,
+                gvk_ciphertext: None,
+            },
+            NewCommitmentEvent {
+                id: "evt-sent".to_string(),
+                commitment: commit(amount, &recipient_note.public)?,
+                index: 1,
+                encrypted_output: encryption::encrypt_output_note_with_ovk(
+                    &recipient_note.public,
+                    &recipient_enc.public,
+                    amount,
+                    &blinding,
+                    None,
+                    &[SpentInput {
+                        commitment: received,
+                        nullifier,
+                    }],
+                    &ovk,
+                )?,
+                gvk_ciphertext: None,
+            },
+        ])?;
+        storage.save_nullifier_events_batch(&vec![NewNullifierEvent {
+            id: "evt-null".to_string(),
+            nullifier,
+            gvk_ciphertext: None,
         }])?;
 
         let mut derive = |account: &AccountKeys,
                           row: &PoolCommitmentRow|
          -> Result<Option<DerivedUserNoteRow>> {
             let opt = crate::zk::notes::try_decrypt_and_derive_user_note(
-                &account.note_keypair,
-                &account.encryption_keypair.private,
+                &account.note_public_key,
+                account.note_private_key.as_ref(),
+                &account.encryption_private_key,
                 &row.commitment,
                 row.leaf_index,
                 &row.encrypted_output,
@@ -2489,207 +5520,402 @@ mod tests {
                 amount: d.amount,
                 blinding: d.blinding,
                 expected_nullifier: d.expected_nullifier,
+                memo: d.memo,
             }))
         };
-        assert!(storage.scan_commitments_for_user_notes(100, &mut derive)?);
-
-        let result = storage.get_user_note_by_commitment("CPOOL", "GTESTACCOUNT", &commitment)?;
-        assert!(result.is_some());
-        let (got_amount, got_blinding, got_leaf_index) = result.expect("just checked is_some");
-        assert_eq!(got_amount, amount);
-        assert_eq!(got_blinding, blinding);
-        assert_eq!(got_leaf_index, 3);
+        let mut derive_sent = |account: &AccountKeys,
+                               row: &PoolCommitmentRow|
+         -> Result<Option<DerivedSenderCopyRow>> {
+            let opt = crate::zk::notes::try_open_sender_copy(
+                &account.note_public_key,
+                &account.outgoing_viewing_key,
+                &row.commitment,
+                &row.encrypted_output,
+            )?;
+            Ok(opt.map(|copy| DerivedSenderCopyRow {
+                sent: copy.sent.map(|s| DerivedSentNoteRow {
+                    recipient_note_key: s.recipient_note_public_key,
+                    recipient_encryption_key: s.recipient_encryption_public_key,
+                    amount: s.amount,
+                }),
+                spent_inputs: copy.spent_inputs,
+            }))
+        };
+        assert!(storage.scan_commitments_for_user_notes(100, &mut derive, &mut derive_sent)?);
+
+        let notes = storage.list_user_notes("GOWNER", 10)?;
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].amount, amount);
+        assert!(notes[0].spent);
+        assert_eq!(storage.list_sent_notes("GOWNER", 10)?.len(), 1);
+        assert!(storage.get_user_keys("GOWNER")?.is_none());
+        assert_eq!(
+            storage.export_viewing_key("GOWNER")?.outgoing_viewing_key.0,
+            ovk.0
+        );
 
         Ok(())
     }
 
     #[test]
-    fn get_user_note_by_commitment_finds_spent_note() -> Result<()> {
+    fn unlock_seals_plaintext_viewing_keys() -> Result<()> {
         let mut storage = Storage::connect_in_memory()?;
+        let (note_keypair, enc_keypair) =
+            encryption::derive_encryption_and_note_keypairs(KeyDerivationSignature(vec![1u8; 64]))?;
+        let ovk = encryption::derive_outgoing_viewing_key(&note_keypair.private);
+        storage.save_viewing_key(&ViewingKey::new(
+            "GOWNER",
+            note_keypair.public.clone(),
+            enc_keypair.private.clone(),
+            ovk.clone(),
+        ))?;
+
+        storage.unlock(&Passphrase("correct horse".to_string()))?;
+        let sealed: bool =
+            storage
+                .conn
+                .query_row("SELECT sealed FROM viewing_keys", [], |row| row.get(0))?;
+        assert!(sealed);
+        assert_eq!(storage.get_accounts_with_latest_keypairs()?.len(), 1);
+
+        storage.lock();
+        assert!(storage.get_accounts_with_latest_keypairs()?.is_empty());
+        assert!(storage.get_viewing_key("GOWNER").is_err());
+
+        storage.unlock(&Passphrase("correct horse".to_string()))?;
+        storage.change_passphrase(
+            &Passphrase("correct horse".to_string()),
+            &Passphrase("battery staple".to_string()),
+        )?;
+        let reopened = storage
+            .get_viewing_key("GOWNER")?
+            .expect("viewing key is stored");
+        assert_eq!(reopened.encryption_private_key.0, enc_keypair.private.0);
+        assert_eq!(reopened.outgoing_viewing_key.0, ovk.0);
 
-        let sig = KeyDerivationSignature(vec![1u8; 64]);
+        Ok(())
+    }
+
+    #[test]
+    fn ensure_sub_account_keys_derives_a_separate_account() -> Result<()> {
+        let mut storage = Storage::connect_in_memory()?;
+        let signature = KeyDerivationSignature(vec![1u8; 64]);
         let (note_keypair, enc_keypair) =
-            encryption::derive_encryption_and_note_keypairs(sig.clone())?;
-        let membership_blinding = encryption::derive_membership_blinding(&sig, "testnet")?;
+            encryption::derive_encryption_and_note_keypairs(signature.clone())?;
+        let membership_blinding = encryption::derive_membership_blinding(&signature, "testnet")?;
         storage.save_encryption_and_note_keypairs(
-            "GTESTACCOUNT",
+            "GOWNER",
             &note_keypair,
             &enc_keypair,
             &membership_blinding,
         )?;
 
-        let amount = NoteAmount::from(5);
-        let mut blinding_le = [0u8; 32];
-        blinding_le[0] = 7;
-        let blinding = Field::try_from_le_bytes(blinding_le)?;
-
-        let amount_field_le = Field::from(amount).to_le_bytes();
-        let commitment_le = crypto::compute_commitment(
-            &amount_field_le,
-            note_keypair.public.as_ref(),
-            &blinding.to_le_bytes(),
+        assert_eq!(storage.ensure_sub_account_keys("GOWNER", 0)?, "GOWNER");
+        let sub_address = storage.ensure_sub_account_keys("GOWNER", 2)?;
+        assert_eq!(sub_address, "GOWNER/2");
+        let (sub_note, sub_enc, sub_blinding) = encryption::derive_sub_account_keys(
+            &note_keypair.private,
+            &enc_keypair.private,
+            &membership_blinding,
+            2,
         )?;
-        let commitment_le: [u8; 32] = commitment_le.try_into().map_err(|v: Vec<u8>| {
-            anyhow::anyhow!("commitment: expected 32 bytes, got {}", v.len())
-        })?;
-        let commitment = Field::try_from_le_bytes(commitment_le)?;
+        let stored = storage
+            .get_user_keys(&sub_address)?
+            .expect("sub-account keys are stored");
+        assert_eq!(stored.note_keypair.public.0, sub_note.public.0);
+        assert_eq!(stored.encryption_keypair.public.0, sub_enc.public.0);
+        assert_eq!(stored.membership_blinding, sub_blinding);
+        assert_ne!(stored.note_keypair.public.0, note_keypair.public.0);
+
+        // A second call keeps the stored keys and adds no account.
+        assert_eq!(storage.ensure_sub_account_keys("GOWNER", 2)?, sub_address);
+        assert_eq!(storage.get_accounts_with_latest_keypairs()?.len(), 2);
+        assert!(storage.ensure_sub_account_keys("GNOBODY", 1).is_err());
 
-        let encrypted_output =
-            encryption::encrypt_output_note(&enc_keypair.public, amount, &blinding)?;
+        Ok(())
+    }
 
+    #[test]
+    fn lookup_public_key_honours_revocations() -> Result<()> {
+        let mut storage = Storage::connect_in_memory()?;
+        let event = |id: &str, ledger: u32| ContractEvent {
+            id: id.to_string(),
+            ledger,
+            contract_id: "CREGISTRY".to_string(),
+            topics: vec!["dummy".to_string()],
+            value: "dummy".to_string(),
+        };
+        let registration = |id: &str, fill: u8, version: u32| PublicKeyEvent {
+            id: id.to_string(),
+            owner: "GOWNER".to_string(),
+            encryption_key: EncryptionPublicKey([fill; 32]),
+            note_key: NotePublicKey([fill; 32]),
+            version: Some(version),
+        };
         storage.save_events_batch(&ContractsEventData {
-            events: vec![dummy_event("evt-commit")],
-            cursor: "cur".to_string(),
-            latest_ledger: 1,
+            cursor: "c1".to_string(),
+            latest_ledger: 30,
+            events: vec![
+                event("evt-reg-1", 10),
+                event("evt-revoke-1", 20),
+                event("evt-reg-2", 30),
+            ],
         })?;
-        storage.save_commitment_events_batch(&vec![NewCommitmentEvent {
-            id: "evt-commit".to_string(),
-            commitment,
-            index: 3,
-            encrypted_output: encrypted_output.clone(),
+
+        storage.save_public_key_events_batch(&vec![registration("evt-reg-1", 1, 1)])?;
+        let entry = storage
+            .lookup_public_key_by_address("GOWNER")?
+            .expect("registered owner should resolve");
+        assert_eq!(entry.ledger, 10);
+        assert_eq!(entry.version, Some(1));
+
+        storage.save_public_key_revocations_batch(&vec![PublicKeyRevokedEvent {
+            id: "evt-revoke-1".to_string(),
+            owner: "GOWNER".to_string(),
+            version: 1,
         }])?;
+        assert!(storage.lookup_public_key_by_address("GOWNER")?.is_none());
 
-        let mut derive = |account: &AccountKeys,
-                          row: &PoolCommitmentRow|
-         -> Result<Option<DerivedUserNoteRow>> {
-            let opt = crate::zk::notes::try_decrypt_and_derive_user_note(
-                &account.note_keypair,
-                &account.encryption_keypair.private,
-                &row.commitment,
-                row.leaf_index,
-                &row.encrypted_output,
-            )?;
-            Ok(opt.map(|d| DerivedUserNoteRow {
-                amount: d.amount,
-                blinding: d.blinding,
-                expected_nullifier: d.expected_nullifier,
-            }))
-        };
-        storage.scan_commitments_for_user_notes(100, &mut derive)?;
+        storage.save_public_key_events_batch(&vec![registration("evt-reg-2", 2, 2)])?;
+        let entry = storage
+            .lookup_public_key_by_address("GOWNER")?
+            .expect("re-registered owner should resolve");
+        assert_eq!(entry.ledger, 30);
+        assert_eq!(entry.version, Some(2));
+        assert_eq!(entry.note_key.0, [2u8; 32]);
+        assert!(storage.get_unprocessed_events(10)?.is_empty());
 
-        // Spend the note via nullifier.
-        let leaf_index: u32 = 3;
-        let mut path_indices_le = [0u8; 32];
-        path_indices_le[..8].copy_from_slice(&(u64::from(leaf_index)).to_le_bytes());
-        let signature = crypto::compute_signature(
-            &note_keypair.private.0,
-            &commitment.to_le_bytes(),
-            &path_indices_le,
-        )?;
-        let nullifier_le =
-            crypto::compute_nullifier(&commitment.to_le_bytes(), &path_indices_le, &signature)?;
-        let nullifier_le: [u8; 32] = nullifier_le.try_into().map_err(|v: Vec<u8>| {
-            anyhow::anyhow!("nullifier: expected 32 bytes, got {}", v.len())
-        })?;
-        let nullifier = Field::try_from_le_bytes(nullifier_le)?;
+        Ok(())
+    }
 
+    #[test]
+    fn asp_non_membership_leaf_ops_follow_event_order() -> Result<()> {
+        let mut storage = Storage::connect_in_memory()?;
+        let field = |v: u8| {
+            let mut le = [0u8; 32];
+            le[0] = v;
+            Field::try_from_le_bytes(le).expect("field")
+        };
+        let event = |id: &str, ledger: u32| ContractEvent {
+            id: id.to_string(),
+            ledger,
+            contract_id: "CNONMEM".to_string(),
+            topics: vec!["LeafInserted".to_string()],
+            value: "dummy".to_string(),
+        };
         storage.save_events_batch(&ContractsEventData {
-            events: vec![dummy_event("evt-null")],
-            cursor: "cur2".to_string(),
-            latest_ledger: 1,
+            cursor: "c1".to_string(),
+            latest_ledger: 30,
+            events: vec![event("evt-ins", 10), event("evt-del", 20)],
         })?;
-        storage.save_nullifier_events_batch(&vec![NewNullifierEvent {
-            id: "evt-null".to_string(),
-            nullifier,
+        storage.save_sync_progress(
+            &[crate::types::SyncMetadata {
+                contract_id: "CNONMEM".to_string(),
+                cursor: "c1".to_string(),
+                last_indexed_ledger: 30,
+                last_fully_indexed_ledger: 0,
+            }],
+            true,
+        )?;
+
+        storage.save_leaf_inserted_events_batch(&vec![LeafInsertedEvent {
+            id: "evt-ins".to_string(),
+            key: field(1),
+            value: field(1),
+            root: field(7),
         }])?;
-        storage.reconcile_nullifiers(100)?;
+        // The deletion is ingested but not processed yet.
+        assert!(matches!(
+            storage.check_asp_non_membership_precondition("CNONMEM", &field(0), 30)?,
+            Some(AspMembershipSync::SyncRequired(Some(20)))
+        ));
 
-        // The unspent-only lookup rejects it, but the spent-tolerant lookup returns it.
+        storage.save_leaf_deleted_events_batch(&vec![LeafDeletedEvent {
+            id: "evt-del".to_string(),
+            key: field(1),
+            root: field(0),
+        }])?;
         assert!(
             storage
-                .get_unspent_user_note_by_commitment("CPOOL", "GTESTACCOUNT", &commitment)?
-                .is_none(),
-            "sanity: note is spent"
+                .check_asp_non_membership_precondition("CNONMEM", &field(0), 30)?
+                .is_none()
         );
-
-        let result = storage.get_user_note_by_commitment("CPOOL", "GTESTACCOUNT", &commitment)?;
-        assert!(result.is_some(), "spent note should still be returned");
-        let (got_amount, got_blinding, got_leaf_index) = result.expect("just checked is_some");
-        assert_eq!(got_amount, amount);
-        assert_eq!(got_blinding, blinding);
-        assert_eq!(got_leaf_index, 3);
+        assert!(
+            storage
+                .check_asp_non_membership_precondition("CNONMEM", &field(9), 30)
+                .is_err()
+        );
+        assert_eq!(
+            storage.get_asp_non_membership_leaf_ops_ordered("CNONMEM")?,
+            vec![
+                AspNonMembershipLeafOp::Insert {
+                    key: field(1),
+                    value: field(1),
+                    root: field(7),
+                },
+                AspNonMembershipLeafOp::Delete {
+                    key: field(1),
+                    root: field(0),
+                },
+            ]
+        );
+        assert!(storage.get_unprocessed_events(10)?.is_empty());
 
         Ok(())
     }
 
     #[test]
-    fn get_pool_commitment_leaves_ordered_returns_ordered_leaves() -> Result<()> {
+    fn pool_gvk_ciphertexts_are_stored_with_their_notes() -> Result<()> {
         let mut storage = Storage::connect_in_memory()?;
-
-        let leaf0 = Field::try_from_le_bytes([0u8; 32])?;
-        let leaf1 = Field::try_from_le_bytes([1u8; 32])?;
-        let leaf2 = Field::try_from_le_bytes([2u8; 32])?;
-
+        let field = |v: u8| {
+            let mut le = [0u8; 32];
+            le[0] = v;
+            Field::try_from_le_bytes(le).expect("field")
+        };
+        let ciphertext = |v: u8| GlobalViewKeyCiphertext {
+            r: BabyJubJubPoint {
+                x: field(v),
+                y: field(v.wrapping_add(1)),
+            },
+            c1: field(v.wrapping_add(2)),
+            c2: field(v.wrapping_add(3)),
+            c3: field(v.wrapping_add(4)),
+        };
+        let event = |id: &str, contract_id: &str| ContractEvent {
+            id: id.to_string(),
+            ledger: 10,
+            contract_id: contract_id.to_string(),
+            topics: vec!["dummy".to_string()],
+            value: "dummy".to_string(),
+        };
         storage.save_events_batch(&ContractsEventData {
+            cursor: "c1".to_string(),
+            latest_ledger: 10,
             events: vec![
-                dummy_event("evt-0"),
-                dummy_event("evt-1"),
-                dummy_event("evt-2"),
+                event("evt-0-0", "CPOOLGVK"),
+                event("evt-0-1", "CPOOLGVK"),
+                event("evt-0-2", "CPOOLGVK"),
+                event("evt-0-3", "CPOOLGVK"),
+                event("evt-1-0", "CPOOL"),
             ],
-            cursor: "cur".to_string(),
-            latest_ledger: 1,
         })?;
+
+        storage.save_nullifier_events_batch(&vec![
+            NewNullifierEvent {
+                id: "evt-0-0".to_string(),
+                nullifier: field(1),
+                gvk_ciphertext: Some(ciphertext(10)),
+            },
+            // View-only pools do not encrypt input notes.
+            NewNullifierEvent {
+                id: "evt-0-1".to_string(),
+                nullifier: field(2),
+                gvk_ciphertext: None,
+            },
+        ])?;
         storage.save_commitment_events_batch(&vec![
             NewCommitmentEvent {
-                id: "evt-0".to_string(),
-                commitment: leaf0,
+                id: "evt-0-2".to_string(),
+                commitment: field(3),
                 index: 0,
                 encrypted_output: vec![],
+                gvk_ciphertext: Some(ciphertext(20)),
             },
             NewCommitmentEvent {
-                id: "evt-1".to_string(),
-                commitment: leaf1,
-                index: 1,
-                encrypted_output: vec![],
-            },
-            NewCommitmentEvent {
-                id: "evt-2".to_string(),
-                commitment: leaf2,
-                index: 2,
+                id: "evt-1-0".to_string(),
+                commitment: field(4),
+                index: 0,
                 encrypted_output: vec![],
+                gvk_ciphertext: None,
             },
         ])?;
 
-        let leaves = storage.get_pool_commitment_leaves_ordered("CPOOL")?;
-        assert_eq!(leaves.len(), 3);
-        assert_eq!(leaves[0], leaf0);
-        assert_eq!(leaves[1], leaf1);
-        assert_eq!(leaves[2], leaf2);
+        assert_eq!(
+            storage.get_pool_gvk_ciphertexts("CPOOLGVK")?,
+            vec![
+                PoolGvkCiphertextRow {
+                    event_id: "evt-0-0".to_string(),
+                    ledger: 10,
+                    note: GvkNoteRef::Input {
+                        nullifier: field(1)
+                    },
+                    ciphertext: ciphertext(10),
+                },
+                PoolGvkCiphertextRow {
+                    event_id: "evt-0-2".to_string(),
+                    ledger: 10,
+                    note: GvkNoteRef::Output {
+                        commitment: field(3),
+                        leaf_index: 0,
+                    },
+                    ciphertext: ciphertext(20),
+                },
+            ]
+        );
+        assert!(storage.get_pool_gvk_ciphertexts("CPOOL")?.is_empty());
 
         Ok(())
     }
 
     #[test]
-    fn get_pool_commitment_leaves_ordered_detects_gaps() -> Result<()> {
+    fn transaction_plans_round_trip_until_finished() -> Result<()> {
         let mut storage = Storage::connect_in_memory()?;
+        let mut le = [0u8; 32];
+        le[0] = 7;
+        let carry = SpendableNote {
+            commitment: Field::try_from_le_bytes(le)?,
+            amount: NoteAmount::from(40u128),
+            created_at_ledger: None,
+        };
+        let mut plan = StoredPlan {
+            id: None,
+            pool_contract_id: "CPOOL".to_string(),
+            user_address: "GTESTACCOUNT".to_string(),
+            status: PlanStatus::Active,
+            tx_count: 3,
+            state: "{\"step\":0}".to_string(),
+            confirmed: vec![],
+            pending_notes: vec![],
+            in_flight: Some(InFlightStep {
+                tx_hash: "hash-0".to_string(),
+                state: "{\"step\":1}".to_string(),
+            }),
+        };
+        let id = storage.save_plan(&plan)?;
+        plan.id = Some(id);
 
-        let leaf0 = Field::try_from_le_bytes([0u8; 32])?;
-        let leaf2 = Field::try_from_le_bytes([2u8; 32])?;
+        // Other accounts and pools do not see the plan.
+        assert!(storage.list_active_plans("CPOOL", "GOTHER")?.is_empty());
+        assert!(
+            storage
+                .list_active_plans("COTHER", "GTESTACCOUNT")?
+                .is_empty()
+        );
+        assert_eq!(
+            storage.list_active_plans("CPOOL", "GTESTACCOUNT")?,
+            vec![plan.clone()]
+        );
 
-        storage.save_events_batch(&ContractsEventData {
-            events: vec![dummy_event("evt-0"), dummy_event("evt-2")],
-            cursor: "cur".to_string(),
-            latest_ledger: 1,
-        })?;
-        storage.save_commitment_events_batch(&vec![
-            NewCommitmentEvent {
-                id: "evt-0".to_string(),
-                commitment: leaf0,
-                index: 0,
-                encrypted_output: vec![],
-            },
-            NewCommitmentEvent {
-                id: "evt-2".to_string(),
-                commitment: leaf2,
-                index: 2,
-                encrypted_output: vec![],
-            },
-        ])?;
+        plan.state = "{\"step\":1}".to_string();
+        plan.confirmed = vec!["hash-0".to_string()];
+        plan.pending_notes = vec![carry];
+        plan.in_flight = None;
+        assert_eq!(storage.save_plan(&plan)?, id);
+        assert_eq!(
+            storage.list_active_plans("CPOOL", "GTESTACCOUNT")?,
+            vec![plan.clone()]
+        );
 
-        let err = storage
-            .get_pool_commitment_leaves_ordered("CPOOL")
-            .expect_err("gap should error");
-        assert!(err.to_string().contains("gap/out-of-order"));
+        plan.status = PlanStatus::Abandoned;
+        storage.save_plan(&plan)?;
+        assert!(
+            storage
+                .list_active_plans("CPOOL", "GTESTACCOUNT")?
+                .is_empty()
+        );
+
+        plan.id = Some(id.saturating_add(1));
+        assert!(storage.save_plan(&plan).is_err());
 
         Ok(())
     }
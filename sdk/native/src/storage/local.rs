@@ -6,7 +6,8 @@ use crate::{
     state::{SqliteStorage, StoredUserKeys},
     types::{
         ContractConfig, ContractsEventData, EncryptionPublicKey, Field, NotePublicKey,
-        OperationalFeedItem, PortfolioBalance, RecipientLookup, SyncMetadata, UserNoteSummary,
+        OperationalFeedItem, Passphrase, PortfolioBalance, RecipientLookup, SentNoteSummary,
+        StoredPlan, SyncMetadata, UserNoteSummary, ViewingKey,
     },
     zk::flows::TransactParams,
 };
@@ -14,7 +15,7 @@ use crate::{
 use super::{
     Storage, map_build_params, map_user_keys, operational_feed_from_storage,
     pool_notes_from_storage, portfolio_balances_from_storage, recipient_lookup_from_storage,
-    spendable_notes_from_storage, user_notes_from_storage,
+    sent_notes_from_storage, spendable_notes_from_storage, user_notes_from_storage,
 };
 use crate::{
     core::process_local_state,
@@ -47,6 +48,33 @@ impl LocalStorage {
     pub fn storage_mut(&self) -> std::cell::RefMut<'_, SqliteStorage> {
         self.db.borrow_mut()
     }
+
+    /// Unlocks the wallet's secret keys, for this handle and every handle
+    /// forked from it. The first unlock sets the passphrase and seals any
+    /// keys stored in plaintext.
+    pub fn unlock(&self, passphrase: &Passphrase) -> Result<(), Error> {
+        self.storage_mut()
+            .unlock(passphrase)
+            .map_err(|e| Error::Other(format!("unlock storage: {e:#}")))
+    }
+
+    /// Forgets the vault key. Key reads fail and note scanning pauses until
+    /// the next [`LocalStorage::unlock`].
+    pub fn lock(&self) {
+        self.storage().lock();
+    }
+
+    pub fn is_locked(&self) -> Result<bool, Error> {
+        self.storage()
+            .is_locked()
+            .map_err(|e| Error::Other(e.to_string()))
+    }
+
+    pub fn change_passphrase(&self, current: &Passphrase, new: &Passphrase) -> Result<(), Error> {
+        self.storage_mut()
+            .change_passphrase(current, new)
+            .map_err(|e| Error::Other(format!("change passphrase: {e:#}")))
+    }
 }
 
 #[async_trait::async_trait(?Send)]
@@ -72,8 +100,9 @@ impl ContractDataStorage for LocalStorage {
 #[async_trait::async_trait(?Send)]
 impl Storage for LocalStorage {
     fn fork(&self) -> Result<Self, Error> {
-        let db = SqliteStorage::connect_file(self.path.as_path())
+        let mut db = SqliteStorage::connect_file(self.path.as_path())
             .map_err(|e| Error::Other(format!("fork storage: {e:#}")))?;
+        db.share_key_vault(self.storage().key_vault());
         Ok(Self {
             path: self.path.clone(),
             db: RefCell::new(db),
@@ -116,6 +145,14 @@ impl Storage for LocalStorage {
         user_notes_from_storage(&self.storage(), user_address, limit)
     }
 
+    async fn list_sent_notes(
+        &self,
+        user_address: &str,
+        limit: u32,
+    ) -> Result<Vec<SentNoteSummary>, Error> {
+        sent_notes_from_storage(&self.storage(), user_address, limit)
+    }
+
     async fn operational_feed(
         &self,
         limit: u32,
@@ -133,7 +170,10 @@ impl Storage for LocalStorage {
     }
 
     async fn build_transact_params(&self, req: &TransactRequest) -> Result<TransactParams, Error> {
-        map_build_params(crate::transact::build_transact_params(&self.storage(), req))
+        map_build_params(crate::transact::build_transact_params(
+            &mut self.storage_mut(),
+            req,
+        ))
     }
 
     async fn build_disclosure_inputs(
@@ -141,7 +181,7 @@ impl Storage for LocalStorage {
         req: &DisclosureInputsRequest,
     ) -> Result<Vec<DisclosureInputs>, Error> {
         map_build_disclosure_inputs(crate::disclosure::build_disclosure_inputs(
-            &self.storage(),
+            &mut self.storage_mut(),
             req,
         ))
     }
@@ -173,13 +213,68 @@ impl Storage for LocalStorage {
             .map_err(|e| Error::Other(format!("lookup recipient: {e:#}")))?
             .ok_or_else(|| {
                 Error::Other(format!(
-                    "recipient {address} not found in the public key registry; \
-                     they must register keys on-chain"
+                    "recipient {address} has no active key in the public key registry; \
+                     they must register (or re-register revoked) keys on-chain"
                 ))
             })?;
         Ok((entry.note_key, entry.encryption_key))
     }
 
+    async fn export_backup(
+        &self,
+        user_address: &str,
+        passphrase: &Passphrase,
+    ) -> Result<String, Error> {
+        self.storage()
+            .export_backup(user_address, passphrase)
+            .map_err(|e| Error::Other(format!("export backup: {e:#}")))
+    }
+
+    async fn import_backup(
+        &self,
+        user_address: &str,
+        backup: &str,
+        passphrase: &Passphrase,
+    ) -> Result<(), Error> {
+        self.storage_mut()
+            .import_backup(user_address, backup, passphrase)
+            .map_err(|e| Error::Other(format!("import backup: {e:#}")))
+    }
+
+    async fn ensure_sub_account(&self, user_address: &str, index: u32) -> Result<String, Error> {
+        self.storage_mut()
+            .ensure_sub_account_keys(user_address, index)
+            .map_err(|e| Error::Other(format!("derive sub-account {index}: {e:#}")))
+    }
+
+    async fn export_viewing_key(&self, user_address: &str) -> Result<ViewingKey, Error> {
+        self.storage()
+            .export_viewing_key(user_address)
+            .map_err(|e| Error::Other(format!("export viewing key: {e:#}")))
+    }
+
+    async fn import_viewing_key(&self, viewing_key: &ViewingKey) -> Result<(), Error> {
+        self.storage_mut()
+            .save_viewing_key(viewing_key)
+            .map_err(|e| Error::Other(format!("import viewing key: {e:#}")))
+    }
+
+    async fn save_plan(&self, plan: &StoredPlan) -> Result<i64, Error> {
+        self.storage_mut()
+            .save_plan(plan)
+            .map_err(|e| Error::Other(format!("save transaction plan: {e:#}")))
+    }
+
+    async fn active_plans(
+        &self,
+        pool_contract_id: &str,
+        user_address: &str,
+    ) -> Result<Vec<StoredPlan>, Error> {
+        self.storage()
+            .list_active_plans(pool_contract_id, user_address)
+            .map_err(|e| Error::Other(format!("load transaction plans: {e:#}")))
+    }
+
     async fn process_pending_state(&self) -> Result<(), Error> {
         process_local_state(&mut self.storage_mut())
     }
@@ -4,8 +4,9 @@ use crate::{
     planner::SpendableNote,
     state::{SqliteStorage, StoredUserKeys},
     types::{
-        ContractConfig, EncryptionPublicKey, Field, NotePublicKey, OperationalFeedItem,
-        PortfolioBalance, RecipientLookup, UserNoteSummary,
+        ContractConfig, EncryptionPublicKey, Field, NotePublicKey, OperationalFeedItem, Passphrase,
+        PortfolioBalance, RecipientLookup, SentNoteSummary, StoredPlan, UserNoteSummary,
+        ViewingKey,
     },
     zk::flows::TransactParams,
 };
@@ -33,14 +34,21 @@ pub(crate) fn map_user_keys(
     storage: &SqliteStorage,
     user_address: &str,
 ) -> Result<StoredUserKeys, Error> {
-    storage
+    if let Some(keys) = storage
         .get_user_keys(user_address)
         .map_err(|e| Error::Other(e.to_string()))?
-        .ok_or_else(|| {
-            Error::Other(format!(
-                "address {user_address} should generate privacy keys and ASP secret first"
-            ))
-        })
+    {
+        return Ok(keys);
+    }
+    if storage
+        .is_watch_only(user_address)
+        .map_err(|e| Error::Other(e.to_string()))?
+    {
+        return Err(Error::WatchOnly(user_address.to_string()));
+    }
+    Err(Error::Other(format!(
+        "address {user_address} should generate privacy keys and ASP secret first"
+    )))
 }
 
 pub(crate) fn spendable_notes_from_storage(
@@ -57,6 +65,7 @@ pub(crate) fn spendable_notes_from_storage(
                 .map(|n| SpendableNote {
                     commitment: n.id,
                     amount: n.amount,
+                    created_at_ledger: Some(n.created_at_ledger),
                 })
                 .collect()
         })
@@ -92,6 +101,16 @@ pub(crate) fn user_notes_from_storage(
         .map_err(|e| Error::Other(e.to_string()))
 }
 
+pub(crate) fn sent_notes_from_storage(
+    storage: &SqliteStorage,
+    user_address: &str,
+    limit: u32,
+) -> Result<Vec<SentNoteSummary>, Error> {
+    storage
+        .list_sent_notes(user_address, limit)
+        .map_err(|e| Error::Other(e.to_string()))
+}
+
 pub(crate) fn operational_feed_from_storage(
     storage: &SqliteStorage,
     limit: u32,
@@ -146,6 +165,12 @@ pub trait Storage: crate::chain::ContractDataStorage {
         limit: u32,
     ) -> Result<Vec<UserNoteSummary>, Error>;
 
+    async fn list_sent_notes(
+        &self,
+        user_address: &str,
+        limit: u32,
+    ) -> Result<Vec<SentNoteSummary>, Error>;
+
     async fn operational_feed(
         &self,
         limit: u32,
@@ -184,6 +209,47 @@ pub trait Storage: crate::chain::ContractDataStorage {
         public_key_registry_contract_id: &str,
     ) -> Result<(NotePublicKey, EncryptionPublicKey), Error>;
 
+    /// Encrypted backup of `user_address`'s wallet, sealed under `passphrase`
+    /// (a [`crate::types::WalletBackupFile`] as JSON).
+    async fn export_backup(
+        &self,
+        user_address: &str,
+        passphrase: &Passphrase,
+    ) -> Result<String, Error>;
+
+    /// Restore a backup made by [`Storage::export_backup`] for `user_address`.
+    async fn import_backup(
+        &self,
+        user_address: &str,
+        backup: &str,
+        passphrase: &Passphrase,
+    ) -> Result<(), Error>;
+
+    /// Derive and store the keys of sub-account `index` of `user_address` if
+    /// missing; returns its storage key
+    /// ([`crate::types::sub_account_address`]).
+    async fn ensure_sub_account(&self, user_address: &str, index: u32) -> Result<String, Error>;
+
+    /// Viewing key of `user_address` (a [`ViewingKey`]), derived from its
+    /// keys or, for a watch-only account, the one it was imported from.
+    async fn export_viewing_key(&self, user_address: &str) -> Result<ViewingKey, Error>;
+
+    /// Store `viewing_key` as a watch-only account; the next note scan finds
+    /// its notes.
+    async fn import_viewing_key(&self, viewing_key: &ViewingKey) -> Result<(), Error>;
+
+    /// Persist a multi-step plan, inserting it when it has no id; returns its
+    /// id. See [`crate::PrivatePool::resume_plans`].
+    async fn save_plan(&self, plan: &StoredPlan) -> Result<i64, Error>;
+
+    /// Plans of `user_address` in `pool_contract_id` that are neither
+    /// completed nor abandoned, oldest first.
+    async fn active_plans(
+        &self,
+        pool_contract_id: &str,
+        user_address: &str,
+    ) -> Result<Vec<StoredPlan>, Error>;
+
     /// Finalize local processing after RPC ingest
     async fn process_pending_state(&self) -> Result<(), Error>;
 
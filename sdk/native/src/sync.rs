@@ -13,10 +13,21 @@ use crate::{
 };
 use futures::task::AtomicWaker;
 
-use crate::{Error, Handle, Storage, chain::RpcClient, sleep::sleep, types::TransactionResult};
+use crate::{
+    Error, Handle, Storage,
+    chain::RpcClient,
+    consolidation::{ConsolidationPolicy, Consolidator},
+    pool::PrivatePool,
+    sleep::sleep,
+    types::TransactionResult,
+};
 
 const CONFIRM_POLL_ATTEMPTS: u32 = 30;
+#[cfg(not(test))]
 const CONFIRM_POLL_INTERVAL_MS: u32 = 1_000;
+/// Tests of transactions that never confirm poll without waiting out 30s.
+#[cfg(test)]
+const CONFIRM_POLL_INTERVAL_MS: u32 = 1;
 const BACKGROUND_SYNC_INTERVAL_MS: u32 = 5_000;
 const BOOTNODE_CATCH_UP_MAX_FAILURES: u32 = 10;
 
@@ -194,6 +205,7 @@ pub struct BackgroundSync<S: Storage> {
     bootnode_url: Option<String>,
     kick: Handle<SyncKick>,
     stop: Handle<AtomicBool>,
+    consolidators: Vec<Consolidator<S>>,
 }
 
 impl<S: Storage> BackgroundSync<S> {
@@ -211,9 +223,23 @@ impl<S: Storage> BackgroundSync<S> {
             bootnode_url,
             kick,
             stop: Handle::new(AtomicBool::new(false)),
+            consolidators: Vec::new(),
         }
     }
 
+    /// Also consolidate `pool`'s notes while idle, per `policy`: after a
+    /// round that caught up, merge its two smallest notes if the policy's
+    /// wait has elapsed and it holds more than `policy.max_notes`.
+    ///
+    /// The pool's signer signs these transactions without asking, so only
+    /// use this with a local signer. A consolidation spends notes a plan
+    /// prepared at the same time may also use; that plan then fails and has
+    /// to be prepared again.
+    pub fn with_consolidation(mut self, pool: PrivatePool<S>, policy: ConsolidationPolicy) -> Self {
+        self.consolidators.push(Consolidator::new(pool, policy));
+        self
+    }
+
     /// Cloneable handle to stop this task (also wakes the idle wait).
     pub fn stop_handle(&self) -> BackgroundSyncStop {
         BackgroundSyncStop {
@@ -233,7 +259,10 @@ impl<S: Storage> BackgroundSync<S> {
     ///
     /// On a main RPC retention gap, syncs historical events via the optional
     /// bootnode until handoff, then resumes on the main RPC.
-    pub async fn run(self) -> Result<(), Error> {
+    ///
+    /// Pools added with [`Self::with_consolidation`] are consolidated after
+    /// rounds that caught up without error.
+    pub async fn run(mut self) -> Result<(), Error> {
         if self.is_stopped() {
             return Ok(());
         }
@@ -287,8 +316,16 @@ impl<S: Storage> BackgroundSync<S> {
                 tracing::info!("background sync stopped");
                 return Ok(());
             }
-            if let Err(e) = catch_up_loop(&indexer, &self.storage, Some(self.stop.as_ref())).await {
-                tracing::error!("background sync fetch failed: {e:#}");
+            match catch_up_loop(&indexer, &self.storage, Some(self.stop.as_ref())).await {
+                Ok(()) => {
+                    for consolidator in &mut self.consolidators {
+                        if self.stop.load(Ordering::Acquire) {
+                            break;
+                        }
+                        consolidator.on_idle().await;
+                    }
+                }
+                Err(e) => tracing::error!("background sync fetch failed: {e:#}"),
             }
             self.kick.wait_timeout(BACKGROUND_SYNC_INTERVAL_MS).await;
         }
@@ -524,7 +561,22 @@ pub(crate) async fn confirm_tx(
     hash: impl AsRef<str>,
 ) -> Result<TransactionResult, Error> {
     let hash = hash.as_ref();
+    match poll_tx_status(rpc, hash).await? {
+        TxConfirmStatus::Success => Ok(TransactionResult {
+            tx_hash: hash.to_string(),
+        }),
+        TxConfirmStatus::Failed { detail } => {
+            Err(Error::Other(format!("transaction failed{detail}")))
+        }
+        TxConfirmStatus::Pending => Err(Error::Other(format!(
+            "transaction confirmation timed out after 30s (hash: {hash})"
+        ))),
+    }
+}
 
+/// Poll a transaction's status until it succeeds or fails, for at most 30s;
+/// [`TxConfirmStatus::Pending`] if it is still pending or unknown by then.
+pub(crate) async fn poll_tx_status(rpc: &RpcClient, hash: &str) -> Result<TxConfirmStatus, Error> {
     for attempt in 1..=CONFIRM_POLL_ATTEMPTS {
         if attempt > 1 {
             sleep(CONFIRM_POLL_INTERVAL_MS).await;
@@ -533,26 +585,11 @@ pub(crate) async fn confirm_tx(
             .await
             .map_err(|e| Error::Other(format!("confirm transaction: {e:#}")))?
         {
-            TxConfirmStatus::Success => {
-                return Ok(TransactionResult {
-                    tx_hash: hash.to_string(),
-                });
-            }
-            TxConfirmStatus::Failed { detail } => {
-                return Err(Error::Other(format!("transaction failed{detail}")));
-            }
-            TxConfirmStatus::Pending if attempt == CONFIRM_POLL_ATTEMPTS => {
-                return Err(Error::Other(format!(
-                    "transaction confirmation timed out after 30s (hash: {hash})"
-                )));
-            }
             TxConfirmStatus::Pending => {}
+            status => return Ok(status),
         }
     }
-
-    Err(Error::Other(format!(
-        "transaction confirmation failed (hash: {hash})"
-    )))
+    Ok(TxConfirmStatus::Pending)
 }
 
 #[cfg(all(test, not(target_arch = "wasm32")))]
@@ -3,17 +3,19 @@
 use crate::{
     chain::{OnchainProofPublicInputs, PreparedSorobanTx},
     planner::Transact,
-    state::{SqliteStorage, StoredUserKeys},
+    state::{AspNonMembershipLeafOp, SqliteStorage, StoredUserKeys},
     types::{
-        AspMembershipProof, AspMembershipSync, AspNonMembershipProof, EncryptionKeyPair,
-        EncryptionPublicKey, ExtAmount, ExtData, Field, NoteAmount, NoteKeyPair, NotePrivateKey,
-        NotePublicKey, PolicyFlags, SMT_DEPTH, TransactChainContext,
+        AspMembershipProof, AspMembershipSync, AspNonMembershipProof, BabyJubJubPoint,
+        EncryptionKeyPair, EncryptionPublicKey, ExtAmount, ExtData, Field, GlobalViewKeyCiphertext,
+        GvkMode, NoteAmount, NoteKeyPair, NotePrivateKey, NotePublicKey, PolicyFlags, SMT_DEPTH,
+        TransactChainContext,
     },
     zk::{
         crypto::asp_membership_leaf,
         encryption::generate_random_blinding,
         flows::{N_OUTPUTS, TransactInputNote, TransactOutput, TransactParams},
         merkle::{MerklePrefixTree, MerklePrefixTreeBuilt, MerkleProof},
+        sparse_merkle::SparseMerkleTree,
     },
 };
 use anyhow::Result;
@@ -31,14 +33,22 @@ pub struct TransactRequest {
     pub aspmem_root: Field,
     pub aspmem_contract_id: String,
     pub aspmem_ledger: u32,
+    pub aspnonmem_root: Field,
+    pub aspnonmem_contract_id: String,
+    pub aspnonmem_ledger: u32,
     pub input_commitments: Vec<Field>,
     pub output_amounts: [NoteAmount; N_OUTPUTS],
     pub out_recipient_note_pubkeys: [Option<NotePublicKey>; N_OUTPUTS],
     pub out_recipient_encryption_pubkeys: [Option<EncryptionPublicKey>; N_OUTPUTS],
+    #[serde(default)]
+    pub out_memos: [Option<String>; N_OUTPUTS],
     pub smt_depth: u32,
     pub tree_depth: u32,
-    pub non_membership_proof: Option<AspNonMembershipProof>,
     pub policy_flags: PolicyFlags,
+    #[serde(default)]
+    pub gvk_mode: GvkMode,
+    #[serde(default)]
+    pub admin_view_key: Option<BabyJubJubPoint>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +61,12 @@ pub struct PreparedTxPublic {
     pub ext_data_hash_be: [u8; 32],
     pub asp_membership_root: Field,
     pub asp_non_membership_root: Field,
+    /// GVK ciphertexts of the input notes; empty outside traceable GVK pools.
+    #[serde(default)]
+    pub input_gvk_ciphertexts: Vec<GlobalViewKeyCiphertext>,
+    /// GVK ciphertexts of the output notes; empty for non-GVK pools.
+    #[serde(default)]
+    pub output_gvk_ciphertexts: Vec<GlobalViewKeyCiphertext>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,6 +89,8 @@ impl From<&PreparedTxPublic> for OnchainProofPublicInputs {
             ext_data_hash_be: p.ext_data_hash_be,
             asp_membership_root: p.asp_membership_root,
             asp_non_membership_root: p.asp_non_membership_root,
+            input_gvk_ciphertexts: p.input_gvk_ciphertexts.clone(),
+            output_gvk_ciphertexts: p.output_gvk_ciphertexts.clone(),
         }
     }
 }
@@ -98,19 +116,24 @@ pub fn transact_request_from_step(
         aspmem_root: chain.asp_membership_root,
         aspmem_contract_id: chain.asp_membership_contract_id.clone(),
         aspmem_ledger: chain.asp_membership_ledger,
+        aspnonmem_root: chain.asp_non_membership_root,
+        aspnonmem_contract_id: chain.asp_non_membership_contract_id.clone(),
+        aspnonmem_ledger: chain.asp_non_membership_ledger,
         input_commitments: step.input_commitments.clone(),
         output_amounts: step.output_amounts,
         out_recipient_note_pubkeys: step.out_recipient_note_pubkeys.clone(),
         out_recipient_encryption_pubkeys: step.out_recipient_encryption_pubkeys.clone(),
+        out_memos: step.out_memos.clone(),
         smt_depth: SMT_DEPTH,
         tree_depth: chain.pool_merkle_levels,
-        non_membership_proof: chain.non_membership_proof.clone(),
         policy_flags: chain.policy_flags,
+        gvk_mode: chain.gvk_mode,
+        admin_view_key: chain.admin_view_key,
     }
 }
 
 pub fn build_transact_params(
-    storage: &SqliteStorage,
+    storage: &mut SqliteStorage,
     req: &TransactRequest,
 ) -> Result<BuildTransactParams> {
     if req.input_commitments.len() > 2 {
@@ -137,6 +160,22 @@ pub fn build_transact_params(
         None
     };
 
+    let non_membership_proof = if req.policy_flags.requires_non_membership_proofs() {
+        match build_non_membership_proof(
+            storage,
+            &req.aspnonmem_contract_id,
+            &note_pubkey,
+            req.aspnonmem_root,
+            req.aspnonmem_ledger,
+            req.smt_depth,
+        )? {
+            Ok(proof) => Some(proof),
+            Err(status) => return Ok(BuildTransactParams::MembershipSync(status)),
+        }
+    } else {
+        None
+    };
+
     let pool_root = req
         .pool_root
         .ok_or_else(|| anyhow::anyhow!("missing pool_root"))?;
@@ -168,6 +207,7 @@ pub fn build_transact_params(
             blinding: generate_random_blinding()?,
             recipient_note_pubkey: note_pk,
             recipient_encryption_pubkey: enc_pk,
+            memo: req.out_memos[i].clone(),
         });
     }
 
@@ -180,10 +220,12 @@ pub fn build_transact_params(
         inputs,
         outputs,
         membership_proof,
-        non_membership_proof: req.non_membership_proof.clone(),
+        non_membership_proof,
         tree_depth: req.tree_depth,
         smt_depth: req.smt_depth,
         policy_flags: req.policy_flags,
+        gvk_mode: req.gvk_mode,
+        admin_view_key: req.admin_view_key,
     })))
 }
 
@@ -200,9 +242,15 @@ pub fn load_user_key_material(
             public: enc_pub, ..
         },
         membership_blinding,
-    } = storage.get_user_keys(user_address)?.ok_or_else(|| {
-        anyhow::anyhow!("address {user_address} should generate privacy keys and ASP secret first")
-    })?;
+    } = match storage.get_user_keys(user_address)? {
+        Some(keys) => keys,
+        None if storage.is_watch_only(user_address)? => {
+            anyhow::bail!("address {user_address} is watch-only: a viewing key cannot prove")
+        }
+        None => anyhow::bail!(
+            "address {user_address} should generate privacy keys and ASP secret first"
+        ),
+    };
 
     Ok((private, note_pub, enc_pub, membership_blinding))
 }
@@ -247,8 +295,113 @@ fn build_membership_proof(
     }))
 }
 
-fn build_pool_inputs(
+/// Builds the ASP non-membership (blocklist) proof from the locally indexed
+/// tree, so the note key is never sent to an RPC node.
+///
+/// - if `aspnonmem_root == 0`, returns a dummy "empty tree" proof padded to
+///   `smt_depth`
+/// - otherwise replays `asp_non_membership_leaves` into a sparse Merkle tree,
+///   checking every step against the on-chain root it produced, pads shorter
+///   sibling paths to `smt_depth`, and rejects deeper ones
+fn build_non_membership_proof(
     storage: &SqliteStorage,
+    aspnonmem_contract_id: &str,
+    note_pubkey: &NotePublicKey,
+    aspnonmem_root: Field,
+    aspnonmem_ledger: u32,
+    smt_depth: u32,
+) -> Result<Result<AspNonMembershipProof, AspMembershipSync>> {
+    let smt_depth = usize::try_from(smt_depth)?;
+    if smt_depth == 0 {
+        anyhow::bail!("smt_depth must be > 0");
+    }
+
+    // NotePublicKey bytes are little-endian field bytes (see
+    // prover::serialization).
+    let key = Field::try_from_le_bytes(*note_pubkey.as_ref())?;
+
+    // Empty tree case (root = 0): non-membership is trivially provable.
+    if aspnonmem_root.is_zero() {
+        return Ok(Ok(AspNonMembershipProof {
+            key,
+            old_key: Field::ZERO,
+            old_value: Field::ZERO,
+            is_old0: true,
+            siblings: vec![Field::ZERO; smt_depth],
+            root: Field::ZERO,
+        }));
+    }
+
+    if let Some(status) = storage.check_asp_non_membership_precondition(
+        aspnonmem_contract_id,
+        &aspnonmem_root,
+        aspnonmem_ledger,
+    )? {
+        return Ok(Err(status));
+    }
+
+    let ops = storage.get_asp_non_membership_leaf_ops_ordered(aspnonmem_contract_id)?;
+    let tree = replay_asp_non_membership_tree(&ops)?;
+    if tree.root() != aspnonmem_root {
+        anyhow::bail!("local ASP non-membership tree root does not match the on-chain root");
+    }
+
+    non_membership_proof_from_tree(&tree, key, smt_depth).map(Ok)
+}
+
+fn replay_asp_non_membership_tree(ops: &[AspNonMembershipLeafOp]) -> Result<SparseMerkleTree> {
+    let mut tree = SparseMerkleTree::new();
+    for op in ops {
+        let (result, root) = match op {
+            AspNonMembershipLeafOp::Insert { key, value, root } => (tree.insert(key, value), root),
+            AspNonMembershipLeafOp::Update { key, value, root } => (tree.update(key, value), root),
+            AspNonMembershipLeafOp::Delete { key, root } => (tree.delete(key), root),
+        };
+        result.map_err(|e| anyhow::anyhow!("replay ASP non-membership tree: {e}"))?;
+        if tree.root() != *root {
+            anyhow::bail!("replay ASP non-membership tree: root diverged from the on-chain event");
+        }
+    }
+    Ok(tree)
+}
+
+fn non_membership_proof_from_tree(
+    tree: &SparseMerkleTree,
+    key: Field,
+    smt_depth: usize,
+) -> Result<AspNonMembershipProof> {
+    let found = tree
+        .find(&key)
+        .map_err(|e| anyhow::anyhow!("ASP non-membership lookup: {e}"))?;
+
+    if found.found {
+        anyhow::bail!("User note key exists in non-membership tree (user is blocklisted)");
+    }
+
+    let mut siblings = found.siblings;
+    if siblings.len() > smt_depth {
+        anyhow::bail!(
+            "ASP non-membership proof has {} sibling(s), but the configured policy SMT depth is {}",
+            siblings.len(),
+            smt_depth
+        );
+    }
+
+    let padding = smt_depth.saturating_sub(siblings.len());
+    siblings.extend(core::iter::repeat_n(Field::ZERO, padding));
+
+    Ok(AspNonMembershipProof {
+        key,
+        old_key: found.not_found_key,
+        old_value: found.not_found_value,
+        is_old0: found.is_old0,
+        siblings,
+        root: tree.root(),
+    })
+}
+
+fn build_pool_inputs(
+    storage: &mut SqliteStorage,
     user_address: &str,
     pool_address: &str,
     pool_next_index: u32,
@@ -260,20 +413,9 @@ fn build_pool_inputs(
         return Ok(Ok(Vec::new()));
     }
 
-    let tree = match build_validated_pool_tree(
-        storage,
-        pool_address,
-        pool_next_index,
-        tree_depth,
-        expected_pool_root,
-    )? {
-        Ok(tree) => tree,
-        Err(status) => return Ok(Err(status)),
-    };
-
-    let mut out = Vec::with_capacity(input_commitments.len());
+    let mut notes = Vec::with_capacity(input_commitments.len());
     for commitment in input_commitments {
-        let Some((amount, blinding, leaf_index)) =
+        let Some(note) =
             storage.get_unspent_user_note_by_commitment(pool_address, user_address, commitment)?
         else {
             tracing::info!(
@@ -282,13 +424,84 @@ fn build_pool_inputs(
             );
             return Ok(Err(AspMembershipSync::SyncRequired(None)));
         };
+        notes.push(note);
+    }
 
-        out.push(build_pool_input_note(amount, blinding, leaf_index, &tree)?);
+    let leaf_indices: Vec<u32> = notes.iter().map(|(_, _, leaf_index)| *leaf_index).collect();
+    let proofs = match build_pool_merkle_proofs(
+        storage,
+        pool_address,
+        pool_next_index,
+        tree_depth,
+        expected_pool_root,
+        &leaf_indices,
+    )? {
+        Ok(proofs) => proofs,
+        Err(status) => return Ok(Err(status)),
+    };
+
+    Ok(Ok(notes
+        .into_iter()
+        .zip(proofs)
+        .map(|((amount, blinding, _), proof)| build_pool_input_note(amount, blinding, proof))
+        .collect()))
+}
+
+/// Pool Merkle proofs of `leaf_indices` against `expected_pool_root`, built
+/// from the persisted commitment-tree frontier in `O(depth)` per owned note.
+///
+/// Returns `SyncRequired` while local commitments lag behind
+/// `pool_next_index`, or run ahead of it without contradicting the recorded
+/// root history. A frontier that disagrees with the chain at the same leaf
+/// count is rebuilt from the stored leaves once before giving up.
+pub fn build_pool_merkle_proofs(
+    storage: &mut SqliteStorage,
+    pool_address: &str,
+    pool_next_index: u32,
+    tree_depth: u32,
+    expected_pool_root: Field,
+    leaf_indices: &[u32],
+) -> Result<Result<Vec<MerkleProof>, AspMembershipSync>> {
+    let next_index = u64::from(pool_next_index);
+    let (mut leaf_count, mut root) = storage.sync_pool_frontier(pool_address, tree_depth)?;
+    if leaf_count == next_index && root != expected_pool_root {
+        tracing::warn!("local pool frontier disagrees with the on-chain root; rebuilding it");
+        (leaf_count, root) = storage.rebuild_pool_frontier(pool_address, tree_depth)?;
     }
 
-    Ok(Ok(out))
+    if leaf_count != next_index {
+        if leaf_count > next_index
+            && storage
+                .pool_merkle_root_at(pool_address, next_index)?
+                .is_some_and(|known| known != expected_pool_root)
+        {
+            anyhow::bail!(
+                "pool root mismatch: local root history disagrees with on-chain root at {next_index} leaves"
+            );
+        }
+        tracing::info!(
+            "pool commitments not synced: local={}, chain={}",
+            leaf_count,
+            pool_next_index
+        );
+        return Ok(Err(AspMembershipSync::SyncRequired(None)));
+    }
+    if root != expected_pool_root {
+        anyhow::bail!("pool root mismatch: local computed root does not match on-chain root");
+    }
+
+    let proofs = leaf_indices
+        .iter()
+        .map(|leaf_index| storage.pool_merkle_proof(pool_address, *leaf_index))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Ok(proofs))
 }
 
+/// Rebuild the whole pool tree from every stored leaf and check it against
+/// `expected_pool_root`.
+///
+/// Costs `O(leaves)` per call; proving paths use
+/// [`build_pool_merkle_proofs`] instead.
 pub fn build_validated_pool_tree(
     storage: &SqliteStorage,
     pool_address: &str,
@@ -319,19 +532,84 @@ pub fn build_validated_pool_tree(
 fn build_pool_input_note(
     amount: crate::types::NoteAmount,
     blinding: Field,
-    leaf_index: u32,
-    tree: &MerklePrefixTreeBuilt,
-) -> Result<TransactInputNote> {
+    proof: MerkleProof,
+) -> TransactInputNote {
     let MerkleProof {
         path_elements,
         path_indices,
         ..
-    } = tree.proof(leaf_index)?;
+    } = proof;
 
-    Ok(TransactInputNote {
+    TransactInputNote {
         amount,
         blinding,
         merkle_path_elements: path_elements,
         merkle_path_indices: path_indices,
-    })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::U256;
+
+    fn field(v: u64) -> Field {
+        Field(U256::from(v))
+    }
+
+    fn replayed_ops(keys: &[u64]) -> Vec<AspNonMembershipLeafOp> {
+        let mut tree = SparseMerkleTree::new();
+        keys.iter()
+            .map(|k| {
+                tree.insert(&field(*k), &field(1)).expect("insert");
+                AspNonMembershipLeafOp::Insert {
+                    key: field(*k),
+                    value: field(1),
+                    root: tree.root(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn non_membership_proof_rejects_over_depth_path() {
+        let tree = replay_asp_non_membership_tree(&replayed_ops(&[1, 2, 3, 5, 9])).expect("replay");
+        let err = non_membership_proof_from_tree(&tree, field(17), 1)
+            .expect_err("over-depth path must be rejected");
+
+        assert!(
+            err.to_string()
+                .contains("but the configured policy SMT depth is 1"),
+            "{err:#}"
+        );
+    }
+
+    #[test]
+    fn non_membership_proof_rejects_blocklisted_key() {
+        let tree = replay_asp_non_membership_tree(&replayed_ops(&[1, 2])).expect("replay");
+        let err = non_membership_proof_from_tree(&tree, field(2), SMT_DEPTH as usize)
+            .expect_err("blocklisted key");
+        assert!(err.to_string().contains("blocklisted"), "{err:#}");
+    }
+
+    #[test]
+    fn non_membership_proof_is_padded_to_depth() {
+        let tree = replay_asp_non_membership_tree(&replayed_ops(&[1, 2])).expect("replay");
+        let proof =
+            non_membership_proof_from_tree(&tree, field(3), SMT_DEPTH as usize).expect("proof");
+        assert_eq!(proof.siblings.len(), SMT_DEPTH as usize);
+        assert_eq!(proof.root, tree.root());
+        assert_eq!(proof.key, field(3));
+    }
+
+    #[test]
+    fn replay_rejects_a_root_that_diverges_from_the_event() {
+        let mut ops = replayed_ops(&[1, 2]);
+        ops.push(AspNonMembershipLeafOp::Delete {
+            key: field(1),
+            root: field(42),
+        });
+        let err = replay_asp_non_membership_tree(&ops).expect_err("diverged root");
+        assert!(err.to_string().contains("diverged"), "{err:#}");
+    }
 }
@@ -0,0 +1,180 @@
+//! Portable wallet backup types.
+//!
+//! A backup is a JSON [`WalletBackupFile`] whose `sealed` payload is a
+//! passphrase-encrypted [`WalletBackup`]. The native SDK and `sdk/web`
+//! produce and consume the same file, so a wallet can move between the CLI
+//! and the browser.
+
+use super::{ContractEvent, EncryptionKeyPair, Field, NoteAmount, NoteKeyPair, UserOperation};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// `format` of every [`WalletBackupFile`].
+pub const WALLET_BACKUP_FORMAT: &str = "spp-wallet-backup";
+
+/// Current [`WalletBackupFile`] schema version.
+pub const WALLET_BACKUP_VERSION: u32 = 1;
+
+/// The only supported [`WalletBackupKdf::algorithm`].
+pub const WALLET_BACKUP_KDF_ARGON2ID: &str = "argon2id";
+
+/// An encrypted wallet backup as written to disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WalletBackupFile {
+    /// Always [`WALLET_BACKUP_FORMAT`].
+    pub format: String,
+    /// Schema version; [`WALLET_BACKUP_VERSION`] for files written by this
+    /// crate.
+    pub version: u32,
+    /// Derivation of the backup key from the passphrase.
+    pub kdf: WalletBackupKdf,
+    /// Base64 of the sealed [`WalletBackup`] JSON:
+    /// `version (1) || nonce (24) || ciphertext || tag (16)`.
+    pub sealed: String,
+}
+
+impl WalletBackupFile {
+    /// Checks the format marker, schema version and KDF algorithm.
+    pub fn validate(&self) -> Result<()> {
+        if self.format != WALLET_BACKUP_FORMAT {
+            return Err(anyhow!("not a wallet backup (format {:?})", self.format));
+        }
+        if self.version != WALLET_BACKUP_VERSION {
+            return Err(anyhow!(
+                "unsupported wallet backup version {} (expected {WALLET_BACKUP_VERSION})",
+                self.version
+            ));
+        }
+        if self.kdf.algorithm != WALLET_BACKUP_KDF_ARGON2ID {
+            return Err(anyhow!(
+                "unsupported wallet backup KDF {:?}",
+                self.kdf.algorithm
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Argon2id parameters of a [`WalletBackupFile`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WalletBackupKdf {
+    /// Always [`WALLET_BACKUP_KDF_ARGON2ID`].
+    pub algorithm: String,
+    /// Base64 of the 16-byte salt.
+    pub salt: String,
+    /// Memory cost in KiB.
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+/// Decrypted contents of a [`WalletBackupFile`] for one account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WalletBackup {
+    /// Stellar address of the account.
+    pub address: String,
+    /// The account's latest privacy keys.
+    pub keys: WalletBackupKeys,
+    /// Every note of the account, spent or not.
+    pub notes: Vec<WalletBackupNote>,
+    /// Operation history across all pools, oldest first.
+    pub operations: Vec<WalletBackupOperation>,
+    /// All app settings (contacts, bootnode, explorer, ...), by key.
+    pub settings: BTreeMap<String, serde_json::Value>,
+    /// Sub-accounts opened on the account, by index. Their keys derive from
+    /// [`Self::keys`], so only notes and operations are stored.
+    #[serde(default)]
+    pub sub_accounts: Vec<WalletBackupSubAccount>,
+}
+
+/// Notes and operations of one sub-account of a [`WalletBackup`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WalletBackupSubAccount {
+    /// Sub-account index, never 0.
+    pub index: u32,
+    pub notes: Vec<WalletBackupNote>,
+    pub operations: Vec<WalletBackupOperation>,
+}
+
+/// Privacy keys of a [`WalletBackup`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WalletBackupKeys {
+    pub note_keypair: NoteKeyPair,
+    pub encryption_keypair: EncryptionKeyPair,
+    pub membership_blinding: Field,
+}
+
+/// One note of a [`WalletBackup`]: its opening and the raw events that
+/// created and, if spent, nullified it.
+///
+/// Restoring the raw events lets the local processor rebuild the note even
+/// after they have left the RPC retention window.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WalletBackupNote {
+    pub pool_contract_id: String,
+    pub commitment: Field,
+    pub leaf_index: u32,
+    pub amount: NoteAmount,
+    pub blinding: Field,
+    pub commitment_event: ContractEvent,
+    pub nullifier_event: Option<ContractEvent>,
+}
+
+/// One recorded operation of a [`WalletBackup`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WalletBackupOperation {
+    pub pool_contract_id: String,
+    pub operation: UserOperation,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file() -> WalletBackupFile {
+        WalletBackupFile {
+            format: WALLET_BACKUP_FORMAT.to_string(),
+            version: WALLET_BACKUP_VERSION,
+            kdf: WalletBackupKdf {
+                algorithm: WALLET_BACKUP_KDF_ARGON2ID.to_string(),
+                salt: String::new(),
+                m_cost: 64,
+                t_cost: 3,
+                p_cost: 1,
+            },
+            sealed: String::new(),
+        }
+    }
+
+    #[test]
+    fn wallet_backup_file_validation() {
+        assert!(file().validate().is_ok());
+
+        let mut other_format = file();
+        other_format.format = "something-else".to_string();
+        assert!(other_format.validate().is_err());
+
+        let mut future = file();
+        future.version = 2;
+        assert!(future.validate().is_err());
+
+        let mut scrypt = file();
+        scrypt.kdf.algorithm = "scrypt".to_string();
+        assert!(scrypt.validate().is_err());
+    }
+
+    #[test]
+    fn wallet_backup_file_uses_camel_case() {
+        let json = serde_json::to_value(file()).expect("serialize backup file");
+        assert_eq!(json["kdf"]["mCost"], 64);
+        assert_eq!(json["format"], WALLET_BACKUP_FORMAT);
+    }
+}
@@ -1,4 +1,7 @@
-use super::{BabyJubJubPoint, EncryptionPublicKey, ExtAmount, Field, NotePublicKey, PolicyFlags};
+use super::{
+    BabyJubJubPoint, EncryptionPublicKey, ExtAmount, Field, GlobalViewKeyCiphertext, GvkMode,
+    NotePublicKey, PolicyFlags,
+};
 use serde::{Deserialize, Serialize};
 
 /// Serde helpers for `[u8; 32]` as a `0x`-prefixed 64-hex string.
@@ -67,6 +70,23 @@ pub struct PoolInfo {
     /// Omitted from serialized output when absent, as for `admin_view_key`.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub gvk_mode: Option<u32>,
+    /// Sum of all deposits, from the pool's aggregate counters. Zero until
+    /// the first counted transaction.
+    #[serde(default)]
+    pub total_deposited: ExtAmount,
+    /// Sum of all withdrawals, from the pool's aggregate counters.
+    #[serde(default)]
+    pub total_withdrawn: ExtAmount,
+    /// Number of spent nullifiers, from the pool's aggregate counters.
+    #[serde(default)]
+    pub spent_nullifiers: u64,
+}
+
+impl PoolInfo {
+    /// Tokens currently held in shielded notes (`deposited - withdrawn`).
+    pub fn shielded_supply(&self) -> Option<ExtAmount> {
+        self.total_deposited.checked_sub(self.total_withdrawn)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -132,14 +152,19 @@ pub struct TransactChainContext {
     pub asp_membership_root: Field,
     pub asp_membership_contract_id: String,
     pub asp_membership_ledger: u32,
-    pub non_membership_proof: Option<AspNonMembershipProof>,
+    pub asp_non_membership_root: Field,
+    pub asp_non_membership_contract_id: String,
+    pub asp_non_membership_ledger: u32,
     pub policy_flags: PolicyFlags,
+    pub gvk_mode: GvkMode,
+    /// On-chain GVK authority key; `Some` iff `gvk_mode` is not
+    /// [`GvkMode::Off`].
+    pub admin_view_key: Option<BabyJubJubPoint>,
 }
 
 pub fn transact_chain_context_from_state(
     data: ContractsStateData,
     pool_contract_id: &str,
-    non_membership_proof: Option<AspNonMembershipProof>,
 ) -> anyhow::Result<TransactChainContext> {
     let pool = data
         .pools
@@ -153,6 +178,7 @@ pub fn transact_chain_context_from_state(
         .merkle_next_index
         .parse::<u32>()
         .map_err(|e| anyhow::anyhow!("invalid pool merkle_next_index: {e}"))?;
+    let gvk_mode = GvkMode::from_on_chain_value(pool.gvk_mode)?;
 
     Ok(TransactChainContext {
         pool_root,
@@ -161,8 +187,12 @@ pub fn transact_chain_context_from_state(
         asp_membership_root: data.asp_membership.root,
         asp_membership_contract_id: data.asp_membership.contract_id,
         asp_membership_ledger: data.asp_membership.ledger,
-        non_membership_proof,
+        asp_non_membership_root: data.asp_non_membership.root,
+        asp_non_membership_contract_id: data.asp_non_membership.contract_id,
+        asp_non_membership_ledger: data.asp_non_membership.ledger,
         policy_flags: pool.policy_flags,
+        gvk_mode,
+        admin_view_key: pool.admin_view_key,
     })
 }
 
@@ -229,6 +259,10 @@ pub struct NewNullifierEvent {
     pub id: String,
     /// The nullifier that was spent (BN254 field element).
     pub nullifier: Field,
+    /// GVK ciphertext of the spent note. Only `pool-gvk` pools in traceable
+    /// mode emit it.
+    #[serde(default)]
+    pub gvk_ciphertext: Option<GlobalViewKeyCiphertext>,
 }
 
 /// Event emitted when a new commitment is added to the Merkle tree
@@ -245,6 +279,10 @@ pub struct NewCommitmentEvent {
     pub index: u32,
     /// Encrypted output data (decryptable by the recipient)
     pub encrypted_output: Vec<u8>,
+    /// GVK ciphertext of the output note, decryptable by the pool admin.
+    /// `None` for pools without a Global View Key.
+    #[serde(default)]
+    pub gvk_ciphertext: Option<GlobalViewKeyCiphertext>,
 }
 
 /// New pubkey pairs in the pool
@@ -261,6 +299,23 @@ pub struct PublicKeyEvent {
     pub encryption_key: EncryptionPublicKey,
     /// BN254 note public key
     pub note_key: NotePublicKey,
+    /// Per-owner registration version. `None` for events emitted before the
+    /// registry tracked versions.
+    pub version: Option<u32>,
+}
+
+/// Revoked pubkey pair in the registry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyRevokedEvent {
+    // Unique identifier for this event, based on the TOID format.
+    // It combines a 19-character TOID and a 10-character, zero-padded event index, separated by a
+    // hyphen.
+    pub id: String,
+    /// Address of the account owner
+    pub owner: String,
+    /// Version of the revoked registration
+    pub version: u32,
 }
 
 /// Event emitted when a new leaf is added to the Merkle tree
@@ -326,6 +381,7 @@ pub enum ProcessedEvent {
     Nullifier(NewNullifierEvent),
     Commitment(NewCommitmentEvent),
     PublicKey(PublicKeyEvent),
+    PublicKeyRevoked(PublicKeyRevokedEvent),
     LeafAdded(LeafAddedEvent),
     LeafInserted(LeafInsertedEvent),
     LeafUpdated(LeafUpdatedEvent),
@@ -360,6 +416,9 @@ mod pool_info_gvk_tests {
                 y: Field(U256::from(2)),
             }),
             gvk_mode: Some(2),
+            total_deposited: ExtAmount::from(500i128),
+            total_withdrawn: ExtAmount::from(200i128),
+            spent_nullifiers: 2,
         }
     }
 
@@ -394,6 +453,10 @@ mod pool_info_gvk_tests {
 
         assert_eq!(decoded.admin_view_key, pool.admin_view_key);
         assert_eq!(decoded.gvk_mode, pool.gvk_mode);
+        assert_eq!(decoded.total_deposited, pool.total_deposited);
+        assert_eq!(decoded.total_withdrawn, pool.total_withdrawn);
+        assert_eq!(decoded.spent_nullifiers, pool.spent_nullifiers);
+        assert_eq!(decoded.shielded_supply(), Some(ExtAmount::from(300i128)));
     }
 
     /// A `contracts/pool` deployment must emit no GVK keys at all, rather
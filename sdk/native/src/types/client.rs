@@ -1,4 +1,5 @@
-use super::{ContractConfig, EncryptionPublicKey, NotePublicKey};
+use super::{ContractConfig, EncryptionPublicKey, NoteAmount, NotePublicKey};
+use crate::planner::{CoinSelection, SpendableNote};
 use serde::{Deserialize, Serialize};
 
 /// Circuit bytes for lazy prover init (load via platform I/O before
@@ -20,15 +21,38 @@ impl ProverArtifacts {
     }
 }
 
+/// Wallet storage key of sub-account `index` of `address`.
+///
+/// Sub-account 0 is the account itself. Others are stored as `{address}/{index}`,
+/// which never collides with a Stellar address.
+pub fn sub_account_address(address: &str, index: u32) -> String {
+    if index == 0 {
+        address.to_string()
+    } else {
+        format!("{address}/{index}")
+    }
+}
+
 /// Per-pool session config (deployment, pool contract, user address).
 #[derive(Debug, Clone)]
 pub struct PrivatePoolConfig {
     pub contract_config: ContractConfig,
     pub pool_contract_id: String,
+    /// Stellar address that signs and submits transactions.
     pub user_address: String,
+    /// Sub-account of `user_address` whose notes the session spends; 0 for
+    /// the account itself.
+    pub sub_account: u32,
+    /// How spends pick their input notes.
+    pub coin_selection: CoinSelection,
 }
 
 impl PrivatePoolConfig {
+    /// Wallet storage key of the session's notes and keys.
+    pub fn storage_address(&self) -> String {
+        sub_account_address(&self.user_address, self.sub_account)
+    }
+
     pub fn validate(&self) -> Result<(), crate::error::Error> {
         if self.pool_contract_id.is_empty() {
             return Err(crate::error::Error::InvalidConfig(
@@ -60,6 +84,9 @@ pub enum TransferRecipient {
         note_public_key: NotePublicKey,
         encryption_public_key: EncryptionPublicKey,
     },
+    /// Another sub-account of the sending wallet; see
+    /// [`crate::Account::sub_account`].
+    SubAccount(u32),
 }
 
 impl TransferRecipient {
@@ -86,10 +113,159 @@ impl From<&str> for TransferRecipient {
     }
 }
 
+/// One recipient of [`crate::PrivatePool::transfer_many`].
+#[derive(Debug, Clone)]
+pub struct PayoutStatus {
+    pub recipient: TransferRecipient,
+    pub amount: NoteAmount,
+    /// Memo requested for this recipient; `None` used the batch memo.
+    pub memo: Option<String>,
+    /// Confirmed transaction that paid this recipient; `None` if unpaid.
+    pub tx_hash: Option<String>,
+}
+
+/// Outcome of [`crate::PrivatePool::transfer_many`]. A chain that stops part
+/// way still reports who was paid; [`crate::PrivatePool::resume_plans`]
+/// finishes the batch, or pass [`Self::remaining`] to a new `transfer_many`
+/// after abandoning the plan.
+#[derive(Debug)]
+pub struct PayoutReport {
+    /// Per-recipient status, in request order.
+    pub payouts: Vec<PayoutStatus>,
+    /// Confirmed transactions, in chain order.
+    pub transactions: Vec<TransactionResult>,
+    /// Why the chain stopped before paying everyone.
+    pub error: Option<crate::error::Error>,
+}
+
+impl PayoutReport {
+    pub fn is_complete(&self) -> bool {
+        self.payouts.iter().all(|payout| payout.tx_hash.is_some())
+    }
+
+    /// Recipients still to be paid, with their memos.
+    pub fn remaining(&self) -> Vec<(TransferRecipient, NoteAmount, Option<String>)> {
+        self.payouts
+            .iter()
+            .filter(|payout| payout.tx_hash.is_none())
+            .map(|payout| (payout.recipient.clone(), payout.amount, payout.memo.clone()))
+            .collect()
+    }
+}
+
+/// Lifecycle of a [`StoredPlan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PlanStatus {
+    /// Steps remain; [`crate::PrivatePool::resume_plans`] picks it up.
+    Active,
+    /// Every step confirmed.
+    Completed,
+    /// Given up before its last step; no further steps are submitted.
+    Abandoned,
+}
+
+impl PlanStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Completed => "completed",
+            Self::Abandoned => "abandoned",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "active" => Some(Self::Active),
+            "completed" => Some(Self::Completed),
+            "abandoned" => Some(Self::Abandoned),
+            _ => None,
+        }
+    }
+}
+
+/// Step of a [`StoredPlan`] that was submitted but not yet seen confirmed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InFlightStep {
+    pub tx_hash: String,
+    /// Plan state once this step confirms (JSON).
+    pub state: String,
+}
+
+/// A [`crate::PreparedTransactionPlan`] persisted in wallet storage while it
+/// runs, so [`crate::PrivatePool::resume_plans`] can finish or abandon it
+/// after a crash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredPlan {
+    /// Storage row; `None` until first saved.
+    pub id: Option<i64>,
+    pub pool_contract_id: String,
+    /// Wallet storage key of the account spending
+    /// ([`PrivatePoolConfig::storage_address`]).
+    pub user_address: String,
+    pub status: PlanStatus,
+    pub tx_count: u32,
+    /// Plan state after the last confirmed step (JSON): the step list and the
+    /// wallet snapshot it resolves inputs against.
+    pub state: String,
+    /// Confirmed transactions, in step order.
+    pub confirmed: Vec<String>,
+    /// Notes created by confirmed steps that the next step spends.
+    pub pending_notes: Vec<SpendableNote>,
+    pub in_flight: Option<InFlightStep>,
+}
+
+/// Outcome of one persisted plan in [`crate::PrivatePool::resume_plans`].
+#[derive(Debug)]
+pub struct ResumedPlan {
+    pub plan_id: i64,
+    pub status: PlanStatus,
+    /// Confirmed transactions of the plan, including those from before the
+    /// resume, in step order.
+    pub transactions: Vec<TransactionResult>,
+    /// Why the plan was abandoned, or why it stopped again while still
+    /// [`PlanStatus::Active`].
+    pub error: Option<crate::error::Error>,
+}
+
+/// Optional settings for [`crate::PrivatePool::transfer`].
+#[derive(Debug, Clone, Default)]
+pub struct TransferOptions {
+    /// Memo encrypted to the recipient with the payment note, e.g. an invoice
+    /// reference. At most [`crate::MAX_MEMO_LEN`] bytes of UTF-8; every output
+    /// is padded to the same size, so the memo length does not leak.
+    pub memo: Option<String>,
+}
+
+impl TransferOptions {
+    pub fn memo(memo: impl Into<String>) -> Self {
+        Self {
+            memo: Some(memo.into()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Estimate {
+    /// Transactions with the pool's [`CoinSelection`].
+    pub tx_count: u32,
+    /// Cost of the spend under each built-in strategy that can fund it.
+    pub strategies: Vec<StrategyEstimate>,
+}
+
+/// Cost of a spend under one [`CoinSelection`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StrategyEstimate {
+    pub strategy: CoinSelection,
     pub tx_count: u32,
+    /// Wallet notes the spend consumes.
+    pub note_count: u32,
+    /// Change note returned to the wallet.
+    pub change: NoteAmount,
 }
 
 #[derive(Debug, Clone)]
@@ -5,7 +5,7 @@
 //! `circuits/src/globalViewKey.circom`. No encryption/decryption logic lives
 //! here. See `circuits/src/test/utils/global_view_key.rs` for that.
 
-use super::{Field, PolicyFlags};
+use super::{Field, NoteAmount, PolicyFlags};
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 
@@ -132,6 +132,188 @@ impl GlobalViewKeyMemo {
     }
 }
 
+/// Current `GvkDecryptionReceipt` schema version.
+pub const GVK_DECRYPTION_RECEIPT_VERSION: u32 = 1;
+
+/// Chaum-Pedersen proof that two points share a discrete log: `D = d * BASE8`
+/// and `P = d * R`.
+///
+/// `a1 = k * BASE8` and `a2 = k * R` are the prover's commitments and
+/// `response = k + c * d mod l`, where `l` is the Baby JubJub subgroup order
+/// and `c` the Fiat-Shamir challenge over all six points.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GvkDleqProof {
+    pub a1: BabyJubJubPoint,
+    pub a2: BabyJubJubPoint,
+    pub response: Field,
+}
+
+/// A pool administrator's verifiable claim about one decrypted GVK note.
+///
+/// Lets a third party check a decryption without the admin's private scalar:
+/// `proof` shows `shared_point = d * ciphertext.r` for the `d` behind
+/// `admin_view_key`, the keystream derived from `8 * shared_point` turns the
+/// ciphertext into the opening, and the opening hashes to `commitment`.
+///
+/// The receipt does not show that `admin_view_key` is the pool's key or that
+/// `ciphertext` was emitted for `commitment`; verifiers check both against
+/// the chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GvkDecryptionReceipt {
+    /// Receipt schema version.
+    pub version: u32,
+    /// Pool whose event carried the ciphertext.
+    pub pool_address: String,
+    /// Id of the pool event that carried the ciphertext.
+    pub event_id: String,
+    /// The administrator view key `D` the ciphertext was decrypted for.
+    pub admin_view_key: BabyJubJubPoint,
+    pub ciphertext: GlobalViewKeyCiphertext,
+    /// `d * R`. The keystream is derived from `8 * shared_point`.
+    pub shared_point: BabyJubJubPoint,
+    pub proof: GvkDleqProof,
+    /// Decrypted note public key.
+    pub note_public_key: Field,
+    /// Decrypted note amount.
+    pub amount: NoteAmount,
+    /// Decrypted note blinding.
+    pub blinding: Field,
+    /// The commitment the opening hashes to.
+    pub commitment: Field,
+}
+
+impl GvkDecryptionReceipt {
+    /// Validates schema-level invariants. Does not verify any cryptographic
+    /// material.
+    pub fn validate(&self) -> Result<()> {
+        if self.version != GVK_DECRYPTION_RECEIPT_VERSION {
+            return Err(anyhow!("Unsupported GVK decryption receipt version"));
+        }
+        if self.pool_address.is_empty() {
+            return Err(anyhow!("GVK decryption receipt pool address is empty"));
+        }
+        if self.event_id.is_empty() {
+            return Err(anyhow!("GVK decryption receipt event id is empty"));
+        }
+        Ok(())
+    }
+}
+
+/// Result of verifying a [`GvkDecryptionReceipt`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GvkDecryptionVerificationReport {
+    /// Whether the DLEQ proof binds `shared_point` to `admin_view_key`.
+    pub proof_verified: bool,
+    /// Whether the ciphertext decrypts to the opening under `shared_point`.
+    pub decryption_verified: bool,
+    /// Whether the opening hashes to `commitment`.
+    pub opening_verified: bool,
+    /// Whether `admin_view_key` is the pool's on-chain view key.
+    pub admin_view_key_matches_pool: bool,
+}
+
+impl GvkDecryptionVerificationReport {
+    /// Returns `true` when every check passed.
+    pub fn is_valid(&self) -> bool {
+        self.proof_verified
+            && self.decryption_verified
+            && self.opening_verified
+            && self.admin_view_key_matches_pool
+    }
+}
+
+/// One dealer's public contribution to a threshold GVK key generation.
+///
+/// `commitments[k] = a_k * BASE8` for the coefficients `a_k` of the dealer's
+/// secret polynomial of degree `threshold - 1`. Broadcast to every
+/// participant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GvkDkgDealing {
+    /// Dealer participant index, starting at 1.
+    pub dealer: u32,
+    pub commitments: Vec<BabyJubJubPoint>,
+}
+
+/// The evaluation of a dealer's secret polynomial at a recipient's index.
+///
+/// Secret: send it to the recipient only, over an authenticated and
+/// encrypted channel.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GvkDkgShare {
+    pub dealer: u32,
+    pub recipient: u32,
+    pub value: Field,
+}
+
+impl std::fmt::Debug for GvkDkgShare {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GvkDkgShare")
+            .field("dealer", &self.dealer)
+            .field("recipient", &self.recipient)
+            .field("value", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Public result of a threshold GVK key generation.
+///
+/// `admin_view_key` is the `D` to configure on the pool. Participant `i`
+/// holds a share `x_i` of the never-assembled `d`, with
+/// `verification_keys[i - 1] = x_i * BASE8`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GvkThresholdPublicKey {
+    /// Number of participants needed to decrypt.
+    pub threshold: u32,
+    pub admin_view_key: BabyJubJubPoint,
+    pub verification_keys: Vec<BabyJubJubPoint>,
+}
+
+impl GvkThresholdPublicKey {
+    /// Number of participants.
+    pub fn participants(&self) -> usize {
+        self.verification_keys.len()
+    }
+
+    /// The verification key of `participant`, starting at 1.
+    pub fn verification_key(&self, participant: u32) -> Option<&BabyJubJubPoint> {
+        let index = usize::try_from(participant.checked_sub(1)?).ok()?;
+        self.verification_keys.get(index)
+    }
+
+    /// Validates that `1 <= threshold <= participants`.
+    pub fn validate(&self) -> Result<()> {
+        let threshold =
+            usize::try_from(self.threshold).map_err(|_| anyhow!("GVK threshold out of range"))?;
+        if threshold == 0 || threshold > self.participants() {
+            return Err(anyhow!(
+                "GVK threshold {} is invalid for {} participants",
+                self.threshold,
+                self.participants()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// One participant's share of the shared point of a GVK ciphertext.
+///
+/// `share = x_i * R` for the ciphertext of `event_id`, with a proof that it
+/// uses the same `x_i` as the participant's verification key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GvkPartialDecryption {
+    pub event_id: String,
+    pub participant: u32,
+    pub share: BabyJubJubPoint,
+    pub proof: GvkDleqProof,
+}
+
 /// Pool-level Global View Key configuration.
 ///
 /// Orthogonal to [`PolicyFlags`] rather than a bit on it: view-only and
@@ -1,4 +1,5 @@
 mod amounts;
+mod backup;
 mod chain_data;
 mod client;
 mod correlation;
@@ -6,9 +7,12 @@ mod disclosure;
 mod ext_data;
 mod gvk;
 mod logging;
+mod payment_request;
 mod policy_tx;
+mod viewing;
 pub use amounts::*;
 use anyhow::{Result, anyhow};
+pub use backup::*;
 pub use chain_data::*;
 pub use client::*;
 pub use correlation::*;
@@ -16,7 +20,9 @@ pub use disclosure::*;
 pub use ext_data::*;
 pub use gvk::*;
 pub use logging::*;
+pub use payment_request::*;
 pub use policy_tx::*;
+pub use viewing::*;
 
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -147,6 +153,9 @@ pub struct PublicKeyEntry {
     pub note_key: NotePublicKey,
     /// Ledger sequence when registered.
     pub ledger: u32,
+    /// Per-owner registration version, if the registry emitted one.
+    #[serde(default)]
+    pub version: Option<u32>,
 }
 
 /// A compact note view for UI rendering and spending selection.
@@ -165,6 +174,30 @@ pub struct UserNoteSummary {
     pub created_at_ledger: u32,
     /// Whether the note has been spent (nullifier observed).
     pub spent: bool,
+    /// Memo the sender attached to the note, if any.
+    #[serde(default)]
+    pub memo: Option<String>,
+}
+
+/// A note this account sent to someone else, recovered with its outgoing
+/// viewing key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SentNoteSummary {
+    /// Pool commitment (hex).
+    pub id: Field,
+    /// Pool contract id that owns the commitment.
+    pub pool_contract_id: String,
+    /// Amount in stroops.
+    pub amount: NoteAmount,
+    /// Commitment leaf index in the pool Merkle tree.
+    pub leaf_index: u32,
+    /// Ledger sequence when the commitment event was observed.
+    pub created_at_ledger: u32,
+    pub recipient_note_public_key: NotePublicKey,
+    pub recipient_encryption_public_key: EncryptionPublicKey,
+    /// Registry owner of the recipient keys, when they were ever registered.
+    pub recipient_address: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -238,6 +271,23 @@ impl Drop for KeyDerivationSignature {
     }
 }
 
+/// User passphrase protecting the wallet's secret keys at rest
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Passphrase(pub String);
+
+impl std::fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Passphrase(<redacted>)")
+    }
+}
+
+impl Drop for Passphrase {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.0);
+    }
+}
+
 /// Encryption private key
 #[derive(Clone)]
 pub struct EncryptionPrivateKey(pub [u8; 32]);
@@ -287,6 +337,23 @@ impl Drop for NotePrivateKey {
 #[derive(Debug, Clone)]
 pub struct NotePublicKey(pub [u8; 32]);
 
+/// Outgoing viewing key: opens the sender copy every output carries, so a
+/// sender can recover what it sent from chain events alone.
+#[derive(Clone)]
+pub struct OutgoingViewingKey(pub [u8; 32]);
+
+impl std::fmt::Debug for OutgoingViewingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "OutgoingViewingKey(<redacted>)")
+    }
+}
+
+impl Drop for OutgoingViewingKey {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.0);
+    }
+}
+
 pub fn encode_0x_hex(bytes: &[u8; 32]) -> String {
     let mut out = String::with_capacity(2 + 64);
     out.push_str("0x");
@@ -321,11 +388,13 @@ impl ContractConfig {
         self.pools.iter().filter(|p| p.enabled)
     }
 
-    /// Contract IDs for enabled pools and ASP membership.
+    /// Contract IDs whose events are indexed: enabled pools, both ASP
+    /// contracts and the public key registry.
     pub fn all_contract_ids(&self) -> Vec<String> {
         self.enabled_pools()
             .map(|p| p.pool_contract_id.clone())
             .chain(std::iter::once(self.asp_membership.clone()))
+            .chain(std::iter::once(self.asp_non_membership.clone()))
             .chain(std::iter::once(self.public_key_registry.clone()))
             .collect()
     }
@@ -409,9 +478,13 @@ impl_key_serde_hex!(EncryptionPrivateKey);
 impl_key_serde_hex!(EncryptionPublicKey);
 impl_key_serde_hex!(NotePrivateKey);
 impl_key_serde_hex!(NotePublicKey);
+impl_key_serde_hex!(OutgoingViewingKey);
 
 mod rusqlite_key_impls {
-    use super::{EncryptionPrivateKey, EncryptionPublicKey, NotePrivateKey, NotePublicKey};
+    use super::{
+        EncryptionPrivateKey, EncryptionPublicKey, NotePrivateKey, NotePublicKey,
+        OutgoingViewingKey,
+    };
     use rusqlite::types::{
         FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, Value, ValueRef,
     };
@@ -449,6 +522,7 @@ mod rusqlite_key_impls {
     impl_key_rusqlite_blob_32!(EncryptionPublicKey);
     impl_key_rusqlite_blob_32!(NotePrivateKey);
     impl_key_rusqlite_blob_32!(NotePublicKey);
+    impl_key_rusqlite_blob_32!(OutgoingViewingKey);
 }
 
 #[cfg(test)]
@@ -532,6 +606,7 @@ mod key_serde_tests {
     hex_key_tests!(EncryptionPublicKey, encryption_public_key);
     hex_key_tests!(NotePrivateKey, note_private_key);
     hex_key_tests!(NotePublicKey, note_public_key);
+    hex_key_tests!(OutgoingViewingKey, outgoing_viewing_key);
 
     #[test]
     fn note_public_key_deserialize_from_plain_str_deserializer() -> Result<()> {
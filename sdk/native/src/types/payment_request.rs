@@ -0,0 +1,303 @@
+//! `spp:` payment-request URIs.
+//!
+//! A [`PaymentRequest`] tells a payer everything a private transfer needs —
+//! pool, amount and the recipient's note and encryption public keys — so keys
+//! do not have to be copied by hand. The recipient shares
+//! [`PaymentRequest::to_uri`] (as text or a QR code) and the payer hands it to
+//! `PrivatePool::pay_request`.
+//!
+//! ```text
+//! spp:<pool C…>?v=1&amount=<stroops>&note=<hex>&enc=<hex>[&memo=<pct>][&exp=<unix secs>]&check=<hex>
+//! ```
+//!
+//! `check` is the first 4 bytes of SHA-256 over everything before `&check=`,
+//! so a mistyped or truncated URI is rejected instead of paying the wrong key.
+
+use std::fmt::Write as _;
+
+use super::{EncryptionPublicKey, NoteAmount, NotePublicKey};
+use crate::zk::encryption::MAX_MEMO_LEN;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// URI scheme of every [`PaymentRequest`].
+pub const PAYMENT_REQUEST_SCHEME: &str = "spp";
+
+/// Current [`PaymentRequest`] URI version.
+pub const PAYMENT_REQUEST_VERSION: u32 = 1;
+
+const CHECKSUM_LEN: usize = 4;
+
+/// A request to be paid privately in one pool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequest {
+    /// Pool contract id (`C…`) the payment is made in.
+    pub pool_contract_id: String,
+    pub amount: NoteAmount,
+    pub note_public_key: NotePublicKey,
+    pub encryption_public_key: EncryptionPublicKey,
+    /// Memo for the payer to attach, at most [`MAX_MEMO_LEN`] bytes.
+    pub memo: Option<String>,
+    /// Unix time (seconds) after which the request should not be paid.
+    pub expires_at: Option<u64>,
+}
+
+impl PaymentRequest {
+    pub fn new(
+        pool_contract_id: impl Into<String>,
+        amount: NoteAmount,
+        note_public_key: NotePublicKey,
+        encryption_public_key: EncryptionPublicKey,
+    ) -> Self {
+        Self {
+            pool_contract_id: pool_contract_id.into(),
+            amount,
+            note_public_key,
+            encryption_public_key,
+            memo: None,
+            expires_at: None,
+        }
+    }
+
+    /// Whether the request has expired at unix time `now` (seconds).
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now > expires_at)
+    }
+
+    /// Encodes the request as an `spp:` URI. Fails when the pool id is not a
+    /// contract address or the memo is too long.
+    pub fn to_uri(&self) -> Result<String> {
+        self.validate()?;
+        let mut uri = format!(
+            "{PAYMENT_REQUEST_SCHEME}:{}?v={PAYMENT_REQUEST_VERSION}&amount={}&note={}&enc={}",
+            self.pool_contract_id,
+            self.amount,
+            hex::encode(self.note_public_key.0),
+            hex::encode(self.encryption_public_key.0),
+        );
+        if let Some(memo) = &self.memo {
+            uri.push_str("&memo=");
+            uri.push_str(&percent_encode(memo));
+        }
+        if let Some(expires_at) = self.expires_at {
+            write!(uri, "&exp={expires_at}").map_err(|e| anyhow!("encode expiry: {e}"))?;
+        }
+        let check = checksum(&uri);
+        uri.push_str("&check=");
+        uri.push_str(&check);
+        Ok(uri)
+    }
+
+    /// Parses an `spp:` URI, verifying its checksum and version. Unknown or
+    /// repeated parameters are rejected.
+    pub fn parse(uri: &str) -> Result<Self> {
+        let uri = uri.trim();
+        let (body, check) = uri
+            .rsplit_once("&check=")
+            .ok_or_else(|| anyhow!("payment request has no checksum"))?;
+        if !checksum(body).eq_ignore_ascii_case(check) {
+            return Err(anyhow!(
+                "payment request checksum mismatch (mistyped or truncated URI?)"
+            ));
+        }
+        let rest = body
+            .strip_prefix(PAYMENT_REQUEST_SCHEME)
+            .and_then(|rest| rest.strip_prefix(':'))
+            .ok_or_else(|| anyhow!("not an {PAYMENT_REQUEST_SCHEME}: payment request"))?;
+        let (pool_contract_id, query) = rest
+            .split_once('?')
+            .ok_or_else(|| anyhow!("payment request has no parameters"))?;
+
+        let mut version = None;
+        let mut amount = None;
+        let mut note_public_key = None;
+        let mut encryption_public_key = None;
+        let mut memo = None;
+        let mut expires_at = None;
+        for pair in query.split('&') {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow!("malformed payment request parameter {pair:?}"))?;
+            let duplicate = match key {
+                "v" => version
+                    .replace(
+                        value
+                            .parse::<u32>()
+                            .map_err(|e| anyhow!("invalid payment request version: {e}"))?,
+                    )
+                    .is_some(),
+                "amount" => amount
+                    .replace(
+                        value
+                            .parse::<NoteAmount>()
+                            .map_err(|e| anyhow!("invalid payment request amount: {e}"))?,
+                    )
+                    .is_some(),
+                "note" => note_public_key
+                    .replace(
+                        NotePublicKey::parse(value)
+                            .map_err(|e| anyhow!("invalid recipient note key: {e}"))?,
+                    )
+                    .is_some(),
+                "enc" => encryption_public_key
+                    .replace(
+                        EncryptionPublicKey::parse(value)
+                            .map_err(|e| anyhow!("invalid recipient encryption key: {e}"))?,
+                    )
+                    .is_some(),
+                "memo" => memo.replace(percent_decode(value)?).is_some(),
+                "exp" => expires_at
+                    .replace(
+                        value
+                            .parse::<u64>()
+                            .map_err(|e| anyhow!("invalid payment request expiry: {e}"))?,
+                    )
+                    .is_some(),
+                other => {
+                    return Err(anyhow!("unknown payment request parameter {other:?}"));
+                }
+            };
+            if duplicate {
+                return Err(anyhow!("payment request repeats parameter {key:?}"));
+            }
+        }
+
+        let version = version.ok_or_else(|| anyhow!("payment request has no version"))?;
+        if version != PAYMENT_REQUEST_VERSION {
+            return Err(anyhow!(
+                "unsupported payment request version {version} (expected {PAYMENT_REQUEST_VERSION})"
+            ));
+        }
+        let request = Self {
+            pool_contract_id: pool_contract_id.to_string(),
+            amount: amount.ok_or_else(|| anyhow!("payment request has no amount"))?,
+            note_public_key: note_public_key
+                .ok_or_else(|| anyhow!("payment request has no note key"))?,
+            encryption_public_key: encryption_public_key
+                .ok_or_else(|| anyhow!("payment request has no encryption key"))?,
+            memo,
+            expires_at,
+        };
+        request.validate()?;
+        Ok(request)
+    }
+
+    fn validate(&self) -> Result<()> {
+        stellar_strkey::Contract::from_string(&self.pool_contract_id)
+            .map_err(|e| anyhow!("invalid pool contract id {:?}: {e}", self.pool_contract_id))?;
+        if let Some(memo) = &self.memo
+            && memo.len() > MAX_MEMO_LEN
+        {
+            return Err(anyhow!(
+                "memo is {} bytes, at most {MAX_MEMO_LEN} are allowed",
+                memo.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn checksum(body: &str) -> String {
+    let digest = Sha256::digest(body.as_bytes());
+    hex::encode(&digest[..CHECKSUM_LEN])
+}
+
+/// Percent-encodes everything except RFC 3986 unreserved characters.
+fn percent_encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(char::from(byte));
+        } else {
+            let _ = write!(out, "%{byte:02X}");
+        }
+    }
+    out
+}
+
+fn percent_decode(value: &str) -> Result<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let (hex_digits, tail) = tail
+                .split_at_checked(2)
+                .ok_or_else(|| anyhow!("truncated percent escape in memo"))?;
+            let digits = std::str::from_utf8(hex_digits)
+                .map_err(|_| anyhow!("invalid percent escape in memo"))?;
+            bytes.push(
+                u8::from_str_radix(digits, 16)
+                    .map_err(|_| anyhow!("invalid percent escape in memo"))?,
+            );
+            rest = tail;
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| anyhow!("memo is not valid UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POOL: &str = "CCYQZY74F3GIOC5G2PZRL7ESUUT5V6T3VX25O2YLAJCMF3XOFONKHNUY";
+
+    fn request() -> PaymentRequest {
+        let mut request = PaymentRequest::new(
+            POOL,
+            NoteAmount::from(12_500_000u128),
+            NotePublicKey([1u8; 32]),
+            EncryptionPublicKey([2u8; 32]),
+        );
+        request.memo = Some("invoice #42 — café".to_string());
+        request.expires_at = Some(1_800_000_000);
+        request
+    }
+
+    #[test]
+    fn uri_roundtrip() -> Result<()> {
+        let uri = request().to_uri()?;
+        assert!(uri.starts_with("spp:CCYQZY74"));
+        let parsed = PaymentRequest::parse(&uri)?;
+        assert_eq!(parsed.pool_contract_id, POOL);
+        assert_eq!(parsed.amount, NoteAmount::from(12_500_000u128));
+        assert_eq!(parsed.note_public_key.0, [1u8; 32]);
+        assert_eq!(parsed.encryption_public_key.0, [2u8; 32]);
+        assert_eq!(parsed.memo.as_deref(), Some("invoice #42 — café"));
+        assert_eq!(parsed.expires_at, Some(1_800_000_000));
+        assert!(!parsed.is_expired(1_800_000_000));
+        assert!(parsed.is_expired(1_800_000_001));
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_tampered_and_unknown_uris() -> Result<()> {
+        let uri = request().to_uri()?;
+        let tampered = uri.replace("amount=12500000", "amount=12500001");
+        assert!(PaymentRequest::parse(&tampered).is_err());
+        assert!(PaymentRequest::parse(uri.trim_end_matches(char::is_alphanumeric)).is_err());
+
+        let mut unchecked = PaymentRequest::new(
+            POOL,
+            NoteAmount::from(1u128),
+            NotePublicKey([1u8; 32]),
+            EncryptionPublicKey([2u8; 32]),
+        )
+        .to_uri()?;
+        unchecked = unchecked.replace("v=1", "v=2");
+        let (body, _) = unchecked
+            .rsplit_once("&check=")
+            .expect("encoded URI has a checksum");
+        let future = format!("{body}&check={}", checksum(body));
+        assert!(PaymentRequest::parse(&future).is_err());
+
+        let mut bad_pool = request();
+        bad_pool.pool_contract_id = "GABC".to_string();
+        assert!(bad_pool.to_uri().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+//! Exportable viewing keys for watch-only accounts.
+//!
+//! A [`ViewingKey`] opens every output addressed to an account and every
+//! sender copy it sealed, but cannot prove: it carries no note private key.
+//! The owner exports it with `Account::export_viewing_key` and a watcher opens
+//! it with `Client::watch_account`.
+
+use super::{EncryptionPrivateKey, NotePublicKey, OutgoingViewingKey};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// `format` of every [`ViewingKey`].
+pub const VIEWING_KEY_FORMAT: &str = "spp-viewing-key";
+
+/// Current [`ViewingKey`] schema version.
+pub const VIEWING_KEY_VERSION: u32 = 1;
+
+/// Keys that read an account's notes without spend authority.
+///
+/// - `encryption_private_key` opens outputs addressed to the account, so the
+///   watcher sees received notes and balances.
+/// - `note_public_key` checks those outputs against their commitments.
+/// - `outgoing_viewing_key` opens the sender copy of every output the account
+///   created: sent history, and the spend record naming the inputs each
+///   transaction consumed. Nullifiers need the note private key, so spent
+///   notes are found through these records instead.
+///
+/// Spends made before spend records existed are not visible to a watcher.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ViewingKey {
+    /// Always [`VIEWING_KEY_FORMAT`].
+    pub format: String,
+    /// Schema version; [`VIEWING_KEY_VERSION`] for keys written by this crate.
+    pub version: u32,
+    /// Stellar address of the watched account.
+    pub address: String,
+    pub note_public_key: NotePublicKey,
+    pub encryption_private_key: EncryptionPrivateKey,
+    pub outgoing_viewing_key: OutgoingViewingKey,
+}
+
+impl ViewingKey {
+    pub fn new(
+        address: impl Into<String>,
+        note_public_key: NotePublicKey,
+        encryption_private_key: EncryptionPrivateKey,
+        outgoing_viewing_key: OutgoingViewingKey,
+    ) -> Self {
+        Self {
+            format: VIEWING_KEY_FORMAT.to_string(),
+            version: VIEWING_KEY_VERSION,
+            address: address.into(),
+            note_public_key,
+            encryption_private_key,
+            outgoing_viewing_key,
+        }
+    }
+
+    /// Parses a viewing key from its JSON export and checks the format
+    /// marker and schema version.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let key: Self =
+            serde_json::from_str(json).map_err(|e| anyhow!("invalid viewing key: {e}"))?;
+        if key.format != VIEWING_KEY_FORMAT {
+            return Err(anyhow!("not a viewing key (format {:?})", key.format));
+        }
+        if key.version != VIEWING_KEY_VERSION {
+            return Err(anyhow!(
+                "unsupported viewing key version {} (expected {VIEWING_KEY_VERSION})",
+                key.version
+            ));
+        }
+        Ok(key)
+    }
+
+    /// JSON export of this key. It reveals the account's full history; share
+    /// it like a password.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| anyhow!("encode viewing key: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> ViewingKey {
+        ViewingKey::new(
+            "GWATCHED",
+            NotePublicKey([1u8; 32]),
+            EncryptionPrivateKey([2u8; 32]),
+            OutgoingViewingKey([3u8; 32]),
+        )
+    }
+
+    #[test]
+    fn json_roundtrip() -> Result<()> {
+        let parsed = ViewingKey::from_json(&key().to_json()?)?;
+        assert_eq!(parsed.address, "GWATCHED");
+        assert_eq!(parsed.note_public_key.0, [1u8; 32]);
+        assert_eq!(parsed.encryption_private_key.0, [2u8; 32]);
+        assert_eq!(parsed.outgoing_viewing_key.0, [3u8; 32]);
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_other_formats_and_versions() -> Result<()> {
+        let mut other = key();
+        other.format = "spp-wallet-backup".to_string();
+        assert!(ViewingKey::from_json(&other.to_json()?).is_err());
+
+        let mut future = key();
+        future.version = VIEWING_KEY_VERSION + 1;
+        assert!(ViewingKey::from_json(&future.to_json()?).is_err());
+        Ok(())
+    }
+}
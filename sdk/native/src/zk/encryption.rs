@@ -19,7 +19,9 @@
 //!        └── signMessage("Privacy Pool Key Derivation [v2]")
 //!                   │
 //!                   ├── SHA-256("privacy-pool/note-key/v2" || sig)
-//!                   │          └── BN254 Note Private Key → Poseidon2 → Note Public Key
+//!                   │          ├── BN254 Note Private Key → Poseidon2 → Note Public Key
+//!                   │          └── SHA-256("privacy-pool/outgoing-viewing-key/v1" || key)
+//!                   │                     └── Outgoing Viewing Key
 //!                   │
 //!                   └── SHA-256("privacy-pool/encryption-key/v2" || sig)
 //!                              └── X25519 Encryption Keypair
@@ -33,6 +35,7 @@ use crate::{
     types::{
         EncryptionKeyPair, EncryptionPrivateKey, EncryptionPublicKey, Field,
         KeyDerivationSignature, NoteAmount, NoteKeyPair, NotePrivateKey, NotePublicKey,
+        OutgoingViewingKey,
     },
     zk::crypto::derive_public_key,
 };
@@ -53,6 +56,109 @@ pub const KEY_DERIVATION_MESSAGE: &str = "Privacy Pool Key Derivation [v1]";
 const NOTE_KEY_DOMAIN: &[u8] = b"privacy-pool/note-key/v1";
 const ENCRYPTION_KEY_DOMAIN: &[u8] = b"privacy-pool/encryption-key/v1";
 const MEMBERSHIP_BLINDING_DOMAIN: &[u8] = b"privacy-pool/asp-secret/v1";
+const VIEW_TAG_DOMAIN: &[u8] = b"privacy-pool/view-tag/v1";
+const OUTGOING_VIEWING_KEY_DOMAIN: &[u8] = b"privacy-pool/outgoing-viewing-key/v1";
+const OUTGOING_CIPHER_KEY_DOMAIN: &[u8] = b"privacy-pool/outgoing-cipher-key/v1";
+const SUB_ACCOUNT_NOTE_KEY_DOMAIN: &[u8] = b"privacy-pool/sub-account/note-key/v1";
+const SUB_ACCOUNT_ENCRYPTION_KEY_DOMAIN: &[u8] = b"privacy-pool/sub-account/encryption-key/v1";
+const SUB_ACCOUNT_MEMBERSHIP_BLINDING_DOMAIN: &[u8] = b"privacy-pool/sub-account/asp-secret/v1";
+
+/// Leading version byte of view-tagged encrypted outputs.
+///
+/// Legacy outputs carry no version byte and are recognised by their exact
+/// length, [`LEGACY_ENCRYPTED_OUTPUT_LEN`].
+pub const ENCRYPTED_OUTPUT_VERSION_VIEW_TAG: u8 = 1;
+
+/// Leading version byte of view-tagged outputs that also carry a sender copy
+/// sealed under the sender's [`OutgoingViewingKey`].
+pub const ENCRYPTED_OUTPUT_VERSION_OUTGOING: u8 = 2;
+
+/// Leading version byte of outputs whose sender copy also carries a spend
+/// record: the inputs of the transaction that created the output.
+pub const ENCRYPTED_OUTPUT_VERSION_SPEND_RECORD: u8 = 3;
+
+/// Length of an unversioned (pre-view-tag) encrypted output.
+pub const LEGACY_ENCRYPTED_OUTPUT_LEN: usize = 120;
+
+/// Bytes of the view tag: a false match costs one AEAD attempt in 65 536.
+pub const VIEW_TAG_LEN: usize = 2;
+
+/// Minimum length of a view-tagged output after its version byte.
+const VIEW_TAGGED_BODY_MIN_LEN: usize = VIEW_TAG_LEN + LEGACY_ENCRYPTED_OUTPUT_LEN;
+
+/// Recipient plaintext without a memo: amount (16 LE), blinding (32).
+const NOTE_PLAINTEXT_LEN: usize = 48;
+
+/// Bytes of the memo field appended to the recipient plaintext: a length byte
+/// followed by the zero-padded memo, so every memo encrypts to the same size.
+pub const MEMO_FIELD_LEN: usize = 128;
+
+/// Longest memo, in bytes of UTF-8, that fits in [`MEMO_FIELD_LEN`].
+pub const MAX_MEMO_LEN: usize = MEMO_FIELD_LEN - 1;
+
+/// Recipient plaintext with the memo field.
+const NOTE_PLAINTEXT_WITH_MEMO_LEN: usize = NOTE_PLAINTEXT_LEN + MEMO_FIELD_LEN;
+
+/// Sender copy plaintext: recipient note key (32), recipient encryption key
+/// (32), amount (16 LE), blinding (32).
+const OUTGOING_PLAINTEXT_LEN: usize = 112;
+
+/// Inputs named by a spend record; a transaction spends at most two notes.
+pub const SPEND_RECORD_INPUTS: usize = 2;
+
+/// Spend record: commitment (32) and nullifier (32) of each input, all zero
+/// for a dummy input.
+const SPEND_RECORD_LEN: usize = SPEND_RECORD_INPUTS * 64;
+
+/// Sender copy plaintext followed by the spend record.
+const OUTGOING_PLAINTEXT_WITH_SPENDS_LEN: usize = OUTGOING_PLAINTEXT_LEN + SPEND_RECORD_LEN;
+
+/// Sender copy on the wire: nonce (24) + ciphertext (112) + tag (16).
+const OUTGOING_SECTION_LEN: usize = 24 + OUTGOING_PLAINTEXT_LEN + 16;
+
+/// Sender copy with a spend record on the wire: nonce (24) + ciphertext (240)
+/// + tag (16).
+const OUTGOING_SECTION_WITH_SPENDS_LEN: usize = 24 + OUTGOING_PLAINTEXT_WITH_SPENDS_LEN + 16;
+
+/// Minimum length of a version 2 output after its version byte.
+const OUTGOING_BODY_MIN_LEN: usize =
+    VIEW_TAG_LEN + LEGACY_ENCRYPTED_OUTPUT_LEN + OUTGOING_SECTION_LEN;
+
+/// Minimum length of a version 3 output after its version byte.
+const SPEND_RECORD_BODY_MIN_LEN: usize =
+    VIEW_TAG_LEN + LEGACY_ENCRYPTED_OUTPUT_LEN + OUTGOING_SECTION_WITH_SPENDS_LEN;
+
+/// An output as its recipient sees it.
+#[derive(Debug, Clone)]
+pub struct OutputNotePlaintext {
+    pub amount: NoteAmount,
+    pub blinding: Field,
+    /// Memo from the sender; `None` when absent or malformed.
+    pub memo: Option<String>,
+}
+
+/// An output as its sender sees it, opened with the outgoing viewing key.
+#[derive(Debug, Clone)]
+pub struct OutgoingNote {
+    pub recipient_note_public_key: NotePublicKey,
+    pub recipient_encryption_public_key: EncryptionPublicKey,
+    pub amount: NoteAmount,
+    pub blinding: Field,
+    /// Inputs of the transaction that created the output; empty for outputs
+    /// sealed before spend records existed.
+    pub spent_inputs: Vec<SpentInput>,
+}
+
+/// A note spent by the transaction that created an output, as named in that
+/// output's spend record.
+///
+/// Nullifiers need the note private key, so a watch-only account learns which
+/// of its notes are spent from these records instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpentInput {
+    pub commitment: Field,
+    pub nullifier: Field,
+}
 
 /// Keypairs derivation
 pub fn derive_encryption_and_note_keypairs(
@@ -98,6 +204,84 @@ pub fn derive_membership_blinding(
     Field::try_from_le_bytes(result)
 }
 
+/// Derive the keys of sub-account `index` from the account's own keys.
+///
+/// Sub-accounts segregate funds under one wallet signature: each has its own
+/// note key, encryption key and ASP membership blinding, so notes of different
+/// sub-accounts are not linkable through a shared key. Index 0 is the account
+/// itself and returns its keys unchanged.
+///
+/// # Derivation
+/// ```text
+/// SHA-256(domain || 0 || index (u32 LE) || 0 || account key) → sub-account key
+/// ```
+/// with a separate domain for the note key (reduced to BN254), the X25519
+/// encryption key and the membership blinding (reduced to BN254).
+pub fn derive_sub_account_keys(
+    note_private_key: &NotePrivateKey,
+    encryption_private_key: &EncryptionPrivateKey,
+    membership_blinding: &Field,
+    index: u32,
+) -> Result<(NoteKeyPair, EncryptionKeyPair, Field)> {
+    let index_le = index.to_le_bytes();
+    let (note_private_key, encryption_seed, membership_blinding) = if index == 0 {
+        (
+            note_private_key.clone(),
+            encryption_private_key.0,
+            *membership_blinding,
+        )
+    } else {
+        let note_seed = hash_signature_with_domain_and_context(
+            &note_private_key.0,
+            SUB_ACCOUNT_NOTE_KEY_DOMAIN,
+            &index_le,
+        );
+        let blinding_seed = hash_signature_with_domain_and_context(
+            &membership_blinding.to_le_bytes(),
+            SUB_ACCOUNT_MEMBERSHIP_BLINDING_DOMAIN,
+            &index_le,
+        );
+        (
+            NotePrivateKey(reduce_to_bn254(&note_seed)),
+            hash_signature_with_domain_and_context(
+                &encryption_private_key.0,
+                SUB_ACCOUNT_ENCRYPTION_KEY_DOMAIN,
+                &index_le,
+            ),
+            Field::try_from_le_bytes(reduce_to_bn254(&blinding_seed))?,
+        )
+    };
+
+    let note_public_key = NotePublicKey(
+        derive_public_key(&note_private_key.0)?
+            .try_into()
+            .map_err(|e: Vec<u8>| anyhow::anyhow!("Expected 32 bytes, but got {}", e.len()))?,
+    );
+    let secret = StaticSecret::from(encryption_seed);
+    let encryption_keypair = EncryptionKeyPair {
+        public: EncryptionPublicKey(PublicKey::from(&secret).to_bytes()),
+        private: EncryptionPrivateKey(secret.to_bytes()),
+    };
+    Ok((
+        NoteKeyPair {
+            private: note_private_key,
+            public: note_public_key,
+        },
+        encryption_keypair,
+        membership_blinding,
+    ))
+}
+
+/// Reduces a 32-byte hash to a BN254 scalar (little-endian).
+fn reduce_to_bn254(bytes: &[u8; 32]) -> [u8; 32] {
+    let field = Fr::from_le_bytes_mod_order(bytes);
+    let mut result = [0u8; 32];
+    field
+        .serialize_compressed(&mut result[..])
+        .expect("Serialization failed");
+    result
+}
+
 /// Encryption key derivation (X25519). Used for off-chain note
 /// encryption/decryption Derive X25519 encryption keypair deterministically
 /// from a Freighter signature.
@@ -179,6 +363,23 @@ fn derive_note_private_key(signature: &KeyDerivationSignature) -> Result<NotePri
     Ok(NotePrivateKey(result))
 }
 
+/// Derive the outgoing viewing key from the note private key.
+///
+/// It is a one-way function of the note key, so it is recovered with the
+/// other keys from `KEY_DERIVATION_MESSAGE` and can be handed to an auditor
+/// of sent history without granting spend authority.
+///
+/// # Derivation
+/// ```text
+/// note private key (32 bytes) → SHA-256 → outgoing viewing key (32 bytes)
+/// ```
+pub fn derive_outgoing_viewing_key(note_private_key: &NotePrivateKey) -> OutgoingViewingKey {
+    OutgoingViewingKey(hash_signature_with_domain(
+        &note_private_key.0,
+        OUTGOING_VIEWING_KEY_DOMAIN,
+    ))
+}
+
 fn hash_signature_with_domain(signature: &[u8], domain: &[u8]) -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(domain);
@@ -245,36 +446,200 @@ pub fn encrypt_output_note(
     encrypt_note_data(recipient_pubkey.as_ref(), &plaintext)
 }
 
+/// Encrypt output note data for on-chain storage, with a memo and a sender
+/// copy.
+///
+/// The recipient plaintext is `amount (16 bytes LE) || blinding (32 bytes) ||
+/// memo field (128 bytes)`; the memo field is always present, so outputs with
+/// and without a memo are the same size. The output additionally carries the
+/// recipient keys, amount and blinding sealed under `outgoing_viewing_key`,
+/// followed by the spend record of `spent_inputs` (at most
+/// [`SPEND_RECORD_INPUTS`]), which [`decrypt_outgoing_note`] opens.
+pub fn encrypt_output_note_with_ovk(
+    recipient_note_pubkey: &NotePublicKey,
+    recipient_enc_pubkey: &EncryptionPublicKey,
+    amount: NoteAmount,
+    blinding: &Field,
+    memo: Option<&str>,
+    spent_inputs: &[SpentInput],
+    outgoing_viewing_key: &OutgoingViewingKey,
+) -> Result<Vec<u8>> {
+    if spent_inputs.len() > SPEND_RECORD_INPUTS {
+        return Err(anyhow!(
+            "spend record holds at most {SPEND_RECORD_INPUTS} inputs, got {}",
+            spent_inputs.len()
+        ));
+    }
+
+    let mut plaintext = [0u8; NOTE_PLAINTEXT_WITH_MEMO_LEN];
+    plaintext[..16].copy_from_slice(&amount.to_le_bytes());
+    plaintext[16..NOTE_PLAINTEXT_LEN].copy_from_slice(&blinding.to_le_bytes());
+    plaintext[NOTE_PLAINTEXT_LEN..].copy_from_slice(&memo_field(memo)?);
+
+    let mut outgoing = [0u8; OUTGOING_PLAINTEXT_WITH_SPENDS_LEN];
+    outgoing[..32].copy_from_slice(recipient_note_pubkey.as_ref());
+    outgoing[32..64].copy_from_slice(recipient_enc_pubkey.as_ref());
+    outgoing[64..80].copy_from_slice(&amount.to_le_bytes());
+    outgoing[80..OUTGOING_PLAINTEXT_LEN].copy_from_slice(&blinding.to_le_bytes());
+    let record = &mut outgoing[OUTGOING_PLAINTEXT_LEN..];
+    for (slot, input) in record.chunks_exact_mut(64).zip(spent_inputs) {
+        slot[..32].copy_from_slice(&input.commitment.to_le_bytes());
+        slot[32..].copy_from_slice(&input.nullifier.to_le_bytes());
+    }
+
+    seal_note_data(
+        recipient_enc_pubkey.as_ref(),
+        &plaintext,
+        Some((outgoing_viewing_key, &outgoing)),
+    )
+}
+
+/// Open the sender copy of an encrypted output.
+///
+/// Returns `Ok(None)` if the output carries no sender copy or it was sealed
+/// under a different outgoing viewing key.
+pub fn decrypt_outgoing_note(
+    outgoing_viewing_key: &OutgoingViewingKey,
+    encrypted_output: &[u8],
+) -> Result<Option<OutgoingNote>> {
+    let Some(EncryptedOutputParts {
+        incoming,
+        outgoing: Some(outgoing),
+        ..
+    }) = split_encrypted_output(encrypted_output)
+    else {
+        return Ok(None);
+    };
+
+    let cipher =
+        XSalsa20Poly1305::new((&outgoing_cipher_key(outgoing_viewing_key, &incoming[..32])).into());
+    let mut nonce_array = [0u8; 24];
+    nonce_array.copy_from_slice(&outgoing[..24]);
+    let Ok(plaintext) = cipher.decrypt(&Nonce::from(nonce_array), &outgoing[24..]) else {
+        return Ok(None);
+    };
+    let spent_inputs = match plaintext.len() {
+        OUTGOING_PLAINTEXT_LEN => Vec::new(),
+        OUTGOING_PLAINTEXT_WITH_SPENDS_LEN => {
+            parse_spend_record(&plaintext[OUTGOING_PLAINTEXT_LEN..])?
+        }
+        len => {
+            return Err(anyhow!(
+                "Decrypted sender copy must be {OUTGOING_PLAINTEXT_LEN} or \
+                 {OUTGOING_PLAINTEXT_WITH_SPENDS_LEN} bytes, got {len}"
+            ));
+        }
+    };
+
+    let mut note_key = [0u8; 32];
+    note_key.copy_from_slice(&plaintext[..32]);
+    let mut enc_key = [0u8; 32];
+    enc_key.copy_from_slice(&plaintext[32..64]);
+    let mut amount_le = [0u8; 16];
+    amount_le.copy_from_slice(&plaintext[64..80]);
+    let mut blinding_le = [0u8; 32];
+    blinding_le.copy_from_slice(&plaintext[80..OUTGOING_PLAINTEXT_LEN]);
+
+    Ok(Some(OutgoingNote {
+        recipient_note_public_key: NotePublicKey(note_key),
+        recipient_encryption_public_key: EncryptionPublicKey(enc_key),
+        amount: NoteAmount::from(u128::from_le_bytes(amount_le)),
+        blinding: Field::try_from_le_bytes(blinding_le)?,
+        spent_inputs,
+    }))
+}
+
+/// Inputs of a spend record, skipping the all-zero slots of dummy inputs.
+fn parse_spend_record(record: &[u8]) -> Result<Vec<SpentInput>> {
+    let mut inputs = Vec::with_capacity(SPEND_RECORD_INPUTS);
+    for slot in record.chunks_exact(64) {
+        if slot.iter().all(|b| *b == 0) {
+            continue;
+        }
+        let mut commitment = [0u8; 32];
+        commitment.copy_from_slice(&slot[..32]);
+        let mut nullifier = [0u8; 32];
+        nullifier.copy_from_slice(&slot[32..]);
+        inputs.push(SpentInput {
+            commitment: Field::try_from_le_bytes(commitment)?,
+            nullifier: Field::try_from_le_bytes(nullifier)?,
+        });
+    }
+    Ok(inputs)
+}
+
+/// Check that `memo` fits in an encrypted output.
+pub fn validate_memo(memo: &str) -> Result<()> {
+    if memo.len() > MAX_MEMO_LEN {
+        return Err(anyhow!(
+            "memo is {} bytes, at most {MAX_MEMO_LEN} are allowed",
+            memo.len()
+        ));
+    }
+    Ok(())
+}
+
 /// Decrypt output note data from on-chain storage.
 ///
 /// Returns `Ok(None)` if the ciphertext is not addressed to the given private
 /// key.
 ///
-/// Expected plaintext format: `amount (16 bytes LE) || blinding (32 bytes LE)`.
+/// Expected plaintext format: `amount (16 bytes LE) || blinding (32 bytes LE)`,
+/// optionally followed by the memo field.
 pub fn decrypt_output_note(
     recipient_privkey: &EncryptionPrivateKey,
     encrypted_output: &[u8],
-) -> Result<Option<(NoteAmount, Field)>> {
+) -> Result<Option<OutputNotePlaintext>> {
     let plaintext = decrypt_note_data(recipient_privkey.as_ref(), encrypted_output)?;
     if plaintext.is_empty() {
         return Ok(None);
     }
-    if plaintext.len() != 48 {
-        return Err(anyhow!(
-            "Decrypted plaintext must be 48 bytes, got {}",
-            plaintext.len()
-        ));
-    }
+    let memo = match plaintext.len() {
+        NOTE_PLAINTEXT_LEN => None,
+        NOTE_PLAINTEXT_WITH_MEMO_LEN => parse_memo_field(&plaintext[NOTE_PLAINTEXT_LEN..]),
+        len => {
+            return Err(anyhow!(
+                "Decrypted plaintext must be {NOTE_PLAINTEXT_LEN} or \
+                 {NOTE_PLAINTEXT_WITH_MEMO_LEN} bytes, got {len}"
+            ));
+        }
+    };
 
     let mut amount_le = [0u8; 16];
     amount_le.copy_from_slice(&plaintext[..16]);
     let amount = NoteAmount::from(u128::from_le_bytes(amount_le));
 
     let mut blinding_le = [0u8; 32];
-    blinding_le.copy_from_slice(&plaintext[16..]);
+    blinding_le.copy_from_slice(&plaintext[16..NOTE_PLAINTEXT_LEN]);
     let blinding = Field::try_from_le_bytes(blinding_le)?;
 
-    Ok(Some((amount, blinding)))
+    Ok(Some(OutputNotePlaintext {
+        amount,
+        blinding,
+        memo,
+    }))
+}
+
+/// Length-prefixed, zero-padded memo field.
+fn memo_field(memo: Option<&str>) -> Result<[u8; MEMO_FIELD_LEN]> {
+    let mut field = [0u8; MEMO_FIELD_LEN];
+    if let Some(memo) = memo {
+        validate_memo(memo)?;
+        field[0] = u8::try_from(memo.len()).map_err(|_| anyhow!("memo length exceeds u8"))?;
+        field[1..=memo.len()].copy_from_slice(memo.as_bytes());
+    }
+    Ok(field)
+}
+
+/// Memo from a memo field. A malformed field only loses the memo, never the
+/// note.
+fn parse_memo_field(field: &[u8]) -> Option<String> {
+    let (&len, body) = field.split_first()?;
+    let memo = body.get(..usize::from(len))?;
+    if memo.is_empty() {
+        return None;
+    }
+    String::from_utf8(memo.to_vec()).ok()
 }
 
 /// Encrypt note data using X25519-XSalsa20-Poly1305 (NaCl crypto_box).
@@ -284,25 +649,50 @@ pub fn decrypt_output_note(
 ///
 /// # Output Format
 /// ```text
-/// [ephemeral_pubkey (32)] [nonce (24)] [ciphertext (48) + tag (16)]
-/// Total: 120 bytes minimum
+/// [version (1)] [view_tag (2)] [ephemeral_pubkey (32)] [nonce (24)] [ciphertext (48) + tag (16)]
+/// Total: 123 bytes
 /// ```
 ///
+/// Outputs with a sender copy (see [`seal_note_data`]) use version 3 and
+/// append `[outgoing_nonce (24)] [outgoing_ciphertext (240) + tag (16)]`,
+/// 531 bytes in total with a memo field. Version 2 outputs, read but no
+/// longer written, carry a 112-byte sender copy without a spend record.
+///
+/// The view tag is `SHA-256(VIEW_TAG_DOMAIN || shared_secret)[..2]`, so a
+/// scanner can reject outputs addressed to someone else after the ECDH and
+/// one hash, without attempting the AEAD.
+///
 /// # Arguments
 /// * `recipient_pubkey_bytes` - Recipient's X25519 encryption public key (32
 ///   bytes)
 /// * `plaintext` - Note data: `[amount (16 bytes LE)] [blinding (32 bytes)]` =
-///   48 bytes
+///   48 bytes, optionally followed by the 128-byte memo field
 ///
 /// # Returns
-/// Encrypted data (120 bytes)
+/// Encrypted data (123 bytes, 251 with a memo field)
 fn encrypt_note_data(recipient_pubkey_bytes: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    seal_note_data(recipient_pubkey_bytes, plaintext, None)
+}
+
+/// [`encrypt_note_data`], optionally appending a sender copy sealed under an
+/// outgoing viewing key.
+///
+/// The sender copy key is `SHA-256(OUTGOING_CIPHER_KEY_DOMAIN || ovk ||
+/// ephemeral_pubkey)`, so it is unique per output and needs no ECDH.
+fn seal_note_data(
+    recipient_pubkey_bytes: &[u8],
+    plaintext: &[u8],
+    outgoing: Option<(
+        &OutgoingViewingKey,
+        &[u8; OUTGOING_PLAINTEXT_WITH_SPENDS_LEN],
+    )>,
+) -> Result<Vec<u8>> {
     if recipient_pubkey_bytes.len() != 32 {
         return Err(anyhow!("Recipient public key must be 32 bytes"));
     }
-    if plaintext.len() != 48 {
+    if plaintext.len() != NOTE_PLAINTEXT_LEN && plaintext.len() != NOTE_PLAINTEXT_WITH_MEMO_LEN {
         return Err(anyhow!(
-            "Plaintext must be 48 bytes (16 amount + 32 blinding)"
+            "Plaintext must be 48 bytes (16 amount + 32 blinding), or 176 with a memo field"
         ));
     }
 
@@ -335,24 +725,48 @@ fn encrypt_note_data(recipient_pubkey_bytes: &[u8], plaintext: &[u8]) -> Result<
         .encrypt(&nonce, plaintext)
         .map_err(|e| anyhow!("Encryption failed: {:?}", e))?;
 
-    // Pack: [ephemeral_pubkey (32)] [nonce (24)] [ciphertext + tag]
-    // 32 (pubkey) + 24 (nonce) = 56 bytes overhead
+    // Pack: [version (1)] [view_tag (2)] [ephemeral_pubkey (32)] [nonce (24)]
+    // [ciphertext + tag] [sender copy]
+    // 1 (version) + 2 (view tag) + 32 (pubkey) + 24 (nonce) = 59 bytes overhead
     let capacity = ciphertext
         .len()
-        .checked_add(56)
+        .checked_add(59)
+        .and_then(|len| len.checked_add(OUTGOING_SECTION_WITH_SPENDS_LEN))
         .expect("Integer overflow on encryption output size");
     let mut result = Vec::with_capacity(capacity);
+    result.push(if outgoing.is_some() {
+        ENCRYPTED_OUTPUT_VERSION_SPEND_RECORD
+    } else {
+        ENCRYPTED_OUTPUT_VERSION_VIEW_TAG
+    });
+    result.extend_from_slice(&view_tag(shared_secret.as_bytes()));
     result.extend_from_slice(ephemeral_public.as_bytes());
     result.extend_from_slice(&nonce_bytes);
     result.extend_from_slice(&ciphertext);
 
+    if let Some((outgoing_viewing_key, outgoing_plaintext)) = outgoing {
+        let cipher = XSalsa20Poly1305::new(
+            (&outgoing_cipher_key(outgoing_viewing_key, ephemeral_public.as_bytes())).into(),
+        );
+        let mut outgoing_nonce = [0u8; 24];
+        getrandom::getrandom(&mut outgoing_nonce)
+            .map_err(|e| anyhow!("Failed to generate nonce: {}", e))?;
+        let sealed = cipher
+            .encrypt(&Nonce::from(outgoing_nonce), outgoing_plaintext.as_slice())
+            .map_err(|e| anyhow!("Encryption failed: {:?}", e))?;
+        result.extend_from_slice(&outgoing_nonce);
+        result.extend_from_slice(&sealed);
+    }
+
     Ok(result)
 }
 
 /// Decrypt note data using X25519-XSalsa20-Poly1305.
 ///
 /// When scanning for notes addressed to us, we try to decrypt each encrypted
-/// output. If decryption succeeds, the note was sent to us.
+/// output. If decryption succeeds, the note was sent to us. View-tagged
+/// outputs whose tag does not match are rejected before the AEAD; legacy
+/// untagged outputs always go through it.
 ///
 /// # Arguments
 /// * `private_key_bytes` - Our X25519 encryption private key (32 bytes)
@@ -368,10 +782,21 @@ fn decrypt_note_data(private_key_bytes: &[u8], encrypted_data: &[u8]) -> Result<
 
     // Minimum size: ephemeral_pubkey (32) + nonce (24) + min ciphertext (48) + tag
     // (16) = 120
-    if encrypted_data.len() < 120 {
+    if encrypted_data.len() < LEGACY_ENCRYPTED_OUTPUT_LEN {
         return Err(anyhow!("Encrypted data too short"));
     }
 
+    // A format this build does not know, or a truncated one: not a note we
+    // can open.
+    let Some(EncryptedOutputParts {
+        view_tag: expected_view_tag,
+        incoming: encrypted_data,
+        ..
+    }) = split_encrypted_output(encrypted_data)
+    else {
+        return Ok(Vec::new());
+    };
+
     // Extract components
     let ephemeral_pubkey = &encrypted_data[0..32];
     let nonce_bytes = &encrypted_data[32..56];
@@ -389,6 +814,10 @@ fn decrypt_note_data(private_key_bytes: &[u8], encrypted_data: &[u8]) -> Result<
     );
     let shared_secret = our_secret.diffie_hellman(&ephemeral_public);
 
+    if expected_view_tag.is_some_and(|tag| tag != view_tag(shared_secret.as_bytes()).as_slice()) {
+        return Ok(Vec::new());
+    }
+
     // Setup XSalsa20Poly1305 cipher
     let cipher = XSalsa20Poly1305::new(shared_secret.as_bytes().into());
 
@@ -407,6 +836,88 @@ fn decrypt_note_data(private_key_bytes: &[u8], encrypted_data: &[u8]) -> Result<
     }
 }
 
+/// Sections of an encrypted output, by format version.
+struct EncryptedOutputParts<'a> {
+    /// `None` for legacy outputs.
+    view_tag: Option<&'a [u8]>,
+    /// `[ephemeral_pubkey (32)] [nonce (24)] [ciphertext + tag]`.
+    incoming: &'a [u8],
+    /// Sender copy, present in version 2 and 3 outputs.
+    outgoing: Option<&'a [u8]>,
+}
+
+fn split_encrypted_output(encrypted_data: &[u8]) -> Option<EncryptedOutputParts<'_>> {
+    if encrypted_data.len() == LEGACY_ENCRYPTED_OUTPUT_LEN {
+        return Some(EncryptedOutputParts {
+            view_tag: None,
+            incoming: encrypted_data,
+            outgoing: None,
+        });
+    }
+    match encrypted_data.split_first()? {
+        (&ENCRYPTED_OUTPUT_VERSION_VIEW_TAG, rest) if rest.len() >= VIEW_TAGGED_BODY_MIN_LEN => {
+            let (tag, incoming) = rest.split_at(VIEW_TAG_LEN);
+            Some(EncryptedOutputParts {
+                view_tag: Some(tag),
+                incoming,
+                outgoing: None,
+            })
+        }
+        (&ENCRYPTED_OUTPUT_VERSION_OUTGOING, rest) if rest.len() >= OUTGOING_BODY_MIN_LEN => {
+            Some(split_with_sender_copy(rest, OUTGOING_SECTION_LEN))
+        }
+        (&ENCRYPTED_OUTPUT_VERSION_SPEND_RECORD, rest)
+            if rest.len() >= SPEND_RECORD_BODY_MIN_LEN =>
+        {
+            Some(split_with_sender_copy(
+                rest,
+                OUTGOING_SECTION_WITH_SPENDS_LEN,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Splits a body holding a sender copy of `outgoing_len` bytes. The sender copy
+/// is fixed-size per version; the recipient part in front of it grows with the
+/// memo field.
+fn split_with_sender_copy(rest: &[u8], outgoing_len: usize) -> EncryptedOutputParts<'_> {
+    let (tag, body) = rest.split_at(VIEW_TAG_LEN);
+    let incoming_len = body
+        .len()
+        .checked_sub(outgoing_len)
+        .expect("body holds the sender copy");
+    let (incoming, outgoing) = body.split_at(incoming_len);
+    EncryptedOutputParts {
+        view_tag: Some(tag),
+        incoming,
+        outgoing: Some(outgoing),
+    }
+}
+
+/// Symmetric key of the sender copy of the output with `ephemeral_pubkey`.
+fn outgoing_cipher_key(
+    outgoing_viewing_key: &OutgoingViewingKey,
+    ephemeral_pubkey: &[u8],
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(OUTGOING_CIPHER_KEY_DOMAIN);
+    hasher.update(outgoing_viewing_key.0);
+    hasher.update(ephemeral_pubkey);
+    hasher.finalize().into()
+}
+
+/// View tag of an output encrypted under `shared_secret`.
+fn view_tag(shared_secret: &[u8; 32]) -> [u8; VIEW_TAG_LEN] {
+    let mut hasher = Sha256::new();
+    hasher.update(VIEW_TAG_DOMAIN);
+    hasher.update(shared_secret);
+    let digest: [u8; 32] = hasher.finalize().into();
+    let mut tag = [0u8; VIEW_TAG_LEN];
+    tag.copy_from_slice(&digest[..VIEW_TAG_LEN]);
+    tag
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -432,6 +943,32 @@ mod tests {
         assert_ne!(note_key.0, enc_key.private.0);
     }
 
+    #[test]
+    fn test_sub_account_keys_are_indexed_and_domain_separated() -> Result<()> {
+        let signature = KeyDerivationSignature(vec![7u8; 64]);
+        let (note, enc) = derive_encryption_and_note_keypairs(signature.clone())?;
+        let blinding = derive_membership_blinding(&signature, "testnet")?;
+        let derive = |index| derive_sub_account_keys(&note.private, &enc.private, &blinding, index);
+
+        let (note0, enc0, blinding0) = derive(0)?;
+        assert_eq!(note0.public.0, note.public.0);
+        assert_eq!(enc0.public.0, enc.public.0);
+        assert_eq!(blinding0, blinding);
+
+        let (note1, enc1, blinding1) = derive(1)?;
+        let (note1_again, _, _) = derive(1)?;
+        let (note2, enc2, blinding2) = derive(2)?;
+        assert_eq!(note1.public.0, note1_again.public.0);
+        assert_ne!(note1.public.0, note.public.0);
+        assert_ne!(note1.public.0, note2.public.0);
+        assert_ne!(enc1.public.0, enc.public.0);
+        assert_ne!(enc1.public.0, enc2.public.0);
+        assert_ne!(blinding1, blinding);
+        assert_ne!(blinding1, blinding2);
+        assert_ne!(note1.private.0, enc1.private.0);
+        Ok(())
+    }
+
     #[test]
     fn test_membership_blinding_is_deterministic_per_network() {
         let signature = KeyDerivationSignature(vec![8u8; 64]);
@@ -516,6 +1053,90 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[test]
+    fn test_view_tagged_output_layout_and_legacy_compatibility() {
+        let keys = derive_keypair_from_signature(&KeyDerivationSignature(vec![6u8; 64]))
+            .expect("Derivation failed");
+        let plaintext = [11u8; 48];
+        let encrypted =
+            encrypt_note_data(keys.public.as_ref(), &plaintext).expect("Encryption failed");
+        assert_eq!(encrypted.len(), 123);
+        assert_eq!(encrypted[0], ENCRYPTED_OUTPUT_VERSION_VIEW_TAG);
+
+        // Without the version byte and tag, the body is a legacy output under
+        // the same shared secret.
+        const HEADER_LEN: usize = 1 + VIEW_TAG_LEN;
+        let legacy = &encrypted[HEADER_LEN..];
+        assert_eq!(legacy.len(), LEGACY_ENCRYPTED_OUTPUT_LEN);
+        let decrypted = decrypt_note_data(keys.private.as_ref(), legacy).expect("legacy decrypt");
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn test_mismatched_view_tag_or_unknown_version_is_skipped() {
+        let keys = derive_keypair_from_signature(&KeyDerivationSignature(vec![6u8; 64]))
+            .expect("Derivation failed");
+        let encrypted =
+            encrypt_note_data(keys.public.as_ref(), &[11u8; 48]).expect("Encryption failed");
+
+        let mut wrong_tag = encrypted.clone();
+        wrong_tag[1] ^= 0xff;
+        let decrypted = decrypt_note_data(keys.private.as_ref(), &wrong_tag).expect("decrypt");
+        assert!(decrypted.is_empty());
+
+        let mut future = encrypted;
+        future[0] = 0xff;
+        let decrypted = decrypt_note_data(keys.private.as_ref(), &future).expect("decrypt");
+        assert!(decrypted.is_empty());
+    }
+
+    #[test]
+    fn test_outgoing_viewing_key_recovers_sent_output() -> Result<()> {
+        let signature = KeyDerivationSignature(vec![10u8; 64]);
+        let sender_note_key = derive_note_private_key(&signature)?;
+        let ovk = derive_outgoing_viewing_key(&sender_note_key);
+        let other_ovk = derive_outgoing_viewing_key(&derive_note_private_key(
+            &KeyDerivationSignature(vec![12u8; 64]),
+        )?);
+        let recipient = derive_keypair_from_signature(&KeyDerivationSignature(vec![11u8; 64]))?;
+        let recipient_note_key = NotePublicKey([5u8; 32]);
+
+        let amount = NoteAmount::from(1234);
+        let mut blind_le = [0u8; 32];
+        blind_le[0] = 7;
+        let blinding = Field::try_from_le_bytes(blind_le)?;
+
+        let encrypted = encrypt_output_note_with_ovk(
+            &recipient_note_key,
+            &recipient.public,
+            amount,
+            &blinding,
+            None,
+            &[],
+            &ovk,
+        )?;
+        assert_eq!(encrypted.len(), 531);
+        assert_eq!(encrypted[0], ENCRYPTED_OUTPUT_VERSION_SPEND_RECORD);
+
+        // The recipient still opens it as usual.
+        let got =
+            decrypt_output_note(&recipient.private, &encrypted)?.expect("recipient should decrypt");
+        assert_eq!(got.amount, amount);
+        assert_eq!(got.blinding.to_le_bytes(), blinding.to_le_bytes());
+
+        let sent = decrypt_outgoing_note(&ovk, &encrypted)?.expect("sender should decrypt");
+        assert_eq!(sent.recipient_note_public_key.0, recipient_note_key.0);
+        assert_eq!(sent.recipient_encryption_public_key.0, recipient.public.0);
+        assert_eq!(sent.amount, amount);
+        assert_eq!(sent.blinding.to_le_bytes(), blinding.to_le_bytes());
+        assert!(sent.spent_inputs.is_empty());
+
+        assert!(decrypt_outgoing_note(&other_ovk, &encrypted)?.is_none());
+        let without_copy = encrypt_output_note(&recipient.public, amount, &blinding)?;
+        assert!(decrypt_outgoing_note(&ovk, &without_copy)?.is_none());
+        Ok(())
+    }
+
     #[test]
     fn test_decrypt_output_note_roundtrip() -> Result<()> {
         let recipient_sig = KeyDerivationSignature(vec![9u8; 64]);
@@ -530,8 +1151,82 @@ mod tests {
         let got = decrypt_output_note(&recip_keys.private, &encrypted)?
             .expect("should decrypt for recipient key");
 
-        assert_eq!(got.0, amount);
-        assert_eq!(got.1.to_le_bytes(), blinding.to_le_bytes());
+        assert_eq!(got.amount, amount);
+        assert_eq!(got.blinding.to_le_bytes(), blinding.to_le_bytes());
+        assert!(got.memo.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_memo_is_padded_and_roundtrips() -> Result<()> {
+        let recipient = derive_keypair_from_signature(&KeyDerivationSignature(vec![13u8; 64]))?;
+        let ovk = OutgoingViewingKey([1u8; 32]);
+        let note_key = NotePublicKey([2u8; 32]);
+        let amount = NoteAmount::from(7);
+        let mut blind_le = [0u8; 32];
+        blind_le[0] = 3;
+        let blinding = Field::try_from_le_bytes(blind_le)?;
+        let encrypt = |memo: Option<&str>| {
+            encrypt_output_note_with_ovk(
+                &note_key,
+                &recipient.public,
+                amount,
+                &blinding,
+                memo,
+                &[],
+                &ovk,
+            )
+        };
+
+        let long_memo = "x".repeat(MAX_MEMO_LEN);
+        let short = encrypt(Some("INV-1"))?;
+        let long = encrypt(Some(long_memo.as_str()))?;
+        let none = encrypt(None)?;
+        assert_eq!(short.len(), long.len());
+        assert_eq!(short.len(), none.len());
+
+        let got = decrypt_output_note(&recipient.private, &short)?.expect("should decrypt");
+        assert_eq!(got.memo.as_deref(), Some("INV-1"));
+        let got = decrypt_output_note(&recipient.private, &long)?.expect("should decrypt");
+        assert_eq!(got.memo, Some(long_memo));
+        let got = decrypt_output_note(&recipient.private, &none)?.expect("should decrypt");
+        assert!(got.memo.is_none());
+
+        assert!(encrypt(Some("x".repeat(MEMO_FIELD_LEN).as_str())).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_spend_record_names_real_inputs() -> Result<()> {
+        let recipient = derive_keypair_from_signature(&KeyDerivationSignature(vec![14u8; 64]))?;
+        let ovk = OutgoingViewingKey([4u8; 32]);
+        let spent = SpentInput {
+            commitment: Field::try_from_le_bytes([8u8; 32])?,
+            nullifier: Field::try_from_le_bytes([9u8; 32])?,
+        };
+
+        let encrypted = encrypt_output_note_with_ovk(
+            &NotePublicKey([2u8; 32]),
+            &recipient.public,
+            NoteAmount::from(5),
+            &Field::ONE,
+            None,
+            &[spent.clone()],
+            &ovk,
+        )?;
+        let opened = decrypt_outgoing_note(&ovk, &encrypted)?.expect("sender should decrypt");
+        assert_eq!(opened.spent_inputs, vec![spent.clone()]);
+
+        let too_many = encrypt_output_note_with_ovk(
+            &NotePublicKey([2u8; 32]),
+            &recipient.public,
+            NoteAmount::from(5),
+            &Field::ONE,
+            None,
+            &[spent.clone(), spent.clone(), spent],
+            &ovk,
+        );
+        assert!(too_many.is_err());
         Ok(())
     }
 }
@@ -3,8 +3,9 @@
 #![allow(clippy::needless_pass_by_value)]
 
 use crate::types::{
-    AspMembershipProof, AspNonMembershipProof, EncryptionPublicKey, ExtAmount, ExtData, Field,
-    NoteAmount, NotePrivateKey, NotePublicKey, PolicyFlags,
+    AspMembershipProof, AspNonMembershipProof, BabyJubJubPoint, EncryptionPublicKey, ExtAmount,
+    ExtData, Field, GlobalViewKeyCiphertext, GvkMode, NoteAmount, NotePrivateKey, NotePublicKey,
+    PolicyFlags,
 };
 use anyhow::{Result, anyhow, bail};
 use serde::{Deserialize, Serialize};
@@ -15,6 +16,8 @@ use crate::zk::{crypto, encryption, serialization::field_bytes_to_hex, types::Ci
 pub const N_INPUTS: usize = 2;
 /// Number of output note slots supported by the current circuit.
 pub const N_OUTPUTS: usize = 2;
+/// Public signals per GVK ciphertext: `R.x`, `R.y`, `c1`, `c2`, `c3`.
+const GVK_CIPHERTEXT_SIGNALS: usize = 5;
 
 /// Input note data for a pool transaction.
 ///
@@ -61,6 +64,10 @@ pub struct TransactOutput {
     ///
     /// If set, `recipient_note_pubkey` must also be set.
     pub recipient_encryption_pubkey: Option<EncryptionPublicKey>,
+    /// Optional memo encrypted to the recipient with the note, at most
+    /// [`encryption::MAX_MEMO_LEN`] bytes.
+    #[serde(default)]
+    pub memo: Option<String>,
 }
 
 /// Convenience bundle of values typically needed to submit a pool transaction.
@@ -86,6 +93,9 @@ pub struct PreparedTx {
     pub asp_membership_root: Field,
     /// ASP non-membership root used for the circuit public inputs.
     pub asp_non_membership_root: Field,
+    /// Global View Key mode the circuit inputs were built for; decides how
+    /// many GVK ciphertexts the proof exposes.
+    pub gvk_mode: GvkMode,
 }
 
 /// Full output of `transact()` and the wrapper flows.
@@ -146,6 +156,13 @@ pub struct TransactParams {
     pub smt_depth: u32,
     /// Pool ASP policy flags (selects the transact circuit).
     pub policy_flags: PolicyFlags,
+    /// Pool Global View Key mode (selects the GVK variant of the circuit).
+    #[serde(default)]
+    pub gvk_mode: GvkMode,
+    /// On-chain GVK authority key `D`. Required iff `gvk_mode` is not
+    /// [`GvkMode::Off`].
+    #[serde(default)]
+    pub admin_view_key: Option<BabyJubJubPoint>,
 }
 
 /// Parameters for a deposit transaction.
@@ -180,6 +197,13 @@ pub struct DepositParams {
     pub smt_depth: u32,
     /// Pool ASP policy flags (selects the transact circuit).
     pub policy_flags: PolicyFlags,
+    /// Pool Global View Key mode (selects the GVK variant of the circuit).
+    #[serde(default)]
+    pub gvk_mode: GvkMode,
+    /// On-chain GVK authority key `D`. Required iff `gvk_mode` is not
+    /// [`GvkMode::Off`].
+    #[serde(default)]
+    pub admin_view_key: Option<BabyJubJubPoint>,
 }
 
 /// Parameters for a withdrawal transaction.
@@ -221,6 +245,13 @@ pub struct WithdrawParams {
     pub smt_depth: u32,
     /// Pool ASP policy flags (selects the transact circuit).
     pub policy_flags: PolicyFlags,
+    /// Pool Global View Key mode (selects the GVK variant of the circuit).
+    #[serde(default)]
+    pub gvk_mode: GvkMode,
+    /// On-chain GVK authority key `D`. Required iff `gvk_mode` is not
+    /// [`GvkMode::Off`].
+    #[serde(default)]
+    pub admin_view_key: Option<BabyJubJubPoint>,
 }
 
 /// Parameters for a private transfer transaction.
@@ -258,6 +289,13 @@ pub struct TransferParams {
     pub smt_depth: u32,
     /// Pool ASP policy flags (selects the transact circuit).
     pub policy_flags: PolicyFlags,
+    /// Pool Global View Key mode (selects the GVK variant of the circuit).
+    #[serde(default)]
+    pub gvk_mode: GvkMode,
+    /// On-chain GVK authority key `D`. Required iff `gvk_mode` is not
+    /// [`GvkMode::Off`].
+    #[serde(default)]
+    pub admin_view_key: Option<BabyJubJubPoint>,
 }
 
 /// Deposit flow
@@ -277,6 +315,8 @@ where
         tree_depth,
         smt_depth,
         policy_flags,
+        gvk_mode,
+        admin_view_key,
     } = params;
 
     transact(
@@ -293,6 +333,8 @@ where
             tree_depth,
             smt_depth,
             policy_flags,
+            gvk_mode,
+            admin_view_key,
         },
         hash_ext_data,
     )
@@ -316,6 +358,8 @@ where
         tree_depth,
         smt_depth,
         policy_flags,
+        gvk_mode,
+        admin_view_key,
     } = params;
 
     let input_total = sum_note_amounts_inputs(&inputs)?;
@@ -344,12 +388,14 @@ where
                     blinding: change_blinding,
                     recipient_note_pubkey: None,
                     recipient_encryption_pubkey: None,
+                    memo: None,
                 },
                 TransactOutput {
                     amount: out1_amount,
                     blinding: dummy_blinding,
                     recipient_note_pubkey: None,
                     recipient_encryption_pubkey: None,
+                    memo: None,
                 },
             ]
         }
@@ -371,6 +417,8 @@ where
             tree_depth,
             smt_depth,
             policy_flags,
+            gvk_mode,
+            admin_view_key,
         },
         hash_ext_data,
     )
@@ -393,6 +441,8 @@ where
         tree_depth,
         smt_depth,
         policy_flags,
+        gvk_mode,
+        admin_view_key,
     } = params;
 
     transact(
@@ -409,6 +459,8 @@ where
             tree_depth,
             smt_depth,
             policy_flags,
+            gvk_mode,
+            admin_view_key,
         },
         hash_ext_data,
     )
@@ -437,6 +489,8 @@ where
         tree_depth,
         smt_depth,
         policy_flags,
+        gvk_mode,
+        admin_view_key,
     } = params;
 
     if tree_depth == 0 {
@@ -500,6 +554,18 @@ where
         }
         (false, None) => None,
     };
+    let admin_view_key = match (gvk_mode, admin_view_key) {
+        (GvkMode::Off, None) => None,
+        (GvkMode::Off, Some(_)) => {
+            return Err(anyhow!(
+                "admin_view_key must be omitted for a pool without a Global View Key"
+            ));
+        }
+        (mode, None) => {
+            return Err(anyhow!("admin_view_key is required for GVK mode {mode:?}"));
+        }
+        (_, Some(key)) => Some(key),
+    };
 
     if outputs.len() > N_OUTPUTS {
         return Err(anyhow!(
@@ -585,6 +651,7 @@ where
             blinding,
             recipient_note_pubkey: None,
             recipient_encryption_pubkey: None,
+            memo: None,
         });
     }
 
@@ -622,6 +689,9 @@ where
     let mut in_path_elements_hex: Vec<String> = Vec::with_capacity(in_path_elements_capacity);
 
     let mut input_nullifiers_fields: [Field; N_INPUTS] = [Field::ZERO; N_INPUTS];
+    // Real inputs, named in every output's spend record for watch-only
+    // viewers of this account.
+    let mut spent_inputs: Vec<encryption::SpentInput> = Vec::with_capacity(N_INPUTS);
 
     for (idx, inp) in input_slots.iter().enumerate() {
         let amount_field = note_amount_to_field(&inp.amount);
@@ -638,6 +708,16 @@ where
             .map_err(|v: Vec<u8>| anyhow!("nullifier: expected 32 bytes, got {}", v.len()))?;
         let nullifier_field = Field::try_from_le_bytes(nullifier_arr)?;
         input_nullifiers_fields[idx] = nullifier_field;
+        if !inp.amount.is_zero() {
+            let commitment_arr: [u8; 32] = commitment
+                .as_slice()
+                .try_into()
+                .map_err(|_| anyhow!("commitment: expected 32 bytes, got {}", commitment.len()))?;
+            spent_inputs.push(encryption::SpentInput {
+                commitment: Field::try_from_le_bytes(commitment_arr)?,
+                nullifier: nullifier_field,
+            });
+        }
 
         input_nullifiers_hex.push(field_to_circuit_hex(&nullifier_field)?);
         in_amount_hex.push(field_to_circuit_hex(&amount_field)?);
@@ -658,6 +738,7 @@ where
     let mut output_commitments_fields: [Field; N_OUTPUTS] = [Field::ZERO; N_OUTPUTS];
     let mut encrypted_outputs: [Vec<u8>; N_OUTPUTS] = [Vec::new(), Vec::new()];
 
+    let outgoing_viewing_key = encryption::derive_outgoing_viewing_key(&priv_key);
     for (idx, out) in output_slots.iter().enumerate() {
         let recipient_note_pubkey: [u8; 32] = out
             .recipient_note_pubkey
@@ -680,8 +761,15 @@ where
         let commitment_field = Field::try_from_le_bytes(commitment_arr)?;
         output_commitments_fields[idx] = commitment_field;
 
-        let enc =
-            encryption::encrypt_output_note(&recipient_enc_pubkey, out.amount, &out.blinding)?;
+        let enc = encryption::encrypt_output_note_with_ovk(
+            &NotePublicKey(recipient_note_pubkey),
+            &recipient_enc_pubkey,
+            out.amount,
+            &out.blinding,
+            out.memo.as_deref(),
+            &spent_inputs,
+            &outgoing_viewing_key,
+        )?;
         encrypted_outputs[idx] = enc;
 
         out_amount_hex.push(field_to_circuit_hex(&amount_field)?);
@@ -800,6 +888,21 @@ where
     let ext_data_hash_be = hash_ext_data(&ext_data)?;
     circuit.set_single("extDataHash", &be32_to_0x_hex(&ext_data_hash_be));
 
+    // GVK encryption inputs. The pool contract feeds `ext_data_hash` into the
+    // circuit's `nonce`, so each transaction encrypts under a fresh nonce.
+    if let Some(admin_view_key) = &admin_view_key {
+        circuit.set_array(
+            "D",
+            vec![
+                field_to_circuit_hex(&admin_view_key.x)?,
+                field_to_circuit_hex(&admin_view_key.y)?,
+            ],
+        );
+        circuit.set_single("nonce", &be32_to_0x_hex(&ext_data_hash_be));
+        circuit.set_array("inSalt", random_salts_hex(N_INPUTS)?);
+        circuit.set_array("outSalt", random_salts_hex(N_OUTPUTS)?);
+    }
+
     Ok(TransactArtifacts {
         circuit_inputs: circuit,
         ext_data,
@@ -817,10 +920,78 @@ where
                 .as_ref()
                 .map(|proof| proof.root)
                 .unwrap_or(Field::ZERO),
+            gvk_mode,
         },
     })
 }
 
+/// GVK ciphertexts exposed by a `policy_tx_gvk_*` proof, split into
+/// `(inputs, outputs)` in slot order.
+///
+/// Circom places public outputs ahead of the public inputs, field-major: every
+/// `R` pair, then every `c1`, `c2` and `c3`, with input ciphertexts first in
+/// traceable mode. `inputs` is empty unless `gvk_mode` is
+/// [`GvkMode::Traceable`], and both are empty for [`GvkMode::Off`].
+///
+/// `public_inputs_le` is the little-endian public-signal vector extracted
+/// from the witness.
+pub fn gvk_ciphertexts_from_public_inputs(
+    public_inputs_le: &[u8],
+    gvk_mode: GvkMode,
+) -> Result<(Vec<GlobalViewKeyCiphertext>, Vec<GlobalViewKeyCiphertext>)> {
+    let n_inputs = match gvk_mode {
+        GvkMode::Off => return Ok((Vec::new(), Vec::new())),
+        GvkMode::ViewOnly => 0,
+        GvkMode::Traceable => N_INPUTS,
+    };
+    let n_enc = n_inputs
+        .checked_add(N_OUTPUTS)
+        .ok_or_else(|| anyhow!("GVK ciphertext count overflow"))?;
+    let n_signals = n_enc
+        .checked_mul(GVK_CIPHERTEXT_SIGNALS)
+        .ok_or_else(|| anyhow!("GVK ciphertext count overflow"))?;
+
+    let signals = public_inputs_le
+        .chunks_exact(32)
+        .take(n_signals)
+        .map(|chunk| {
+            let bytes: [u8; 32] = chunk.try_into().expect("chunks_exact yields 32 bytes");
+            Field::try_from_le_bytes(bytes)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if signals.len() != n_signals {
+        bail!(
+            "expected at least {n_signals} public signals for GVK mode {gvk_mode:?}, got {}",
+            signals.len()
+        );
+    }
+
+    let (r, rest) = signals.split_at(n_enc.saturating_mul(2));
+    let (c1, rest) = rest.split_at(n_enc);
+    let (c2, c3) = rest.split_at(n_enc);
+    let mut ciphertexts: Vec<GlobalViewKeyCiphertext> = r
+        .chunks_exact(2)
+        .zip(c1)
+        .zip(c2)
+        .zip(c3)
+        .map(|(((r, c1), c2), c3)| GlobalViewKeyCiphertext {
+            r: BabyJubJubPoint { x: r[0], y: r[1] },
+            c1: *c1,
+            c2: *c2,
+            c3: *c3,
+        })
+        .collect();
+    let outputs = ciphertexts.split_off(n_inputs);
+
+    Ok((ciphertexts, outputs))
+}
+
+fn random_salts_hex(count: usize) -> Result<Vec<String>> {
+    (0..count)
+        .map(|_| field_to_circuit_hex(&encryption::generate_random_blinding()?))
+        .collect()
+}
+
 fn dummy_input(tree_depth: usize) -> Result<TransactInputNote> {
     let blinding = encryption::generate_random_blinding()?;
     Ok(TransactInputNote {
@@ -1033,6 +1204,36 @@ pub fn selective_disclosure_1(params: SelectiveDisclosure1Params) -> Result<Disc
     selective_disclosure(params.into())
 }
 
+/// Parameters for a `notePossession` proof.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotePossessionParams {
+    /// Note private key whose public key is being registered.
+    pub note_private_key: NotePrivateKey,
+    /// Registration binding (see `chain::possession_binding_offchain`).
+    pub binding: Field,
+}
+
+/// Generates circuit inputs for a `notePossession` proof.
+///
+/// The public key is derived from `note_private_key`, so the proof always
+/// covers the key the caller actually holds.
+pub fn note_possession(params: NotePossessionParams) -> Result<CircuitInputs> {
+    let public_key = crypto::derive_public_key(&params.note_private_key.0)?;
+    let public_key_arr: [u8; 32] = public_key
+        .try_into()
+        .map_err(|v: Vec<u8>| anyhow!("derive_public_key: expected 32 bytes, got {}", v.len()))?;
+
+    let mut circuit = CircuitInputs::new();
+    circuit.set_single("publicKey", &field_bytes_to_hex(&public_key_arr)?);
+    circuit.set_single("binding", &field_to_circuit_hex(&params.binding)?);
+    circuit.set_single(
+        "privateKey",
+        &field_bytes_to_hex(&params.note_private_key.0)?,
+    );
+    Ok(circuit)
+}
+
 fn sum_note_amounts_inputs(inputs: &[TransactInputNote]) -> Result<NoteAmount> {
     let mut sum = NoteAmount::ZERO;
     for n in inputs {
@@ -1102,12 +1303,15 @@ mod tests {
                     blinding: out_blinding,
                     recipient_note_pubkey: None,
                     recipient_encryption_pubkey: None,
+                    memo: None,
                 }],
                 membership_proof: Some(zero_membership(tree_depth_usize)),
                 non_membership_proof: Some(zero_non_membership(smt_depth_usize)),
                 tree_depth,
                 smt_depth,
                 policy_flags: PolicyFlags::ALLOWLIST | PolicyFlags::BLOCKLIST,
+                gvk_mode: GvkMode::Off,
+                admin_view_key: None,
             },
             |_| Ok([0u8; 32]),
         )
@@ -1150,6 +1354,14 @@ mod tests {
         // Encrypted outputs should be present for both slots.
         assert!(artifacts.ext_data.encrypted_output0.len() >= 112);
         assert!(artifacts.ext_data.encrypted_output1.len() >= 112);
+
+        // A deposit spends only dummy inputs, so its spend record is empty.
+        let ovk = encryption::derive_outgoing_viewing_key(&NotePrivateKey([1u8; 32]));
+        let sender_copy =
+            encryption::decrypt_outgoing_note(&ovk, &artifacts.ext_data.encrypted_output0)
+                .expect("open sender copy")
+                .expect("deposit outputs carry a sender copy");
+        assert!(sender_copy.spent_inputs.is_empty());
     }
 
     #[test]
@@ -1171,12 +1383,15 @@ mod tests {
                     blinding: Field::try_from_le_bytes([3u8; 32]).expect("field"),
                     recipient_note_pubkey: None,
                     recipient_encryption_pubkey: None,
+                    memo: None,
                 }],
                 membership_proof: None,
                 non_membership_proof: Some(zero_non_membership(smt_depth_usize)),
                 tree_depth,
                 smt_depth,
                 policy_flags: PolicyFlags::BLOCKLIST,
+                gvk_mode: GvkMode::Off,
+                admin_view_key: None,
             },
             |_| Ok([0u8; 32]),
         )
@@ -1215,12 +1430,15 @@ mod tests {
                     blinding: Field::try_from_le_bytes([3u8; 32]).expect("field"),
                     recipient_note_pubkey: None,
                     recipient_encryption_pubkey: None,
+                    memo: None,
                 }],
                 membership_proof: None,
                 non_membership_proof: None,
                 tree_depth,
                 smt_depth,
                 policy_flags: PolicyFlags::EMPTY,
+                gvk_mode: GvkMode::Off,
+                admin_view_key: None,
             },
             |_| Ok([0u8; 32]),
         )
@@ -1242,6 +1460,150 @@ mod tests {
         assert!(artifacts.prepared.asp_non_membership_root.is_zero());
     }
 
+    fn open_deposit_params(
+        gvk_mode: GvkMode,
+        admin_view_key: Option<BabyJubJubPoint>,
+    ) -> TransactParams {
+        TransactParams {
+            priv_key: NotePrivateKey([1u8; 32]),
+            encryption_pubkey: EncryptionPublicKey([2u8; 32]),
+            pool_root: Field::try_from_le_bytes([9u8; 32]).expect("field"),
+            ext_recipient: "POOL".into(),
+            ext_amount: ExtAmount::from(10),
+            inputs: Vec::new(),
+            outputs: vec![TransactOutput {
+                amount: NoteAmount::from(10),
+                blinding: Field::try_from_le_bytes([3u8; 32]).expect("field"),
+                recipient_note_pubkey: None,
+                recipient_encryption_pubkey: None,
+                memo: None,
+            }],
+            membership_proof: None,
+            non_membership_proof: None,
+            tree_depth: 10,
+            smt_depth: 10,
+            policy_flags: PolicyFlags::EMPTY,
+            gvk_mode,
+            admin_view_key,
+        }
+    }
+
+    fn test_admin_view_key() -> BabyJubJubPoint {
+        BabyJubJubPoint {
+            x: Field::try_from_le_bytes([7u8; 32]).expect("field"),
+            y: Field::try_from_le_bytes([11u8; 32]).expect("field"),
+        }
+    }
+
+    #[test]
+    fn gvk_transact_sets_encryption_inputs() {
+        let key = test_admin_view_key();
+        let artifacts = transact(open_deposit_params(GvkMode::Traceable, Some(key)), |_| {
+            Ok([0x42u8; 32])
+        })
+        .expect("gvk transact builds");
+        let signals = &artifacts.circuit_inputs.signals;
+
+        let Some(InputValue::Array(d)) = signals.get("D") else {
+            panic!("D should be an array");
+        };
+        assert_eq!(
+            d,
+            &vec![
+                field_to_circuit_hex(&key.x).expect("hex"),
+                field_to_circuit_hex(&key.y).expect("hex"),
+            ]
+        );
+        // The contract reuses `ext_data_hash` as the circuit nonce.
+        assert_eq!(signals.get("nonce"), signals.get("extDataHash"));
+        let Some(InputValue::Array(in_salt)) = signals.get("inSalt") else {
+            panic!("inSalt should be an array");
+        };
+        let Some(InputValue::Array(out_salt)) = signals.get("outSalt") else {
+            panic!("outSalt should be an array");
+        };
+        assert_eq!(in_salt.len(), N_INPUTS);
+        assert_eq!(out_salt.len(), N_OUTPUTS);
+        assert_eq!(artifacts.prepared.gvk_mode, GvkMode::Traceable);
+    }
+
+    #[test]
+    fn non_gvk_transact_omits_encryption_inputs() {
+        let artifacts = transact(open_deposit_params(GvkMode::Off, None), |_| Ok([0u8; 32]))
+            .expect("open transact builds");
+        let signals = &artifacts.circuit_inputs.signals;
+
+        for name in ["D", "nonce", "inSalt", "outSalt"] {
+            assert!(!signals.contains_key(name), "{name} should be omitted");
+        }
+        assert_eq!(artifacts.prepared.gvk_mode, GvkMode::Off);
+    }
+
+    #[test]
+    fn gvk_transact_rejects_mismatched_admin_view_key() {
+        assert!(
+            transact(open_deposit_params(GvkMode::ViewOnly, None), |_| Ok(
+                [0u8; 32]
+            ))
+            .is_err()
+        );
+        assert!(
+            transact(
+                open_deposit_params(GvkMode::Off, Some(test_admin_view_key())),
+                |_| Ok([0u8; 32]),
+            )
+            .is_err()
+        );
+    }
+
+    fn public_signals_le(values: impl IntoIterator<Item = u128>) -> Vec<u8> {
+        values
+            .into_iter()
+            .flat_map(|v| Field::from(NoteAmount::from(v)).to_le_bytes())
+            .collect()
+    }
+
+    fn signal(v: u128) -> Field {
+        Field::from(NoteAmount::from(v))
+    }
+
+    #[test]
+    fn gvk_ciphertexts_split_traceable_public_signals() {
+        // 4 ciphertexts: R pairs (8), then c1s, c2s, c3s (4 each), then the
+        // declared public inputs, which must be ignored.
+        let signals = public_signals_le((1..=20).chain([999, 998]));
+        let (inputs, outputs) =
+            gvk_ciphertexts_from_public_inputs(&signals, GvkMode::Traceable).expect("split");
+
+        assert_eq!(inputs.len(), N_INPUTS);
+        assert_eq!(outputs.len(), N_OUTPUTS);
+        assert_eq!(inputs[0].r.x, signal(1));
+        assert_eq!(inputs[0].r.y, signal(2));
+        assert_eq!(inputs[1].c1, signal(10));
+        assert_eq!(outputs[0].r.x, signal(5));
+        assert_eq!(outputs[0].c1, signal(11));
+        assert_eq!(outputs[0].c2, signal(15));
+        assert_eq!(outputs[1].c3, signal(20));
+    }
+
+    #[test]
+    fn gvk_ciphertexts_split_view_only_public_signals() {
+        let signals = public_signals_le(1..=10);
+        let (inputs, outputs) =
+            gvk_ciphertexts_from_public_inputs(&signals, GvkMode::ViewOnly).expect("split");
+
+        assert!(inputs.is_empty());
+        assert_eq!(outputs.len(), N_OUTPUTS);
+        assert_eq!(outputs[1].r.y, signal(4));
+        assert_eq!(outputs[1].c1, signal(6));
+        assert_eq!(outputs[1].c3, signal(10));
+
+        assert!(
+            gvk_ciphertexts_from_public_inputs(&public_signals_le(1..=9), GvkMode::ViewOnly)
+                .is_err()
+        );
+    }
+
     #[test]
     fn allowlist_transact_omits_blocklist_witness() {
         let tree_depth: u32 = 10;
@@ -1261,12 +1623,15 @@ mod tests {
                     blinding: Field::try_from_le_bytes([3u8; 32]).expect("field"),
                     recipient_note_pubkey: None,
                     recipient_encryption_pubkey: None,
+                    memo: None,
                 }],
                 membership_proof: Some(zero_membership(tree_depth_usize)),
                 non_membership_proof: None,
                 tree_depth,
                 smt_depth,
                 policy_flags: PolicyFlags::ALLOWLIST,
+                gvk_mode: GvkMode::Off,
+                admin_view_key: None,
             },
             |_| Ok([0u8; 32]),
         )
@@ -1307,12 +1672,15 @@ mod tests {
                     blinding: Field::try_from_le_bytes([3u8; 32]).expect("field"),
                     recipient_note_pubkey: None,
                     recipient_encryption_pubkey: None,
+                    memo: None,
                 }],
                 membership_proof: Some(zero_membership(tree_depth_usize)),
                 non_membership_proof: Some(zero_non_membership(smt_depth_usize)),
                 tree_depth,
                 smt_depth,
                 policy_flags: PolicyFlags::BLOCKLIST,
+                gvk_mode: GvkMode::Off,
+                admin_view_key: None,
             },
             |_| Ok([0u8; 32]),
         );
@@ -1339,12 +1707,15 @@ mod tests {
                     blinding: Field::try_from_le_bytes([3u8; 32]).expect("field"),
                     recipient_note_pubkey: None,
                     recipient_encryption_pubkey: None,
+                    memo: None,
                 }],
                 membership_proof: Some(zero_membership(tree_depth_usize)),
                 non_membership_proof: None,
                 tree_depth,
                 smt_depth,
                 policy_flags: PolicyFlags::ALLOWLIST | PolicyFlags::BLOCKLIST,
+                gvk_mode: GvkMode::Off,
+                admin_view_key: None,
             },
             |_| Ok([0u8; 32]),
         );
@@ -1371,12 +1742,15 @@ mod tests {
                     blinding: Field::try_from_le_bytes([3u8; 32]).expect("field"),
                     recipient_note_pubkey: None,
                     recipient_encryption_pubkey: None,
+                    memo: None,
                 }],
                 membership_proof: None,
                 non_membership_proof: Some(zero_non_membership(smt_depth_usize)),
                 tree_depth,
                 smt_depth,
                 policy_flags: PolicyFlags::ALLOWLIST | PolicyFlags::BLOCKLIST,
+                gvk_mode: GvkMode::Off,
+                admin_view_key: None,
             },
             |_| Ok([0u8; 32]),
         );
@@ -1415,6 +1789,8 @@ mod tests {
                 tree_depth,
                 smt_depth,
                 policy_flags: PolicyFlags::ALLOWLIST | PolicyFlags::BLOCKLIST,
+                gvk_mode: GvkMode::Off,
+                admin_view_key: None,
             },
             |_| Ok([0u8; 32]),
         )
@@ -1453,6 +1829,7 @@ mod tests {
             blinding: Field::try_from_le_bytes([7u8; 32]).expect("field"),
             recipient_note_pubkey: None,
             recipient_encryption_pubkey: None,
+            memo: None,
         };
 
         let res = transfer(
@@ -1468,6 +1845,8 @@ mod tests {
                 tree_depth,
                 smt_depth,
                 policy_flags: PolicyFlags::ALLOWLIST | PolicyFlags::BLOCKLIST,
+                gvk_mode: GvkMode::Off,
+                admin_view_key: None,
             },
             |_| Ok([0u8; 32]),
         );
@@ -1507,6 +1886,8 @@ mod tests {
                 tree_depth,
                 smt_depth,
                 policy_flags: PolicyFlags::ALLOWLIST | PolicyFlags::BLOCKLIST,
+                gvk_mode: GvkMode::Off,
+                admin_view_key: None,
             },
             |_| Ok([0u8; 32]),
         );
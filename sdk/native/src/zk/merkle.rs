@@ -88,6 +88,48 @@ fn hash_pair(left: Field, right: Field) -> Field {
     scalar_to_field(&hashed)
 }
 
+fn checked_depth(depth: u32) -> Result<usize> {
+    let depth = usize::try_from(depth).map_err(|_| anyhow!("tree depth too large"))?;
+    if depth == 0 || depth > 32 {
+        return Err(anyhow!("Depth must be between 1 and 32"));
+    }
+    Ok(depth)
+}
+
+/// Empty-subtree chain `empty[0..=depth]`, built from the same zero leaf as
+/// the contract.
+fn empty_subtrees(depth: usize) -> Result<Vec<Field>> {
+    let mut zero_leaf_be = crypto::zero_leaf();
+    zero_leaf_be.reverse();
+    let zero_leaf_le: [u8; 32] = zero_leaf_be
+        .try_into()
+        .map_err(|_| anyhow!("zero leaf: expected 32 bytes"))?;
+    let zero = Field::try_from_le_bytes(zero_leaf_le)?;
+
+    let empty_cap = depth
+        .checked_add(1)
+        .ok_or_else(|| anyhow!("depth overflow"))?;
+    let mut empty = Vec::with_capacity(empty_cap);
+    empty.push(zero);
+    for i in 0..depth {
+        empty.push(hash_pair(empty[i], empty[i]));
+    }
+    Ok(empty)
+}
+
+/// Index of the ancestor of leaf `index` at `level`.
+fn node_index(index: u64, level: usize) -> u64 {
+    let shift = u32::try_from(level).expect("level fits in u32");
+    index.checked_shr(shift).unwrap_or(0)
+}
+
+/// Path-index bits of leaf `index`, packed into a field element.
+fn path_indices_field(index: u64) -> Result<Field> {
+    let mut path_indices_le = [0u8; 32];
+    path_indices_le[..8].copy_from_slice(&index.to_le_bytes());
+    Field::try_from_le_bytes(path_indices_le)
+}
+
 /// Merkle proof data
 pub struct MerkleProof {
     /// Path elements
@@ -163,27 +205,8 @@ impl MerklePrefixTree {
     /// contract's `zero_leaf` value, and the computed root/proofs match the
     /// circuit's Poseidon2 merkle implementation.
     pub fn new(depth: u32, leaves: &[Field]) -> Result<Self> {
-        let depth = usize::try_from(depth).map_err(|_| anyhow!("tree depth too large"))?;
-        if depth == 0 || depth > 32 {
-            return Err(anyhow!("Depth must be between 1 and 32"));
-        }
-
-        // Build the empty-subtree chain using the same zero leaf as the contract.
-        let mut zero_leaf_be = crypto::zero_leaf();
-        zero_leaf_be.reverse();
-        let zero_leaf_le: [u8; 32] = zero_leaf_be
-            .try_into()
-            .map_err(|_| anyhow!("zero leaf: expected 32 bytes"))?;
-        let zero = Field::try_from_le_bytes(zero_leaf_le)?;
-
-        let empty_cap = depth
-            .checked_add(1)
-            .ok_or_else(|| anyhow!("depth overflow"))?;
-        let mut empty = Vec::with_capacity(empty_cap);
-        empty.push(zero);
-        for i in 0..depth {
-            empty.push(hash_pair(empty[i], empty[i]));
-        }
+        let depth = checked_depth(depth)?;
+        let empty = empty_subtrees(depth)?;
 
         let scalar_leaves = leaves.to_vec();
 
@@ -328,9 +351,7 @@ impl MerklePrefixTreeBuilt {
             current_index /= 2;
         }
 
-        let mut path_indices_le = [0u8; 32];
-        path_indices_le[..8].copy_from_slice(&path_indices_bits.to_le_bytes());
-        let path_indices = Field::try_from_le_bytes(path_indices_le)?;
+        let path_indices = path_indices_field(path_indices_bits)?;
 
         let root = self.root()?;
 
@@ -343,6 +364,339 @@ impl MerklePrefixTreeBuilt {
     }
 }
 
+/// Append-only Merkle frontier: the `O(depth)` state needed to extend a tree
+/// one leaf at a time, like the pool contract's `filled_subtrees`.
+///
+/// Together with [`MerkleWitness`] it keeps the root and the proofs of
+/// tracked leaves current without rehashing the prefix on every append.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleFrontier {
+    depth: usize,
+    leaf_count: u64,
+    /// `filled[level]` is the latest even-indexed node at `level`: the left
+    /// child the next odd node at that level is hashed with.
+    filled: Vec<Field>,
+    /// `edge[level]` is the node at `level` on the path of the latest leaf,
+    /// with missing leaves treated as empty; `edge[depth]` is the root.
+    edge: Vec<Field>,
+    /// See [`MerklePrefixTree::empty`].
+    empty: Vec<Field>,
+}
+
+impl MerkleFrontier {
+    /// Frontier of an empty tree of the given `depth`.
+    pub fn new(depth: u32) -> Result<Self> {
+        let depth = checked_depth(depth)?;
+        let empty = empty_subtrees(depth)?;
+        Ok(Self {
+            depth,
+            leaf_count: 0,
+            filled: empty[..depth].to_vec(),
+            edge: empty.clone(),
+            empty,
+        })
+    }
+
+    /// Frontier of an already built prefix tree, without rehashing it.
+    pub fn from_built(tree: &MerklePrefixTreeBuilt) -> Self {
+        let mut filled = tree.empty[..tree.depth].to_vec();
+        let mut edge = tree.empty.clone();
+        let leaf_count = u64::try_from(tree.leaf_count()).expect("leaf count fits in u64");
+        if let Some(last) = leaf_count.checked_sub(1) {
+            for (level, nodes) in tree.levels.iter().enumerate() {
+                let node = usize::try_from(node_index(last, level)).expect("node index fits");
+                edge[level] = nodes[node];
+                if level < tree.depth {
+                    filled[level] = nodes[node & !1];
+                }
+            }
+        }
+        Self {
+            depth: tree.depth,
+            leaf_count,
+            filled,
+            edge,
+            empty: tree.empty.clone(),
+        }
+    }
+
+    /// Restore a frontier from the values returned by [`Self::filled`] and
+    /// [`Self::edge`].
+    pub fn from_parts(
+        depth: u32,
+        leaf_count: u64,
+        filled: Vec<Field>,
+        edge: Vec<Field>,
+    ) -> Result<Self> {
+        let depth = checked_depth(depth)?;
+        let empty = empty_subtrees(depth)?;
+        if filled.len() != depth || edge.len() != empty.len() {
+            return Err(anyhow!(
+                "frontier of depth {depth} needs {depth} filled and {} edge nodes, got {} and {}",
+                empty.len(),
+                filled.len(),
+                edge.len()
+            ));
+        }
+        let frontier = Self {
+            depth,
+            leaf_count,
+            filled,
+            edge,
+            empty,
+        };
+        if leaf_count > frontier.capacity() {
+            return Err(anyhow!(
+                "frontier of depth {depth} cannot hold {leaf_count} leaves"
+            ));
+        }
+        Ok(frontier)
+    }
+
+    /// Full Merkle depth of the tree.
+    pub fn depth(&self) -> u32 {
+        u32::try_from(self.depth).expect("depth fits in u32")
+    }
+
+    /// Number of leaves appended so far.
+    pub fn leaf_count(&self) -> u64 {
+        self.leaf_count
+    }
+
+    /// See [`Self::from_parts`].
+    pub fn filled(&self) -> &[Field] {
+        &self.filled
+    }
+
+    /// See [`Self::from_parts`].
+    pub fn edge(&self) -> &[Field] {
+        &self.edge
+    }
+
+    /// Full-depth Merkle root of the leaves appended so far.
+    pub fn root(&self) -> Field {
+        self.edge[self.depth]
+    }
+
+    fn capacity(&self) -> u64 {
+        let depth = u32::try_from(self.depth).expect("depth fits in u32");
+        1u64.checked_shl(depth).expect("depth is at most 32")
+    }
+
+    /// Append the next leaf, hashing one path of `depth` nodes.
+    ///
+    /// Tracked [`MerkleWitness`]es must be [updated](MerkleWitness::update)
+    /// after every append.
+    pub fn append(&mut self, leaf: Field) -> Result<()> {
+        let index = self.leaf_count;
+        if index >= self.capacity() {
+            return Err(anyhow!("Merkle tree is full ({index} leaves)"));
+        }
+
+        let mut node = leaf;
+        self.edge[0] = leaf;
+        for (level, parent) in (0..self.depth).zip(1..) {
+            node = if node_index(index, level) & 1 == 0 {
+                self.filled[level] = node;
+                hash_pair(node, self.empty[level])
+            } else {
+                hash_pair(self.filled[level], node)
+            };
+            self.edge[parent] = node;
+        }
+
+        self.leaf_count = index
+            .checked_add(1)
+            .ok_or_else(|| anyhow!("leaf count overflow"))?;
+        Ok(())
+    }
+
+    /// First leaf index [`Self::witness`] needs for `leaf_index`.
+    ///
+    /// Leaves from this index up to [`Self::leaf_count`] share the smallest
+    /// subtree holding both `leaf_index` and the latest leaf; siblings above
+    /// that subtree come from the frontier itself. The window is a single
+    /// leaf for the leaf just appended and at most the whole tree for the
+    /// first one.
+    pub fn window_start(&self, leaf_index: u32) -> Result<u64> {
+        let (index, last) = self.index_and_last(leaf_index)?;
+        let split = split_level(index, last);
+        Ok(node_index(index, split)
+            .checked_shl(u32::try_from(split).expect("level fits in u32"))
+            .unwrap_or(0))
+    }
+
+    /// Compute the witness of an already appended leaf.
+    ///
+    /// `window` holds the leaves from [`Self::window_start`] up to
+    /// [`Self::leaf_count`].
+    pub fn witness(&self, leaf_index: u32, window: &[Field]) -> Result<MerkleWitness> {
+        let (index, last) = self.index_and_last(leaf_index)?;
+        let start = self.window_start(leaf_index)?;
+        let expected = self
+            .leaf_count
+            .checked_sub(start)
+            .ok_or_else(|| anyhow!("window start past the last leaf"))?;
+        if u64::try_from(window.len()).ok() != Some(expected) {
+            return Err(anyhow!(
+                "witness window: expected {expected} leaves from index {start}, got {}",
+                window.len()
+            ));
+        }
+
+        let split = split_level(index, last);
+        let mut path = Vec::with_capacity(self.depth);
+        if split > 0 {
+            let subtree = MerklePrefixTree::build_from_parts(
+                split,
+                window.to_vec(),
+                self.empty[..=split].to_vec(),
+            );
+            let offset = index
+                .checked_sub(start)
+                .ok_or_else(|| anyhow!("leaf index before window start"))?;
+            for level in 0..split {
+                let sibling = usize::try_from(node_index(offset, level) ^ 1)
+                    .map_err(|_| anyhow!("index too large"))?;
+                path.push(
+                    subtree.levels[level]
+                        .get(sibling)
+                        .copied()
+                        .unwrap_or(self.empty[level]),
+                );
+            }
+        }
+        // Above the window `leaf_index` shares its ancestors with the latest
+        // leaf: left siblings are filled subtrees, right siblings are empty.
+        for level in split..self.depth {
+            path.push(if node_index(last, level) & 1 == 1 {
+                self.filled[level]
+            } else {
+                self.empty[level]
+            });
+        }
+
+        Ok(MerkleWitness {
+            leaf_index,
+            leaf_count: self.leaf_count,
+            path,
+        })
+    }
+
+    fn index_and_last(&self, leaf_index: u32) -> Result<(u64, u64)> {
+        let index = u64::from(leaf_index);
+        let last = self
+            .leaf_count
+            .checked_sub(1)
+            .filter(|last| index <= *last)
+            .ok_or_else(|| {
+                anyhow!(
+                    "leaf index out of range: index={index}, leaves={}",
+                    self.leaf_count
+                )
+            })?;
+        Ok((index, last))
+    }
+}
+
+/// Lowest level at which leaves `a` and `b` have a common ancestor.
+fn split_level(a: u64, b: u64) -> usize {
+    match a ^ b {
+        0 => 0,
+        diff => usize::try_from(diff.ilog2())
+            .expect("level fits in usize")
+            .checked_add(1)
+            .expect("level overflow"),
+    }
+}
+
+/// Authentication path of one leaf, kept current by replaying the appends of
+/// a [`MerkleFrontier`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleWitness {
+    leaf_index: u32,
+    /// Frontier [`leaf_count`](MerkleFrontier::leaf_count) this path is
+    /// valid for.
+    leaf_count: u64,
+    path: Vec<Field>,
+}
+
+impl MerkleWitness {
+    /// Restore a witness from the values returned by its accessors.
+    pub fn from_parts(leaf_index: u32, leaf_count: u64, path: Vec<Field>) -> Self {
+        Self {
+            leaf_index,
+            leaf_count,
+            path,
+        }
+    }
+
+    /// Index of the witnessed leaf.
+    pub fn leaf_index(&self) -> u32 {
+        self.leaf_index
+    }
+
+    /// Number of leaves the path accounts for.
+    pub fn leaf_count(&self) -> u64 {
+        self.leaf_count
+    }
+
+    /// Sibling nodes from the leaf level up.
+    pub fn path(&self) -> &[Field] {
+        &self.path
+    }
+
+    /// Account for the leaf `frontier` appended last.
+    ///
+    /// The only sibling that changes is the one at the level where the new
+    /// leaf's path joins this one, and it is the frontier's edge node there.
+    pub fn update(&mut self, frontier: &MerkleFrontier) -> Result<()> {
+        if frontier.depth != self.path.len() {
+            return Err(anyhow!(
+                "witness depth {} does not match frontier depth {}",
+                self.path.len(),
+                frontier.depth
+            ));
+        }
+        if frontier.leaf_count == self.leaf_count {
+            return Ok(());
+        }
+        if self.leaf_count.checked_add(1) != Some(frontier.leaf_count) {
+            return Err(anyhow!(
+                "witness at {} leaves cannot catch up with a frontier at {}",
+                self.leaf_count,
+                frontier.leaf_count
+            ));
+        }
+
+        let appended = self.leaf_count;
+        let level = split_level(u64::from(self.leaf_index), appended)
+            .checked_sub(1)
+            .ok_or_else(|| anyhow!("witnessed leaf was appended again"))?;
+        self.path[level] = frontier.edge[level];
+        self.leaf_count = frontier.leaf_count;
+        Ok(())
+    }
+
+    /// Merkle proof against the current root of `frontier`.
+    pub fn proof(&self, frontier: &MerkleFrontier) -> Result<MerkleProof> {
+        if frontier.leaf_count != self.leaf_count || frontier.depth != self.path.len() {
+            return Err(anyhow!(
+                "witness for leaf {} is stale: {} leaves, frontier has {}",
+                self.leaf_index,
+                self.leaf_count,
+                frontier.leaf_count
+            ));
+        }
+        Ok(MerkleProof {
+            path_elements: self.path.clone(),
+            path_indices: path_indices_field(u64::from(self.leaf_index))?,
+            root: frontier.root(),
+            levels: self.path.len(),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -419,6 +773,114 @@ mod tests {
         }
     }
 
+    fn leaf(i: u8) -> Field {
+        Field::try_from_le_bytes([i; 32]).expect("field")
+    }
+
+    #[test]
+    fn frontier_matches_prefix_tree_after_every_append() {
+        let depth = 4u32;
+        let leaves: Vec<Field> = (1..=11).map(leaf).collect();
+        let mut frontier = MerkleFrontier::new(depth).expect("frontier");
+
+        for count in 0..=leaves.len() {
+            let built = MerklePrefixTree::new(depth, &leaves[..count])
+                .expect("new")
+                .into_built();
+            assert_eq!(
+                frontier.root(),
+                built.root().expect("root"),
+                "count={count}"
+            );
+            assert_eq!(
+                frontier,
+                MerkleFrontier::from_built(&built),
+                "count={count}"
+            );
+
+            if let Some(next) = leaves.get(count) {
+                frontier.append(*next).expect("append");
+            }
+        }
+
+        let restored = MerkleFrontier::from_parts(
+            frontier.depth(),
+            frontier.leaf_count(),
+            frontier.filled().to_vec(),
+            frontier.edge().to_vec(),
+        )
+        .expect("from_parts");
+        assert_eq!(restored, frontier);
+    }
+
+    #[test]
+    fn witnesses_follow_prefix_tree_proofs() {
+        let depth = 4u32;
+        let leaves: Vec<Field> = (1..=13).map(leaf).collect();
+        let mut frontier = MerkleFrontier::new(depth).expect("frontier");
+        let mut witnesses: Vec<MerkleWitness> = Vec::new();
+
+        for (index, next) in leaves.iter().enumerate() {
+            frontier.append(*next).expect("append");
+            for witness in &mut witnesses {
+                witness.update(&frontier).expect("update");
+            }
+            let index_u32 = u32::try_from(index).expect("index");
+            witnesses.push(frontier.witness(index_u32, &[*next]).expect("witness"));
+
+            let built = MerklePrefixTree::new(depth, &leaves[..=index])
+                .expect("new")
+                .into_built();
+            for witness in &witnesses {
+                let proof = witness.proof(&frontier).expect("proof");
+                let expected = built.proof(witness.leaf_index()).expect("proof");
+                assert_eq!(proof.root, expected.root, "index={index}");
+                assert_eq!(proof.path_elements, expected.path_elements, "index={index}");
+                assert_eq!(proof.path_indices, expected.path_indices, "index={index}");
+            }
+        }
+    }
+
+    #[test]
+    fn late_witness_uses_the_shared_subtree_window() {
+        let depth = 4u32;
+        let leaves: Vec<Field> = (1..=11).map(leaf).collect();
+        let mut frontier = MerkleFrontier::new(depth).expect("frontier");
+        for next in &leaves {
+            frontier.append(*next).expect("append");
+        }
+        let built = MerklePrefixTree::new(depth, &leaves)
+            .expect("new")
+            .into_built();
+
+        // Leaves 8..=10 share a level-2 subtree; 0..=7 only the root.
+        assert_eq!(frontier.window_start(9).expect("start"), 8);
+        assert_eq!(frontier.window_start(3).expect("start"), 0);
+        assert_eq!(frontier.window_start(10).expect("start"), 10);
+
+        for index in 0..11u32 {
+            let start =
+                usize::try_from(frontier.window_start(index).expect("start")).expect("start fits");
+            let witness = frontier.witness(index, &leaves[start..]).expect("witness");
+            assert_eq!(
+                witness.proof(&frontier).expect("proof").path_elements,
+                built.proof(index).expect("proof").path_elements,
+                "index={index}"
+            );
+        }
+
+        assert!(frontier.witness(9, &leaves[9..]).is_err());
+        assert!(frontier.window_start(11).is_err());
+    }
+
+    #[test]
+    fn full_frontier_rejects_append() {
+        let mut frontier = MerkleFrontier::new(1).expect("frontier");
+        frontier.append(leaf(1)).expect("append");
+        frontier.append(leaf(2)).expect("append");
+        assert!(frontier.append(leaf(3)).is_err());
+    }
+
     #[test]
     fn field_to_scalar_roundtrip_zero_and_one() {
         let zero = Field::ZERO;
@@ -1,12 +1,17 @@
 //! Note discovery helpers (decrypt + verify + derive nullifier).
 //!
 //! This encapsulates the logic required to turn a pool commitment row into a
-//! `user_notes` row, given an account's keypairs.
+//! `user_notes` row, or a `user_sent_notes` row, given an account's keypairs.
 
-use crate::types::{EncryptionPrivateKey, Field, NoteAmount, NoteKeyPair};
+use crate::types::{
+    EncryptionPrivateKey, Field, NoteAmount, NotePrivateKey, NotePublicKey, OutgoingViewingKey,
+};
 use anyhow::{Result, anyhow};
 
-use crate::zk::{crypto, encryption};
+use crate::zk::{
+    crypto,
+    encryption::{self, OutgoingNote, SpentInput},
+};
 
 /// Data derived from a decryptable pool commitment for a specific user.
 #[derive(Debug, Clone)]
@@ -16,26 +21,35 @@ pub struct DerivedUserNote {
     /// Decrypted note blinding factor.
     pub blinding: Field,
     /// Expected nullifier for this note (matches on-chain nullifier event when
-    /// spent).
-    pub expected_nullifier: Field,
+    /// spent). `None` without the note private key.
+    pub expected_nullifier: Option<Field>,
+    /// Decrypted sender memo, if any.
+    pub memo: Option<String>,
 }
 
 /// Try to decrypt a commitment's encrypted output for a given account and, if
 /// it is addressed to that account, derive the expected nullifier.
 ///
+/// Watch-only accounts pass no `note_private_key`; their notes come back
+/// without an expected nullifier.
+///
 /// Returns `Ok(None)` if:
 /// - the ciphertext isn't for this account (decryption fails), or
 /// - the decrypted plaintext doesn't match the on-chain commitment, or
 /// - the note is a dummy 0-amount output.
 pub fn try_decrypt_and_derive_user_note(
-    note_keypair: &NoteKeyPair,
+    note_public_key: &NotePublicKey,
+    note_private_key: Option<&NotePrivateKey>,
     encryption_private_key: &EncryptionPrivateKey,
     commitment: &Field,
     leaf_index: u32,
     encrypted_output: &[u8],
 ) -> Result<Option<DerivedUserNote>> {
-    let Some((amount, blinding)) =
-        encryption::decrypt_output_note(encryption_private_key, encrypted_output)?
+    let Some(encryption::OutputNotePlaintext {
+        amount,
+        blinding,
+        memo,
+    }) = encryption::decrypt_output_note(encryption_private_key, encrypted_output)?
     else {
         return Ok(None);
     };
@@ -49,7 +63,7 @@ pub fn try_decrypt_and_derive_user_note(
     let amount_field_le = Field::from(amount).to_le_bytes();
     let computed = crypto::compute_commitment(
         &amount_field_le,
-        note_keypair.public.as_ref(),
+        note_public_key.as_ref(),
         &blinding.to_le_bytes(),
     )?;
     let computed: [u8; 32] = computed
@@ -60,24 +74,79 @@ pub fn try_decrypt_and_derive_user_note(
         return Ok(None);
     }
 
-    // Expected nullifier derivation matches the circuits:
-    // path_indices = leaf_index packed into LE field bytes.
+    let expected_nullifier = note_private_key
+        .map(|key| expected_nullifier(key, commitment, leaf_index))
+        .transpose()?;
+
+    Ok(Some(DerivedUserNote {
+        amount,
+        blinding,
+        expected_nullifier,
+        memo,
+    }))
+}
+
+/// Expected nullifier derivation matches the circuits:
+/// path_indices = leaf_index packed into LE field bytes.
+fn expected_nullifier(
+    note_private_key: &NotePrivateKey,
+    commitment: &Field,
+    leaf_index: u32,
+) -> Result<Field> {
     let commitment_le = commitment.to_le_bytes();
     let mut path_indices_le = [0u8; 32];
     path_indices_le[..8].copy_from_slice(&(u64::from(leaf_index)).to_le_bytes());
 
     let signature =
-        crypto::compute_signature(&note_keypair.private.0, &commitment_le, &path_indices_le)?;
-    let expected_nullifier =
-        crypto::compute_nullifier(&commitment_le, &path_indices_le, &signature)?;
-    let expected_nullifier: [u8; 32] = expected_nullifier
+        crypto::compute_signature(&note_private_key.0, &commitment_le, &path_indices_le)?;
+    let nullifier = crypto::compute_nullifier(&commitment_le, &path_indices_le, &signature)?;
+    let nullifier: [u8; 32] = nullifier
         .try_into()
         .map_err(|v: Vec<u8>| anyhow!("nullifier: expected 32 bytes, got {}", v.len()))?;
-    let expected_nullifier = Field::try_from_le_bytes(expected_nullifier)?;
+    Field::try_from_le_bytes(nullifier)
+}
 
-    Ok(Some(DerivedUserNote {
-        amount,
-        blinding,
-        expected_nullifier,
+/// What the sender copy of one output tells the account that created it.
+#[derive(Debug, Clone)]
+pub struct SenderCopy {
+    /// The output, when it went to someone else; `None` for dummy 0-amount
+    /// outputs and change back to the sender.
+    pub sent: Option<OutgoingNote>,
+    /// Inputs of the transaction that created the output.
+    pub spent_inputs: Vec<SpentInput>,
+}
+
+/// Try to open the sender copy of a commitment's encrypted output with the
+/// account's outgoing viewing key.
+///
+/// Returns `Ok(None)` if:
+/// - the output carries no sender copy, or one sealed under another key, or
+/// - the sender copy doesn't match the on-chain commitment.
+pub fn try_open_sender_copy(
+    note_public_key: &NotePublicKey,
+    outgoing_viewing_key: &OutgoingViewingKey,
+    commitment: &Field,
+    encrypted_output: &[u8],
+) -> Result<Option<SenderCopy>> {
+    let Some(mut sent) = encryption::decrypt_outgoing_note(outgoing_viewing_key, encrypted_output)?
+    else {
+        return Ok(None);
+    };
+
+    let amount_field_le = Field::from(sent.amount).to_le_bytes();
+    let computed = crypto::compute_commitment(
+        &amount_field_le,
+        sent.recipient_note_public_key.as_ref(),
+        &sent.blinding.to_le_bytes(),
+    )?;
+    if computed.as_slice() != commitment.to_le_bytes().as_slice() {
+        return Ok(None);
+    }
+
+    let spent_inputs = std::mem::take(&mut sent.spent_inputs);
+    let is_sent = !sent.amount.is_zero() && sent.recipient_note_public_key.0 != note_public_key.0;
+    Ok(Some(SenderCopy {
+        sent: is_sent.then_some(sent),
+        spent_inputs,
     }))
 }
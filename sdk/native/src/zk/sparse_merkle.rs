@@ -354,6 +354,70 @@ impl SparseMerkleTree {
             is_old0: false,
         })
     }
+
+    /// Delete a key
+    ///
+    /// Mirrors `delete_leaf` in the `asp-non-membership` contract: a sibling
+    /// leaf left alone on its branch is promoted upwards, so the resulting
+    /// root matches the on-chain `LeafDeleted` root.
+    pub fn delete(&mut self, key: &Field) -> Result<SMTResult, &'static str> {
+        let find_result = self.find(key)?;
+
+        if !find_result.found {
+            return Err("Key does not exist");
+        }
+
+        let old_root = self.root;
+        let key_bits = field_to_bits(key);
+
+        let mut siblings = find_result.siblings.clone();
+        let mut current_hash = match siblings.last() {
+            Some(last) => match self.get_node(last) {
+                Some(Node::Leaf { .. }) => {
+                    // Promote the sibling leaf in place of the deleted one
+                    let promoted = *last;
+                    siblings.pop();
+                    promoted
+                }
+                Some(Node::Internal { .. }) => Field::ZERO,
+                _ => return Err("Node not found in database"),
+            },
+            // The deleted leaf was the only one
+            None => Field::ZERO,
+        };
+
+        // Rebuild from the deletion point upwards; levels below the first
+        // non-zero sibling collapse away.
+        let mut mixed = false;
+        for (level, sibling) in siblings.iter().enumerate().rev() {
+            if *sibling != Field::ZERO {
+                mixed = true;
+            }
+            if mixed {
+                let (left, right) = if key_bits[level] {
+                    (*sibling, current_hash)
+                } else {
+                    (current_hash, *sibling)
+                };
+
+                current_hash = smt_hash_pair_field(left, right);
+                self.put_node(current_hash, Node::Internal { left, right });
+            }
+        }
+
+        self.root = current_hash;
+
+        Ok(SMTResult {
+            old_root,
+            new_root: self.root,
+            siblings: find_result.siblings,
+            old_key: *key,
+            old_value: find_result.found_value,
+            new_key: *key,
+            new_value: Field::ZERO,
+            is_old0: false,
+        })
+    }
 }
 
 /// WASM-friendly Sparse Merkle Tree wrapper
@@ -667,3 +731,45 @@ pub fn smt_hash_leaf(key: &[u8], value: &[u8]) -> Result<Vec<u8>> {
     let result = poseidon2_hash_leaf(k, v);
     Ok(result.to_le_bytes().to_vec())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::U256;
+
+    fn field(v: u64) -> Field {
+        Field(U256::from(v))
+    }
+
+    #[test]
+    fn delete_restores_the_root_without_the_key() {
+        let mut tree = SparseMerkleTree::new();
+        for k in 1..=5u64 {
+            tree.insert(&field(k), &field(k.saturating_add(100)))
+                .expect("insert");
+        }
+        tree.delete(&field(3)).expect("delete");
+
+        let mut expected = SparseMerkleTree::new();
+        for k in [1u64, 2, 4, 5] {
+            expected
+                .insert(&field(k), &field(k.saturating_add(100)))
+                .expect("insert");
+        }
+        assert_eq!(tree.root(), expected.root());
+        assert!(!tree.find(&field(3)).expect("find").found);
+    }
+
+    #[test]
+    fn deleting_every_key_empties_the_tree() {
+        let mut tree = SparseMerkleTree::new();
+        for k in 1..=3u64 {
+            tree.insert(&field(k), &field(k)).expect("insert");
+        }
+        for k in 1..=3u64 {
+            tree.delete(&field(k)).expect("delete");
+        }
+        assert_eq!(tree.root(), Field::ZERO);
+        assert!(tree.delete(&field(1)).is_err());
+    }
+}
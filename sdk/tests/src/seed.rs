@@ -8,9 +8,8 @@ use stellar_private_payments::{
     TransactChainContext,
     state::SqliteStorage,
     types::{
-        AspNonMembershipProof, ContractEvent, ContractsEventData, Field, KeyDerivationSignature,
-        LeafAddedEvent, NewCommitmentEvent, NoteAmount, NoteKeyPair, PolicyFlags, SMT_DEPTH,
-        SyncMetadata,
+        ContractEvent, ContractsEventData, Field, GvkMode, KeyDerivationSignature, LeafAddedEvent,
+        NewCommitmentEvent, NoteAmount, NoteKeyPair, PolicyFlags, SyncMetadata,
     },
     zk::{crypto, encryption, merkle::MerklePrefixTree},
 };
@@ -110,6 +109,7 @@ pub fn seed_prove_wallet(
             commitment,
             index: u32::try_from(leaf_index).context("leaf index")?,
             encrypted_output,
+            gvk_ciphertext: None,
         }])?;
 
         pool_leaves.push(commitment);
@@ -227,7 +227,8 @@ pub fn apply_proved_step(
         }
 
         let Some(derived) = notes::try_decrypt_and_derive_user_note(
-            &note_keypair,
+            &note_keypair.public,
+            Some(&note_keypair.private),
             &encryption_keypair.private,
             &commitment,
             leaf_index,
@@ -255,6 +256,7 @@ pub fn apply_proved_step(
             commitment,
             index: leaf_index,
             encrypted_output: encrypted_output.to_vec(),
+            gvk_ciphertext: None,
         }])?;
 
         insert_user_notes(
@@ -264,7 +266,9 @@ pub fn apply_proved_step(
                 commitment,
                 derived.amount,
                 derived.blinding,
-                derived.expected_nullifier,
+                derived
+                    .expected_nullifier
+                    .context("a note private key derives the nullifier")?,
             )],
         )?;
 
@@ -287,10 +291,6 @@ fn chain_snapshot_from_storage(
 ) -> Result<TransactChainContext> {
     let storage =
         SqliteStorage::connect_file(storage_path).context("open storage for chain snapshot")?;
-    let signature = test_derivation_signature();
-    let (note_keypair, _) = encryption::derive_encryption_and_note_keypairs(signature)?;
-    let note_pubkey_field = Field::try_from_le_bytes(*note_keypair.public.as_ref())?;
-
     let pool_leaves = storage.get_pool_commitment_leaves_ordered(pool_contract_id)?;
     let pool_root = MerklePrefixTree::new(POOL_MERKLE_LEVELS, &pool_leaves)?
         .into_built()
@@ -309,15 +309,13 @@ fn chain_snapshot_from_storage(
         asp_membership_root,
         asp_membership_contract_id: asp_membership_contract_id.to_string(),
         asp_membership_ledger: TEST_LEDGER,
-        non_membership_proof: Some(AspNonMembershipProof {
-            key: note_pubkey_field,
-            old_key: Field::ZERO,
-            old_value: Field::ZERO,
-            is_old0: true,
-            siblings: vec![Field::ZERO; SMT_DEPTH as usize],
-            root: Field::ZERO,
-        }),
+        // Empty blocklist: a zero root needs no indexed non-membership tree.
+        asp_non_membership_root: Field::ZERO,
+        asp_non_membership_contract_id: asp_membership_contract_id.to_string(),
+        asp_non_membership_ledger: TEST_LEDGER,
         policy_flags: PolicyFlags::ALLOWLIST | PolicyFlags::BLOCKLIST,
+        gvk_mode: GvkMode::Off,
+        admin_view_key: None,
     })
 }
 
@@ -1,7 +1,9 @@
 //! PrivatePool per-step planning.
 
 use crate::pool::{test_pool, test_recipient};
-use stellar_private_payments::{Error, types::NoteAmount};
+use stellar_private_payments::{
+    CoinSelection, Error, MAX_MEMO_LEN, TransferOptions, types::NoteAmount,
+};
 
 #[test]
 fn transfer_two_steps() {
@@ -15,7 +17,7 @@ fn transfer_two_steps() {
 
     let wallet = pool.spendable_notes().expect("spendable notes");
     let plan = pool
-        .prepare_transfer(&wallet, recipient, amount)
+        .prepare_transfer(&wallet, recipient, amount, TransferOptions::default())
         .expect("prepare transfer");
     assert_eq!(plan.tx_count(), 2);
     assert_eq!(plan.current_tx(), 0);
@@ -35,6 +37,108 @@ fn deposit_single_step_plan() {
     assert!(!plan.is_complete());
 }
 
+#[test]
+fn deposit_to_pairs_recipients_per_step() {
+    let pool = test_pool(Some(&[])).expect("test pool");
+
+    let single = pool
+        .prepare_deposit_to(
+            vec![(test_recipient(), NoteAmount::from(5u128))],
+            TransferOptions::default(),
+        )
+        .expect("prepare deposit to one recipient");
+    assert_eq!(single.tx_count(), 1);
+
+    let recipients = (1..=5u128)
+        .map(|amount| (test_recipient(), NoteAmount::from(amount)))
+        .collect();
+    let batch = pool
+        .prepare_deposit_to(recipients, TransferOptions::memo("payroll"))
+        .expect("prepare deposit to five recipients");
+    assert_eq!(batch.tx_count(), 3, "two recipients per transaction");
+    assert!(!batch.is_complete());
+
+    let err = pool
+        .prepare_deposit_to(
+            vec![
+                (test_recipient(), NoteAmount::from(5u128)),
+                (test_recipient(), NoteAmount::ZERO),
+            ],
+            TransferOptions::default(),
+        )
+        .expect_err("zero recipient amount should not plan");
+    assert!(matches!(err, Error::InvalidConfig(_)));
+}
+
+#[test]
+fn transfer_many_chains_payouts() {
+    let pool = test_pool(Some(&[2, 3, 5])).expect("test pool");
+    let wallet = pool.spendable_notes().expect("spendable notes");
+
+    let recipients = vec![
+        (test_recipient(), NoteAmount::from(4u128), None),
+        (
+            test_recipient(),
+            NoteAmount::from(1u128),
+            Some("invoice 7".to_string()),
+        ),
+        (test_recipient(), NoteAmount::from(5u128), None),
+    ];
+    let plan = pool
+        .prepare_transfer_many(&wallet, recipients, TransferOptions::memo("payroll"))
+        .expect("prepare transfer to three recipients");
+    assert!(
+        plan.tx_count() <= 3,
+        "payments share steps with note merges"
+    );
+    let mut paid = plan.paid_by_step().concat();
+    paid.sort_unstable();
+    assert_eq!(paid, vec![0, 1, 2], "every recipient is paid exactly once");
+
+    let err = pool
+        .prepare_transfer_many(
+            &wallet,
+            vec![(test_recipient(), NoteAmount::from(11u128), None)],
+            TransferOptions::default(),
+        )
+        .expect_err("payouts above the balance should not plan");
+    assert!(matches!(err, Error::Plan(_) | Error::SpendSession(_)));
+
+    let err = pool
+        .prepare_transfer_many(
+            &wallet,
+            vec![(
+                test_recipient(),
+                NoteAmount::from(1u128),
+                Some("x".repeat(MAX_MEMO_LEN.saturating_add(1))),
+            )],
+            TransferOptions::default(),
+        )
+        .expect_err("a recipient memo over the limit should not plan");
+    assert!(matches!(err, Error::InvalidConfig(_)));
+}
+
+#[test]
+fn estimate_reports_each_coin_selection() {
+    let mut pool = test_pool(Some(&[2, 3, 5, 11])).expect("test pool");
+    let amount = NoteAmount::from(10u128);
+
+    let estimate = pool.estimate(amount).expect("estimate");
+    assert_eq!(estimate.tx_count, 1, "min-steps spends 2 + 11 in one tx");
+    let min_change = estimate
+        .strategies
+        .iter()
+        .find(|strategy| strategy.strategy == CoinSelection::MinChange)
+        .expect("min-change estimate");
+    assert_eq!(min_change.note_count, 3);
+    assert_eq!(min_change.tx_count, 2);
+    assert_eq!(min_change.change, NoteAmount::ZERO);
+
+    pool.set_coin_selection(CoinSelection::MinChange);
+    let estimate = pool.estimate(amount).expect("estimate");
+    assert_eq!(estimate.tx_count, 2);
+}
+
 #[test]
 fn transfer_one_step_exact() {
     let pool = test_pool(Some(&[10])).expect("test pool");
@@ -45,7 +149,12 @@ fn transfer_one_step_exact() {
 
     let wallet = pool.spendable_notes().expect("spendable notes");
     let plan = pool
-        .prepare_transfer(&wallet, test_recipient(), amount)
+        .prepare_transfer(
+            &wallet,
+            test_recipient(),
+            amount,
+            TransferOptions::default(),
+        )
         .expect("prepare transfer");
     assert_eq!(plan.tx_count(), 1);
     assert!(!plan.is_complete());
@@ -72,7 +181,12 @@ fn transfer_insufficient_funds() {
 
     let wallet = pool.spendable_notes().expect("spendable notes");
     let err = pool
-        .prepare_transfer(&wallet, test_recipient(), NoteAmount::from(100u128))
+        .prepare_transfer(
+            &wallet,
+            test_recipient(),
+            NoteAmount::from(100u128),
+            TransferOptions::default(),
+        )
         .expect_err("transfer above wallet sum should not plan");
 
     assert!(matches!(err, Error::SpendSession(_)));
@@ -110,3 +224,29 @@ fn withdraw_zero() {
 
     assert!(matches!(err, Error::InvalidConfig(_)));
 }
+
+#[test]
+fn transfer_memo_too_long() {
+    let pool = test_pool(Some(&[10])).expect("test pool");
+
+    let wallet = pool.spendable_notes().expect("spendable notes");
+    let memo = "x".repeat(MAX_MEMO_LEN);
+    pool.prepare_transfer(
+        &wallet,
+        test_recipient(),
+        NoteAmount::from(10u128),
+        TransferOptions::memo(memo.clone()),
+    )
+    .expect("memo at the limit should plan");
+
+    let err = pool
+        .prepare_transfer(
+            &wallet,
+            test_recipient(),
+            NoteAmount::from(10u128),
+            TransferOptions::memo(memo + "x"),
+        )
+        .expect_err("memo above the limit should not plan");
+
+    assert!(matches!(err, Error::InvalidConfig(_)));
+}
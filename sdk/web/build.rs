@@ -237,6 +237,50 @@ pub const EXPECTED_{const_prefix}_R1CS_LEN: usize = {disc_r1cs_len};
         ));
     }
 
+    // notePossession is optional until its keys are committed (see
+    // deployments/testnet/circuit_keys/README.md). Without them the browser
+    // cannot register with a registry that has a verifier.
+    let keys_dir = repo_root.join("deployments/testnet/circuit_keys");
+    let np_pk_path = keys_dir.join("notePossession_proving_key.bin");
+    let np_graph_path = keys_dir.join("notePossession.graph.bin");
+    let np_r1cs_path = circuits_out.join("notePossession.r1cs");
+
+    println!("cargo:rerun-if-changed={}", np_pk_path.display());
+    println!("cargo:rerun-if-changed={}", np_graph_path.display());
+    println!("cargo:rerun-if-changed={}", np_r1cs_path.display());
+
+    if np_pk_path.is_file() && np_graph_path.is_file() && np_r1cs_path.is_file() {
+        let np_pk_bytes = read_file(&np_pk_path);
+        let np_graph_bytes = read_file(&np_graph_path);
+        let np_r1cs_bytes = read_file(&np_r1cs_path);
+
+        out.push_str(&format!(
+            "
+pub const NOTE_POSSESSION_ARTIFACT_HASHES: Option<PolicyTransactArtifactHashes> =
+    Some(PolicyTransactArtifactHashes {{
+        proving_key_len: {pk_len},
+        proving_key_sha256: {pk_hash},
+        graph_len: {graph_len},
+        graph_sha256: {graph_hash},
+        r1cs_len: {r1cs_len},
+        r1cs_sha256: {r1cs_hash},
+    }});
+",
+            pk_len = np_pk_bytes.len(),
+            pk_hash = fmt_u8_array(&sha256(&np_pk_bytes)),
+            graph_len = np_graph_bytes.len(),
+            graph_hash = fmt_u8_array(&sha256(&np_graph_bytes)),
+            r1cs_len = np_r1cs_bytes.len(),
+            r1cs_hash = fmt_u8_array(&sha256(&np_r1cs_bytes)),
+        ));
+    } else {
+        out.push_str(
+            "
+pub const NOTE_POSSESSION_ARTIFACT_HASHES: Option<PolicyTransactArtifactHashes> = None;
+",
+        );
+    }
+
     let out_path = out_dir.join("artifact_hashes.rs");
     fs::write(&out_path, out).unwrap_or_else(|e| {
         panic!(
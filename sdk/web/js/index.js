@@ -4,6 +4,8 @@ import init, {
   Storage as WasmStorage,
   bootnodeRequired as wasmBootnodeRequired,
   deriveAspUserLeaf as wasmDeriveAspUserLeaf,
+  encodePaymentRequest as wasmEncodePaymentRequest,
+  parsePaymentRequest as wasmParsePaymentRequest,
   verifySelectiveDisclosure as wasmVerifySelectiveDisclosure,
   configureTelemetry,
   set_log_level,
@@ -44,18 +46,44 @@ function deriveAspUserLeaf(notePublicKey, membershipBlinding) {
   return wasmDeriveAspUserLeaf(notePublicKey, membershipBlinding);
 }
 
+/**
+ * Encode a payment request as an `spp:` URI.
+ * @param {{ poolContractId: string, amount: string, notePublicKey: string, encryptionPublicKey: string, memo?: string, expiresAt?: number }} request
+ *   `amount` in stroops as a decimal string; keys as `0x` hex
+ * @returns {string}
+ */
+function encodePaymentRequest(request) {
+  return wasmEncodePaymentRequest(request);
+}
+
+/**
+ * Parse and checksum-verify an `spp:` payment request URI.
+ * @param {string} uri
+ */
+function parsePaymentRequest(uri) {
+  return wasmParsePaymentRequest(uri);
+}
+
 function wrapAccount(wasmAccount) {
   return {
     get userAddress() {
       return wasmAccount.userAddress;
     },
+    get subAccountIndex() {
+      return wasmAccount.subAccountIndex;
+    },
+    subAccount: async (index) => wrapAccount(await wasmAccount.subAccount(index)),
     portfolio: () => wasmAccount.portfolio(),
     userPublicKeys: () => wasmAccount.userPublicKeys(),
+    paymentRequest: (poolContract, amount, memo, expiresAt) =>
+      wasmAccount.paymentRequest(poolContract, amount, memo, expiresAt),
     aspSecret: () => wasmAccount.aspSecret(),
     userNotes: (limit) => wasmAccount.userNotes(limit),
+    sentNotes: (limit) => wasmAccount.sentNotes(limit),
     isRegistered: () => wasmAccount.isRegistered(),
     deriveAspUserLeaf: () => wasmAccount.deriveAspUserLeaf(),
     registerPublicKeys: (options) => wasmAccount.registerPublicKeys(options),
+    exportViewingKey: () => wasmAccount.exportViewingKey(),
     pool: (options) => wasmAccount.pool(options),
   };
 }
@@ -84,6 +112,7 @@ function wrapClient(wasmClient) {
       );
       return wrapAccount(wasmAccount);
     },
+    watchAccount: async (viewingKey) => wrapAccount(await wasmClient.watchAccount(viewingKey)),
     recipientLookup: (address) => wasmClient.recipientLookup(address),
     aspState: () => wasmClient.aspState(),
     allContractsData: () => wasmClient.allContractsData(),
@@ -132,6 +161,13 @@ export const Client = {
   new: newClient,
   contractConfig: WasmClient.contractConfig,
 };
-export { PrivatePool, bootnodeRequired, deriveAspUserLeaf, verifySelectiveDisclosure };
+export {
+  PrivatePool,
+  bootnodeRequired,
+  deriveAspUserLeaf,
+  encodePaymentRequest,
+  parsePaymentRequest,
+  verifySelectiveDisclosure,
+};
 export { configureTelemetry, set_log_level, dump_recent_logs, debugLogsEnabled };
 export { default } from '../dist/stellar_private_payments_web.js';
@@ -37,13 +37,27 @@ export type {
 /** Wallet session returned by {@link Client.account}. */
 export interface Account {
   readonly userAddress: string;
+  /** Sub-account index of this session; 0 for the account itself. */
+  readonly subAccountIndex: number;
+  /** Sub-account `index` of this wallet: own keys and notes, same signer. */
+  subAccount(index: number): Promise<Account>;
   portfolio(): Promise<unknown>;
   userPublicKeys(): Promise<unknown>;
+  /** `spp:` payment request URI paid to this account; `expiresAt` in unix seconds. */
+  paymentRequest(
+    poolContract: string,
+    amount: bigint,
+    memo?: string | null,
+    expiresAt?: number | null,
+  ): Promise<string>;
   aspSecret(): Promise<string>;
   userNotes(limit: number): Promise<unknown>;
+  sentNotes(limit: number): Promise<unknown>;
   isRegistered(): Promise<boolean>;
   deriveAspUserLeaf(): Promise<string>;
   registerPublicKeys(options?: RegisterPublicKeysOptions | null): Promise<string>;
+  /** Viewing key JSON for {@link Client.watchAccount}; reveals every note of the account. */
+  exportViewingKey(): Promise<string>;
   pool(options: PoolOptions): Promise<PrivatePool>;
 }
 
@@ -54,6 +68,8 @@ export interface Client {
   sync(): Promise<void>;
   operationalFeed(limit: number): Promise<unknown>;
   account(options: AccountOptions, signer: WalletSigner): Promise<Account>;
+  /** Watch-only session from a viewing key; proving and signing reject. */
+  watchAccount(viewingKey: string): Promise<Account>;
   recipientLookup(address: string): Promise<unknown>;
   aspState(): Promise<unknown>;
   allContractsData(): Promise<unknown>;
@@ -82,6 +98,26 @@ export declare function deriveAspUserLeaf(
   membershipBlinding: string,
 ): string;
 
+/** Contents of an `spp:` payment request URI. */
+export interface PaymentRequest {
+  poolContractId: string;
+  /** Stroops as a decimal string. */
+  amount: string;
+  /** `0x`-prefixed 32-byte hex. */
+  notePublicKey: string;
+  /** `0x`-prefixed 32-byte hex. */
+  encryptionPublicKey: string;
+  memo?: string | null;
+  /** Unix time in seconds. */
+  expiresAt?: number | null;
+}
+
+/** Encode a payment request as an `spp:` URI (with checksum). */
+export declare function encodePaymentRequest(request: PaymentRequest): string;
+
+/** Parse and checksum-verify an `spp:` payment request URI. */
+export declare function parsePaymentRequest(uri: string): PaymentRequest;
+
 /** Walletless selective-disclosure verification (no storage / Client). */
 export declare function verifySelectiveDisclosure(
   rpcUrl: string,
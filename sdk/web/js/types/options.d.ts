@@ -28,6 +28,11 @@ export interface AccountOptions {
 /** Options for {@link Account.pool}. */
 export interface PoolOptions {
   poolContract: string;
+  /**
+   * How spends pick input notes: fewest transactions (default), smallest
+   * change, or avoiding notes received in the same ledger together.
+   */
+  coinSelection?: "minSteps" | "minChange" | "privacy";
 }
 
 /** Options for {@link Account.registerPublicKeys}. */
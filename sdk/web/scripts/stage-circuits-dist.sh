@@ -28,6 +28,12 @@ CIRCUIT_ARTIFACTS=(
   selectiveDisclosure_4.r1cs
 )
 
+# notePossession is staged once its keys are committed (sdk/web/build.rs only
+# bundles its hashes in that case).
+if [[ -f "$KEYS_DIR/notePossession_proving_key.bin" ]]; then
+  CIRCUIT_ARTIFACTS+=(notePossession.graph.bin notePossession.r1cs)
+fi
+
 # Allow other scripts to source this file just to read the CIRCUIT_ARTIFACTS array.
 if [[ "${STAGE_CIRCUITS_DIST_SOURCE_ONLY:-}" == "1" ]]; then
   return 0
@@ -68,6 +74,10 @@ KEYS=(
   selectiveDisclosure_4_proving_key.bin
 )
 
+if [[ -f "$KEYS_DIR/notePossession_proving_key.bin" ]]; then
+  KEYS+=(notePossession_proving_key.bin)
+fi
+
 for name in "${KEYS[@]}"; do
   src="$KEYS_DIR/$name"
   [[ -f "$src" ]] || { echo "error: missing proving key $src" >&2; exit 1; }
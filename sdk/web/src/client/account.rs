@@ -4,8 +4,8 @@ use std::rc::Rc;
 
 use serde::{Deserialize, Serialize};
 use stellar_private_payments::{
-    Account as NativeAccount,
-    types::{EncryptionPublicKey, NotePublicKey},
+    Account as NativeAccount, CoinSelection,
+    types::{EncryptionPublicKey, NoteAmount, NotePublicKey, Passphrase},
 };
 
 use wasm_bindgen::prelude::*;
@@ -32,6 +32,8 @@ struct RegisterPublicKeysOptions {
 #[serde(rename_all = "camelCase")]
 struct PoolOptions {
     pool_contract: String,
+    #[serde(default)]
+    coin_selection: CoinSelection,
 }
 
 /// Wallet session for one Stellar account. Construct via
@@ -54,12 +56,48 @@ impl Account {
         self.inner.user_address().to_string()
     }
 
+    /// Sub-account index of this session; 0 for the account itself.
+    #[wasm_bindgen(getter, js_name = subAccountIndex)]
+    pub fn sub_account_index(&self) -> u32 {
+        self.inner.sub_account_index()
+    }
+
+    /// Open sub-account `index` of this wallet. It has its own keys, notes and
+    /// ASP leaf; transactions are still signed by the connected wallet.
+    #[wasm_bindgen(js_name = subAccount)]
+    pub async fn sub_account(&self, index: u32) -> Result<Account, JsError> {
+        let inner = self.inner.sub_account(index).await.map_err(pool_err)?;
+        Ok(Self::new(Rc::new(inner)))
+    }
+
     /// Portfolio balances across all enabled pools in the deployment.
     pub async fn portfolio(&self) -> Result<JsValue, JsError> {
         let portfolio = self.inner.portfolio().await.map_err(pool_err)?;
         Ok(serde_wasm_bindgen::to_value(&portfolio)?)
     }
 
+    /// `spp:` payment request URI for `amount` stroops in `poolContract`, paid
+    /// to this account's keys. `expiresAt` is a unix time in seconds.
+    #[wasm_bindgen(js_name = paymentRequest)]
+    pub async fn payment_request(
+        &self,
+        pool_contract: String,
+        amount: u128,
+        memo: Option<String>,
+        expires_at: Option<u64>,
+    ) -> Result<String, JsError> {
+        let mut request = self
+            .inner
+            .payment_request(pool_contract, NoteAmount::from(amount))
+            .await
+            .map_err(pool_err)?;
+        request.memo = memo;
+        request.expires_at = expires_at;
+        request
+            .to_uri()
+            .map_err(|e| JsError::new(&format!("{e:#}")))
+    }
+
     /// Locally derived note and encryption public keys for this account.
     #[wasm_bindgen(js_name = userPublicKeys)]
     pub async fn user_public_keys(&self) -> Result<JsValue, JsError> {
@@ -77,6 +115,13 @@ impl Account {
         Ok(serde_wasm_bindgen::to_value(&notes)?)
     }
 
+    /// Notes this account sent to others across all pools (newest first).
+    #[wasm_bindgen(js_name = sentNotes)]
+    pub async fn sent_notes(&self, limit: u32) -> Result<JsValue, JsError> {
+        let notes = self.inner.sent_notes(limit).await.map_err(pool_err)?;
+        Ok(serde_wasm_bindgen::to_value(&notes)?)
+    }
+
     /// Locally derived ASP membership blinding for this account.
     #[wasm_bindgen(js_name = aspSecret)]
     pub async fn asp_secret(&self) -> Result<String, JsError> {
@@ -133,10 +178,45 @@ impl Account {
         Ok(result.tx_hash)
     }
 
+    /// Encrypted, portable backup of this account's keys, notes, operation
+    /// history and app settings (JSON). The same file restores in the CLI.
+    #[wasm_bindgen(js_name = exportBackup)]
+    pub async fn export_backup(&self, passphrase: String) -> Result<String, JsError> {
+        self.inner
+            .export_backup(&Passphrase(passphrase))
+            .await
+            .map_err(pool_err)
+    }
+
+    /// Restore a backup made by [`Account::export_backup`] (or the CLI) for
+    /// this account.
+    #[wasm_bindgen(js_name = importBackup)]
+    pub async fn import_backup(&self, backup: String, passphrase: String) -> Result<(), JsError> {
+        self.inner
+            .import_backup(&backup, &Passphrase(passphrase))
+            .await
+            .map_err(pool_err)
+    }
+
+    /// Viewing key of this account (JSON). `Client.watchAccount` opens it as a
+    /// watch-only account, here or in the CLI. Anyone holding it sees every
+    /// note of the account.
+    #[wasm_bindgen(js_name = exportViewingKey)]
+    pub async fn export_viewing_key(&self) -> Result<String, JsError> {
+        self.inner
+            .export_viewing_key()
+            .await
+            .map_err(pool_err)?
+            .to_json()
+            .map_err(|e| JsError::new(&format!("{e:#}")))
+    }
+
     /// Open a private pool session for this account.
     pub async fn pool(&self, options: JsValue) -> Result<PrivatePool, JsError> {
         let opts: PoolOptions = serde_wasm_bindgen::from_value(options)?;
-        let inner = Rc::new(self.inner.pool(opts.pool_contract).map_err(pool_err)?);
+        let mut pool = self.inner.pool(opts.pool_contract).map_err(pool_err)?;
+        pool.set_coin_selection(opts.coin_selection);
+        let inner = Rc::new(pool);
         Ok(PrivatePool::from_parts(
             inner,
             self.inner.user_address().to_string(),
@@ -557,7 +557,7 @@ async fn e2e_transfer_halts_at_signing() {
 
     start_progress_capture();
     let response = pool
-        .transfer(recipient, FLOW_AMOUNT_STROOPS)
+        .transfer(recipient, FLOW_AMOUNT_STROOPS, None)
         .await
         .expect("transfer must resolve at the JS boundary, not throw");
     let stages = captured_stages();
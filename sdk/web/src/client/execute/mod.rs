@@ -44,6 +44,15 @@ enum ExecuteJsResponse {
     AspNotReady,
 }
 
+/// [`ExecuteJsResponse`] plus, per recipient of a `transferMany` plan, the
+/// hash of the transaction that paid it (`null` when unpaid).
+#[derive(Serialize)]
+struct PayoutJsResponse {
+    #[serde(flatten)]
+    execution: ExecuteJsResponse,
+    payouts: Vec<Option<String>>,
+}
+
 impl ExecuteFailure {
     fn plan(completed: Vec<TransactionResult>, error: Error) -> ExecuteOutcome {
         Err(Self::Failed(PlanExecutionError::into_error(
@@ -114,6 +123,35 @@ impl PrivatePool {
         ExecuteJsResponse::from(outcome).try_into()
     }
 
+    /// [`Self::execute_plan`] for a `prepare_transfer_many` plan paying
+    /// `recipients` recipients; the response also has `payouts`.
+    pub(crate) async fn execute_payout_plan(
+        &self,
+        plan: &mut PreparedTransactionPlan,
+        recipients: usize,
+    ) -> Result<JsValue, JsError> {
+        let paid_by_step = plan.paid_by_step();
+        let execution = ExecuteJsResponse::from(self.execute_plan_inner(plan, "transfer").await);
+        let hashes: &[String] = match &execution {
+            ExecuteJsResponse::Complete { hashes } | ExecuteJsResponse::Failed { hashes, .. } => {
+                hashes
+            }
+            ExecuteJsResponse::AspNotReady => &[],
+        };
+        let mut payouts = vec![None; recipients];
+        for (hash, paid) in hashes.iter().zip(&paid_by_step) {
+            for index in paid {
+                if let Some(payout) = payouts.get_mut(*index) {
+                    *payout = Some(hash.clone());
+                }
+            }
+        }
+        Ok(serde_wasm_bindgen::to_value(&PayoutJsResponse {
+            execution,
+            payouts,
+        })?)
+    }
+
     async fn execute_plan_inner(
         &self,
         plan: &mut PreparedTransactionPlan,
@@ -199,11 +237,11 @@ impl PrivatePool {
                 Some(current),
                 Some(total),
             );
-            let hash = match pool.submit(signed).await {
+            let hash = match pool.submit_step(plan, signed).await {
                 Ok(hash) => hash,
                 Err(error) => return ExecuteFailure::plan(completed, error),
             };
-            if let Err(error) = pool.confirm(&hash).await {
+            if let Err(error) = pool.confirm_step(plan, &hash).await {
                 return ExecuteFailure::plan(completed, error);
             }
             completed.push(TransactionResult { tx_hash: hash });
@@ -15,7 +15,10 @@ use stellar_private_payments::{
     Account as NativeAccount, BackgroundSyncStop, Client as NativeClient, Error, Handle,
     chain::{RpcClient, StateFetcher},
     crypto::derive_asp_user_leaf as derive_asp_user_leaf_native,
-    types::{DisclosureReceipt, Field, KeyDerivationSignature, NotePublicKey},
+    types::{
+        DisclosureReceipt, Field, KeyDerivationSignature, NotePublicKey, Passphrase,
+        PaymentRequest, ViewingKey,
+    },
     verify_disclosure_receipt,
 };
 use wasm_bindgen::prelude::*;
@@ -220,6 +223,68 @@ impl Client {
         .await
     }
 
+    /// Import a viewing key (JSON from `Account.exportViewingKey`) and return
+    /// a watch-only [`Account`] session. It reads balances and history; pool
+    /// operations that prove or sign reject.
+    #[wasm_bindgen(js_name = watchAccount)]
+    pub async fn watch_account(&self, viewing_key: String) -> Result<Account, JsError> {
+        with_correlation_id(new_correlation_id(), async {
+            let viewing_key =
+                ViewingKey::from_json(&viewing_key).map_err(|e| JsError::new(&format!("{e:#}")))?;
+            Ok(Account::new(Rc::new(
+                self.inner
+                    .watch_account(&viewing_key)
+                    .await
+                    .map_err(pool_err)?,
+            )))
+        })
+        .await
+    }
+
+    /// Unlock the wallet's secret keys for every session on this storage.
+    /// The first unlock sets `passphrase` and seals keys stored in plaintext.
+    pub async fn unlock(&self, passphrase: String) -> Result<(), JsError> {
+        self.storage
+            .bridge()
+            .unlock(Passphrase(passphrase))
+            .await
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Forget the vault key. Key reads fail and note scanning pauses until
+    /// the next [`Self::unlock`].
+    pub async fn lock(&self) -> Result<(), JsError> {
+        self.storage
+            .bridge()
+            .lock()
+            .await
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Whether a passphrase is set and the wallet is not unlocked.
+    #[wasm_bindgen(js_name = isLocked)]
+    pub async fn is_locked(&self) -> Result<bool, JsError> {
+        self.storage
+            .bridge()
+            .is_locked()
+            .await
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Re-seal the wallet's secret keys under `new_passphrase`.
+    #[wasm_bindgen(js_name = changePassphrase)]
+    pub async fn change_passphrase(
+        &self,
+        current_passphrase: String,
+        new_passphrase: String,
+    ) -> Result<(), JsError> {
+        self.storage
+            .bridge()
+            .change_passphrase(Passphrase(current_passphrase), Passphrase(new_passphrase))
+            .await
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
     /// Catch local storage up to the current chain tip for the deployment.
     #[wasm_bindgen(js_name = sync)]
     pub async fn sync(&self) -> Result<(), JsError> {
@@ -309,6 +374,27 @@ pub fn derive_asp_user_leaf(
     Ok(leaf.to_string())
 }
 
+/// Encode a payment request (`{ poolContractId, amount, notePublicKey,
+/// encryptionPublicKey, memo?, expiresAt? }`) as an `spp:` URI.
+#[wasm_bindgen(js_name = encodePaymentRequest)]
+pub fn encode_payment_request(request: JsValue) -> Result<String, JsError> {
+    crate::wasm_start();
+
+    let request: PaymentRequest = serde_wasm_bindgen::from_value(request)?;
+    request
+        .to_uri()
+        .map_err(|e| JsError::new(&format!("{e:#}")))
+}
+
+/// Parse and checksum-verify an `spp:` payment request URI.
+#[wasm_bindgen(js_name = parsePaymentRequest)]
+pub fn parse_payment_request(uri: &str) -> Result<JsValue, JsError> {
+    crate::wasm_start();
+
+    let request = PaymentRequest::parse(uri).map_err(|e| JsError::new(&format!("{e:#}")))?;
+    Ok(serde_wasm_bindgen::to_value(&request)?)
+}
+
 /// Verify a selective-disclosure receipt with no wallet, no local storage,
 /// and no [`Client`] instance — just an RPC URL. Skips the OPFS/SQLite
 /// storage worker entirely, since verification never reads local state.
@@ -2,14 +2,15 @@
 
 use std::rc::Rc;
 
+use serde::{Deserialize, Serialize};
 use stellar_private_payments::{
-    DisclosureRequest, PrivatePool as NativePrivatePool,
+    DisclosureRequest, PlanStatus, PrivatePool as NativePrivatePool, ResumedPlan, TransferOptions,
     types::{DisclosureReceipt, EncryptionPublicKey, NoteAmount, NotePublicKey, TransferRecipient},
 };
 use wasm_bindgen::prelude::*;
 
 use crate::{
-    client::{execute::emit, pool_err, transact::parse_transact_step},
+    client::{execute::emit, pool_err, pool_err_message, transact::parse_transact_step},
     correlation::{new_correlation_id, with_correlation_id},
     workers::storage::StorageBridge,
 };
@@ -37,6 +38,80 @@ impl PrivatePool {
     }
 }
 
+impl PrivatePool {
+    async fn deposit_to_recipients(
+        &self,
+        recipients: Vec<(TransferRecipient, NoteAmount)>,
+        memo: Option<String>,
+    ) -> Result<JsValue, JsError> {
+        with_correlation_id(new_correlation_id(), async {
+            let mut plan = self
+                .inner()
+                .prepare_deposit_to(recipients, TransferOptions { memo })
+                .await
+                .map_err(pool_err)?;
+            self.execute_plan(&mut plan, "deposit").await
+        })
+        .await
+    }
+}
+
+/// One entry of the `resumePlans` result.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResumedPlanOut {
+    plan_id: i64,
+    status: PlanStatus,
+    hashes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+impl From<ResumedPlan> for ResumedPlanOut {
+    fn from(plan: ResumedPlan) -> Self {
+        Self {
+            plan_id: plan.plan_id,
+            status: plan.status,
+            hashes: plan.transactions.into_iter().map(|tx| tx.tx_hash).collect(),
+            message: plan.error.map(pool_err_message),
+        }
+    }
+}
+
+/// One entry of `depositToMany` / `transferMany`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct RecipientAmount {
+    to: Option<String>,
+    note_public_key: Option<NotePublicKey>,
+    encryption_public_key: Option<EncryptionPublicKey>,
+    sub_account: Option<u32>,
+    amount: NoteAmount,
+    /// `transferMany` only: memo for this recipient instead of the batch memo.
+    memo: Option<String>,
+}
+
+impl RecipientAmount {
+    fn into_native(self) -> Result<(TransferRecipient, NoteAmount, Option<String>), JsError> {
+        let recipient = match (
+            self.to,
+            self.note_public_key,
+            self.encryption_public_key,
+            self.sub_account,
+        ) {
+            (Some(address), None, None, None) => TransferRecipient::from(address),
+            (None, Some(note), Some(enc), None) => TransferRecipient::keys(note, enc),
+            (None, None, None, Some(index)) => TransferRecipient::SubAccount(index),
+            _ => {
+                return Err(JsError::new(
+                    "each recipient needs `to`, both `notePublicKey` and `encryptionPublicKey`, or `subAccount`",
+                ));
+            }
+        };
+        Ok((recipient, self.amount, self.memo))
+    }
+}
+
 #[wasm_bindgen]
 impl PrivatePool {
     /// Balance in stroops (`bigint` in JS).
@@ -74,13 +149,81 @@ impl PrivatePool {
         .await
     }
 
+    /// Deposit tokens straight into `recipient`'s shielded balance (a `G...`
+    /// address looked up in the registry); no note is created for this
+    /// account. `memo` is encrypted to the recipient.
+    #[wasm_bindgen(js_name = depositTo)]
+    pub async fn deposit_to(
+        &self,
+        recipient: &str,
+        amount: u128,
+        memo: Option<String>,
+    ) -> Result<JsValue, JsError> {
+        let recipients = vec![(TransferRecipient::from(recipient), NoteAmount::from(amount))];
+        self.deposit_to_recipients(recipients, memo).await
+    }
+
+    /// Deposit to many recipients, two per transaction. `recipients` is an
+    /// array of `{ to }`, `{ notePublicKey, encryptionPublicKey }` or
+    /// `{ subAccount }`, each with `amount` in stroops as a decimal string.
+    #[wasm_bindgen(js_name = depositToMany)]
+    pub async fn deposit_to_many(
+        &self,
+        recipients: JsValue,
+        memo: Option<String>,
+    ) -> Result<JsValue, JsError> {
+        let recipients: Vec<RecipientAmount> = serde_wasm_bindgen::from_value(recipients)?;
+        let recipients = recipients
+            .into_iter()
+            .map(|entry| match entry.into_native()? {
+                (recipient, amount, None) => Ok((recipient, amount)),
+                (_, _, Some(_)) => Err(JsError::new(
+                    "depositToMany takes one `memo` argument, not a memo per recipient",
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.deposit_to_recipients(recipients, memo).await
+    }
+
+    /// Pay several recipients in one chain of transactions, merging notes and
+    /// paying in the same steps. `recipients` has the same shape as in
+    /// `depositToMany`, plus an optional `memo` per entry; `memo` goes to the
+    /// entries without one. The response adds `payouts`: per recipient, the
+    /// hash of the transaction that paid it or `null`. After a partial
+    /// failure, call again with the unpaid entries.
+    #[wasm_bindgen(js_name = transferMany)]
+    pub async fn transfer_many(
+        &self,
+        recipients: JsValue,
+        memo: Option<String>,
+    ) -> Result<JsValue, JsError> {
+        let recipients: Vec<RecipientAmount> = serde_wasm_bindgen::from_value(recipients)?;
+        let recipients = recipients
+            .into_iter()
+            .map(RecipientAmount::into_native)
+            .collect::<Result<Vec<_>, _>>()?;
+        with_correlation_id(new_correlation_id(), async {
+            let count = recipients.len();
+            let wallet = self.inner().spendable_notes().await.map_err(pool_err)?;
+            let mut plan = self
+                .inner()
+                .prepare_transfer_many(&wallet, recipients, TransferOptions { memo })
+                .await
+                .map_err(pool_err)?;
+            self.execute_payout_plan(&mut plan, count).await
+        })
+        .await
+    }
+
     /// Transfer privately to explicit recipient keys (note + encryption hex).
+    /// `memo` is encrypted to the recipient alongside the note.
     #[wasm_bindgen(js_name = transferToKeys)]
     pub async fn transfer_to_keys(
         &self,
         note_public_key_hex: &str,
         encryption_public_key_hex: &str,
         amount: u128,
+        memo: Option<String>,
     ) -> Result<JsValue, JsError> {
         with_correlation_id(new_correlation_id(), async {
             let recipient = TransferRecipient::keys(
@@ -92,7 +235,59 @@ impl PrivatePool {
             let wallet = self.inner().spendable_notes().await.map_err(pool_err)?;
             let mut plan = self
                 .inner()
-                .prepare_transfer(&wallet, recipient, NoteAmount::from(amount))
+                .prepare_transfer(
+                    &wallet,
+                    recipient,
+                    NoteAmount::from(amount),
+                    TransferOptions { memo },
+                )
+                .await
+                .map_err(pool_err)?;
+            self.execute_plan(&mut plan, "transfer").await
+        })
+        .await
+    }
+
+    /// Pay an `spp:` payment request URI (see `encodePaymentRequest`). Fails
+    /// when the request is for another pool or has expired.
+    #[wasm_bindgen(js_name = payRequest)]
+    pub async fn pay_request(&self, uri: &str) -> Result<JsValue, JsError> {
+        with_correlation_id(new_correlation_id(), async {
+            let request = self.inner().payment_request(uri).map_err(pool_err)?;
+            let wallet = self.inner().spendable_notes().await.map_err(pool_err)?;
+            let mut plan = self
+                .inner()
+                .prepare_transfer(
+                    &wallet,
+                    TransferRecipient::keys(request.note_public_key, request.encryption_public_key),
+                    request.amount,
+                    TransferOptions { memo: request.memo },
+                )
+                .await
+                .map_err(pool_err)?;
+            self.execute_plan(&mut plan, "transfer").await
+        })
+        .await
+    }
+
+    /// Transfer privately to sub-account `index` of this wallet.
+    #[wasm_bindgen(js_name = transferToSubAccount)]
+    pub async fn transfer_to_sub_account(
+        &self,
+        index: u32,
+        amount: u128,
+        memo: Option<String>,
+    ) -> Result<JsValue, JsError> {
+        with_correlation_id(new_correlation_id(), async {
+            let wallet = self.inner().spendable_notes().await.map_err(pool_err)?;
+            let mut plan = self
+                .inner()
+                .prepare_transfer(
+                    &wallet,
+                    TransferRecipient::SubAccount(index),
+                    NoteAmount::from(amount),
+                    TransferOptions { memo },
+                )
                 .await
                 .map_err(pool_err)?;
             self.execute_plan(&mut plan, "transfer").await
@@ -100,13 +295,24 @@ impl PrivatePool {
         .await
     }
 
-    /// Transfer privately. `recipient` is a Stellar `G...` address.
-    pub async fn transfer(&self, recipient: &str, amount: u128) -> Result<JsValue, JsError> {
+    /// Transfer privately. `recipient` is a Stellar `G...` address; `memo` is
+    /// encrypted to the recipient alongside the note.
+    pub async fn transfer(
+        &self,
+        recipient: &str,
+        amount: u128,
+        memo: Option<String>,
+    ) -> Result<JsValue, JsError> {
         with_correlation_id(new_correlation_id(), async {
             let wallet = self.inner().spendable_notes().await.map_err(pool_err)?;
             let mut plan = self
                 .inner()
-                .prepare_transfer(&wallet, recipient, NoteAmount::from(amount))
+                .prepare_transfer(
+                    &wallet,
+                    recipient,
+                    NoteAmount::from(amount),
+                    TransferOptions { memo },
+                )
                 .await
                 .map_err(pool_err)?;
             self.execute_plan(&mut plan, "transfer").await
@@ -143,6 +349,18 @@ impl PrivatePool {
         .await
     }
 
+    /// Finish or abandon multi-step plans interrupted by a crash or a closed
+    /// tab. Call after sync, while no other plan is running.
+    #[wasm_bindgen(js_name = resumePlans)]
+    pub async fn resume_plans(&self) -> Result<JsValue, JsError> {
+        with_correlation_id(new_correlation_id(), async {
+            let resumed = self.inner().resume_plans().await.map_err(pool_err)?;
+            let out: Vec<ResumedPlanOut> = resumed.into_iter().map(Into::into).collect();
+            Ok(serde_wasm_bindgen::to_value(&out)?)
+        })
+        .await
+    }
+
     /// Generate a selective-disclosure proof for a note commitment.
     ///
     /// `config` matches [`DisclosureRequest`] (camelCase; `selectedCommitments`
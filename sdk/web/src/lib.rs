@@ -21,7 +21,8 @@ pub(crate) const DEPLOYMENT: &str = include_str!("../../../deployments/testnet/d
 
 pub use bootnode::bootnode_required_js as bootnode_required;
 pub use client::{
-    Account, Client, PrivatePool, derive_asp_user_leaf, verify_selective_disclosure_standalone,
+    Account, Client, PrivatePool, derive_asp_user_leaf, encode_payment_request,
+    parse_payment_request, verify_selective_disclosure_standalone,
 };
 pub use storage::Storage;
 
@@ -16,10 +16,11 @@ pub use stellar_private_payments::{
 use stellar_private_payments::{
     types::{
         AspMembershipSync, ContractsEventData, DisclosureReceipt, EncryptionPublicKey, Field,
-        KeyDerivationSignature, NotePublicKey, OperationalFeedItem, PortfolioBalance,
-        RecipientLookup, SyncMetadata, UserNoteSummary, UserOperation,
+        KeyDerivationSignature, NotePublicKey, OperationalFeedItem, Passphrase, PortfolioBalance,
+        RecipientLookup, SentNoteSummary, StoredPlan, SyncMetadata, UserNoteSummary, UserOperation,
+        ViewingKey,
     },
-    zk::flows::TransactParams,
+    zk::flows::{NotePossessionParams, TransactParams},
 };
 
 pub type Address = String;
@@ -71,6 +72,34 @@ pub enum StorageWorkerRequest {
     ClearIndexingCursors,
     ClampLastFullyIndexedLedger(u32),
     DeriveSaveUserKeys(Address, KeyDerivationSignature, String),
+    /// Unlock the wallet's secret keys; the first unlock sets the passphrase.
+    Unlock(Passphrase),
+    Lock,
+    IsLocked,
+    ChangePassphrase {
+        current: Passphrase,
+        new: Passphrase,
+    },
+    /// Encrypted wallet backup of the account, sealed under the passphrase.
+    ExportBackup {
+        address: Address,
+        passphrase: Passphrase,
+    },
+    /// Restore a wallet backup for the account.
+    ImportBackup {
+        address: Address,
+        backup: String,
+        passphrase: Passphrase,
+    },
+    /// Derive and store the keys of a sub-account if missing.
+    EnsureSubAccount {
+        address: Address,
+        index: u32,
+    },
+    /// Viewing key of the account, for a watch-only import elsewhere.
+    ExportViewingKey(Address),
+    /// Store a viewing key as a watch-only account.
+    ImportViewingKey(ViewingKey),
     DisclaimerState(Address),
     AcceptDisclaimer(Address, String),
     GetSetting(String),
@@ -81,6 +110,7 @@ pub enum StorageWorkerRequest {
     UserKeys(Address),
     AspSecret(Address),
     UserNotes(Address, u32),
+    SentNotes(Address, u32),
     PortfolioBalances(Address),
     RecordOperation {
         address: Address,
@@ -96,6 +126,13 @@ pub enum StorageWorkerRequest {
         pool_contract_id: String,
         limit: u32,
     },
+    /// Persist a multi-step transaction plan; inserted when it has no id.
+    SavePlan(StoredPlan),
+    /// Plans of the account in the pool that are still active.
+    ActivePlans {
+        user_address: Address,
+        pool_contract_id: Address,
+    },
     UnspentUserNotes {
         user_address: Address,
         pool_contract_id: Address,
@@ -129,11 +166,21 @@ pub enum StorageWorkerResponse {
     Error(String),
     DisclaimerState(DisclaimerStatePayload),
     Setting(Option<String>),
+    Locked(bool),
+    /// `WalletBackupFile` JSON.
+    Backup(String),
+    ViewingKey(ViewingKey),
+    /// Storage key of a sub-account.
+    SubAccount(String),
     UserKeys(Option<UserKeys>),
     AspSecret(Option<AspSecret>),
     UserNotes(Vec<UserNoteSummary>),
+    SentNotes(Vec<SentNoteSummary>),
     PortfolioBalances(Vec<PortfolioBalance>),
     Operations(Vec<UserOperation>),
+    /// Storage id of a saved plan.
+    PlanId(i64),
+    Plans(Vec<StoredPlan>),
     RecipientLookup(RecipientLookup),
     OperationalFeed(Vec<OperationalFeedItem>),
     AspMembershipSync(AspMembershipSync),
@@ -149,6 +196,7 @@ pub enum ProverWorkerRequest {
     Ping,
     Transact(TransactParams),
     Disclosure(DisclosureProveParams),
+    NotePossession(NotePossessionParams),
     VerifyDisclosureProof(DisclosureReceipt, String),
     ConfigureTelemetry(WorkerTelemetryConfig),
     DumpLogs,
@@ -161,6 +209,7 @@ pub enum ProverWorkerResponse {
     Error(String),
     TransactPrepared(PreparedProverTx),
     Disclosure(DisclosureReceipt),
+    NotePossessionProof(Vec<u8>),
     DisclosureProofVerified(bool),
     Logs(String),
 }
@@ -19,10 +19,13 @@ use stellar_private_payments::{
         SELECTIVE_DISCLOSURE_2_LEVELS, SELECTIVE_DISCLOSURE_2_N_NOTES,
         SELECTIVE_DISCLOSURE_3_CIRCUIT, SELECTIVE_DISCLOSURE_3_LEVELS,
         SELECTIVE_DISCLOSURE_3_N_NOTES, SELECTIVE_DISCLOSURE_4_CIRCUIT,
-        SELECTIVE_DISCLOSURE_4_LEVELS, SELECTIVE_DISCLOSURE_4_N_NOTES,
+        SELECTIVE_DISCLOSURE_4_LEVELS, SELECTIVE_DISCLOSURE_4_N_NOTES, gvk_circuit_stem,
     },
     zk::{
-        flows::{DisclosureNote, SelectiveDisclosureParams, TransactParams, selective_disclosure},
+        flows::{
+            DisclosureNote, NotePossessionParams, SelectiveDisclosureParams, TransactParams,
+            selective_disclosure,
+        },
         prover::Prover as Groth16Prover,
         witness::WitnessCalculator,
     },
@@ -59,6 +62,7 @@ thread_local! {
         const { RefCell::new([None, None, None, None]) };
     static DISCLOSURE_PROVERS: RefCell<[Option<Groth16Prover>; 4]> =
         const { RefCell::new([None, None, None, None]) };
+    static NOTE_POSSESSION_PROVER: RefCell<Option<ProverEngine>> = const { RefCell::new(None) };
     static INIT_STATE: RefCell<InitState> = const { RefCell::new(InitState::Pending) };
 }
 
@@ -227,6 +231,41 @@ async fn ensure_disclosure_prover(n_notes: usize) -> Result<(), JsError> {
     Ok(())
 }
 
+/// Load the `notePossession` prover on first use. It is only needed when
+/// registering keys, so it is not part of the worker's startup set.
+async fn ensure_note_possession_prover() -> Result<(), JsError> {
+    if NOTE_POSSESSION_PROVER.with(|s| s.borrow().is_some()) {
+        return Ok(());
+    }
+
+    let hashes = crate::artifact_hashes::NOTE_POSSESSION_ARTIFACT_HASHES.ok_or_else(|| {
+        JsError::new(
+            "notePossession artifacts were not bundled with this build; the public key \
+             registry requires a proof of possession",
+        )
+    })?;
+
+    let (proving_key, graph_bytes, r1cs_bytes) = try_join!(
+        fetch_circuit_file_verified(
+            "notePossession_proving_key.bin",
+            hashes.proving_key_len,
+            hashes.proving_key_sha256
+        ),
+        fetch_circuit_file_verified(
+            "notePossession.graph.bin",
+            hashes.graph_len,
+            hashes.graph_sha256
+        ),
+        fetch_circuit_file_verified("notePossession.r1cs", hashes.r1cs_len, hashes.r1cs_sha256)
+    )?;
+
+    let engine = ProverEngine::new(&proving_key, &graph_bytes, &r1cs_bytes)
+        .map_err(|e| JsError::new(&format!("failed to init notePossession prover: {e:#}")))?;
+    NOTE_POSSESSION_PROVER.with(|cell| *cell.borrow_mut() = Some(engine));
+
+    Ok(())
+}
+
 /// Fallback disclosure builder: fetch the compressed proving key and build the
 /// prover via the original [`Groth16Prover::new`] (with point decompression).
 async fn build_disclosure_from_compressed(
@@ -477,7 +516,7 @@ pub(crate) async fn router(req: ProverWorkerRequest) -> Result<ProverWorkerRespo
         }
         ProverWorkerRequest::Transact(params) => {
             tracing::debug!("[{WORKER_NAME}] transact");
-            let stem = params.policy_flags.circuit_stem();
+            let stem = gvk_circuit_stem(params.policy_flags, params.gvk_mode);
             let prepared = TRANSACT_PROVERS.with(|cell| {
                 let mut borrow = cell.borrow_mut();
                 let engine = borrow.get_mut(&stem).ok_or_else(|| {
@@ -603,6 +642,20 @@ pub(crate) async fn router(req: ProverWorkerRequest) -> Result<ProverWorkerRespo
 
             ProverWorkerResponse::Disclosure(receipt)
         }
+        ProverWorkerRequest::NotePossession(params) => {
+            tracing::debug!("[{WORKER_NAME}] note possession");
+            ensure_note_possession_prover()
+                .await
+                .map_err(|e| anyhow::anyhow!("{e:?}"))?;
+            let proof = NOTE_POSSESSION_PROVER.with(|cell| {
+                let mut borrow = cell.borrow_mut();
+                let engine = borrow
+                    .as_mut()
+                    .ok_or_else(|| anyhow::anyhow!("notePossession prover is not initialized"))?;
+                engine.prove_note_possession(params)
+            })?;
+            ProverWorkerResponse::NotePossessionProof(proof)
+        }
         ProverWorkerRequest::ConfigureTelemetry(config) => {
             let _ = crate::telemetry::set_log_level(&config.level);
             stellar_private_payments::types::set_reveal_sensitive(config.reveal_sensitive);
@@ -730,6 +783,22 @@ impl Prover for ProverBridge {
         }
     }
 
+    async fn prove_note_possession(&self, params: NotePossessionParams) -> Result<Vec<u8>, Error> {
+        match self
+            .call(
+                ProverWorkerRequest::NotePossession(params),
+                PROVE_TIMEOUT_MS,
+            )
+            .await
+        {
+            Ok(ProverWorkerResponse::NotePossessionProof(proof)) => Ok(proof),
+            Ok(other) => Err(Error::Other(format!(
+                "unexpected prover worker response: {other:?}"
+            ))),
+            Err(e) => Err(Error::Other(e.to_string())),
+        }
+    }
+
     async fn verify_disclosure_proof(
         &self,
         receipt: &DisclosureReceipt,
@@ -18,8 +18,8 @@ use stellar_private_payments::{
     state::{SqliteStorage, StoredUserKeys, process_local_state_batch},
     types::{
         ContractConfig, ContractsEventData, EncryptionPublicKey, Field, NotePublicKey,
-        OperationalFeedItem, PortfolioBalance, RecipientLookup, Sensitive, SyncMetadata,
-        UserNoteSummary,
+        OperationalFeedItem, Passphrase, PortfolioBalance, RecipientLookup, Sensitive,
+        SentNoteSummary, StoredPlan, SyncMetadata, UserNoteSummary, ViewingKey,
     },
     zk::{
         crypto::asp_membership_leaf,
@@ -36,6 +36,8 @@ use wasm_bindgen_futures::spawn_local;
 // wasm threads?
 
 const WORKER_NAME: &str = "WORKER-STORAGE";
+/// Backup export/import runs Argon2id in the worker, which is slow in wasm.
+const BACKUP_TIMEOUT_MS: u32 = 60_000;
 
 #[derive(Clone, Debug)]
 enum InitState {
@@ -267,6 +269,76 @@ pub(crate) async fn router(req: StorageWorkerRequest) -> Result<StorageWorkerRes
             kick_processor();
             StorageWorkerResponse::Saved
         }
+        StorageWorkerRequest::Unlock(passphrase) => {
+            tracing::trace!("[{WORKER_NAME}] unlocking wallet keys");
+            with_storage_mut!(s => s.unlock(&passphrase)?)?;
+            kick_processor();
+            StorageWorkerResponse::Saved
+        }
+        StorageWorkerRequest::Lock => {
+            tracing::trace!("[{WORKER_NAME}] locking wallet keys");
+            with_storage!(s => s.lock())?;
+            StorageWorkerResponse::Saved
+        }
+        StorageWorkerRequest::IsLocked => {
+            let locked = with_storage!(s => s.is_locked()?)?;
+            StorageWorkerResponse::Locked(locked)
+        }
+        StorageWorkerRequest::ChangePassphrase { current, new } => {
+            tracing::trace!("[{WORKER_NAME}] changing wallet passphrase");
+            with_storage_mut!(s => s.change_passphrase(&current, &new)?)?;
+            StorageWorkerResponse::Saved
+        }
+        StorageWorkerRequest::ExportBackup {
+            address,
+            passphrase,
+        } => {
+            tracing::trace!(
+                "[{WORKER_NAME}] exporting wallet backup for the account {}",
+                Sensitive(&address)
+            );
+            let backup = with_storage!(s => s.export_backup(&address, &passphrase)?)?;
+            StorageWorkerResponse::Backup(backup)
+        }
+        StorageWorkerRequest::ImportBackup {
+            address,
+            backup,
+            passphrase,
+        } => {
+            tracing::trace!(
+                "[{WORKER_NAME}] importing wallet backup for the account {}",
+                Sensitive(&address)
+            );
+            with_storage_mut!(s => s.import_backup(&address, &backup, &passphrase)?)?;
+            kick_processor();
+            StorageWorkerResponse::Saved
+        }
+        StorageWorkerRequest::EnsureSubAccount { address, index } => {
+            tracing::trace!(
+                "[{WORKER_NAME}] ensuring sub-account {index} of the account {}",
+                Sensitive(&address)
+            );
+            let sub_address = with_storage_mut!(s => s.ensure_sub_account_keys(&address, index)?)?;
+            kick_processor();
+            StorageWorkerResponse::SubAccount(sub_address)
+        }
+        StorageWorkerRequest::ExportViewingKey(address) => {
+            tracing::trace!(
+                "[{WORKER_NAME}] exporting the viewing key of the account {}",
+                Sensitive(&address)
+            );
+            let viewing_key = with_storage!(s => s.export_viewing_key(&address)?)?;
+            StorageWorkerResponse::ViewingKey(viewing_key)
+        }
+        StorageWorkerRequest::ImportViewingKey(viewing_key) => {
+            tracing::trace!(
+                "[{WORKER_NAME}] importing a viewing key for the account {}",
+                Sensitive(&viewing_key.address)
+            );
+            with_storage_mut!(s => s.save_viewing_key(&viewing_key)?)?;
+            kick_processor();
+            StorageWorkerResponse::Saved
+        }
         StorageWorkerRequest::DisclaimerState(address) => {
             tracing::trace!(
                 "[{WORKER_NAME}] disclaimer state for account {}",
@@ -348,6 +420,19 @@ pub(crate) async fn router(req: StorageWorkerRequest) -> Result<StorageWorkerRes
             );
             StorageWorkerResponse::UserNotes(list)
         }
+        StorageWorkerRequest::SentNotes(address, limit) => {
+            tracing::trace!(
+                "[{WORKER_NAME}] list sent notes for the account {}",
+                Sensitive(&address)
+            );
+            let list = with_storage!(s => s.list_sent_notes(&address, limit)?)?;
+            tracing::trace!(
+                "[{WORKER_NAME}] fetched {} sent notes for the account {}",
+                list.len(),
+                Sensitive(&address)
+            );
+            StorageWorkerResponse::SentNotes(list)
+        }
         StorageWorkerRequest::PortfolioBalances(address) => {
             tracing::trace!(
                 "[{WORKER_NAME}] list portfolio balances for the account {}",
@@ -389,6 +474,28 @@ pub(crate) async fn router(req: StorageWorkerRequest) -> Result<StorageWorkerRes
             let list = with_storage!(s => s.list_operations(&address, &pool_contract_id, limit)?)?;
             StorageWorkerResponse::Operations(list)
         }
+        StorageWorkerRequest::SavePlan(plan) => {
+            tracing::trace!(
+                "[{WORKER_NAME}] save transaction plan {:?} for the account {}",
+                plan.id,
+                Sensitive(&plan.user_address)
+            );
+            let id = with_storage_mut!(s => s.save_plan(&plan)?)?;
+            StorageWorkerResponse::PlanId(id)
+        }
+        StorageWorkerRequest::ActivePlans {
+            user_address,
+            pool_contract_id,
+        } => {
+            tracing::trace!(
+                "[{WORKER_NAME}] list active plans for the account {} in {pool_contract_id}",
+                Sensitive(&user_address)
+            );
+            let plans = with_storage!(s =>
+                s.list_active_plans(&pool_contract_id, &user_address)?
+            )?;
+            StorageWorkerResponse::Plans(plans)
+        }
         StorageWorkerRequest::UnspentUserNotes {
             user_address,
             pool_contract_id,
@@ -582,6 +689,51 @@ impl StorageBridge {
             other => Err(anyhow!("unexpected response: {other:?}")),
         }
     }
+
+    /// Unlock the wallet's secret keys; the first unlock sets the passphrase.
+    /// Allows for the Argon2 derivation.
+    pub(crate) async fn unlock(&self, passphrase: Passphrase) -> anyhow::Result<()> {
+        match self
+            .call(StorageWorkerRequest::Unlock(passphrase), 30_000)
+            .await?
+        {
+            StorageWorkerResponse::Saved => Ok(()),
+            other => Err(anyhow!("unexpected response: {other:?}")),
+        }
+    }
+
+    pub(crate) async fn lock(&self) -> anyhow::Result<()> {
+        match self.call(StorageWorkerRequest::Lock, 5_000).await? {
+            StorageWorkerResponse::Saved => Ok(()),
+            other => Err(anyhow!("unexpected response: {other:?}")),
+        }
+    }
+
+    pub(crate) async fn is_locked(&self) -> anyhow::Result<bool> {
+        match self.call(StorageWorkerRequest::IsLocked, 5_000).await? {
+            StorageWorkerResponse::Locked(locked) => Ok(locked),
+            other => Err(anyhow!("unexpected response: {other:?}")),
+        }
+    }
+
+    /// Re-seal every key under `new`; derives both the current and the new
+    /// vault key.
+    pub(crate) async fn change_passphrase(
+        &self,
+        current: Passphrase,
+        new: Passphrase,
+    ) -> anyhow::Result<()> {
+        match self
+            .call(
+                StorageWorkerRequest::ChangePassphrase { current, new },
+                60_000,
+            )
+            .await?
+        {
+            StorageWorkerResponse::Saved => Ok(()),
+            other => Err(anyhow!("unexpected response: {other:?}")),
+        }
+    }
 }
 
 #[async_trait::async_trait(?Send)]
@@ -632,6 +784,141 @@ impl Storage for StorageBridge {
         })
     }
 
+    async fn export_backup(
+        &self,
+        user_address: &str,
+        passphrase: &Passphrase,
+    ) -> Result<String, Error> {
+        match self
+            .call(
+                StorageWorkerRequest::ExportBackup {
+                    address: user_address.to_string(),
+                    passphrase: passphrase.clone(),
+                },
+                BACKUP_TIMEOUT_MS,
+            )
+            .await
+        {
+            Ok(StorageWorkerResponse::Backup(backup)) => Ok(backup),
+            Ok(other) => Err(Error::Other(format!(
+                "unexpected storage response exporting backup: {other:?}"
+            ))),
+            Err(e) => Err(Error::Other(e.to_string())),
+        }
+    }
+
+    async fn import_backup(
+        &self,
+        user_address: &str,
+        backup: &str,
+        passphrase: &Passphrase,
+    ) -> Result<(), Error> {
+        match self
+            .call(
+                StorageWorkerRequest::ImportBackup {
+                    address: user_address.to_string(),
+                    backup: backup.to_string(),
+                    passphrase: passphrase.clone(),
+                },
+                BACKUP_TIMEOUT_MS,
+            )
+            .await
+        {
+            Ok(StorageWorkerResponse::Saved) => Ok(()),
+            Ok(other) => Err(Error::Other(format!(
+                "unexpected storage response importing backup: {other:?}"
+            ))),
+            Err(e) => Err(Error::Other(e.to_string())),
+        }
+    }
+
+    async fn ensure_sub_account(&self, user_address: &str, index: u32) -> Result<String, Error> {
+        match self
+            .call(
+                StorageWorkerRequest::EnsureSubAccount {
+                    address: user_address.to_string(),
+                    index,
+                },
+                5_000,
+            )
+            .await
+        {
+            Ok(StorageWorkerResponse::SubAccount(sub_address)) => Ok(sub_address),
+            Ok(other) => Err(Error::Other(format!(
+                "unexpected storage response deriving sub-account: {other:?}"
+            ))),
+            Err(e) => Err(Error::Other(e.to_string())),
+        }
+    }
+
+    async fn export_viewing_key(&self, user_address: &str) -> Result<ViewingKey, Error> {
+        match self
+            .call(
+                StorageWorkerRequest::ExportViewingKey(user_address.to_string()),
+                5_000,
+            )
+            .await
+        {
+            Ok(StorageWorkerResponse::ViewingKey(viewing_key)) => Ok(viewing_key),
+            Ok(other) => Err(Error::Other(format!(
+                "unexpected storage response exporting viewing key: {other:?}"
+            ))),
+            Err(e) => Err(Error::Other(e.to_string())),
+        }
+    }
+
+    async fn import_viewing_key(&self, viewing_key: &ViewingKey) -> Result<(), Error> {
+        match self
+            .call(
+                StorageWorkerRequest::ImportViewingKey(viewing_key.clone()),
+                5_000,
+            )
+            .await
+        {
+            Ok(StorageWorkerResponse::Saved) => Ok(()),
+            Ok(other) => Err(Error::Other(format!(
+                "unexpected storage response importing viewing key: {other:?}"
+            ))),
+            Err(e) => Err(Error::Other(e.to_string())),
+        }
+    }
+
+    async fn save_plan(&self, plan: &StoredPlan) -> Result<i64, Error> {
+        match self
+            .call(StorageWorkerRequest::SavePlan(plan.clone()), 5_000)
+            .await
+        {
+            Ok(StorageWorkerResponse::PlanId(id)) => Ok(id),
+            Ok(other) => Err(Error::Other(format!(
+                "unexpected storage response saving transaction plan: {other:?}"
+            ))),
+            Err(e) => Err(Error::Other(e.to_string())),
+        }
+    }
+
+    async fn active_plans(
+        &self,
+        pool_contract_id: &str,
+        user_address: &str,
+    ) -> Result<Vec<StoredPlan>, Error> {
+        match self
+            .call(
+                StorageWorkerRequest::ActivePlans {
+                    user_address: user_address.to_string(),
+                    pool_contract_id: pool_contract_id.to_string(),
+                },
+                5_000,
+            )
+            .await
+        {
+            Ok(StorageWorkerResponse::Plans(plans)) => Ok(plans),
+            Ok(other) => Err(Error::Other(format!(
+                "unexpected storage response listing transaction plans: {other:?}"
+            ))),
+            Err(e) => Err(Error::Other(e.to_string())),
+        }
+    }
+
     async fn process_pending_state(&self) -> Result<(), Error> {
         match self
             .call(StorageWorkerRequest::ProcessPendingState, 30_000)
@@ -694,6 +981,7 @@ impl Storage for StorageBridge {
                 .map(|n| SpendableNote {
                     commitment: n.id,
                     amount: n.amount,
+                    created_at_ledger: Some(n.created_at_ledger),
                 })
                 .collect()),
             Ok(other) => Err(Error::Other(format!(
@@ -769,6 +1057,26 @@ impl Storage for StorageBridge {
         }
     }
 
+    async fn list_sent_notes(
+        &self,
+        user_address: &str,
+        limit: u32,
+    ) -> Result<Vec<SentNoteSummary>, Error> {
+        match self
+            .call(
+                StorageWorkerRequest::SentNotes(user_address.to_string(), limit),
+                5_000,
+            )
+            .await
+        {
+            Ok(StorageWorkerResponse::SentNotes(notes)) => Ok(notes),
+            Ok(other) => Err(Error::Other(format!(
+                "unexpected storage response loading sent notes: {other:?}"
+            ))),
+            Err(e) => Err(Error::Other(e.to_string())),
+        }
+    }
+
     async fn operational_feed(
         &self,
         limit: u32,
@@ -921,8 +1229,8 @@ impl Storage for StorageBridge {
             Ok(StorageWorkerResponse::RecipientLookup(lookup)) => {
                 let entry = lookup.entry.ok_or_else(|| {
                     Error::Other(format!(
-                        "recipient {address} not found in the public key registry; \
-                         they must register keys on-chain"
+                        "recipient {address} has no active key in the public key registry; \
+                         they must register (or re-register revoked) keys on-chain"
                     ))
                 })?;
                 Ok((entry.note_key, entry.encryption_key))